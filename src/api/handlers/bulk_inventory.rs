@@ -0,0 +1,161 @@
+//! Bulk inventory import/export API handlers
+//!
+//! File-based imports with column mapping profiles and dry runs, and
+//! full-inventory exports in the same formats.
+
+use crate::api::AppState;
+use crate::services::{ColumnMapping, DataFormat, ImportMode, ImportOptions};
+use axum::{
+    body::Body,
+    extract::{Json, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    pub format: Option<String>,
+    pub profile: Option<String>,
+    pub mode: Option<String>,
+    pub location: Option<String>,
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<String>,
+    pub profile: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SaveProfileRequest {
+    pub name: String,
+    pub mapping: ColumnMapping,
+}
+
+fn bad_request(message: String) -> axum::response::Response {
+    (StatusCode::BAD_REQUEST, Json(json!({"error": message}))).into_response()
+}
+
+/// Import a CSV or JSON file from the request body. Rows are applied as the
+/// body streams in; a dry run (the default) reports the diff without saving.
+pub async fn import_inventory(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Query(params): Query<ImportQuery>,
+    body: Body,
+) -> impl IntoResponse {
+    let format = match params.format.as_deref().map(DataFormat::parse) {
+        None => DataFormat::Csv,
+        Some(Some(format)) => format,
+        Some(None) => return bad_request("format must be csv or json".to_string()),
+    };
+    let mode = match params.mode.as_deref().map(ImportMode::parse) {
+        None => ImportMode::Add,
+        Some(Some(mode)) => mode,
+        Some(None) => return bad_request("mode must be add or replace".to_string()),
+    };
+    let options = ImportOptions {
+        format,
+        profile: params.profile.unwrap_or_else(|| "vaultsync".to_string()),
+        mode,
+        default_location: params.location.unwrap_or_else(|| "Main".to_string()),
+        dry_run: params.dry_run.unwrap_or(true),
+    };
+    let imported_by = Uuid::parse_str(&user.user_uuid).ok();
+
+    let mut session = match state
+        .commerce
+        .bulk_inventory
+        .begin_import(options, imported_by)
+        .await
+    {
+        Ok(session) => session,
+        Err(e) => return bad_request(e.to_string()),
+    };
+
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => return bad_request(format!("Failed to read upload: {}", e)),
+        };
+        if let Err(e) = session.push(&chunk).await {
+            return bad_request(e.to_string());
+        }
+    }
+
+    match session.finish().await {
+        Ok(report) => {
+            let status = if report.committed {
+                StatusCode::CREATED
+            } else {
+                StatusCode::OK
+            };
+            (status, Json(report)).into_response()
+        }
+        Err(e) => bad_request(e.to_string()),
+    }
+}
+
+/// Export all inventory on hand
+pub async fn export_inventory(
+    State(state): State<AppState>,
+    Query(params): Query<ExportQuery>,
+) -> impl IntoResponse {
+    let format = match params.format.as_deref().map(DataFormat::parse) {
+        None => DataFormat::Csv,
+        Some(Some(format)) => format,
+        Some(None) => return bad_request("format must be csv or json".to_string()),
+    };
+    let profile = params.profile.unwrap_or_else(|| "vaultsync".to_string());
+
+    match state.commerce.bulk_inventory.export(format, &profile).await {
+        Ok(body) => {
+            let content_type = match format {
+                DataFormat::Csv => "text/csv",
+                DataFormat::Json => "application/json",
+            };
+            (
+                StatusCode::OK,
+                [(axum::http::header::CONTENT_TYPE, content_type)],
+                body,
+            )
+                .into_response()
+        }
+        Err(e) => bad_request(e.to_string()),
+    }
+}
+
+/// Built-in and saved column mapping profiles
+pub async fn get_import_profiles(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.bulk_inventory.get_profiles().await {
+        Ok(profiles) => (StatusCode::OK, Json(profiles)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Save a custom column mapping, e.g. for another POS's export
+pub async fn save_import_profile(
+    State(state): State<AppState>,
+    Json(req): Json<SaveProfileRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .bulk_inventory
+        .save_profile(&req.name, req.mapping)
+        .await
+    {
+        Ok(profile) => (StatusCode::CREATED, Json(profile)).into_response(),
+        Err(e) => bad_request(e.to_string()),
+    }
+}
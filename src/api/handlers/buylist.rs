@@ -6,7 +6,7 @@ use crate::api::AppState;
 use crate::buylist::{BuylistItem, PaymentMethod};
 use crate::core::TransactionItem;
 use axum::{
-    extract::{Json, State},
+    extract::{Json, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
@@ -28,18 +28,44 @@ pub struct TradeInProcessRequest {
     pub customer_uuid: Option<Uuid>,
 }
 
+#[derive(Deserialize)]
+pub struct QuoteDisplayQuery {
+    /// Terminal whose customer display should show the offer
+    pub terminal_id: Option<String>,
+}
+
 /// Get an instant quote for a buylist item
 pub async fn get_buylist_quote(
     State(state): State<AppState>,
+    Query(params): Query<QuoteDisplayQuery>,
     Json(item): Json<BuylistItem>,
 ) -> impl IntoResponse {
     match state
         .commerce
         .buylist
-        .calculate_instant_quote(item.product_uuid, item.condition)
+        .calculate_instant_quote(item.product_uuid, item.condition.clone())
         .await
     {
-        Ok(quote) => (StatusCode::OK, Json(quote)).into_response(),
+        Ok(quote) => {
+            if let Some(terminal_id) = params.terminal_id {
+                if let Err(e) = state
+                    .system
+                    .customer_display
+                    .add_trade_in_offer(
+                        &terminal_id,
+                        item.product_uuid,
+                        item.condition,
+                        item.quantity,
+                        quote.cash_price,
+                        quote.credit_price,
+                    )
+                    .await
+                {
+                    tracing::warn!("Failed to show trade-in offer on {}: {}", terminal_id, e);
+                }
+            }
+            (StatusCode::OK, Json(quote)).into_response()
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({"error": e.to_string()})),
@@ -0,0 +1,227 @@
+//! Consignment API handlers
+//!
+//! Consignor accounts, intake of consigned stock, returns and expiry,
+//! payouts and per-consignor statements.
+
+use crate::api::AppState;
+use crate::services::{
+    ConsignmentIntakeRequest, ConsignmentStatus, CreateConsignorRequest, PayoutMethod,
+};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+/// List consignors with their current balances
+pub async fn get_consignors(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.consignment.get_consignors().await {
+        Ok(consignors) => (StatusCode::OK, Json(consignors)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Create a consignor account
+pub async fn create_consignor(
+    State(state): State<AppState>,
+    Json(req): Json<CreateConsignorRequest>,
+) -> impl IntoResponse {
+    match state.commerce.consignment.create_consignor(req).await {
+        Ok(consignor) => (StatusCode::CREATED, Json(consignor)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Get a single consignor
+pub async fn get_consignor(
+    State(state): State<AppState>,
+    Path(consignor_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .consignment
+        .get_consignor(consignor_uuid)
+        .await
+    {
+        Ok(Some(consignor)) => (StatusCode::OK, Json(consignor)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Consignor not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ConsignmentItemsQuery {
+    pub status: Option<String>,
+}
+
+/// List a consignor's items, optionally filtered by status
+pub async fn get_consignor_items(
+    State(state): State<AppState>,
+    Path(consignor_uuid): Path<Uuid>,
+    Query(params): Query<ConsignmentItemsQuery>,
+) -> impl IntoResponse {
+    let status = match params.status.as_deref() {
+        Some(s) => match ConsignmentStatus::parse(s) {
+            Some(status) => Some(status),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown status '{}'", s)})),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
+    };
+
+    match state
+        .commerce
+        .consignment
+        .get_items(consignor_uuid, status)
+        .await
+    {
+        Ok(items) => (StatusCode::OK, Json(items)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct StatementQuery {
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+/// Consignor statement (defaults to the last 30 days)
+pub async fn get_consignor_statement(
+    State(state): State<AppState>,
+    Path(consignor_uuid): Path<Uuid>,
+    Query(params): Query<StatementQuery>,
+) -> impl IntoResponse {
+    let start = params
+        .start_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .unwrap_or_else(|| chrono::Utc::now() - chrono::Duration::days(30));
+
+    let end = params
+        .end_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .unwrap_or_else(chrono::Utc::now);
+
+    match state
+        .commerce
+        .consignment
+        .get_statement(consignor_uuid, start, end)
+        .await
+    {
+        Ok(statement) => (StatusCode::OK, Json(statement)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// Receive consigned stock into inventory
+pub async fn intake_consignment(
+    State(state): State<AppState>,
+    Json(req): Json<ConsignmentIntakeRequest>,
+) -> impl IntoResponse {
+    match state.commerce.consignment.intake_item(req).await {
+        Ok(item) => (StatusCode::CREATED, Json(item)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Return an unsold item to its consignor
+pub async fn return_consignment(
+    State(state): State<AppState>,
+    Path(consignment_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .consignment
+        .return_to_consignor(consignment_uuid)
+        .await
+    {
+        Ok(item) => (StatusCode::OK, Json(item)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Pull items past their consignment period off the floor
+pub async fn expire_consignments(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.consignment.expire_overdue_items().await {
+        Ok(expired) => (
+            StatusCode::OK,
+            Json(json!({"expired": expired.len(), "consignment_uuids": expired})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ConsignorPayoutRequest {
+    /// Defaults to the full balance owed
+    pub amount: Option<f64>,
+    pub method: PayoutMethod,
+    pub notes: Option<String>,
+}
+
+/// Pay out a consignor's balance in cash or store credit
+pub async fn pay_consignor(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(consignor_uuid): Path<Uuid>,
+    Json(req): Json<ConsignorPayoutRequest>,
+) -> impl IntoResponse {
+    let paid_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .commerce
+        .consignment
+        .pay_consignor(consignor_uuid, req.amount, req.method, paid_by, req.notes)
+        .await
+    {
+        Ok(payout) => (StatusCode::CREATED, Json(payout)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,141 @@
+//! Currency API handlers
+//!
+//! Base currency, manually entered exchange rates and conversion lookups.
+
+use crate::api::AppState;
+use crate::core::{Currency, Money};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+/// Get the base currency and all exchange rates
+pub async fn get_currency_settings(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.currency.get_settings().await {
+        Ok(settings) => Json(settings).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SetBaseCurrencyRequest {
+    pub currency: Currency,
+}
+
+/// Change the store's base currency (manager only)
+pub async fn set_base_currency(
+    State(state): State<AppState>,
+    Json(req): Json<SetBaseCurrencyRequest>,
+) -> impl IntoResponse {
+    let currency = &state.commerce.currency;
+    match currency.set_base_currency(req.currency).await {
+        Ok(_) => match currency.get_settings().await {
+            Ok(settings) => Json(settings).into_response(),
+            Err(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response(),
+        },
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SetExchangeRateRequest {
+    pub currency: Currency,
+    /// Units of base currency per one unit of `currency`
+    pub rate_to_base: f64,
+}
+
+/// Enter or update an exchange rate (manager only)
+pub async fn set_exchange_rate(
+    State(state): State<AppState>,
+    Json(req): Json<SetExchangeRateRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .currency
+        .set_exchange_rate(req.currency, req.rate_to_base)
+        .await
+    {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Remove an exchange rate (manager only)
+pub async fn delete_exchange_rate(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+) -> impl IntoResponse {
+    let currency = match Currency::from_code(&code) {
+        Some(c) => c,
+        None => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": format!("Invalid currency code: {}", code)})),
+            )
+                .into_response()
+        }
+    };
+
+    match state.commerce.currency.remove_exchange_rate(currency).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ConvertQuery {
+    pub amount: f64,
+    pub from: Currency,
+    /// Target currency (defaults to the base currency)
+    pub to: Option<Currency>,
+}
+
+/// Convert an amount between currencies using the stored rates
+pub async fn convert_currency(
+    State(state): State<AppState>,
+    Query(params): Query<ConvertQuery>,
+) -> impl IntoResponse {
+    let currency = &state.commerce.currency;
+    let amount = Money::from_f64_lossy_in(params.amount, params.from);
+    let result = match params.to {
+        Some(to) => currency.convert(amount, to).await,
+        None => currency.to_base(amount).await,
+    };
+
+    match result {
+        Ok(converted) => Json(json!({
+            "from": {"amount": amount.inner(), "currency": amount.currency()},
+            "to": {"amount": converted.inner(), "currency": converted.currency()},
+            "display": converted.to_string(),
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
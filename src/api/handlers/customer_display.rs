@@ -0,0 +1,174 @@
+//! Customer-facing display API handlers
+//!
+//! Streams per-terminal display state over Server-Sent Events and manages
+//! the promotional content shown while a terminal is idle.
+
+use crate::api::AppState;
+use crate::services::{DisplayEvent, DisplayPromotionRequest};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+fn to_sse_event(event: &DisplayEvent) -> Result<Event, axum::Error> {
+    Event::default().event(event.name()).json_data(event)
+}
+
+/// Stream a terminal's display state (current state first, then every change)
+pub async fn stream_customer_display(
+    State(state): State<AppState>,
+    Path(terminal_id): Path<String>,
+) -> impl IntoResponse {
+    let (current, receiver) = match state.system.customer_display.subscribe(&terminal_id).await {
+        Ok(subscription) => subscription,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
+    };
+
+    let updates = futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                // A slow display only needs the latest state, skip what it missed
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let stream = futures::stream::once(async move { current })
+        .chain(updates)
+        .map(|event| to_sse_event(&event));
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Get what a terminal's display is currently showing
+pub async fn get_customer_display_state(
+    State(state): State<AppState>,
+    Path(terminal_id): Path<String>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .customer_display
+        .current_state(&terminal_id)
+        .await
+    {
+        Ok(event) => (StatusCode::OK, Json(event)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PaymentPromptRequest {
+    pub amount_due: f64,
+    #[serde(default)]
+    pub amount_paid: f64,
+    pub method: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Prompt the customer for payment on the terminal's display
+pub async fn show_payment_prompt(
+    State(state): State<AppState>,
+    Path(terminal_id): Path<String>,
+    Json(req): Json<PaymentPromptRequest>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .customer_display
+        .show_payment_prompt(
+            &terminal_id,
+            req.amount_due,
+            req.amount_paid,
+            req.method,
+            req.message,
+        )
+        .await
+    {
+        Ok(view) => (StatusCode::OK, Json(view)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Return the terminal's display to idle promotions (e.g. sale abandoned)
+pub async fn reset_customer_display(
+    State(state): State<AppState>,
+    Path(terminal_id): Path<String>,
+) -> impl IntoResponse {
+    match state.system.customer_display.show_idle(&terminal_id).await {
+        Ok(()) => (StatusCode::OK, Json(json!({"status": "idle"}))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// List all display promotions
+pub async fn get_display_promotions(State(state): State<AppState>) -> impl IntoResponse {
+    match state.system.customer_display.get_promotions().await {
+        Ok(promotions) => (StatusCode::OK, Json(promotions)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Add a promotion to the idle rotation
+pub async fn create_display_promotion(
+    State(state): State<AppState>,
+    Json(req): Json<DisplayPromotionRequest>,
+) -> impl IntoResponse {
+    match state.system.customer_display.create_promotion(req).await {
+        Ok(promotion) => (StatusCode::CREATED, Json(promotion)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Retire a promotion from the idle rotation
+pub async fn deactivate_display_promotion(
+    State(state): State<AppState>,
+    Path(promotion_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .customer_display
+        .deactivate_promotion(promotion_uuid)
+        .await
+    {
+        Ok(()) => (StatusCode::OK, Json(json!({"status": "deactivated"}))).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
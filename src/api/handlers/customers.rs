@@ -2,10 +2,13 @@
 
 use crate::api::AppState;
 use crate::core::Customer;
+use crate::services::customer::DEFAULT_DUPLICATE_THRESHOLD;
+use crate::services::CustomerUpdate;
 use axum::{
     extract::{Json, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
+    Extension,
 };
 use serde::Deserialize;
 use serde_json::json;
@@ -124,3 +127,142 @@ pub async fn update_store_credit(
             .into_response(),
     }
 }
+
+/// Edit a customer's name, contact details, notes or tier
+pub async fn update_customer(
+    State(state): State<AppState>,
+    Path(customer_uuid): Path<Uuid>,
+    Json(req): Json<CustomerUpdate>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .customers
+        .update_customer(customer_uuid, req)
+        .await
+    {
+        Ok(customer) => (StatusCode::OK, Json(customer)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DuplicateQuery {
+    pub threshold: Option<f64>,
+}
+
+/// Suggested duplicate profiles across the whole customer list
+pub async fn find_duplicate_customers(
+    State(state): State<AppState>,
+    Query(query): Query<DuplicateQuery>,
+) -> impl IntoResponse {
+    let threshold = query.threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD);
+    match state.commerce.customers.find_duplicates(threshold).await {
+        Ok(candidates) => (StatusCode::OK, Json(candidates)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Profiles that look like the same person as this customer
+pub async fn get_customer_duplicates(
+    State(state): State<AppState>,
+    Path(customer_uuid): Path<Uuid>,
+    Query(query): Query<DuplicateQuery>,
+) -> impl IntoResponse {
+    let threshold = query.threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD);
+    match state
+        .commerce
+        .customers
+        .find_duplicates_of(customer_uuid, threshold)
+        .await
+    {
+        Ok(candidates) => (StatusCode::OK, Json(candidates)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MergeCustomerRequest {
+    pub duplicate_uuid: Uuid,
+}
+
+/// Fold a duplicate profile into this one (manager only)
+pub async fn merge_customers(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(customer_uuid): Path<Uuid>,
+    Json(req): Json<MergeCustomerRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .customers
+        .merge_customers(
+            customer_uuid,
+            req.duplicate_uuid,
+            Uuid::parse_str(&user.user_uuid).ok(),
+        )
+        .await
+    {
+        Ok(summary) => (StatusCode::OK, Json(summary)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Everything held about a customer, for a data access request (manager only)
+pub async fn export_customer_data(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(customer_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .customers
+        .export_customer_data(customer_uuid, Uuid::parse_str(&user.user_uuid).ok())
+        .await
+    {
+        Ok(export) => (StatusCode::OK, Json(export)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AnonymizeCustomerRequest {
+    pub reason: Option<String>,
+}
+
+/// Erase personal details while keeping financial records (manager only)
+pub async fn anonymize_customer(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(customer_uuid): Path<Uuid>,
+    Json(req): Json<AnonymizeCustomerRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .customers
+        .anonymize_customer(
+            customer_uuid,
+            req.reason,
+            Uuid::parse_str(&user.user_uuid).ok(),
+        )
+        .await
+    {
+        Ok(summary) => (StatusCode::OK, Json(summary)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
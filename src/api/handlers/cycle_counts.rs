@@ -0,0 +1,295 @@
+//! Cycle count API handlers
+//!
+//! Count plans and ABC classes, count sessions, per-counter entries,
+//! recount rounds and approval.
+
+use crate::api::AppState;
+use crate::services::{
+    CountEntryRequest, CountSessionStatus, CreateCountPlanRequest, CreateCountSessionRequest,
+};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct SessionQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ClassifyRequest {
+    pub lookback_days: Option<i64>,
+    pub a_share: Option<f64>,
+    pub b_share: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct RecordCountsRequest {
+    pub entries: Vec<CountEntryRequest>,
+}
+
+/// List active count plans
+pub async fn get_count_plans(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.cycle_counts.get_plans().await {
+        Ok(plans) => (StatusCode::OK, Json(plans)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Create a scheduled count plan for a location
+pub async fn create_count_plan(
+    State(state): State<AppState>,
+    Json(req): Json<CreateCountPlanRequest>,
+) -> impl IntoResponse {
+    match state.commerce.cycle_counts.create_plan(req).await {
+        Ok(plan) => (StatusCode::CREATED, Json(plan)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Run a plan now, opening a session for whatever is due
+pub async fn run_count_plan(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(plan_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    let created_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .commerce
+        .cycle_counts
+        .run_plan(plan_uuid, created_by)
+        .await
+    {
+        Ok(Some(session)) => (StatusCode::CREATED, Json(json!(session))).into_response(),
+        Ok(None) => (
+            StatusCode::OK,
+            Json(json!({"message": "Nothing is due for a count"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Current ABC classes, highest score first
+pub async fn get_abc_classes(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.cycle_counts.get_classifications().await {
+        Ok(classes) => (StatusCode::OK, Json(classes)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Re-rank products into ABC classes by value and sales velocity
+pub async fn classify_products(
+    State(state): State<AppState>,
+    Json(req): Json<ClassifyRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .cycle_counts
+        .classify_products(
+            req.lookback_days.unwrap_or(90),
+            req.a_share.unwrap_or(0.8),
+            req.b_share.unwrap_or(0.95),
+        )
+        .await
+    {
+        Ok(classes) => (StatusCode::OK, Json(classes)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// List count sessions, optionally by status (e.g. `Review`)
+pub async fn get_count_sessions(
+    State(state): State<AppState>,
+    Query(params): Query<SessionQuery>,
+) -> impl IntoResponse {
+    let status = match params.status.as_deref() {
+        Some(s) => match CountSessionStatus::parse(s) {
+            Some(status) => Some(status),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown session status '{}'", s)})),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
+    };
+
+    match state.commerce.cycle_counts.get_sessions(status).await {
+        Ok(sessions) => (StatusCode::OK, Json(sessions)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Open an ad-hoc count session for a location or bin
+pub async fn create_count_session(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Json(req): Json<CreateCountSessionRequest>,
+) -> impl IntoResponse {
+    let created_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .commerce
+        .cycle_counts
+        .create_session(req, created_by)
+        .await
+    {
+        Ok(session) => (StatusCode::CREATED, Json(session)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Full session with expected quantities and variances
+pub async fn get_count_session(
+    State(state): State<AppState>,
+    Path(session_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.cycle_counts.get_session(session_uuid).await {
+        Ok(Some(session)) => (StatusCode::OK, Json(session)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Count session not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Lines to count this round, as a counter should see them
+pub async fn get_count_sheet(
+    State(state): State<AppState>,
+    Path(session_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .cycle_counts
+        .get_count_sheet(session_uuid)
+        .await
+    {
+        Ok(sheet) => (StatusCode::OK, Json(sheet)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// Submit the signed-in counter's quantities for the current round
+pub async fn record_counts(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(session_uuid): Path<Uuid>,
+    Json(req): Json<RecordCountsRequest>,
+) -> impl IntoResponse {
+    let counted_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .commerce
+        .cycle_counts
+        .record_counts(session_uuid, counted_by, req.entries)
+        .await
+    {
+        Ok(_) => (StatusCode::OK, Json(json!({"status": "recorded"}))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Close the round: flag recounts or move the session to review
+pub async fn finish_count_round(
+    State(state): State<AppState>,
+    Path(session_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.cycle_counts.finish_round(session_uuid).await {
+        Ok(session) => (
+            StatusCode::OK,
+            Json(json!({
+                "status": session.status,
+                "round": session.round,
+                "recount_lines": session.lines.iter().filter(|l| l.needs_recount).count(),
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Approve a reviewed session and post its variances to inventory
+pub async fn approve_count_session(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(session_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    let approved_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .commerce
+        .cycle_counts
+        .approve(session_uuid, approved_by)
+        .await
+    {
+        Ok(session) => (StatusCode::OK, Json(session)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Abandon a session without touching inventory
+pub async fn cancel_count_session(
+    State(state): State<AppState>,
+    Path(session_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.cycle_counts.cancel(session_uuid).await {
+        Ok(session) => (StatusCode::OK, Json(session)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
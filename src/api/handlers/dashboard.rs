@@ -18,6 +18,9 @@ pub struct DashboardStats {
     pub total_inventory_items: i64,
     pub total_inventory_value: f64,
     pub low_stock_count: i64,
+    pub reorder_count: i64,
+    pub dead_stock_count: i64,
+    pub dead_stock_value: f64,
     pub today_sales: f64,
     pub today_transactions: i64,
     pub pending_sync_changes: i64,
@@ -46,6 +49,14 @@ pub async fn get_dashboard_stats(State(state): State<AppState>) -> impl IntoResp
             // Low stock count using optimized query
             let low_stock_count = state.db.inventory.get_low_stock_count(5).await.unwrap_or(0);
 
+            // Replenishment: what needs ordering and what isn't moving
+            let replenishment = state
+                .commerce
+                .replenishment
+                .get_summary(crate::services::ForecastOptions::default())
+                .await
+                .ok();
+
             // Sync pending changes
             let sync_pending = match state.db.sync.get_changes_since(0, 10000).await {
                 Ok(c) => c.len() as i64,
@@ -80,6 +91,10 @@ pub async fn get_dashboard_stats(State(state): State<AppState>) -> impl IntoResp
                     "total_inventory_items": inventory_count,
                     "total_inventory_value": total_inventory_value,
                     "low_stock_count": low_stock_count,
+                    "reorder_count": replenishment.as_ref().map_or(0, |r| r.reorder_count),
+                    "stockout_count": replenishment.as_ref().map_or(0, |r| r.stockout_count),
+                    "dead_stock_count": replenishment.as_ref().map_or(0, |r| r.dead_stock_count),
+                    "dead_stock_value": replenishment.as_ref().map_or(0.0, |r| r.dead_stock_value),
                     "today_sales": metrics.total_sales_today,
                     "today_transactions": metrics.transaction_count_today,
                     "pending_sync_changes": sync_pending,
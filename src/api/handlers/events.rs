@@ -1,10 +1,18 @@
 //! Event-related API handlers
 //!
-//! Handles tournament/event creation, listing, and participant registration.
+//! Handles tournament/event creation, listing, and participant registration,
+//! plus running the tournament itself: rounds, results, drops and standings,
+//! and the money side: entry payments, prize payouts and event P&L. Also
+//! waitlists, check-in, cancellations and recurring event templates.
 
 use crate::api::AppState;
+use crate::events::{
+    EntryPaymentMethod, EventTemplateRequest, MatchResult, PrizeTierRequest, TournamentConfig,
+    DEFAULT_SPAWN_HORIZON_DAYS,
+};
+use crate::services::PrintJobType;
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Extension, Json, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
@@ -92,3 +100,613 @@ pub async fn register_participant(
             .into_response(),
     }
 }
+
+/// Fix the tournament format and pair round one
+pub async fn start_tournament(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+    Json(config): Json<TournamentConfig>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .tournaments
+        .start_tournament(event_uuid, config)
+        .await
+    {
+        Ok(round) => (StatusCode::CREATED, Json(round)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Get every round with its pairings and results
+pub async fn get_rounds(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.tournaments.get_rounds(event_uuid).await {
+        Ok(rounds) => (StatusCode::OK, Json(rounds)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Pair the next Swiss or top cut round
+pub async fn pair_next_round(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.tournaments.pair_next_round(event_uuid).await {
+        Ok(round) => (StatusCode::CREATED, Json(round)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Get one round, including the time left on its clock
+pub async fn get_round(
+    State(state): State<AppState>,
+    Path((event_uuid, round_number)): Path<(Uuid, i32)>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .tournaments
+        .get_round(event_uuid, round_number)
+        .await
+    {
+        Ok(Some(round)) => (StatusCode::OK, Json(round)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Round not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct StartRoundRequest {
+    pub minutes: Option<i32>,
+}
+
+/// Start the round clock
+pub async fn start_round(
+    State(state): State<AppState>,
+    Path((event_uuid, round_number)): Path<(Uuid, i32)>,
+    req: Option<Json<StartRoundRequest>>,
+) -> impl IntoResponse {
+    let minutes = req.and_then(|Json(r)| r.minutes);
+    match state
+        .system
+        .tournaments
+        .start_round(event_uuid, round_number, minutes)
+        .await
+    {
+        Ok(round) => (StatusCode::OK, Json(round)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Enter or correct a match result
+pub async fn report_match_result(
+    State(state): State<AppState>,
+    Path(match_uuid): Path<Uuid>,
+    Json(result): Json<MatchResult>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .tournaments
+        .report_result(match_uuid, result)
+        .await
+    {
+        Ok(m) => (StatusCode::OK, Json(m)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Drop a player from future rounds
+pub async fn drop_participant(
+    State(state): State<AppState>,
+    Path((event_uuid, participant_uuid)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .tournaments
+        .drop_player(event_uuid, participant_uuid)
+        .await
+    {
+        Ok(_) => (StatusCode::OK, Json(json!({"status": "dropped"}))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Get Swiss standings with tiebreakers
+pub async fn get_standings(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.tournaments.get_standings(event_uuid).await {
+        Ok(standings) => (StatusCode::OK, Json(standings)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PrintRequest {
+    pub printer_id: String,
+}
+
+async fn event_name(state: &AppState, event_uuid: Uuid) -> Result<String, String> {
+    match state.db.events.get_by_id(event_uuid).await {
+        Ok(Some(event)) => Ok(event.name),
+        Ok(None) => Err(format!("Event {} not found", event_uuid)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Send a round's pairings to a receipt printer
+pub async fn print_pairings(
+    State(state): State<AppState>,
+    Path((event_uuid, round_number)): Path<(Uuid, i32)>,
+    Json(req): Json<PrintRequest>,
+) -> impl IntoResponse {
+    let name = match event_name(&state, event_uuid).await {
+        Ok(name) => name,
+        Err(e) => return (StatusCode::NOT_FOUND, Json(json!({"error": e}))).into_response(),
+    };
+    let round = match state
+        .system
+        .tournaments
+        .get_round(event_uuid, round_number)
+        .await
+    {
+        Ok(Some(round)) => round,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Round not found"})),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
+    };
+
+    let content = state
+        .system
+        .printers
+        .generate_pairings_escpos(&name, &round);
+    match state
+        .system
+        .printers
+        .queue_print_job(req.printer_id, PrintJobType::Report, content)
+        .await
+    {
+        Ok(job_uuid) => (StatusCode::ACCEPTED, Json(json!({"job_uuid": job_uuid}))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Send the current standings to a receipt printer
+pub async fn print_standings(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+    Json(req): Json<PrintRequest>,
+) -> impl IntoResponse {
+    let name = match event_name(&state, event_uuid).await {
+        Ok(name) => name,
+        Err(e) => return (StatusCode::NOT_FOUND, Json(json!({"error": e}))).into_response(),
+    };
+    let standings = match state.system.tournaments.get_standings(event_uuid).await {
+        Ok(standings) => standings,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
+    };
+
+    let content = state
+        .system
+        .printers
+        .generate_standings_escpos(&name, &standings);
+    match state
+        .system
+        .printers
+        .queue_print_job(req.printer_id, PrintJobType::Report, content)
+        .await
+    {
+        Ok(job_uuid) => (StatusCode::ACCEPTED, Json(json!({"job_uuid": job_uuid}))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct CashPaymentRequest {
+    pub amount: Option<f64>,
+}
+
+/// Record an entry fee paid in cash
+pub async fn record_cash_payment(
+    State(state): State<AppState>,
+    Path(participant_uuid): Path<Uuid>,
+    req: Option<Json<CashPaymentRequest>>,
+) -> impl IntoResponse {
+    let amount = req.and_then(|Json(r)| r.amount);
+    match state
+        .system
+        .events
+        .record_cash_payment(participant_uuid, amount)
+        .await
+    {
+        Ok(participant) => (StatusCode::OK, Json(participant)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Get an event's prize structure
+pub async fn get_prize_structure(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.prizes.get_prize_structure(event_uuid).await {
+        Ok(tiers) => (StatusCode::OK, Json(tiers)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Replace an event's prize structure
+pub async fn set_prize_structure(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+    Json(tiers): Json<Vec<PrizeTierRequest>>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .prizes
+        .set_prize_structure(event_uuid, tiers)
+        .await
+    {
+        Ok(tiers) => (StatusCode::OK, Json(tiers)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Pay out every prize earned and not yet paid
+pub async fn pay_out_prizes(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(event_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_uuid = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .system
+        .prizes
+        .pay_out_prizes(event_uuid, user_uuid)
+        .await
+    {
+        Ok(summary) => (StatusCode::OK, Json(summary)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// List prizes already paid for an event
+pub async fn get_prize_payouts(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.prizes.get_payouts(event_uuid).await {
+        Ok(payouts) => (StatusCode::OK, Json(payouts)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Entry fees against prize cost for one event
+pub async fn get_event_pnl(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.prizes.get_pnl(event_uuid).await {
+        Ok(pnl) => (StatusCode::OK, Json(pnl)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AttendanceQuery {
+    pub event_type: Option<String>,
+}
+
+/// Attendance and margin per event over time
+pub async fn get_event_attendance(
+    State(state): State<AppState>,
+    Query(query): Query<AttendanceQuery>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .prizes
+        .get_attendance(query.event_type.as_deref())
+        .await
+    {
+        Ok(history) => (StatusCode::OK, Json(history)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// List everyone signed up, including the waitlist
+pub async fn get_event_participants(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.events.get_participants(event_uuid).await {
+        Ok(participants) => (StatusCode::OK, Json(participants)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PreRegisterRequest {
+    pub customer_uuid: Uuid,
+    pub deposit: Option<f64>,
+}
+
+/// Pre-register a customer, paying a deposit from store credit
+pub async fn pre_register(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+    Json(req): Json<PreRegisterRequest>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .events
+        .pre_register(event_uuid, req.customer_uuid, req.deposit)
+        .await
+    {
+        Ok(participant) => (StatusCode::CREATED, Json(participant)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct CheckInRequest {
+    pub payment_method: Option<EntryPaymentMethod>,
+}
+
+/// Check a player in, collecting any balance owed
+pub async fn check_in_participant(
+    State(state): State<AppState>,
+    Path(participant_uuid): Path<Uuid>,
+    req: Option<Json<CheckInRequest>>,
+) -> impl IntoResponse {
+    let payment = req.and_then(|Json(r)| r.payment_method);
+    match state
+        .system
+        .events
+        .check_in(participant_uuid, payment)
+        .await
+    {
+        Ok(participant) => (StatusCode::OK, Json(participant)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Mark registered players who never checked in as no-shows
+pub async fn mark_no_shows(
+    State(state): State<AppState>,
+    Path(event_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.events.mark_no_shows(event_uuid).await {
+        Ok(no_shows) => (StatusCode::OK, Json(no_shows)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct CancelRegistrationRequest {
+    pub refund_method: Option<EntryPaymentMethod>,
+    pub amount: Option<f64>,
+}
+
+/// Cancel a registration with an optional refund; promotes the waitlist
+pub async fn cancel_registration(
+    State(state): State<AppState>,
+    Path(participant_uuid): Path<Uuid>,
+    req: Option<Json<CancelRegistrationRequest>>,
+) -> impl IntoResponse {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    match state
+        .system
+        .events
+        .cancel_registration(participant_uuid, req.refund_method, req.amount)
+        .await
+    {
+        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// List recurring event templates
+pub async fn get_event_templates(State(state): State<AppState>) -> impl IntoResponse {
+    match state.system.events.get_templates().await {
+        Ok(templates) => (StatusCode::OK, Json(templates)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Create a recurring event template and spawn its upcoming instances
+pub async fn create_event_template(
+    State(state): State<AppState>,
+    Json(req): Json<EventTemplateRequest>,
+) -> impl IntoResponse {
+    let template = match state.system.events.create_template(req).await {
+        Ok(template) => template,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
+    };
+    match state
+        .system
+        .events
+        .spawn_instances(template.template_uuid, DEFAULT_SPAWN_HORIZON_DAYS)
+        .await
+    {
+        Ok(events) => (
+            StatusCode::CREATED,
+            Json(json!({"template": template, "events": events})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTemplateRequest {
+    pub active: bool,
+}
+
+/// Pause or resume a recurring event
+pub async fn update_event_template(
+    State(state): State<AppState>,
+    Path(template_uuid): Path<Uuid>,
+    Json(req): Json<UpdateTemplateRequest>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .events
+        .set_template_active(template_uuid, req.active)
+        .await
+    {
+        Ok(_) => (StatusCode::OK, Json(json!({"status": "updated"}))).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct SpawnInstancesRequest {
+    pub horizon_days: Option<i64>,
+}
+
+/// Spawn a template's events further ahead than the scheduler does
+pub async fn spawn_event_instances(
+    State(state): State<AppState>,
+    Path(template_uuid): Path<Uuid>,
+    req: Option<Json<SpawnInstancesRequest>>,
+) -> impl IntoResponse {
+    let horizon_days = req
+        .and_then(|Json(r)| r.horizon_days)
+        .unwrap_or(DEFAULT_SPAWN_HORIZON_DAYS)
+        .clamp(1, 365);
+    match state
+        .system
+        .events
+        .spawn_instances(template_uuid, horizon_days)
+        .await
+    {
+        Ok(events) => (StatusCode::OK, Json(events)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,226 @@
+//! Grading submission API handlers
+//!
+//! Batches of raw cards sent to a grading company: creation, milestones,
+//! results entry and the grading ROI report.
+
+use crate::api::AppState;
+use crate::services::{
+    CreateSubmissionRequest, GradingCompany, ItemResultRequest, SubmissionStatus,
+};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+fn user_uuid(user: &crate::api::middleware::AuthenticatedUser) -> Option<Uuid> {
+    Uuid::parse_str(&user.user_uuid).ok()
+}
+
+fn error_response(e: anyhow::Error) -> axum::response::Response {
+    let message = e.to_string();
+    let status = if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    (status, Json(json!({"error": message}))).into_response()
+}
+
+/// Pull cards into a new submission (manager only)
+pub async fn create_grading_submission(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Json(req): Json<CreateSubmissionRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .grading
+        .create_submission(req, user_uuid(&user))
+        .await
+    {
+        Ok(submission) => (StatusCode::CREATED, Json(submission)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SubmissionListQuery {
+    pub status: Option<String>,
+}
+
+pub async fn list_grading_submissions(
+    State(state): State<AppState>,
+    Query(query): Query<SubmissionListQuery>,
+) -> impl IntoResponse {
+    let status = match query.status.as_deref() {
+        Some(s) => match SubmissionStatus::parse(s) {
+            Some(status) => Some(status),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown status '{}'", s)})),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
+    };
+    match state.commerce.grading.get_submissions(status).await {
+        Ok(submissions) => (StatusCode::OK, Json(submissions)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn get_grading_submission(
+    State(state): State<AppState>,
+    Path(submission_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.grading.get_submission(submission_uuid).await {
+        Ok(Some(submission)) => (StatusCode::OK, Json(submission)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Submission not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MilestoneRequest {
+    pub status: String,
+    pub notes: Option<String>,
+}
+
+/// Record a milestone (Shipped, Received, Grading, Returned)
+pub async fn update_grading_submission_status(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(submission_uuid): Path<Uuid>,
+    Json(req): Json<MilestoneRequest>,
+) -> impl IntoResponse {
+    let Some(status) = SubmissionStatus::parse(&req.status) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("Unknown status '{}'", req.status)})),
+        )
+            .into_response();
+    };
+    match state
+        .commerce
+        .grading
+        .advance(submission_uuid, status, req.notes, user_uuid(&user))
+        .await
+    {
+        Ok(submission) => (StatusCode::OK, Json(submission)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct CancelSubmissionRequest {
+    pub notes: Option<String>,
+}
+
+/// Cancel an unshipped submission and restock its cards (manager only)
+pub async fn cancel_grading_submission(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(submission_uuid): Path<Uuid>,
+    body: Option<Json<CancelSubmissionRequest>>,
+) -> impl IntoResponse {
+    let notes = body.and_then(|Json(b)| b.notes);
+    match state
+        .commerce
+        .grading
+        .cancel(submission_uuid, notes, user_uuid(&user))
+        .await
+    {
+        Ok(submission) => (StatusCode::OK, Json(submission)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GradingResultsRequest {
+    pub results: Vec<ItemResultRequest>,
+}
+
+/// Enter grades for returned cards (manager only)
+pub async fn record_grading_results(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(submission_uuid): Path<Uuid>,
+    Json(req): Json<GradingResultsRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .grading
+        .record_results(submission_uuid, req.results, user_uuid(&user))
+        .await
+    {
+        Ok(submission) => (StatusCode::OK, Json(submission)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GradingRoiQuery {
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub grader: Option<String>,
+}
+
+/// Grading ROI (defaults to the last year)
+pub async fn get_grading_roi_report(
+    State(state): State<AppState>,
+    Query(params): Query<GradingRoiQuery>,
+) -> impl IntoResponse {
+    let now = Utc::now();
+    let start = params
+        .start_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| now - chrono::Duration::days(365));
+    let end = params
+        .end_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or(now);
+    let grader = match params.grader.as_deref() {
+        Some(g) => match GradingCompany::parse(g) {
+            Some(grader) => Some(grader),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown grader '{}'", g)})),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
+    };
+
+    match state.commerce.grading.roi_report(start, end, grader).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -55,6 +55,7 @@ pub struct CreateHoldRequest {
     pub deposit_method: String,
     pub notes: Option<String>,
     pub hold_days: Option<i64>,
+    pub plan_uuid: Option<Uuid>,
 }
 
 #[derive(Deserialize)]
@@ -84,6 +85,7 @@ pub async fn create_hold(
         deposit_method: req.deposit_method,
         notes: req.notes,
         hold_days: req.hold_days,
+        plan_uuid: req.plan_uuid,
     };
 
     match state.commerce.holds.create_hold(request).await {
@@ -179,3 +181,73 @@ pub async fn expire_overdue_holds(State(state): State<AppState>) -> impl IntoRes
             .into_response(),
     }
 }
+
+/// Get active layaway plans
+pub async fn get_layaway_plans(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.holds.list_layaway_plans().await {
+        Ok(plans) => Json(plans).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Create a layaway plan
+pub async fn create_layaway_plan(
+    State(state): State<AppState>,
+    Json(req): Json<crate::services::layaway::CreateLayawayPlanRequest>,
+) -> impl IntoResponse {
+    match state.commerce.holds.create_layaway_plan(req).await {
+        Ok(plan) => (StatusCode::CREATED, Json(plan)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Get the installment schedule for a hold
+pub async fn get_hold_installments(
+    State(state): State<AppState>,
+    Path(hold_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.holds.get_installments(hold_uuid).await {
+        Ok(installments) => Json(installments).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Mark overdue installments late and apply late fees
+pub async fn assess_late_fees(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.holds.assess_late_fees().await {
+        Ok(assessed) => Json(json!({
+            "assessed_count": assessed.len(),
+            "installment_ids": assessed.iter().map(|u| u.to_string()).collect::<Vec<_>>()
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Outstanding layaway liability report
+pub async fn get_layaway_liability_report(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.holds.get_layaway_liability_report().await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -149,3 +149,76 @@ pub async fn delete_inventory_item(
             .into_response(),
     }
 }
+
+#[derive(Deserialize)]
+pub struct StockCardQuery {
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+fn parse_date_param(value: Option<&str>) -> Option<chrono::DateTime<chrono::Utc>> {
+    value
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+}
+
+/// Stock card for a pile: opening balance, every movement and closing balance
+/// (defaults to the last 30 days)
+pub async fn get_stock_card(
+    State(state): State<AppState>,
+    Path(inventory_uuid): Path<Uuid>,
+    Query(params): Query<StockCardQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let end = parse_date_param(params.end_date.as_deref()).unwrap_or_else(chrono::Utc::now);
+    let start = parse_date_param(params.start_date.as_deref())
+        .unwrap_or_else(|| end - chrono::Duration::days(30));
+
+    let card = state
+        .db
+        .movements
+        .get_stock_card(inventory_uuid, start, end)
+        .await?;
+    Ok((StatusCode::OK, Json(card)))
+}
+
+#[derive(Deserialize)]
+pub struct AsOfQuery {
+    /// RFC 3339 timestamp; defaults to now
+    pub at: Option<String>,
+}
+
+/// Quantity a pile held at a point in time
+pub async fn get_inventory_quantity_as_of(
+    State(state): State<AppState>,
+    Path(inventory_uuid): Path<Uuid>,
+    Query(params): Query<AsOfQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let at = parse_date_param(params.at.as_deref()).unwrap_or_else(chrono::Utc::now);
+    let quantity = state
+        .db
+        .movements
+        .quantity_as_of(inventory_uuid, at)
+        .await?;
+    Ok((
+        StatusCode::OK,
+        Json(json!({"inventory_uuid": inventory_uuid, "as_of": at, "quantity": quantity})),
+    ))
+}
+
+/// Quantity of a product across all of its piles at a point in time
+pub async fn get_product_quantity_as_of(
+    State(state): State<AppState>,
+    Path(product_uuid): Path<Uuid>,
+    Query(params): Query<AsOfQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let at = parse_date_param(params.at.as_deref()).unwrap_or_else(chrono::Utc::now);
+    let quantity = state
+        .db
+        .movements
+        .product_quantity_as_of(product_uuid, at)
+        .await?;
+    Ok((
+        StatusCode::OK,
+        Json(json!({"product_uuid": product_uuid, "as_of": at, "quantity": quantity})),
+    ))
+}
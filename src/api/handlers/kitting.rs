@@ -0,0 +1,172 @@
+//! Kitting API handlers
+//!
+//! Bills of materials, breaking sealed product and assembling kits.
+
+use crate::api::AppState;
+use crate::services::{AssembleRequest, BreakRequest, SetBomRequest};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct BuildableQuery {
+    pub location_tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ConversionQuery {
+    pub product_uuid: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct BreakQuantity {
+    pub quantity: i32,
+}
+
+/// What a product is made of
+pub async fn get_product_bom(
+    State(state): State<AppState>,
+    Path(product_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.kitting.get_bom(product_uuid).await {
+        Ok(Some(bom)) => (StatusCode::OK, Json(bom)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Product has no bill of materials"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Define or replace a product's bill of materials
+pub async fn set_product_bom(
+    State(state): State<AppState>,
+    Path(product_uuid): Path<Uuid>,
+    Json(req): Json<SetBomRequest>,
+) -> impl IntoResponse {
+    match state.commerce.kitting.set_bom(product_uuid, req).await {
+        Ok(bom) => (StatusCode::OK, Json(bom)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn delete_product_bom(
+    State(state): State<AppState>,
+    Path(product_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.kitting.delete_bom(product_uuid).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// Open units of a sealed pile into its components
+pub async fn break_inventory(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(inventory_uuid): Path<Uuid>,
+    Json(req): Json<BreakQuantity>,
+) -> impl IntoResponse {
+    let performed_by = Uuid::parse_str(&user.user_uuid).ok();
+    let request = BreakRequest {
+        inventory_uuid,
+        quantity: req.quantity,
+    };
+    match state
+        .commerce
+        .kitting
+        .break_product(request, performed_by)
+        .await
+    {
+        Ok(conversion) => (StatusCode::CREATED, Json(conversion)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Build a product from component stock
+pub async fn assemble_product(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(product_uuid): Path<Uuid>,
+    Json(req): Json<AssembleRequest>,
+) -> impl IntoResponse {
+    let performed_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .commerce
+        .kitting
+        .assemble(product_uuid, req, performed_by)
+        .await
+    {
+        Ok(conversion) => (StatusCode::CREATED, Json(conversion)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// How many kits could be sold from component stock
+pub async fn get_kit_buildable(
+    State(state): State<AppState>,
+    Path(product_uuid): Path<Uuid>,
+    Query(params): Query<BuildableQuery>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .kitting
+        .buildable_quantity(product_uuid, params.location_tag.as_deref())
+        .await
+    {
+        Ok(buildable) => (
+            StatusCode::OK,
+            Json(json!({"product_uuid": product_uuid, "buildable": buildable})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Break, assemble and kit-sale history
+pub async fn get_inventory_conversions(
+    State(state): State<AppState>,
+    Query(params): Query<ConversionQuery>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .kitting
+        .get_conversions(params.product_uuid, params.limit.unwrap_or(100))
+        .await
+    {
+        Ok(conversions) => (StatusCode::OK, Json(conversions)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -1,12 +1,16 @@
 //! Location and transfer API handlers
 //!
-//! Handles multi-location management and inventory transfers.
+//! Handles multi-location management and inventory transfers: requesting,
+//! shipping into transit, receiving with short/over counts, closing out
+//! shortages, packing slips and pick lists.
 
 use crate::api::AppState;
+use crate::services::{ShortageResolution, TransferReceipt, TransferStatus};
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Json, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
+    Extension,
 };
 use serde::Deserialize;
 use serde_json::json;
@@ -102,3 +106,178 @@ pub async fn update_transfer_status(
             .into_response(),
     }
 }
+
+#[derive(Deserialize)]
+pub struct TransferListQuery {
+    pub status: Option<String>,
+    /// Transfers into or out of this location
+    pub location: Option<String>,
+}
+
+pub async fn get_transfers(
+    State(state): State<AppState>,
+    Query(params): Query<TransferListQuery>,
+) -> impl IntoResponse {
+    let status = match params.status.as_deref() {
+        Some(s) => match TransferStatus::parse(s) {
+            Some(status) => Some(status),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown transfer status: {}", s)})),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
+    };
+    match state
+        .system
+        .locations
+        .get_transfers(status, params.location.as_deref())
+        .await
+    {
+        Ok(transfers) => (StatusCode::OK, Json(transfers)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn get_transfer(
+    State(state): State<AppState>,
+    Path(transfer_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.locations.get_transfer(transfer_uuid).await {
+        Ok(Some(transfer)) => (StatusCode::OK, Json(transfer)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Transfer not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Ship an approved transfer: stock leaves the source and sits in transit
+pub async fn ship_transfer(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(transfer_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_uuid = Uuid::parse_str(&user.user_uuid).unwrap_or_default();
+    match state
+        .system
+        .locations
+        .ship_transfer(transfer_uuid, user_uuid)
+        .await
+    {
+        Ok(transfer) => (StatusCode::OK, Json(transfer)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReceiveTransferRequest {
+    /// Counted quantities per line; empty receives everything as shipped
+    #[serde(default)]
+    pub items: Vec<TransferReceipt>,
+}
+
+/// Receive a shipped transfer at its destination
+pub async fn receive_transfer(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(transfer_uuid): Path<Uuid>,
+    Json(req): Json<ReceiveTransferRequest>,
+) -> impl IntoResponse {
+    let user_uuid = Uuid::parse_str(&user.user_uuid).unwrap_or_default();
+    match state
+        .system
+        .locations
+        .receive_transfer(transfer_uuid, req.items, user_uuid)
+        .await
+    {
+        Ok(transfer) => (StatusCode::OK, Json(transfer)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CloseTransferRequest {
+    pub resolution: ShortageResolution,
+}
+
+/// Close a short transfer, writing off or returning what never arrived
+pub async fn close_transfer(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(transfer_uuid): Path<Uuid>,
+    Json(req): Json<CloseTransferRequest>,
+) -> impl IntoResponse {
+    let user_uuid = Uuid::parse_str(&user.user_uuid).unwrap_or_default();
+    match state
+        .system
+        .locations
+        .close_transfer(transfer_uuid, req.resolution, user_uuid)
+        .await
+    {
+        Ok(transfer) => (StatusCode::OK, Json(transfer)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Printable packing slip for a transfer
+pub async fn get_transfer_packing_slip(
+    State(state): State<AppState>,
+    Path(transfer_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .locations
+        .packing_slip_html(transfer_uuid)
+        .await
+    {
+        Ok(html) => (
+            StatusCode::OK,
+            [(axum::http::header::CONTENT_TYPE, "text/html")],
+            html,
+        )
+            .into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// Pick list for a transfer at its source store, in walking order
+pub async fn get_transfer_pick_list(
+    State(state): State<AppState>,
+    Path(transfer_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .storage
+        .pick_list_for_transfer(transfer_uuid)
+        .await
+    {
+        Ok(list) => (StatusCode::OK, Json(list)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
@@ -0,0 +1,198 @@
+//! Loyalty API handlers
+//!
+//! Program configuration, customer balances and ledger history, manual
+//! adjustments and on-demand expiry / tier runs.
+
+use crate::api::AppState;
+use crate::services::{CategoryRate, LoyaltySettings, LoyaltyTier};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Earn rate, point value, expiry and tier window
+pub async fn get_loyalty_settings(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.loyalty.get_settings().await {
+        Ok(settings) => Json(settings).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Update program settings (manager only)
+pub async fn update_loyalty_settings(
+    State(state): State<AppState>,
+    Json(req): Json<LoyaltySettings>,
+) -> impl IntoResponse {
+    match state.commerce.loyalty.update_settings(req).await {
+        Ok(settings) => Json(settings).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn get_loyalty_category_rates(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.loyalty.get_category_rates().await {
+        Ok(rates) => Json(rates).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Replace the per-category earn rates (manager only)
+pub async fn set_loyalty_category_rates(
+    State(state): State<AppState>,
+    Json(req): Json<Vec<CategoryRate>>,
+) -> impl IntoResponse {
+    match state.commerce.loyalty.set_category_rates(req).await {
+        Ok(rates) => Json(rates).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn get_loyalty_tiers(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.loyalty.get_tiers().await {
+        Ok(tiers) => Json(tiers).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Replace the tier ladder (manager only)
+pub async fn set_loyalty_tiers(
+    State(state): State<AppState>,
+    Json(req): Json<Vec<LoyaltyTier>>,
+) -> impl IntoResponse {
+    match state.commerce.loyalty.set_tiers(req).await {
+        Ok(tiers) => Json(tiers).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// A customer's balance and tier standing
+pub async fn get_customer_loyalty(
+    State(state): State<AppState>,
+    Path(customer_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.loyalty.get_account(customer_uuid).await {
+        Ok(account) => Json(account).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LoyaltyHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+/// Points ledger and tier moves for a customer, newest first
+pub async fn get_customer_loyalty_history(
+    State(state): State<AppState>,
+    Path(customer_uuid): Path<Uuid>,
+    Query(query): Query<LoyaltyHistoryQuery>,
+) -> impl IntoResponse {
+    let loyalty = &state.commerce.loyalty;
+    let entries = match loyalty
+        .get_history(customer_uuid, query.limit.unwrap_or(100))
+        .await
+    {
+        Ok(entries) => entries,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
+    };
+    match loyalty.get_tier_history(customer_uuid).await {
+        Ok(tiers) => Json(json!({"entries": entries, "tier_changes": tiers})).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AdjustPointsRequest {
+    pub points: i64,
+    pub notes: String,
+}
+
+/// Manually add or remove points with a reason (manager only)
+pub async fn adjust_loyalty_points(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(customer_uuid): Path<Uuid>,
+    Json(req): Json<AdjustPointsRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .loyalty
+        .adjust_points(
+            customer_uuid,
+            req.points,
+            req.notes,
+            Uuid::parse_str(&user.user_uuid).ok(),
+        )
+        .await
+    {
+        Ok(entry) => (StatusCode::CREATED, Json(entry)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Expire overdue points now rather than waiting for the scheduler (manager only)
+pub async fn expire_loyalty_points(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.loyalty.expire_points().await {
+        Ok(points) => Json(json!({"points_expired": points})).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Re-evaluate every managed customer's tier (manager only)
+pub async fn evaluate_loyalty_tiers(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.loyalty.evaluate_all_tiers().await {
+        Ok(changes) => Json(changes).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,242 @@
+//! Media API handlers
+//!
+//! Photo uploads for products, inventory and trade-ins, and serving the
+//! stored images. Content is addressed by hash, so responses are cached
+//! indefinitely and revalidated by ETag.
+
+use crate::api::AppState;
+use crate::services::media::{MediaEntity, MediaItem, MediaUpdate};
+use axum::{
+    body::Body,
+    extract::{Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use axum_extra::extract::Multipart;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+fn media_json(item: &MediaItem) -> serde_json::Value {
+    let mut value = json!(item);
+    value["url"] = json!(item.content_url());
+    value["thumbnail_url"] = json!(item.thumbnail_url());
+    value
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({"error": message.into()}))).into_response()
+}
+
+fn not_found_or(e: anyhow::Error, status: StatusCode) -> Response {
+    let message = e.to_string();
+    if message.contains("not found") {
+        error(StatusCode::NOT_FOUND, message)
+    } else {
+        error(status, message)
+    }
+}
+
+/// Upload an image (multipart: `file`, `entity_type`, `entity_uuid`,
+/// optional `caption`)
+pub async fn upload_media(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let mut file: Option<(Option<String>, Vec<u8>)> = None;
+    let mut entity_type = None;
+    let mut entity_uuid = None;
+    let mut caption = None;
+
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return error(StatusCode::BAD_REQUEST, e.to_string()),
+        };
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            let filename = field.file_name().map(|f| f.to_string());
+            match field.bytes().await {
+                Ok(bytes) => file = Some((filename, bytes.to_vec())),
+                Err(e) => return error(StatusCode::BAD_REQUEST, e.to_string()),
+            }
+            continue;
+        }
+        let text = match field.text().await {
+            Ok(text) => text,
+            Err(e) => return error(StatusCode::BAD_REQUEST, e.to_string()),
+        };
+        match name.as_str() {
+            "entity_type" => entity_type = Some(text),
+            "entity_uuid" => entity_uuid = Some(text),
+            "caption" => caption = Some(text),
+            _ => {}
+        }
+    }
+
+    let Some((filename, bytes)) = file else {
+        return error(StatusCode::BAD_REQUEST, "Missing 'file' field");
+    };
+    let Some(entity_type) = entity_type.as_deref().and_then(MediaEntity::parse) else {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "entity_type must be product, inventory or trade_in",
+        );
+    };
+    let Some(entity_uuid) = entity_uuid.and_then(|u| Uuid::parse_str(u.trim()).ok()) else {
+        return error(StatusCode::BAD_REQUEST, "Missing or invalid entity_uuid");
+    };
+
+    match state
+        .system
+        .media
+        .upload(
+            &bytes,
+            filename,
+            entity_type,
+            entity_uuid,
+            caption,
+            Uuid::parse_str(&user.user_uuid).ok(),
+        )
+        .await
+    {
+        Ok(item) => (StatusCode::CREATED, Json(media_json(&item))).into_response(),
+        Err(e) => error(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MediaListQuery {
+    pub entity_type: String,
+    pub entity_uuid: Uuid,
+}
+
+/// Images attached to a product, inventory item or trade-in
+pub async fn list_media(
+    State(state): State<AppState>,
+    Query(query): Query<MediaListQuery>,
+) -> impl IntoResponse {
+    let Some(entity_type) = MediaEntity::parse(&query.entity_type) else {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "entity_type must be product, inventory or trade_in",
+        );
+    };
+    match state
+        .system
+        .media
+        .list(entity_type, query.entity_uuid)
+        .await
+    {
+        Ok(items) => (
+            StatusCode::OK,
+            Json(items.iter().map(media_json).collect::<Vec<_>>()),
+        )
+            .into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+pub async fn get_media(
+    State(state): State<AppState>,
+    Path(media_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.media.get(media_uuid).await {
+        Ok(Some(item)) => (StatusCode::OK, Json(media_json(&item))).into_response(),
+        Ok(None) => error(StatusCode::NOT_FOUND, "Media not found"),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// Change an image's caption or position
+pub async fn update_media(
+    State(state): State<AppState>,
+    Path(media_uuid): Path<Uuid>,
+    Json(update): Json<MediaUpdate>,
+) -> impl IntoResponse {
+    match state.system.media.update(media_uuid, update).await {
+        Ok(item) => (StatusCode::OK, Json(media_json(&item))).into_response(),
+        Err(e) => not_found_or(e, StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Detach an image (manager only)
+pub async fn delete_media(
+    State(state): State<AppState>,
+    Path(media_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.media.delete(media_uuid).await {
+        Ok(item) => (StatusCode::OK, Json(media_json(&item))).into_response(),
+        Err(e) => not_found_or(e, StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Immutable-content response with an ETag of the content hash
+fn cached_bytes(headers: &HeaderMap, etag: &str, content_type: &str, bytes: Vec<u8>) -> Response {
+    let etag = format!("\"{}\"", etag);
+    let builder = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, "public, max-age=31536000, immutable");
+    let matches = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').any(|t| t.trim() == etag || t.trim() == "*"))
+        .unwrap_or(false);
+    let response = if matches {
+        builder.status(StatusCode::NOT_MODIFIED).body(Body::empty())
+    } else {
+        builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, bytes.len())
+            .body(Body::from(bytes))
+    };
+    response.unwrap_or_else(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// The image, fetched from a peer first if it hasn't replicated here yet
+pub async fn get_media_content(
+    State(state): State<AppState>,
+    Path(media_uuid): Path<Uuid>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let peers = state.sync_actor.get_devices().await;
+    match state.system.media.content(media_uuid, &peers).await {
+        Ok((item, bytes)) => cached_bytes(&headers, &item.content_hash, &item.mime_type, bytes),
+        Err(e) => not_found_or(e, StatusCode::BAD_GATEWAY),
+    }
+}
+
+pub async fn get_media_thumbnail(
+    State(state): State<AppState>,
+    Path(media_uuid): Path<Uuid>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let peers = state.sync_actor.get_devices().await;
+    match state.system.media.thumbnail(media_uuid, &peers).await {
+        Ok((item, content_type, bytes)) => cached_bytes(
+            &headers,
+            &format!("{}-thumb", item.content_hash),
+            &content_type,
+            bytes,
+        ),
+        Err(e) => not_found_or(e, StatusCode::BAD_GATEWAY),
+    }
+}
+
+/// Raw content by hash, for peers replicating media. Only serves what is
+/// on this device so requests don't bounce between peers.
+pub async fn get_media_blob(
+    State(state): State<AppState>,
+    Path(content_hash): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    match state.system.media.read_blob(&content_hash).await {
+        Ok(Some(bytes)) => cached_bytes(&headers, &content_hash, "application/octet-stream", bytes),
+        Ok(None) => error(StatusCode::NOT_FOUND, "Media content not found"),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
@@ -9,26 +9,39 @@
 pub mod audit;
 pub mod backups;
 pub mod barcode;
+pub mod bulk_inventory;
 pub mod buylist;
 pub mod cash_drawer;
+pub mod consignment;
+pub mod currency;
+pub mod customer_display;
 pub mod customers;
+pub mod cycle_counts;
 pub mod dashboard;
 pub mod events;
+pub mod grading;
 pub mod health;
 pub mod holds;
 pub mod inventory;
 pub mod invoices;
+pub mod kitting;
 pub mod labels;
 pub mod locations;
+pub mod loyalty;
+pub mod media;
 pub mod notifications;
 pub mod pricing;
 pub mod printers;
 pub mod products;
+pub mod purchasing;
 pub mod receipts;
+pub mod replenishment;
 pub mod reports;
 pub mod returns;
 pub mod scheduler;
 pub mod serialized_inventory;
+pub mod shrinkage;
+pub mod storage;
 pub mod sync;
 pub mod tax;
 pub mod trade_in;
@@ -57,6 +70,12 @@ pub use barcode::generate_barcode;
 pub use barcode::generate_qrcode;
 pub use barcode::lookup_by_barcode;
 
+// Bulk inventory import/export handlers
+pub use bulk_inventory::export_inventory;
+pub use bulk_inventory::get_import_profiles;
+pub use bulk_inventory::import_inventory;
+pub use bulk_inventory::save_import_profile;
+
 // Buylist handlers
 pub use buylist::get_buylist_quote;
 pub use buylist::process_buylist;
@@ -71,11 +90,59 @@ pub use cash_drawer::open_cash_drawer;
 pub use cash_drawer::open_shift;
 pub use cash_drawer::record_cash_count;
 
+// Consignment handlers
+pub use consignment::create_consignor;
+pub use consignment::expire_consignments;
+pub use consignment::get_consignor;
+pub use consignment::get_consignor_items;
+pub use consignment::get_consignor_statement;
+pub use consignment::get_consignors;
+pub use consignment::intake_consignment;
+pub use consignment::pay_consignor;
+pub use consignment::return_consignment;
+
+// Currency handlers
+pub use currency::convert_currency;
+pub use currency::delete_exchange_rate;
+pub use currency::get_currency_settings;
+pub use currency::set_base_currency;
+pub use currency::set_exchange_rate;
+
+// Customer display handlers
+pub use customer_display::create_display_promotion;
+pub use customer_display::deactivate_display_promotion;
+pub use customer_display::get_customer_display_state;
+pub use customer_display::get_display_promotions;
+pub use customer_display::reset_customer_display;
+pub use customer_display::show_payment_prompt;
+pub use customer_display::stream_customer_display;
+
+// Cycle count handlers
+pub use cycle_counts::approve_count_session;
+pub use cycle_counts::cancel_count_session;
+pub use cycle_counts::classify_products;
+pub use cycle_counts::create_count_plan;
+pub use cycle_counts::create_count_session;
+pub use cycle_counts::finish_count_round;
+pub use cycle_counts::get_abc_classes;
+pub use cycle_counts::get_count_plans;
+pub use cycle_counts::get_count_session;
+pub use cycle_counts::get_count_sessions;
+pub use cycle_counts::get_count_sheet;
+pub use cycle_counts::record_counts;
+pub use cycle_counts::run_count_plan;
+
 // Customer handlers
+pub use customers::anonymize_customer;
 pub use customers::create_customer;
+pub use customers::export_customer_data;
+pub use customers::find_duplicate_customers;
 pub use customers::get_customer_by_id;
+pub use customers::get_customer_duplicates;
 pub use customers::get_customer_history;
 pub use customers::get_customers;
+pub use customers::merge_customers;
+pub use customers::update_customer;
 pub use customers::update_store_credit;
 
 // Dashboard handlers
@@ -83,23 +150,65 @@ pub use dashboard::get_alerts;
 pub use dashboard::get_dashboard_stats;
 
 // Event handlers
+pub use events::cancel_registration;
+pub use events::check_in_participant;
 pub use events::create_event;
+pub use events::create_event_template;
+pub use events::drop_participant;
+pub use events::get_event_attendance;
+pub use events::get_event_participants;
+pub use events::get_event_pnl;
+pub use events::get_event_templates;
 pub use events::get_events;
+pub use events::get_prize_payouts;
+pub use events::get_prize_structure;
+pub use events::get_round;
+pub use events::get_rounds;
+pub use events::get_standings;
+pub use events::mark_no_shows;
+pub use events::pair_next_round;
+pub use events::pay_out_prizes;
+pub use events::pre_register;
+pub use events::print_pairings;
+pub use events::print_standings;
+pub use events::record_cash_payment;
 pub use events::register_participant;
+pub use events::report_match_result;
+pub use events::set_prize_structure;
+pub use events::spawn_event_instances;
+pub use events::start_round;
+pub use events::start_tournament;
+pub use events::update_event_template;
+
+// Grading submission handlers
+pub use grading::cancel_grading_submission;
+pub use grading::create_grading_submission;
+pub use grading::get_grading_roi_report;
+pub use grading::get_grading_submission;
+pub use grading::list_grading_submissions;
+pub use grading::record_grading_results;
+pub use grading::update_grading_submission_status;
 
 // Health handlers
 pub use health::get_audit_log;
 pub use health::get_record_audit_history;
 pub use health::health_check;
 pub use health::health_check_detailed;
+pub use health::metrics_json;
+pub use health::metrics_prometheus;
 
 // Holds/Layaway handlers
+pub use holds::assess_late_fees;
 pub use holds::cancel_hold;
 pub use holds::complete_hold;
 pub use holds::create_hold;
+pub use holds::create_layaway_plan;
 pub use holds::expire_overdue_holds;
 pub use holds::get_customer_holds;
 pub use holds::get_hold;
+pub use holds::get_hold_installments;
+pub use holds::get_layaway_liability_report;
+pub use holds::get_layaway_plans;
 pub use holds::make_hold_payment;
 
 // Inventory handlers
@@ -109,25 +218,76 @@ pub use inventory::delete_inventory_item;
 pub use inventory::get_inventory;
 pub use inventory::get_inventory_item;
 pub use inventory::get_inventory_matrix;
+pub use inventory::get_inventory_quantity_as_of;
 pub use inventory::get_low_stock;
+pub use inventory::get_product_quantity_as_of;
+pub use inventory::get_stock_card;
 
 // Invoice handlers
 pub use invoices::generate_invoice;
 
+// Kitting handlers
+pub use kitting::assemble_product;
+pub use kitting::break_inventory;
+pub use kitting::delete_product_bom;
+pub use kitting::get_inventory_conversions;
+pub use kitting::get_kit_buildable;
+pub use kitting::get_product_bom;
+pub use kitting::set_product_bom;
+
 // Label handlers
 pub use labels::get_inventory_label;
 pub use labels::get_product_label;
 
 // Location/Transfer handlers
+pub use locations::close_transfer;
 pub use locations::create_transfer;
 pub use locations::get_locations;
+pub use locations::get_transfer;
+pub use locations::get_transfer_packing_slip;
+pub use locations::get_transfer_pick_list;
+pub use locations::get_transfers;
+pub use locations::receive_transfer;
+pub use locations::ship_transfer;
 pub use locations::update_transfer_status;
 pub use locations::upsert_location;
 
+// Loyalty handlers
+pub use loyalty::adjust_loyalty_points;
+pub use loyalty::evaluate_loyalty_tiers;
+pub use loyalty::expire_loyalty_points;
+pub use loyalty::get_customer_loyalty;
+pub use loyalty::get_customer_loyalty_history;
+pub use loyalty::get_loyalty_category_rates;
+pub use loyalty::get_loyalty_settings;
+pub use loyalty::get_loyalty_tiers;
+pub use loyalty::set_loyalty_category_rates;
+pub use loyalty::set_loyalty_tiers;
+pub use loyalty::update_loyalty_settings;
+
+// Media handlers
+pub use media::delete_media;
+pub use media::get_media;
+pub use media::get_media_blob;
+pub use media::get_media_content;
+pub use media::get_media_thumbnail;
+pub use media::list_media;
+pub use media::update_media;
+pub use media::upload_media;
+
 // Notification handlers
 pub use notifications::email_receipt;
 pub use notifications::email_trade_in_quote;
+pub use notifications::get_notification_log;
+pub use notifications::get_notification_preferences;
+pub use notifications::list_notification_templates;
 pub use notifications::notify_customer;
+pub use notifications::record_notification_bounce;
+pub use notifications::reset_notification_template;
+pub use notifications::retry_notifications;
+pub use notifications::unsubscribe_notifications;
+pub use notifications::update_notification_preferences;
+pub use notifications::update_notification_template;
 
 // Pricing handlers
 pub use pricing::get_price_cache_stats;
@@ -148,17 +308,40 @@ pub use products::get_product_by_id;
 pub use products::get_products;
 pub use products::search_products;
 
+// Purchasing handlers
+pub use purchasing::cancel_purchase_order;
+pub use purchasing::close_purchase_order;
+pub use purchasing::create_purchase_order;
+pub use purchasing::create_suggested_purchase_orders;
+pub use purchasing::create_supplier;
+pub use purchasing::get_backorders;
+pub use purchasing::get_purchase_order;
+pub use purchasing::get_purchase_order_labels;
+pub use purchasing::get_purchase_orders;
+pub use purchasing::get_purchase_suggestions;
+pub use purchasing::get_suppliers;
+pub use purchasing::receive_purchase_order;
+pub use purchasing::submit_purchase_order;
+
 // Receipt handlers
 pub use receipts::get_receipt;
 
+// Replenishment handlers
+pub use replenishment::get_dead_stock;
+pub use replenishment::get_forecasts;
+pub use replenishment::get_product_forecast;
+pub use replenishment::get_reorder_suggestions;
+
 // Report handlers
 pub use reports::get_cash_flow_report;
+pub use reports::get_count_accuracy_report;
 pub use reports::get_employee_performance_report;
 pub use reports::get_inventory_aging_report;
 pub use reports::get_inventory_valuation;
 pub use reports::get_low_stock_report;
 pub use reports::get_sales_report;
 pub use reports::get_sales_report_csv_export;
+pub use reports::get_shrink_report;
 pub use reports::get_top_sellers;
 
 // Returns handlers
@@ -174,7 +357,28 @@ pub use scheduler::run_scheduled_notifications;
 pub use serialized_inventory::add_certificate;
 pub use serialized_inventory::add_grading;
 pub use serialized_inventory::get_serialized_details;
+pub use serialized_inventory::list_cert_verifications;
+pub use serialized_inventory::lookup_grading_cert;
 pub use serialized_inventory::update_serialized_details;
+pub use serialized_inventory::verify_serialized_certificate;
+
+// Shrinkage handlers
+pub use shrinkage::get_damage_records;
+pub use shrinkage::record_count_shrink;
+pub use shrinkage::report_damage;
+pub use shrinkage::resolve_damage;
+
+// Storage location handlers
+pub use storage::create_pick_list;
+pub use storage::create_storage_location;
+pub use storage::get_hold_pick_list;
+pub use storage::get_product_bin_moves;
+pub use storage::get_storage_contents;
+pub use storage::get_storage_location;
+pub use storage::get_storage_tree;
+pub use storage::locate_product;
+pub use storage::move_stock;
+pub use storage::update_storage_location;
 
 // Sync handlers
 pub use sync::get_discovered_devices;
@@ -188,9 +392,17 @@ pub use sync::resolve_sync_conflict;
 pub use sync::trigger_peer_sync;
 
 // Tax handlers
+pub use tax::add_customer_tax_exemption;
+pub use tax::configure_location_tax;
+pub use tax::create_tax_jurisdiction;
 pub use tax::create_tax_rate;
+pub use tax::get_customer_tax_exemptions;
 pub use tax::get_default_tax_rate;
+pub use tax::get_location_tax_jurisdictions;
+pub use tax::get_tax_jurisdictions;
+pub use tax::get_tax_liability_report;
 pub use tax::get_tax_rates;
+pub use tax::revoke_tax_exemption;
 
 // Trade-in handlers
 pub use trade_in::check_trade_in_eligibility;
@@ -198,14 +410,25 @@ pub use trade_in::get_trade_in_history;
 pub use trade_in::log_suspicious_activity;
 
 // Transaction handlers
+pub use transactions::attach_transaction_customer;
+pub use transactions::complete_checkout;
+pub use transactions::correct_transaction_price;
 pub use transactions::create_transaction;
+pub use transactions::get_transaction_adjustments;
 pub use transactions::get_transaction_by_id;
 pub use transactions::get_transactions;
+pub use transactions::retender_transaction;
+pub use transactions::validate_checkout;
+pub use transactions::void_transaction_line;
 
 // User handlers
 pub use users::get_current_user;
 
 // Wants list handlers
 pub use wants::create_wants_list;
+pub use wants::decline_wants_match;
+pub use wants::fulfill_wants_match;
+pub use wants::get_customer_wants_matches;
 pub use wants::get_wants_lists;
+pub use wants::list_wants_matches;
 pub use wants::update_inventory_item;
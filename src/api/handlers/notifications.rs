@@ -1,12 +1,17 @@
 //! Notification API handlers
 //!
-//! Handles email receipts, customer notifications, and trade-in quote emails.
+//! Handles email receipts, customer notifications, trade-in quote emails,
+//! messaging preferences, templates and the delivery log.
 
 use crate::api::AppState;
+use crate::services::notification::{
+    DeliveryStatus, MessageCategory, NotificationChannel, PreferencesUpdate, TemplateKey,
+};
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Json, Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{Html, IntoResponse},
+    Extension,
 };
 use serde::Deserialize;
 use serde_json::json;
@@ -15,6 +20,9 @@ use uuid::Uuid;
 #[derive(Deserialize)]
 pub struct EmailReceiptRequest {
     email: String,
+    /// Terminal whose customer display should confirm the receipt was sent
+    #[serde(default)]
+    terminal_id: Option<String>,
 }
 
 pub async fn email_receipt(
@@ -30,22 +38,55 @@ pub async fn email_receipt(
         }
     };
 
-    // 2. Create Message
-    let message = crate::services::notification::EmailMessage {
-        to: payload.email,
-        subject: format!("Receipt for Transaction {}", transaction_uuid),
-        body: html_content,
-        attachment_path: None,
+    // 2. Send from the receipt template, logged for delivery tracking
+    let delivery = match state
+        .system
+        .notifications
+        .send_email(
+            &payload.email,
+            None,
+            TemplateKey::Receipt,
+            &[
+                ("transaction_id", transaction_uuid.to_string()),
+                ("receipt", html_content),
+            ],
+        )
+        .await
+    {
+        Ok(delivery) => delivery,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
     };
 
-    // 3. Send Email
-    match state.system.email.send_email(&message).await {
-        Ok(_) => (StatusCode::OK, Json(json!({"message": "Receipt sent"}))).into_response(),
-        Err(e) => (
+    if delivery.is_sent() {
+        if let Some(terminal_id) = payload.terminal_id {
+            state
+                .system
+                .customer_display
+                .mark_receipt_sent(&terminal_id, &payload.email);
+        }
+        (
+            StatusCode::OK,
+            Json(json!({
+                "message": "Receipt sent",
+                "notification_uuid": delivery.notification_uuid
+            })),
+        )
+            .into_response()
+    } else {
+        (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": e.to_string()})),
+            Json(json!({
+                "error": delivery.last_error.unwrap_or_else(|| "Receipt not sent".to_string()),
+                "notification_uuid": delivery.notification_uuid
+            })),
         )
-            .into_response(),
+            .into_response()
     }
 }
 
@@ -53,6 +94,9 @@ pub async fn email_receipt(
 pub struct NotificationRequest {
     message: String,
     channel: String, // "sms", "email", "both"
+    /// Promotional messages need the customer's marketing consent
+    #[serde(default)]
+    marketing: bool,
 }
 
 pub async fn notify_customer(
@@ -79,40 +123,60 @@ pub async fn notify_customer(
         }
     };
 
-    let mut success_count = 0;
+    let mut channels = Vec::new();
     let mut errors = Vec::new();
-
-    // 2. Send SMS
     if payload.channel == "sms" || payload.channel == "both" {
-        if let Some(phone) = &customer.phone {
-            if !phone.is_empty() {
-                match state.system.sms.send_sms(phone, &payload.message).await {
-                    Ok(_) => success_count += 1,
-                    Err(e) => errors.push(format!("SMS failed: {}", e)),
-                }
-            } else {
-                errors.push("SMS requested but customer has no phone".to_string());
-            }
+        match customer.phone.as_deref() {
+            Some(phone) if !phone.is_empty() => channels.push(NotificationChannel::Sms),
+            _ => errors.push("SMS requested but customer has no phone".to_string()),
         }
     }
-
-    // 3. Send Email
     if payload.channel == "email" || payload.channel == "both" {
-        if let Some(email) = &customer.email {
-            if !email.is_empty() {
-                let msg = crate::services::notification::EmailMessage {
-                    to: email.clone(),
-                    subject: "Notification from VaultSync".to_string(),
-                    body: payload.message.clone(),
-                    attachment_path: None,
-                };
-                match state.system.email.send_email(&msg).await {
-                    Ok(_) => success_count += 1,
-                    Err(e) => errors.push(format!("Email failed: {}", e)),
-                }
-            } else {
-                errors.push("Email requested but customer has no email".to_string());
-            }
+        match customer.email.as_deref() {
+            Some(email) if !email.is_empty() => channels.push(NotificationChannel::Email),
+            _ => errors.push("Email requested but customer has no email".to_string()),
+        }
+    }
+
+    // 2. Send on each channel, subject to preferences and consent
+    let category = if payload.marketing {
+        MessageCategory::Marketing
+    } else {
+        MessageCategory::Transactional
+    };
+    let deliveries = match state
+        .system
+        .notifications
+        .send_to_customer(
+            customer_uuid,
+            TemplateKey::CustomMessage,
+            category,
+            &[("message", payload.message)],
+            &channels,
+            false,
+        )
+        .await
+    {
+        Ok(deliveries) => deliveries,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
+    };
+
+    let mut success_count = 0;
+    for delivery in &deliveries {
+        if delivery.is_sent() {
+            success_count += 1;
+        } else {
+            errors.push(format!(
+                "{} not sent: {}",
+                delivery.channel.map(|c| c.as_str()).unwrap_or("message"),
+                delivery.last_error.as_deref().unwrap_or("unknown error")
+            ));
         }
     }
     if success_count > 0 {
@@ -121,14 +185,19 @@ pub async fn notify_customer(
             Json(json!({
                 "message": "Notification process completed",
                 "sent": success_count,
-                "errors": errors
+                "errors": errors,
+                "deliveries": deliveries
             })),
         )
             .into_response()
     } else {
         (
             StatusCode::BAD_REQUEST,
-            Json(json!({"error": "Failed to send notification", "details": errors})),
+            Json(json!({
+                "error": "Failed to send notification",
+                "details": errors,
+                "deliveries": deliveries
+            })),
         )
             .into_response()
     }
@@ -280,3 +349,259 @@ pub async fn email_trade_in_quote(
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e.to_string()}))).into_response(),
     }
 }
+
+/// A customer's channel preferences and marketing consent
+pub async fn get_notification_preferences(
+    State(state): State<AppState>,
+    Path(customer_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .notifications
+        .get_preferences(customer_uuid)
+        .await
+    {
+        Ok(prefs) => (StatusCode::OK, Json(prefs)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// Record channel choices or marketing consent given at the counter
+pub async fn update_notification_preferences(
+    State(state): State<AppState>,
+    Path(customer_uuid): Path<Uuid>,
+    Json(update): Json<PreferencesUpdate>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .notifications
+        .update_preferences(customer_uuid, update)
+        .await
+    {
+        Ok(prefs) => (StatusCode::OK, Json(prefs)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Unsubscribe link target; public so customers can follow it from a message
+pub async fn unsubscribe_notifications(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> impl IntoResponse {
+    match state.system.notifications.unsubscribe(&token).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Html(format!(
+                "<!DOCTYPE html><html><body style=\"font-family: Arial, sans-serif;\">\
+                 <h2>You're unsubscribed</h2>\
+                 <p>You won't receive marketing messages from {} any more. \
+                 We'll still let you know about your orders, holds and events.</p>\
+                 </body></html>",
+                state.config.store_name
+            )),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Html(format!(
+                "<!DOCTYPE html><html><body><p>{}</p></body></html>",
+                e
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Every message template, with the store's edits applied (manager only)
+pub async fn list_notification_templates(State(state): State<AppState>) -> impl IntoResponse {
+    match state.system.notifications.list_templates().await {
+        Ok(templates) => {
+            let variables: serde_json::Map<String, serde_json::Value> = TemplateKey::ALL
+                .iter()
+                .map(|key| (key.as_str().to_string(), json!(key.variables())))
+                .collect();
+            (
+                StatusCode::OK,
+                Json(json!({"templates": templates, "variables": variables})),
+            )
+                .into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+fn parse_template_path(
+    template_key: &str,
+    channel: &str,
+) -> Result<(TemplateKey, NotificationChannel), (StatusCode, Json<serde_json::Value>)> {
+    let key = TemplateKey::parse(template_key).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": format!("Unknown template '{}'", template_key)})),
+        )
+    })?;
+    let channel = NotificationChannel::parse(channel).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": format!("Unknown channel '{}'", channel)})),
+        )
+    })?;
+    Ok((key, channel))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTemplateRequest {
+    #[serde(default)]
+    pub subject: String,
+    pub body: String,
+}
+
+/// Replace a message template (manager only)
+pub async fn update_notification_template(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path((template_key, channel)): Path<(String, String)>,
+    Json(req): Json<UpdateTemplateRequest>,
+) -> impl IntoResponse {
+    let (key, channel) = match parse_template_path(&template_key, &channel) {
+        Ok(parsed) => parsed,
+        Err(e) => return e.into_response(),
+    };
+    match state
+        .system
+        .notifications
+        .save_template(
+            key,
+            channel,
+            req.subject,
+            req.body,
+            Uuid::parse_str(&user.user_uuid).ok(),
+        )
+        .await
+    {
+        Ok(template) => (StatusCode::OK, Json(template)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Go back to the built-in template (manager only)
+pub async fn reset_notification_template(
+    State(state): State<AppState>,
+    Path((template_key, channel)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (key, channel) = match parse_template_path(&template_key, &channel) {
+        Ok(parsed) => parsed,
+        Err(e) => return e.into_response(),
+    };
+    match state
+        .system
+        .notifications
+        .reset_template(key, channel)
+        .await
+    {
+        Ok(template) => (StatusCode::OK, Json(template)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DeliveryLogQuery {
+    pub customer_uuid: Option<Uuid>,
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Customer messages sent, failed, bounced or suppressed (manager only)
+pub async fn get_notification_log(
+    State(state): State<AppState>,
+    Query(query): Query<DeliveryLogQuery>,
+) -> impl IntoResponse {
+    let status = match query.status.as_deref() {
+        Some(s) => match DeliveryStatus::parse(s) {
+            Some(status) => Some(status),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown status '{}'", s)})),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
+    };
+    match state
+        .system
+        .notifications
+        .delivery_log(
+            query.customer_uuid,
+            status,
+            query.limit.unwrap_or(100).clamp(1, 1000),
+        )
+        .await
+    {
+        Ok(log) => (StatusCode::OK, Json(log)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Resend failed messages that are due now (manager only)
+pub async fn retry_notifications(State(state): State<AppState>) -> impl IntoResponse {
+    match state.system.notifications.retry_failed().await {
+        Ok(retried) => (
+            StatusCode::OK,
+            Json(json!({
+                "retried": retried.len(),
+                "sent": retried.iter().filter(|r| r.is_sent()).count(),
+                "deliveries": retried
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BounceRequest {
+    pub reason: Option<String>,
+}
+
+/// Record a bounce reported by the mail or SMS provider (manager only)
+pub async fn record_notification_bounce(
+    State(state): State<AppState>,
+    Path(notification_uuid): Path<Uuid>,
+    Json(req): Json<BounceRequest>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .notifications
+        .record_bounce(notification_uuid, req.reason)
+        .await
+    {
+        Ok(record) => (StatusCode::OK, Json(record)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
@@ -0,0 +1,344 @@
+//! Purchasing API handlers
+//!
+//! Suppliers, purchase orders, reorder suggestions and receiving.
+
+use crate::api::AppState;
+use crate::services::{
+    CreatePurchaseOrderRequest, CreateSupplierRequest, PurchaseOrderReceipt, PurchaseOrderStatus,
+    ReceiveLineRequest,
+};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+/// Printable label for stock that came in on a receipt
+#[derive(Serialize)]
+pub struct ReceivedLabel {
+    pub inventory_uuid: Uuid,
+    pub quantity: i32,
+    pub html: String,
+}
+
+async fn receipt_labels(state: &AppState, receipts: &[PurchaseOrderReceipt]) -> Vec<ReceivedLabel> {
+    let mut labels = Vec::with_capacity(receipts.len());
+    for receipt in receipts {
+        match state
+            .system
+            .labels
+            .generate_inventory_label_html(receipt.inventory_uuid)
+            .await
+        {
+            Ok(html) => labels.push(ReceivedLabel {
+                inventory_uuid: receipt.inventory_uuid,
+                quantity: receipt.quantity,
+                html,
+            }),
+            Err(e) => tracing::warn!(
+                "Could not generate label for {}: {}",
+                receipt.inventory_uuid,
+                e
+            ),
+        }
+    }
+    labels
+}
+
+fn user_uuid(user: &crate::api::middleware::AuthenticatedUser) -> Option<Uuid> {
+    Uuid::parse_str(&user.user_uuid).ok()
+}
+
+/// List active suppliers
+pub async fn get_suppliers(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.purchasing.get_suppliers().await {
+        Ok(suppliers) => (StatusCode::OK, Json(suppliers)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Create a supplier
+pub async fn create_supplier(
+    State(state): State<AppState>,
+    Json(req): Json<CreateSupplierRequest>,
+) -> impl IntoResponse {
+    match state.commerce.purchasing.create_supplier(req).await {
+        Ok(supplier) => (StatusCode::CREATED, Json(supplier)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PurchaseOrderQuery {
+    pub status: Option<String>,
+    pub supplier_uuid: Option<Uuid>,
+}
+
+/// List purchase orders, optionally by status or supplier
+pub async fn get_purchase_orders(
+    State(state): State<AppState>,
+    Query(params): Query<PurchaseOrderQuery>,
+) -> impl IntoResponse {
+    let status = match params.status.as_deref() {
+        Some(s) => match PurchaseOrderStatus::parse(s) {
+            Some(status) => Some(status),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown status '{}'", s)})),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
+    };
+
+    match state
+        .commerce
+        .purchasing
+        .get_purchase_orders(status, params.supplier_uuid)
+        .await
+    {
+        Ok(orders) => (StatusCode::OK, Json(orders)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Get a purchase order with its lines
+pub async fn get_purchase_order(
+    State(state): State<AppState>,
+    Path(po_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.purchasing.get_purchase_order(po_uuid).await {
+        Ok(Some(order)) => (StatusCode::OK, Json(order)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Purchase order not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Create a draft purchase order
+pub async fn create_purchase_order(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Json(req): Json<CreatePurchaseOrderRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .purchasing
+        .create_purchase_order(req, user_uuid(&user))
+        .await
+    {
+        Ok(order) => (StatusCode::CREATED, Json(order)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Mark a draft as sent to the supplier
+pub async fn submit_purchase_order(
+    State(state): State<AppState>,
+    Path(po_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .purchasing
+        .submit_purchase_order(po_uuid)
+        .await
+    {
+        Ok(order) => (StatusCode::OK, Json(order)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Cancel an order that has not been received against
+pub async fn cancel_purchase_order(
+    State(state): State<AppState>,
+    Path(po_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .purchasing
+        .cancel_purchase_order(po_uuid)
+        .await
+    {
+        Ok(order) => (StatusCode::OK, Json(order)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Close a partially received order short
+pub async fn close_purchase_order(
+    State(state): State<AppState>,
+    Path(po_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .purchasing
+        .close_purchase_order(po_uuid)
+        .await
+    {
+        Ok(order) => (StatusCode::OK, Json(order)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReceivePurchaseOrderRequest {
+    pub lines: Vec<ReceiveLineRequest>,
+}
+
+/// Receive stock against a purchase order and print labels for it
+pub async fn receive_purchase_order(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(po_uuid): Path<Uuid>,
+    Json(req): Json<ReceivePurchaseOrderRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .purchasing
+        .receive(po_uuid, req.lines, user_uuid(&user))
+        .await
+    {
+        Ok(result) => {
+            let labels = receipt_labels(&state, &result.receipts).await;
+            (
+                StatusCode::OK,
+                Json(json!({
+                    "purchase_order": result.purchase_order,
+                    "receipts": result.receipts,
+                    "labels": labels,
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Reprint labels for everything received on a purchase order
+pub async fn get_purchase_order_labels(
+    State(state): State<AppState>,
+    Path(po_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.commerce.purchasing.get_receipts(po_uuid).await {
+        Ok(receipts) => {
+            let labels = receipt_labels(&state, &receipts).await;
+            (StatusCode::OK, Json(labels)).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Lines the supplier has backordered on open orders
+pub async fn get_backorders(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.purchasing.get_backorders().await {
+        Ok(backorders) => (StatusCode::OK, Json(backorders)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SuggestionQuery {
+    pub lookback_days: Option<i64>,
+    pub cover_days: Option<i64>,
+}
+
+/// Suggested orders from reorder points and sales velocity
+pub async fn get_purchase_suggestions(
+    State(state): State<AppState>,
+    Query(params): Query<SuggestionQuery>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .purchasing
+        .suggest_purchase_orders(
+            params.lookback_days.unwrap_or(30),
+            params.cover_days.unwrap_or(30),
+        )
+        .await
+    {
+        Ok(suggestions) => (StatusCode::OK, Json(suggestions)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Create draft purchase orders from the current suggestions
+pub async fn create_suggested_purchase_orders(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Query(params): Query<SuggestionQuery>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .purchasing
+        .create_suggested_drafts(
+            params.lookback_days.unwrap_or(30),
+            params.cover_days.unwrap_or(30),
+            user_uuid(&user),
+        )
+        .await
+    {
+        Ok(orders) => (StatusCode::CREATED, Json(orders)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
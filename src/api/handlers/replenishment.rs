@@ -0,0 +1,136 @@
+//! Replenishment API handlers
+//!
+//! Demand forecasts, reorder suggestions and dead stock.
+
+use crate::api::AppState;
+use crate::services::{ForecastOptions, StockStatus};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct ForecastQuery {
+    pub lookback_days: Option<i64>,
+    pub cover_days: Option<i64>,
+    pub dead_stock_days: Option<i64>,
+    /// reorder, healthy, overstocked or dead
+    pub status: Option<String>,
+}
+
+impl ForecastQuery {
+    fn options(&self) -> ForecastOptions {
+        let defaults = ForecastOptions::default();
+        ForecastOptions {
+            lookback_days: self.lookback_days.unwrap_or(defaults.lookback_days),
+            cover_days: self.cover_days.unwrap_or(defaults.cover_days),
+            dead_stock_days: self.dead_stock_days.unwrap_or(defaults.dead_stock_days),
+        }
+    }
+}
+
+/// Forecast for every stocked or recently sold product
+pub async fn get_forecasts(
+    State(state): State<AppState>,
+    Query(params): Query<ForecastQuery>,
+) -> impl IntoResponse {
+    let status = match params.status.as_deref() {
+        Some(s) => match StockStatus::parse(s) {
+            Some(status) => Some(status),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown stock status: {}", s)})),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
+    };
+    match state
+        .commerce
+        .replenishment
+        .get_forecasts(params.options())
+        .await
+    {
+        Ok(forecasts) => {
+            let forecasts: Vec<_> = forecasts
+                .into_iter()
+                .filter(|f| status.is_none_or(|s| f.status == s))
+                .collect();
+            (StatusCode::OK, Json(forecasts)).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Products that need ordering now, with suggested quantities
+pub async fn get_reorder_suggestions(
+    State(state): State<AppState>,
+    Query(params): Query<ForecastQuery>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .replenishment
+        .get_reorder_suggestions(params.options())
+        .await
+    {
+        Ok(suggestions) => (StatusCode::OK, Json(suggestions)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn get_product_forecast(
+    State(state): State<AppState>,
+    Path(product_uuid): Path<Uuid>,
+    Query(params): Query<ForecastQuery>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .replenishment
+        .forecast_product(product_uuid, params.options())
+        .await
+    {
+        Ok(forecasts) => (StatusCode::OK, Json(forecasts)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DeadStockQuery {
+    pub days: Option<i64>,
+}
+
+/// Stock that hasn't sold within the window, most money tied up first
+pub async fn get_dead_stock(
+    State(state): State<AppState>,
+    Query(params): Query<DeadStockQuery>,
+) -> impl IntoResponse {
+    let days = params
+        .days
+        .unwrap_or(ForecastOptions::default().dead_stock_days);
+    match state.commerce.replenishment.get_dead_stock(days).await {
+        Ok(items) => (StatusCode::OK, Json(items)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -354,3 +354,76 @@ pub async fn get_cash_flow_report(
             .into_response(),
     }
 }
+
+/// Shrink report (defaults to the last 30 days)
+pub async fn get_shrink_report(
+    State(state): State<AppState>,
+    Query(params): Query<ReportQuery>,
+) -> impl IntoResponse {
+    let now = chrono::Utc::now();
+    let days = match params.period.as_deref() {
+        Some("today") => 1,
+        Some("week") => 7,
+        Some("year") => 365,
+        _ => 30,
+    };
+
+    let start = params
+        .start_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| now - chrono::Duration::days(days));
+    let end = params
+        .end_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or(now);
+
+    match state.system.reporting.get_shrink_report(start, end).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Count accuracy KPIs for sessions approved in the period
+pub async fn get_count_accuracy_report(
+    State(state): State<AppState>,
+    Query(params): Query<ReportQuery>,
+) -> impl IntoResponse {
+    let now = chrono::Utc::now();
+    let days = match params.period.as_deref() {
+        Some("today") => 1,
+        Some("week") => 7,
+        Some("year") => 365,
+        _ => 30,
+    };
+
+    let start = params
+        .start_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| now - chrono::Duration::days(days));
+    let end = params
+        .end_date
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or(now);
+
+    match state
+        .commerce
+        .cycle_counts
+        .get_accuracy_report(start, end)
+        .await
+    {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -1,13 +1,17 @@
 //! Serialized inventory API handlers
 //!
-//! Handles unique/serialized item details, grading, and certificates.
+//! Handles unique/serialized item details, grading, certificates and
+//! cert verification with the grading companies.
 
 use crate::api::AppState;
+use crate::services::GradingCompany;
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Json, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
+    Extension,
 };
+use serde::Deserialize;
 use serde_json::json;
 use uuid::Uuid;
 
@@ -98,3 +102,89 @@ pub async fn add_certificate(
             .into_response(),
     }
 }
+
+/// Check an item's cert with its grader and pull in the official grade and
+/// population
+pub async fn verify_serialized_certificate(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(inventory_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .serialized
+        .verify_certificate(inventory_uuid, Uuid::parse_str(&user.user_uuid).ok())
+        .await
+    {
+        Ok(verification) => (StatusCode::OK, Json(verification)).into_response(),
+        Err(e) => {
+            let message = e.to_string();
+            let status = if message.contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+            (status, Json(json!({"error": message}))).into_response()
+        }
+    }
+}
+
+/// Look up a cert without an item, e.g. before buying a slab
+pub async fn lookup_grading_cert(
+    State(state): State<AppState>,
+    Path((grader, cert_number)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let Some(grader) = GradingCompany::parse(&grader) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("Unknown grader '{}'", grader)})),
+        )
+            .into_response();
+    };
+    match state
+        .system
+        .serialized
+        .lookup_cert(grader, &cert_number)
+        .await
+    {
+        Ok((_, Some(cert))) => (StatusCode::OK, Json(cert)).into_response(),
+        Ok((_, None)) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": format!("{} has no record of cert {}", grader, cert_number)})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CertVerificationQuery {
+    pub inventory_uuid: Option<Uuid>,
+    /// Only items whose latest check didn't verify
+    #[serde(default)]
+    pub flagged: bool,
+}
+
+/// Cert check history, or the possible fakes awaiting review
+pub async fn list_cert_verifications(
+    State(state): State<AppState>,
+    Query(query): Query<CertVerificationQuery>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .serialized
+        .get_cert_verifications(query.inventory_uuid, query.flagged)
+        .await
+    {
+        Ok(verifications) => (StatusCode::OK, Json(verifications)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,116 @@
+//! Shrinkage API handlers
+//!
+//! Damage/loss reports, disposition workflow and count-variance write-offs.
+
+use crate::api::AppState;
+use crate::services::{Disposition, ReportDamageRequest, ResolveDamageRequest};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct DamageQuery {
+    pub disposition: Option<String>,
+}
+
+/// List damage records, optionally by disposition (e.g. `Pending`)
+pub async fn get_damage_records(
+    State(state): State<AppState>,
+    Query(params): Query<DamageQuery>,
+) -> impl IntoResponse {
+    let disposition = match params.disposition.as_deref() {
+        Some(s) => match Disposition::parse(s) {
+            Some(d) => Some(d),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown disposition '{}'", s)})),
+                )
+                    .into_response()
+            }
+        },
+        None => None,
+    };
+
+    match state.commerce.shrinkage.get_records(disposition).await {
+        Ok(records) => (StatusCode::OK, Json(records)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Report damaged or missing stock
+pub async fn report_damage(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Json(req): Json<ReportDamageRequest>,
+) -> impl IntoResponse {
+    let reported_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .commerce
+        .shrinkage
+        .report_damage(req, reported_by)
+        .await
+    {
+        Ok(record) => (StatusCode::CREATED, Json(record)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Resolve a damage record with a disposition and recovered value
+pub async fn resolve_damage(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(damage_uuid): Path<Uuid>,
+    Json(req): Json<ResolveDamageRequest>,
+) -> impl IntoResponse {
+    let resolved_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .commerce
+        .shrinkage
+        .resolve(damage_uuid, req, resolved_by)
+        .await
+    {
+        Ok(record) => (StatusCode::OK, Json(record)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Write off a blind-count shortfall as missing stock
+pub async fn record_count_shrink(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(conflict_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    let reported_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state
+        .commerce
+        .shrinkage
+        .record_count_shortage(conflict_uuid, reported_by)
+        .await
+    {
+        Ok(records) => (StatusCode::CREATED, Json(records)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,191 @@
+//! Storage location API handlers
+//!
+//! Rooms, cases, shelves, rows and bins within a store, bin moves, pick
+//! lists and product lookup across every bin.
+
+use crate::api::AppState;
+use crate::services::{
+    CreateStorageRequest, MoveStockRequest, PickRequestLine, UpdateStorageRequest,
+};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct StorageTreeQuery {
+    pub location_tag: String,
+}
+
+#[derive(Deserialize)]
+pub struct BinMoveQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct PickListRequest {
+    pub location_tag: String,
+    /// What the list is for, e.g. an online order number
+    pub reference: Option<String>,
+    pub lines: Vec<PickRequestLine>,
+}
+
+/// A store's storage tree in walking order
+pub async fn get_storage_tree(
+    State(state): State<AppState>,
+    Query(params): Query<StorageTreeQuery>,
+) -> impl IntoResponse {
+    match state.system.storage.get_tree(&params.location_tag).await {
+        Ok(nodes) => (StatusCode::OK, Json(nodes)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn create_storage_location(
+    State(state): State<AppState>,
+    Json(req): Json<CreateStorageRequest>,
+) -> impl IntoResponse {
+    match state.system.storage.create_node(req).await {
+        Ok(node) => (StatusCode::CREATED, Json(node)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn get_storage_location(
+    State(state): State<AppState>,
+    Path(storage_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.storage.get_node(storage_uuid).await {
+        Ok(Some(node)) => (StatusCode::OK, Json(node)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Storage location not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Rename, resize, resequence or retire a storage location
+pub async fn update_storage_location(
+    State(state): State<AppState>,
+    Path(storage_uuid): Path<Uuid>,
+    Json(req): Json<UpdateStorageRequest>,
+) -> impl IntoResponse {
+    match state.system.storage.update_node(storage_uuid, req).await {
+        Ok(node) => (StatusCode::OK, Json(node)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Everything stored in a location and below it
+pub async fn get_storage_contents(
+    State(state): State<AppState>,
+    Path(storage_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.storage.get_contents(storage_uuid).await {
+        Ok(contents) => (StatusCode::OK, Json(contents)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// Move stock from one bin to another
+pub async fn move_stock(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Json(req): Json<MoveStockRequest>,
+) -> impl IntoResponse {
+    let moved_by = Uuid::parse_str(&user.user_uuid).ok();
+    match state.system.storage.move_stock(req, moved_by).await {
+        Ok(bin_move) => (StatusCode::CREATED, Json(bin_move)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn get_product_bin_moves(
+    State(state): State<AppState>,
+    Path(product_uuid): Path<Uuid>,
+    Query(params): Query<BinMoveQuery>,
+) -> impl IntoResponse {
+    match state
+        .system
+        .storage
+        .get_moves(product_uuid, params.limit.unwrap_or(50))
+        .await
+    {
+        Ok(moves) => (StatusCode::OK, Json(moves)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Where every copy of a product is, across stores and bins
+pub async fn locate_product(
+    State(state): State<AppState>,
+    Path(product_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.storage.locate_product(product_uuid).await {
+        Ok(whereabouts) => (StatusCode::OK, Json(whereabouts)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// Pick list for a hold, in walking order
+pub async fn get_hold_pick_list(
+    State(state): State<AppState>,
+    Path(hold_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.system.storage.pick_list_for_hold(hold_uuid).await {
+        Ok(list) => (StatusCode::OK, Json(list)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+/// Pick list for arbitrary lines, such as an online order
+pub async fn create_pick_list(
+    State(state): State<AppState>,
+    Json(req): Json<PickListRequest>,
+) -> impl IntoResponse {
+    let source = req.reference.unwrap_or_else(|| "adhoc".to_string());
+    match state
+        .system
+        .storage
+        .pick_list(&req.location_tag, source, req.lines)
+        .await
+    {
+        Ok(list) => (StatusCode::OK, Json(list)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
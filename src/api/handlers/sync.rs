@@ -57,6 +57,12 @@ pub async fn pull_sync_changes(
                         "PriceInfo" => crate::core::RecordType::PriceInfo,
                         "Transaction" => crate::core::RecordType::Transaction,
                         "Customer" => crate::core::RecordType::Customer,
+                        "WantsList" => crate::core::RecordType::WantsList,
+                        "Event" => crate::core::RecordType::Event,
+                        "EventParticipant" => crate::core::RecordType::EventParticipant,
+                        "InventoryTransfer" => crate::core::RecordType::InventoryTransfer,
+                        "LoyaltyEntry" => crate::core::RecordType::LoyaltyEntry,
+                        "Media" => crate::core::RecordType::Media,
                         _ => crate::core::RecordType::Product,
                     };
 
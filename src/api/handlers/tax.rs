@@ -1,15 +1,20 @@
 //! Tax-related API handlers
 //!
-//! Handles tax rate CRUD and default tax rate lookup.
+//! Handles tax rate CRUD and default tax rate lookup, stacked
+//! jurisdictions per location, exemption certificates and the
+//! tax liability report.
 
 use crate::api::AppState;
+use crate::services::{JurisdictionLevel, TaxExemptionCertificate, TaxJurisdiction};
 use axum::{
-    extract::{Json, State},
+    extract::{Json, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::json;
+use uuid::Uuid;
 
 /// Get all configured tax rates
 pub async fn get_tax_rates(State(state): State<AppState>) -> impl IntoResponse {
@@ -67,3 +72,261 @@ pub async fn get_default_tax_rate(State(state): State<AppState>) -> impl IntoRes
             .into_response(),
     }
 }
+
+/// Get all active tax jurisdictions
+pub async fn get_tax_jurisdictions(State(state): State<AppState>) -> impl IntoResponse {
+    match state.commerce.taxes.get_jurisdictions().await {
+        Ok(jurisdictions) => Json(jurisdictions).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateJurisdictionRequest {
+    pub name: String,
+    pub code: Option<String>,
+    pub level: JurisdictionLevel,
+    pub rate: f64,
+    pub is_compound: Option<bool>,
+    pub applies_to_category: Option<String>,
+}
+
+/// Create a taxing jurisdiction
+pub async fn create_tax_jurisdiction(
+    State(state): State<AppState>,
+    Json(req): Json<CreateJurisdictionRequest>,
+) -> impl IntoResponse {
+    let jurisdiction = TaxJurisdiction {
+        jurisdiction_uuid: Uuid::new_v4(),
+        name: req.name,
+        code: req.code,
+        level: req.level,
+        rate: req.rate,
+        is_compound: req.is_compound.unwrap_or(false),
+        applies_to_category: req.applies_to_category,
+        is_active: true,
+        created_at: Utc::now(),
+    };
+
+    match state
+        .commerce
+        .taxes
+        .create_jurisdiction(&jurisdiction)
+        .await
+    {
+        Ok(_) => (StatusCode::CREATED, Json(jurisdiction)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Get the jurisdictions applied at a store location, in compounding order
+pub async fn get_location_tax_jurisdictions(
+    State(state): State<AppState>,
+    Path(location_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    let taxes = &state.commerce.taxes;
+    let jurisdictions = match taxes.get_location_jurisdictions(location_uuid).await {
+        Ok(j) => j,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
+    };
+
+    match taxes.location_prices_include_tax(location_uuid).await {
+        Ok(prices_include_tax) => Json(json!({
+            "location_uuid": location_uuid,
+            "prices_include_tax": prices_include_tax,
+            "jurisdictions": jurisdictions,
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LocationJurisdictionEntry {
+    pub jurisdiction_uuid: Uuid,
+    pub sequence: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct ConfigureLocationTaxRequest {
+    pub jurisdictions: Option<Vec<LocationJurisdictionEntry>>,
+    pub prices_include_tax: Option<bool>,
+}
+
+/// Assign jurisdictions and/or tax-inclusive pricing to a store location
+pub async fn configure_location_tax(
+    State(state): State<AppState>,
+    Path(location_uuid): Path<Uuid>,
+    Json(req): Json<ConfigureLocationTaxRequest>,
+) -> impl IntoResponse {
+    let taxes = &state.commerce.taxes;
+
+    for (idx, entry) in req.jurisdictions.unwrap_or_default().iter().enumerate() {
+        let sequence = entry.sequence.unwrap_or(idx as i32);
+        if let Err(e) = taxes
+            .assign_jurisdiction_to_location(location_uuid, entry.jurisdiction_uuid, sequence)
+            .await
+        {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response();
+        }
+    }
+
+    if let Some(prices_include_tax) = req.prices_include_tax {
+        if let Err(e) = taxes
+            .set_location_prices_include_tax(location_uuid, prices_include_tax)
+            .await
+        {
+            return (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response();
+        }
+    }
+
+    match taxes.get_location_jurisdictions(location_uuid).await {
+        Ok(jurisdictions) => Json(jurisdictions).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Get a customer's exemption certificates
+pub async fn get_customer_tax_exemptions(
+    State(state): State<AppState>,
+    Path(customer_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .taxes
+        .get_exemption_certificates(customer_uuid)
+        .await
+    {
+        Ok(certificates) => Json(certificates).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AddExemptionCertificateRequest {
+    pub certificate_number: String,
+    pub exemption_type: String,
+    pub jurisdiction_uuid: Option<Uuid>,
+    pub effective_date: Option<DateTime<Utc>>,
+    pub expiry_date: Option<DateTime<Utc>>,
+    pub document_ref: Option<String>,
+}
+
+/// Record an exemption certificate for a customer
+pub async fn add_customer_tax_exemption(
+    State(state): State<AppState>,
+    Path(customer_uuid): Path<Uuid>,
+    Json(req): Json<AddExemptionCertificateRequest>,
+) -> impl IntoResponse {
+    let now = Utc::now();
+    let certificate = TaxExemptionCertificate {
+        certificate_uuid: Uuid::new_v4(),
+        customer_uuid,
+        certificate_number: req.certificate_number,
+        exemption_type: req.exemption_type,
+        jurisdiction_uuid: req.jurisdiction_uuid,
+        effective_date: req.effective_date.unwrap_or(now),
+        expiry_date: req.expiry_date,
+        document_ref: req.document_ref,
+        created_at: now,
+        revoked_at: None,
+    };
+
+    match state
+        .commerce
+        .taxes
+        .add_exemption_certificate(&certificate)
+        .await
+    {
+        Ok(_) => (StatusCode::CREATED, Json(certificate)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Revoke an exemption certificate
+pub async fn revoke_tax_exemption(
+    State(state): State<AppState>,
+    Path(certificate_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .taxes
+        .revoke_exemption_certificate(certificate_uuid)
+        .await
+    {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TaxLiabilityQuery {
+    pub start_date: String,
+    pub end_date: String,
+}
+
+/// Get tax collected by jurisdiction for a filing period
+pub async fn get_tax_liability_report(
+    State(state): State<AppState>,
+    Query(params): Query<TaxLiabilityQuery>,
+) -> impl IntoResponse {
+    let start = chrono::DateTime::parse_from_rfc3339(&params.start_date)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now() - chrono::Duration::days(30));
+
+    let end = chrono::DateTime::parse_from_rfc3339(&params.end_date)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+
+    match state
+        .commerce
+        .taxes
+        .get_tax_liability_report(start, end)
+        .await
+    {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
@@ -110,3 +110,211 @@ pub async fn get_transaction_by_id(
             .into_response(),
     }
 }
+
+/// Validate a POS cart (stock, limits, tax, payments) without recording it.
+/// When `terminal_id` is set the cart is mirrored to that customer display.
+pub async fn validate_checkout(
+    State(state): State<AppState>,
+    Json(req): Json<crate::services::TransactionRequest>,
+) -> impl IntoResponse {
+    match state.commerce.checkout.validate_transaction(&req).await {
+        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Validate and record a POS sale with split payments
+pub async fn complete_checkout(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Json(req): Json<crate::services::TransactionRequest>,
+) -> impl IntoResponse {
+    let user_uuid = Uuid::parse_str(&user.user_uuid).ok();
+
+    match state
+        .commerce
+        .checkout
+        .process_transaction(&req, user_uuid)
+        .await
+    {
+        Ok(result) if result.success => (StatusCode::CREATED, Json(result)).into_response(),
+        Ok(result) => (StatusCode::UNPROCESSABLE_ENTITY, Json(result)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Edit history of a transaction
+pub async fn get_transaction_adjustments(
+    State(state): State<AppState>,
+    Path(transaction_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .adjustments
+        .get_adjustments(transaction_uuid)
+        .await
+    {
+        Ok(adjustments) => (StatusCode::OK, Json(adjustments)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RetenderRequest {
+    pub payment_uuid: Uuid,
+    pub method: crate::services::PaymentMethodType,
+    pub reason: String,
+}
+
+/// Change the tender type of a recorded payment
+pub async fn retender_transaction(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(transaction_uuid): Path<Uuid>,
+    Json(req): Json<RetenderRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .adjustments
+        .retender(
+            transaction_uuid,
+            req.payment_uuid,
+            req.method,
+            &req.reason,
+            Uuid::parse_str(&user.user_uuid).ok(),
+        )
+        .await
+    {
+        Ok(adjustment) => (StatusCode::CREATED, Json(adjustment)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AttachCustomerRequest {
+    pub customer_uuid: Uuid,
+    pub reason: String,
+}
+
+/// Attach a customer to a completed sale
+pub async fn attach_transaction_customer(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(transaction_uuid): Path<Uuid>,
+    Json(req): Json<AttachCustomerRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .adjustments
+        .attach_customer(
+            transaction_uuid,
+            req.customer_uuid,
+            &req.reason,
+            Uuid::parse_str(&user.user_uuid).ok(),
+        )
+        .await
+    {
+        Ok(adjustment) => (StatusCode::CREATED, Json(adjustment)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PriceCorrectionRequest {
+    pub item_uuid: Uuid,
+    pub corrected_unit_price: f64,
+    pub reason: String,
+}
+
+/// Correct a mistyped price on a completed sale. Mounted on the manager
+/// routes; the authenticated manager is recorded as the approver.
+pub async fn correct_transaction_price(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(transaction_uuid): Path<Uuid>,
+    Json(req): Json<PriceCorrectionRequest>,
+) -> impl IntoResponse {
+    let Ok(approved_by) = Uuid::parse_str(&user.user_uuid) else {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": "Price corrections require manager approval"})),
+        )
+            .into_response();
+    };
+
+    match state
+        .commerce
+        .adjustments
+        .correct_price(
+            transaction_uuid,
+            req.item_uuid,
+            req.corrected_unit_price,
+            &req.reason,
+            Some(approved_by),
+            approved_by,
+        )
+        .await
+    {
+        Ok(adjustment) => (StatusCode::CREATED, Json(adjustment)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LineVoidRequest {
+    pub item_uuid: Uuid,
+    pub quantity: i32,
+    pub reason: String,
+}
+
+/// Void part of a line on a completed sale
+pub async fn void_transaction_line(
+    State(state): State<AppState>,
+    Extension(user): Extension<crate::api::middleware::AuthenticatedUser>,
+    Path(transaction_uuid): Path<Uuid>,
+    Json(req): Json<LineVoidRequest>,
+) -> impl IntoResponse {
+    match state
+        .commerce
+        .adjustments
+        .void_line(
+            transaction_uuid,
+            req.item_uuid,
+            req.quantity,
+            &req.reason,
+            Uuid::parse_str(&user.user_uuid).ok(),
+        )
+        .await
+    {
+        Ok(adjustment) => (StatusCode::CREATED, Json(adjustment)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
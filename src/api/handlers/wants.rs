@@ -1,11 +1,13 @@
 //! Wants list API handlers
 //!
-//! Handles customer wants list creation, listing, and inventory updates.
+//! Handles customer wants list creation, listing, match follow-up and
+//! inventory updates.
 
 use crate::api::AppState;
+use crate::buylist::matcher::{WantsMatchStatus, WantsMatchingService};
 use crate::core::InventoryItem;
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Json, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
@@ -57,6 +59,91 @@ pub async fn get_wants_lists(
     }
 }
 
+#[derive(Deserialize)]
+pub struct WantsMatchQuery {
+    pub customer_uuid: Option<Uuid>,
+    pub status: Option<String>,
+}
+
+/// Wants matches, newest first, filtered by customer and/or status
+pub async fn list_wants_matches(
+    State(state): State<AppState>,
+    Query(query): Query<WantsMatchQuery>,
+) -> impl IntoResponse {
+    let status = query.status.as_deref().map(WantsMatchStatus::parse);
+    match WantsMatchingService::new(state.db.clone())
+        .list_matches(query.customer_uuid, status)
+        .await
+    {
+        Ok(matches) => (StatusCode::OK, Json(matches)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Stock offered to a customer against their wants
+pub async fn get_customer_wants_matches(
+    State(state): State<AppState>,
+    Path(customer_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match WantsMatchingService::new(state.db.clone())
+        .list_matches(Some(customer_uuid), None)
+        .await
+    {
+        Ok(matches) => (StatusCode::OK, Json(matches)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct FulfillWantsMatchRequest {
+    pub transaction_uuid: Option<Uuid>,
+}
+
+/// Mark an un-held match as bought
+pub async fn fulfill_wants_match(
+    State(state): State<AppState>,
+    Path(match_uuid): Path<Uuid>,
+    Json(req): Json<FulfillWantsMatchRequest>,
+) -> impl IntoResponse {
+    match WantsMatchingService::new(state.db.clone())
+        .fulfill_match(match_uuid, req.transaction_uuid)
+        .await
+    {
+        Ok(wants_match) => (StatusCode::OK, Json(wants_match)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// The customer passed on a match; reserved stock is released
+pub async fn decline_wants_match(
+    State(state): State<AppState>,
+    Path(match_uuid): Path<Uuid>,
+) -> impl IntoResponse {
+    match WantsMatchingService::new(state.db.clone())
+        .decline_match(match_uuid)
+        .await
+    {
+        Ok(wants_match) => (StatusCode::OK, Json(wants_match)).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
 /// Update an inventory item
 pub async fn update_inventory_item(
     State(state): State<AppState>,
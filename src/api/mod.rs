@@ -87,6 +87,228 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             post(handlers::invalidate_price_cache),
         )
         .route("/api/sync/trigger", post(handlers::trigger_peer_sync))
+        // Tax jurisdictions and exemption certificates
+        .route(
+            "/api/tax/jurisdictions",
+            post(handlers::create_tax_jurisdiction),
+        )
+        .route(
+            "/api/locations/:location_uuid/tax",
+            post(handlers::configure_location_tax),
+        )
+        .route(
+            "/api/customers/:customer_uuid/tax-exemptions",
+            post(handlers::add_customer_tax_exemption),
+        )
+        .route(
+            "/api/tax/exemptions/:certificate_uuid/revoke",
+            post(handlers::revoke_tax_exemption),
+        )
+        // Currency configuration
+        .route(
+            "/api/currency/base",
+            axum::routing::put(handlers::set_base_currency),
+        )
+        .route("/api/currency/rates", post(handlers::set_exchange_rate))
+        .route(
+            "/api/currency/rates/:code",
+            axum::routing::delete(handlers::delete_exchange_rate),
+        )
+        // Customer display promotions
+        .route(
+            "/api/display/promotions",
+            post(handlers::create_display_promotion),
+        )
+        .route(
+            "/api/display/promotions/:promotion_uuid",
+            axum::routing::delete(handlers::deactivate_display_promotion),
+        )
+        // Consignment expiry and payouts
+        .route(
+            "/api/consignment/expire",
+            post(handlers::expire_consignments),
+        )
+        .route(
+            "/api/consignors/:consignor_uuid/payouts",
+            post(handlers::pay_consignor),
+        )
+        // Post-sale price corrections; the approving manager is the caller
+        .route(
+            "/api/transactions/:transaction_uuid/adjustments/price",
+            post(handlers::correct_transaction_price),
+        )
+        // Event prize payouts
+        .route(
+            "/api/events/:event_uuid/prizes/payouts",
+            post(handlers::pay_out_prizes),
+        )
+        // Layaway plan configuration
+        .route("/api/layaway/plans", post(handlers::create_layaway_plan))
+        // Loyalty program configuration and corrections
+        .route(
+            "/api/loyalty/settings",
+            axum::routing::put(handlers::update_loyalty_settings),
+        )
+        .route(
+            "/api/loyalty/rates",
+            axum::routing::put(handlers::set_loyalty_category_rates),
+        )
+        .route(
+            "/api/loyalty/tiers",
+            axum::routing::put(handlers::set_loyalty_tiers),
+        )
+        .route(
+            "/api/loyalty/tiers/evaluate",
+            post(handlers::evaluate_loyalty_tiers),
+        )
+        .route("/api/loyalty/expire", post(handlers::expire_loyalty_points))
+        .route(
+            "/api/customers/:customer_uuid/loyalty/adjust",
+            post(handlers::adjust_loyalty_points),
+        )
+        // Customer merge and privacy requests
+        .route(
+            "/api/customers/:customer_uuid/merge",
+            post(handlers::merge_customers),
+        )
+        .route(
+            "/api/customers/:customer_uuid/export",
+            get(handlers::export_customer_data),
+        )
+        .route(
+            "/api/customers/:customer_uuid/anonymize",
+            post(handlers::anonymize_customer),
+        )
+        // Customer messaging: templates and delivery log
+        .route(
+            "/api/notifications/templates",
+            get(handlers::list_notification_templates),
+        )
+        .route(
+            "/api/notifications/templates/:template_key/:channel",
+            axum::routing::put(handlers::update_notification_template)
+                .delete(handlers::reset_notification_template),
+        )
+        .route(
+            "/api/notifications/log",
+            get(handlers::get_notification_log),
+        )
+        .route(
+            "/api/notifications/retry",
+            post(handlers::retry_notifications),
+        )
+        .route(
+            "/api/notifications/:notification_uuid/bounce",
+            post(handlers::record_notification_bounce),
+        )
+        // Media
+        .route(
+            "/api/media/:media_uuid",
+            axum::routing::delete(handlers::delete_media),
+        )
+        // Grading submissions
+        .route(
+            "/api/grading/submissions",
+            post(handlers::create_grading_submission),
+        )
+        .route(
+            "/api/grading/submissions/:submission_uuid/cancel",
+            post(handlers::cancel_grading_submission),
+        )
+        .route(
+            "/api/grading/submissions/:submission_uuid/results",
+            post(handlers::record_grading_results),
+        )
+        .route(
+            "/api/reports/grading-roi",
+            get(handlers::get_grading_roi_report),
+        )
+        .route(
+            "/api/grading/cert-verifications",
+            get(handlers::list_cert_verifications),
+        )
+        // Suppliers and purchase orders
+        .route("/api/suppliers", post(handlers::create_supplier))
+        .route(
+            "/api/purchase-orders",
+            post(handlers::create_purchase_order),
+        )
+        .route(
+            "/api/purchase-orders/suggestions",
+            post(handlers::create_suggested_purchase_orders),
+        )
+        .route(
+            "/api/purchase-orders/:po_uuid/submit",
+            post(handlers::submit_purchase_order),
+        )
+        .route(
+            "/api/purchase-orders/:po_uuid/cancel",
+            post(handlers::cancel_purchase_order),
+        )
+        .route(
+            "/api/purchase-orders/:po_uuid/close",
+            post(handlers::close_purchase_order),
+        )
+        // Shrinkage dispositions and count write-offs
+        .route(
+            "/api/inventory/damage/:damage_uuid/resolve",
+            post(handlers::resolve_damage),
+        )
+        .route(
+            "/api/audit/conflicts/:conflict_uuid/shrink",
+            post(handlers::record_count_shrink),
+        )
+        // Storage layout within a store
+        .route("/api/storage", post(handlers::create_storage_location))
+        .route(
+            "/api/storage/:storage_uuid",
+            axum::routing::put(handlers::update_storage_location),
+        )
+        // File import/export with mapping profiles
+        .route("/api/inventory/import", post(handlers::import_inventory))
+        .route("/api/inventory/export", get(handlers::export_inventory))
+        .route(
+            "/api/inventory/import-profiles",
+            get(handlers::get_import_profiles).post(handlers::save_import_profile),
+        )
+        // Bills of materials for sealed product and kits
+        .route(
+            "/api/products/:product_uuid/bom",
+            axum::routing::put(handlers::set_product_bom).delete(handlers::delete_product_bom),
+        )
+        // Cycle count plans, sessions and approval
+        .route(
+            "/api/cycle-counts/plans",
+            get(handlers::get_count_plans).post(handlers::create_count_plan),
+        )
+        .route(
+            "/api/cycle-counts/plans/:plan_uuid/run",
+            post(handlers::run_count_plan),
+        )
+        .route(
+            "/api/cycle-counts/classes",
+            get(handlers::get_abc_classes).post(handlers::classify_products),
+        )
+        .route(
+            "/api/cycle-counts/sessions",
+            get(handlers::get_count_sessions).post(handlers::create_count_session),
+        )
+        .route(
+            "/api/cycle-counts/sessions/:session_uuid",
+            get(handlers::get_count_session),
+        )
+        .route(
+            "/api/cycle-counts/sessions/:session_uuid/approve",
+            post(handlers::approve_count_session),
+        )
+        .route(
+            "/api/cycle-counts/sessions/:session_uuid/cancel",
+            post(handlers::cancel_count_session),
+        )
+        .route(
+            "/api/reports/count-accuracy",
+            get(handlers::get_count_accuracy_report),
+        )
         // Backup routes (Phase 11)
         .route("/api/admin/backup", post(handlers::create_backup))
         .route("/api/admin/backups", get(handlers::list_backups))
@@ -146,6 +368,63 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
                 .delete(handlers::delete_inventory_item)
                 .put(handlers::update_inventory_item),
         )
+        .route(
+            "/api/inventory/:inventory_uuid/stock-card",
+            get(handlers::get_stock_card),
+        )
+        .route(
+            "/api/inventory/:inventory_uuid/quantity-as-of",
+            get(handlers::get_inventory_quantity_as_of),
+        )
+        .route(
+            "/api/products/:product_uuid/quantity-as-of",
+            get(handlers::get_product_quantity_as_of),
+        )
+        // Breaking and kitting
+        .route(
+            "/api/products/:product_uuid/bom",
+            get(handlers::get_product_bom),
+        )
+        .route(
+            "/api/products/:product_uuid/assemble",
+            post(handlers::assemble_product),
+        )
+        .route(
+            "/api/products/:product_uuid/buildable",
+            get(handlers::get_kit_buildable),
+        )
+        .route(
+            "/api/inventory/:inventory_uuid/break",
+            post(handlers::break_inventory),
+        )
+        .route(
+            "/api/inventory/conversions",
+            get(handlers::get_inventory_conversions),
+        )
+        // Bins, bin moves, pick lists and product lookup
+        .route("/api/storage", get(handlers::get_storage_tree))
+        .route(
+            "/api/storage/:storage_uuid",
+            get(handlers::get_storage_location),
+        )
+        .route(
+            "/api/storage/:storage_uuid/contents",
+            get(handlers::get_storage_contents),
+        )
+        .route("/api/storage/moves", post(handlers::move_stock))
+        .route(
+            "/api/products/:product_uuid/locations",
+            get(handlers::locate_product),
+        )
+        .route(
+            "/api/products/:product_uuid/bin-moves",
+            get(handlers::get_product_bin_moves),
+        )
+        .route(
+            "/api/holds/:hold_uuid/pick-list",
+            get(handlers::get_hold_pick_list),
+        )
+        .route("/api/pick-lists", post(handlers::create_pick_list))
         .route(
             "/api/inventory/label/:inventory_uuid",
             get(handlers::get_inventory_label),
@@ -185,6 +464,109 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             "/api/transactions/:transaction_uuid/invoice",
             get(handlers::generate_invoice),
         )
+        // Post-sale adjustments
+        .route(
+            "/api/transactions/:transaction_uuid/adjustments",
+            get(handlers::get_transaction_adjustments),
+        )
+        .route(
+            "/api/transactions/:transaction_uuid/adjustments/retender",
+            post(handlers::retender_transaction),
+        )
+        .route(
+            "/api/transactions/:transaction_uuid/adjustments/customer",
+            post(handlers::attach_transaction_customer),
+        )
+        .route(
+            "/api/transactions/:transaction_uuid/adjustments/line-void",
+            post(handlers::void_transaction_line),
+        )
+        // POS checkout
+        .route("/api/checkout/validate", post(handlers::validate_checkout))
+        .route("/api/checkout", post(handlers::complete_checkout))
+        // Customer-facing displays
+        .route(
+            "/api/display/terminals/:terminal_id",
+            get(handlers::get_customer_display_state),
+        )
+        .route(
+            "/api/display/terminals/:terminal_id/stream",
+            get(handlers::stream_customer_display),
+        )
+        .route(
+            "/api/display/terminals/:terminal_id/payment-prompt",
+            post(handlers::show_payment_prompt),
+        )
+        .route(
+            "/api/display/terminals/:terminal_id/reset",
+            post(handlers::reset_customer_display),
+        )
+        .route(
+            "/api/display/promotions",
+            get(handlers::get_display_promotions),
+        )
+        // Purchasing and receiving
+        .route("/api/suppliers", get(handlers::get_suppliers))
+        .route("/api/purchase-orders", get(handlers::get_purchase_orders))
+        .route(
+            "/api/purchase-orders/suggestions",
+            get(handlers::get_purchase_suggestions),
+        )
+        .route(
+            "/api/purchase-orders/backorders",
+            get(handlers::get_backorders),
+        )
+        // Replenishment forecasting
+        .route("/api/replenishment/forecast", get(handlers::get_forecasts))
+        .route(
+            "/api/replenishment/suggestions",
+            get(handlers::get_reorder_suggestions),
+        )
+        .route(
+            "/api/replenishment/dead-stock",
+            get(handlers::get_dead_stock),
+        )
+        .route(
+            "/api/products/:product_uuid/forecast",
+            get(handlers::get_product_forecast),
+        )
+        .route(
+            "/api/purchase-orders/:po_uuid",
+            get(handlers::get_purchase_order),
+        )
+        .route(
+            "/api/purchase-orders/:po_uuid/receive",
+            post(handlers::receive_purchase_order),
+        )
+        .route(
+            "/api/purchase-orders/:po_uuid/labels",
+            get(handlers::get_purchase_order_labels),
+        )
+        // Consignment
+        .route(
+            "/api/consignors",
+            get(handlers::get_consignors).post(handlers::create_consignor),
+        )
+        .route(
+            "/api/consignors/:consignor_uuid",
+            get(handlers::get_consignor),
+        )
+        .route(
+            "/api/consignors/:consignor_uuid/items",
+            get(handlers::get_consignor_items),
+        )
+        .route(
+            "/api/consignors/:consignor_uuid/statement",
+            get(handlers::get_consignor_statement),
+        )
+        .route(
+            "/api/consignment/intake",
+            post(handlers::intake_consignment),
+        )
+        .route(
+            "/api/consignment/items/:consignment_uuid/return",
+            post(handlers::return_consignment),
+        )
         // Customers
         .route(
             "/api/customers",
@@ -195,10 +577,33 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             get(handlers::get_customer_history),
         )
         .route("/api/customers/credit", post(handlers::update_store_credit))
+        .route(
+            "/api/customers/duplicates",
+            get(handlers::find_duplicate_customers),
+        )
         .route(
             "/api/customers/:customer_uuid",
-            get(handlers::get_customer_by_id),
+            get(handlers::get_customer_by_id).put(handlers::update_customer),
+        )
+        .route(
+            "/api/customers/:customer_uuid/duplicates",
+            get(handlers::get_customer_duplicates),
+        )
+        // Loyalty
+        .route(
+            "/api/customers/:customer_uuid/loyalty",
+            get(handlers::get_customer_loyalty),
+        )
+        .route(
+            "/api/customers/:customer_uuid/loyalty/history",
+            get(handlers::get_customer_loyalty_history),
+        )
+        .route("/api/loyalty/settings", get(handlers::get_loyalty_settings))
+        .route(
+            "/api/loyalty/rates",
+            get(handlers::get_loyalty_category_rates),
         )
+        .route("/api/loyalty/tiers", get(handlers::get_loyalty_tiers))
         // Buylist
         .route("/api/buylist/quote", post(handlers::get_buylist_quote))
         .route("/api/buylist/process", post(handlers::process_buylist))
@@ -221,6 +626,31 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
         )
         // Sync Progress (TASK-125)
         .route("/api/sync/progress", get(handlers::get_sync_progress))
+        // Media: uploads, cached serving and peer replication
+        .route(
+            "/api/media",
+            get(handlers::list_media)
+                .post(handlers::upload_media)
+                .layer(axum::extract::DefaultBodyLimit::max(
+                    crate::services::media::MAX_UPLOAD_BYTES + 64 * 1024,
+                )),
+        )
+        .route(
+            "/api/media/:media_uuid",
+            get(handlers::get_media).put(handlers::update_media),
+        )
+        .route(
+            "/api/media/:media_uuid/content",
+            get(handlers::get_media_content),
+        )
+        .route(
+            "/api/media/:media_uuid/thumbnail",
+            get(handlers::get_media_thumbnail),
+        )
+        .route(
+            "/api/media/blobs/:content_hash",
+            get(handlers::get_media_blob),
+        )
         // Reports
         .route("/api/reports/sales", get(handlers::get_sales_report))
         .route(
@@ -243,12 +673,110 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             "/api/events/:event_uuid/register",
             post(handlers::register_participant),
         )
+        .route(
+            "/api/events/:event_uuid/tournament/start",
+            post(handlers::start_tournament),
+        )
+        .route(
+            "/api/events/:event_uuid/rounds",
+            get(handlers::get_rounds).post(handlers::pair_next_round),
+        )
+        .route(
+            "/api/events/:event_uuid/rounds/:round_number",
+            get(handlers::get_round),
+        )
+        .route(
+            "/api/events/:event_uuid/rounds/:round_number/start",
+            post(handlers::start_round),
+        )
+        .route(
+            "/api/events/:event_uuid/rounds/:round_number/print",
+            post(handlers::print_pairings),
+        )
+        .route(
+            "/api/events/:event_uuid/participants/:participant_uuid/drop",
+            post(handlers::drop_participant),
+        )
+        .route(
+            "/api/events/:event_uuid/standings",
+            get(handlers::get_standings),
+        )
+        .route(
+            "/api/events/:event_uuid/standings/print",
+            post(handlers::print_standings),
+        )
+        .route(
+            "/api/events/matches/:match_uuid/result",
+            axum::routing::put(handlers::report_match_result),
+        )
+        .route(
+            "/api/events/participants/:participant_uuid/payment",
+            post(handlers::record_cash_payment),
+        )
+        .route(
+            "/api/events/:event_uuid/prizes",
+            get(handlers::get_prize_structure).put(handlers::set_prize_structure),
+        )
+        .route(
+            "/api/events/:event_uuid/prizes/payouts",
+            get(handlers::get_prize_payouts),
+        )
+        .route("/api/events/:event_uuid/pnl", get(handlers::get_event_pnl))
+        .route(
+            "/api/events/attendance",
+            get(handlers::get_event_attendance),
+        )
+        .route(
+            "/api/events/:event_uuid/participants",
+            get(handlers::get_event_participants),
+        )
+        .route(
+            "/api/events/:event_uuid/preregister",
+            post(handlers::pre_register),
+        )
+        .route(
+            "/api/events/:event_uuid/no-shows",
+            post(handlers::mark_no_shows),
+        )
+        .route(
+            "/api/events/participants/:participant_uuid/check-in",
+            post(handlers::check_in_participant),
+        )
+        .route(
+            "/api/events/participants/:participant_uuid/cancel",
+            post(handlers::cancel_registration),
+        )
+        .route(
+            "/api/events/templates",
+            get(handlers::get_event_templates).post(handlers::create_event_template),
+        )
+        .route(
+            "/api/events/templates/:template_uuid",
+            axum::routing::put(handlers::update_event_template),
+        )
+        .route(
+            "/api/events/templates/:template_uuid/spawn",
+            post(handlers::spawn_event_instances),
+        )
         // Wants
         .route("/api/wants", post(handlers::create_wants_list))
         .route(
             "/api/customers/:customer_uuid/wants",
             get(handlers::get_wants_lists),
         )
+        .route(
+            "/api/customers/:customer_uuid/wants/matches",
+            get(handlers::get_customer_wants_matches),
+        )
+        .route("/api/wants/matches", get(handlers::list_wants_matches))
+        .route(
+            "/api/wants/matches/:match_uuid/fulfill",
+            post(handlers::fulfill_wants_match),
+        )
+        .route(
+            "/api/wants/matches/:match_uuid/decline",
+            post(handlers::decline_wants_match),
+        )
         // Audit
         .route("/api/audit/conflicts", get(handlers::get_conflicts))
         .route(
@@ -259,12 +787,49 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             "/api/audit/conflicts/:conflict_uuid/resolve",
             post(handlers::resolve_conflict),
         )
+        // Damage and shrinkage
+        .route(
+            "/api/inventory/damage",
+            get(handlers::get_damage_records).post(handlers::report_damage),
+        )
+        // Cycle counting
+        .route(
+            "/api/cycle-counts/sessions/:session_uuid/sheet",
+            get(handlers::get_count_sheet),
+        )
+        .route(
+            "/api/cycle-counts/sessions/:session_uuid/counts",
+            post(handlers::record_counts),
+        )
+        .route(
+            "/api/cycle-counts/sessions/:session_uuid/finish-round",
+            post(handlers::finish_count_round),
+        )
+        // Currency
+        .route("/api/currency", get(handlers::get_currency_settings))
+        .route("/api/currency/convert", get(handlers::convert_currency))
         // Tax Rates
         .route(
             "/api/tax/rates",
             get(handlers::get_tax_rates).post(handlers::create_tax_rate),
         )
         .route("/api/tax/default-rate", get(handlers::get_default_tax_rate))
+        .route(
+            "/api/tax/jurisdictions",
+            get(handlers::get_tax_jurisdictions),
+        )
+        .route(
+            "/api/locations/:location_uuid/tax",
+            get(handlers::get_location_tax_jurisdictions),
+        )
+        .route(
+            "/api/customers/:customer_uuid/tax-exemptions",
+            get(handlers::get_customer_tax_exemptions),
+        )
+        .route(
+            "/api/reports/tax-liability",
+            get(handlers::get_tax_liability_report),
+        )
         // Holds/Layaway
         .route("/api/holds", post(handlers::create_hold))
         .route("/api/holds/:hold_uuid", get(handlers::get_hold))
@@ -281,6 +846,19 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             "/api/holds/expire-overdue",
             post(handlers::expire_overdue_holds),
         )
+        .route(
+            "/api/holds/:hold_uuid/installments",
+            get(handlers::get_hold_installments),
+        )
+        .route(
+            "/api/holds/assess-late-fees",
+            post(handlers::assess_late_fees),
+        )
+        .route("/api/layaway/plans", get(handlers::get_layaway_plans))
+        .route(
+            "/api/reports/layaway-liability",
+            get(handlers::get_layaway_liability_report),
+        )
         .route(
             "/api/customers/:customer_uuid/holds",
             get(handlers::get_customer_holds),
@@ -326,6 +904,27 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             "/api/inventory/serialized/:inventory_uuid/certificate",
             post(handlers::add_certificate),
         )
+        .route(
+            "/api/inventory/serialized/:inventory_uuid/verify",
+            post(handlers::verify_serialized_certificate),
+        )
+        .route(
+            "/api/grading/certs/:grader/:cert_number",
+            get(handlers::lookup_grading_cert),
+        )
+        // Grading submissions
+        .route(
+            "/api/grading/submissions",
+            get(handlers::list_grading_submissions),
+        )
+        .route(
+            "/api/grading/submissions/:submission_uuid",
+            get(handlers::get_grading_submission),
+        )
+        .route(
+            "/api/grading/submissions/:submission_uuid/status",
+            post(handlers::update_grading_submission_status),
+        )
         // Trade-In Protection (Phase 7: TASK-162 to TASK-167)
         .route(
             "/api/trade-in/check",
@@ -344,11 +943,35 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             "/api/locations",
             get(handlers::get_locations).post(handlers::upsert_location),
         )
-        .route("/api/transfers", post(handlers::create_transfer))
+        .route(
+            "/api/transfers",
+            get(handlers::get_transfers).post(handlers::create_transfer),
+        )
+        .route("/api/transfers/:transfer_uuid", get(handlers::get_transfer))
         .route(
             "/api/transfers/:transfer_uuid/status",
             axum::routing::put(handlers::update_transfer_status),
         )
+        .route(
+            "/api/transfers/:transfer_uuid/ship",
+            post(handlers::ship_transfer),
+        )
+        .route(
+            "/api/transfers/:transfer_uuid/receive",
+            post(handlers::receive_transfer),
+        )
+        .route(
+            "/api/transfers/:transfer_uuid/close",
+            post(handlers::close_transfer),
+        )
+        .route(
+            "/api/transfers/:transfer_uuid/packing-slip",
+            get(handlers::get_transfer_packing_slip),
+        )
+        .route(
+            "/api/transfers/:transfer_uuid/pick-list",
+            get(handlers::get_transfer_pick_list),
+        )
         // Reporting (Phase 8)
         .route(
             "/api/reports/sales/csv",
@@ -362,6 +985,7 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             "/api/reports/inventory-aging",
             get(handlers::get_inventory_aging_report),
         )
+        .route("/api/reports/shrink", get(handlers::get_shrink_report))
         // Notifications (Phase 9)
         .route(
             "/api/transactions/:transaction_uuid/email-receipt",
@@ -371,6 +995,11 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
             "/api/customers/:customer_uuid/notify",
             post(handlers::notify_customer),
         )
+        .route(
+            "/api/customers/:customer_uuid/notification-preferences",
+            get(handlers::get_notification_preferences)
+                .put(handlers::update_notification_preferences),
+        )
         // Quote Email (Task 196)
         .route(
             "/api/buylist/quote/email",
@@ -436,6 +1065,11 @@ pub fn create_router(state: AppState, config: &crate::config::Config) -> Router
         .route("/health/alerts", get(handlers::get_alerts))
         .route("/metrics", get(handlers::metrics_prometheus))
         .route("/metrics/json", get(handlers::metrics_json))
+        // Unsubscribe links in customer messages
+        .route(
+            "/api/notifications/unsubscribe/:token",
+            get(handlers::unsubscribe_notifications),
+        )
         .merge(auth_routes)
         .merge(manager_routes)
         .merge(api_routes)
@@ -11,14 +11,29 @@ pub struct CommerceServices {
     pub holds: Arc<services::HoldsService>,
     pub payments: Arc<services::PaymentService>,
     pub taxes: Arc<services::TaxService>,
+    pub currency: Arc<services::CurrencyService>,
+    pub checkout: Arc<services::TransactionValidationService>,
+    pub adjustments: Arc<services::TransactionAdjustmentService>,
     pub returns: Arc<services::ReturnsService>,
     pub trade_in: Arc<services::TradeInProtectionService>,
+    pub consignment: Arc<services::ConsignmentService>,
+    pub purchasing: Arc<services::PurchasingService>,
+    pub shrinkage: Arc<services::ShrinkageService>,
+    pub cycle_counts: Arc<services::CycleCountService>,
+    pub kitting: Arc<services::KittingService>,
+    pub bulk_inventory: Arc<services::BulkInventoryService>,
+    pub replenishment: Arc<services::ReplenishmentService>,
+    pub loyalty: Arc<services::LoyaltyService>,
+    pub customers: Arc<services::CustomerService>,
+    pub grading: Arc<services::GradingService>,
 }
 
 #[derive(Clone)]
 pub struct SystemServices {
     pub audit: Arc<crate::audit::AuditService>,
     pub events: Arc<crate::events::EventService>,
+    pub tournaments: Arc<crate::events::TournamentService>,
+    pub prizes: Arc<crate::events::PrizeService>,
     pub barcode: Arc<services::BarcodeService>,
     pub receipts: Arc<services::ReceiptService>,
     pub invoices: Arc<services::InvoiceService>,
@@ -28,8 +43,12 @@ pub struct SystemServices {
     pub catalog: Arc<services::CatalogLookupService>,
     pub serialized: Arc<services::SerializedInventoryService>,
     pub locations: Arc<services::LocationService>,
+    pub storage: Arc<services::StorageService>,
     pub reporting: Arc<services::ReportingService>,
+    pub customer_display: Arc<services::CustomerDisplayService>,
     pub email: Arc<Box<dyn services::notification::EmailProvider>>,
     pub sms: Arc<Box<dyn services::notification::sms::SmsProvider>>,
     pub notification_scheduler: Arc<services::notification::scheduler::NotificationScheduler>,
+    pub notifications: Arc<services::notification::NotificationService>,
+    pub media: Arc<services::MediaService>,
 }
@@ -1,9 +1,226 @@
-use crate::core::{Condition, Customer, WantsItem};
+//! Wants-list matching
+//!
+//! One engine decides whether a copy in stock satisfies a customer's want:
+//! condition at or above the minimum, the wanted variant if one was named,
+//! a price within the customer's limit, and quantity still outstanding.
+//! Matches are recorded in `Wants_Matches` so a customer is told about a
+//! copy once. When the want asks for it, matched stock is reserved as a
+//! deposit-free hold for `hold_hours` before the customer is notified.
+//!
+//! A match ends Fulfilled (the customer bought it), Declined, or Expired
+//! (the reservation lapsed, or nobody acted on the notice in time).
+
+use crate::core::{Condition, Customer, VariantType, WantsItem};
 use crate::database::Database;
 use crate::errors::Result;
+use crate::services::holds::{HoldItemRequest, HoldsService};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::Row;
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Un-held matches nobody acted on stop counting against the want after this
+pub const OPEN_MATCH_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum WantsMatchStatus {
+    /// Customer told (or about to be); stock not reserved
+    Open,
+    /// Stock reserved on a hold awaiting pickup
+    Held,
+    Fulfilled,
+    Declined,
+    Expired,
+}
+
+impl WantsMatchStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WantsMatchStatus::Open => "Open",
+            WantsMatchStatus::Held => "Held",
+            WantsMatchStatus::Fulfilled => "Fulfilled",
+            WantsMatchStatus::Declined => "Declined",
+            WantsMatchStatus::Expired => "Expired",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "Held" => WantsMatchStatus::Held,
+            "Fulfilled" => WantsMatchStatus::Fulfilled,
+            "Declined" => WantsMatchStatus::Declined,
+            "Expired" => WantsMatchStatus::Expired,
+            _ => WantsMatchStatus::Open,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self, WantsMatchStatus::Open | WantsMatchStatus::Held)
+    }
+}
+
+/// A copy in stock offered to a customer against one of their wants
+#[derive(Debug, Clone, Serialize)]
+pub struct WantsMatch {
+    pub match_uuid: Uuid,
+    pub item_uuid: Uuid,
+    pub customer_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub inventory_uuid: Uuid,
+    pub quantity: i32,
+    pub unit_price: Option<f64>,
+    pub status: WantsMatchStatus,
+    pub hold_uuid: Option<Uuid>,
+    pub notified_via: Option<String>,
+    pub notified_at: Option<DateTime<Utc>>,
+    pub transaction_uuid: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MatchRefresh {
+    pub fulfilled: Vec<Uuid>,
+    pub expired: Vec<Uuid>,
+}
+
+fn parse_time(s: Option<String>) -> Option<DateTime<Utc>> {
+    s.and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|d| d.with_timezone(&Utc))
+}
+
+fn match_from_row(row: &sqlx::sqlite::SqliteRow) -> WantsMatch {
+    let uuid = |col: &str| {
+        row.try_get::<Option<String>, _>(col)
+            .ok()
+            .flatten()
+            .and_then(|s| Uuid::parse_str(&s).ok())
+    };
+    let text = |col: &str| row.try_get::<Option<String>, _>(col).ok().flatten();
+    let status: String = row.try_get("status").unwrap_or_default();
+    WantsMatch {
+        match_uuid: uuid("match_uuid").unwrap_or_default(),
+        item_uuid: uuid("item_uuid").unwrap_or_default(),
+        customer_uuid: uuid("customer_uuid").unwrap_or_default(),
+        product_uuid: uuid("product_uuid").unwrap_or_default(),
+        inventory_uuid: uuid("inventory_uuid").unwrap_or_default(),
+        quantity: row.try_get("quantity").unwrap_or(0),
+        unit_price: row.try_get("unit_price").ok().flatten(),
+        status: WantsMatchStatus::parse(&status),
+        hold_uuid: uuid("hold_uuid"),
+        notified_via: text("notified_via"),
+        notified_at: parse_time(text("notified_at")),
+        transaction_uuid: uuid("transaction_uuid"),
+        created_at: parse_time(text("created_at")).unwrap_or_default(),
+        updated_at: parse_time(text("updated_at")).unwrap_or_default(),
+    }
+}
+
+/// Count a purchase toward the want's quantity and re-sync its list
+async fn add_fulfilled(db: &Database, item_uuid: Uuid, quantity: i32) -> Result<()> {
+    let list: Option<String> = sqlx::query_scalar(
+        "UPDATE Wants_Items SET quantity_fulfilled = MIN(quantity, quantity_fulfilled + ?)
+         WHERE item_uuid = ? RETURNING wants_list_uuid",
+    )
+    .bind(quantity)
+    .bind(item_uuid.to_string())
+    .fetch_optional(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    if let Some(list_uuid) = list.and_then(|s| Uuid::parse_str(&s).ok()) {
+        db.customers.log_wants_list(list_uuid).await?;
+    }
+    Ok(())
+}
+
+async fn set_status(
+    db: &Database,
+    match_uuid: Uuid,
+    status: WantsMatchStatus,
+    transaction_uuid: Option<Uuid>,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE Wants_Matches SET status = ?, transaction_uuid = COALESCE(?, transaction_uuid), updated_at = ?
+         WHERE match_uuid = ?",
+    )
+    .bind(status.as_str())
+    .bind(transaction_uuid.map(|u| u.to_string()))
+    .bind(Utc::now().to_rfc3339())
+    .bind(match_uuid.to_string())
+    .execute(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    Ok(())
+}
+
+/// Credit a completed sale against the customer's wants: each line counts
+/// toward the oldest outstanding want it satisfies, closing that want's
+/// open matches once nothing is left to find
+pub async fn record_purchase(
+    db: &Database,
+    customer_uuid: Uuid,
+    transaction_uuid: Uuid,
+) -> Result<u32> {
+    let lines = sqlx::query(
+        "SELECT product_uuid, condition, SUM(quantity) AS quantity
+         FROM Transaction_Items WHERE transaction_uuid = ?
+         GROUP BY product_uuid, condition",
+    )
+    .bind(transaction_uuid.to_string())
+    .fetch_all(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let mut credited = 0;
+    for line in lines {
+        let product_uuid: String = line.try_get("product_uuid").unwrap_or_default();
+        let condition: String = line.try_get("condition").unwrap_or_default();
+        let condition: Condition =
+            serde_json::from_value(serde_json::Value::String(condition)).unwrap_or(Condition::NM);
+        let mut remaining: i64 = line.try_get("quantity").unwrap_or(0);
+
+        let wants = db
+            .customers
+            .get_wants_items_by_product(Uuid::parse_str(&product_uuid)?)
+            .await?;
+        for (customer, want) in wants {
+            if remaining <= 0 {
+                break;
+            }
+            // The register doesn't record variants, so only condition is checked
+            if customer.customer_uuid != customer_uuid || !condition.meets(&want.min_condition) {
+                continue;
+            }
+            let take = remaining.min(want.quantity_outstanding() as i64) as i32;
+            add_fulfilled(db, want.item_uuid, take).await?;
+            remaining -= take as i64;
+            credited += take as u32;
+
+            if take >= want.quantity_outstanding() {
+                let open = sqlx::query_scalar::<_, String>(
+                    "SELECT match_uuid FROM Wants_Matches WHERE item_uuid = ? AND status = 'Open'",
+                )
+                .bind(want.item_uuid.to_string())
+                .fetch_all(&db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+                for match_uuid in open.iter().filter_map(|s| Uuid::parse_str(s).ok()) {
+                    set_status(
+                        db,
+                        match_uuid,
+                        WantsMatchStatus::Fulfilled,
+                        Some(transaction_uuid),
+                    )
+                    .await?;
+                }
+            }
+        }
+    }
+    Ok(credited)
+}
+
 pub struct WantsMatchingService {
     db: Arc<Database>,
 }
@@ -14,69 +231,384 @@ impl WantsMatchingService {
     }
 
     /// HIGH-007 FIX: Now uses indexed query instead of O(N²) iteration
-    /// Checks if a newly acquired item matches any active wants list.
-    /// Returns a list of matches (Customer, WantsItem).
+    /// Outstanding wants on the product that a copy in this condition,
+    /// variant and price would satisfy, oldest want first
     pub async fn find_matches(
         &self,
         product_uuid: Uuid,
         condition: &Condition,
-        price: f64,
+        variant: Option<&VariantType>,
+        price: Option<f64>,
     ) -> Result<Vec<(Customer, WantsItem)>> {
-        // Use the new indexed query to get all wants for this specific product
-        let potential_matches = self
+        Ok(self
             .db
             .customers
             .get_wants_items_by_product(product_uuid)
-            .await?;
+            .await?
+            .into_iter()
+            .filter(|(_, item)| item.accepts(condition, variant, price))
+            .collect())
+    }
+
+    /// Quantity on active matches per want, so nobody is offered more
+    /// copies than they asked for
+    async fn active_quantities(&self, product_uuid: Uuid) -> Result<HashMap<Uuid, i32>> {
+        let rows = sqlx::query(
+            "SELECT item_uuid, SUM(quantity) AS quantity FROM Wants_Matches
+             WHERE product_uuid = ? AND status IN ('Open', 'Held')
+             GROUP BY item_uuid",
+        )
+        .bind(product_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let item: String = row.try_get("item_uuid").ok()?;
+                let quantity: i64 = row.try_get("quantity").ok()?;
+                Some((Uuid::parse_str(&item).ok()?, quantity as i32))
+            })
+            .collect())
+    }
 
-        let mut matches = Vec::new();
+    /// Record new matches for one stock pile, reserving copies for wants that
+    /// asked for a hold. Returns only matches created by this call.
+    pub async fn match_inventory(
+        &self,
+        inventory_uuid: Uuid,
+    ) -> Result<Vec<(Customer, WantsMatch)>> {
+        let Some(item) = self.db.inventory.get_by_id(inventory_uuid).await? else {
+            return Err(anyhow::anyhow!(
+                "Inventory item {} not found",
+                inventory_uuid
+            ));
+        };
+        if item.deleted_at.is_some() || item.quantity_on_hand <= 0 {
+            return Ok(Vec::new());
+        }
+        let market: Option<f64> =
+            sqlx::query_scalar("SELECT market_mid FROM Pricing_Matrix WHERE product_uuid = ?")
+                .bind(item.product_uuid.to_string())
+                .fetch_optional(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+                .flatten();
+        let price = item.specific_price.or(market);
 
-        for (customer, item) in potential_matches {
-            // Check condition (is acquired condition >= wanted min_condition?)
-            if !self.is_condition_acceptable(condition, &item.min_condition) {
+        let candidates = self
+            .find_matches(
+                item.product_uuid,
+                &item.condition,
+                item.variant_type.as_ref(),
+                price,
+            )
+            .await?;
+        let active = self.active_quantities(item.product_uuid).await?;
+        let holds = HoldsService::new(self.db.clone());
+
+        let mut available = item.quantity_on_hand;
+        let mut created = Vec::new();
+        for (customer, want) in candidates {
+            if available <= 0 {
+                break;
+            }
+            let wanted =
+                want.quantity_outstanding() - active.get(&want.item_uuid).copied().unwrap_or(0);
+            if wanted <= 0 {
                 continue;
             }
+            let quantity = wanted.min(available);
+
+            let hold_uuid = match want.hold_hours.filter(|h| *h > 0) {
+                Some(hours) => {
+                    let summary = holds
+                        .reserve_items(
+                            customer.customer_uuid,
+                            vec![HoldItemRequest {
+                                inventory_uuid,
+                                quantity,
+                                unit_price: price.unwrap_or(0.0),
+                            }],
+                            hours,
+                            Some("Wants list match".to_string()),
+                        )
+                        .await?;
+                    // Reserved copies are gone for everyone else
+                    available -= quantity;
+                    Some(summary.hold.hold_uuid)
+                }
+                None => None,
+            };
+
+            let now = Utc::now();
+            let wants_match = WantsMatch {
+                match_uuid: Uuid::new_v4(),
+                item_uuid: want.item_uuid,
+                customer_uuid: customer.customer_uuid,
+                product_uuid: item.product_uuid,
+                inventory_uuid,
+                quantity,
+                unit_price: price,
+                status: if hold_uuid.is_some() {
+                    WantsMatchStatus::Held
+                } else {
+                    WantsMatchStatus::Open
+                },
+                hold_uuid,
+                notified_via: None,
+                notified_at: None,
+                transaction_uuid: None,
+                created_at: now,
+                updated_at: now,
+            };
+            sqlx::query(
+                "INSERT INTO Wants_Matches
+                 (match_uuid, item_uuid, customer_uuid, product_uuid, inventory_uuid, quantity, unit_price, status, hold_uuid, created_at, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(wants_match.match_uuid.to_string())
+            .bind(wants_match.item_uuid.to_string())
+            .bind(wants_match.customer_uuid.to_string())
+            .bind(wants_match.product_uuid.to_string())
+            .bind(inventory_uuid.to_string())
+            .bind(quantity)
+            .bind(price)
+            .bind(wants_match.status.as_str())
+            .bind(hold_uuid.map(|u| u.to_string()))
+            .bind(now.to_rfc3339())
+            .bind(now.to_rfc3339())
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to record wants match: {}", e))?;
+
+            created.push((customer, wants_match));
+        }
+
+        Ok(created)
+    }
+
+    /// Match every in-stock pile of a product
+    pub async fn match_product(&self, product_uuid: Uuid) -> Result<Vec<(Customer, WantsMatch)>> {
+        let mut created = Vec::new();
+        for item in self.db.inventory.get_by_product(product_uuid).await? {
+            if item.quantity_on_hand > 0 && item.deleted_at.is_none() {
+                created.extend(self.match_inventory(item.inventory_uuid).await?);
+            }
+        }
+        Ok(created)
+    }
+
+    pub async fn mark_notified(&self, match_uuid: Uuid, channel: &str) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "UPDATE Wants_Matches SET notified_via = ?, notified_at = ?, updated_at = ? WHERE match_uuid = ?",
+        )
+        .bind(channel)
+        .bind(&now)
+        .bind(&now)
+        .bind(match_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn get_match(&self, match_uuid: Uuid) -> Result<Option<WantsMatch>> {
+        let row = sqlx::query("SELECT * FROM Wants_Matches WHERE match_uuid = ?")
+            .bind(match_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(row.as_ref().map(match_from_row))
+    }
+
+    /// Matches newest first, optionally for one customer or in one status
+    pub async fn list_matches(
+        &self,
+        customer_uuid: Option<Uuid>,
+        status: Option<WantsMatchStatus>,
+    ) -> Result<Vec<WantsMatch>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Wants_Matches
+             WHERE (?1 IS NULL OR customer_uuid = ?1) AND (?2 IS NULL OR status = ?2)
+             ORDER BY created_at DESC",
+        )
+        .bind(customer_uuid.map(|u| u.to_string()))
+        .bind(status.map(|s| s.as_str()))
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().map(match_from_row).collect())
+    }
+
+    async fn active_match(&self, match_uuid: Uuid) -> Result<WantsMatch> {
+        let wants_match = self
+            .get_match(match_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wants match {} not found", match_uuid))?;
+        if !wants_match.status.is_active() {
+            return Err(anyhow::anyhow!(
+                "Wants match is already {}",
+                wants_match.status.as_str()
+            ));
+        }
+        Ok(wants_match)
+    }
+
+    /// The customer bought a matched copy outside the normal sale flow.
+    /// Reserved matches are fulfilled by paying off their hold instead.
+    pub async fn fulfill_match(
+        &self,
+        match_uuid: Uuid,
+        transaction_uuid: Option<Uuid>,
+    ) -> Result<WantsMatch> {
+        let wants_match = self.active_match(match_uuid).await?;
+        if let Some(hold_uuid) = wants_match.hold_uuid {
+            return Err(anyhow::anyhow!(
+                "Match is reserved on hold {}; take payment on the hold to fulfil it",
+                hold_uuid
+            ));
+        }
+        add_fulfilled(&self.db, wants_match.item_uuid, wants_match.quantity).await?;
+        set_status(
+            &self.db,
+            match_uuid,
+            WantsMatchStatus::Fulfilled,
+            transaction_uuid,
+        )
+        .await?;
+        self.get_match(match_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wants match {} not found", match_uuid))
+    }
+
+    /// The customer passed; any reserved stock goes back on the shelf
+    pub async fn decline_match(&self, match_uuid: Uuid) -> Result<WantsMatch> {
+        let wants_match = self.active_match(match_uuid).await?;
+        if let Some(hold_uuid) = wants_match.hold_uuid {
+            HoldsService::new(self.db.clone())
+                .cancel_hold(hold_uuid, "Wants match declined")
+                .await?;
+        }
+        set_status(&self.db, match_uuid, WantsMatchStatus::Declined, None).await?;
+        self.get_match(match_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wants match {} not found", match_uuid))
+    }
+
+    /// Settle matches whose outcome is decided elsewhere: paid-off holds are
+    /// fulfilled, lapsed or cancelled holds expire (releasing overdue
+    /// reservations), and stale un-held matches expire
+    pub async fn refresh_matches(&self) -> Result<MatchRefresh> {
+        let now = Utc::now();
+        let rows = sqlx::query(
+            "SELECT m.match_uuid, m.item_uuid, m.quantity, m.hold_uuid, h.status AS hold_status, h.expiration_date
+             FROM Wants_Matches m
+             LEFT JOIN Holds h ON h.hold_uuid = m.hold_uuid
+             WHERE m.status = 'Held'",
+        )
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
 
-            // Check price constraint
-            if let Some(max_price) = item.max_price {
-                if price > max_price {
-                    continue;
+        let holds = HoldsService::new(self.db.clone());
+        let mut refresh = MatchRefresh::default();
+        for row in rows {
+            let match_uuid: String = row.try_get("match_uuid").unwrap_or_default();
+            let match_uuid = Uuid::parse_str(&match_uuid)?;
+            let item_uuid: String = row.try_get("item_uuid").unwrap_or_default();
+            let quantity: i32 = row.try_get("quantity").unwrap_or(0);
+            let hold_status: Option<String> = row.try_get("hold_status").ok().flatten();
+            let expires = parse_time(row.try_get("expiration_date").ok().flatten());
+
+            match hold_status.as_deref() {
+                Some("Completed") => {
+                    add_fulfilled(&self.db, Uuid::parse_str(&item_uuid)?, quantity).await?;
+                    set_status(&self.db, match_uuid, WantsMatchStatus::Fulfilled, None).await?;
+                    refresh.fulfilled.push(match_uuid);
+                }
+                Some("Active") if expires.is_some_and(|e| e < now) => {
+                    let hold_uuid: String = row.try_get("hold_uuid").unwrap_or_default();
+                    holds
+                        .cancel_hold(Uuid::parse_str(&hold_uuid)?, "Wants match not collected")
+                        .await?;
+                    set_status(&self.db, match_uuid, WantsMatchStatus::Expired, None).await?;
+                    refresh.expired.push(match_uuid);
+                }
+                Some("Active") => {}
+                _ => {
+                    set_status(&self.db, match_uuid, WantsMatchStatus::Expired, None).await?;
+                    refresh.expired.push(match_uuid);
                 }
             }
+        }
 
-            matches.push((customer, item));
+        let stale = sqlx::query_scalar::<_, String>(
+            "SELECT match_uuid FROM Wants_Matches WHERE status = 'Open' AND created_at < ?",
+        )
+        .bind((now - Duration::days(OPEN_MATCH_DAYS)).to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        for match_uuid in stale.iter().filter_map(|s| Uuid::parse_str(s).ok()) {
+            set_status(&self.db, match_uuid, WantsMatchStatus::Expired, None).await?;
+            refresh.expired.push(match_uuid);
         }
 
-        Ok(matches)
-    }
-
-    fn is_condition_acceptable(&self, actual: &Condition, min_required: &Condition) -> bool {
-        // Simple hierarchy check.
-        // A better way is to move this into the Condition enum itself (impl PartialOrd).
-        // For now, we'll do a quick map to integer.
-        let actual_score = self.condition_score(actual);
-        let min_score = self.condition_score(min_required);
-
-        actual_score >= min_score
-    }
-
-    fn condition_score(&self, c: &Condition) -> i32 {
-        match c {
-            Condition::GemMint => 100,
-            Condition::Mint => 90,
-            Condition::NearMintMint => 85,
-            Condition::NM => 80,
-            Condition::New => 80, // Eq to NM
-            Condition::LP => 70,
-            Condition::VeryFine => 70,
-            Condition::OpenBox => 70,
-            Condition::MP => 60,
-            Condition::Fine => 60,
-            Condition::Used => 60,
-            Condition::HP => 40,
-            Condition::Good => 40,
-            Condition::DMG => 20,
-            Condition::Poor => 20,
+        Ok(refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn want(
+        min_condition: Condition,
+        variant: Option<VariantType>,
+        max_price: Option<f64>,
+    ) -> WantsItem {
+        WantsItem {
+            item_uuid: Uuid::new_v4(),
+            product_uuid: Uuid::new_v4(),
+            min_condition,
+            max_price,
+            variant_type: variant,
+            quantity: 2,
+            quantity_fulfilled: 0,
+            hold_hours: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_want_acceptance() {
+        let lp = want(Condition::LP, None, Some(10.0));
+        assert!(lp.accepts(&Condition::NM, None, Some(9.99)));
+        assert!(lp.accepts(&Condition::LP, Some(&VariantType::Foil), Some(10.0)));
+        assert!(!lp.accepts(&Condition::MP, None, Some(5.0)));
+        assert!(!lp.accepts(&Condition::NM, None, Some(10.5)));
+        assert!(!lp.accepts(&Condition::NM, None, None));
+
+        let foil = want(Condition::HP, Some(VariantType::Foil), None);
+        assert!(foil.accepts(&Condition::HP, Some(&VariantType::Foil), None));
+        assert!(!foil.accepts(&Condition::NM, Some(&VariantType::Normal), Some(1.0)));
+        assert!(!foil.accepts(&Condition::NM, None, Some(1.0)));
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in [
+            WantsMatchStatus::Open,
+            WantsMatchStatus::Held,
+            WantsMatchStatus::Fulfilled,
+            WantsMatchStatus::Declined,
+            WantsMatchStatus::Expired,
+        ] {
+            assert_eq!(WantsMatchStatus::parse(status.as_str()), status);
         }
+        assert!(WantsMatchStatus::Held.is_active());
+        assert!(!WantsMatchStatus::Expired.is_active());
     }
 }
@@ -192,7 +192,7 @@ impl BuylistService {
                 // Check for wants matches (read-only operation)
                 let item_matches = self
                     .matcher
-                    .find_matches(item.product_uuid, &item.condition, item_price)
+                    .find_matches(item.product_uuid, &item.condition, None, Some(item_price))
                     .await?;
                 matches.extend(item_matches);
             } else {
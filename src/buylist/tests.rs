@@ -54,6 +54,7 @@ async fn test_volatility_guard_triggers() {
         market_mid: 100.0,
         market_low: 90.0,
         last_sync_timestamp: Utc::now(),
+        currency: crate::core::Currency::USD,
     };
 
     let fresh_price = PriceInfo {
@@ -62,6 +63,7 @@ async fn test_volatility_guard_triggers() {
         market_mid: 120.0, // 20% jump
         market_low: 100.0,
         last_sync_timestamp: Utc::now(),
+        currency: crate::core::Currency::USD,
     };
 
     let pricing_service = Arc::new(MockPricingService {
@@ -162,6 +164,7 @@ async fn test_safe_price_passes() {
         market_mid: 100.0,
         market_low: 90.0,
         last_sync_timestamp: Utc::now(),
+        currency: crate::core::Currency::USD,
     };
 
     let fresh_price = PriceInfo {
@@ -170,6 +173,7 @@ async fn test_safe_price_passes() {
         market_mid: 105.0,
         market_low: 95.0,
         last_sync_timestamp: Utc::now(),
+        currency: crate::core::Currency::USD,
     };
 
     let pricing_service = Arc::new(MockPricingService {
@@ -242,6 +246,7 @@ async fn test_instant_quote() {
         market_mid: 100.0,
         market_low: 90.0,
         last_sync_timestamp: Utc::now(),
+        currency: crate::core::Currency::USD,
     };
 
     let pricing_service = Arc::new(MockPricingService {
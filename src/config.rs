@@ -54,6 +54,14 @@ pub struct Config {
     /// Store Website (Optional)
     pub store_website: Option<String>,
 
+    /// Base URL customers can reach this server on, used for unsubscribe
+    /// links in marketing messages (Optional)
+    pub public_url: Option<String>,
+
+    /// Directory uploaded photos and their thumbnails are kept in
+    /// (default: ./media)
+    pub media_dir: std::path::PathBuf,
+
     /// Pricing volatility threshold for flagging (default: 0.15 = 15%)
     pub pricing_volatility_threshold: f64,
 
@@ -96,6 +104,8 @@ impl Default for Config {
             store_address: "123 Test St".to_string(),
             store_phone: None,
             store_website: None,
+            public_url: None,
+            media_dir: std::env::temp_dir().join("vaultsync_test_media"),
             pricing_volatility_threshold: 0.15,
             sync_batch_size: 100,
             thermal_line_width: 42,
@@ -216,6 +226,10 @@ impl Config {
             std::env::var("STORE_ADDRESS").unwrap_or_else(|_| "123 Local St".to_string());
         let store_phone = std::env::var("STORE_PHONE").ok();
         let store_website = std::env::var("STORE_WEBSITE").ok();
+        let public_url = std::env::var("PUBLIC_URL").ok();
+        let media_dir = std::env::var("MEDIA_DIR")
+            .unwrap_or_else(|_| "./media".to_string())
+            .into();
 
         // Pricing volatility threshold
         let pricing_volatility_threshold = std::env::var("PRICING_VOLATILITY_THRESHOLD")
@@ -277,6 +291,8 @@ impl Config {
             store_address,
             store_phone,
             store_website,
+            public_url,
+            media_dir,
             pricing_volatility_threshold,
             sync_batch_size,
             thermal_line_width,
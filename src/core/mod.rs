@@ -5,7 +5,7 @@ use uuid::Uuid;
 
 // P0 Fix: Proper decimal handling for monetary values
 pub mod money;
-pub use money::Money;
+pub use money::{Currency, Money};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
 pub enum Category {
@@ -69,7 +69,7 @@ pub struct InventoryItem {
     pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
 pub enum VariantType {
     Normal,
     Foil,
@@ -108,6 +108,28 @@ pub enum Condition {
     Poor,
 }
 
+impl Condition {
+    /// Position on a common quality scale, so conditions from different
+    /// grading families can be compared (NM and New rank together)
+    pub fn rank(&self) -> i32 {
+        match self {
+            Condition::GemMint => 100,
+            Condition::Mint => 90,
+            Condition::NearMintMint => 85,
+            Condition::NM | Condition::New => 80,
+            Condition::LP | Condition::VeryFine | Condition::OpenBox => 70,
+            Condition::MP | Condition::Fine | Condition::Used => 60,
+            Condition::HP | Condition::Good => 40,
+            Condition::DMG | Condition::Poor => 20,
+        }
+    }
+
+    /// At least as good as `minimum`
+    pub fn meets(&self, minimum: &Condition) -> bool {
+        self.rank() >= minimum.rank()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct InventoryItemWithProduct {
     #[serde(flatten)]
@@ -134,6 +156,10 @@ pub struct PriceInfo {
     pub market_mid: f64,
     pub market_low: f64,
     pub last_sync_timestamp: DateTime<Utc>,
+    /// Currency the market prices are quoted in
+    #[serde(default)]
+    #[schema(value_type = String)]
+    pub currency: Currency,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -188,9 +214,52 @@ pub struct WantsItem {
     pub product_uuid: Uuid,
     pub min_condition: Condition,
     pub max_price: Option<f64>,
+    /// Only this printing/variant will do; None accepts any
+    #[serde(default)]
+    pub variant_type: Option<VariantType>,
+    #[serde(default = "default_wants_quantity")]
+    pub quantity: i32,
+    #[serde(default)]
+    pub quantity_fulfilled: i32,
+    /// Reserve matched stock for this many hours before notifying
+    #[serde(default)]
+    pub hold_hours: Option<i64>,
     pub created_at: DateTime<Utc>,
 }
 
+fn default_wants_quantity() -> i32 {
+    1
+}
+
+impl WantsItem {
+    pub fn quantity_outstanding(&self) -> i32 {
+        (self.quantity - self.quantity_fulfilled).max(0)
+    }
+
+    /// Whether a copy in this condition, variant and price satisfies the want.
+    /// A max price can't be checked against an unpriced copy, so it doesn't match.
+    pub fn accepts(
+        &self,
+        condition: &Condition,
+        variant: Option<&VariantType>,
+        price: Option<f64>,
+    ) -> bool {
+        if !condition.meets(&self.min_condition) {
+            return false;
+        }
+        if let Some(wanted) = &self.variant_type {
+            if variant != Some(wanted) {
+                return false;
+            }
+        }
+        match (self.max_price, price) {
+            (Some(max), Some(price)) => price <= max + 0.005,
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Event {
     pub event_uuid: Uuid,
@@ -209,10 +278,68 @@ pub struct EventParticipant {
     pub customer_uuid: Option<Uuid>, // Optional for walk-ins
     pub name: String,
     pub paid: bool,
+    /// Entry fee actually collected
+    #[serde(default)]
+    pub fee_paid: f64,
+    /// How the latest payment was made: "credit" or "cash"
+    #[serde(default)]
+    pub payment_method: Option<String>,
+    /// Portion of `fee_paid` taken in cash; the rest came from store credit
+    #[serde(default)]
+    pub cash_paid: f64,
+    #[serde(default)]
+    pub status: ParticipantStatus,
+    #[serde(default)]
+    pub checked_in_at: Option<DateTime<Utc>>,
+    /// Entry fee handed back on cancellation
+    #[serde(default)]
+    pub refunded_amount: f64,
     pub placement: Option<i32>,
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipantStatus {
+    #[default]
+    Registered,
+    /// Event was full; promoted in sign-up order as seats open
+    Waitlisted,
+    CheckedIn,
+    NoShow,
+    Cancelled,
+}
+
+impl ParticipantStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParticipantStatus::Registered => "registered",
+            ParticipantStatus::Waitlisted => "waitlisted",
+            ParticipantStatus::CheckedIn => "checked_in",
+            ParticipantStatus::NoShow => "no_show",
+            ParticipantStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "waitlisted" => ParticipantStatus::Waitlisted,
+            "checked_in" => ParticipantStatus::CheckedIn,
+            "no_show" => ParticipantStatus::NoShow,
+            "cancelled" => ParticipantStatus::Cancelled,
+            _ => ParticipantStatus::Registered,
+        }
+    }
+
+    /// Holds one of the event's seats
+    pub fn is_seated(&self) -> bool {
+        matches!(
+            self,
+            ParticipantStatus::Registered | ParticipantStatus::CheckedIn
+        )
+    }
+}
+
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
@@ -291,6 +418,9 @@ pub enum RecordType {
     WantsList,
     Event,
     EventParticipant,
+    InventoryTransfer,
+    LoyaltyEntry,
+    Media,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
@@ -8,33 +8,159 @@
 //! `0.1 + 0.2 != 0.3` in IEEE 754 floats. After thousands of transactions,
 //! customer store credits and inventory valuations WILL drift. This is
 //! unacceptable for a POS system.
+//!
+//! ## Currencies
+//!
+//! Every `Money` carries an ISO 4217 [`Currency`]. Amounts in different
+//! currencies never mix implicitly: `+`/`-` panic on a mismatch, and
+//! [`Money::checked_add`] / [`Money::checked_sub`] return `None` for code
+//! that can see foreign amounts. Use [`Money::convert`] with an exchange
+//! rate to move between them. Constructors without a currency argument
+//! produce USD for compatibility with existing callers.
+//!
+//! On the wire `Money` stays a bare decimal, as it always has been. Fields
+//! that can hold a non-base amount opt into [`tagged`], which writes
+//! `{"amount": "12.34", "currency": "EUR"}`.
+//!
+//! ## Scope
+//!
+//! `Money` is used where a currency boundary is crossed: foreign tender,
+//! conversions and price display. Transactions, tax, holds and the other
+//! ledgers still store `f64` amounts in the store's base currency (rounded
+//! with [`round_cents`]), which is why the base currency cannot change once
+//! amounts exist. Moving those ledgers onto `Money` is a separate migration.
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::ops::{Add, Mul, Sub};
 
+/// An ISO 4217 currency code (e.g. `USD`, `EUR`, `JPY`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const USD: Currency = Currency(*b"USD");
+    pub const EUR: Currency = Currency(*b"EUR");
+    pub const GBP: Currency = Currency(*b"GBP");
+    pub const CAD: Currency = Currency(*b"CAD");
+    pub const AUD: Currency = Currency(*b"AUD");
+    pub const JPY: Currency = Currency(*b"JPY");
+
+    /// Parse a three-letter ISO code (case-insensitive)
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let mut bytes = [0u8; 3];
+        bytes.copy_from_slice(code.to_ascii_uppercase().as_bytes());
+        Some(Self(bytes))
+    }
+
+    /// The ISO code as a string slice
+    pub fn code(&self) -> &str {
+        // Only ever constructed from ASCII letters
+        std::str::from_utf8(&self.0).unwrap_or("USD")
+    }
+
+    /// Display symbol for well-known currencies
+    pub fn symbol(&self) -> Option<&'static str> {
+        match &self.0 {
+            b"USD" | b"CAD" | b"AUD" => Some("$"),
+            b"EUR" => Some("€"),
+            b"GBP" => Some("£"),
+            b"JPY" => Some("¥"),
+            _ => None,
+        }
+    }
+
+    /// Number of decimal places used by the currency
+    pub fn minor_units(&self) -> u32 {
+        match &self.0 {
+            b"JPY" | b"KRW" | b"VND" | b"CLP" | b"ISK" => 0,
+            _ => 2,
+        }
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Self::USD
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl std::str::FromStr for Currency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_code(s).ok_or_else(|| format!("Invalid currency code: {}", s))
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// A monetary value with proper decimal handling.
 ///
 /// Internally represented as `rust_decimal::Decimal` which can exactly
 /// represent values like $0.01 without floating-point errors.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-#[serde(transparent)]
-pub struct Money(Decimal);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    amount: Decimal,
+    currency: Currency,
+}
 
 impl Money {
     /// Zero dollars
-    pub const ZERO: Money = Money(Decimal::ZERO);
+    pub const ZERO: Money = Money {
+        amount: Decimal::ZERO,
+        currency: Currency::USD,
+    };
 
-    /// Create from a decimal value
+    /// Create from a decimal value (USD)
     pub fn new(value: Decimal) -> Self {
-        Self(value)
+        Self::new_in(value, Currency::USD)
+    }
+
+    /// Create from a decimal value in the given currency
+    pub fn new_in(value: Decimal, currency: Currency) -> Self {
+        Self {
+            amount: value,
+            currency,
+        }
+    }
+
+    /// Zero in the given currency
+    pub fn zero(currency: Currency) -> Self {
+        Self::new_in(Decimal::ZERO, currency)
     }
 
     /// Create from cents (100 = $1.00)
     pub fn from_cents(cents: i64) -> Self {
-        Self(Decimal::new(cents, 2))
+        Self::from_minor(cents, Currency::USD)
+    }
+
+    /// Create from the currency's minor units (cents, pence, or whole yen)
+    pub fn from_minor(units: i64, currency: Currency) -> Self {
+        Self::new_in(Decimal::new(units, currency.minor_units()), currency)
     }
 
     /// Create from a float (ONLY for migration from legacy f64 data)
@@ -43,7 +169,12 @@ impl Money {
     /// This should only be used when reading legacy data.
     /// New code should use `from_cents` or `from_str`.
     pub fn from_f64_lossy(value: f64) -> Self {
-        Self(Decimal::try_from(value).unwrap_or(Decimal::ZERO))
+        Self::from_f64_lossy_in(value, Currency::USD)
+    }
+
+    /// Create from a legacy f64 amount in the given currency
+    pub fn from_f64_lossy_in(value: f64, currency: Currency) -> Self {
+        Self::new_in(Decimal::try_from(value).unwrap_or(Decimal::ZERO), currency)
     }
 
     /// Convert to f64 (ONLY for legacy API compatibility)
@@ -53,37 +184,71 @@ impl Money {
     #[deprecated(note = "Use proper Money serialization instead")]
     pub fn to_f64_lossy(&self) -> f64 {
         use rust_decimal::prelude::ToPrimitive;
-        self.0.to_f64().unwrap_or(0.0)
+        self.amount.to_f64().unwrap_or(0.0)
     }
 
     /// Get the inner Decimal value
     pub fn inner(&self) -> Decimal {
-        self.0
+        self.amount
+    }
+
+    /// The currency this amount is denominated in
+    pub fn currency(&self) -> Currency {
+        self.currency
     }
 
     /// Check if zero
     pub fn is_zero(&self) -> bool {
-        self.0.is_zero()
+        self.amount.is_zero()
     }
 
     /// Check if negative
     pub fn is_negative(&self) -> bool {
-        self.0.is_sign_negative()
+        self.amount.is_sign_negative()
     }
 
     /// Absolute value
     pub fn abs(&self) -> Self {
-        Self(self.0.abs())
+        Self::new_in(self.amount.abs(), self.currency)
     }
 
     /// Round to 2 decimal places (standard currency precision)
     pub fn round_cents(&self) -> Self {
-        Self(self.0.round_dp(2))
+        Self::new_in(self.amount.round_dp(2), self.currency)
+    }
+
+    /// Round to the currency's own precision (0 places for JPY, 2 for USD)
+    pub fn round_minor(&self) -> Self {
+        Self::new_in(
+            self.amount.round_dp(self.currency.minor_units()),
+            self.currency,
+        )
     }
 
     /// Apply a percentage (e.g., tax rate of 8.25%)
     pub fn apply_percentage(&self, percent: Decimal) -> Self {
-        Self((self.0 * percent / dec!(100)).round_dp(2))
+        Self::new_in(self.amount * percent / dec!(100), self.currency).round_minor()
+    }
+
+    /// Convert to another currency. `rate` is units of `to` per one unit of
+    /// this amount's currency; the result is rounded to the target precision.
+    pub fn convert(&self, to: Currency, rate: Decimal) -> Self {
+        if to == self.currency {
+            return *self;
+        }
+        Self::new_in(self.amount * rate, to).round_minor()
+    }
+
+    /// Add two amounts, returning `None` if their currencies differ
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        (self.currency == rhs.currency)
+            .then(|| Self::new_in(self.amount + rhs.amount, self.currency))
+    }
+
+    /// Subtract two amounts, returning `None` if their currencies differ
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        (self.currency == rhs.currency)
+            .then(|| Self::new_in(self.amount - rhs.amount, self.currency))
     }
 }
 
@@ -95,60 +260,171 @@ impl Default for Money {
 
 impl fmt::Display for Money {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "${:.2}", self.0)
+        let places = self.currency.minor_units() as usize;
+        let sign = if self.amount.is_sign_negative() && !self.amount.is_zero() {
+            "-"
+        } else {
+            ""
+        };
+        let magnitude = self.amount.abs();
+        match self.currency.symbol() {
+            Some(symbol) => write!(f, "{}{}{:.*}", sign, symbol, places, magnitude),
+            None => write!(f, "{}{:.*} {}", sign, places, magnitude, self.currency),
+        }
     }
 }
 
+/// Serializes as the bare decimal amount; the currency is not written
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(&self.amount, serializer)
+    }
+}
+
+/// Reads a bare decimal amount as USD
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <Decimal as Deserialize>::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Panics if the currencies differ
 impl Add for Money {
     type Output = Self;
     fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0)
+        self.checked_add(rhs)
+            .unwrap_or_else(|| panic!("cannot add {} to {}", rhs.currency, self.currency))
     }
 }
 
+/// Panics if the currencies differ
 impl Sub for Money {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0)
+        self.checked_sub(rhs)
+            .unwrap_or_else(|| panic!("cannot subtract {} from {}", rhs.currency, self.currency))
     }
 }
 
 impl Mul<i32> for Money {
     type Output = Self;
     fn mul(self, rhs: i32) -> Self::Output {
-        Self(self.0 * Decimal::from(rhs))
+        Self::new_in(self.amount * Decimal::from(rhs), self.currency)
     }
 }
 
 impl Mul<Decimal> for Money {
     type Output = Self;
     fn mul(self, rhs: Decimal) -> Self::Output {
-        Self(self.0 * rhs)
+        Self::new_in(self.amount * rhs, self.currency)
     }
 }
 
 impl From<Decimal> for Money {
     fn from(d: Decimal) -> Self {
-        Self(d)
+        Self::new(d)
     }
 }
 
 impl From<Money> for Decimal {
     fn from(m: Money) -> Self {
-        m.0
+        m.amount
     }
 }
 
-/// Parse money from string like "12.34" or "$12.34"
+/// Parse money from strings like "12.34", "$12.34", "€12.34" or "12.34 EUR".
+/// Bare numbers and "$" are treated as USD.
 impl std::str::FromStr for Money {
     type Err = rust_decimal::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cleaned = s.trim().trim_start_matches('$');
-        Ok(Self(cleaned.parse()?))
+        let trimmed = s.trim();
+
+        // Trailing or leading ISO code: "12.34 EUR" / "EUR 12.34"
+        let parts: Vec<&str> = trimmed.split_whitespace().collect();
+        if parts.len() == 2 {
+            if let Some(currency) = Currency::from_code(parts[1]) {
+                return Ok(Self::new_in(parts[0].parse()?, currency));
+            }
+            if let Some(currency) = Currency::from_code(parts[0]) {
+                return Ok(Self::new_in(parts[1].parse()?, currency));
+            }
+        }
+
+        let (currency, cleaned) = if let Some(rest) = trimmed.strip_prefix('€') {
+            (Currency::EUR, rest)
+        } else if let Some(rest) = trimmed.strip_prefix('£') {
+            (Currency::GBP, rest)
+        } else if let Some(rest) = trimmed.strip_prefix('¥') {
+            (Currency::JPY, rest)
+        } else {
+            (Currency::USD, trimmed.trim_start_matches('$'))
+        };
+        Ok(Self::new_in(cleaned.parse()?, currency))
+    }
+}
+
+/// Serde adapter writing `Money` with its currency, for fields that may hold
+/// non-base amounts: `#[serde(with = "crate::core::money::tagged")]`
+pub mod tagged {
+    use super::{Currency, Money};
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Tagged {
+        amount: Decimal,
+        currency: Currency,
+    }
+
+    pub fn serialize<S: Serializer>(money: &Money, serializer: S) -> Result<S::Ok, S::Error> {
+        Tagged {
+            amount: money.amount,
+            currency: money.currency,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        let tagged = Tagged::deserialize(deserializer)?;
+        Ok(Money::new_in(tagged.amount, tagged.currency))
+    }
+
+    /// The same for `Option<Money>`
+    pub mod option {
+        use super::Money;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        #[derive(Serialize, Deserialize)]
+        struct Wrap(#[serde(with = "super")] Money);
+
+        pub fn serialize<S: Serializer>(
+            money: &Option<Money>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            money.map(Wrap).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Money>, D::Error> {
+            Ok(Option::<Wrap>::deserialize(deserializer)?.map(|w| w.0))
+        }
     }
 }
 
+/// Render a legacy f64 amount in the given currency (e.g. for receipts and labels)
+pub fn format_amount(amount: f64, currency: Currency) -> String {
+    Money::from_f64_lossy_in(amount, currency)
+        .round_minor()
+        .to_string()
+}
+
+/// Round a legacy f64 amount to two decimal places (whole cents)
+pub fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,4 +465,75 @@ mod tests {
         let amount = Money::from_cents(1234);
         assert_eq!(format!("{}", amount), "$12.34");
     }
+
+    #[test]
+    fn test_currency_display_and_precision() {
+        assert_eq!(Money::from_minor(1234, Currency::EUR).to_string(), "€12.34");
+        assert_eq!(Money::from_minor(1500, Currency::JPY).to_string(), "¥1500");
+        let chf = Currency::from_code("chf").unwrap();
+        assert_eq!(Money::from_minor(999, chf).to_string(), "9.99 CHF");
+        assert_eq!(Money::from_cents(-250).to_string(), "-$2.50");
+        assert!(Currency::from_code("US").is_none());
+    }
+
+    #[test]
+    fn test_currency_conversion_rounds_to_target() {
+        let eur = Money::from_minor(1000, Currency::EUR);
+        let usd = eur.convert(Currency::USD, dec!(1.0875));
+        assert_eq!(usd, Money::from_cents(1088));
+
+        let yen = usd.convert(Currency::JPY, dec!(149.37));
+        assert_eq!(yen, Money::from_minor(1625, Currency::JPY));
+    }
+
+    #[test]
+    fn test_mixed_currency_arithmetic_is_rejected() {
+        let usd = Money::from_cents(100);
+        let eur = Money::from_minor(100, Currency::EUR);
+        assert!(usd.checked_add(eur).is_none());
+        assert_eq!(usd.checked_add(usd), Some(Money::from_cents(200)));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot add EUR to USD")]
+    fn test_mixed_currency_operator_panics() {
+        let _ = Money::from_cents(100) + Money::from_minor(100, Currency::EUR);
+    }
+
+    #[test]
+    fn test_money_parse_with_currency() {
+        assert_eq!(
+            "12.34 EUR".parse::<Money>().unwrap(),
+            Money::from_minor(1234, Currency::EUR)
+        );
+        assert_eq!(
+            "€5.00".parse::<Money>().unwrap(),
+            Money::from_minor(500, Currency::EUR)
+        );
+        assert_eq!("$1.99".parse::<Money>().unwrap(), Money::from_cents(199));
+    }
+
+    #[test]
+    fn test_money_serializes_as_bare_decimal_unless_tagged() {
+        let gbp = Money::from_minor(1234, Currency::GBP);
+        assert_eq!(serde_json::to_value(gbp).unwrap(), "12.34");
+        assert_eq!(
+            serde_json::from_str::<Money>("\"12.34\"").unwrap(),
+            Money::from_cents(1234)
+        );
+
+        #[derive(Serialize, Deserialize)]
+        struct Tender {
+            #[serde(with = "tagged::option")]
+            tendered: Option<Money>,
+        }
+        let json = serde_json::to_value(Tender {
+            tendered: Some(gbp),
+        })
+        .unwrap();
+        assert_eq!(json["tendered"]["amount"], "12.34");
+        assert_eq!(json["tendered"]["currency"], "GBP");
+        let back: Tender = serde_json::from_value(json).unwrap();
+        assert_eq!(back.tendered, Some(gbp));
+    }
 }
@@ -516,5 +516,886 @@ pub fn get_schema_migrations() -> Vec<(i32, &'static str, Vec<&'static str>)> {
             "ALTER TABLE Transactions ADD COLUMN user_uuid TEXT",
             "CREATE INDEX IF NOT EXISTS idx_transactions_user ON Transactions(user_uuid)"
         ]),
+        // Layaway payment plans
+        (29, "Layaway Plans", vec![
+            "CREATE TABLE IF NOT EXISTS Layaway_Plans (
+                plan_uuid TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                installment_count INTEGER NOT NULL CHECK(installment_count > 0),
+                installment_interval_days INTEGER NOT NULL CHECK(installment_interval_days > 0),
+                minimum_deposit_percent REAL NOT NULL DEFAULT 0.2,
+                grace_period_days INTEGER NOT NULL DEFAULT 0,
+                late_fee REAL NOT NULL DEFAULT 0,
+                forfeiture_policy TEXT NOT NULL CHECK(forfeiture_policy IN ('RefundAll', 'StoreCredit', 'RetainRestockingFee', 'RetainAll')),
+                restocking_fee_percent REAL NOT NULL DEFAULT 0,
+                is_active INTEGER DEFAULT 1,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Hold_Installments (
+                installment_uuid TEXT PRIMARY KEY,
+                hold_uuid TEXT NOT NULL,
+                sequence INTEGER NOT NULL,
+                due_date TEXT NOT NULL,
+                amount_due REAL NOT NULL,
+                amount_paid REAL NOT NULL DEFAULT 0,
+                late_fee REAL NOT NULL DEFAULT 0,
+                status TEXT NOT NULL CHECK(status IN ('Pending', 'Paid', 'Late', 'Waived')),
+                paid_at TEXT,
+                FOREIGN KEY (hold_uuid) REFERENCES Holds(hold_uuid)
+            )",
+            "ALTER TABLE Holds ADD COLUMN plan_uuid TEXT",
+            "ALTER TABLE Holds ADD COLUMN forfeited_amount REAL DEFAULT 0",
+            "ALTER TABLE Holds ADD COLUMN refunded_amount REAL DEFAULT 0",
+            "ALTER TABLE Holds ADD COLUMN refund_method TEXT",
+            "ALTER TABLE Holds ADD COLUMN late_fees REAL NOT NULL DEFAULT 0",
+            "CREATE INDEX IF NOT EXISTS idx_hold_installments_hold ON Hold_Installments(hold_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_hold_installments_due ON Hold_Installments(status, due_date)"
+        ]),
+        (30, "Tax Jurisdictions", vec![
+            "CREATE TABLE IF NOT EXISTS Tax_Jurisdictions (
+                jurisdiction_uuid TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                code TEXT,
+                level TEXT NOT NULL CHECK(level IN ('State', 'County', 'City', 'Special')),
+                rate REAL NOT NULL CHECK(rate >= 0 AND rate <= 1),
+                is_compound INTEGER DEFAULT 0,
+                applies_to_category TEXT,
+                is_active INTEGER DEFAULT 1,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Location_Tax_Jurisdictions (
+                location_uuid TEXT NOT NULL,
+                jurisdiction_uuid TEXT NOT NULL,
+                sequence INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (location_uuid, jurisdiction_uuid),
+                FOREIGN KEY (location_uuid) REFERENCES Store_Locations(location_uuid),
+                FOREIGN KEY (jurisdiction_uuid) REFERENCES Tax_Jurisdictions(jurisdiction_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Tax_Exemption_Certificates (
+                certificate_uuid TEXT PRIMARY KEY,
+                customer_uuid TEXT NOT NULL,
+                certificate_number TEXT NOT NULL,
+                exemption_type TEXT NOT NULL,
+                jurisdiction_uuid TEXT,
+                effective_date TEXT NOT NULL,
+                expiry_date TEXT,
+                document_ref TEXT,
+                created_at TEXT NOT NULL,
+                revoked_at TEXT,
+                FOREIGN KEY (customer_uuid) REFERENCES Customers(customer_uuid),
+                FOREIGN KEY (jurisdiction_uuid) REFERENCES Tax_Jurisdictions(jurisdiction_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Transaction_Tax_Lines (
+                line_uuid TEXT PRIMARY KEY,
+                transaction_uuid TEXT NOT NULL,
+                jurisdiction_uuid TEXT,
+                jurisdiction_name TEXT NOT NULL,
+                rate REAL NOT NULL,
+                taxable_amount REAL NOT NULL,
+                tax_amount REAL NOT NULL,
+                exempt INTEGER DEFAULT 0,
+                certificate_uuid TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (transaction_uuid) REFERENCES Transactions(transaction_uuid)
+            )",
+            "ALTER TABLE Store_Locations ADD COLUMN prices_include_tax INTEGER DEFAULT 0",
+            "CREATE INDEX IF NOT EXISTS idx_tax_certificates_customer ON Tax_Exemption_Certificates(customer_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_transaction_tax_lines_txn ON Transaction_Tax_Lines(transaction_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_transaction_tax_lines_jurisdiction ON Transaction_Tax_Lines(jurisdiction_uuid)"
+        ]),
+        (31, "Multi Currency", vec![
+            "CREATE TABLE IF NOT EXISTS Currency_Settings (
+                id INTEGER PRIMARY KEY CHECK(id = 1),
+                base_currency TEXT NOT NULL DEFAULT 'USD',
+                updated_at TEXT NOT NULL
+            )",
+            "INSERT OR IGNORE INTO Currency_Settings (id, base_currency, updated_at) VALUES (1, 'USD', datetime('now'))",
+            "CREATE TABLE IF NOT EXISTS Exchange_Rates (
+                currency_code TEXT PRIMARY KEY,
+                rate_to_base REAL NOT NULL CHECK(rate_to_base > 0),
+                updated_at TEXT NOT NULL
+            )",
+            "ALTER TABLE Transactions ADD COLUMN currency TEXT",
+            "ALTER TABLE Payment_Methods ADD COLUMN tendered_currency TEXT",
+            "ALTER TABLE Payment_Methods ADD COLUMN tendered_amount REAL",
+            "ALTER TABLE Payment_Methods ADD COLUMN exchange_rate REAL",
+            "ALTER TABLE Pricing_Matrix ADD COLUMN currency TEXT DEFAULT 'USD'",
+            "ALTER TABLE Price_History ADD COLUMN currency TEXT DEFAULT 'USD'"
+        ]),
+        (32, "Customer Display Promotions", vec![
+            "CREATE TABLE IF NOT EXISTS Display_Promotions (
+                promotion_uuid TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                body TEXT,
+                image_url TEXT,
+                display_seconds INTEGER NOT NULL DEFAULT 10,
+                sort_order INTEGER NOT NULL DEFAULT 0,
+                starts_at TEXT,
+                ends_at TEXT,
+                is_active INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_display_promotions_active ON Display_Promotions(is_active, sort_order)"
+        ]),
+        (33, "Transaction Adjustments", vec![
+            "CREATE TABLE IF NOT EXISTS Transaction_Adjustments (
+                adjustment_uuid TEXT PRIMARY KEY,
+                transaction_uuid TEXT NOT NULL,
+                previous_adjustment_uuid TEXT,
+                adjustment_type TEXT NOT NULL CHECK(adjustment_type IN ('retender', 'attach_customer', 'price_correction', 'line_void')),
+                item_uuid TEXT,
+                payment_uuid TEXT,
+                total_before REAL NOT NULL,
+                total_after REAL NOT NULL,
+                amount_delta REAL NOT NULL,
+                before_values TEXT NOT NULL,
+                after_values TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                performed_by TEXT,
+                approved_by TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (transaction_uuid) REFERENCES Transactions(transaction_uuid),
+                FOREIGN KEY (previous_adjustment_uuid) REFERENCES Transaction_Adjustments(adjustment_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_transaction_adjustments_txn ON Transaction_Adjustments(transaction_uuid, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_transaction_adjustments_created ON Transaction_Adjustments(created_at)",
+            "CREATE TRIGGER IF NOT EXISTS trg_transaction_adjustments_no_update
+             BEFORE UPDATE ON Transaction_Adjustments
+             BEGIN
+                 SELECT RAISE(ABORT, 'Transaction adjustments are immutable');
+             END",
+            "CREATE TRIGGER IF NOT EXISTS trg_transaction_adjustments_no_delete
+             BEFORE DELETE ON Transaction_Adjustments
+             BEGIN
+                 SELECT RAISE(ABORT, 'Transaction adjustments are immutable');
+             END"
+        ]),
+        (34, "Consignment Workflow", vec![
+            "ALTER TABLE Consignors ADD COLUMN customer_uuid TEXT",
+            "ALTER TABLE Consignment_Items ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1",
+            "ALTER TABLE Consignment_Items ADD COLUMN quantity_sold INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE Consignment_Items ADD COLUMN expires_at TEXT",
+            "ALTER TABLE Consignment_Items ADD COLUMN returned_date TEXT",
+            "ALTER TABLE Consignment_Items ADD COLUMN notes TEXT",
+            "CREATE TABLE IF NOT EXISTS Consignment_Sales (
+                sale_uuid TEXT PRIMARY KEY,
+                consignment_uuid TEXT NOT NULL,
+                consignor_uuid TEXT NOT NULL,
+                transaction_uuid TEXT NOT NULL,
+                quantity INTEGER NOT NULL,
+                unit_price REAL NOT NULL,
+                gross_amount REAL NOT NULL,
+                commission_rate REAL NOT NULL,
+                commission_amount REAL NOT NULL,
+                consignor_share REAL NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (consignment_uuid) REFERENCES Consignment_Items(consignment_uuid),
+                FOREIGN KEY (consignor_uuid) REFERENCES Consignors(consignor_uuid),
+                FOREIGN KEY (transaction_uuid) REFERENCES Transactions(transaction_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Consignor_Payouts (
+                payout_uuid TEXT PRIMARY KEY,
+                consignor_uuid TEXT NOT NULL,
+                amount REAL NOT NULL CHECK(amount > 0),
+                method TEXT NOT NULL CHECK(method IN ('cash', 'store_credit')),
+                customer_uuid TEXT,
+                paid_by TEXT,
+                notes TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (consignor_uuid) REFERENCES Consignors(consignor_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_consignment_inventory ON Consignment_Items(inventory_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_consignment_sales_consignor ON Consignment_Sales(consignor_uuid, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_consignor_payouts_consignor ON Consignor_Payouts(consignor_uuid, created_at)"
+        ]),
+        // Purchasing: supplier orders, receiving and backorders
+        (35, "Purchase Orders", vec![
+            "ALTER TABLE Suppliers ADD COLUMN lead_time_days INTEGER DEFAULT 7",
+            "CREATE TABLE IF NOT EXISTS Purchase_Orders (
+                po_uuid TEXT PRIMARY KEY,
+                po_number TEXT NOT NULL UNIQUE,
+                supplier_uuid TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'Draft' CHECK(status IN ('Draft', 'Ordered', 'PartiallyReceived', 'Received', 'Closed', 'Cancelled')),
+                shipping_cost REAL NOT NULL DEFAULT 0,
+                other_costs REAL NOT NULL DEFAULT 0,
+                expected_date TEXT,
+                notes TEXT,
+                created_by TEXT,
+                created_at TEXT NOT NULL,
+                ordered_at TEXT,
+                closed_at TEXT,
+                FOREIGN KEY (supplier_uuid) REFERENCES Suppliers(supplier_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Purchase_Order_Lines (
+                line_uuid TEXT PRIMARY KEY,
+                po_uuid TEXT NOT NULL,
+                product_uuid TEXT NOT NULL,
+                condition TEXT NOT NULL DEFAULT 'NM',
+                variant_type TEXT,
+                quantity_ordered INTEGER NOT NULL CHECK(quantity_ordered > 0),
+                quantity_received INTEGER NOT NULL DEFAULT 0,
+                quantity_backordered INTEGER NOT NULL DEFAULT 0,
+                unit_cost REAL NOT NULL,
+                backorder_expected_date TEXT,
+                FOREIGN KEY (po_uuid) REFERENCES Purchase_Orders(po_uuid),
+                FOREIGN KEY (product_uuid) REFERENCES Global_Catalog(product_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Purchase_Order_Receipts (
+                receipt_uuid TEXT PRIMARY KEY,
+                po_uuid TEXT NOT NULL,
+                line_uuid TEXT NOT NULL,
+                inventory_uuid TEXT NOT NULL,
+                quantity INTEGER NOT NULL CHECK(quantity > 0),
+                unit_cost REAL NOT NULL,
+                landed_unit_cost REAL NOT NULL,
+                received_by TEXT,
+                received_at TEXT NOT NULL,
+                FOREIGN KEY (po_uuid) REFERENCES Purchase_Orders(po_uuid),
+                FOREIGN KEY (line_uuid) REFERENCES Purchase_Order_Lines(line_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_purchase_orders_supplier ON Purchase_Orders(supplier_uuid, status)",
+            "CREATE INDEX IF NOT EXISTS idx_po_lines_po ON Purchase_Order_Lines(po_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_po_lines_product ON Purchase_Order_Lines(product_uuid, condition)",
+            "CREATE INDEX IF NOT EXISTS idx_po_receipts_po ON Purchase_Order_Receipts(po_uuid)"
+        ]),
+        // Shrinkage: where damage/loss reports came from, plus the returns tables ReturnsService writes
+        (36, "Shrinkage Tracking", vec![
+            "ALTER TABLE Damaged_Items ADD COLUMN source TEXT NOT NULL DEFAULT 'manual'",
+            "ALTER TABLE Damaged_Items ADD COLUMN source_uuid TEXT",
+            "ALTER TABLE Damaged_Items ADD COLUMN resolved_by TEXT",
+            "ALTER TABLE Damaged_Items ADD COLUMN resolution_notes TEXT",
+            "CREATE INDEX IF NOT EXISTS idx_damaged_items_created ON Damaged_Items(created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_damaged_items_inventory ON Damaged_Items(inventory_uuid)",
+            "CREATE TABLE IF NOT EXISTS Returns (
+                return_uuid TEXT PRIMARY KEY,
+                transaction_uuid TEXT NOT NULL,
+                customer_uuid TEXT,
+                reason_code TEXT NOT NULL,
+                reason_notes TEXT,
+                subtotal REAL NOT NULL DEFAULT 0,
+                restocking_fee REAL NOT NULL DEFAULT 0,
+                refund_amount REAL NOT NULL DEFAULT 0,
+                processed_at TEXT NOT NULL,
+                FOREIGN KEY (transaction_uuid) REFERENCES Transactions(transaction_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Return_Items (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                return_uuid TEXT NOT NULL,
+                inventory_uuid TEXT NOT NULL,
+                quantity INTEGER NOT NULL,
+                original_price REAL NOT NULL,
+                refund_amount REAL NOT NULL,
+                restocking_fee REAL NOT NULL DEFAULT 0,
+                returned_to_inventory INTEGER NOT NULL DEFAULT 1,
+                FOREIGN KEY (return_uuid) REFERENCES Returns(return_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_returns_customer ON Returns(customer_uuid, processed_at)"
+        ]),
+        // Immutable ledger of every quantity change, seeded with current balances
+        (37, "Inventory Movement Ledger", vec![
+            "CREATE TABLE IF NOT EXISTS Inventory_Movements (
+                movement_uuid TEXT PRIMARY KEY,
+                inventory_uuid TEXT NOT NULL,
+                product_uuid TEXT NOT NULL,
+                movement_type TEXT NOT NULL,
+                quantity_change INTEGER NOT NULL,
+                quantity_before INTEGER NOT NULL,
+                quantity_after INTEGER NOT NULL,
+                source_uuid TEXT,
+                user_uuid TEXT,
+                terminal_id TEXT NOT NULL,
+                notes TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_inventory_movements_item ON Inventory_Movements(inventory_uuid, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_inventory_movements_product ON Inventory_Movements(product_uuid, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_inventory_movements_source ON Inventory_Movements(source_uuid)",
+            "INSERT INTO Inventory_Movements
+             (movement_uuid, inventory_uuid, product_uuid, movement_type, quantity_change, quantity_before, quantity_after, terminal_id, created_at)
+             SELECT lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-8' || substr(hex(randomblob(2)), 2) || '-' || hex(randomblob(6))),
+                    inventory_uuid, product_uuid, 'opening_balance', quantity_on_hand, 0, quantity_on_hand, 'migration',
+                    strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+             FROM Local_Inventory
+             WHERE quantity_on_hand <> 0",
+            "CREATE TRIGGER IF NOT EXISTS trg_inventory_movements_no_update
+             BEFORE UPDATE ON Inventory_Movements
+             BEGIN
+                 SELECT RAISE(ABORT, 'Inventory movements are immutable');
+             END",
+            "CREATE TRIGGER IF NOT EXISTS trg_inventory_movements_no_delete
+             BEFORE DELETE ON Inventory_Movements
+             BEGIN
+                 SELECT RAISE(ABORT, 'Inventory movements are immutable');
+             END"
+        ]),
+        // Cycle counts: scheduled plans, ABC classes and multi-counter sessions
+        (38, "Cycle Counts", vec![
+            "CREATE TABLE IF NOT EXISTS Count_Plans (
+                plan_uuid TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                location_tag TEXT NOT NULL,
+                a_interval_days INTEGER NOT NULL DEFAULT 30,
+                b_interval_days INTEGER NOT NULL DEFAULT 90,
+                c_interval_days INTEGER NOT NULL DEFAULT 180,
+                a_share REAL NOT NULL DEFAULT 0.8,
+                b_share REAL NOT NULL DEFAULT 0.95,
+                lookback_days INTEGER NOT NULL DEFAULT 90,
+                recount_units INTEGER NOT NULL DEFAULT 3,
+                recount_value REAL NOT NULL DEFAULT 25,
+                blind INTEGER NOT NULL DEFAULT 1,
+                run_every_days INTEGER NOT NULL DEFAULT 7,
+                next_run_at TEXT NOT NULL,
+                last_run_at TEXT,
+                is_active INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Product_ABC_Classes (
+                product_uuid TEXT PRIMARY KEY,
+                abc_class TEXT NOT NULL CHECK (abc_class IN ('A', 'B', 'C')),
+                score REAL NOT NULL DEFAULT 0,
+                on_hand_value REAL NOT NULL DEFAULT 0,
+                sales_value REAL NOT NULL DEFAULT 0,
+                classified_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Count_Sessions (
+                session_uuid TEXT PRIMARY KEY,
+                plan_uuid TEXT,
+                location_tag TEXT NOT NULL,
+                bin_location TEXT,
+                status TEXT NOT NULL DEFAULT 'Counting'
+                    CHECK (status IN ('Counting', 'Recount', 'Review', 'Approved', 'Cancelled')),
+                round INTEGER NOT NULL DEFAULT 1,
+                blind INTEGER NOT NULL DEFAULT 1,
+                recount_units INTEGER NOT NULL DEFAULT 3,
+                recount_value REAL NOT NULL DEFAULT 25,
+                created_by TEXT,
+                approved_by TEXT,
+                created_at TEXT NOT NULL,
+                reviewed_at TEXT,
+                approved_at TEXT,
+                notes TEXT,
+                FOREIGN KEY (plan_uuid) REFERENCES Count_Plans(plan_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Count_Session_Lines (
+                line_uuid TEXT PRIMARY KEY,
+                session_uuid TEXT NOT NULL,
+                inventory_uuid TEXT NOT NULL,
+                product_uuid TEXT NOT NULL,
+                abc_class TEXT,
+                expected_quantity INTEGER NOT NULL,
+                unit_value REAL NOT NULL DEFAULT 0,
+                counted_quantity INTEGER,
+                variance INTEGER,
+                needs_recount INTEGER NOT NULL DEFAULT 0,
+                rounds INTEGER NOT NULL DEFAULT 0,
+                FOREIGN KEY (session_uuid) REFERENCES Count_Sessions(session_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Count_Entries (
+                entry_uuid TEXT PRIMARY KEY,
+                line_uuid TEXT NOT NULL,
+                session_uuid TEXT NOT NULL,
+                round INTEGER NOT NULL,
+                counted_by TEXT NOT NULL DEFAULT '',
+                quantity INTEGER NOT NULL,
+                counted_at TEXT NOT NULL,
+                FOREIGN KEY (line_uuid) REFERENCES Count_Session_Lines(line_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_count_sessions_status ON Count_Sessions(status, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_count_session_lines_session ON Count_Session_Lines(session_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_count_session_lines_inventory ON Count_Session_Lines(inventory_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_count_entries_line ON Count_Entries(line_uuid, round)"
+        ]),
+        // Bills of materials for sealed product and kits, and the conversions made with them
+        (39, "Product Kitting", vec![
+            "CREATE TABLE IF NOT EXISTS Product_Boms (
+                product_uuid TEXT PRIMARY KEY,
+                bom_type TEXT NOT NULL CHECK (bom_type IN ('Sealed', 'Kit')),
+                notes TEXT,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (product_uuid) REFERENCES Global_Catalog(product_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Product_Bom_Components (
+                product_uuid TEXT NOT NULL,
+                component_product_uuid TEXT NOT NULL,
+                quantity INTEGER NOT NULL CHECK (quantity > 0),
+                cost_weight REAL,
+                PRIMARY KEY (product_uuid, component_product_uuid),
+                FOREIGN KEY (product_uuid) REFERENCES Product_Boms(product_uuid),
+                FOREIGN KEY (component_product_uuid) REFERENCES Global_Catalog(product_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Inventory_Conversions (
+                conversion_uuid TEXT PRIMARY KEY,
+                conversion_type TEXT NOT NULL CHECK (conversion_type IN ('Break', 'Assemble', 'KitSale')),
+                product_uuid TEXT NOT NULL,
+                quantity INTEGER NOT NULL,
+                total_cost REAL NOT NULL DEFAULT 0,
+                transaction_uuid TEXT,
+                performed_by TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Inventory_Conversion_Lines (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                conversion_uuid TEXT NOT NULL,
+                inventory_uuid TEXT NOT NULL,
+                product_uuid TEXT NOT NULL,
+                quantity_change INTEGER NOT NULL,
+                unit_cost REAL NOT NULL DEFAULT 0,
+                FOREIGN KEY (conversion_uuid) REFERENCES Inventory_Conversions(conversion_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_bom_components_component ON Product_Bom_Components(component_product_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_conversions_product ON Inventory_Conversions(product_uuid, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_conversions_transaction ON Inventory_Conversions(transaction_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_conversion_lines_conversion ON Inventory_Conversion_Lines(conversion_uuid)"
+        ]),
+        // Bulk inventory import: saved column mappings, an audit of committed runs and catalog matching
+        (40, "Inventory Import", vec![
+            "CREATE TABLE IF NOT EXISTS Import_Profiles (
+                name TEXT PRIMARY KEY,
+                mapping TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Inventory_Imports (
+                import_uuid TEXT PRIMARY KEY,
+                profile TEXT NOT NULL,
+                format TEXT NOT NULL,
+                mode TEXT NOT NULL,
+                rows INTEGER NOT NULL,
+                created INTEGER NOT NULL,
+                updated INTEGER NOT NULL,
+                unchanged INTEGER NOT NULL,
+                imported_by TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_products_set_number ON Global_Catalog(set_code, collector_number)"
+        ]),
+        // Storage hierarchy: rooms, cases, shelves, rows and bins within a store, with pick order
+        (41, "Storage Locations", vec![
+            "CREATE TABLE IF NOT EXISTS Storage_Locations (
+                storage_uuid TEXT PRIMARY KEY,
+                location_tag TEXT NOT NULL,
+                parent_uuid TEXT,
+                kind TEXT NOT NULL CHECK(kind IN ('Room', 'Case', 'Shelf', 'Row', 'Bin')),
+                code TEXT NOT NULL,
+                name TEXT,
+                full_code TEXT NOT NULL,
+                capacity INTEGER,
+                pick_sequence INTEGER NOT NULL DEFAULT 0,
+                walk_key TEXT NOT NULL,
+                is_active INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (parent_uuid) REFERENCES Storage_Locations(storage_uuid)
+            )",
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_storage_full_code ON Storage_Locations(location_tag, full_code)",
+            "CREATE INDEX IF NOT EXISTS idx_storage_walk ON Storage_Locations(location_tag, walk_key)",
+            "ALTER TABLE Local_Inventory ADD COLUMN storage_uuid TEXT",
+            "CREATE INDEX IF NOT EXISTS idx_inventory_storage ON Local_Inventory(storage_uuid)",
+            "CREATE TABLE IF NOT EXISTS Bin_Moves (
+                move_uuid TEXT PRIMARY KEY,
+                source_inventory_uuid TEXT NOT NULL,
+                target_inventory_uuid TEXT NOT NULL,
+                product_uuid TEXT NOT NULL,
+                from_storage_uuid TEXT,
+                to_storage_uuid TEXT NOT NULL,
+                quantity INTEGER NOT NULL,
+                moved_by TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_bin_moves_product ON Bin_Moves(product_uuid, created_at)"
+        ]),
+        // Store-to-store transfers that ship through a transit location and receive with discrepancies
+        (42, "Inventory Transfers", vec![
+            "ALTER TABLE Store_Locations ADD COLUMN location_type TEXT NOT NULL DEFAULT 'retail'",
+            "CREATE TABLE IF NOT EXISTS Inventory_Transfers (
+                transfer_uuid TEXT PRIMARY KEY,
+                source_location TEXT NOT NULL,
+                target_location TEXT NOT NULL,
+                status TEXT NOT NULL CHECK(status IN ('pending', 'approved', 'in_transit', 'partially_received', 'received', 'cancelled')),
+                requested_by TEXT NOT NULL,
+                approved_by TEXT,
+                shipped_by TEXT,
+                shipped_at TEXT,
+                received_by TEXT,
+                received_at TEXT,
+                notes TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Transfer_Items (
+                transfer_item_uuid TEXT PRIMARY KEY,
+                transfer_uuid TEXT NOT NULL,
+                product_uuid TEXT NOT NULL,
+                inventory_uuid TEXT,
+                quantity INTEGER NOT NULL,
+                quantity_shipped INTEGER NOT NULL DEFAULT 0,
+                quantity_received INTEGER NOT NULL DEFAULT 0,
+                whole_pile INTEGER NOT NULL DEFAULT 0,
+                transit_inventory_uuid TEXT,
+                shortage_resolution TEXT,
+                FOREIGN KEY (transfer_uuid) REFERENCES Inventory_Transfers(transfer_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_transfers_status ON Inventory_Transfers(status, updated_at)",
+            "CREATE INDEX IF NOT EXISTS idx_transfer_items_transfer ON Transfer_Items(transfer_uuid)"
+        ]),
+        // Tournament rounds: Swiss pairings, match results, drops and top cut
+        (43, "Tournament Rounds", vec![
+            "ALTER TABLE Events ADD COLUMN swiss_rounds INTEGER",
+            "ALTER TABLE Events ADD COLUMN top_cut INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE Events ADD COLUMN round_minutes INTEGER NOT NULL DEFAULT 50",
+            "ALTER TABLE Event_Participants ADD COLUMN dropped_after_round INTEGER",
+            "CREATE TABLE IF NOT EXISTS Event_Rounds (
+                round_uuid TEXT PRIMARY KEY,
+                event_uuid TEXT NOT NULL,
+                round_number INTEGER NOT NULL,
+                stage TEXT NOT NULL CHECK(stage IN ('swiss', 'top_cut')),
+                status TEXT NOT NULL CHECK(status IN ('paired', 'in_progress', 'completed')),
+                time_limit_minutes INTEGER NOT NULL,
+                started_at TEXT,
+                ends_at TEXT,
+                completed_at TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (event_uuid) REFERENCES Events(event_uuid),
+                UNIQUE(event_uuid, round_number)
+            )",
+            "CREATE TABLE IF NOT EXISTS Event_Matches (
+                match_uuid TEXT PRIMARY KEY,
+                round_uuid TEXT NOT NULL,
+                event_uuid TEXT NOT NULL,
+                table_number INTEGER NOT NULL,
+                player1_uuid TEXT NOT NULL,
+                player2_uuid TEXT,
+                player1_wins INTEGER NOT NULL DEFAULT 0,
+                player2_wins INTEGER NOT NULL DEFAULT 0,
+                draws INTEGER NOT NULL DEFAULT 0,
+                reported_at TEXT,
+                FOREIGN KEY (round_uuid) REFERENCES Event_Rounds(round_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_event_matches_round ON Event_Matches(round_uuid, table_number)",
+            "CREATE INDEX IF NOT EXISTS idx_event_matches_event ON Event_Matches(event_uuid)"
+        ]),
+        // Event prizes: payout structures, paid prizes and entry fee accounting
+        (44, "Event Prizes", vec![
+            "ALTER TABLE Event_Participants ADD COLUMN fee_paid REAL NOT NULL DEFAULT 0",
+            "ALTER TABLE Event_Participants ADD COLUMN payment_method TEXT",
+            "UPDATE Event_Participants SET fee_paid = COALESCE((SELECT entry_fee FROM Events e WHERE e.event_uuid = Event_Participants.event_uuid), 0),
+                    payment_method = 'credit'
+             WHERE paid = 1 AND customer_uuid IS NOT NULL
+               AND event_uuid IN (SELECT event_uuid FROM Events WHERE entry_fee > 0)",
+            "CREATE TABLE IF NOT EXISTS Event_Prize_Tiers (
+                tier_uuid TEXT PRIMARY KEY,
+                event_uuid TEXT NOT NULL,
+                basis TEXT NOT NULL CHECK(basis IN ('placement', 'record')),
+                place_from INTEGER,
+                place_to INTEGER,
+                min_match_points INTEGER,
+                prize_type TEXT NOT NULL CHECK(prize_type IN ('credit', 'product')),
+                credit_amount REAL NOT NULL DEFAULT 0,
+                product_uuid TEXT,
+                condition TEXT,
+                quantity INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (event_uuid) REFERENCES Events(event_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Event_Prize_Payouts (
+                payout_uuid TEXT PRIMARY KEY,
+                event_uuid TEXT NOT NULL,
+                tier_uuid TEXT NOT NULL,
+                participant_uuid TEXT NOT NULL,
+                customer_uuid TEXT,
+                prize_type TEXT NOT NULL,
+                credit_amount REAL NOT NULL DEFAULT 0,
+                product_uuid TEXT,
+                quantity INTEGER NOT NULL DEFAULT 0,
+                transaction_uuid TEXT,
+                cost REAL NOT NULL DEFAULT 0,
+                user_uuid TEXT,
+                paid_at TEXT NOT NULL,
+                FOREIGN KEY (tier_uuid) REFERENCES Event_Prize_Tiers(tier_uuid),
+                UNIQUE(tier_uuid, participant_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_prize_tiers_event ON Event_Prize_Tiers(event_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_prize_payouts_event ON Event_Prize_Payouts(event_uuid)"
+        ]),
+        // Event registration: waitlists, check-in, refunds and recurring templates
+        (45, "Event Registration", vec![
+            "ALTER TABLE Event_Participants ADD COLUMN status TEXT NOT NULL DEFAULT 'registered'",
+            "ALTER TABLE Event_Participants ADD COLUMN checked_in_at TEXT",
+            "ALTER TABLE Event_Participants ADD COLUMN refunded_amount REAL NOT NULL DEFAULT 0",
+            "ALTER TABLE Event_Participants ADD COLUMN cash_paid REAL NOT NULL DEFAULT 0",
+            "UPDATE Event_Participants SET cash_paid = fee_paid WHERE payment_method = 'cash'",
+            "ALTER TABLE Events ADD COLUMN template_uuid TEXT",
+            "CREATE TABLE IF NOT EXISTS Event_Templates (
+                template_uuid TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                event_type TEXT NOT NULL,
+                entry_fee REAL NOT NULL DEFAULT 0,
+                max_participants INTEGER,
+                weekday INTEGER NOT NULL CHECK(weekday BETWEEN 0 AND 6),
+                start_time TEXT NOT NULL,
+                interval_weeks INTEGER NOT NULL DEFAULT 1,
+                starts_on TEXT NOT NULL,
+                active INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_event_participants_status ON Event_Participants(event_uuid, status)",
+            "CREATE INDEX IF NOT EXISTS idx_events_template ON Events(template_uuid, date)"
+        ]),
+        // Loyalty: append-only points ledger, earn rates and spend-based tiers
+        (46, "Loyalty Program", vec![
+            // Rebuilt to widen the method_type CHECK for points (and gift cards)
+            "DROP TABLE IF EXISTS Payment_Methods_New",
+            "CREATE TABLE Payment_Methods_New (
+                payment_uuid TEXT PRIMARY KEY,
+                transaction_uuid TEXT NOT NULL,
+                method_type TEXT NOT NULL CHECK(method_type IN ('Cash', 'Card', 'StoreCredit', 'Check', 'GiftCard', 'LoyaltyPoints', 'Other')),
+                amount REAL NOT NULL,
+                reference TEXT,
+                card_last_four TEXT,
+                auth_code TEXT,
+                created_at TEXT NOT NULL,
+                tendered_currency TEXT,
+                tendered_amount REAL,
+                exchange_rate REAL,
+                FOREIGN KEY (transaction_uuid) REFERENCES Transactions(transaction_uuid)
+            )",
+            "INSERT INTO Payment_Methods_New
+                (payment_uuid, transaction_uuid, method_type, amount, reference, card_last_four, auth_code, created_at,
+                 tendered_currency, tendered_amount, exchange_rate)
+             SELECT payment_uuid, transaction_uuid, method_type, amount, reference, card_last_four, auth_code, created_at,
+                    tendered_currency, tendered_amount, exchange_rate
+             FROM Payment_Methods",
+            "DROP TABLE Payment_Methods",
+            "ALTER TABLE Payment_Methods_New RENAME TO Payment_Methods",
+            "CREATE INDEX IF NOT EXISTS idx_payment_methods_transaction ON Payment_Methods(transaction_uuid)",
+            "CREATE TABLE IF NOT EXISTS Loyalty_Settings (
+                id INTEGER PRIMARY KEY CHECK(id = 1),
+                points_per_dollar REAL NOT NULL DEFAULT 1,
+                point_value REAL NOT NULL DEFAULT 0.01,
+                expiry_months INTEGER NOT NULL DEFAULT 12,
+                tier_window_days INTEGER NOT NULL DEFAULT 365,
+                updated_at TEXT NOT NULL
+            )",
+            "INSERT OR IGNORE INTO Loyalty_Settings (id, updated_at) VALUES (1, datetime('now'))",
+            "CREATE TABLE IF NOT EXISTS Loyalty_Category_Rates (
+                category TEXT PRIMARY KEY,
+                points_per_dollar REAL NOT NULL CHECK(points_per_dollar >= 0),
+                updated_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Loyalty_Tiers (
+                tier_name TEXT PRIMARY KEY,
+                min_spend REAL NOT NULL CHECK(min_spend >= 0),
+                created_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Loyalty_Ledger (
+                entry_uuid TEXT PRIMARY KEY,
+                customer_uuid TEXT NOT NULL,
+                entry_type TEXT NOT NULL CHECK(entry_type IN ('earn', 'redeem', 'reverse', 'expire', 'adjust')),
+                points INTEGER NOT NULL,
+                transaction_uuid TEXT,
+                expires_at TEXT,
+                notes TEXT,
+                user_uuid TEXT,
+                node_id TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (customer_uuid) REFERENCES Customers(customer_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Loyalty_Tier_History (
+                change_uuid TEXT PRIMARY KEY,
+                customer_uuid TEXT NOT NULL,
+                old_tier TEXT,
+                new_tier TEXT NOT NULL,
+                rolling_spend REAL NOT NULL,
+                changed_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_loyalty_ledger_customer ON Loyalty_Ledger(customer_uuid, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_loyalty_ledger_transaction ON Loyalty_Ledger(transaction_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_loyalty_tier_history_customer ON Loyalty_Tier_History(customer_uuid, changed_at)"
+        ]),
+        // Customer profiles: merges and privacy requests
+        (47, "Customer Merge and Privacy", vec![
+            "ALTER TABLE Customers ADD COLUMN merged_into TEXT",
+            "ALTER TABLE Customers ADD COLUMN anonymized_at TEXT",
+            "CREATE TABLE IF NOT EXISTS Customer_Merges (
+                merge_uuid TEXT PRIMARY KEY,
+                survivor_uuid TEXT NOT NULL,
+                merged_uuid TEXT NOT NULL,
+                merged_snapshot TEXT NOT NULL,
+                rows_moved INTEGER NOT NULL DEFAULT 0,
+                store_credit_moved REAL NOT NULL DEFAULT 0,
+                points_moved INTEGER NOT NULL DEFAULT 0,
+                user_uuid TEXT,
+                merged_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Customer_Privacy_Requests (
+                request_uuid TEXT PRIMARY KEY,
+                customer_uuid TEXT NOT NULL,
+                request_type TEXT NOT NULL CHECK(request_type IN ('export', 'anonymize')),
+                reason TEXT,
+                user_uuid TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_customers_email ON Customers(email)",
+            "CREATE INDEX IF NOT EXISTS idx_customers_phone ON Customers(phone)",
+            "CREATE INDEX IF NOT EXISTS idx_customer_merges_survivor ON Customer_Merges(survivor_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_privacy_requests_customer ON Customer_Privacy_Requests(customer_uuid)"
+        ]),
+        (48, "Wants Matching", vec![
+            "ALTER TABLE Wants_Items ADD COLUMN variant_type TEXT",
+            "ALTER TABLE Wants_Items ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1",
+            "ALTER TABLE Wants_Items ADD COLUMN quantity_fulfilled INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE Wants_Items ADD COLUMN hold_hours INTEGER",
+            "CREATE TABLE IF NOT EXISTS Wants_Matches (
+                match_uuid TEXT PRIMARY KEY,
+                item_uuid TEXT NOT NULL,
+                customer_uuid TEXT NOT NULL,
+                product_uuid TEXT NOT NULL,
+                inventory_uuid TEXT NOT NULL,
+                quantity INTEGER NOT NULL,
+                unit_price REAL,
+                status TEXT NOT NULL CHECK(status IN ('Open', 'Held', 'Fulfilled', 'Declined', 'Expired')),
+                hold_uuid TEXT,
+                notified_via TEXT,
+                notified_at TEXT,
+                transaction_uuid TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_wants_matches_item ON Wants_Matches(item_uuid, status)",
+            "CREATE INDEX IF NOT EXISTS idx_wants_matches_customer ON Wants_Matches(customer_uuid, status)"
+        ]),
+        // Customer messaging: consent, templates and the delivery log
+        (49, "Notification Preferences and Templates", vec![
+            "ALTER TABLE Notifications ADD COLUMN customer_uuid TEXT",
+            "ALTER TABLE Notifications ADD COLUMN channel TEXT",
+            "ALTER TABLE Notifications ADD COLUMN recipient TEXT",
+            "ALTER TABLE Notifications ADD COLUMN template_key TEXT",
+            "ALTER TABLE Notifications ADD COLUMN category TEXT",
+            "ALTER TABLE Notifications ADD COLUMN status TEXT",
+            "ALTER TABLE Notifications ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0",
+            "ALTER TABLE Notifications ADD COLUMN last_error TEXT",
+            "ALTER TABLE Notifications ADD COLUMN next_attempt_at TEXT",
+            "ALTER TABLE Notifications ADD COLUMN sent_at TEXT",
+            "ALTER TABLE Notifications ADD COLUMN bounced_at TEXT",
+            "ALTER TABLE Events ADD COLUMN reminder_sent INTEGER NOT NULL DEFAULT 0",
+            "CREATE TABLE IF NOT EXISTS Customer_Notification_Preferences (
+                customer_uuid TEXT PRIMARY KEY,
+                email_enabled INTEGER NOT NULL DEFAULT 1,
+                sms_enabled INTEGER NOT NULL DEFAULT 1,
+                email_marketing_consent_at TEXT,
+                sms_marketing_consent_at TEXT,
+                unsubscribe_token TEXT NOT NULL UNIQUE,
+                unsubscribed_at TEXT,
+                updated_at TEXT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS Notification_Templates (
+                template_key TEXT NOT NULL,
+                channel TEXT NOT NULL CHECK(channel IN ('email', 'sms')),
+                subject TEXT NOT NULL DEFAULT '',
+                body TEXT NOT NULL,
+                updated_by TEXT,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (template_key, channel)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_notifications_customer ON Notifications(customer_uuid, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_notifications_retry ON Notifications(status, next_attempt_at)",
+            "CREATE INDEX IF NOT EXISTS idx_notifications_recipient ON Notifications(recipient, status)"
+        ]),
+        // Photos attached to products, inventory and trade-ins; bytes live
+        // on disk by content hash
+        (50, "Media Storage", vec![
+            "CREATE TABLE IF NOT EXISTS Media (
+                media_uuid TEXT PRIMARY KEY,
+                content_hash TEXT NOT NULL,
+                mime_type TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                original_filename TEXT,
+                entity_type TEXT NOT NULL CHECK(entity_type IN ('product', 'inventory', 'trade_in')),
+                entity_uuid TEXT NOT NULL,
+                caption TEXT,
+                sort_order INTEGER NOT NULL DEFAULT 0,
+                uploaded_by TEXT,
+                created_at TEXT NOT NULL,
+                deleted_at TEXT
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_media_entity ON Media(entity_type, entity_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_media_hash ON Media(content_hash)"
+        ]),
+        // Grading submissions, and the provenance log serialized items use
+        (51, "Grading Submissions", vec![
+            "CREATE TABLE IF NOT EXISTS Item_Provenance (
+                entry_uuid TEXT PRIMARY KEY,
+                inventory_uuid TEXT NOT NULL,
+                event_type TEXT NOT NULL,
+                description TEXT NOT NULL,
+                event_date TEXT NOT NULL,
+                source TEXT,
+                price REAL
+            )",
+            "CREATE TABLE IF NOT EXISTS Grading_Submissions (
+                submission_uuid TEXT PRIMARY KEY,
+                submission_number TEXT NOT NULL UNIQUE,
+                grader TEXT NOT NULL,
+                service_tier TEXT NOT NULL,
+                grader_reference TEXT,
+                status TEXT NOT NULL,
+                fee_per_item REAL NOT NULL DEFAULT 0,
+                shipping_cost REAL NOT NULL DEFAULT 0,
+                insurance_cost REAL NOT NULL DEFAULT 0,
+                notes TEXT,
+                created_by TEXT,
+                created_at TEXT NOT NULL,
+                shipped_at TEXT,
+                returned_at TEXT,
+                completed_at TEXT
+            )",
+            "CREATE TABLE IF NOT EXISTS Grading_Submission_Items (
+                item_uuid TEXT PRIMARY KEY,
+                submission_uuid TEXT NOT NULL,
+                product_uuid TEXT NOT NULL,
+                source_inventory_uuid TEXT NOT NULL,
+                inventory_uuid TEXT NOT NULL,
+                source_location TEXT NOT NULL,
+                declared_value REAL NOT NULL,
+                raw_cost REAL,
+                outcome TEXT NOT NULL DEFAULT 'pending',
+                grade TEXT,
+                cert_number TEXT,
+                sub_grades TEXT,
+                upcharge REAL NOT NULL DEFAULT 0,
+                graded_value REAL,
+                grading_cost REAL,
+                resolved_at TEXT,
+                FOREIGN KEY (submission_uuid) REFERENCES Grading_Submissions (submission_uuid)
+            )",
+            "CREATE TABLE IF NOT EXISTS Grading_Submission_Events (
+                event_uuid TEXT PRIMARY KEY,
+                submission_uuid TEXT NOT NULL,
+                status TEXT NOT NULL,
+                notes TEXT,
+                user_uuid TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (submission_uuid) REFERENCES Grading_Submissions (submission_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_provenance_inventory ON Item_Provenance(inventory_uuid, event_date)",
+            "CREATE INDEX IF NOT EXISTS idx_grading_submissions_status ON Grading_Submissions(status, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_grading_items_submission ON Grading_Submission_Items(submission_uuid)",
+            "CREATE INDEX IF NOT EXISTS idx_grading_events_submission ON Grading_Submission_Events(submission_uuid, created_at)"
+        ]),
+        (52, "Cert Verifications", vec![
+            "CREATE TABLE IF NOT EXISTS Cert_Verifications (
+                verification_uuid TEXT PRIMARY KEY,
+                inventory_uuid TEXT NOT NULL,
+                grader TEXT NOT NULL,
+                cert_number TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                status TEXT NOT NULL,
+                flags TEXT NOT NULL DEFAULT '[]',
+                official TEXT,
+                verified_by TEXT,
+                verified_at TEXT NOT NULL,
+                FOREIGN KEY (inventory_uuid) REFERENCES Local_Inventory (inventory_uuid)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_cert_verifications_inventory ON Cert_Verifications(inventory_uuid, verified_at)",
+            "CREATE INDEX IF NOT EXISTS idx_cert_verifications_status ON Cert_Verifications(status, verified_at)"
+        ]),
     ]
 }
@@ -13,6 +13,7 @@ use repositories::auth::AuthRepository;
 use repositories::customers::CustomerRepository;
 use repositories::events::EventRepository;
 use repositories::inventory::InventoryRepository;
+use repositories::movements::MovementRepository;
 use repositories::pricing::PricingRepository;
 use repositories::products::ProductRepository;
 use repositories::sync::SyncRepository;
@@ -22,6 +23,7 @@ pub struct Database {
     pub pool: SqlitePool,
     pub products: ProductRepository,
     pub inventory: InventoryRepository,
+    pub movements: MovementRepository,
     pub transactions: TransactionRepository,
     pub customers: CustomerRepository,
     pub events: EventRepository,
@@ -55,6 +57,7 @@ impl Database {
             pool: pool.clone(),
             products: ProductRepository::new(pool.clone(), sync_repo.clone()),
             inventory: InventoryRepository::new(pool.clone(), sync_repo.clone()),
+            movements: MovementRepository::new(pool.clone()),
             transactions: TransactionRepository::new(pool.clone(), node_id.clone()),
             customers: CustomerRepository::new(pool.clone(), sync_repo.clone()),
             events: EventRepository::new(pool.clone(), sync_repo.clone()),
@@ -426,7 +429,15 @@ impl Database {
                         if let Ok(i) =
                             serde_json::from_str::<crate::core::InventoryItem>(&state_data)
                         {
-                            if let Err(e) = self.inventory.insert_with_tx(&mut tx, &i).await {
+                            let source = repositories::movements::MovementSource::new(
+                                repositories::movements::MovementType::SyncMerge,
+                                Uuid::parse_str(conflict_uuid).ok(),
+                                None,
+                                &self.node_id,
+                            );
+                            if let Err(e) =
+                                self.inventory.insert_with_tx(&mut tx, &i, &source).await
+                            {
                                 tracing::error!("Failed to apply resolved inventory: {}", e);
                                 return Err(e);
                             }
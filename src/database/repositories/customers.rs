@@ -36,7 +36,7 @@ impl CustomerRepository {
     }
 
     pub async fn get_all(&self) -> Result<Vec<Customer>> {
-        let rows = sqlx::query("SELECT customer_uuid, name, email, phone, store_credit, tier, created_at FROM Customers")
+        let rows = sqlx::query("SELECT customer_uuid, name, email, phone, store_credit, tier, created_at FROM Customers WHERE deleted_at IS NULL")
             .fetch_all(&self.pool)
             .await
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
@@ -47,10 +47,10 @@ impl CustomerRepository {
             let customer_uuid_str: String = row.try_get("customer_uuid").unwrap_or_default();
             let customer_uuid = Uuid::parse_str(&customer_uuid_str).unwrap_or_default();
             let name: String = row.try_get("name").unwrap_or_default();
-            let email: Option<String> = row.try_get("email").ok();
-            let phone: Option<String> = row.try_get("phone").ok();
+            let email: Option<String> = row.try_get("email").ok().flatten();
+            let phone: Option<String> = row.try_get("phone").ok().flatten();
             let store_credit: f64 = row.try_get("store_credit").unwrap_or_default();
-            let tier: Option<String> = row.try_get("tier").ok();
+            let tier: Option<String> = row.try_get("tier").ok().flatten();
             let created_at_str: String = row.try_get("created_at").unwrap_or_default();
             let created_at =
                 chrono::DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&chrono::Utc);
@@ -79,10 +79,10 @@ impl CustomerRepository {
             let customer_uuid_str: String = row.try_get("customer_uuid").unwrap_or_default();
             let customer_uuid = Uuid::parse_str(&customer_uuid_str).unwrap_or_default();
             let name: String = row.try_get("name").unwrap_or_default();
-            let email: Option<String> = row.try_get("email").ok();
-            let phone: Option<String> = row.try_get("phone").ok();
+            let email: Option<String> = row.try_get("email").ok().flatten();
+            let phone: Option<String> = row.try_get("phone").ok().flatten();
             let store_credit: f64 = row.try_get("store_credit").unwrap_or_default();
-            let tier: Option<String> = row.try_get("tier").ok();
+            let tier: Option<String> = row.try_get("tier").ok().flatten();
             let created_at_str: String = row.try_get("created_at").unwrap_or_default();
             let created_at =
                 chrono::DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&chrono::Utc);
@@ -108,17 +108,66 @@ impl CustomerRepository {
             .await
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
+        self.update_store_credit_with_tx(&mut tx, customer_uuid, amount)
+            .await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Adjust store credit inside the caller's transaction
+    pub async fn update_store_credit_with_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        customer_uuid: Uuid,
+        amount: f64,
+    ) -> Result<()> {
         sqlx::query("UPDATE Customers SET store_credit = store_credit + ? WHERE customer_uuid = ?")
             .bind(amount)
             .bind(customer_uuid.to_string())
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
+        self.log_customer_with_tx(tx, customer_uuid).await
+    }
+
+    /// Set the customer's tier (the name pricing rules match on)
+    pub async fn update_tier(&self, customer_uuid: Uuid, tier: &str) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("UPDATE Customers SET tier = ? WHERE customer_uuid = ?")
+            .bind(tier)
+            .bind(customer_uuid.to_string())
             .execute(&mut *tx)
             .await
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
-        // Fetch for logging
+        self.log_customer_with_tx(&mut tx, customer_uuid).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Log the customer's current row for sync
+    pub async fn log_customer_with_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        customer_uuid: Uuid,
+    ) -> Result<()> {
         let row = sqlx::query("SELECT customer_uuid, name, email, phone, store_credit, tier, created_at FROM Customers WHERE customer_uuid = ?")
             .bind(customer_uuid.to_string())
-            .fetch_optional(&mut *tx)
+            .fetch_optional(&mut **tx)
             .await
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
@@ -126,10 +175,10 @@ impl CustomerRepository {
             let customer_uuid_str: String = row.try_get("customer_uuid").unwrap_or_default();
             let customer_uuid = Uuid::parse_str(&customer_uuid_str).unwrap_or_default();
             let name: String = row.try_get("name").unwrap_or_default();
-            let email: Option<String> = row.try_get("email").ok();
-            let phone: Option<String> = row.try_get("phone").ok();
+            let email: Option<String> = row.try_get("email").ok().flatten();
+            let phone: Option<String> = row.try_get("phone").ok().flatten();
             let store_credit: f64 = row.try_get("store_credit").unwrap_or_default();
-            let tier: Option<String> = row.try_get("tier").ok();
+            let tier: Option<String> = row.try_get("tier").ok().flatten();
             let created_at_str: String = row.try_get("created_at").unwrap_or_default();
             let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
                 .unwrap_or_default()
@@ -147,7 +196,7 @@ impl CustomerRepository {
 
             self.sync
                 .log_change_with_tx(
-                    &mut tx,
+                    tx,
                     &customer_uuid.to_string(),
                     "Customer",
                     "Update",
@@ -156,10 +205,6 @@ impl CustomerRepository {
                 .await?;
         }
 
-        tx.commit()
-            .await
-            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
-
         Ok(())
     }
 
@@ -190,13 +235,17 @@ impl CustomerRepository {
 
         for item in &list.items {
             sqlx::query(
-                "INSERT INTO Wants_Items (item_uuid, wants_list_uuid, product_uuid, min_condition, max_price, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+                "INSERT INTO Wants_Items (item_uuid, wants_list_uuid, product_uuid, min_condition, max_price, variant_type, quantity, quantity_fulfilled, hold_hours, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             )
             .bind(item.item_uuid.to_string())
             .bind(list.wants_list_uuid.to_string())
             .bind(item.product_uuid.to_string())
             .bind(format!("{:?}", item.min_condition))
             .bind(item.max_price)
+            .bind(item.variant_type.as_ref().map(|v| format!("{:?}", v)))
+            .bind(item.quantity.max(1))
+            .bind(item.quantity_fulfilled)
+            .bind(item.hold_hours)
             .bind(item.created_at.to_rfc3339())
             .execute(&mut *tx)
             .await
@@ -249,56 +298,72 @@ impl CustomerRepository {
     }
 
     async fn get_wants_items(&self, wants_list_uuid: Uuid) -> Result<Vec<WantsItem>> {
-        let rows = sqlx::query("SELECT item_uuid, product_uuid, min_condition, max_price, created_at FROM Wants_Items WHERE wants_list_uuid = ?")
+        let rows = sqlx::query("SELECT item_uuid, product_uuid, min_condition, max_price, variant_type, quantity, quantity_fulfilled, hold_hours, created_at FROM Wants_Items WHERE wants_list_uuid = ? ORDER BY created_at")
             .bind(wants_list_uuid.to_string())
             .fetch_all(&self.pool)
             .await
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
-        let mut items = Vec::new();
-        for row in rows {
-            let item_uuid_str: String = row.try_get("item_uuid").unwrap_or_default();
-            let item_uuid = Uuid::parse_str(&item_uuid_str).unwrap_or_default();
-            let product_uuid_str: String = row.try_get("product_uuid").unwrap_or_default();
-            let product_uuid = Uuid::parse_str(&product_uuid_str).unwrap_or_default();
-            let min_condition_str: String = row.try_get("min_condition").unwrap_or_default();
-
-            // Simple parsing fallbacks as seen in original code
-            let min_condition = match min_condition_str.as_str() {
-                "NM" => crate::core::Condition::NM,
-                _ => crate::core::Condition::NM, // fallback
-            };
+        Ok(rows.iter().map(wants_item_from_row).collect())
+    }
 
-            let max_price: Option<f64> = row.try_get("max_price").ok();
-            let created_at_str: String = row.try_get("created_at").unwrap_or_default();
-            let created_at =
-                chrono::DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&chrono::Utc);
+    pub async fn get_wants_list(&self, wants_list_uuid: Uuid) -> Result<Option<WantsList>> {
+        let row = sqlx::query(
+            "SELECT customer_uuid, created_at FROM Wants_Lists WHERE wants_list_uuid = ?",
+        )
+        .bind(wants_list_uuid.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
-            items.push(WantsItem {
-                item_uuid,
-                product_uuid,
-                min_condition,
-                max_price,
-                created_at,
-            });
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let customer_uuid_str: String = row.try_get("customer_uuid").unwrap_or_default();
+        let created_at_str: String = row.try_get("created_at").unwrap_or_default();
+        Ok(Some(WantsList {
+            wants_list_uuid,
+            customer_uuid: Uuid::parse_str(&customer_uuid_str).unwrap_or_default(),
+            items: self.get_wants_items(wants_list_uuid).await?,
+            created_at: chrono::DateTime::parse_from_rfc3339(&created_at_str)
+                .unwrap_or_default()
+                .with_timezone(&chrono::Utc),
+        }))
+    }
+
+    /// Queue a list for sync after its items changed outside `save_wants_list`
+    pub async fn log_wants_list(&self, wants_list_uuid: Uuid) -> Result<()> {
+        if let Some(list) = self.get_wants_list(wants_list_uuid).await? {
+            self.sync
+                .log_change(
+                    &wants_list_uuid.to_string(),
+                    "WantsList",
+                    "Update",
+                    &serde_json::to_value(&list).unwrap_or_default(),
+                )
+                .await?;
         }
-        Ok(items)
+        Ok(())
     }
 
     /// HIGH-007 FIX: Get wants items by product_uuid directly using index
-    /// Returns list of (Customer, WantsItem) tuples for items matching the product
+    /// Returns (Customer, WantsItem) tuples for active customers' outstanding
+    /// wants on the product, oldest want first
     pub async fn get_wants_items_by_product(
         &self,
         product_uuid: Uuid,
     ) -> Result<Vec<(Customer, WantsItem)>> {
         // Use JOIN to fetch wants items with their owning customer in one query
         let rows = sqlx::query(
-            "SELECT wi.item_uuid, wi.product_uuid, wi.min_condition, wi.max_price, wi.created_at,
+            "SELECT wi.item_uuid, wi.product_uuid, wi.min_condition, wi.max_price, wi.variant_type,
+                    wi.quantity, wi.quantity_fulfilled, wi.hold_hours, wi.created_at,
                     c.customer_uuid, c.name, c.email, c.phone, c.store_credit, c.tier, c.created_at as customer_created_at
              FROM Wants_Items wi
              JOIN Wants_Lists wl ON wi.wants_list_uuid = wl.wants_list_uuid
              JOIN Customers c ON wl.customer_uuid = c.customer_uuid
-             WHERE wi.product_uuid = ?"
+             WHERE wi.product_uuid = ? AND wi.quantity_fulfilled < wi.quantity
+               AND c.deleted_at IS NULL
+             ORDER BY wi.created_at"
         )
         .bind(product_uuid.to_string())
         .fetch_all(&self.pool)
@@ -308,42 +373,16 @@ impl CustomerRepository {
         let mut results = Vec::new();
 
         for row in rows {
-            // Parse WantsItem
-            let item_uuid_str: String = row.try_get("item_uuid").unwrap_or_default();
-            let item_uuid = Uuid::parse_str(&item_uuid_str).unwrap_or_default();
-            let product_uuid_str: String = row.try_get("product_uuid").unwrap_or_default();
-            let product_uuid = Uuid::parse_str(&product_uuid_str).unwrap_or_default();
-            let min_condition_str: String = row.try_get("min_condition").unwrap_or_default();
-            let min_condition = match min_condition_str.as_str() {
-                "NM" => crate::core::Condition::NM,
-                "LP" => crate::core::Condition::LP,
-                "MP" => crate::core::Condition::MP,
-                "HP" => crate::core::Condition::HP,
-                "DMG" => crate::core::Condition::DMG,
-                _ => crate::core::Condition::NM,
-            };
-            let max_price: Option<f64> = row.try_get("max_price").ok();
-            let created_at_str: String = row.try_get("created_at").unwrap_or_default();
-            let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
-                .unwrap_or_default()
-                .with_timezone(&chrono::Utc);
-
-            let wants_item = WantsItem {
-                item_uuid,
-                product_uuid,
-                min_condition,
-                max_price,
-                created_at,
-            };
+            let wants_item = wants_item_from_row(&row);
 
             // Parse Customer
             let customer_uuid_str: String = row.try_get("customer_uuid").unwrap_or_default();
             let customer_uuid = Uuid::parse_str(&customer_uuid_str).unwrap_or_default();
             let name: String = row.try_get("name").unwrap_or_default();
-            let email: Option<String> = row.try_get("email").ok();
-            let phone: Option<String> = row.try_get("phone").ok();
+            let email: Option<String> = row.try_get("email").ok().flatten();
+            let phone: Option<String> = row.try_get("phone").ok().flatten();
             let store_credit: f64 = row.try_get("store_credit").unwrap_or_default();
-            let tier: Option<String> = row.try_get("tier").ok();
+            let tier: Option<String> = row.try_get("tier").ok().flatten();
             let customer_created_at_str: String =
                 row.try_get("customer_created_at").unwrap_or_default();
             let customer_created_at =
@@ -367,3 +406,30 @@ impl CustomerRepository {
         Ok(results)
     }
 }
+
+/// Enum columns are stored as their variant names
+fn parse_variant<T: serde::de::DeserializeOwned>(s: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(s.to_string())).ok()
+}
+
+fn wants_item_from_row(row: &sqlx::sqlite::SqliteRow) -> WantsItem {
+    let item_uuid_str: String = row.try_get("item_uuid").unwrap_or_default();
+    let product_uuid_str: String = row.try_get("product_uuid").unwrap_or_default();
+    let min_condition_str: String = row.try_get("min_condition").unwrap_or_default();
+    let variant_type: Option<String> = row.try_get("variant_type").ok().flatten();
+    let created_at_str: String = row.try_get("created_at").unwrap_or_default();
+
+    WantsItem {
+        item_uuid: Uuid::parse_str(&item_uuid_str).unwrap_or_default(),
+        product_uuid: Uuid::parse_str(&product_uuid_str).unwrap_or_default(),
+        min_condition: parse_variant(&min_condition_str).unwrap_or(crate::core::Condition::NM),
+        max_price: row.try_get("max_price").ok().flatten(),
+        variant_type: variant_type.as_deref().and_then(parse_variant),
+        quantity: row.try_get("quantity").unwrap_or(1),
+        quantity_fulfilled: row.try_get("quantity_fulfilled").unwrap_or(0),
+        hold_hours: row.try_get("hold_hours").ok().flatten(),
+        created_at: chrono::DateTime::parse_from_rfc3339(&created_at_str)
+            .unwrap_or_default()
+            .with_timezone(&chrono::Utc),
+    }
+}
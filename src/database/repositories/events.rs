@@ -1,4 +1,4 @@
-use crate::core::{Event, EventParticipant};
+use crate::core::{Event, EventParticipant, ParticipantStatus};
 use crate::errors::Result;
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
@@ -69,7 +69,7 @@ impl EventRepository {
             let date_str: String = row.try_get("date").unwrap_or_default();
             let date = chrono::DateTime::parse_from_rfc3339(&date_str)?.with_timezone(&chrono::Utc);
             let entry_fee: f64 = row.try_get("entry_fee").unwrap_or_default();
-            let max_participants: Option<i32> = row.try_get("max_participants").ok();
+            let max_participants: Option<i32> = row.try_get("max_participants").ok().flatten();
             let created_at_str: String = row.try_get("created_at").unwrap_or_default();
             let created_at =
                 chrono::DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&chrono::Utc);
@@ -102,7 +102,7 @@ impl EventRepository {
             let date_str: String = row.try_get("date").unwrap_or_default();
             let date = chrono::DateTime::parse_from_rfc3339(&date_str)?.with_timezone(&chrono::Utc);
             let entry_fee: f64 = row.try_get("entry_fee").unwrap_or_default();
-            let max_participants: Option<i32> = row.try_get("max_participants").ok();
+            let max_participants: Option<i32> = row.try_get("max_participants").ok().flatten();
             let created_at_str: String = row.try_get("created_at").unwrap_or_default();
             let created_at =
                 chrono::DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&chrono::Utc);
@@ -129,13 +129,19 @@ impl EventRepository {
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
         sqlx::query(
-            "INSERT OR REPLACE INTO Event_Participants (participant_uuid, event_uuid, customer_uuid, name, paid, placement, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            "INSERT OR REPLACE INTO Event_Participants (participant_uuid, event_uuid, customer_uuid, name, paid, fee_paid, payment_method, cash_paid, status, checked_in_at, refunded_amount, placement, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(participant.participant_uuid.to_string())
         .bind(participant.event_uuid.to_string())
         .bind(participant.customer_uuid.map(|id| id.to_string()))
         .bind(&participant.name)
         .bind(participant.paid)
+        .bind(participant.fee_paid)
+        .bind(&participant.payment_method)
+        .bind(participant.cash_paid)
+        .bind(participant.status.as_str())
+        .bind(participant.checked_in_at.map(|t| t.to_rfc3339()))
+        .bind(participant.refunded_amount)
         .bind(participant.placement)
         .bind(participant.created_at.to_rfc3339())
         .execute(&mut *tx)
@@ -159,37 +165,166 @@ impl EventRepository {
         Ok(())
     }
 
+    /// Set or clear a participant's final placement
+    pub async fn update_placement(
+        &self,
+        participant_uuid: Uuid,
+        placement: Option<i32>,
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
+        let updated =
+            sqlx::query("UPDATE Event_Participants SET placement = ? WHERE participant_uuid = ?")
+                .bind(placement)
+                .bind(participant_uuid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+        if updated.rows_affected() == 0 {
+            return Err(anyhow::anyhow!(
+                "Participant {} not found",
+                participant_uuid
+            ));
+        }
+
+        self.log_participant_with_tx(&mut tx, participant_uuid)
+            .await?;
+        tx.commit()
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Save registration changes: payment, status, check-in and refunds
+    pub async fn update_participant(&self, participant: &EventParticipant) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
+        let updated = sqlx::query(
+            "UPDATE Event_Participants SET paid = ?, fee_paid = ?, payment_method = ?, cash_paid = ?, status = ?, checked_in_at = ?, refunded_amount = ? WHERE participant_uuid = ?",
+        )
+        .bind(participant.paid)
+        .bind(participant.fee_paid)
+        .bind(&participant.payment_method)
+        .bind(participant.cash_paid)
+        .bind(participant.status.as_str())
+        .bind(participant.checked_in_at.map(|t| t.to_rfc3339()))
+        .bind(participant.refunded_amount)
+        .bind(participant.participant_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+        if updated.rows_affected() == 0 {
+            return Err(anyhow::anyhow!(
+                "Participant {} not found",
+                participant.participant_uuid
+            ));
+        }
+
+        self.log_participant_with_tx(&mut tx, participant.participant_uuid)
+            .await?;
+        tx.commit()
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Players holding a seat, i.e. not waitlisted, cancelled or no-shows
+    pub async fn count_seated(&self, event_uuid: Uuid) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM Event_Participants WHERE event_uuid = ? AND status IN ('registered', 'checked_in')",
+        )
+        .bind(event_uuid.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+        Ok(count)
+    }
+
+    async fn log_participant_with_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        participant_uuid: Uuid,
+    ) -> Result<()> {
+        let row = sqlx::query(
+            "SELECT participant_uuid, event_uuid, customer_uuid, name, paid, fee_paid, payment_method, cash_paid, status, checked_in_at, refunded_amount, placement, created_at FROM Event_Participants WHERE participant_uuid = ?",
+        )
+        .bind(participant_uuid.to_string())
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+        let participant = map_participant(&row)?;
+
+        self.sync
+            .log_change_with_tx(
+                tx,
+                &participant_uuid.to_string(),
+                "EventParticipant",
+                "Update",
+                &serde_json::to_value(&participant).unwrap_or_default(),
+            )
+            .await
+    }
+
+    pub async fn get_participant(
+        &self,
+        participant_uuid: Uuid,
+    ) -> Result<Option<EventParticipant>> {
+        let row = sqlx::query("SELECT participant_uuid, event_uuid, customer_uuid, name, paid, fee_paid, payment_method, cash_paid, status, checked_in_at, refunded_amount, placement, created_at FROM Event_Participants WHERE participant_uuid = ?")
+            .bind(participant_uuid.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
+        row.as_ref().map(map_participant).transpose()
+    }
+
     pub async fn get_participants(&self, event_uuid: Uuid) -> Result<Vec<EventParticipant>> {
-        let rows = sqlx::query("SELECT participant_uuid, event_uuid, customer_uuid, name, paid, placement, created_at FROM Event_Participants WHERE event_uuid = ?")
+        let rows = sqlx::query("SELECT participant_uuid, event_uuid, customer_uuid, name, paid, fee_paid, payment_method, cash_paid, status, checked_in_at, refunded_amount, placement, created_at FROM Event_Participants WHERE event_uuid = ? ORDER BY created_at")
             .bind(event_uuid.to_string())
             .fetch_all(&self.pool)
             .await
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
-        let mut participants = Vec::new();
-        for row in rows {
-            let participant_uuid_str: String = row.try_get("participant_uuid").unwrap_or_default();
-            let participant_uuid = Uuid::parse_str(&participant_uuid_str).unwrap_or_default();
-            // event_uuid is known
-            let customer_uuid_str: Option<String> = row.try_get("customer_uuid").ok();
-            let customer_uuid = customer_uuid_str.map(|s| Uuid::parse_str(&s).unwrap_or_default());
-            let name: String = row.try_get("name").unwrap_or_default();
-            let paid: bool = row.try_get("paid").unwrap_or_default();
-            let placement: Option<i32> = row.try_get("placement").ok();
-            let created_at_str: String = row.try_get("created_at").unwrap_or_default();
-            let created_at =
-                chrono::DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&chrono::Utc);
-
-            participants.push(EventParticipant {
-                participant_uuid,
-                event_uuid,
-                customer_uuid,
-                name,
-                paid,
-                placement,
-                created_at,
-            });
-        }
-        Ok(participants)
+        rows.iter().map(map_participant).collect()
     }
 }
+
+fn map_participant(row: &sqlx::sqlite::SqliteRow) -> Result<EventParticipant> {
+    let participant_uuid_str: String = row.try_get("participant_uuid").unwrap_or_default();
+    let event_uuid_str: String = row.try_get("event_uuid").unwrap_or_default();
+    let customer_uuid_str: Option<String> = row.try_get("customer_uuid").ok().flatten();
+    let created_at_str: String = row.try_get("created_at").unwrap_or_default();
+    let created_at =
+        chrono::DateTime::parse_from_rfc3339(&created_at_str)?.with_timezone(&chrono::Utc);
+
+    Ok(EventParticipant {
+        participant_uuid: Uuid::parse_str(&participant_uuid_str).unwrap_or_default(),
+        event_uuid: Uuid::parse_str(&event_uuid_str).unwrap_or_default(),
+        customer_uuid: customer_uuid_str.and_then(|s| Uuid::parse_str(&s).ok()),
+        name: row.try_get("name").unwrap_or_default(),
+        paid: row.try_get("paid").unwrap_or_default(),
+        fee_paid: row.try_get("fee_paid").unwrap_or_default(),
+        payment_method: row.try_get("payment_method").ok().flatten(),
+        cash_paid: row.try_get("cash_paid").unwrap_or_default(),
+        status: ParticipantStatus::parse(&row.try_get::<String, _>("status").unwrap_or_default()),
+        checked_in_at: row
+            .try_get::<Option<String>, _>("checked_in_at")
+            .ok()
+            .flatten()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+            .map(|d| d.with_timezone(&chrono::Utc)),
+        refunded_amount: row.try_get("refunded_amount").unwrap_or_default(),
+        placement: row.try_get("placement").ok().flatten(),
+        created_at,
+    })
+}
@@ -3,6 +3,7 @@ use crate::errors::Result;
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
+use super::movements::{self, MovementSource, MovementType};
 use super::sync::SyncRepository;
 
 #[derive(Clone)]
@@ -136,41 +137,28 @@ impl InventoryRepository {
     }
 
     pub async fn insert(&self, item: &InventoryItem) -> Result<()> {
-        let variant_str = item.variant_type.as_ref().map(|v| format!("{:?}", v));
+        let source = MovementSource::new(
+            MovementType::ManualAdjustment,
+            None,
+            None,
+            self.sync.node_id(),
+        );
+        self.insert_with_movement(item, &source).await
+    }
 
+    /// Insert or overwrite a pile, recording any quantity change under `source`
+    pub async fn insert_with_movement(
+        &self,
+        item: &InventoryItem,
+        source: &MovementSource,
+    ) -> Result<()> {
         let mut tx = self
             .pool
             .begin()
             .await
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
-        sqlx::query(
-            "INSERT OR REPLACE INTO Local_Inventory 
-            (inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, specific_price, serialized_details) 
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-        )
-        .bind(item.inventory_uuid.to_string())
-        .bind(item.product_uuid.to_string())
-        .bind(variant_str)
-        .bind(format!("{:?}", item.condition))
-        .bind(item.quantity_on_hand as i64)
-        .bind(&item.location_tag)
-        .bind(item.specific_price)
-        .bind(item.serialized_details.as_ref().map(|v| v.to_string()))
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
-
-        self.sync
-            .log_change_with_tx(
-                &mut tx,
-                &item.inventory_uuid.to_string(),
-                "InventoryItem",
-                "Update",
-                &serde_json::to_value(item)
-                    .map_err(|e| crate::errors::VaultSyncError::SerializationError(e))?,
-            )
-            .await?;
+        self.insert_with_tx(&mut tx, item, source).await?;
 
         tx.commit()
             .await
@@ -183,13 +171,25 @@ impl InventoryRepository {
         &self,
         tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
         item: &InventoryItem,
+        source: &MovementSource,
     ) -> Result<()> {
         let variant_str = item.variant_type.as_ref().map(|v| format!("{:?}", v));
 
+        let previous_qty: Option<i64> = sqlx::query_scalar(
+            "SELECT quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?",
+        )
+        .bind(item.inventory_uuid.to_string())
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+
         sqlx::query(
             "INSERT OR REPLACE INTO Local_Inventory 
-            (inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, specific_price, serialized_details) 
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            (inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, specific_price, serialized_details,
+             storage_uuid, bin_location) 
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?,
+             (SELECT storage_uuid FROM Local_Inventory WHERE inventory_uuid = ? AND location_tag = ?),
+             (SELECT bin_location FROM Local_Inventory WHERE inventory_uuid = ? AND location_tag = ?))",
         )
         .bind(item.inventory_uuid.to_string())
         .bind(item.product_uuid.to_string())
@@ -199,10 +199,24 @@ impl InventoryRepository {
         .bind(&item.location_tag)
         .bind(item.specific_price)
         .bind(item.serialized_details.as_ref().map(|v| v.to_string()))
+        .bind(item.inventory_uuid.to_string())
+        .bind(&item.location_tag)
+        .bind(item.inventory_uuid.to_string())
+        .bind(&item.location_tag)
         .execute(&mut **tx)
         .await
         .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
+        movements::record_movement_with_tx(
+            tx,
+            item.inventory_uuid,
+            item.product_uuid,
+            previous_qty.unwrap_or(0) as i32,
+            item.quantity_on_hand,
+            source,
+        )
+        .await?;
+
         self.sync
             .log_change_with_tx(
                 tx,
@@ -217,6 +231,31 @@ impl InventoryRepository {
         Ok(())
     }
 
+    /// Queue a pile's current state for sync after a service changed it in `tx`
+    pub async fn log_sync_with_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        inventory_uuid: Uuid,
+    ) -> Result<()> {
+        let row = sqlx::query("SELECT inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, specific_price, serialized_details, cost_basis, supplier_uuid, received_date, min_stock_level, max_stock_level, reorder_point, bin_location, last_sold_date, last_counted_date, deleted_at FROM Local_Inventory WHERE inventory_uuid = ?")
+            .bind(inventory_uuid.to_string())
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+        let item = Self::map_row(&row)?;
+
+        self.sync
+            .log_change_with_tx(
+                tx,
+                &item.inventory_uuid.to_string(),
+                "InventoryItem",
+                "Update",
+                &serde_json::to_value(&item)
+                    .map_err(crate::errors::VaultSyncError::SerializationError)?,
+            )
+            .await
+    }
+
     pub async fn get_by_id(&self, inventory_uuid: Uuid) -> Result<Option<InventoryItem>> {
         let row = sqlx::query("SELECT inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, specific_price, serialized_details, cost_basis, supplier_uuid, received_date, min_stock_level, max_stock_level, reorder_point, bin_location, last_sold_date, last_counted_date, deleted_at FROM Local_Inventory WHERE inventory_uuid = ?")
             .bind(inventory_uuid.to_string())
@@ -284,18 +323,30 @@ impl InventoryRepository {
     }
 
     pub async fn update_quantity(&self, inventory_uuid: Uuid, delta: i32) -> Result<()> {
+        let source = MovementSource::new(
+            MovementType::ManualAdjustment,
+            None,
+            None,
+            self.sync.node_id(),
+        );
+        self.update_quantity_with_movement(inventory_uuid, delta, &source)
+            .await
+    }
+
+    /// Apply a quantity delta, recording it in the movement ledger under `source`
+    pub async fn update_quantity_with_movement(
+        &self,
+        inventory_uuid: Uuid,
+        delta: i32,
+        source: &MovementSource,
+    ) -> Result<()> {
         let mut tx = self
             .pool
             .begin()
             .await
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
-        sqlx::query("UPDATE Local_Inventory SET quantity_on_hand = quantity_on_hand + ? WHERE inventory_uuid = ?")
-            .bind(delta)
-            .bind(inventory_uuid.to_string())
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+        movements::adjust_quantity_with_tx(&mut tx, inventory_uuid, delta, source).await?;
 
         // Fetch updated item for logging
         let row = sqlx::query("SELECT inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, specific_price, serialized_details FROM Local_Inventory WHERE inventory_uuid = ?")
@@ -431,8 +482,10 @@ impl InventoryRepository {
         Ok(())
     }
 
+    /// Items at or below their own reorder point (or minimum stock level);
+    /// `threshold` applies to items with neither set
     pub async fn get_low_stock(&self, threshold: i32) -> Result<Vec<InventoryItem>> {
-        let rows = sqlx::query("SELECT inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, specific_price, serialized_details FROM Local_Inventory WHERE quantity_on_hand <= ?")
+        let rows = sqlx::query("SELECT inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, specific_price, serialized_details FROM Local_Inventory WHERE quantity_on_hand <= COALESCE(reorder_point, NULLIF(min_stock_level, 0), ?)")
             .bind(threshold as i64)
             .fetch_all(&self.pool)
             .await
@@ -497,7 +550,7 @@ impl InventoryRepository {
 
     /// Get low stock count (for dashboard)
     pub async fn get_low_stock_count(&self, threshold: i32) -> Result<i64> {
-        let row = sqlx::query("SELECT COUNT(*) as cnt FROM Local_Inventory WHERE quantity_on_hand <= ? AND quantity_on_hand > 0")
+        let row = sqlx::query("SELECT COUNT(*) as cnt FROM Local_Inventory WHERE quantity_on_hand <= COALESCE(reorder_point, NULLIF(min_stock_level, 0), ?) AND quantity_on_hand > 0")
             .bind(threshold as i64)
             .fetch_one(&self.pool)
             .await
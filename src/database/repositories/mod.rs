@@ -3,6 +3,7 @@ pub mod auth;
 pub mod customers;
 pub mod events;
 pub mod inventory;
+pub mod movements;
 pub mod pricing;
 pub mod products;
 pub mod sync;
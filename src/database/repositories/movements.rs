@@ -0,0 +1,462 @@
+//! Inventory movement ledger
+//!
+//! Every change to `Local_Inventory.quantity_on_hand` goes through
+//! [`adjust_quantity_with_tx`], [`set_quantity_with_tx`] or
+//! [`record_movement_with_tx`], which write an immutable row to
+//! `Inventory_Movements` in the same database transaction as the stock
+//! change. The ledger backs per-item stock cards and point-in-time quantity
+//! lookups.
+
+use crate::errors::{Result, VaultSyncError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+/// Why a pile's quantity changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementType {
+    /// Balance carried in when the ledger was introduced
+    OpeningBalance,
+    Sale,
+    Return,
+    /// Bought from a customer
+    Buy,
+    TradeIn,
+    /// Received from a supplier or consignor
+    Receive,
+    TransferOut,
+    TransferIn,
+    CountAdjustment,
+    Damage,
+    Void,
+    Hold,
+    HoldRelease,
+    ConsignmentReturn,
+    /// Sealed product opened into its components
+    Break,
+    /// Components built into a kit or sealed product
+    Assemble,
+    /// Loaded from a bulk import file
+    Import,
+    /// Moved between bins within a store
+    BinMove,
+    /// Edited directly through the inventory API
+    ManualAdjustment,
+    SyncMerge,
+}
+
+impl MovementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementType::OpeningBalance => "opening_balance",
+            MovementType::Sale => "sale",
+            MovementType::Return => "return",
+            MovementType::Buy => "buy",
+            MovementType::TradeIn => "trade_in",
+            MovementType::Receive => "receive",
+            MovementType::TransferOut => "transfer_out",
+            MovementType::TransferIn => "transfer_in",
+            MovementType::CountAdjustment => "count_adjustment",
+            MovementType::Damage => "damage",
+            MovementType::Void => "void",
+            MovementType::Hold => "hold",
+            MovementType::HoldRelease => "hold_release",
+            MovementType::ConsignmentReturn => "consignment_return",
+            MovementType::Break => "break",
+            MovementType::Assemble => "assemble",
+            MovementType::Import => "import",
+            MovementType::BinMove => "bin_move",
+            MovementType::ManualAdjustment => "manual_adjustment",
+            MovementType::SyncMerge => "sync_merge",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "opening_balance" => Some(MovementType::OpeningBalance),
+            "sale" => Some(MovementType::Sale),
+            "return" => Some(MovementType::Return),
+            "buy" => Some(MovementType::Buy),
+            "trade_in" => Some(MovementType::TradeIn),
+            "receive" => Some(MovementType::Receive),
+            "transfer_out" => Some(MovementType::TransferOut),
+            "transfer_in" => Some(MovementType::TransferIn),
+            "count_adjustment" => Some(MovementType::CountAdjustment),
+            "damage" => Some(MovementType::Damage),
+            "void" => Some(MovementType::Void),
+            "hold" => Some(MovementType::Hold),
+            "hold_release" => Some(MovementType::HoldRelease),
+            "consignment_return" => Some(MovementType::ConsignmentReturn),
+            "break" => Some(MovementType::Break),
+            "assemble" => Some(MovementType::Assemble),
+            "import" => Some(MovementType::Import),
+            "bin_move" => Some(MovementType::BinMove),
+            "manual_adjustment" => Some(MovementType::ManualAdjustment),
+            "sync_merge" => Some(MovementType::SyncMerge),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MovementType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What caused a movement: the document, the user and the terminal
+#[derive(Debug, Clone)]
+pub struct MovementSource {
+    pub movement_type: MovementType,
+    /// Transaction, return, purchase order, transfer, damage record, etc.
+    pub source_uuid: Option<Uuid>,
+    pub user_uuid: Option<Uuid>,
+    pub terminal_id: String,
+    pub notes: Option<String>,
+}
+
+impl MovementSource {
+    pub fn new(
+        movement_type: MovementType,
+        source_uuid: Option<Uuid>,
+        user_uuid: Option<Uuid>,
+        terminal_id: &str,
+    ) -> Self {
+        Self {
+            movement_type,
+            source_uuid,
+            user_uuid,
+            terminal_id: terminal_id.to_string(),
+            notes: None,
+        }
+    }
+}
+
+/// A single recorded stock change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockMovement {
+    pub movement_uuid: Uuid,
+    pub inventory_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub movement_type: MovementType,
+    pub quantity_change: i32,
+    pub quantity_before: i32,
+    pub quantity_after: i32,
+    pub source_uuid: Option<Uuid>,
+    pub user_uuid: Option<Uuid>,
+    pub terminal_id: String,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Movement history for one pile over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockCard {
+    pub inventory_uuid: Uuid,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub opening_quantity: i32,
+    pub closing_quantity: i32,
+    pub movements: Vec<StockMovement>,
+}
+
+fn db_err(e: sqlx::Error) -> VaultSyncError {
+    VaultSyncError::DatabaseError(e.to_string())
+}
+
+/// Apply a quantity delta to a pile and record it in the ledger.
+///
+/// Returns the new quantity. A zero delta changes nothing and records nothing.
+pub async fn adjust_quantity_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    inventory_uuid: Uuid,
+    delta: i32,
+    source: &MovementSource,
+) -> Result<i32> {
+    let row = sqlx::query(
+        "SELECT product_uuid, quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?",
+    )
+    .bind(inventory_uuid.to_string())
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(db_err)?
+    .ok_or_else(|| {
+        VaultSyncError::InventoryError(format!("Inventory item {} not found", inventory_uuid))
+    })?;
+
+    let product_uuid: String = row.try_get("product_uuid").map_err(db_err)?;
+    let before: i64 = row.try_get("quantity_on_hand").map_err(db_err)?;
+    let before = before as i32;
+    if delta == 0 {
+        return Ok(before);
+    }
+    let after = before + delta;
+
+    sqlx::query("UPDATE Local_Inventory SET quantity_on_hand = ? WHERE inventory_uuid = ?")
+        .bind(after)
+        .bind(inventory_uuid.to_string())
+        .execute(&mut **tx)
+        .await
+        .map_err(db_err)?;
+
+    let product_uuid = Uuid::parse_str(&product_uuid)?;
+    record_movement_with_tx(tx, inventory_uuid, product_uuid, before, after, source).await?;
+    Ok(after)
+}
+
+/// Set a pile to an absolute quantity and record the difference in the ledger
+pub async fn set_quantity_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    inventory_uuid: Uuid,
+    quantity: i32,
+    source: &MovementSource,
+) -> Result<()> {
+    let current: Option<i64> =
+        sqlx::query_scalar("SELECT quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?")
+            .bind(inventory_uuid.to_string())
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(db_err)?;
+    let current = current.ok_or_else(|| {
+        VaultSyncError::InventoryError(format!("Inventory item {} not found", inventory_uuid))
+    })?;
+
+    adjust_quantity_with_tx(tx, inventory_uuid, quantity - current as i32, source).await?;
+    Ok(())
+}
+
+/// Record a change the caller has already applied, e.g. a freshly inserted pile
+/// (`before` = 0) or a row overwritten wholesale by a sync merge.
+pub async fn record_movement_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    inventory_uuid: Uuid,
+    product_uuid: Uuid,
+    quantity_before: i32,
+    quantity_after: i32,
+    source: &MovementSource,
+) -> Result<Option<Uuid>> {
+    if quantity_before == quantity_after {
+        return Ok(None);
+    }
+
+    let movement_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Inventory_Movements
+         (movement_uuid, inventory_uuid, product_uuid, movement_type, quantity_change,
+          quantity_before, quantity_after, source_uuid, user_uuid, terminal_id, notes, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(movement_uuid.to_string())
+    .bind(inventory_uuid.to_string())
+    .bind(product_uuid.to_string())
+    .bind(source.movement_type.as_str())
+    .bind(quantity_after - quantity_before)
+    .bind(quantity_before)
+    .bind(quantity_after)
+    .bind(source.source_uuid.map(|u| u.to_string()))
+    .bind(source.user_uuid.map(|u| u.to_string()))
+    .bind(&source.terminal_id)
+    .bind(&source.notes)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut **tx)
+    .await
+    .map_err(db_err)?;
+
+    Ok(Some(movement_uuid))
+}
+
+#[derive(Clone)]
+pub struct MovementRepository {
+    pool: SqlitePool,
+}
+
+impl MovementRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    fn map_row(row: &sqlx::sqlite::SqliteRow) -> Result<StockMovement> {
+        let parse_uuid = |col: &str| -> Result<Uuid> {
+            let s: String = row.try_get(col).map_err(db_err)?;
+            Ok(Uuid::parse_str(&s)?)
+        };
+        let optional_uuid = |col: &str| -> Option<Uuid> {
+            row.try_get::<Option<String>, _>(col)
+                .ok()
+                .flatten()
+                .and_then(|s| Uuid::parse_str(&s).ok())
+        };
+
+        let movement_type: String = row.try_get("movement_type").map_err(db_err)?;
+        let created_at: String = row.try_get("created_at").map_err(db_err)?;
+
+        Ok(StockMovement {
+            movement_uuid: parse_uuid("movement_uuid")?,
+            inventory_uuid: parse_uuid("inventory_uuid")?,
+            product_uuid: parse_uuid("product_uuid")?,
+            movement_type: MovementType::parse(&movement_type).ok_or_else(|| {
+                VaultSyncError::ValidationError(format!(
+                    "Unknown movement type '{}'",
+                    movement_type
+                ))
+            })?,
+            quantity_change: row.try_get("quantity_change").map_err(db_err)?,
+            quantity_before: row.try_get("quantity_before").map_err(db_err)?,
+            quantity_after: row.try_get("quantity_after").map_err(db_err)?,
+            source_uuid: optional_uuid("source_uuid"),
+            user_uuid: optional_uuid("user_uuid"),
+            terminal_id: row.try_get("terminal_id").unwrap_or_default(),
+            notes: row.try_get("notes").ok().flatten(),
+            created_at: DateTime::parse_from_rfc3339(&created_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| VaultSyncError::ValidationError(e.to_string()))?,
+        })
+    }
+
+    /// Movements for a pile within `[start, end]`, oldest first
+    pub async fn get_movements(
+        &self,
+        inventory_uuid: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<StockMovement>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Inventory_Movements
+             WHERE inventory_uuid = ? AND created_at >= ? AND created_at <= ?
+             ORDER BY created_at ASC, rowid ASC",
+        )
+        .bind(inventory_uuid.to_string())
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_err)?;
+
+        rows.iter().map(Self::map_row).collect()
+    }
+
+    /// Movements raised by one source document (a sale, a receipt, a transfer...)
+    pub async fn get_by_source(&self, source_uuid: Uuid) -> Result<Vec<StockMovement>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Inventory_Movements WHERE source_uuid = ? ORDER BY created_at ASC, rowid ASC",
+        )
+        .bind(source_uuid.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_err)?;
+
+        rows.iter().map(Self::map_row).collect()
+    }
+
+    /// Quantity of a pile at a point in time, from the last movement at or before it
+    pub async fn quantity_as_of(&self, inventory_uuid: Uuid, at: DateTime<Utc>) -> Result<i32> {
+        let quantity: Option<i64> = sqlx::query_scalar(
+            "SELECT quantity_after FROM Inventory_Movements
+             WHERE inventory_uuid = ? AND created_at <= ?
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT 1",
+        )
+        .bind(inventory_uuid.to_string())
+        .bind(at.to_rfc3339())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_err)?;
+
+        Ok(quantity.unwrap_or(0) as i32)
+    }
+
+    /// Quantity of a product across all of its piles at a point in time
+    pub async fn product_quantity_as_of(
+        &self,
+        product_uuid: Uuid,
+        at: DateTime<Utc>,
+    ) -> Result<i32> {
+        let quantity: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(m.quantity_after), 0)
+             FROM Inventory_Movements m
+             WHERE m.product_uuid = ?
+               AND m.rowid = (
+                   SELECT m2.rowid FROM Inventory_Movements m2
+                   WHERE m2.inventory_uuid = m.inventory_uuid AND m2.created_at <= ?
+                   ORDER BY m2.created_at DESC, m2.rowid DESC
+                   LIMIT 1
+               )",
+        )
+        .bind(product_uuid.to_string())
+        .bind(at.to_rfc3339())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(db_err)?;
+
+        Ok(quantity as i32)
+    }
+
+    /// Opening balance, movements and closing balance for a pile over a period
+    pub async fn get_stock_card(
+        &self,
+        inventory_uuid: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<StockCard> {
+        let opening_quantity = self
+            .quantity_as_of(inventory_uuid, start - chrono::Duration::microseconds(1))
+            .await?;
+        let movements = self.get_movements(inventory_uuid, start, end).await?;
+        let closing_quantity = movements
+            .last()
+            .map(|m| m.quantity_after)
+            .unwrap_or(opening_quantity);
+
+        Ok(StockCard {
+            inventory_uuid,
+            period_start: start,
+            period_end: end,
+            opening_quantity,
+            closing_quantity,
+            movements,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_movement_type_round_trip() {
+        for movement_type in [
+            MovementType::OpeningBalance,
+            MovementType::Sale,
+            MovementType::Return,
+            MovementType::Buy,
+            MovementType::TradeIn,
+            MovementType::Receive,
+            MovementType::TransferOut,
+            MovementType::TransferIn,
+            MovementType::CountAdjustment,
+            MovementType::Damage,
+            MovementType::Void,
+            MovementType::Hold,
+            MovementType::HoldRelease,
+            MovementType::ConsignmentReturn,
+            MovementType::Break,
+            MovementType::Assemble,
+            MovementType::Import,
+            MovementType::BinMove,
+            MovementType::ManualAdjustment,
+            MovementType::SyncMerge,
+        ] {
+            assert_eq!(
+                MovementType::parse(movement_type.as_str()),
+                Some(movement_type)
+            );
+            assert_eq!(
+                serde_json::to_value(movement_type).unwrap(),
+                serde_json::json!(movement_type.as_str())
+            );
+        }
+        assert_eq!(MovementType::parse("shoplifting"), None);
+    }
+}
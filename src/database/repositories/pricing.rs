@@ -1,4 +1,4 @@
-use crate::core::PriceInfo;
+use crate::core::{Currency, PriceInfo};
 use crate::errors::Result;
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
@@ -13,17 +13,26 @@ impl PricingRepository {
         Self { pool }
     }
 
+    fn row_currency(row: &sqlx::sqlite::SqliteRow) -> Currency {
+        row.try_get::<Option<String>, _>("currency")
+            .ok()
+            .flatten()
+            .and_then(|code| Currency::from_code(&code))
+            .unwrap_or_default()
+    }
+
     pub async fn insert_matrix(&self, price: &PriceInfo) -> Result<()> {
         sqlx::query(
             "INSERT OR REPLACE INTO Pricing_Matrix 
-            (price_uuid, product_uuid, market_mid, market_low, last_sync_timestamp) 
-            VALUES (?, ?, ?, ?, ?)",
+            (price_uuid, product_uuid, market_mid, market_low, last_sync_timestamp, currency) 
+            VALUES (?, ?, ?, ?, ?, ?)",
         )
         .bind(price.price_uuid.to_string())
         .bind(price.product_uuid.to_string())
         .bind(price.market_mid)
         .bind(price.market_low)
         .bind(price.last_sync_timestamp.to_rfc3339())
+        .bind(price.currency.code())
         .execute(&self.pool)
         .await
         .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
@@ -32,7 +41,7 @@ impl PricingRepository {
     }
 
     pub async fn get_for_product(&self, product_uuid: Uuid) -> Result<Option<PriceInfo>> {
-        let row = sqlx::query("SELECT price_uuid, product_uuid, market_mid, market_low, last_sync_timestamp FROM Pricing_Matrix WHERE product_uuid = ?")
+        let row = sqlx::query("SELECT price_uuid, product_uuid, market_mid, market_low, last_sync_timestamp, currency FROM Pricing_Matrix WHERE product_uuid = ?")
             .bind(product_uuid.to_string())
             .fetch_optional(&self.pool)
             .await
@@ -56,6 +65,7 @@ impl PricingRepository {
                 market_mid,
                 market_low,
                 last_sync_timestamp,
+                currency: Self::row_currency(&row),
             }))
         } else {
             Ok(None)
@@ -64,7 +74,7 @@ impl PricingRepository {
 
     pub async fn get_recent(&self, limit: i64) -> Result<Vec<PriceInfo>> {
         let rows = sqlx::query(
-            "SELECT price_uuid, product_uuid, market_mid, market_low, last_sync_timestamp 
+            "SELECT price_uuid, product_uuid, market_mid, market_low, last_sync_timestamp, currency 
              FROM Pricing_Matrix 
              ORDER BY last_sync_timestamp DESC 
              LIMIT ?",
@@ -94,6 +104,7 @@ impl PricingRepository {
                 market_mid,
                 market_low,
                 last_sync_timestamp,
+                currency: Self::row_currency(&row),
             });
         }
         Ok(results)
@@ -147,8 +158,8 @@ impl PricingRepository {
     // --- Price History (Task 086) ---
     pub async fn record_price_history(&self, price: &PriceInfo, source: &str) -> Result<()> {
         sqlx::query(
-            "INSERT INTO Price_History (history_uuid, product_uuid, market_mid, market_low, source, recorded_at) 
-             VALUES (?, ?, ?, ?, ?, ?)"
+            "INSERT INTO Price_History (history_uuid, product_uuid, market_mid, market_low, source, recorded_at, currency) 
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(Uuid::new_v4().to_string())
         .bind(price.product_uuid.to_string())
@@ -156,6 +167,7 @@ impl PricingRepository {
         .bind(price.market_low)
         .bind(source)
         .bind(chrono::Utc::now().to_rfc3339())
+        .bind(price.currency.code())
         .execute(&self.pool)
         .await
         .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
@@ -13,6 +13,10 @@ impl SyncRepository {
         Self { pool, node_id }
     }
 
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
     pub async fn log_change(
         &self,
         record_id: &str,
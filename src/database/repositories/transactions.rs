@@ -6,6 +6,9 @@ use serde::{Deserialize, Serialize};
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
+use super::movements::{self, MovementSource, MovementType};
+use super::sync::SyncRepository;
+
 #[derive(Clone)]
 pub struct TransactionRepository {
     pool: SqlitePool,
@@ -47,7 +50,7 @@ impl TransactionRepository {
 
         // Insert Transaction Header
         sqlx::query(
-            "INSERT INTO Transactions (transaction_uuid, customer_uuid, user_uuid, timestamp, transaction_type) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO Transactions (transaction_uuid, customer_uuid, user_uuid, timestamp, transaction_type, currency) VALUES (?, ?, ?, ?, ?, (SELECT base_currency FROM Currency_Settings WHERE id = 1))",
         )
         .bind(transaction.transaction_uuid.to_string())
         .bind(transaction.customer_uuid.map(|id| id.to_string()))
@@ -678,7 +681,8 @@ impl TransactionRepository {
         end_date: &str,
     ) -> Result<std::collections::HashMap<String, f64>> {
         // Query Payment_Methods table directly
-        // Note: We should filter by transaction date, so we join Transactions
+        // Note: We should filter by transaction date, so we join Transactions.
+        // Post-sale settlements are reported with their adjustment instead.
         let rows = sqlx::query(
             "SELECT 
                  pm.method_type,
@@ -686,6 +690,10 @@ impl TransactionRepository {
               FROM Payment_Methods pm
               JOIN Transactions t ON pm.transaction_uuid = t.transaction_uuid
               WHERE t.timestamp >= ? AND t.timestamp < ? AND t.transaction_type = 'Sale'
+                AND pm.payment_uuid NOT IN (
+                    SELECT payment_uuid FROM Transaction_Adjustments
+                    WHERE payment_uuid IS NOT NULL
+                      AND adjustment_type IN ('price_correction', 'line_void'))
               GROUP BY pm.method_type",
         )
         .bind(start_date)
@@ -780,12 +788,28 @@ impl TransactionRepository {
         items: Vec<TransactionItem>,
         transaction_type: TransactionType,
     ) -> Result<Transaction> {
+        let transaction_uuid = Uuid::new_v4();
+        let movement_type = match transaction_type {
+            TransactionType::Return => MovementType::Return,
+            _ => MovementType::Sale,
+        };
+        let source = MovementSource::new(
+            movement_type,
+            Some(transaction_uuid),
+            user_uuid,
+            &self.node_id,
+        );
+
         // 1. Validate and Deduct Inventory
+        // (inventory_uuid, quantity, unit_price) drawn from each row, for consignment accrual
+        let mut deducted: Vec<(String, i32, f64)> = Vec::new();
         for item in &items {
+            // Store-owned stock sells before consigned stock of the same product
             let rows = sqlx::query(
                 "SELECT * FROM Local_Inventory 
                  WHERE product_uuid = ? AND condition = ? 
-                 ORDER BY inventory_uuid ASC",
+                 ORDER BY inventory_uuid IN (SELECT inventory_uuid FROM Consignment_Items WHERE status = 'Active') ASC,
+                          inventory_uuid ASC",
             )
             .bind(item.product_uuid.to_string())
             .bind(format!("{:?}", item.condition))
@@ -793,13 +817,27 @@ impl TransactionRepository {
             .await
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
-            let mut remaining_needed = item.quantity;
             let mut available_total = 0;
             for row in &rows {
                 let q: i64 = row.try_get("quantity_on_hand").unwrap_or(0);
                 available_total += q as i32;
             }
 
+            // Kits short on assembled stock are made up from their components
+            let mut remaining_needed = item.quantity;
+            if movement_type == MovementType::Sale {
+                remaining_needed -= crate::services::kitting::consume_kit_shortfall_with_tx(
+                    tx,
+                    item.product_uuid,
+                    available_total,
+                    item.quantity,
+                    None,
+                    &format!("{:?}", item.condition),
+                    &source,
+                )
+                .await?;
+            }
+
             if available_total < remaining_needed {
                 return Err(crate::errors::VaultSyncError::InventoryError(format!(
                     "Insufficient inventory for product {}. Required: {}, Available: {}",
@@ -874,27 +912,14 @@ impl TransactionRepository {
                 let deduct = std::cmp::min(remaining_needed, current_qty);
                 let new_qty = current_qty - deduct;
                 remaining_needed -= deduct;
+                if deduct > 0 {
+                    deducted.push((inv_uuid_str.clone(), deduct, item.unit_price));
+                }
 
                 inv_item.quantity_on_hand = new_qty;
 
-                if new_qty == 0 {
-                    sqlx::query(
-                        "UPDATE Local_Inventory SET quantity_on_hand = 0 WHERE inventory_uuid = ?",
-                    )
-                    .bind(&inv_uuid_str)
-                    .execute(&mut **tx)
-                    .await
-                    .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
-                } else {
-                    sqlx::query(
-                        "UPDATE Local_Inventory SET quantity_on_hand = ? WHERE inventory_uuid = ?",
-                    )
-                    .bind(new_qty as i64)
-                    .bind(&inv_uuid_str)
-                    .execute(&mut **tx)
-                    .await
-                    .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
-                }
+                movements::adjust_quantity_with_tx(tx, inv_item.inventory_uuid, -deduct, &source)
+                    .await?;
 
                 // SECURITY FIX: Use ? operator for serialization
                 self.log_change_internal(
@@ -909,7 +934,6 @@ impl TransactionRepository {
         }
 
         // 2. Create Transaction Record
-        let transaction_uuid = Uuid::new_v4();
         let timestamp = chrono::Utc::now();
 
         let transaction = Transaction {
@@ -922,7 +946,7 @@ impl TransactionRepository {
         };
 
         sqlx::query(
-            "INSERT INTO Transactions (transaction_uuid, customer_uuid, user_uuid, timestamp, transaction_type) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO Transactions (transaction_uuid, customer_uuid, user_uuid, timestamp, transaction_type, currency) VALUES (?, ?, ?, ?, ?, (SELECT base_currency FROM Currency_Settings WHERE id = 1))",
         )
         .bind(transaction_uuid.to_string())
         .bind(customer_uuid.map(|id| id.to_string()))
@@ -933,6 +957,18 @@ impl TransactionRepository {
         .await
         .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
+        // Split consigned sales between the store and the consignor
+        for (inventory_uuid, quantity, unit_price) in &deducted {
+            crate::services::consignment::accrue_sale_with_tx(
+                tx,
+                transaction_uuid,
+                inventory_uuid,
+                *quantity,
+                *unit_price,
+            )
+            .await?;
+        }
+
         // 3. Create Transaction Items
         for item in &items {
             sqlx::query(
@@ -951,6 +987,23 @@ impl TransactionRepository {
             .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
         }
 
+        // Sales to a known customer earn loyalty points like checkout sales do
+        if let (TransactionType::Sale, Some(customer_uuid)) = (&transaction_type, customer_uuid) {
+            let total_due: f64 = items
+                .iter()
+                .map(|item| item.unit_price * item.quantity as f64)
+                .sum();
+            crate::services::loyalty::accrue_sale_with_tx(
+                &SyncRepository::new(self.pool.clone(), self.node_id.clone()),
+                tx,
+                transaction_uuid,
+                customer_uuid,
+                total_due,
+                0.0,
+            )
+            .await?;
+        }
+
         // SECURITY FIX: Use ? operator for serialization
         self.log_change_internal(
             tx,
@@ -973,6 +1026,18 @@ impl TransactionRepository {
         items: Vec<TransactionItem>,
         transaction_type: TransactionType,
     ) -> Result<Transaction> {
+        let transaction_uuid = Uuid::new_v4();
+        let movement_type = match transaction_type {
+            TransactionType::Trade => MovementType::TradeIn,
+            _ => MovementType::Buy,
+        };
+        let source = MovementSource::new(
+            movement_type,
+            Some(transaction_uuid),
+            user_uuid,
+            &self.node_id,
+        );
+
         // 1. Add Inventory
         for item in &items {
             // Check for existing bulk pile (Same product, condition, no special fields)
@@ -1004,14 +1069,13 @@ impl TransactionRepository {
                 })?;
                 let new_qty = current_qty + item.quantity as i64;
 
-                sqlx::query(
-                    "UPDATE Local_Inventory SET quantity_on_hand = ? WHERE inventory_uuid = ?",
+                movements::adjust_quantity_with_tx(
+                    tx,
+                    Uuid::parse_str(&inv_uuid_str)?,
+                    item.quantity,
+                    &source,
                 )
-                .bind(new_qty)
-                .bind(&inv_uuid_str)
-                .execute(&mut **tx)
-                .await
-                .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
+                .await?;
 
                 let product_uuid_str: String = row.try_get("product_uuid").map_err(|e| {
                     crate::errors::VaultSyncError::DatabaseError(format!(
@@ -1087,6 +1151,16 @@ impl TransactionRepository {
                 .await
                 .map_err(|e| crate::errors::VaultSyncError::DatabaseError(e.to_string()))?;
 
+                movements::record_movement_with_tx(
+                    tx,
+                    new_inv_uuid,
+                    item.product_uuid,
+                    0,
+                    item.quantity,
+                    &source,
+                )
+                .await?;
+
                 self.log_change_internal(
                     tx,
                     &new_inv_uuid.to_string(),
@@ -1099,7 +1173,6 @@ impl TransactionRepository {
         }
 
         // 2. Create Transaction Record
-        let transaction_uuid = Uuid::new_v4();
         let timestamp = chrono::Utc::now();
 
         let transaction = Transaction {
@@ -1112,7 +1185,7 @@ impl TransactionRepository {
         };
 
         sqlx::query(
-            "INSERT INTO Transactions (transaction_uuid, customer_uuid, user_uuid, timestamp, transaction_type) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO Transactions (transaction_uuid, customer_uuid, user_uuid, timestamp, transaction_type, currency) VALUES (?, ?, ?, ?, ?, (SELECT base_currency FROM Currency_Settings WHERE id = 1))",
         )
         .bind(transaction_uuid.to_string())
         .bind(customer_uuid.map(|id| id.to_string()))
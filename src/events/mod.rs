@@ -1,10 +1,41 @@
-use crate::core::{Event, EventParticipant};
+pub mod prizes;
+pub mod recurring;
+pub mod tournament;
+
+pub use prizes::{
+    EventAttendance, EventPnl, PayoutSummary, PrizeBasis, PrizePayout, PrizeService, PrizeTier,
+    PrizeTierRequest, PrizeType, SkippedPrize,
+};
+pub use recurring::{EventTemplate, EventTemplateRequest, DEFAULT_SPAWN_HORIZON_DAYS};
+pub use tournament::{
+    MatchResult, RoundStage, RoundStatus, StandingsEntry, TournamentConfig, TournamentMatch,
+    TournamentRound, TournamentService,
+};
+
+use crate::core::{Event, EventParticipant, ParticipantStatus};
 use crate::database::Database;
 use crate::errors::Result;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// How an entry fee balance is collected or a refund paid
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryPaymentMethod {
+    StoreCredit,
+    Cash,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegistrationCancellation {
+    pub participant: EventParticipant,
+    pub refunded: f64,
+    /// Waitlisted player moved into the freed seat
+    pub promoted: Option<EventParticipant>,
+}
+
 pub struct EventService {
     db: Arc<Database>,
 }
@@ -45,7 +76,7 @@ impl EventService {
 
     /// HIGH-006 FIX: Register a player with proper validation
     /// - Validates event exists
-    /// - Enforces max_participants limit
+    /// - Puts the player on the waitlist once max_participants is reached
     /// - Handles store credit payment
     pub async fn register_player(
         &self,
@@ -54,7 +85,6 @@ impl EventService {
         customer_uuid: Option<Uuid>,
         pay_with_credit: bool,
     ) -> Result<EventParticipant> {
-        // 1. Validate event exists and get details
         let event = self
             .db
             .events
@@ -62,75 +92,108 @@ impl EventService {
             .await?
             .ok_or_else(|| anyhow::anyhow!("Event {} not found", event_uuid))?;
 
-        // 2. Check max participants limit
-        if let Some(max) = event.max_participants {
-            let current_participants = self.db.events.get_participants(event_uuid).await?;
-            let current_count = current_participants.len() as i32;
+        // Cash is collected at the register (or at check-in) and recorded separately
+        let credit_due = match customer_uuid {
+            Some(_) if pay_with_credit => Some(event.entry_fee),
+            _ => None,
+        };
+        self.enroll(&event, player_name, customer_uuid, credit_due)
+            .await
+    }
 
-            if current_count >= max {
-                return Err(anyhow::anyhow!(
-                    "Event '{}' is full ({}/{} participants)",
+    /// Pre-register a customer ahead of the event, taking a deposit (the
+    /// full fee by default) from store credit. Full events waitlist them
+    /// without charging.
+    pub async fn pre_register(
+        &self,
+        event_uuid: Uuid,
+        customer_uuid: Uuid,
+        deposit: Option<f64>,
+    ) -> Result<EventParticipant> {
+        let event = self
+            .db
+            .events
+            .get_by_id(event_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Event {} not found", event_uuid))?;
+        if event.date <= Utc::now() {
+            return Err(anyhow::anyhow!(
+                "Event '{}' has already started",
+                event.name
+            ));
+        }
+        let customer = self
+            .db
+            .customers
+            .get_by_id(customer_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Customer {} not found", customer_uuid))?;
+        let deposit = deposit.unwrap_or(event.entry_fee);
+        if deposit < 0.0 || deposit > event.entry_fee {
+            return Err(anyhow::anyhow!(
+                "Deposit must be between $0.00 and the ${:.2} entry fee",
+                event.entry_fee
+            ));
+        }
+
+        self.enroll(&event, customer.name, Some(customer_uuid), Some(deposit))
+            .await
+    }
+
+    /// Seat or waitlist a player, charging `credit_due` from store credit
+    /// when they get a seat
+    async fn enroll(
+        &self,
+        event: &Event,
+        player_name: String,
+        customer_uuid: Option<Uuid>,
+        credit_due: Option<f64>,
+    ) -> Result<EventParticipant> {
+        let mut status = ParticipantStatus::Registered;
+        if let Some(max) = event.max_participants {
+            let seated = self.db.events.count_seated(event.event_uuid).await? as i32;
+            if seated >= max {
+                status = ParticipantStatus::Waitlisted;
+                tracing::info!(
+                    "Event '{}' is full ({}/{}), waitlisting '{}'",
                     event.name,
-                    current_count,
-                    max
-                ));
+                    seated,
+                    max,
+                    player_name
+                );
             }
-
-            tracing::debug!(
-                "Event '{}' has capacity: {}/{} participants",
-                event.name,
-                current_count,
-                max
-            );
         }
 
-        // 3. Handle payment
+        let mut fee_paid = 0.0;
         let mut paid = false;
-
-        if pay_with_credit {
-            if let Some(c_uuid) = customer_uuid {
-                if event.entry_fee > 0.0 {
-                    // Check customer has sufficient credit
-                    let customer = self
-                        .db
-                        .customers
-                        .get_by_id(c_uuid)
-                        .await?
-                        .ok_or_else(|| anyhow::anyhow!("Customer {} not found", c_uuid))?;
-
-                    if customer.store_credit < event.entry_fee {
-                        return Err(anyhow::anyhow!(
-                            "Insufficient store credit: ${:.2} available, ${:.2} required",
-                            customer.store_credit,
-                            event.entry_fee
-                        ));
-                    }
-
-                    self.db
-                        .customers
-                        .update_store_credit(c_uuid, -event.entry_fee)
-                        .await?;
-                    paid = true;
-                    tracing::info!(
-                        "Deducted ${:.2} store credit from customer {} for event '{}'",
-                        event.entry_fee,
-                        c_uuid,
-                        event.name
-                    );
-                } else {
-                    paid = true; // Free event
-                }
+        if let (Some(c_uuid), Some(amount), ParticipantStatus::Registered) =
+            (customer_uuid, credit_due, status)
+        {
+            if amount > 0.0 {
+                self.charge_credit(c_uuid, amount).await?;
+                fee_paid = amount;
+                tracing::info!(
+                    "Deducted ${:.2} store credit from customer {} for event '{}'",
+                    amount,
+                    c_uuid,
+                    event.name
+                );
             }
+            paid = fee_paid >= event.entry_fee;
         }
-        // If not pay_with_credit, paid remains false (cash payment to be collected manually)
 
-        // 4. Create and save participant
         let participant = EventParticipant {
             participant_uuid: Uuid::new_v4(),
-            event_uuid,
+            event_uuid: event.event_uuid,
             customer_uuid,
             name: player_name.clone(),
             paid,
+            fee_paid,
+            payment_method: (fee_paid > 0.0).then(|| "credit".to_string()),
+            cash_paid: 0.0,
+            status,
+            checked_in_at: None,
+            refunded_amount: 0.0,
             placement: None,
             created_at: Utc::now(),
         };
@@ -138,19 +201,238 @@ impl EventService {
         self.db.events.register_participant(&participant).await?;
 
         tracing::info!(
-            "Registered '{}' for event '{}' (paid: {})",
+            "Registered '{}' for event '{}' ({}, paid: {})",
             player_name,
             event.name,
+            status.as_str(),
             paid
         );
 
         Ok(participant)
     }
 
-    pub async fn record_placement(&self, _participant_uuid: Uuid, _placement: i32) -> Result<()> {
-        // Update placement logic would go here.
-        // Need `update_participant_placement` in DB.
-        // Skipping for this MVP phase.
-        Ok(())
+    async fn charge_credit(&self, customer_uuid: Uuid, amount: f64) -> Result<()> {
+        let customer = self
+            .db
+            .customers
+            .get_by_id(customer_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Customer {} not found", customer_uuid))?;
+        if customer.store_credit < amount {
+            return Err(anyhow::anyhow!(
+                "Insufficient store credit: ${:.2} available, ${:.2} required",
+                customer.store_credit,
+                amount
+            ));
+        }
+        self.db
+            .customers
+            .update_store_credit(customer_uuid, -amount)
+            .await
+    }
+
+    async fn load_participant(&self, participant_uuid: Uuid) -> Result<(EventParticipant, Event)> {
+        let participant = self
+            .db
+            .events
+            .get_participant(participant_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Participant {} not found", participant_uuid))?;
+        let event = self
+            .db
+            .events
+            .get_by_id(participant.event_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Event {} not found", participant.event_uuid))?;
+        Ok((participant, event))
+    }
+
+    /// Record an entry fee taken at the register. Defaults to the balance owed.
+    pub async fn record_cash_payment(
+        &self,
+        participant_uuid: Uuid,
+        amount: Option<f64>,
+    ) -> Result<EventParticipant> {
+        let (mut participant, event) = self.load_participant(participant_uuid).await?;
+        if participant.paid {
+            return Err(anyhow::anyhow!("{} has already paid", participant.name));
+        }
+        let amount = amount.unwrap_or(event.entry_fee - participant.fee_paid);
+        if amount < 0.0 {
+            return Err(anyhow::anyhow!("Amount cannot be negative"));
+        }
+
+        participant.fee_paid += amount;
+        participant.cash_paid += amount;
+        participant.payment_method = Some("cash".to_string());
+        participant.paid = participant.fee_paid + 0.005 >= event.entry_fee;
+        self.db.events.update_participant(&participant).await?;
+        Ok(participant)
+    }
+
+    /// Check a player in at the door, collecting any balance still owed.
+    /// Waitlisted players can check in while a seat is free.
+    pub async fn check_in(
+        &self,
+        participant_uuid: Uuid,
+        payment: Option<EntryPaymentMethod>,
+    ) -> Result<EventParticipant> {
+        let (mut participant, event) = self.load_participant(participant_uuid).await?;
+        match participant.status {
+            ParticipantStatus::Registered => {}
+            ParticipantStatus::Waitlisted => {
+                if let Some(max) = event.max_participants {
+                    if self.db.events.count_seated(event.event_uuid).await? >= max as i64 {
+                        return Err(anyhow::anyhow!("Event '{}' is still full", event.name));
+                    }
+                }
+            }
+            ParticipantStatus::CheckedIn => {
+                return Err(anyhow::anyhow!(
+                    "{} is already checked in",
+                    participant.name
+                ))
+            }
+            ParticipantStatus::NoShow | ParticipantStatus::Cancelled => {
+                return Err(anyhow::anyhow!(
+                    "{}'s registration is {}",
+                    participant.name,
+                    participant.status.as_str()
+                ))
+            }
+        }
+
+        let balance = event.entry_fee - participant.fee_paid;
+        if balance > 0.005 {
+            match payment {
+                Some(EntryPaymentMethod::StoreCredit) => {
+                    let customer_uuid = participant.customer_uuid.ok_or_else(|| {
+                        anyhow::anyhow!("Walk-in players can't pay with store credit")
+                    })?;
+                    self.charge_credit(customer_uuid, balance).await?;
+                    participant.payment_method = Some("credit".to_string());
+                }
+                Some(EntryPaymentMethod::Cash) => {
+                    participant.cash_paid += balance;
+                    participant.payment_method = Some("cash".to_string());
+                }
+                None => {
+                    return Err(anyhow::anyhow!(
+                        "{} owes ${:.2} before checking in",
+                        participant.name,
+                        balance
+                    ))
+                }
+            }
+            participant.fee_paid += balance;
+        }
+
+        participant.paid = true;
+        participant.status = ParticipantStatus::CheckedIn;
+        participant.checked_in_at = Some(Utc::now());
+        self.db.events.update_participant(&participant).await?;
+        Ok(participant)
+    }
+
+    /// At the start of the event, mark everyone registered but not
+    /// checked in as a no-show. Deposits are kept.
+    pub async fn mark_no_shows(&self, event_uuid: Uuid) -> Result<Vec<EventParticipant>> {
+        let mut no_shows = Vec::new();
+        for mut participant in self.db.events.get_participants(event_uuid).await? {
+            if participant.status == ParticipantStatus::Registered {
+                participant.status = ParticipantStatus::NoShow;
+                self.db.events.update_participant(&participant).await?;
+                no_shows.push(participant);
+            }
+        }
+        Ok(no_shows)
+    }
+
+    /// Cancel a registration, refunding what was paid (or `amount` of it)
+    /// and promoting the first player on the waitlist into the seat
+    pub async fn cancel_registration(
+        &self,
+        participant_uuid: Uuid,
+        refund: Option<EntryPaymentMethod>,
+        amount: Option<f64>,
+    ) -> Result<RegistrationCancellation> {
+        let (mut participant, event) = self.load_participant(participant_uuid).await?;
+        if matches!(
+            participant.status,
+            ParticipantStatus::Cancelled | ParticipantStatus::NoShow
+        ) {
+            return Err(anyhow::anyhow!(
+                "{}'s registration is already {}",
+                participant.name,
+                participant.status.as_str()
+            ));
+        }
+        let was_seated = participant.status.is_seated();
+
+        let refundable = participant.fee_paid - participant.refunded_amount;
+        let refunded = match refund {
+            None => 0.0,
+            Some(method) => {
+                let amount = amount.unwrap_or(refundable);
+                if amount < 0.0 || amount > refundable + 0.005 {
+                    return Err(anyhow::anyhow!(
+                        "Refund must be between $0.00 and ${:.2}",
+                        refundable
+                    ));
+                }
+                if method == EntryPaymentMethod::StoreCredit && amount > 0.0 {
+                    let customer_uuid = participant.customer_uuid.ok_or_else(|| {
+                        anyhow::anyhow!("Walk-in players can't be refunded to store credit")
+                    })?;
+                    self.db
+                        .customers
+                        .update_store_credit(customer_uuid, amount)
+                        .await?;
+                }
+                amount
+            }
+        };
+
+        participant.refunded_amount += refunded;
+        participant.status = ParticipantStatus::Cancelled;
+        self.db.events.update_participant(&participant).await?;
+
+        let mut promoted = None;
+        if was_seated {
+            let waitlist = self.db.events.get_participants(event.event_uuid).await?;
+            if let Some(mut next) = waitlist
+                .into_iter()
+                .find(|p| p.status == ParticipantStatus::Waitlisted)
+            {
+                next.status = ParticipantStatus::Registered;
+                self.db.events.update_participant(&next).await?;
+                tracing::info!(
+                    "Promoted '{}' from the waitlist for event '{}'",
+                    next.name,
+                    event.name
+                );
+                promoted = Some(next);
+            }
+        }
+
+        Ok(RegistrationCancellation {
+            participant,
+            refunded,
+            promoted,
+        })
+    }
+
+    pub async fn get_participants(&self, event_uuid: Uuid) -> Result<Vec<EventParticipant>> {
+        self.db.events.get_participants(event_uuid).await
+    }
+
+    pub async fn record_placement(&self, participant_uuid: Uuid, placement: i32) -> Result<()> {
+        if placement < 1 {
+            return Err(anyhow::anyhow!("Placement must be 1 or higher"));
+        }
+        self.db
+            .events
+            .update_placement(participant_uuid, Some(placement))
+            .await
     }
 }
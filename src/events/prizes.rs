@@ -0,0 +1,705 @@
+//! Prize support for events
+//!
+//! Each event carries a prize structure: tiers paid by final placement
+//! ("1st-2nd: $20 credit") or by Swiss record ("6+ match points: 2 packs").
+//! Paying out credits `store_credit` or rings up a zero-price sale so prize
+//! product leaves inventory through the normal ledger. The event P&L weighs
+//! entry fees collected against what the prizes cost the store.
+
+use crate::core::money::round_cents;
+use crate::core::{Condition, TransactionItem};
+use crate::database::Database;
+use crate::errors::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::TournamentService;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PrizeBasis {
+    /// Paid for finishing within a range of places
+    Placement,
+    /// Paid for reaching a number of match points, best tier only
+    Record,
+}
+
+impl PrizeBasis {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PrizeBasis::Placement => "placement",
+            PrizeBasis::Record => "record",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PrizeType {
+    Credit,
+    Product,
+}
+
+impl PrizeType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PrizeType::Credit => "credit",
+            PrizeType::Product => "product",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        if s == "product" {
+            PrizeType::Product
+        } else {
+            PrizeType::Credit
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrizeTierRequest {
+    pub basis: PrizeBasis,
+    pub place_from: Option<i32>,
+    pub place_to: Option<i32>,
+    pub min_match_points: Option<i32>,
+    pub prize_type: PrizeType,
+    pub credit_amount: Option<f64>,
+    pub product_uuid: Option<Uuid>,
+    pub condition: Option<Condition>,
+    pub quantity: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrizeTier {
+    pub tier_uuid: Uuid,
+    pub event_uuid: Uuid,
+    pub basis: PrizeBasis,
+    pub place_from: Option<i32>,
+    pub place_to: Option<i32>,
+    pub min_match_points: Option<i32>,
+    pub prize_type: PrizeType,
+    pub credit_amount: f64,
+    pub product_uuid: Option<Uuid>,
+    pub condition: Option<String>,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrizePayout {
+    pub payout_uuid: Uuid,
+    pub event_uuid: Uuid,
+    pub tier_uuid: Uuid,
+    pub participant_uuid: Uuid,
+    pub participant_name: String,
+    pub customer_uuid: Option<Uuid>,
+    pub prize_type: PrizeType,
+    pub credit_amount: f64,
+    pub product_uuid: Option<Uuid>,
+    pub quantity: i32,
+    /// The zero-price sale that took product prizes out of stock
+    pub transaction_uuid: Option<Uuid>,
+    /// Face value for credit, cost basis for product
+    pub cost: f64,
+    pub paid_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedPrize {
+    pub participant_uuid: Uuid,
+    pub name: String,
+    pub tier_uuid: Uuid,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PayoutSummary {
+    pub event_uuid: Uuid,
+    pub payouts: Vec<PrizePayout>,
+    pub skipped: Vec<SkippedPrize>,
+    pub credit_paid: f64,
+    pub product_cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventPnl {
+    pub event_uuid: Uuid,
+    pub name: String,
+    pub date: DateTime<Utc>,
+    pub attendance: i64,
+    pub paid_entries: i64,
+    pub unpaid_entries: i64,
+    pub entry_fees_credit: f64,
+    pub entry_fees_cash: f64,
+    pub refunds: f64,
+    /// Collected less refunds; no-show deposits are kept
+    pub entry_fees_total: f64,
+    pub prize_credit: f64,
+    pub prize_product_cost: f64,
+    pub prize_cost_total: f64,
+    pub net: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventAttendance {
+    pub event_uuid: Uuid,
+    pub name: String,
+    pub event_type: String,
+    pub date: DateTime<Utc>,
+    pub attendance: i64,
+    pub entry_fees: f64,
+    pub prize_cost: f64,
+    pub net: f64,
+}
+
+/// Tiers a player qualifies for: every placement tier covering their
+/// place, plus the single best record tier their match points reach
+pub fn eligible_tiers(
+    tiers: &[PrizeTier],
+    placement: Option<i32>,
+    match_points: Option<i32>,
+) -> Vec<&PrizeTier> {
+    let mut eligible: Vec<&PrizeTier> = tiers
+        .iter()
+        .filter(|t| t.basis == PrizeBasis::Placement)
+        .filter(|t| {
+            placement.is_some_and(|p| {
+                t.place_from.is_some_and(|from| p >= from)
+                    && t.place_to.or(t.place_from).is_some_and(|to| p <= to)
+            })
+        })
+        .collect();
+    let best_record = tiers
+        .iter()
+        .filter(|t| t.basis == PrizeBasis::Record)
+        .filter(|t| match_points.is_some_and(|mp| t.min_match_points.is_some_and(|min| mp >= min)))
+        .max_by_key(|t| t.min_match_points);
+    eligible.extend(best_record);
+    eligible
+}
+
+fn parse_uuid(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<Uuid> {
+    row.try_get::<Option<String>, _>(col)
+        .ok()
+        .flatten()
+        .and_then(|s| Uuid::parse_str(&s).ok())
+}
+
+fn parse_date(row: &sqlx::sqlite::SqliteRow, col: &str) -> DateTime<Utc> {
+    row.try_get::<String, _>(col)
+        .ok()
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
+fn map_tier(row: &sqlx::sqlite::SqliteRow) -> Option<PrizeTier> {
+    Some(PrizeTier {
+        tier_uuid: parse_uuid(row, "tier_uuid")?,
+        event_uuid: parse_uuid(row, "event_uuid")?,
+        basis: if row.try_get::<String, _>("basis").ok()? == "record" {
+            PrizeBasis::Record
+        } else {
+            PrizeBasis::Placement
+        },
+        place_from: row.try_get("place_from").ok().flatten(),
+        place_to: row.try_get("place_to").ok().flatten(),
+        min_match_points: row.try_get("min_match_points").ok().flatten(),
+        prize_type: PrizeType::parse(&row.try_get::<String, _>("prize_type").ok()?),
+        credit_amount: row.try_get("credit_amount").unwrap_or(0.0),
+        product_uuid: parse_uuid(row, "product_uuid"),
+        condition: row.try_get("condition").ok().flatten(),
+        quantity: row.try_get("quantity").unwrap_or(0),
+    })
+}
+
+fn map_payout(row: &sqlx::sqlite::SqliteRow) -> Option<PrizePayout> {
+    Some(PrizePayout {
+        payout_uuid: parse_uuid(row, "payout_uuid")?,
+        event_uuid: parse_uuid(row, "event_uuid")?,
+        tier_uuid: parse_uuid(row, "tier_uuid")?,
+        participant_uuid: parse_uuid(row, "participant_uuid")?,
+        participant_name: row
+            .try_get::<Option<String>, _>("participant_name")
+            .ok()
+            .flatten()
+            .unwrap_or_default(),
+        customer_uuid: parse_uuid(row, "customer_uuid"),
+        prize_type: PrizeType::parse(&row.try_get::<String, _>("prize_type").ok()?),
+        credit_amount: row.try_get("credit_amount").unwrap_or(0.0),
+        product_uuid: parse_uuid(row, "product_uuid"),
+        quantity: row.try_get("quantity").unwrap_or(0),
+        transaction_uuid: parse_uuid(row, "transaction_uuid"),
+        cost: row.try_get("cost").unwrap_or(0.0),
+        paid_at: parse_date(row, "paid_at"),
+    })
+}
+
+pub struct PrizeService {
+    db: Arc<Database>,
+}
+
+impl PrizeService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Replace an event's prize structure. Locked once anything is paid.
+    pub async fn set_prize_structure(
+        &self,
+        event_uuid: Uuid,
+        tiers: Vec<PrizeTierRequest>,
+    ) -> Result<Vec<PrizeTier>> {
+        self.db
+            .events
+            .get_by_id(event_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Event {} not found", event_uuid))?;
+        let paid: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM Event_Prize_Payouts WHERE event_uuid = ?")
+                .bind(event_uuid.to_string())
+                .fetch_one(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if paid > 0 {
+            return Err(anyhow::anyhow!(
+                "Prizes have already been paid for this event"
+            ));
+        }
+
+        for tier in &tiers {
+            match tier.basis {
+                PrizeBasis::Placement => {
+                    let from = tier
+                        .place_from
+                        .ok_or_else(|| anyhow::anyhow!("Placement tiers need place_from"))?;
+                    if from < 1 || tier.place_to.is_some_and(|to| to < from) {
+                        return Err(anyhow::anyhow!("Invalid placement range"));
+                    }
+                }
+                PrizeBasis::Record => {
+                    if tier.min_match_points.is_none_or(|mp| mp < 0) {
+                        return Err(anyhow::anyhow!("Record tiers need min_match_points"));
+                    }
+                }
+            }
+            match tier.prize_type {
+                PrizeType::Credit => {
+                    if tier.credit_amount.is_none_or(|a| a <= 0.0) {
+                        return Err(anyhow::anyhow!("Credit prizes need a positive amount"));
+                    }
+                }
+                PrizeType::Product => {
+                    if tier.product_uuid.is_none() || tier.quantity.is_none_or(|q| q <= 0) {
+                        return Err(anyhow::anyhow!(
+                            "Product prizes need a product and a positive quantity"
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        sqlx::query("DELETE FROM Event_Prize_Tiers WHERE event_uuid = ?")
+            .bind(event_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let now = Utc::now().to_rfc3339();
+        for tier in &tiers {
+            let is_credit = tier.prize_type == PrizeType::Credit;
+            sqlx::query(
+                "INSERT INTO Event_Prize_Tiers
+                 (tier_uuid, event_uuid, basis, place_from, place_to, min_match_points, prize_type,
+                  credit_amount, product_uuid, condition, quantity, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(event_uuid.to_string())
+            .bind(tier.basis.as_str())
+            .bind(tier.place_from)
+            .bind(tier.place_to.or(tier.place_from))
+            .bind(tier.min_match_points)
+            .bind(tier.prize_type.as_str())
+            .bind(if is_credit {
+                round_cents(tier.credit_amount.unwrap_or(0.0))
+            } else {
+                0.0
+            })
+            .bind((!is_credit).then(|| tier.product_uuid.map(|u| u.to_string())))
+            .bind(
+                (!is_credit)
+                    .then(|| format!("{:?}", tier.condition.clone().unwrap_or(Condition::New))),
+            )
+            .bind(if is_credit {
+                0
+            } else {
+                tier.quantity.unwrap_or(0)
+            })
+            .bind(&now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        self.get_prize_structure(event_uuid).await
+    }
+
+    pub async fn get_prize_structure(&self, event_uuid: Uuid) -> Result<Vec<PrizeTier>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Event_Prize_Tiers WHERE event_uuid = ?
+             ORDER BY basis, place_from, min_match_points DESC",
+        )
+        .bind(event_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().filter_map(map_tier).collect())
+    }
+
+    /// Pay every prize earned and not yet paid. Prizes that can't be paid
+    /// (walk-ins owed credit, prize product out of stock) are reported and
+    /// left for a later run.
+    pub async fn pay_out_prizes(
+        &self,
+        event_uuid: Uuid,
+        user_uuid: Option<Uuid>,
+    ) -> Result<PayoutSummary> {
+        let tiers = self.get_prize_structure(event_uuid).await?;
+        if tiers.is_empty() {
+            return Err(anyhow::anyhow!("Event has no prize structure"));
+        }
+        let participants = self.db.events.get_participants(event_uuid).await?;
+        let match_points: HashMap<Uuid, i32> = TournamentService::new(self.db.clone())
+            .get_standings(event_uuid)
+            .await?
+            .into_iter()
+            .map(|s| (s.participant_uuid, s.match_points))
+            .collect();
+        let already_paid: Vec<(String, String)> = sqlx::query_as(
+            "SELECT tier_uuid, participant_uuid FROM Event_Prize_Payouts WHERE event_uuid = ?",
+        )
+        .bind(event_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut summary = PayoutSummary {
+            event_uuid,
+            payouts: Vec::new(),
+            skipped: Vec::new(),
+            credit_paid: 0.0,
+            product_cost: 0.0,
+        };
+
+        for participant in &participants {
+            let mp = match_points.get(&participant.participant_uuid).copied();
+            for tier in eligible_tiers(&tiers, participant.placement, mp) {
+                if already_paid.iter().any(|(t, p)| {
+                    *t == tier.tier_uuid.to_string()
+                        && *p == participant.participant_uuid.to_string()
+                }) {
+                    continue;
+                }
+                let skip = |reason: String| SkippedPrize {
+                    participant_uuid: participant.participant_uuid,
+                    name: participant.name.clone(),
+                    tier_uuid: tier.tier_uuid,
+                    reason,
+                };
+
+                let (transaction_uuid, cost) = match tier.prize_type {
+                    PrizeType::Credit => {
+                        let Some(customer_uuid) = participant.customer_uuid else {
+                            summary
+                                .skipped
+                                .push(skip("Walk-in players can't hold store credit".to_string()));
+                            continue;
+                        };
+                        self.db
+                            .customers
+                            .update_store_credit(customer_uuid, tier.credit_amount)
+                            .await?;
+                        (None, tier.credit_amount)
+                    }
+                    PrizeType::Product => {
+                        match self.issue_product(tier, participant, user_uuid).await {
+                            Ok(issued) => issued,
+                            Err(e) => {
+                                summary.skipped.push(skip(e.to_string()));
+                                continue;
+                            }
+                        }
+                    }
+                };
+
+                let payout = PrizePayout {
+                    payout_uuid: Uuid::new_v4(),
+                    event_uuid,
+                    tier_uuid: tier.tier_uuid,
+                    participant_uuid: participant.participant_uuid,
+                    participant_name: participant.name.clone(),
+                    customer_uuid: participant.customer_uuid,
+                    prize_type: tier.prize_type,
+                    credit_amount: tier.credit_amount,
+                    product_uuid: tier.product_uuid,
+                    quantity: tier.quantity,
+                    transaction_uuid,
+                    cost: round_cents(cost),
+                    paid_at: Utc::now(),
+                };
+                sqlx::query(
+                    "INSERT INTO Event_Prize_Payouts
+                     (payout_uuid, event_uuid, tier_uuid, participant_uuid, customer_uuid, prize_type,
+                      credit_amount, product_uuid, quantity, transaction_uuid, cost, user_uuid, paid_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(payout.payout_uuid.to_string())
+                .bind(event_uuid.to_string())
+                .bind(tier.tier_uuid.to_string())
+                .bind(participant.participant_uuid.to_string())
+                .bind(participant.customer_uuid.map(|u| u.to_string()))
+                .bind(tier.prize_type.as_str())
+                .bind(payout.credit_amount)
+                .bind(payout.product_uuid.map(|u| u.to_string()))
+                .bind(payout.quantity)
+                .bind(transaction_uuid.map(|u| u.to_string()))
+                .bind(payout.cost)
+                .bind(user_uuid.map(|u| u.to_string()))
+                .bind(payout.paid_at.to_rfc3339())
+                .execute(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+                match payout.prize_type {
+                    PrizeType::Credit => summary.credit_paid += payout.cost,
+                    PrizeType::Product => summary.product_cost += payout.cost,
+                }
+                summary.payouts.push(payout);
+            }
+        }
+
+        summary.credit_paid = round_cents(summary.credit_paid);
+        summary.product_cost = round_cents(summary.product_cost);
+        tracing::info!(
+            "Paid {} prizes for event {} ({} skipped)",
+            summary.payouts.len(),
+            event_uuid,
+            summary.skipped.len()
+        );
+        Ok(summary)
+    }
+
+    /// Ring up prize product as a zero-price sale and cost what it drew
+    async fn issue_product(
+        &self,
+        tier: &PrizeTier,
+        participant: &crate::core::EventParticipant,
+        user_uuid: Option<Uuid>,
+    ) -> Result<(Option<Uuid>, f64)> {
+        let product_uuid = tier
+            .product_uuid
+            .ok_or_else(|| anyhow::anyhow!("Prize tier has no product"))?;
+        let condition: Condition = tier
+            .condition
+            .as_deref()
+            .and_then(|c| serde_json::from_value(serde_json::Value::String(c.to_string())).ok())
+            .unwrap_or(Condition::New);
+
+        let transaction = self
+            .db
+            .transactions
+            .execute_sale(
+                participant.customer_uuid,
+                user_uuid,
+                vec![TransactionItem {
+                    item_uuid: Uuid::new_v4(),
+                    product_uuid,
+                    quantity: tier.quantity,
+                    unit_price: 0.0,
+                    condition,
+                }],
+            )
+            .await?;
+
+        let cost: f64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(-m.quantity_change * COALESCE(li.cost_basis, 0.0)), 0.0)
+             FROM Inventory_Movements m
+             JOIN Local_Inventory li ON li.inventory_uuid = m.inventory_uuid
+             WHERE m.source_uuid = ?",
+        )
+        .bind(transaction.transaction_uuid.to_string())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok((Some(transaction.transaction_uuid), cost))
+    }
+
+    pub async fn get_payouts(&self, event_uuid: Uuid) -> Result<Vec<PrizePayout>> {
+        let rows = sqlx::query(
+            "SELECT p.*, ep.name AS participant_name
+             FROM Event_Prize_Payouts p
+             LEFT JOIN Event_Participants ep ON ep.participant_uuid = p.participant_uuid
+             WHERE p.event_uuid = ?
+             ORDER BY p.paid_at",
+        )
+        .bind(event_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().filter_map(map_payout).collect())
+    }
+
+    /// Entry fees against prize cost for one event
+    pub async fn get_pnl(&self, event_uuid: Uuid) -> Result<EventPnl> {
+        let event = self
+            .db
+            .events
+            .get_by_id(event_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Event {} not found", event_uuid))?;
+
+        let fees = sqlx::query(
+            "SELECT COALESCE(SUM(CASE WHEN status IN ('registered', 'checked_in') THEN 1 ELSE 0 END), 0) AS attendance,
+                    COALESCE(SUM(CASE WHEN status IN ('registered', 'checked_in') AND paid THEN 1 ELSE 0 END), 0) AS paid_entries,
+                    COALESCE(SUM(fee_paid - cash_paid), 0.0) AS fees_credit,
+                    COALESCE(SUM(cash_paid), 0.0) AS fees_cash,
+                    COALESCE(SUM(refunded_amount), 0.0) AS refunds
+             FROM Event_Participants WHERE event_uuid = ?",
+        )
+        .bind(event_uuid.to_string())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let prizes = sqlx::query(
+            "SELECT COALESCE(SUM(CASE WHEN prize_type = 'credit' THEN cost ELSE 0.0 END), 0.0) AS prize_credit,
+                    COALESCE(SUM(CASE WHEN prize_type = 'product' THEN cost ELSE 0.0 END), 0.0) AS prize_product
+             FROM Event_Prize_Payouts WHERE event_uuid = ?",
+        )
+        .bind(event_uuid.to_string())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let attendance: i64 = fees.try_get("attendance")?;
+        let paid_entries: i64 = fees.try_get("paid_entries")?;
+        let entry_fees_credit = round_cents(fees.try_get("fees_credit")?);
+        let entry_fees_cash = round_cents(fees.try_get("fees_cash")?);
+        let prize_credit = round_cents(prizes.try_get("prize_credit")?);
+        let prize_product_cost = round_cents(prizes.try_get("prize_product")?);
+        let refunds = round_cents(fees.try_get("refunds")?);
+        let entry_fees_total = round_cents(entry_fees_credit + entry_fees_cash - refunds);
+        let prize_cost_total = round_cents(prize_credit + prize_product_cost);
+
+        Ok(EventPnl {
+            event_uuid,
+            name: event.name,
+            date: event.date,
+            attendance,
+            paid_entries,
+            unpaid_entries: attendance - paid_entries,
+            entry_fees_credit,
+            entry_fees_cash,
+            refunds,
+            entry_fees_total,
+            prize_credit,
+            prize_product_cost,
+            prize_cost_total,
+            net: round_cents(entry_fees_total - prize_cost_total),
+        })
+    }
+
+    /// Attendance and margin per event, oldest first, to see how a night
+    /// or format is trending
+    pub async fn get_attendance(&self, event_type: Option<&str>) -> Result<Vec<EventAttendance>> {
+        let rows = sqlx::query(
+            "SELECT e.event_uuid, e.name, e.event_type, e.date,
+                    (SELECT COUNT(*) FROM Event_Participants p
+                     WHERE p.event_uuid = e.event_uuid AND p.status IN ('registered', 'checked_in')) AS attendance,
+                    (SELECT COALESCE(SUM(p.fee_paid - p.refunded_amount), 0.0) FROM Event_Participants p
+                     WHERE p.event_uuid = e.event_uuid) AS entry_fees,
+                    (SELECT COALESCE(SUM(x.cost), 0.0) FROM Event_Prize_Payouts x WHERE x.event_uuid = e.event_uuid) AS prize_cost
+             FROM Events e
+             WHERE (?1 IS NULL OR e.event_type = ?1)
+             ORDER BY e.date",
+        )
+        .bind(event_type)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let entry_fees = round_cents(row.try_get("entry_fees").ok()?);
+                let prize_cost = round_cents(row.try_get("prize_cost").ok()?);
+                Some(EventAttendance {
+                    event_uuid: parse_uuid(row, "event_uuid")?,
+                    name: row.try_get("name").ok()?,
+                    event_type: row.try_get("event_type").ok()?,
+                    date: parse_date(row, "date"),
+                    attendance: row.try_get("attendance").ok()?,
+                    entry_fees,
+                    prize_cost,
+                    net: round_cents(entry_fees - prize_cost),
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(basis: PrizeBasis, from: Option<i32>, to: Option<i32>, mp: Option<i32>) -> PrizeTier {
+        PrizeTier {
+            tier_uuid: Uuid::new_v4(),
+            event_uuid: Uuid::nil(),
+            basis,
+            place_from: from,
+            place_to: to,
+            min_match_points: mp,
+            prize_type: PrizeType::Credit,
+            credit_amount: 10.0,
+            product_uuid: None,
+            condition: None,
+            quantity: 0,
+        }
+    }
+
+    #[test]
+    fn test_eligible_tiers() {
+        let tiers = vec![
+            tier(PrizeBasis::Placement, Some(1), Some(1), None),
+            tier(PrizeBasis::Placement, Some(1), Some(4), None),
+            tier(PrizeBasis::Record, None, None, Some(9)),
+            tier(PrizeBasis::Record, None, None, Some(6)),
+        ];
+
+        // First place with a 3-0 record: both placement tiers, best record tier
+        let first = eligible_tiers(&tiers, Some(1), Some(9));
+        assert_eq!(first.len(), 3);
+        assert!(first.iter().any(|t| t.min_match_points == Some(9)));
+        assert!(!first.iter().any(|t| t.min_match_points == Some(6)));
+
+        let third = eligible_tiers(&tiers, Some(3), Some(6));
+        assert_eq!(third.len(), 2);
+
+        assert!(eligible_tiers(&tiers, Some(5), Some(3)).is_empty());
+        assert!(eligible_tiers(&tiers, None, None).is_empty());
+    }
+}
@@ -0,0 +1,297 @@
+//! Recurring event templates
+//!
+//! A template describes a standing event ("Friday Night Magic, Fridays at
+//! 18:30, $5, 32 seats"). Instances are spawned as ordinary events a few
+//! weeks ahead so players can pre-register. Start times are UTC, like every
+//! other event date.
+
+use super::EventService;
+use crate::core::Event;
+use crate::errors::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// How far ahead the scheduler keeps instances spawned
+pub const DEFAULT_SPAWN_HORIZON_DAYS: i64 = 28;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventTemplateRequest {
+    pub name: String,
+    pub event_type: String,
+    #[serde(default)]
+    pub entry_fee: f64,
+    pub max_participants: Option<i32>,
+    pub weekday: Weekday,
+    /// "HH:MM"
+    pub start_time: String,
+    pub interval_weeks: Option<i32>,
+    /// First date the series may run; defaults to today
+    pub starts_on: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventTemplate {
+    pub template_uuid: Uuid,
+    pub name: String,
+    pub event_type: String,
+    pub entry_fee: f64,
+    pub max_participants: Option<i32>,
+    pub weekday: Weekday,
+    pub start_time: String,
+    pub interval_weeks: i32,
+    pub starts_on: NaiveDate,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Start times of a series that fall within `[from, until]`
+pub fn occurrences(
+    starts_on: NaiveDate,
+    weekday: Weekday,
+    interval_weeks: i32,
+    start_time: NaiveTime,
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    let offset = (7 + weekday.num_days_from_monday() as i64
+        - starts_on.weekday().num_days_from_monday() as i64)
+        % 7;
+    let step = Duration::weeks(interval_weeks.max(1) as i64);
+    let mut date = starts_on + Duration::days(offset);
+    let mut result = Vec::new();
+    loop {
+        let at = date.and_time(start_time).and_utc();
+        if at > until {
+            break;
+        }
+        if at >= from {
+            result.push(at);
+        }
+        date += step;
+    }
+    result
+}
+
+fn weekday_from_index(index: i64) -> Weekday {
+    match index {
+        0 => Weekday::Mon,
+        1 => Weekday::Tue,
+        2 => Weekday::Wed,
+        3 => Weekday::Thu,
+        4 => Weekday::Fri,
+        5 => Weekday::Sat,
+        _ => Weekday::Sun,
+    }
+}
+
+fn map_template(row: &sqlx::sqlite::SqliteRow) -> Option<EventTemplate> {
+    Some(EventTemplate {
+        template_uuid: Uuid::parse_str(&row.try_get::<String, _>("template_uuid").ok()?).ok()?,
+        name: row.try_get("name").ok()?,
+        event_type: row.try_get("event_type").ok()?,
+        entry_fee: row.try_get("entry_fee").unwrap_or(0.0),
+        max_participants: row.try_get("max_participants").ok().flatten(),
+        weekday: weekday_from_index(row.try_get("weekday").ok()?),
+        start_time: row.try_get("start_time").ok()?,
+        interval_weeks: row.try_get("interval_weeks").unwrap_or(1),
+        starts_on: NaiveDate::parse_from_str(
+            &row.try_get::<String, _>("starts_on").ok()?,
+            "%Y-%m-%d",
+        )
+        .ok()?,
+        active: row.try_get::<i32, _>("active").unwrap_or(1) == 1,
+        created_at: row
+            .try_get::<String, _>("created_at")
+            .ok()
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|d| d.with_timezone(&Utc))
+            .unwrap_or_default(),
+    })
+}
+
+impl EventService {
+    pub async fn create_template(&self, request: EventTemplateRequest) -> Result<EventTemplate> {
+        if request.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Template needs a name"));
+        }
+        NaiveTime::parse_from_str(&request.start_time, "%H:%M")
+            .map_err(|_| anyhow::anyhow!("start_time must be HH:MM"))?;
+        if request.entry_fee < 0.0 {
+            return Err(anyhow::anyhow!("Entry fee cannot be negative"));
+        }
+        let interval_weeks = request.interval_weeks.unwrap_or(1);
+        if interval_weeks < 1 {
+            return Err(anyhow::anyhow!("interval_weeks must be at least 1"));
+        }
+
+        let template_uuid = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO Event_Templates
+             (template_uuid, name, event_type, entry_fee, max_participants, weekday, start_time, interval_weeks, starts_on, active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?)",
+        )
+        .bind(template_uuid.to_string())
+        .bind(request.name.trim())
+        .bind(&request.event_type)
+        .bind(request.entry_fee)
+        .bind(request.max_participants)
+        .bind(request.weekday.num_days_from_monday() as i64)
+        .bind(&request.start_time)
+        .bind(interval_weeks)
+        .bind(
+            request
+                .starts_on
+                .unwrap_or_else(|| Utc::now().date_naive())
+                .format("%Y-%m-%d")
+                .to_string(),
+        )
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        self.get_template(template_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Template not found"))
+    }
+
+    pub async fn get_template(&self, template_uuid: Uuid) -> Result<Option<EventTemplate>> {
+        let row = sqlx::query("SELECT * FROM Event_Templates WHERE template_uuid = ?")
+            .bind(template_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(row.as_ref().and_then(map_template))
+    }
+
+    pub async fn get_templates(&self) -> Result<Vec<EventTemplate>> {
+        let rows = sqlx::query("SELECT * FROM Event_Templates ORDER BY weekday, start_time")
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().filter_map(map_template).collect())
+    }
+
+    /// Pause or resume a series; already spawned events are left alone
+    pub async fn set_template_active(&self, template_uuid: Uuid, active: bool) -> Result<()> {
+        let updated = sqlx::query("UPDATE Event_Templates SET active = ? WHERE template_uuid = ?")
+            .bind(active as i32)
+            .bind(template_uuid.to_string())
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if updated.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Template {} not found", template_uuid));
+        }
+        Ok(())
+    }
+
+    /// Create the series' events for the next `horizon_days`, skipping
+    /// dates that already have one
+    pub async fn spawn_instances(
+        &self,
+        template_uuid: Uuid,
+        horizon_days: i64,
+    ) -> Result<Vec<Event>> {
+        let template = self
+            .get_template(template_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Template {} not found", template_uuid))?;
+        if !template.active {
+            return Ok(Vec::new());
+        }
+        let start_time = NaiveTime::parse_from_str(&template.start_time, "%H:%M")
+            .map_err(|_| anyhow::anyhow!("Template has an invalid start time"))?;
+        let now = Utc::now();
+
+        let mut spawned = Vec::new();
+        for date in occurrences(
+            template.starts_on,
+            template.weekday,
+            template.interval_weeks,
+            start_time,
+            now,
+            now + Duration::days(horizon_days),
+        ) {
+            // Name and date too, so an instance synced from another node counts
+            let existing: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM Events WHERE date = ? AND (template_uuid = ? OR name = ?)",
+            )
+            .bind(date.to_rfc3339())
+            .bind(template_uuid.to_string())
+            .bind(&template.name)
+            .fetch_one(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            if existing > 0 {
+                continue;
+            }
+
+            let event = self
+                .create_event(
+                    template.name.clone(),
+                    template.event_type.clone(),
+                    date,
+                    template.entry_fee,
+                    template.max_participants,
+                )
+                .await?;
+            sqlx::query("UPDATE Events SET template_uuid = ? WHERE event_uuid = ?")
+                .bind(template_uuid.to_string())
+                .bind(event.event_uuid.to_string())
+                .execute(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            spawned.push(event);
+        }
+
+        if !spawned.is_empty() {
+            tracing::info!(
+                "Spawned {} '{}' events from template",
+                spawned.len(),
+                template.name
+            );
+        }
+        Ok(spawned)
+    }
+
+    /// Scheduler entry point: top up every active series
+    pub async fn spawn_due_instances(&self, horizon_days: i64) -> Result<usize> {
+        let mut total = 0;
+        for template in self.get_templates().await?.into_iter().filter(|t| t.active) {
+            total += self
+                .spawn_instances(template.template_uuid, horizon_days)
+                .await?
+                .len();
+        }
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_occurrences_weekly_and_fortnightly() {
+        // 2026-10-18 is a Sunday
+        let starts_on = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let time = NaiveTime::from_hms_opt(18, 30, 0).unwrap();
+        let from = starts_on.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let until = from + Duration::days(28);
+
+        let weekly = occurrences(starts_on, Weekday::Fri, 1, time, from, until);
+        let days: Vec<u32> = weekly.iter().map(|d| d.day()).collect();
+        assert_eq!(days, vec![23, 30, 6, 13]);
+        assert!(weekly.iter().all(|d| d.weekday() == Weekday::Fri));
+
+        let fortnightly = occurrences(starts_on, Weekday::Fri, 2, time, from, until);
+        assert_eq!(fortnightly.len(), 2);
+
+        // Same weekday as the start date runs that day
+        let sunday = occurrences(starts_on, Weekday::Sun, 1, time, from, until);
+        assert_eq!(sunday[0].day(), 18);
+    }
+}
@@ -0,0 +1,1078 @@
+//! Tournament engine on top of `Events` and `Event_Participants`
+//!
+//! Swiss rounds are paired top-down within match-point brackets, avoiding
+//! rematches, with the bye going to the lowest-ranked player who hasn't had
+//! one. Standings use match points then OMW%, GW% and OGW%, with the usual
+//! 33% floor. After the Swiss rounds an optional single-elimination top cut
+//! is seeded 1 v 8, 4 v 5, 2 v 7, 3 v 6. Each round carries its own timer.
+
+use crate::database::Database;
+use crate::errors::Result;
+use chrono::{DateTime, Duration, Utc};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Match points for a win; a draw is worth one
+const WIN_POINTS: i32 = 3;
+/// Floor applied to every MW% and GW% in the tiebreakers
+const PERCENT_FLOOR: f64 = 1.0 / 3.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundStage {
+    Swiss,
+    TopCut,
+}
+
+impl RoundStage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RoundStage::Swiss => "swiss",
+            RoundStage::TopCut => "top_cut",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundStatus {
+    /// Pairings posted, clock not started
+    Paired,
+    InProgress,
+    Completed,
+}
+
+impl RoundStatus {
+    fn parse(s: &str) -> Self {
+        match s {
+            "in_progress" => RoundStatus::InProgress,
+            "completed" => RoundStatus::Completed,
+            _ => RoundStatus::Paired,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TournamentConfig {
+    /// Defaults to enough rounds to leave one undefeated player
+    pub swiss_rounds: Option<i32>,
+    /// 0 for none, otherwise 2, 4, 8, ...
+    pub top_cut: Option<i32>,
+    pub round_minutes: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TournamentMatch {
+    pub match_uuid: Uuid,
+    pub table_number: i32,
+    pub player1_uuid: Uuid,
+    pub player1_name: String,
+    /// None for a bye
+    pub player2_uuid: Option<Uuid>,
+    pub player2_name: Option<String>,
+    pub player1_wins: i32,
+    pub player2_wins: i32,
+    pub draws: i32,
+    pub reported: bool,
+}
+
+impl TournamentMatch {
+    pub fn is_bye(&self) -> bool {
+        self.player2_uuid.is_none()
+    }
+
+    fn winner(&self) -> Option<Uuid> {
+        match self.player2_uuid {
+            None => Some(self.player1_uuid),
+            Some(p2) if self.player2_wins > self.player1_wins => Some(p2),
+            Some(_) if self.player1_wins > self.player2_wins => Some(self.player1_uuid),
+            Some(_) => None,
+        }
+    }
+
+    fn loser(&self) -> Option<Uuid> {
+        let winner = self.winner()?;
+        self.player2_uuid
+            .map(|p2| if winner == p2 { self.player1_uuid } else { p2 })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TournamentRound {
+    pub round_uuid: Uuid,
+    pub event_uuid: Uuid,
+    pub round_number: i32,
+    pub stage: RoundStage,
+    pub status: RoundStatus,
+    pub time_limit_minutes: i32,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Seconds left on the clock; negative once time is called
+    pub remaining_seconds: Option<i64>,
+    pub matches: Vec<TournamentMatch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub player1_wins: i32,
+    pub player2_wins: i32,
+    #[serde(default)]
+    pub draws: i32,
+}
+
+/// A finished match as the standings see it
+#[derive(Debug, Clone)]
+pub struct ReportedMatch {
+    pub player1_uuid: Uuid,
+    pub player2_uuid: Option<Uuid>,
+    pub player1_wins: i32,
+    pub player2_wins: i32,
+    pub draws: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StandingsEntry {
+    pub rank: i32,
+    pub participant_uuid: Uuid,
+    pub name: String,
+    pub match_points: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub draws: i32,
+    pub omw_pct: f64,
+    pub gw_pct: f64,
+    pub ogw_pct: f64,
+    pub dropped: bool,
+}
+
+/// A player as the pairing algorithm sees them, in standings order
+#[derive(Debug, Clone)]
+pub struct PairingCandidate {
+    pub participant_uuid: Uuid,
+    pub match_points: i32,
+    pub opponents: HashSet<Uuid>,
+    pub had_bye: bool,
+}
+
+/// Rounds needed for a single undefeated player
+pub fn default_swiss_rounds(players: usize) -> i32 {
+    let mut rounds = 1;
+    while (1usize << rounds) < players {
+        rounds += 1;
+    }
+    rounds
+}
+
+#[derive(Default)]
+struct Tally {
+    match_points: i32,
+    wins: i32,
+    losses: i32,
+    draws: i32,
+    rounds: i32,
+    game_points: i32,
+    games: i32,
+    opponents: Vec<Uuid>,
+}
+
+impl Tally {
+    fn mw_pct(&self) -> f64 {
+        if self.rounds == 0 {
+            return PERCENT_FLOOR;
+        }
+        (self.match_points as f64 / (WIN_POINTS * self.rounds) as f64).max(PERCENT_FLOOR)
+    }
+
+    fn gw_pct(&self) -> f64 {
+        if self.games == 0 {
+            return PERCENT_FLOOR;
+        }
+        (self.game_points as f64 / (WIN_POINTS * self.games) as f64).max(PERCENT_FLOOR)
+    }
+
+    fn record(&mut self, own_wins: i32, their_wins: i32, draws: i32, opponent: Option<Uuid>) {
+        self.rounds += 1;
+        self.game_points += own_wins * WIN_POINTS + draws;
+        self.games += own_wins + their_wins + draws;
+        match own_wins.cmp(&their_wins) {
+            std::cmp::Ordering::Greater => {
+                self.wins += 1;
+                self.match_points += WIN_POINTS;
+            }
+            std::cmp::Ordering::Less => self.losses += 1,
+            std::cmp::Ordering::Equal => {
+                self.draws += 1;
+                self.match_points += 1;
+            }
+        }
+        if let Some(opponent) = opponent {
+            self.opponents.push(opponent);
+        }
+    }
+}
+
+fn tally(matches: &[ReportedMatch]) -> HashMap<Uuid, Tally> {
+    let mut tallies: HashMap<Uuid, Tally> = HashMap::new();
+    for m in matches {
+        match m.player2_uuid {
+            // A bye is a 2-0 win against no one
+            None => tallies
+                .entry(m.player1_uuid)
+                .or_default()
+                .record(2, 0, 0, None),
+            Some(p2) => {
+                tallies.entry(m.player1_uuid).or_default().record(
+                    m.player1_wins,
+                    m.player2_wins,
+                    m.draws,
+                    Some(p2),
+                );
+                tallies.entry(p2).or_default().record(
+                    m.player2_wins,
+                    m.player1_wins,
+                    m.draws,
+                    Some(m.player1_uuid),
+                );
+            }
+        }
+    }
+    tallies
+}
+
+fn round3(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// Standings from Swiss results: match points, then OMW%, GW%, OGW%
+pub fn compute_standings(
+    players: &[(Uuid, String, bool)],
+    matches: &[ReportedMatch],
+) -> Vec<StandingsEntry> {
+    let tallies = tally(matches);
+    let empty = Tally::default();
+    let average = |opponents: &[Uuid], pct: fn(&Tally) -> f64| -> f64 {
+        if opponents.is_empty() {
+            return 0.0;
+        }
+        opponents
+            .iter()
+            .map(|o| pct(tallies.get(o).unwrap_or(&empty)))
+            .sum::<f64>()
+            / opponents.len() as f64
+    };
+
+    let mut entries: Vec<StandingsEntry> = players
+        .iter()
+        .map(|(participant_uuid, name, dropped)| {
+            let t = tallies.get(participant_uuid).unwrap_or(&empty);
+            StandingsEntry {
+                rank: 0,
+                participant_uuid: *participant_uuid,
+                name: name.clone(),
+                match_points: t.match_points,
+                wins: t.wins,
+                losses: t.losses,
+                draws: t.draws,
+                omw_pct: round3(average(&t.opponents, Tally::mw_pct)),
+                gw_pct: round3(t.gw_pct()),
+                ogw_pct: round3(average(&t.opponents, Tally::gw_pct)),
+                dropped: *dropped,
+            }
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        b.match_points
+            .cmp(&a.match_points)
+            .then(b.omw_pct.total_cmp(&a.omw_pct))
+            .then(b.gw_pct.total_cmp(&a.gw_pct))
+            .then(b.ogw_pct.total_cmp(&a.ogw_pct))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.rank = i as i32 + 1;
+    }
+    entries
+}
+
+/// Pair one Swiss round. `candidates` must be in standings order; the
+/// result is (player, opponent) with `None` as the bye.
+pub fn swiss_pairings(candidates: &[PairingCandidate]) -> Vec<(Uuid, Option<Uuid>)> {
+    let mut pool: Vec<&PairingCandidate> = candidates.iter().collect();
+    let mut pairings = Vec::with_capacity(pool.len() / 2 + 1);
+
+    let mut bye = None;
+    if pool.len() % 2 == 1 {
+        let index = pool
+            .iter()
+            .rposition(|c| !c.had_bye)
+            .unwrap_or(pool.len() - 1);
+        bye = Some(pool.remove(index).participant_uuid);
+    }
+
+    let pairs = pair_without_rematches(&pool).unwrap_or_else(|| {
+        // Everyone has played everyone they could: pair straight down
+        pool.chunks(2).map(|c| (c[0], c[1])).collect()
+    });
+    for (a, b) in pairs {
+        pairings.push((a.participant_uuid, Some(b.participant_uuid)));
+    }
+    if let Some(bye) = bye {
+        pairings.push((bye, None));
+    }
+    pairings
+}
+
+/// Highest player first, each with the best-ranked opponent they haven't
+/// played, backing up when the players left can't all be paired
+fn pair_without_rematches<'a>(
+    pool: &[&'a PairingCandidate],
+) -> Option<Vec<(&'a PairingCandidate, &'a PairingCandidate)>> {
+    let Some((first, rest)) = pool.split_first() else {
+        return Some(Vec::new());
+    };
+    for (i, opponent) in rest.iter().enumerate() {
+        if first.opponents.contains(&opponent.participant_uuid) {
+            continue;
+        }
+        let remaining: Vec<&PairingCandidate> = rest
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, c)| *c)
+            .collect();
+        if let Some(mut pairs) = pair_without_rematches(&remaining) {
+            pairs.insert(0, (*first, *opponent));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+/// Seed positions for a bracket so 1 and 2 can only meet in the final
+pub fn bracket_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < size {
+        let next = order.len() * 2 + 1;
+        order = order.iter().flat_map(|&s| [s, next - s]).collect();
+    }
+    order
+}
+
+fn parse_date(s: Option<String>) -> Option<DateTime<Utc>> {
+    s.and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|d| d.with_timezone(&Utc))
+}
+
+struct EventSettings {
+    swiss_rounds: Option<i32>,
+    top_cut: i32,
+    round_minutes: i32,
+}
+
+pub struct TournamentService {
+    db: Arc<Database>,
+}
+
+impl TournamentService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Fix the format and pair round one
+    pub async fn start_tournament(
+        &self,
+        event_uuid: Uuid,
+        config: TournamentConfig,
+    ) -> Result<TournamentRound> {
+        let rounds: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM Event_Rounds WHERE event_uuid = ?")
+                .bind(event_uuid.to_string())
+                .fetch_one(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if rounds > 0 {
+            return Err(anyhow::anyhow!("Tournament has already started"));
+        }
+        let players = self.load_players(event_uuid).await?;
+        let active = players.iter().filter(|(_, _, dropped)| !dropped).count();
+        if active < 2 {
+            return Err(anyhow::anyhow!("A tournament needs at least two players"));
+        }
+
+        let top_cut = config.top_cut.unwrap_or(0);
+        if top_cut != 0 && (top_cut < 2 || !(top_cut as u32).is_power_of_two()) {
+            return Err(anyhow::anyhow!("Top cut must be 0, 2, 4, 8, ..."));
+        }
+        if top_cut as usize > active {
+            return Err(anyhow::anyhow!(
+                "Top {} needs at least {} players",
+                top_cut,
+                top_cut
+            ));
+        }
+        let swiss_rounds = config
+            .swiss_rounds
+            .unwrap_or_else(|| default_swiss_rounds(active));
+        if swiss_rounds < 1 {
+            return Err(anyhow::anyhow!(
+                "A tournament needs at least one Swiss round"
+            ));
+        }
+        let round_minutes = config.round_minutes.unwrap_or(50).max(1);
+
+        let updated = sqlx::query(
+            "UPDATE Events SET swiss_rounds = ?, top_cut = ?, round_minutes = ?, status = 'In Progress'
+             WHERE event_uuid = ?",
+        )
+        .bind(swiss_rounds)
+        .bind(top_cut)
+        .bind(round_minutes)
+        .bind(event_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if updated.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Event {} not found", event_uuid));
+        }
+
+        self.pair_next_round(event_uuid).await
+    }
+
+    /// Pair the next Swiss round, the top cut, or the next bracket round
+    pub async fn pair_next_round(&self, event_uuid: Uuid) -> Result<TournamentRound> {
+        let settings = self.load_settings(event_uuid).await?;
+        let swiss_rounds = settings
+            .swiss_rounds
+            .ok_or_else(|| anyhow::anyhow!("Tournament has not been started"))?;
+        let rounds = self.get_rounds(event_uuid).await?;
+        if let Some(last) = rounds.last() {
+            if last.status != RoundStatus::Completed {
+                return Err(anyhow::anyhow!(
+                    "Round {} still has unreported matches",
+                    last.round_number
+                ));
+            }
+        }
+        let swiss_played = rounds
+            .iter()
+            .filter(|r| r.stage == RoundStage::Swiss)
+            .count() as i32;
+
+        let (stage, pairings) = if swiss_played < swiss_rounds {
+            (
+                RoundStage::Swiss,
+                self.swiss_round(event_uuid, &rounds).await?,
+            )
+        } else {
+            let cut_rounds: Vec<&TournamentRound> = rounds
+                .iter()
+                .filter(|r| r.stage == RoundStage::TopCut)
+                .collect();
+            match cut_rounds.last() {
+                None if settings.top_cut > 0 => {
+                    let standings = self.get_standings(event_uuid).await?;
+                    let seeds: Vec<Uuid> = standings
+                        .iter()
+                        .filter(|s| !s.dropped)
+                        .take(settings.top_cut as usize)
+                        .map(|s| s.participant_uuid)
+                        .collect();
+                    if seeds.len() < settings.top_cut as usize {
+                        return Err(anyhow::anyhow!(
+                            "Only {} players left for a top {}",
+                            seeds.len(),
+                            settings.top_cut
+                        ));
+                    }
+                    let order = bracket_order(seeds.len());
+                    let pairings = order
+                        .chunks(2)
+                        .map(|pair| (seeds[pair[0] - 1], Some(seeds[pair[1] - 1])))
+                        .collect();
+                    (RoundStage::TopCut, pairings)
+                }
+                Some(last) if last.matches.len() > 1 => {
+                    let winners: Vec<Uuid> =
+                        last.matches.iter().filter_map(|m| m.winner()).collect();
+                    let pairings = winners
+                        .chunks(2)
+                        .map(|pair| (pair[0], pair.get(1).copied()))
+                        .collect();
+                    (RoundStage::TopCut, pairings)
+                }
+                _ => return Err(anyhow::anyhow!("Tournament is complete")),
+            }
+        };
+
+        let round_number = rounds.len() as i32 + 1;
+        let round_uuid = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        sqlx::query(
+            "INSERT INTO Event_Rounds (round_uuid, event_uuid, round_number, stage, status, time_limit_minutes, created_at)
+             VALUES (?, ?, ?, ?, 'paired', ?, ?)",
+        )
+        .bind(round_uuid.to_string())
+        .bind(event_uuid.to_string())
+        .bind(round_number)
+        .bind(stage.as_str())
+        .bind(settings.round_minutes)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut table = 0;
+        for (player1, player2) in pairings {
+            // Byes are decided on the spot and don't take a table
+            let table_number = match player2 {
+                Some(_) => {
+                    table += 1;
+                    table
+                }
+                None => 0,
+            };
+            sqlx::query(
+                "INSERT INTO Event_Matches (match_uuid, round_uuid, event_uuid, table_number, player1_uuid, player2_uuid,
+                                            player1_wins, reported_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(round_uuid.to_string())
+            .bind(event_uuid.to_string())
+            .bind(table_number)
+            .bind(player1.to_string())
+            .bind(player2.map(|p| p.to_string()))
+            .bind(if player2.is_none() { 2 } else { 0 })
+            .bind(player2.is_none().then(|| now.clone()))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        self.refresh_round_status(round_uuid).await?;
+        self.get_round(event_uuid, round_number)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Round not found"))
+    }
+
+    async fn swiss_round(
+        &self,
+        event_uuid: Uuid,
+        rounds: &[TournamentRound],
+    ) -> Result<Vec<(Uuid, Option<Uuid>)>> {
+        let standings = self.get_standings(event_uuid).await?;
+        let mut opponents: HashMap<Uuid, HashSet<Uuid>> = HashMap::new();
+        let mut had_bye: HashSet<Uuid> = HashSet::new();
+        for m in rounds.iter().flat_map(|r| &r.matches) {
+            match m.player2_uuid {
+                Some(p2) => {
+                    opponents.entry(m.player1_uuid).or_default().insert(p2);
+                    opponents.entry(p2).or_default().insert(m.player1_uuid);
+                }
+                None => {
+                    had_bye.insert(m.player1_uuid);
+                }
+            }
+        }
+
+        let mut candidates: Vec<PairingCandidate> = standings
+            .into_iter()
+            .filter(|s| !s.dropped)
+            .map(|s| PairingCandidate {
+                participant_uuid: s.participant_uuid,
+                match_points: s.match_points,
+                opponents: opponents.remove(&s.participant_uuid).unwrap_or_default(),
+                had_bye: had_bye.contains(&s.participant_uuid),
+            })
+            .collect();
+        // Random order within each point bracket; round one is fully random
+        candidates.shuffle(&mut rand::thread_rng());
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.match_points));
+        Ok(swiss_pairings(&candidates))
+    }
+
+    /// Start the round clock, optionally with a different time limit
+    pub async fn start_round(
+        &self,
+        event_uuid: Uuid,
+        round_number: i32,
+        minutes: Option<i32>,
+    ) -> Result<TournamentRound> {
+        let round = self
+            .get_round(event_uuid, round_number)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Round {} not found", round_number))?;
+        if round.status == RoundStatus::Completed {
+            return Err(anyhow::anyhow!("Round {} is already over", round_number));
+        }
+        let minutes = minutes.unwrap_or(round.time_limit_minutes).max(1);
+        let started_at = Utc::now();
+        sqlx::query(
+            "UPDATE Event_Rounds SET status = 'in_progress', time_limit_minutes = ?, started_at = ?, ends_at = ?
+             WHERE round_uuid = ?",
+        )
+        .bind(minutes)
+        .bind(started_at.to_rfc3339())
+        .bind((started_at + Duration::minutes(minutes as i64)).to_rfc3339())
+        .bind(round.round_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        self.get_round(event_uuid, round_number)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Round not found"))
+    }
+
+    /// Enter or correct a match result. Results can change until the next
+    /// round is paired; top cut matches need a winner.
+    pub async fn report_result(
+        &self,
+        match_uuid: Uuid,
+        result: MatchResult,
+    ) -> Result<TournamentMatch> {
+        let row = sqlx::query(
+            "SELECT m.event_uuid, m.player2_uuid, r.round_uuid, r.round_number, r.stage
+             FROM Event_Matches m JOIN Event_Rounds r ON r.round_uuid = m.round_uuid
+             WHERE m.match_uuid = ?",
+        )
+        .bind(match_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Match {} not found", match_uuid))?;
+
+        let event_uuid: String = row.try_get("event_uuid")?;
+        let round_uuid: String = row.try_get("round_uuid")?;
+        let round_number: i32 = row.try_get("round_number")?;
+        let stage: String = row.try_get("stage")?;
+        if row.try_get::<Option<String>, _>("player2_uuid")?.is_none() {
+            return Err(anyhow::anyhow!("Byes don't take a result"));
+        }
+        let games = [result.player1_wins, result.player2_wins, result.draws];
+        if games.iter().any(|g| !(0..=3).contains(g)) || games.iter().sum::<i32>() > 5 {
+            return Err(anyhow::anyhow!("Game counts are out of range"));
+        }
+        if stage == RoundStage::TopCut.as_str() && result.player1_wins == result.player2_wins {
+            return Err(anyhow::anyhow!("Top cut matches need a winner"));
+        }
+        let later: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM Event_Rounds WHERE event_uuid = ? AND round_number > ?",
+        )
+        .bind(&event_uuid)
+        .bind(round_number)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if later > 0 {
+            return Err(anyhow::anyhow!(
+                "Round {} has been paired; its results are final",
+                round_number + 1
+            ));
+        }
+
+        sqlx::query(
+            "UPDATE Event_Matches SET player1_wins = ?, player2_wins = ?, draws = ?, reported_at = ?
+             WHERE match_uuid = ?",
+        )
+        .bind(result.player1_wins)
+        .bind(result.player2_wins)
+        .bind(result.draws)
+        .bind(Utc::now().to_rfc3339())
+        .bind(match_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let round_uuid = Uuid::parse_str(&round_uuid)?;
+        let event_uuid = Uuid::parse_str(&event_uuid)?;
+        if self.refresh_round_status(round_uuid).await? {
+            self.finish_if_complete(event_uuid).await?;
+        }
+
+        self.get_round(event_uuid, round_number)
+            .await?
+            .and_then(|r| r.matches.into_iter().find(|m| m.match_uuid == match_uuid))
+            .ok_or_else(|| anyhow::anyhow!("Match not found"))
+    }
+
+    /// Take a player out of future pairings; their results stay in the standings
+    pub async fn drop_player(&self, event_uuid: Uuid, participant_uuid: Uuid) -> Result<()> {
+        let updated = sqlx::query(
+            "UPDATE Event_Participants
+             SET dropped_after_round = (SELECT COALESCE(MAX(round_number), 0) FROM Event_Rounds WHERE event_uuid = ?)
+             WHERE participant_uuid = ? AND event_uuid = ? AND dropped_after_round IS NULL",
+        )
+        .bind(event_uuid.to_string())
+        .bind(participant_uuid.to_string())
+        .bind(event_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if updated.rows_affected() == 0 {
+            return Err(anyhow::anyhow!(
+                "Player is not in this event or has already dropped"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Swiss standings with tiebreakers
+    pub async fn get_standings(&self, event_uuid: Uuid) -> Result<Vec<StandingsEntry>> {
+        let players = self.load_players(event_uuid).await?;
+        let matches: Vec<ReportedMatch> = self
+            .get_rounds(event_uuid)
+            .await?
+            .into_iter()
+            .filter(|r| r.stage == RoundStage::Swiss)
+            .flat_map(|r| r.matches)
+            .filter(|m| m.reported)
+            .map(|m| ReportedMatch {
+                player1_uuid: m.player1_uuid,
+                player2_uuid: m.player2_uuid,
+                player1_wins: m.player1_wins,
+                player2_wins: m.player2_wins,
+                draws: m.draws,
+            })
+            .collect();
+        Ok(compute_standings(&players, &matches))
+    }
+
+    pub async fn get_rounds(&self, event_uuid: Uuid) -> Result<Vec<TournamentRound>> {
+        let numbers: Vec<i32> = sqlx::query_scalar(
+            "SELECT round_number FROM Event_Rounds WHERE event_uuid = ? ORDER BY round_number",
+        )
+        .bind(event_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let mut rounds = Vec::with_capacity(numbers.len());
+        for number in numbers {
+            if let Some(round) = self.get_round(event_uuid, number).await? {
+                rounds.push(round);
+            }
+        }
+        Ok(rounds)
+    }
+
+    pub async fn get_round(
+        &self,
+        event_uuid: Uuid,
+        round_number: i32,
+    ) -> Result<Option<TournamentRound>> {
+        let Some(row) = sqlx::query(
+            "SELECT round_uuid, stage, status, time_limit_minutes, started_at, ends_at, completed_at
+             FROM Event_Rounds WHERE event_uuid = ? AND round_number = ?",
+        )
+        .bind(event_uuid.to_string())
+        .bind(round_number)
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        else {
+            return Ok(None);
+        };
+        let round_uuid: String = row.try_get("round_uuid")?;
+
+        let match_rows = sqlx::query(
+            "SELECT m.match_uuid, m.table_number, m.player1_uuid, p1.name AS player1_name,
+                    m.player2_uuid, p2.name AS player2_name, m.player1_wins, m.player2_wins, m.draws,
+                    m.reported_at
+             FROM Event_Matches m
+             LEFT JOIN Event_Participants p1 ON p1.participant_uuid = m.player1_uuid
+             LEFT JOIN Event_Participants p2 ON p2.participant_uuid = m.player2_uuid
+             WHERE m.round_uuid = ?
+             ORDER BY m.table_number = 0, m.table_number",
+        )
+        .bind(&round_uuid)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut matches = Vec::with_capacity(match_rows.len());
+        for m in match_rows {
+            matches.push(TournamentMatch {
+                match_uuid: Uuid::parse_str(&m.try_get::<String, _>("match_uuid")?)?,
+                table_number: m.try_get("table_number")?,
+                player1_uuid: Uuid::parse_str(&m.try_get::<String, _>("player1_uuid")?)?,
+                player1_name: m
+                    .try_get::<Option<String>, _>("player1_name")?
+                    .unwrap_or_default(),
+                player2_uuid: m
+                    .try_get::<Option<String>, _>("player2_uuid")?
+                    .and_then(|s| Uuid::parse_str(&s).ok()),
+                player2_name: m.try_get("player2_name")?,
+                player1_wins: m.try_get("player1_wins")?,
+                player2_wins: m.try_get("player2_wins")?,
+                draws: m.try_get("draws")?,
+                reported: m.try_get::<Option<String>, _>("reported_at")?.is_some(),
+            });
+        }
+
+        let status = RoundStatus::parse(&row.try_get::<String, _>("status")?);
+        let ends_at = parse_date(row.try_get("ends_at")?);
+        Ok(Some(TournamentRound {
+            round_uuid: Uuid::parse_str(&round_uuid)?,
+            event_uuid,
+            round_number,
+            stage: if row.try_get::<String, _>("stage")? == "top_cut" {
+                RoundStage::TopCut
+            } else {
+                RoundStage::Swiss
+            },
+            status,
+            time_limit_minutes: row.try_get("time_limit_minutes")?,
+            started_at: parse_date(row.try_get("started_at")?),
+            ends_at,
+            completed_at: parse_date(row.try_get("completed_at")?),
+            remaining_seconds: ends_at
+                .filter(|_| status == RoundStatus::InProgress)
+                .map(|end| (end - Utc::now()).num_seconds()),
+            matches,
+        }))
+    }
+
+    /// Mark a round completed once every match is in; true if it is
+    async fn refresh_round_status(&self, round_uuid: Uuid) -> Result<bool> {
+        let open: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM Event_Matches WHERE round_uuid = ? AND reported_at IS NULL",
+        )
+        .bind(round_uuid.to_string())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if open > 0 {
+            return Ok(false);
+        }
+        sqlx::query(
+            "UPDATE Event_Rounds SET status = 'completed', completed_at = COALESCE(completed_at, ?)
+             WHERE round_uuid = ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(round_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(true)
+    }
+
+    /// After the last round, record final placements: the bracket decides
+    /// the top places, Swiss standings the rest
+    async fn finish_if_complete(&self, event_uuid: Uuid) -> Result<()> {
+        let settings = self.load_settings(event_uuid).await?;
+        let rounds = self.get_rounds(event_uuid).await?;
+        let swiss_played = rounds
+            .iter()
+            .filter(|r| r.stage == RoundStage::Swiss)
+            .count() as i32;
+        let cut: Vec<&TournamentRound> = rounds
+            .iter()
+            .filter(|r| r.stage == RoundStage::TopCut)
+            .collect();
+        let finished = match cut.last() {
+            Some(last) => last.matches.len() == 1 && last.status == RoundStatus::Completed,
+            None => {
+                settings.top_cut == 0
+                    && settings.swiss_rounds.is_some_and(|n| swiss_played >= n)
+                    && rounds
+                        .last()
+                        .is_some_and(|r| r.status == RoundStatus::Completed)
+            }
+        };
+        if !finished {
+            return Ok(());
+        }
+
+        let mut placements: Vec<Uuid> = Vec::new();
+        if let Some(final_match) = cut.last().and_then(|r| r.matches.first()) {
+            placements.extend(final_match.winner());
+            placements.extend(final_match.loser());
+        }
+        let standings = self.get_standings(event_uuid).await?;
+        let swiss_rank: HashMap<Uuid, i32> = standings
+            .iter()
+            .map(|s| (s.participant_uuid, s.rank))
+            .collect();
+        // Earlier bracket losers, later rounds first, by Swiss rank within a round
+        for round in cut.iter().rev().skip(1) {
+            let mut losers: Vec<Uuid> = round.matches.iter().filter_map(|m| m.loser()).collect();
+            losers.sort_by_key(|p| swiss_rank.get(p).copied().unwrap_or(i32::MAX));
+            placements.extend(losers);
+        }
+        for entry in &standings {
+            if !placements.contains(&entry.participant_uuid) {
+                placements.push(entry.participant_uuid);
+            }
+        }
+
+        for (i, participant_uuid) in placements.iter().enumerate() {
+            self.db
+                .events
+                .update_placement(*participant_uuid, Some(i as i32 + 1))
+                .await?;
+        }
+        sqlx::query("UPDATE Events SET status = 'Completed' WHERE event_uuid = ?")
+            .bind(event_uuid.to_string())
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(())
+    }
+
+    async fn load_settings(&self, event_uuid: Uuid) -> Result<EventSettings> {
+        let row = sqlx::query(
+            "SELECT swiss_rounds, top_cut, round_minutes FROM Events WHERE event_uuid = ?",
+        )
+        .bind(event_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Event {} not found", event_uuid))?;
+        Ok(EventSettings {
+            swiss_rounds: row.try_get("swiss_rounds")?,
+            top_cut: row.try_get("top_cut")?,
+            round_minutes: row.try_get("round_minutes")?,
+        })
+    }
+
+    /// (participant, name, dropped) for everyone holding a seat
+    async fn load_players(&self, event_uuid: Uuid) -> Result<Vec<(Uuid, String, bool)>> {
+        let rows: Vec<(String, String, Option<i32>)> = sqlx::query_as(
+            "SELECT participant_uuid, name, dropped_after_round FROM Event_Participants
+             WHERE event_uuid = ? AND status IN ('registered', 'checked_in') ORDER BY created_at",
+        )
+        .bind(event_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(uuid, name, dropped)| {
+                Some((Uuid::parse_str(&uuid).ok()?, name, dropped.is_some()))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(n: usize) -> Vec<Uuid> {
+        (0..n).map(|_| Uuid::new_v4()).collect()
+    }
+
+    fn played(a: Uuid, b: Uuid, a_wins: i32, b_wins: i32) -> ReportedMatch {
+        ReportedMatch {
+            player1_uuid: a,
+            player2_uuid: Some(b),
+            player1_wins: a_wins,
+            player2_wins: b_wins,
+            draws: 0,
+        }
+    }
+
+    #[test]
+    fn test_default_swiss_rounds() {
+        assert_eq!(default_swiss_rounds(2), 1);
+        assert_eq!(default_swiss_rounds(8), 3);
+        assert_eq!(default_swiss_rounds(9), 4);
+        assert_eq!(default_swiss_rounds(32), 5);
+    }
+
+    #[test]
+    fn test_bracket_order_keeps_top_seeds_apart() {
+        assert_eq!(bracket_order(2), vec![1, 2]);
+        assert_eq!(bracket_order(4), vec![1, 4, 2, 3]);
+        assert_eq!(bracket_order(8), vec![1, 8, 4, 5, 2, 7, 3, 6]);
+    }
+
+    #[test]
+    fn test_swiss_pairings_avoid_rematches_and_repeat_byes() {
+        let p = ids(5);
+        let candidate =
+            |i: usize, points: i32, opponents: &[usize], had_bye: bool| PairingCandidate {
+                participant_uuid: p[i],
+                match_points: points,
+                opponents: opponents.iter().map(|&o| p[o]).collect(),
+                had_bye,
+            };
+        // p0 already played p1; p4 at the bottom already had the bye
+        let candidates = vec![
+            candidate(0, 3, &[1], false),
+            candidate(1, 3, &[0], false),
+            candidate(2, 0, &[3], false),
+            candidate(3, 0, &[2], false),
+            candidate(4, 3, &[], true),
+        ];
+        let pairings = swiss_pairings(&candidates);
+        assert_eq!(pairings.len(), 3);
+        assert_eq!(pairings.last(), Some(&(p[3], None)));
+        for (a, b) in &pairings {
+            let Some(b) = b else { continue };
+            let a_index = p.iter().position(|x| x == a).unwrap();
+            assert!(!candidates[a_index].opponents.contains(b));
+        }
+        assert!(pairings.contains(&(p[0], Some(p[2]))));
+    }
+
+    #[test]
+    fn test_standings_tiebreakers() {
+        let [a, b, c, d] = ids(4).try_into().unwrap();
+        let players: Vec<(Uuid, String, bool)> = [(a, "A"), (b, "B"), (c, "C"), (d, "D")]
+            .iter()
+            .map(|(id, name)| (*id, name.to_string(), false))
+            .collect();
+        // Round 1: A beats B 2-0, C beats D 2-0. Round 2: A beats C 2-1, B beats D 2-0
+        let matches = vec![
+            played(a, b, 2, 0),
+            played(c, d, 2, 0),
+            played(a, c, 2, 1),
+            played(b, d, 2, 0),
+        ];
+        let standings = compute_standings(&players, &matches);
+        let order: Vec<&str> = standings.iter().map(|s| s.name.as_str()).collect();
+        // B and C are both 1-1 against the same opponents, so GW% decides
+        assert_eq!(order, vec!["A", "C", "B", "D"]);
+        let a_entry = &standings[0];
+        assert_eq!((a_entry.wins, a_entry.match_points), (2, 6));
+        assert_eq!(a_entry.omw_pct, 0.5);
+        // D's MW% is floored at a third
+        let d_entry = &standings[3];
+        assert_eq!(d_entry.match_points, 0);
+        assert_eq!(d_entry.gw_pct, 0.333);
+    }
+
+    #[test]
+    fn test_bye_counts_as_two_nil_win() {
+        let [a, b] = ids(2).try_into().unwrap();
+        let players = vec![(a, "A".to_string(), false), (b, "B".to_string(), false)];
+        let matches = vec![ReportedMatch {
+            player1_uuid: a,
+            player2_uuid: None,
+            player1_wins: 2,
+            player2_wins: 0,
+            draws: 0,
+        }];
+        let standings = compute_standings(&players, &matches);
+        assert_eq!(standings[0].participant_uuid, a);
+        assert_eq!(standings[0].match_points, 3);
+        assert_eq!(standings[0].gw_pct, 1.0);
+        // No opponents yet
+        assert_eq!(standings[0].omw_pct, 0.0);
+    }
+}
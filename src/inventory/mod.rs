@@ -78,12 +78,11 @@ impl InventoryService {
             return Err(anyhow::anyhow!("Not enough quantity in stock"));
         }
 
+        self.repository
+            .update_quantity(inventory_uuid, -quantity)
+            .await?;
         if item.quantity_on_hand == quantity {
             self.repository.delete(inventory_uuid).await?;
-        } else {
-            self.repository
-                .update_quantity(inventory_uuid, -quantity)
-                .await?;
         }
 
         Ok(())
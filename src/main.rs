@@ -99,6 +99,16 @@ async fn main() -> Result<()> {
     // Initialize new Phase 2 services
     let tax_service = Arc::new(vaultsync::services::TaxService::new(db.clone()));
     let payment_service = Arc::new(vaultsync::services::PaymentService::new(db.clone()));
+    let customer_display_service =
+        Arc::new(vaultsync::services::CustomerDisplayService::new(db.clone()));
+    let checkout_service = Arc::new(
+        vaultsync::services::TransactionValidationService::new(
+            db.clone(),
+            tax_service.clone(),
+            payment_service.clone(),
+        )
+        .with_customer_display(customer_display_service.clone()),
+    );
     let holds_service = Arc::new(vaultsync::services::HoldsService::new(db.clone()));
     let barcode_service = Arc::new(vaultsync::services::BarcodeService::new(db.clone()));
     let receipt_service = Arc::new(vaultsync::services::ReceiptService::new(
@@ -123,7 +133,8 @@ async fn main() -> Result<()> {
     // Phase 7: Advanced Features
     let returns_service = Arc::new(vaultsync::services::ReturnsService::new(db.clone()));
     let serialized_inventory_service = Arc::new(
-        vaultsync::services::SerializedInventoryService::new(db.clone()),
+        vaultsync::services::SerializedInventoryService::new(db.clone())
+            .with_cert_providers(vaultsync::services::cert_verification::get_cert_providers()),
     );
     let trade_in_protection_service = Arc::new(vaultsync::services::TradeInProtectionService::new(
         db.clone(),
@@ -134,6 +145,9 @@ async fn main() -> Result<()> {
 
     // Phase 8: Reporting
     let reporting_service = Arc::new(vaultsync::services::ReportingService::new(db.clone()));
+    let cycle_count_service = Arc::new(vaultsync::services::CycleCountService::new(db.clone()));
+    let event_service = Arc::new(vaultsync::events::EventService::new(db.clone()));
+    let loyalty_service = Arc::new(vaultsync::services::LoyaltyService::new(db.clone()));
 
     // Phase 9: Notifications
     let email_service = Arc::new(vaultsync::services::notification::email::get_email_provider());
@@ -145,6 +159,14 @@ async fn main() -> Result<()> {
             sms_service.clone(),
         ),
     );
+    let notification_service = Arc::new(
+        vaultsync::services::notification::NotificationService::new(
+            db.clone(),
+            email_service.clone(),
+            sms_service.clone(),
+        )
+        .with_public_url(config.public_url.clone()),
+    );
 
     // Create ProductService (ARCH-02: Services inject repos directly)
     let product_service = Arc::new(services::ProductService::new(db.products.clone()));
@@ -163,12 +185,29 @@ async fn main() -> Result<()> {
             holds: holds_service,
             payments: payment_service,
             taxes: tax_service,
+            currency: Arc::new(vaultsync::services::CurrencyService::new(db.clone())),
+            checkout: checkout_service,
+            adjustments: Arc::new(vaultsync::services::TransactionAdjustmentService::new(
+                db.clone(),
+            )),
             returns: returns_service,
             trade_in: trade_in_protection_service,
+            consignment: Arc::new(vaultsync::services::ConsignmentService::new(db.clone())),
+            purchasing: Arc::new(vaultsync::services::PurchasingService::new(db.clone())),
+            shrinkage: Arc::new(vaultsync::services::ShrinkageService::new(db.clone())),
+            cycle_counts: cycle_count_service.clone(),
+            kitting: Arc::new(vaultsync::services::KittingService::new(db.clone())),
+            bulk_inventory: Arc::new(vaultsync::services::BulkInventoryService::new(db.clone())),
+            replenishment: Arc::new(vaultsync::services::ReplenishmentService::new(db.clone())),
+            loyalty: loyalty_service.clone(),
+            customers: Arc::new(vaultsync::services::CustomerService::new(db.clone())),
+            grading: Arc::new(vaultsync::services::GradingService::new(db.clone())),
         },
         system: api::state_groups::SystemServices {
             audit: Arc::new(audit::AuditService::new(db.clone())),
-            events: Arc::new(vaultsync::events::EventService::new(db.clone())),
+            events: event_service.clone(),
+            tournaments: Arc::new(vaultsync::events::TournamentService::new(db.clone())),
+            prizes: Arc::new(vaultsync::events::PrizeService::new(db.clone())),
             barcode: barcode_service,
             receipts: receipt_service,
             invoices: invoice_service,
@@ -178,10 +217,18 @@ async fn main() -> Result<()> {
             catalog: catalog_lookup_service,
             serialized: serialized_inventory_service,
             locations: location_service,
+            storage: Arc::new(vaultsync::services::StorageService::new(db.clone())),
             reporting: reporting_service,
+            customer_display: customer_display_service,
             email: email_service,
             sms: sms_service,
             notification_scheduler: notification_scheduler.clone(),
+            notifications: notification_service,
+            media: Arc::new(vaultsync::services::MediaService::new(
+                db.clone(),
+                config.media_dir.clone(),
+                vaultsync::services::media::get_thumbnail_generator(),
+            )),
         },
         sync_actor: sync_actor_handle,
         config: Arc::new(config.clone()),
@@ -279,6 +326,63 @@ async fn main() -> Result<()> {
         })
         .await;
 
+    // 4. Scheduled Cycle Counts (Supervised)
+    let cycle_counts_for_task = cycle_count_service.clone();
+    supervisor
+        .spawn("cycle_count_scheduler", move || {
+            let cycle_counts = cycle_counts_for_task.clone();
+            async move {
+                tracing::info!("Cycle count scheduler started (interval: 1 hour)");
+                loop {
+                    tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
+                    if let Err(e) = cycle_counts.run_due_plans().await {
+                        tracing::error!("Scheduled cycle counts failed: {}", e);
+                    }
+                }
+            }
+        })
+        .await;
+
+    // 5. Recurring Event Instances (Supervised)
+    let events_for_task = event_service.clone();
+    supervisor
+        .spawn("event_template_scheduler", move || {
+            let events = events_for_task.clone();
+            async move {
+                tracing::info!("Event template scheduler started (interval: 1 hour)");
+                loop {
+                    if let Err(e) = events
+                        .spawn_due_instances(vaultsync::events::DEFAULT_SPAWN_HORIZON_DAYS)
+                        .await
+                    {
+                        tracing::error!("Spawning recurring events failed: {}", e);
+                    }
+                    tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
+                }
+            }
+        })
+        .await;
+
+    // 6. Loyalty Expiry and Tier Review (Supervised)
+    let loyalty_for_task = loyalty_service.clone();
+    supervisor
+        .spawn("loyalty_scheduler", move || {
+            let loyalty = loyalty_for_task.clone();
+            async move {
+                tracing::info!("Loyalty scheduler started (interval: 24 hours)");
+                loop {
+                    if let Err(e) = loyalty.expire_points().await {
+                        tracing::error!("Expiring loyalty points failed: {}", e);
+                    }
+                    if let Err(e) = loyalty.evaluate_all_tiers().await {
+                        tracing::error!("Loyalty tier review failed: {}", e);
+                    }
+                    tokio::time::sleep(tokio::time::Duration::from_secs(86400)).await;
+                }
+            }
+        })
+        .await;
+
     // Start Server
     let bind_addr = format!("0.0.0.0:{}", config.api_port);
     let listener = TcpListener::bind(&bind_addr).await?;
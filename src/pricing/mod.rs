@@ -160,7 +160,18 @@ impl PricingService {
 
                     if let Some(provider) = provider {
                         match provider.get_price(&product).await {
-                            Ok(price_info) => {
+                            Ok(quote) => {
+                                let price_info = match normalize_currency(&db, quote).await {
+                                    Ok(price_info) => price_info,
+                                    Err(e) => {
+                                        tracing::warn!(
+                                            "Skipping price for {}: {}",
+                                            product.name,
+                                            e
+                                        );
+                                        return;
+                                    }
+                                };
                                 cache.set(price_info.clone()).await;
                                 if let Err(e) = db.pricing.insert_matrix(&price_info).await {
                                     tracing::debug!(
@@ -294,7 +305,12 @@ impl PricingService {
                     }
                 };
 
-                match provider.get_price(&product).await {
+                let quote = match provider.get_price(&product).await {
+                    Ok(quote) => normalize_currency(&self.db, quote).await,
+                    Err(e) => Err(e),
+                };
+
+                match quote {
                     Ok(new_price) => {
                         // Update caches
                         self.cache.set(new_price.clone()).await;
@@ -382,6 +398,29 @@ impl PricingService {
     }
 }
 
+/// Convert a provider quote into the store's base currency so the pricing
+/// matrix, cache and labels only ever hold one currency.
+async fn normalize_currency(db: &Arc<Database>, price: PriceInfo) -> Result<PriceInfo> {
+    let currencies = crate::services::CurrencyService::new(db.clone());
+    let base = currencies.get_base_currency().await?;
+    if price.currency == base {
+        return Ok(price);
+    }
+
+    let (market_mid, _) = currencies
+        .to_base_f64(price.market_mid, price.currency)
+        .await?;
+    let (market_low, _) = currencies
+        .to_base_f64(price.market_low, price.currency)
+        .await?;
+    Ok(PriceInfo {
+        market_mid,
+        market_low,
+        currency: base,
+        ..price
+    })
+}
+
 #[async_trait::async_trait]
 impl PricingServiceTrait for PricingService {
     async fn get_price_for_card(&self, product_uuid: Uuid) -> Option<PriceInfo> {
@@ -1,4 +1,4 @@
-use crate::core::{PriceInfo, Product};
+use crate::core::{Currency, PriceInfo, Product};
 use crate::errors::Result;
 use async_trait::async_trait;
 use chrono::Utc;
@@ -14,16 +14,42 @@ pub trait PricingProvider: Send + Sync {
 
 pub struct ScryfallProvider {
     client: reqwest::Client,
+    preferred_currency: Currency,
 }
 
 impl ScryfallProvider {
     pub fn new() -> Self {
+        Self::with_preferred_currency(Currency::USD)
+    }
+
+    /// Prefer quotes in `currency` (Scryfall publishes USD and EUR)
+    pub fn with_preferred_currency(currency: Currency) -> Self {
         Self {
             client: reqwest::Client::new(),
+            preferred_currency: currency,
         }
     }
 }
 
+/// Pick a price from a Scryfall `prices` object, preferring `preferred` and
+/// falling back to USD then EUR so a card with only EUR pricing still gets one.
+pub(crate) fn scryfall_price(prices: &Value, preferred: Currency) -> (f64, Currency) {
+    let quote = |currency: Currency| {
+        let key = currency.code().to_ascii_lowercase();
+        prices[key.as_str()]
+            .as_str()
+            .or_else(|| prices[format!("{}_foil", key).as_str()].as_str())
+            .and_then(|p| p.parse::<f64>().ok())
+            .filter(|p| *p > 0.0)
+            .map(|p| (p, currency))
+    };
+
+    quote(preferred)
+        .or_else(|| quote(Currency::USD))
+        .or_else(|| quote(Currency::EUR))
+        .unwrap_or((0.0, Currency::USD))
+}
+
 #[async_trait]
 impl PricingProvider for ScryfallProvider {
     async fn get_price(&self, product: &Product) -> Result<PriceInfo> {
@@ -57,12 +83,8 @@ impl PricingProvider for ScryfallProvider {
                 let body: Value = resp.json().await?;
 
                 // Parse price from prices.usd or prices.eur
-                let usd_price = body["prices"]["usd"]
-                    .as_str()
-                    .or_else(|| body["prices"]["usd_foil"].as_str())
-                    .unwrap_or("0.0");
-
-                let market_mid = usd_price.parse::<f64>().unwrap_or(0.0);
+                let (market_mid, currency) =
+                    scryfall_price(&body["prices"], self.preferred_currency);
 
                 return Ok(PriceInfo {
                     price_uuid: Uuid::new_v4(),
@@ -70,6 +92,7 @@ impl PricingProvider for ScryfallProvider {
                     market_mid,
                     market_low: market_mid * 0.85, // Rough estimate
                     last_sync_timestamp: Utc::now(),
+                    currency,
                 });
             }
         } else if !product.name.is_empty() {
@@ -88,11 +111,8 @@ impl PricingProvider for ScryfallProvider {
 
             if resp.status().is_success() {
                 let body: Value = resp.json().await?;
-                let usd_price = body["prices"]["usd"]
-                    .as_str()
-                    .or_else(|| body["prices"]["usd_foil"].as_str())
-                    .unwrap_or("0.0");
-                let market_mid = usd_price.parse::<f64>().unwrap_or(0.0);
+                let (market_mid, currency) =
+                    scryfall_price(&body["prices"], self.preferred_currency);
 
                 return Ok(PriceInfo {
                     price_uuid: Uuid::new_v4(),
@@ -100,6 +120,7 @@ impl PricingProvider for ScryfallProvider {
                     market_mid,
                     market_low: market_mid * 0.85,
                     last_sync_timestamp: Utc::now(),
+                    currency,
                 });
             }
         }
@@ -132,6 +153,7 @@ impl PricingProvider for MockProvider {
             market_mid: price,
             market_low: price * 0.8,
             last_sync_timestamp: Utc::now(),
+            currency: Currency::USD,
         })
     }
 
@@ -208,6 +230,7 @@ impl PricingProvider for PokemonTcgProvider {
                                         market_mid: market_price,
                                         market_low: low_price,
                                         last_sync_timestamp: Utc::now(),
+                                        currency: Currency::USD,
                                     });
                                 }
                             }
@@ -254,6 +277,7 @@ impl PricingProvider for PokemonTcgProvider {
                                         market_mid: market_price,
                                         market_low: low_price,
                                         last_sync_timestamp: Utc::now(),
+                                        currency: Currency::USD,
                                     });
                                 }
                             }
@@ -278,6 +302,7 @@ impl PricingProvider for PokemonTcgProvider {
             market_mid: price,
             market_low: price * 0.7,
             last_sync_timestamp: Utc::now(),
+            currency: Currency::USD,
         })
     }
 
@@ -330,6 +355,7 @@ impl PriceChartingProvider {
             market_mid,
             market_low: market_mid * 0.9,
             last_sync_timestamp: Utc::now(),
+            currency: Currency::USD,
         })
     }
 }
@@ -372,3 +398,21 @@ impl PricingProvider for SportsCardProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scryfall_price_keeps_eur_when_usd_missing() {
+        let prices = serde_json::json!({"usd": null, "usd_foil": null, "eur": "3.40"});
+        assert_eq!(
+            scryfall_price(&prices, Currency::USD),
+            (3.40, Currency::EUR)
+        );
+
+        let both = serde_json::json!({"usd": "4.00", "eur": "3.40"});
+        assert_eq!(scryfall_price(&both, Currency::USD), (4.00, Currency::USD));
+        assert_eq!(scryfall_price(&both, Currency::EUR), (3.40, Currency::EUR));
+    }
+}
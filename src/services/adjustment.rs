@@ -0,0 +1,1086 @@
+//! Post-sale transaction adjustments
+//!
+//! Completed transactions are never edited silently. Each correction is an
+//! immutable record linked to the previous adjustment on the same sale, with
+//! the totals before and after, and is mirrored to the audit log:
+//! - Re-tender (change the payment method)
+//! - Attach a customer after the fact
+//! - Price correction (requires manager approval)
+//! - Partial void of a single line
+//!
+//! Price corrections and line voids change what the customer owes. A
+//! decrease is refunded to the original tender; an increase is recorded as a
+//! balance due rather than as a payment. The sale's own rows
+//! (`Transactions`, `Transaction_Items`, `Transaction_Tax_Lines`) keep what
+//! was rung up: the current state of a line or total is read from the latest
+//! adjustment, and reports show adjustments in the period they were made.
+
+use crate::core::money::round_cents;
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
+use crate::database::Database;
+use crate::errors::Result;
+use crate::monitoring::AuditLogService;
+use crate::services::PaymentMethodType;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Kind of post-sale adjustment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentType {
+    Retender,
+    AttachCustomer,
+    PriceCorrection,
+    LineVoid,
+}
+
+impl std::fmt::Display for AdjustmentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdjustmentType::Retender => write!(f, "retender"),
+            AdjustmentType::AttachCustomer => write!(f, "attach_customer"),
+            AdjustmentType::PriceCorrection => write!(f, "price_correction"),
+            AdjustmentType::LineVoid => write!(f, "line_void"),
+        }
+    }
+}
+
+impl AdjustmentType {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "retender" => Some(AdjustmentType::Retender),
+            "attach_customer" => Some(AdjustmentType::AttachCustomer),
+            "price_correction" => Some(AdjustmentType::PriceCorrection),
+            "line_void" => Some(AdjustmentType::LineVoid),
+            _ => None,
+        }
+    }
+}
+
+/// An immutable record of one change made to a completed transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionAdjustment {
+    pub adjustment_uuid: Uuid,
+    pub transaction_uuid: Uuid,
+    /// The adjustment made just before this one on the same transaction
+    pub previous_adjustment_uuid: Option<Uuid>,
+    pub adjustment_type: AdjustmentType,
+    pub item_uuid: Option<Uuid>,
+    /// Payment row that was re-tendered, or that settled the difference
+    pub payment_uuid: Option<Uuid>,
+    pub total_before: f64,
+    pub total_after: f64,
+    pub amount_delta: f64,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+    pub reason: String,
+    pub performed_by: Option<Uuid>,
+    pub approved_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Adjustments made within a reporting period
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdjustmentSummary {
+    pub adjustment_count: i64,
+    /// Net change to sale totals (negative = refunded to customers)
+    pub net_amount: f64,
+    pub count_by_type: HashMap<String, i64>,
+    pub amount_by_type: HashMap<String, f64>,
+    /// Net change to tax collected
+    #[serde(default)]
+    pub tax_amount: f64,
+    /// Differences settled against each tender (negative = refunded)
+    #[serde(default)]
+    pub settled_by_method: HashMap<String, f64>,
+    /// Net increase in what customers owe on sales corrected upwards
+    #[serde(default)]
+    pub balance_due: f64,
+}
+
+/// Totals of the transaction being adjusted, after earlier adjustments
+struct TransactionHeader {
+    customer_uuid: Option<String>,
+    subtotal: f64,
+    tax_amount: f64,
+    total: f64,
+}
+
+/// A sold line, after earlier corrections and voids
+struct SoldLine {
+    product_uuid: String,
+    quantity: i32,
+    unit_price: f64,
+    condition: String,
+}
+
+/// How a change in total was settled
+struct Settlement {
+    /// Refund row written against the original tender
+    payment_uuid: Option<Uuid>,
+    /// Change to what the customer still owes on the sale
+    balance_due: f64,
+}
+
+/// Fields shared by every adjustment insert
+struct NewAdjustment {
+    transaction_uuid: Uuid,
+    adjustment_type: AdjustmentType,
+    item_uuid: Option<Uuid>,
+    payment_uuid: Option<Uuid>,
+    total_before: f64,
+    total_after: f64,
+    before: serde_json::Value,
+    after: serde_json::Value,
+    reason: String,
+    performed_by: Option<Uuid>,
+    approved_by: Option<Uuid>,
+}
+
+pub struct TransactionAdjustmentService {
+    db: Arc<Database>,
+}
+
+impl TransactionAdjustmentService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Move a payment to a different tender type (e.g. rung up as cash, paid by card)
+    pub async fn retender(
+        &self,
+        transaction_uuid: Uuid,
+        payment_uuid: Uuid,
+        new_method: PaymentMethodType,
+        reason: &str,
+        performed_by: Option<Uuid>,
+    ) -> Result<TransactionAdjustment> {
+        require_reason(reason)?;
+        let mut tx = self.begin().await?;
+        let header = load_header(&mut tx, transaction_uuid).await?;
+
+        let row = sqlx::query(
+            "SELECT method_type, amount FROM Payment_Methods WHERE payment_uuid = ? AND transaction_uuid = ?",
+        )
+        .bind(payment_uuid.to_string())
+        .bind(transaction_uuid.to_string())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Payment {} not found on transaction {}",
+                payment_uuid,
+                transaction_uuid
+            )
+        })?;
+
+        let old_method: String = sqlx::Row::try_get(&row, "method_type").unwrap_or_default();
+        let amount: f64 = sqlx::Row::try_get(&row, "amount").unwrap_or(0.0);
+        let old_method: PaymentMethodType = old_method.parse()?;
+        if old_method == new_method {
+            return Err(anyhow::anyhow!("Payment is already {}", new_method));
+        }
+
+        // Points are a ledger of their own; re-tendering them isn't supported
+        if old_method == PaymentMethodType::LoyaltyPoints
+            || new_method == PaymentMethodType::LoyaltyPoints
+        {
+            return Err(anyhow::anyhow!(
+                "Loyalty points payments cannot be re-tendered; void and ring the sale again"
+            ));
+        }
+
+        // Store credit moves with the tender
+        if old_method == PaymentMethodType::StoreCredit
+            || new_method == PaymentMethodType::StoreCredit
+        {
+            let customer_uuid = header.customer_uuid.clone().ok_or_else(|| {
+                anyhow::anyhow!("Store credit re-tender requires a customer on the transaction")
+            })?;
+            if old_method == PaymentMethodType::StoreCredit {
+                adjust_store_credit(&mut tx, &customer_uuid, amount).await?;
+            }
+            if new_method == PaymentMethodType::StoreCredit {
+                let balance: f64 = sqlx::query_scalar(
+                    "SELECT store_credit FROM Customers WHERE customer_uuid = ?",
+                )
+                .bind(&customer_uuid)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+                if balance < amount - 0.001 {
+                    return Err(anyhow::anyhow!(
+                        "Insufficient store credit: ${:.2} available, ${:.2} required",
+                        balance,
+                        amount
+                    ));
+                }
+                adjust_store_credit(&mut tx, &customer_uuid, -amount).await?;
+            }
+        }
+
+        sqlx::query("UPDATE Payment_Methods SET method_type = ? WHERE payment_uuid = ?")
+            .bind(new_method.to_string())
+            .bind(payment_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to re-tender payment: {}", e))?;
+
+        let adjustment = insert_adjustment(
+            &mut tx,
+            NewAdjustment {
+                transaction_uuid,
+                adjustment_type: AdjustmentType::Retender,
+                item_uuid: None,
+                payment_uuid: Some(payment_uuid),
+                total_before: header.total,
+                total_after: header.total,
+                before: json!({"method": old_method.to_string(), "amount": amount}),
+                after: json!({"method": new_method.to_string(), "amount": amount}),
+                reason: reason.to_string(),
+                performed_by,
+                approved_by: None,
+            },
+        )
+        .await?;
+
+        self.commit(tx, &adjustment).await?;
+        Ok(adjustment)
+    }
+
+    /// Attach a customer to a sale that was rung up without one
+    pub async fn attach_customer(
+        &self,
+        transaction_uuid: Uuid,
+        customer_uuid: Uuid,
+        reason: &str,
+        performed_by: Option<Uuid>,
+    ) -> Result<TransactionAdjustment> {
+        require_reason(reason)?;
+        let mut tx = self.begin().await?;
+        let header = load_header(&mut tx, transaction_uuid).await?;
+
+        if let Some(existing) = &header.customer_uuid {
+            return Err(anyhow::anyhow!(
+                "Transaction already belongs to customer {}",
+                existing
+            ));
+        }
+
+        let exists: Option<String> = sqlx::query_scalar(
+            "SELECT customer_uuid FROM Customers WHERE customer_uuid = ? AND deleted_at IS NULL",
+        )
+        .bind(customer_uuid.to_string())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if exists.is_none() {
+            return Err(anyhow::anyhow!("Customer {} not found", customer_uuid));
+        }
+
+        sqlx::query("UPDATE Transactions SET customer_uuid = ? WHERE transaction_uuid = ?")
+            .bind(customer_uuid.to_string())
+            .bind(transaction_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to attach customer: {}", e))?;
+
+        let adjustment = insert_adjustment(
+            &mut tx,
+            NewAdjustment {
+                transaction_uuid,
+                adjustment_type: AdjustmentType::AttachCustomer,
+                item_uuid: None,
+                payment_uuid: None,
+                total_before: header.total,
+                total_after: header.total,
+                before: json!({"customer_uuid": null}),
+                after: json!({"customer_uuid": customer_uuid}),
+                reason: reason.to_string(),
+                performed_by,
+                approved_by: None,
+            },
+        )
+        .await?;
+
+        self.commit(tx, &adjustment).await?;
+        Ok(adjustment)
+    }
+
+    /// Correct a mistyped unit price. A manager or admin must approve.
+    pub async fn correct_price(
+        &self,
+        transaction_uuid: Uuid,
+        item_uuid: Uuid,
+        corrected_unit_price: f64,
+        reason: &str,
+        performed_by: Option<Uuid>,
+        approved_by: Uuid,
+    ) -> Result<TransactionAdjustment> {
+        require_reason(reason)?;
+        if !corrected_unit_price.is_finite() || corrected_unit_price < 0.0 {
+            return Err(anyhow::anyhow!("Corrected price must be zero or more"));
+        }
+
+        let mut tx = self.begin().await?;
+        require_manager_approval(&mut tx, approved_by).await?;
+        let header = load_header(&mut tx, transaction_uuid).await?;
+        let line = load_line(&mut tx, transaction_uuid, item_uuid).await?;
+
+        if (line.unit_price - corrected_unit_price).abs() < 0.005 {
+            return Err(anyhow::anyhow!(
+                "Item is already priced at ${:.2}",
+                line.unit_price
+            ));
+        }
+
+        let subtotal_delta = (corrected_unit_price - line.unit_price) * line.quantity as f64;
+        let (new_subtotal, new_tax, new_total) = adjusted_totals(&header, subtotal_delta);
+        let settlement =
+            settle_difference(&mut tx, transaction_uuid, &header, new_total - header.total).await?;
+
+        let adjustment = insert_adjustment(
+            &mut tx,
+            NewAdjustment {
+                transaction_uuid,
+                adjustment_type: AdjustmentType::PriceCorrection,
+                item_uuid: Some(item_uuid),
+                payment_uuid: settlement.payment_uuid,
+                total_before: header.total,
+                total_after: new_total,
+                before: json!({
+                    "unit_price": line.unit_price,
+                    "quantity": line.quantity,
+                    "subtotal": header.subtotal,
+                    "tax_amount": header.tax_amount,
+                }),
+                after: json!({
+                    "unit_price": corrected_unit_price,
+                    "quantity": line.quantity,
+                    "subtotal": new_subtotal,
+                    "tax_amount": new_tax,
+                    "balance_due": settlement.balance_due,
+                }),
+                reason: reason.to_string(),
+                performed_by,
+                approved_by: Some(approved_by),
+            },
+        )
+        .await?;
+
+        self.commit(tx, &adjustment).await?;
+        Ok(adjustment)
+    }
+
+    /// Void part (or all) of a single line: returns stock and refunds the difference
+    pub async fn void_line(
+        &self,
+        transaction_uuid: Uuid,
+        item_uuid: Uuid,
+        quantity: i32,
+        reason: &str,
+        performed_by: Option<Uuid>,
+    ) -> Result<TransactionAdjustment> {
+        require_reason(reason)?;
+        let mut tx = self.begin().await?;
+        let header = load_header(&mut tx, transaction_uuid).await?;
+        let line = load_line(&mut tx, transaction_uuid, item_uuid).await?;
+
+        if quantity <= 0 || quantity > line.quantity {
+            return Err(anyhow::anyhow!(
+                "Can void between 1 and {} of this line",
+                line.quantity
+            ));
+        }
+
+        // Return stock to the piles the line was sold from
+        let source = MovementSource {
+            notes: Some(reason.to_string()),
+            ..MovementSource::new(
+                MovementType::Void,
+                Some(transaction_uuid),
+                performed_by,
+                &self.db.node_id,
+            )
+        };
+        let restock = restock_targets(&mut tx, transaction_uuid, &line, quantity).await?;
+        if restock.is_empty() {
+            tracing::warn!(
+                "No inventory row for product {} ({}) to restock voided line",
+                line.product_uuid,
+                line.condition
+            );
+        }
+        for (inventory_uuid, restored) in restock {
+            movements::adjust_quantity_with_tx(&mut tx, inventory_uuid, restored, &source).await?;
+            crate::services::consignment::reverse_sale_with_tx(
+                &mut tx,
+                transaction_uuid,
+                &inventory_uuid.to_string(),
+                restored,
+            )
+            .await?;
+        }
+
+        let subtotal_delta = -(line.unit_price * quantity as f64);
+        let (new_subtotal, new_tax, new_total) = adjusted_totals(&header, subtotal_delta);
+        let settlement =
+            settle_difference(&mut tx, transaction_uuid, &header, new_total - header.total).await?;
+
+        let adjustment = insert_adjustment(
+            &mut tx,
+            NewAdjustment {
+                transaction_uuid,
+                adjustment_type: AdjustmentType::LineVoid,
+                item_uuid: Some(item_uuid),
+                payment_uuid: settlement.payment_uuid,
+                total_before: header.total,
+                total_after: new_total,
+                before: json!({
+                    "quantity": line.quantity,
+                    "unit_price": line.unit_price,
+                    "subtotal": header.subtotal,
+                    "tax_amount": header.tax_amount,
+                }),
+                after: json!({
+                    "quantity": line.quantity - quantity,
+                    "unit_price": line.unit_price,
+                    "subtotal": new_subtotal,
+                    "tax_amount": new_tax,
+                    "balance_due": settlement.balance_due,
+                }),
+                reason: reason.to_string(),
+                performed_by,
+                approved_by: None,
+            },
+        )
+        .await?;
+
+        self.commit(tx, &adjustment).await?;
+        Ok(adjustment)
+    }
+
+    /// Edit history of a transaction, oldest first
+    pub async fn get_adjustments(
+        &self,
+        transaction_uuid: Uuid,
+    ) -> Result<Vec<TransactionAdjustment>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Transaction_Adjustments WHERE transaction_uuid = ? ORDER BY created_at, rowid",
+        )
+        .bind(transaction_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().filter_map(map_adjustment).collect())
+    }
+
+    /// Adjustments made between `start` and `end`
+    pub async fn get_summary(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<AdjustmentSummary> {
+        adjustment_summary(&self.db, start, end).await
+    }
+
+    async fn begin(&self) -> Result<sqlx::Transaction<'static, sqlx::Sqlite>> {
+        self.db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))
+    }
+
+    async fn commit(
+        &self,
+        tx: sqlx::Transaction<'static, sqlx::Sqlite>,
+        adjustment: &TransactionAdjustment,
+    ) -> Result<()> {
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!(
+            "Transaction {} adjusted ({}): ${:.2} -> ${:.2}",
+            adjustment.transaction_uuid,
+            adjustment.adjustment_type,
+            adjustment.total_before,
+            adjustment.total_after
+        );
+
+        // The adjustment itself is the record of truth; a failed audit write is logged, not fatal
+        let audit = AuditLogService::new(self.db.pool.clone());
+        let logged = async {
+            audit.init().await?;
+            audit
+                .log_update(
+                    "Transactions",
+                    adjustment.transaction_uuid,
+                    json!({
+                        "adjustment_type": adjustment.adjustment_type,
+                        "total": adjustment.total_before,
+                        "values": adjustment.before,
+                    }),
+                    json!({
+                        "adjustment_uuid": adjustment.adjustment_uuid,
+                        "adjustment_type": adjustment.adjustment_type,
+                        "total": adjustment.total_after,
+                        "values": adjustment.after,
+                        "reason": adjustment.reason,
+                        "approved_by": adjustment.approved_by,
+                    }),
+                    adjustment.performed_by,
+                    None,
+                )
+                .await
+        }
+        .await;
+        if let Err(e) = logged {
+            tracing::warn!(
+                "Failed to audit adjustment {}: {}",
+                adjustment.adjustment_uuid,
+                e
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Adjustment totals for reports (shared with `ReportingService`)
+pub(crate) async fn adjustment_summary(
+    db: &Database,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<AdjustmentSummary> {
+    let rows = sqlx::query(
+        "SELECT adjustment_type, COUNT(*) as adjustment_count, COALESCE(SUM(amount_delta), 0.0) as net_amount
+         FROM Transaction_Adjustments
+         WHERE created_at >= ? AND created_at < ?
+         GROUP BY adjustment_type",
+    )
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_all(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let mut summary = AdjustmentSummary::default();
+    for row in rows {
+        let kind: String = sqlx::Row::try_get(&row, "adjustment_type").unwrap_or_default();
+        let count: i64 = sqlx::Row::try_get(&row, "adjustment_count").unwrap_or(0);
+        let amount: f64 = sqlx::Row::try_get(&row, "net_amount").unwrap_or(0.0);
+        summary.adjustment_count += count;
+        summary.net_amount = round_cents(summary.net_amount + amount);
+        summary.count_by_type.insert(kind.clone(), count);
+        summary.amount_by_type.insert(kind, round_cents(amount));
+    }
+
+    summary.tax_amount = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(CAST(json_extract(after_values, '$.tax_amount') AS REAL)
+                           - CAST(json_extract(before_values, '$.tax_amount') AS REAL)), 0.0)
+         FROM Transaction_Adjustments
+         WHERE adjustment_type IN ('price_correction', 'line_void')
+           AND created_at >= ? AND created_at < ?",
+    )
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_one(&db.pool)
+    .await
+    .map(round_cents)
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    summary.balance_due = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(CAST(json_extract(after_values, '$.balance_due') AS REAL)), 0.0)
+         FROM Transaction_Adjustments
+         WHERE created_at >= ? AND created_at < ?",
+    )
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_one(&db.pool)
+    .await
+    .map(round_cents)
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let settled = sqlx::query(
+        "SELECT pm.method_type, COALESCE(SUM(pm.amount), 0.0) as amount
+         FROM Transaction_Adjustments a
+         JOIN Payment_Methods pm ON pm.payment_uuid = a.payment_uuid
+         WHERE a.adjustment_type IN ('price_correction', 'line_void')
+           AND a.created_at >= ? AND a.created_at < ?
+         GROUP BY pm.method_type",
+    )
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_all(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    for row in settled {
+        let method: String = sqlx::Row::try_get(&row, "method_type").unwrap_or_default();
+        let amount: f64 = sqlx::Row::try_get(&row, "amount").unwrap_or(0.0);
+        summary
+            .settled_by_method
+            .insert(method, round_cents(amount));
+    }
+    Ok(summary)
+}
+
+/// A sold line's quantity and unit price after the latest correction or
+/// void, or `None` when the line was never adjusted
+pub(crate) async fn adjusted_line<'e, E>(executor: E, item_uuid: &str) -> Result<Option<(i32, f64)>>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    let row = sqlx::query(
+        "SELECT CAST(json_extract(after_values, '$.quantity') AS INTEGER) as quantity,
+                CAST(json_extract(after_values, '$.unit_price') AS REAL) as unit_price
+         FROM Transaction_Adjustments
+         WHERE item_uuid = ? AND adjustment_type IN ('price_correction', 'line_void')
+         ORDER BY created_at DESC, rowid DESC LIMIT 1",
+    )
+    .bind(item_uuid)
+    .fetch_optional(executor)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    Ok(row.map(|row| {
+        (
+            sqlx::Row::try_get(&row, "quantity").unwrap_or(0),
+            sqlx::Row::try_get(&row, "unit_price").unwrap_or(0.0),
+        )
+    }))
+}
+
+fn require_reason(reason: &str) -> Result<()> {
+    if reason.trim().is_empty() {
+        return Err(anyhow::anyhow!(
+            "A reason is required for post-sale adjustments"
+        ));
+    }
+    Ok(())
+}
+
+async fn require_manager_approval(
+    tx: &mut sqlx::Transaction<'static, sqlx::Sqlite>,
+    approved_by: Uuid,
+) -> Result<()> {
+    let role: Option<String> = sqlx::query_scalar("SELECT role FROM Users WHERE user_uuid = ?")
+        .bind(approved_by.to_string())
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    match role.as_deref() {
+        Some("Manager") | Some("Admin") => Ok(()),
+        Some(_) => Err(anyhow::anyhow!(
+            "Price corrections must be approved by a manager"
+        )),
+        None => Err(anyhow::anyhow!("Approving user {} not found", approved_by)),
+    }
+}
+
+async fn load_header(
+    tx: &mut sqlx::Transaction<'static, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+) -> Result<TransactionHeader> {
+    let row = sqlx::query(
+        "SELECT customer_uuid, subtotal, tax_amount, total, voided_at, transaction_type
+         FROM Transactions WHERE transaction_uuid = ?",
+    )
+    .bind(transaction_uuid.to_string())
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+    .ok_or_else(|| anyhow::anyhow!("Transaction {} not found", transaction_uuid))?;
+
+    let voided_at: Option<String> = sqlx::Row::try_get(&row, "voided_at").ok().flatten();
+    if voided_at.is_some() {
+        return Err(anyhow::anyhow!(
+            "Transaction {} has been voided and cannot be adjusted",
+            transaction_uuid
+        ));
+    }
+    let transaction_type: String = sqlx::Row::try_get(&row, "transaction_type").unwrap_or_default();
+    if transaction_type != "Sale" {
+        return Err(anyhow::anyhow!("Only sales can be adjusted after the fact"));
+    }
+
+    let mut header = TransactionHeader {
+        customer_uuid: sqlx::Row::try_get(&row, "customer_uuid").ok().flatten(),
+        subtotal: sqlx::Row::try_get::<Option<f64>, _>(&row, "subtotal")
+            .ok()
+            .flatten()
+            .unwrap_or(0.0),
+        tax_amount: sqlx::Row::try_get::<Option<f64>, _>(&row, "tax_amount")
+            .ok()
+            .flatten()
+            .unwrap_or(0.0),
+        total: sqlx::Row::try_get::<Option<f64>, _>(&row, "total")
+            .ok()
+            .flatten()
+            .unwrap_or(0.0),
+    };
+
+    // The sale row keeps what was rung up; start from the latest change to it
+    let latest = sqlx::query(
+        "SELECT total_after,
+                CAST(json_extract(after_values, '$.subtotal') AS REAL) as subtotal,
+                CAST(json_extract(after_values, '$.tax_amount') AS REAL) as tax_amount
+         FROM Transaction_Adjustments
+         WHERE transaction_uuid = ? AND adjustment_type IN ('price_correction', 'line_void')
+         ORDER BY created_at DESC, rowid DESC LIMIT 1",
+    )
+    .bind(transaction_uuid.to_string())
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    if let Some(latest) = latest {
+        header.subtotal = sqlx::Row::try_get(&latest, "subtotal").unwrap_or(header.subtotal);
+        header.tax_amount = sqlx::Row::try_get(&latest, "tax_amount").unwrap_or(header.tax_amount);
+        header.total = sqlx::Row::try_get(&latest, "total_after").unwrap_or(header.total);
+    }
+
+    Ok(header)
+}
+
+async fn load_line(
+    tx: &mut sqlx::Transaction<'static, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+    item_uuid: Uuid,
+) -> Result<SoldLine> {
+    let row = sqlx::query(
+        "SELECT product_uuid, quantity, unit_price, condition FROM Transaction_Items
+         WHERE item_uuid = ? AND transaction_uuid = ?",
+    )
+    .bind(item_uuid.to_string())
+    .bind(transaction_uuid.to_string())
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+    .ok_or_else(|| {
+        anyhow::anyhow!(
+            "Line {} not found on transaction {}",
+            item_uuid,
+            transaction_uuid
+        )
+    })?;
+
+    let mut line = SoldLine {
+        product_uuid: sqlx::Row::try_get(&row, "product_uuid").unwrap_or_default(),
+        quantity: sqlx::Row::try_get(&row, "quantity").unwrap_or(0),
+        unit_price: sqlx::Row::try_get(&row, "unit_price").unwrap_or(0.0),
+        condition: sqlx::Row::try_get(&row, "condition").unwrap_or_else(|_| "NM".to_string()),
+    };
+    if let Some((quantity, unit_price)) = adjusted_line(&mut **tx, &item_uuid.to_string()).await? {
+        line.quantity = quantity;
+        line.unit_price = unit_price;
+    }
+
+    Ok(line)
+}
+
+/// Apply a subtotal change, keeping the sale's effective tax rate.
+/// Returns the new subtotal, tax amount and total.
+fn adjusted_totals(header: &TransactionHeader, subtotal_delta: f64) -> (f64, f64, f64) {
+    let new_subtotal = round_cents(header.subtotal + subtotal_delta);
+    let ratio = if header.subtotal.abs() > f64::EPSILON {
+        new_subtotal / header.subtotal
+    } else {
+        1.0
+    };
+    let new_tax = round_cents(header.tax_amount * ratio);
+    let new_total = round_cents(
+        header.total + (new_subtotal - header.subtotal) + (new_tax - header.tax_amount),
+    );
+    (new_subtotal, new_tax, new_total)
+}
+
+/// Settle a change in total. An increase is recorded as a balance due
+/// from the customer rather than as money collected. A decrease first
+/// clears any balance still due; the rest is refunded to the sale's main
+/// tender.
+async fn settle_difference(
+    tx: &mut sqlx::Transaction<'static, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+    header: &TransactionHeader,
+    amount: f64,
+) -> Result<Settlement> {
+    let amount = round_cents(amount);
+    if amount.abs() < 0.005 {
+        return Ok(Settlement {
+            payment_uuid: None,
+            balance_due: 0.0,
+        });
+    }
+    if amount > 0.0 {
+        return Ok(Settlement {
+            payment_uuid: None,
+            balance_due: amount,
+        });
+    }
+
+    let outstanding = balance_due_with_tx(tx, transaction_uuid).await?;
+    let cleared = outstanding.max(0.0).min(-amount);
+    let refund = round_cents(amount + cleared);
+    if refund.abs() < 0.005 {
+        return Ok(Settlement {
+            payment_uuid: None,
+            balance_due: -cleared,
+        });
+    }
+
+    let method: String = sqlx::query_scalar(
+        "SELECT method_type FROM Payment_Methods WHERE transaction_uuid = ? AND amount > 0
+         ORDER BY amount DESC LIMIT 1",
+    )
+    .bind(transaction_uuid.to_string())
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+    // Differences aren't settled in points
+    .filter(|m| *m != PaymentMethodType::LoyaltyPoints.to_string())
+    .unwrap_or_else(|| PaymentMethodType::Cash.to_string());
+
+    if method == PaymentMethodType::StoreCredit.to_string() {
+        let customer_uuid = header
+            .customer_uuid
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Store credit settlement requires a customer"))?;
+        adjust_store_credit(tx, customer_uuid, -refund).await?;
+    }
+
+    let payment_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Payment_Methods (payment_uuid, transaction_uuid, method_type, amount, reference, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(payment_uuid.to_string())
+    .bind(transaction_uuid.to_string())
+    .bind(&method)
+    .bind(refund)
+    .bind("Post-sale adjustment")
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record settlement: {}", e))?;
+
+    Ok(Settlement {
+        payment_uuid: Some(payment_uuid),
+        balance_due: -cleared,
+    })
+}
+
+/// What the customer still owes on a sale after post-sale increases
+async fn balance_due_with_tx(
+    tx: &mut sqlx::Transaction<'static, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+) -> Result<f64> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(CAST(json_extract(after_values, '$.balance_due') AS REAL)), 0.0)
+         FROM Transaction_Adjustments WHERE transaction_uuid = ?",
+    )
+    .bind(transaction_uuid.to_string())
+    .fetch_one(&mut **tx)
+    .await
+    .map(round_cents)
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))
+}
+
+/// Piles to put voided units back into: those the line was sold from, most
+/// units first, falling back to any live pile of the same product and
+/// condition for sales that predate the movement ledger
+async fn restock_targets(
+    tx: &mut sqlx::Transaction<'static, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+    line: &SoldLine,
+    quantity: i32,
+) -> Result<Vec<(Uuid, i32)>> {
+    let rows = sqlx::query(
+        "SELECT m.inventory_uuid, -SUM(m.quantity_change) as sold
+         FROM Inventory_Movements m
+         JOIN Local_Inventory li ON li.inventory_uuid = m.inventory_uuid
+         WHERE m.source_uuid = ? AND m.movement_type = ? AND m.product_uuid = ? AND li.condition = ?
+         GROUP BY m.inventory_uuid
+         ORDER BY sold DESC, m.inventory_uuid",
+    )
+    .bind(transaction_uuid.to_string())
+    .bind(MovementType::Sale.as_str())
+    .bind(&line.product_uuid)
+    .bind(&line.condition)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to find the piles sold from: {}", e))?;
+
+    let mut sold_from: Vec<(Uuid, i32)> = rows
+        .iter()
+        .filter_map(|row| {
+            let inventory_uuid: String = sqlx::Row::try_get(row, "inventory_uuid").ok()?;
+            let sold: i32 = sqlx::Row::try_get(row, "sold").unwrap_or(0);
+            Some((Uuid::parse_str(&inventory_uuid).ok()?, sold))
+        })
+        .collect();
+
+    if sold_from.is_empty() {
+        let fallback: Option<String> = sqlx::query_scalar(
+            "SELECT inventory_uuid FROM Local_Inventory
+             WHERE product_uuid = ? AND condition = ? AND deleted_at IS NULL
+             LIMIT 1",
+        )
+        .bind(&line.product_uuid)
+        .bind(&line.condition)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to restore inventory: {}", e))?;
+        return Ok(fallback
+            .and_then(|s| Uuid::parse_str(&s).ok())
+            .map(|inventory_uuid| vec![(inventory_uuid, quantity)])
+            .unwrap_or_default());
+    }
+
+    // Spread the voided units over the piles by what each supplied; any
+    // excess (e.g. from an earlier void) goes to the largest
+    let mut remaining = quantity;
+    for (_, share) in sold_from.iter_mut() {
+        *share = (*share).clamp(0, remaining);
+        remaining -= *share;
+    }
+    sold_from[0].1 += remaining;
+    sold_from.retain(|(_, share)| *share > 0);
+    Ok(sold_from)
+}
+
+async fn adjust_store_credit(
+    tx: &mut sqlx::Transaction<'static, sqlx::Sqlite>,
+    customer_uuid: &str,
+    amount: f64,
+) -> Result<()> {
+    sqlx::query("UPDATE Customers SET store_credit = store_credit + ? WHERE customer_uuid = ?")
+        .bind(amount)
+        .bind(customer_uuid)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update store credit: {}", e))?;
+    Ok(())
+}
+
+async fn insert_adjustment(
+    tx: &mut sqlx::Transaction<'static, sqlx::Sqlite>,
+    new: NewAdjustment,
+) -> Result<TransactionAdjustment> {
+    let previous: Option<String> = sqlx::query_scalar(
+        "SELECT adjustment_uuid FROM Transaction_Adjustments WHERE transaction_uuid = ?
+         ORDER BY created_at DESC, rowid DESC LIMIT 1",
+    )
+    .bind(new.transaction_uuid.to_string())
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let adjustment = TransactionAdjustment {
+        adjustment_uuid: Uuid::new_v4(),
+        transaction_uuid: new.transaction_uuid,
+        previous_adjustment_uuid: previous.and_then(|s| Uuid::parse_str(&s).ok()),
+        adjustment_type: new.adjustment_type,
+        item_uuid: new.item_uuid,
+        payment_uuid: new.payment_uuid,
+        total_before: new.total_before,
+        total_after: new.total_after,
+        amount_delta: round_cents(new.total_after - new.total_before),
+        before: new.before,
+        after: new.after,
+        reason: new.reason,
+        performed_by: new.performed_by,
+        approved_by: new.approved_by,
+        created_at: Utc::now(),
+    };
+
+    sqlx::query(
+        "INSERT INTO Transaction_Adjustments
+         (adjustment_uuid, transaction_uuid, previous_adjustment_uuid, adjustment_type, item_uuid, payment_uuid,
+          total_before, total_after, amount_delta, before_values, after_values, reason, performed_by, approved_by, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(adjustment.adjustment_uuid.to_string())
+    .bind(adjustment.transaction_uuid.to_string())
+    .bind(adjustment.previous_adjustment_uuid.map(|u| u.to_string()))
+    .bind(adjustment.adjustment_type.to_string())
+    .bind(adjustment.item_uuid.map(|u| u.to_string()))
+    .bind(adjustment.payment_uuid.map(|u| u.to_string()))
+    .bind(adjustment.total_before)
+    .bind(adjustment.total_after)
+    .bind(adjustment.amount_delta)
+    .bind(adjustment.before.to_string())
+    .bind(adjustment.after.to_string())
+    .bind(&adjustment.reason)
+    .bind(adjustment.performed_by.map(|u| u.to_string()))
+    .bind(adjustment.approved_by.map(|u| u.to_string()))
+    .bind(adjustment.created_at.to_rfc3339())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record adjustment: {}", e))?;
+
+    Ok(adjustment)
+}
+
+fn map_adjustment(row: &sqlx::sqlite::SqliteRow) -> Option<TransactionAdjustment> {
+    let uuid = |col: &str| -> Option<Uuid> {
+        sqlx::Row::try_get::<Option<String>, _>(row, col)
+            .ok()
+            .flatten()
+            .and_then(|s| Uuid::parse_str(&s).ok())
+    };
+    let json_col = |col: &str| -> serde_json::Value {
+        sqlx::Row::try_get::<String, _>(row, col)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or(serde_json::Value::Null)
+    };
+    let kind: String = sqlx::Row::try_get(row, "adjustment_type").ok()?;
+
+    Some(TransactionAdjustment {
+        adjustment_uuid: uuid("adjustment_uuid")?,
+        transaction_uuid: uuid("transaction_uuid")?,
+        previous_adjustment_uuid: uuid("previous_adjustment_uuid"),
+        adjustment_type: AdjustmentType::parse(&kind)?,
+        item_uuid: uuid("item_uuid"),
+        payment_uuid: uuid("payment_uuid"),
+        total_before: sqlx::Row::try_get(row, "total_before").unwrap_or(0.0),
+        total_after: sqlx::Row::try_get(row, "total_after").unwrap_or(0.0),
+        amount_delta: sqlx::Row::try_get(row, "amount_delta").unwrap_or(0.0),
+        before: json_col("before_values"),
+        after: json_col("after_values"),
+        reason: sqlx::Row::try_get(row, "reason").unwrap_or_default(),
+        performed_by: uuid("performed_by"),
+        approved_by: uuid("approved_by"),
+        created_at: sqlx::Row::try_get::<String, _>(row, "created_at")
+            .ok()
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(Utc::now),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjustment_type_round_trip() {
+        for kind in [
+            AdjustmentType::Retender,
+            AdjustmentType::AttachCustomer,
+            AdjustmentType::PriceCorrection,
+            AdjustmentType::LineVoid,
+        ] {
+            assert_eq!(AdjustmentType::parse(&kind.to_string()), Some(kind));
+        }
+        assert_eq!(AdjustmentType::parse("refund"), None);
+    }
+}
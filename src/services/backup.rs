@@ -7,6 +7,7 @@
 //! - Backup verification
 //! - Retention policy management
 //! - Manual and scheduled backups
+//! - Uploaded media, mirrored alongside the database backups
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Utc};
@@ -33,6 +34,10 @@ pub struct BackupConfig {
 
     /// Whether to create checksums for backups
     pub create_checksum: bool,
+
+    /// Media directory whose stored images are backed up with the database
+    /// (None to skip media)
+    pub media_dir: Option<PathBuf>,
 }
 
 impl Default for BackupConfig {
@@ -43,6 +48,7 @@ impl Default for BackupConfig {
             retention_days: 30,
             max_backups: 50,
             create_checksum: true,
+            media_dir: Some(PathBuf::from("./media")),
         }
     }
 }
@@ -68,6 +74,11 @@ impl BackupConfig {
             create_checksum: std::env::var("BACKUP_CHECKSUM")
                 .map(|s| s.to_lowercase() != "false")
                 .unwrap_or(true),
+            media_dir: Some(
+                std::env::var("MEDIA_DIR")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| PathBuf::from("./media")),
+            ),
         }
     }
 }
@@ -92,6 +103,38 @@ pub struct BackupResult {
     pub checksum: Option<String>,
     pub duration_ms: u64,
     pub message: String,
+    /// Images newly copied into the backup's media store
+    pub media_files_copied: usize,
+}
+
+/// Copy every file under `src` that `dest` doesn't have yet, keeping the
+/// relative layout. Media blobs are named by their content hash, so an
+/// existing file never needs overwriting. Returns how many were copied.
+pub fn mirror_missing_files(src: &Path, dest: &Path) -> Result<usize> {
+    if !src.is_dir() {
+        return Ok(0);
+    }
+    let mut copied = 0;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copied += mirror_missing_files(&path, &target)?;
+        } else if !target.exists() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Skip half-written uploads
+            if name.contains(".tmp-") {
+                continue;
+            }
+            fs::create_dir_all(dest).context("Failed to create media backup directory")?;
+            let partial = dest.join(format!("{}.partial", name));
+            fs::copy(&path, &partial).context("Failed to copy media file")?;
+            fs::rename(&partial, &target).context("Failed to copy media file")?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
 }
 
 /// Backup service for managing database backups
@@ -118,6 +161,11 @@ impl BackupService {
         Ok(())
     }
 
+    /// Where backed-up media is kept
+    fn media_backup_dir(&self) -> PathBuf {
+        self.config.backup_dir.join("media")
+    }
+
     /// Generate a backup filename with timestamp
     fn generate_backup_filename(&self) -> String {
         let timestamp = Utc::now().format("%Y-%m-%d_%H-%M-%S");
@@ -158,6 +206,7 @@ impl BackupService {
                 checksum: None,
                 duration_ms: start.elapsed().as_millis() as u64,
                 message: "Source database does not exist".to_string(),
+                media_files_copied: 0,
             });
         }
 
@@ -184,7 +233,22 @@ impl BackupService {
             None
         };
 
-        tracing::info!("Backup created: {} ({} bytes)", backup_filename, size_bytes);
+        // Media is content-addressed, so one shared store serves every
+        // database backup and only new images are copied
+        let media_files_copied = match &self.config.media_dir {
+            Some(media_dir) => mirror_missing_files(
+                &media_dir.join("blobs"),
+                &self.media_backup_dir().join("blobs"),
+            )?,
+            None => 0,
+        };
+
+        tracing::info!(
+            "Backup created: {} ({} bytes, {} new media files)",
+            backup_filename,
+            size_bytes,
+            media_files_copied
+        );
 
         Ok(BackupResult {
             success: true,
@@ -193,6 +257,7 @@ impl BackupService {
             checksum,
             duration_ms: start.elapsed().as_millis() as u64,
             message: format!("Backup created successfully: {}", backup_filename),
+            media_files_copied,
         })
     }
 
@@ -364,6 +429,18 @@ impl BackupService {
         // Copy backup to database location
         fs::copy(backup_path, &self.config.database_path).context("Failed to restore backup")?;
 
+        // Bring back any images the restored database refers to that are
+        // missing locally
+        if let Some(media_dir) = &self.config.media_dir {
+            let restored = mirror_missing_files(
+                &self.media_backup_dir().join("blobs"),
+                &media_dir.join("blobs"),
+            )?;
+            if restored > 0 {
+                tracing::info!("Restored {} media files", restored);
+            }
+        }
+
         tracing::info!(
             "Database restored from {:?}. Pre-restore backup saved to {:?}",
             backup_path,
@@ -395,4 +472,27 @@ mod tests {
         assert_eq!(config.max_backups, 50);
         assert!(config.create_checksum);
     }
+
+    #[test]
+    fn test_mirror_missing_files() {
+        let root = std::env::temp_dir().join(format!("vaultsync_mirror_{}", std::process::id()));
+        let src = root.join("src");
+        let dest = root.join("dest");
+        fs::create_dir_all(src.join("ab")).unwrap();
+        fs::write(src.join("ab").join("abc123"), b"image").unwrap();
+        fs::write(src.join("ab").join("abc456.tmp-1"), b"partial").unwrap();
+
+        assert_eq!(mirror_missing_files(&src, &dest).unwrap(), 1);
+        assert_eq!(fs::read(dest.join("ab").join("abc123")).unwrap(), b"image");
+        assert!(!dest.join("ab").join("abc456.tmp-1").exists());
+
+        // Nothing new the second time
+        assert_eq!(mirror_missing_files(&src, &dest).unwrap(), 0);
+        assert_eq!(
+            mirror_missing_files(&root.join("missing"), &dest).unwrap(),
+            0
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
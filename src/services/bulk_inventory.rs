@@ -0,0 +1,1396 @@
+//! Bulk inventory import and export
+//!
+//! Loads inventory from CSV or JSON files, such as another POS's export or a
+//! Manabox, Deckbox or TCGplayer collection, using a column mapping profile.
+//! Rows are matched against `Global_Catalog` and applied inside a single
+//! database transaction as the file streams in. A dry run rolls that
+//! transaction back, so its diff is exactly what a commit would do; a real
+//! import commits only when every row is clean. Export writes the full
+//! inventory back out in any of the same profiles.
+
+use crate::core::{Condition, VariantType};
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
+use crate::database::Database;
+use crate::errors::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    Csv,
+    Json,
+}
+
+impl std::fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataFormat::Csv => write!(f, "csv"),
+            DataFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl DataFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Some(DataFormat::Csv),
+            "json" => Some(DataFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Built-in column layouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportProfile {
+    /// Our own export; round-trips every field
+    VaultSync,
+    Manabox,
+    Deckbox,
+    TcgPlayer,
+}
+
+impl std::fmt::Display for ImportProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportProfile::VaultSync => write!(f, "vaultsync"),
+            ImportProfile::Manabox => write!(f, "manabox"),
+            ImportProfile::Deckbox => write!(f, "deckbox"),
+            ImportProfile::TcgPlayer => write!(f, "tcgplayer"),
+        }
+    }
+}
+
+impl ImportProfile {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "vaultsync" => Some(ImportProfile::VaultSync),
+            "manabox" => Some(ImportProfile::Manabox),
+            "deckbox" => Some(ImportProfile::Deckbox),
+            "tcgplayer" => Some(ImportProfile::TcgPlayer),
+            _ => None,
+        }
+    }
+
+    pub fn all() -> [ImportProfile; 4] {
+        [
+            ImportProfile::VaultSync,
+            ImportProfile::Manabox,
+            ImportProfile::Deckbox,
+            ImportProfile::TcgPlayer,
+        ]
+    }
+
+    pub fn mapping(&self) -> ColumnMapping {
+        let col = |s: &str| Some(s.to_string());
+        match self {
+            ImportProfile::VaultSync => ColumnMapping {
+                inventory_uuid: col("inventory_uuid"),
+                product_uuid: col("product_uuid"),
+                name: col("name"),
+                set_code: col("set_code"),
+                collector_number: col("collector_number"),
+                barcode: col("barcode"),
+                condition: col("condition"),
+                foil: col("variant"),
+                quantity: col("quantity"),
+                location: col("location"),
+                bin: col("bin"),
+                cost: col("cost"),
+                price: col("price"),
+            },
+            ImportProfile::Manabox => ColumnMapping {
+                name: col("Name"),
+                set_code: col("Set code"),
+                collector_number: col("Collector number"),
+                condition: col("Condition"),
+                foil: col("Foil"),
+                quantity: col("Quantity"),
+                cost: col("Purchase price"),
+                ..ColumnMapping::default()
+            },
+            ImportProfile::Deckbox => ColumnMapping {
+                name: col("Name"),
+                set_code: col("Edition Code"),
+                collector_number: col("Card Number"),
+                condition: col("Condition"),
+                foil: col("Foil"),
+                quantity: col("Count"),
+                price: col("My Price"),
+                ..ColumnMapping::default()
+            },
+            // Foil is part of the condition ("Near Mint Foil")
+            ImportProfile::TcgPlayer => ColumnMapping {
+                name: col("Product Name"),
+                collector_number: col("Number"),
+                condition: col("Condition"),
+                quantity: col("Total Quantity"),
+                price: col("TCG Marketplace Price"),
+                ..ColumnMapping::default()
+            },
+        }
+    }
+
+    /// How this format spells a condition (and foil, where it's combined)
+    fn condition_label(&self, condition: &Condition, foil: bool) -> String {
+        let tcg = |nm: &'static str, lp, mp, hp, dmg| match condition {
+            Condition::NM | Condition::New | Condition::GemMint | Condition::Mint => Some(nm),
+            Condition::LP | Condition::NearMintMint | Condition::VeryFine => Some(lp),
+            Condition::MP | Condition::Fine | Condition::OpenBox | Condition::Used => Some(mp),
+            Condition::HP | Condition::Good => Some(hp),
+            Condition::DMG | Condition::Poor => Some(dmg),
+        };
+        match self {
+            ImportProfile::VaultSync => format!("{:?}", condition),
+            ImportProfile::Manabox => tcg(
+                "near_mint",
+                "lightly_played",
+                "moderately_played",
+                "heavily_played",
+                "damaged",
+            )
+            .unwrap_or("near_mint")
+            .to_string(),
+            ImportProfile::Deckbox => tcg(
+                "Near Mint",
+                "Good (Lightly Played)",
+                "Played",
+                "Heavily Played",
+                "Poor",
+            )
+            .unwrap_or("Near Mint")
+            .to_string(),
+            ImportProfile::TcgPlayer => {
+                let label = tcg(
+                    "Near Mint",
+                    "Lightly Played",
+                    "Moderately Played",
+                    "Heavily Played",
+                    "Damaged",
+                )
+                .unwrap_or("Near Mint");
+                if foil {
+                    format!("{} Foil", label)
+                } else {
+                    label.to_string()
+                }
+            }
+        }
+    }
+
+    fn foil_label(&self, variant: Option<&str>) -> String {
+        let foil = variant == Some("Foil");
+        match self {
+            ImportProfile::VaultSync => variant.unwrap_or_default().to_string(),
+            ImportProfile::Manabox => if foil { "foil" } else { "normal" }.to_string(),
+            ImportProfile::Deckbox => if foil { "foil" } else { "" }.to_string(),
+            ImportProfile::TcgPlayer => String::new(),
+        }
+    }
+}
+
+/// Which file column feeds each inventory field. Unmapped fields are
+/// skipped on import and left out of exports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    pub inventory_uuid: Option<String>,
+    pub product_uuid: Option<String>,
+    pub name: Option<String>,
+    pub set_code: Option<String>,
+    pub collector_number: Option<String>,
+    pub barcode: Option<String>,
+    pub condition: Option<String>,
+    /// Foil/variant flag
+    pub foil: Option<String>,
+    pub quantity: Option<String>,
+    pub location: Option<String>,
+    pub bin: Option<String>,
+    pub cost: Option<String>,
+    pub price: Option<String>,
+}
+
+impl ColumnMapping {
+    /// Header names in export order
+    fn columns(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("inventory_uuid", &self.inventory_uuid),
+            ("product_uuid", &self.product_uuid),
+            ("name", &self.name),
+            ("set_code", &self.set_code),
+            ("collector_number", &self.collector_number),
+            ("barcode", &self.barcode),
+            ("condition", &self.condition),
+            ("foil", &self.foil),
+            ("quantity", &self.quantity),
+            ("location", &self.location),
+            ("bin", &self.bin),
+            ("cost", &self.cost),
+            ("price", &self.price),
+        ]
+        .into_iter()
+        .filter_map(|(field, header)| header.as_deref().map(|h| (field, h)))
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingProfile {
+    pub name: String,
+    pub built_in: bool,
+    pub mapping: ColumnMapping,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Add file quantities on top of what's on hand
+    Add,
+    /// Set on-hand quantities to what the file says
+    Replace,
+}
+
+impl std::fmt::Display for ImportMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportMode::Add => write!(f, "add"),
+            ImportMode::Replace => write!(f, "replace"),
+        }
+    }
+}
+
+impl ImportMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "add" => Some(ImportMode::Add),
+            "replace" => Some(ImportMode::Replace),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    pub format: DataFormat,
+    /// A built-in profile or the name of a saved one
+    pub profile: String,
+    pub mode: ImportMode,
+    /// Location for rows that don't name one
+    pub default_location: String,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RowAction {
+    Create,
+    Update,
+    Unchanged,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowResult {
+    /// 1-based data row, not counting the header
+    pub row: usize,
+    pub action: RowAction,
+    pub product_uuid: Option<Uuid>,
+    pub product_name: Option<String>,
+    /// How the catalog match was made, e.g. `set_code+collector_number`
+    pub matched_by: Option<String>,
+    pub inventory_uuid: Option<Uuid>,
+    pub condition: Option<String>,
+    pub location_tag: Option<String>,
+    pub quantity_before: i32,
+    pub quantity_after: i32,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub import_uuid: Uuid,
+    pub profile: String,
+    pub mode: ImportMode,
+    pub dry_run: bool,
+    /// True only when the rows were written; any row error rolls back all of them
+    pub committed: bool,
+    pub rows: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub errors: usize,
+    pub units_added: i64,
+    pub units_removed: i64,
+    pub results: Vec<ImportRowResult>,
+}
+
+/// Incremental RFC 4180 reader: feed text in any chunking and get back the
+/// records completed so far. Handles quoted fields with embedded commas,
+/// quotes and newlines, CRLF line endings and a leading byte-order mark.
+#[derive(Debug, Default)]
+pub struct CsvReader {
+    field: String,
+    record: Vec<String>,
+    in_quotes: bool,
+    /// Saw a quote inside a quoted field: either an escaped quote or the end
+    quote_pending: bool,
+    started: bool,
+}
+
+impl CsvReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, text: &str) -> Vec<Vec<String>> {
+        let mut records = Vec::new();
+        let mut chars = text.chars();
+        if !self.started {
+            self.started = true;
+            let mut peek = chars.clone();
+            if peek.next() == Some('\u{feff}') {
+                chars = peek;
+            }
+        }
+        for c in chars {
+            if self.quote_pending {
+                self.quote_pending = false;
+                if c == '"' {
+                    self.field.push('"');
+                    continue;
+                }
+                self.in_quotes = false;
+            }
+            if self.in_quotes {
+                if c == '"' {
+                    self.quote_pending = true;
+                } else {
+                    self.field.push(c);
+                }
+                continue;
+            }
+            match c {
+                '"' if self.field.is_empty() => self.in_quotes = true,
+                ',' => self.record.push(std::mem::take(&mut self.field)),
+                '\n' => {
+                    if let Some(record) = self.end_record() {
+                        records.push(record);
+                    }
+                }
+                '\r' => {}
+                _ => self.field.push(c),
+            }
+        }
+        records
+    }
+
+    /// The last record, if the input didn't end with a newline
+    pub fn finish(&mut self) -> Option<Vec<String>> {
+        self.quote_pending = false;
+        self.in_quotes = false;
+        self.end_record()
+    }
+
+    fn end_record(&mut self) -> Option<Vec<String>> {
+        self.record.push(std::mem::take(&mut self.field));
+        let record = std::mem::take(&mut self.record);
+        // Blank lines aren't records
+        if record.len() == 1 && record[0].trim().is_empty() {
+            None
+        } else {
+            Some(record)
+        }
+    }
+}
+
+/// Quote a CSV field if it needs it
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Read a condition as any of the common formats spell it. Returns the
+/// condition and whether the label also said foil (TCGplayer style).
+pub fn normalize_condition(label: &str) -> Option<(Condition, bool)> {
+    let mut label = label.trim().to_lowercase().replace(['_', '-'], " ");
+    let foil = label.ends_with(" foil");
+    if foil {
+        label.truncate(label.len() - " foil".len());
+    }
+    let condition = match label.trim() {
+        "nm" | "near mint" | "mint" | "m" | "nm/m" | "nm m" => Condition::NM,
+        "lp"
+        | "lightly played"
+        | "good (lightly played)"
+        | "excellent"
+        | "ex"
+        | "sp"
+        | "slightly played" => Condition::LP,
+        "mp" | "moderately played" | "played" | "pl" => Condition::MP,
+        "hp" | "heavily played" => Condition::HP,
+        "dmg" | "damaged" | "poor" => Condition::DMG,
+        "new" | "sealed" => Condition::New,
+        "openbox" | "open box" => Condition::OpenBox,
+        "used" => Condition::Used,
+        "gemmint" | "gem mint" => Condition::GemMint,
+        "nearmintmint" | "near mint mint" => Condition::NearMintMint,
+        "veryfine" | "very fine" => Condition::VeryFine,
+        "fine" => Condition::Fine,
+        "good" => Condition::Good,
+        _ => return None,
+    };
+    Some((condition, foil))
+}
+
+fn parse_variant(value: &str) -> std::result::Result<Option<VariantType>, String> {
+    match value.trim().to_lowercase().as_str() {
+        "" | "normal" | "false" | "no" | "0" | "nonfoil" | "non-foil" => Ok(None),
+        "foil" | "true" | "yes" | "1" | "etched" => Ok(Some(VariantType::Foil)),
+        "reverseholo" | "reverse holo" => Ok(Some(VariantType::ReverseHolo)),
+        "firstedition" | "first edition" | "1st edition" => Ok(Some(VariantType::FirstEdition)),
+        "stamped" => Ok(Some(VariantType::Stamped)),
+        "signed" => Ok(Some(VariantType::Signed)),
+        other => Err(format!("Unknown variant '{}'", other)),
+    }
+}
+
+fn parse_money(value: &str) -> std::result::Result<f64, String> {
+    let cleaned: String = value
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '$' | ',' | ' '))
+        .collect();
+    cleaned
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .ok_or_else(|| format!("Invalid amount '{}'", value))
+}
+
+/// One file row, by header name
+type Fields = HashMap<String, String>;
+
+fn get<'a>(fields: &'a Fields, header: &Option<String>) -> Option<&'a str> {
+    let header = header.as_ref()?.trim().to_lowercase();
+    fields
+        .get(&header)
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+}
+
+#[derive(Clone)]
+pub struct BulkInventoryService {
+    db: Arc<Database>,
+}
+
+impl BulkInventoryService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Built-in profiles followed by saved ones
+    pub async fn get_profiles(&self) -> Result<Vec<MappingProfile>> {
+        let mut profiles: Vec<MappingProfile> = ImportProfile::all()
+            .iter()
+            .map(|p| MappingProfile {
+                name: p.to_string(),
+                built_in: true,
+                mapping: p.mapping(),
+            })
+            .collect();
+
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT name, mapping FROM Import_Profiles ORDER BY name")
+                .fetch_all(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        for (name, mapping) in rows {
+            match serde_json::from_str(&mapping) {
+                Ok(mapping) => profiles.push(MappingProfile {
+                    name,
+                    built_in: false,
+                    mapping,
+                }),
+                Err(e) => tracing::warn!("Skipping unreadable import profile {}: {}", name, e),
+            }
+        }
+        Ok(profiles)
+    }
+
+    /// Save a custom column mapping under a name for later imports and exports
+    pub async fn save_profile(&self, name: &str, mapping: ColumnMapping) -> Result<MappingProfile> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Profile name is required"));
+        }
+        if ImportProfile::parse(name).is_some() {
+            return Err(anyhow::anyhow!("'{}' is a built-in profile", name));
+        }
+        if mapping.quantity.is_none() {
+            return Err(anyhow::anyhow!("A mapping needs a quantity column"));
+        }
+        if mapping.inventory_uuid.is_none()
+            && mapping.product_uuid.is_none()
+            && mapping.name.is_none()
+            && mapping.barcode.is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "A mapping needs a column to match products by (name, barcode or product_uuid)"
+            ));
+        }
+
+        sqlx::query(
+            "INSERT OR REPLACE INTO Import_Profiles (name, mapping, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(name)
+        .bind(serde_json::to_string(&mapping)?)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to save import profile: {}", e))?;
+
+        Ok(MappingProfile {
+            name: name.to_string(),
+            built_in: false,
+            mapping,
+        })
+    }
+
+    async fn resolve_profile(
+        &self,
+        profile: &str,
+    ) -> Result<(Option<ImportProfile>, ColumnMapping)> {
+        if let Some(built_in) = ImportProfile::parse(profile) {
+            return Ok((Some(built_in), built_in.mapping()));
+        }
+        let mapping: Option<String> =
+            sqlx::query_scalar("SELECT mapping FROM Import_Profiles WHERE name = ?")
+                .bind(profile)
+                .fetch_optional(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let mapping =
+            mapping.ok_or_else(|| anyhow::anyhow!("Unknown import profile '{}'", profile))?;
+        Ok((None, serde_json::from_str(&mapping)?))
+    }
+
+    /// Start an import. Feed the file with [`ImportSession::push`] as it
+    /// arrives, then call [`ImportSession::finish`] for the report.
+    pub async fn begin_import(
+        &self,
+        options: ImportOptions,
+        imported_by: Option<Uuid>,
+    ) -> Result<ImportSession> {
+        let (_, mapping) = self.resolve_profile(&options.profile).await?;
+        if mapping.quantity.is_none() {
+            return Err(anyhow::anyhow!(
+                "Profile '{}' has no quantity column",
+                options.profile
+            ));
+        }
+        let tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        let import_uuid = Uuid::new_v4();
+
+        Ok(ImportSession {
+            tx,
+            source: MovementSource {
+                notes: Some(format!("{} import", options.profile)),
+                ..MovementSource::new(
+                    MovementType::Import,
+                    Some(import_uuid),
+                    imported_by,
+                    &self.db.node_id,
+                )
+            },
+            report: ImportReport {
+                import_uuid,
+                profile: options.profile.clone(),
+                mode: options.mode,
+                dry_run: options.dry_run,
+                committed: false,
+                rows: 0,
+                created: 0,
+                updated: 0,
+                unchanged: 0,
+                errors: 0,
+                units_added: 0,
+                units_removed: 0,
+                results: Vec::new(),
+            },
+            options,
+            mapping,
+            reader: CsvReader::new(),
+            pending: Vec::new(),
+            headers: None,
+        })
+    }
+
+    /// Import a whole file in one call
+    pub async fn import(
+        &self,
+        data: &[u8],
+        options: ImportOptions,
+        imported_by: Option<Uuid>,
+    ) -> Result<ImportReport> {
+        let mut session = self.begin_import(options, imported_by).await?;
+        session.push(data).await?;
+        session.finish().await
+    }
+
+    /// Every live inventory row in the profile's columns
+    pub async fn export(&self, format: DataFormat, profile: &str) -> Result<String> {
+        let (built_in, mapping) = self.resolve_profile(profile).await?;
+        let labels = built_in.unwrap_or(ImportProfile::VaultSync);
+        let columns = mapping.columns();
+
+        let rows = sqlx::query(
+            "SELECT li.inventory_uuid, li.product_uuid, g.name, g.set_code, g.collector_number,
+                    g.barcode, li.condition, li.variant_type, li.quantity_on_hand, li.location_tag,
+                    li.bin_location, li.cost_basis, li.specific_price
+             FROM Local_Inventory li
+             JOIN Global_Catalog g ON g.product_uuid = li.product_uuid
+             WHERE li.deleted_at IS NULL AND li.quantity_on_hand > 0
+             ORDER BY g.name, g.set_code, li.condition, li.location_tag",
+        )
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut records: Vec<Vec<(String, String)>> = Vec::with_capacity(rows.len());
+        for row in &rows {
+            use sqlx::Row;
+            let text = |col: &str| -> String {
+                row.try_get::<Option<String>, _>(col)
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default()
+            };
+            let money = |col: &str| -> String {
+                row.try_get::<Option<f64>, _>(col)
+                    .ok()
+                    .flatten()
+                    .map(|v| format!("{:.2}", v))
+                    .unwrap_or_default()
+            };
+            let variant = row
+                .try_get::<Option<String>, _>("variant_type")
+                .ok()
+                .flatten();
+            let condition = normalize_condition(&text("condition"))
+                .map(|(c, _)| c)
+                .unwrap_or(Condition::NM);
+            let foil = variant.as_deref() == Some("Foil");
+
+            records.push(
+                columns
+                    .iter()
+                    .map(|(field, header)| {
+                        let value = match *field {
+                            "inventory_uuid" => text("inventory_uuid"),
+                            "product_uuid" => text("product_uuid"),
+                            "name" => text("name"),
+                            "set_code" => text("set_code"),
+                            "collector_number" => text("collector_number"),
+                            "barcode" => text("barcode"),
+                            "condition" => labels.condition_label(&condition, foil),
+                            "foil" => labels.foil_label(variant.as_deref()),
+                            "quantity" => row
+                                .try_get::<i64, _>("quantity_on_hand")
+                                .unwrap_or(0)
+                                .to_string(),
+                            "location" => text("location_tag"),
+                            "bin" => text("bin_location"),
+                            "cost" => money("cost_basis"),
+                            "price" => money("specific_price"),
+                            _ => String::new(),
+                        };
+                        (header.to_string(), value)
+                    })
+                    .collect(),
+            );
+        }
+
+        Ok(match format {
+            DataFormat::Csv => {
+                let mut out = columns
+                    .iter()
+                    .map(|(_, header)| csv_field(header))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                out.push('\n');
+                for record in records {
+                    out.push_str(
+                        &record
+                            .iter()
+                            .map(|(_, value)| csv_field(value))
+                            .collect::<Vec<_>>()
+                            .join(","),
+                    );
+                    out.push('\n');
+                }
+                out
+            }
+            DataFormat::Json => serde_json::to_string_pretty(
+                &records
+                    .into_iter()
+                    .map(|record| {
+                        record
+                            .into_iter()
+                            .map(|(k, v)| (k, serde_json::Value::String(v)))
+                            .collect::<serde_json::Map<_, _>>()
+                    })
+                    .collect::<Vec<_>>(),
+            )?,
+        })
+    }
+}
+
+/// An import in progress, holding the open database transaction
+pub struct ImportSession {
+    tx: sqlx::Transaction<'static, sqlx::Sqlite>,
+    options: ImportOptions,
+    mapping: ColumnMapping,
+    source: MovementSource,
+    report: ImportReport,
+    reader: CsvReader,
+    /// Bytes not yet decoded: a split UTF-8 character for CSV, the whole body for JSON
+    pending: Vec<u8>,
+    headers: Option<Vec<String>>,
+}
+
+impl ImportSession {
+    /// Apply the next chunk of the file. CSV rows are applied as soon as
+    /// they are complete; JSON is applied on `finish`.
+    pub async fn push(&mut self, chunk: &[u8]) -> Result<()> {
+        self.pending.extend_from_slice(chunk);
+        if self.options.format == DataFormat::Json {
+            return Ok(());
+        }
+
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(anyhow::anyhow!("File is not valid UTF-8: {}", e)),
+        };
+        let text: String = String::from_utf8(self.pending.drain(..valid).collect())?;
+        for record in self.reader.feed(&text) {
+            self.apply_record(record).await?;
+        }
+        Ok(())
+    }
+
+    /// Finish the file and commit, unless this is a dry run or any row failed
+    pub async fn finish(mut self) -> Result<ImportReport> {
+        match self.options.format {
+            DataFormat::Csv => {
+                if !self.pending.is_empty() {
+                    return Err(anyhow::anyhow!("File ends in the middle of a character"));
+                }
+                if let Some(record) = self.reader.finish() {
+                    self.apply_record(record).await?;
+                }
+            }
+            DataFormat::Json => {
+                let rows: Vec<serde_json::Map<String, serde_json::Value>> =
+                    serde_json::from_slice(&self.pending)
+                        .map_err(|e| anyhow::anyhow!("Expected a JSON array of objects: {}", e))?;
+                for row in rows {
+                    let fields = row
+                        .into_iter()
+                        .map(|(k, v)| {
+                            let value = match v {
+                                serde_json::Value::String(s) => s,
+                                serde_json::Value::Null => String::new(),
+                                other => other.to_string(),
+                            };
+                            (k.trim().to_lowercase(), value)
+                        })
+                        .collect();
+                    self.apply_row(fields).await?;
+                }
+            }
+        }
+
+        if self.report.rows == 0 {
+            return Err(anyhow::anyhow!("The file has no rows"));
+        }
+
+        if self.options.dry_run || self.report.errors > 0 {
+            self.tx
+                .rollback()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to roll back import: {}", e))?;
+            return Ok(self.report);
+        }
+
+        sqlx::query(
+            "INSERT INTO Inventory_Imports
+             (import_uuid, profile, format, mode, rows, created, updated, unchanged, imported_by, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(self.report.import_uuid.to_string())
+        .bind(&self.report.profile)
+        .bind(self.options.format.to_string())
+        .bind(self.options.mode.to_string())
+        .bind(self.report.rows as i64)
+        .bind(self.report.created as i64)
+        .bind(self.report.updated as i64)
+        .bind(self.report.unchanged as i64)
+        .bind(self.source.user_uuid.map(|u| u.to_string()))
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *self.tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to record import: {}", e))?;
+
+        self.tx
+            .commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit import: {}", e))?;
+        self.report.committed = true;
+        tracing::info!(
+            "Imported {} rows ({} created, {} updated) with profile {}",
+            self.report.rows,
+            self.report.created,
+            self.report.updated,
+            self.report.profile
+        );
+        Ok(self.report)
+    }
+
+    async fn apply_record(&mut self, record: Vec<String>) -> Result<()> {
+        let Some(headers) = &self.headers else {
+            self.headers = Some(record.iter().map(|h| h.trim().to_lowercase()).collect());
+            return Ok(());
+        };
+        let fields = headers.iter().cloned().zip(record).collect();
+        self.apply_row(fields).await
+    }
+
+    /// Validate, match and apply one row. Database failures abort the
+    /// import; problems with the row itself are recorded against it.
+    async fn apply_row(&mut self, fields: Fields) -> Result<()> {
+        self.report.rows += 1;
+        let mut result = ImportRowResult {
+            row: self.report.rows,
+            action: RowAction::Error,
+            product_uuid: None,
+            product_name: None,
+            matched_by: None,
+            inventory_uuid: None,
+            condition: None,
+            location_tag: None,
+            quantity_before: 0,
+            quantity_after: 0,
+            errors: Vec::new(),
+        };
+
+        let parsed = self.parse_row(&fields);
+        let row = match parsed {
+            Ok(row) => row,
+            Err(errors) => {
+                result.errors = errors;
+                self.record(result);
+                return Ok(());
+            }
+        };
+        result.condition = Some(format!("{:?}", row.condition));
+        result.location_tag = Some(row.location.clone());
+
+        let matched = match self.match_product(&fields).await? {
+            Ok(matched) => matched,
+            Err(error) => {
+                result.errors.push(error);
+                self.record(result);
+                return Ok(());
+            }
+        };
+        result.product_uuid = Some(matched.product_uuid);
+        result.product_name = Some(matched.name.clone());
+        result.matched_by = Some(matched.matched_by.to_string());
+
+        let pile = match row.inventory_uuid {
+            Some(inventory_uuid) => {
+                self.pile_by_id(inventory_uuid, matched.product_uuid)
+                    .await?
+            }
+            None => Ok(self.find_pile(matched.product_uuid, &row).await?),
+        };
+        let pile = match pile {
+            Ok(pile) => pile,
+            Err(error) => {
+                result.errors.push(error);
+                self.record(result);
+                return Ok(());
+            }
+        };
+
+        let before = pile.as_ref().map(|p| p.quantity).unwrap_or(0);
+        let after = match self.options.mode {
+            ImportMode::Add => before + row.quantity,
+            ImportMode::Replace => row.quantity,
+        };
+        result.quantity_before = before;
+        result.quantity_after = after;
+
+        let created = pile.is_none();
+        let (inventory_uuid, changed_details) = match pile {
+            Some(pile) => {
+                let changed = self.update_details(&pile, &row).await?;
+                (pile.inventory_uuid, changed)
+            }
+            None => (self.create_pile(matched.product_uuid, &row).await?, false),
+        };
+        movements::set_quantity_with_tx(&mut self.tx, inventory_uuid, after, &self.source).await?;
+
+        result.inventory_uuid = Some(inventory_uuid);
+        result.action = if created {
+            RowAction::Create
+        } else if after != before || changed_details {
+            RowAction::Update
+        } else {
+            RowAction::Unchanged
+        };
+        self.record(result);
+        Ok(())
+    }
+
+    fn record(&mut self, result: ImportRowResult) {
+        match result.action {
+            RowAction::Create => self.report.created += 1,
+            RowAction::Update => self.report.updated += 1,
+            RowAction::Unchanged => self.report.unchanged += 1,
+            RowAction::Error => self.report.errors += 1,
+        }
+        let delta = (result.quantity_after - result.quantity_before) as i64;
+        if delta > 0 {
+            self.report.units_added += delta;
+        } else {
+            self.report.units_removed -= delta;
+        }
+        self.report.results.push(result);
+    }
+
+    fn parse_row(&self, fields: &Fields) -> std::result::Result<ParsedRow, Vec<String>> {
+        let m = &self.mapping;
+        let mut errors = Vec::new();
+
+        let quantity = match get(fields, &m.quantity) {
+            Some(q) => match q.parse::<i32>() {
+                Ok(q) if q >= 0 => q,
+                _ => {
+                    errors.push(format!("Invalid quantity '{}'", q));
+                    0
+                }
+            },
+            None => {
+                errors.push("Missing quantity".to_string());
+                0
+            }
+        };
+
+        let (condition, condition_foil) = match get(fields, &m.condition) {
+            Some(label) => normalize_condition(label).unwrap_or_else(|| {
+                errors.push(format!("Unknown condition '{}'", label));
+                (Condition::NM, false)
+            }),
+            None => (Condition::NM, false),
+        };
+        let variant = match get(fields, &m.foil).map(parse_variant) {
+            Some(Ok(variant)) => variant,
+            Some(Err(e)) => {
+                errors.push(e);
+                None
+            }
+            None => None,
+        };
+        let variant = variant.or(condition_foil.then_some(VariantType::Foil));
+
+        let mut money = |header: &Option<String>| match get(fields, header).map(parse_money) {
+            Some(Ok(v)) => Some(v),
+            Some(Err(e)) => {
+                errors.push(e);
+                None
+            }
+            None => None,
+        };
+        let cost = money(&m.cost);
+        let price = money(&m.price);
+
+        let inventory_uuid = match get(fields, &m.inventory_uuid) {
+            Some(s) => match Uuid::parse_str(s) {
+                Ok(u) => Some(u),
+                Err(_) => {
+                    errors.push(format!("Invalid inventory_uuid '{}'", s));
+                    None
+                }
+            },
+            None => None,
+        };
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(ParsedRow {
+            inventory_uuid,
+            quantity,
+            condition,
+            variant: variant.map(|v| format!("{:?}", v)),
+            location: get(fields, &m.location)
+                .unwrap_or(&self.options.default_location)
+                .to_string(),
+            bin: get(fields, &m.bin).map(str::to_string),
+            cost,
+            price,
+        })
+    }
+
+    /// Match a row to the catalog: product_uuid, then barcode, then set code
+    /// and collector number, then name with set code or number, then a
+    /// unique name. Returns a row error when nothing (or too much) matches.
+    async fn match_product(
+        &mut self,
+        fields: &Fields,
+    ) -> Result<std::result::Result<ProductMatch, String>> {
+        let m = self.mapping.clone();
+        let name = get(fields, &m.name);
+        let set_code = get(fields, &m.set_code);
+        let number = get(fields, &m.collector_number);
+
+        if let Some(product_uuid) = get(fields, &m.product_uuid) {
+            let found = self
+                .catalog(
+                    "SELECT product_uuid, name FROM Global_Catalog WHERE product_uuid = ? AND deleted_at IS NULL",
+                    &[product_uuid],
+                )
+                .await?;
+            return Ok(single(found, "product_uuid")
+                .ok_or_else(|| format!("Product {} is not in the catalog", product_uuid)));
+        }
+
+        if let Some(barcode) = get(fields, &m.barcode) {
+            let found = self
+                .catalog(
+                    "SELECT product_uuid, name FROM Global_Catalog
+                     WHERE (barcode = ? OR upc = ?) AND deleted_at IS NULL",
+                    &[barcode, barcode],
+                )
+                .await?;
+            if let Some(matched) = single(found, "barcode") {
+                return Ok(Ok(matched));
+            }
+        }
+
+        let same_number = "ltrim(lower(collector_number), '0') = ltrim(lower(?), '0')";
+        let mut candidates = Vec::new();
+        if let (Some(set_code), Some(number)) = (set_code, number) {
+            let found = self
+                .catalog(
+                    &format!(
+                        "SELECT product_uuid, name FROM Global_Catalog
+                         WHERE lower(set_code) = lower(?) AND {} AND deleted_at IS NULL",
+                        same_number
+                    ),
+                    &[set_code, number],
+                )
+                .await?;
+            // Reprints can share a number across names; let the name decide
+            let narrowed: Vec<_> = match name {
+                Some(name) if found.len() > 1 => found
+                    .iter()
+                    .filter(|(_, n)| n.eq_ignore_ascii_case(name))
+                    .cloned()
+                    .collect(),
+                _ => found.clone(),
+            };
+            if let Some(matched) = single(narrowed, "set_code+collector_number") {
+                return Ok(Ok(matched));
+            }
+            candidates = found;
+        }
+
+        if let Some(name) = name {
+            let (sql, binds, matched_by) = match (set_code, number) {
+                (Some(set_code), _) => (
+                    "SELECT product_uuid, name FROM Global_Catalog
+                     WHERE lower(name) = lower(?) AND lower(set_code) = lower(?) AND deleted_at IS NULL"
+                        .to_string(),
+                    vec![name, set_code],
+                    "name+set_code",
+                ),
+                (None, Some(number)) => (
+                    format!(
+                        "SELECT product_uuid, name FROM Global_Catalog
+                         WHERE lower(name) = lower(?) AND {} AND deleted_at IS NULL",
+                        same_number
+                    ),
+                    vec![name, number],
+                    "name+collector_number",
+                ),
+                (None, None) => (
+                    "SELECT product_uuid, name FROM Global_Catalog
+                     WHERE lower(name) = lower(?) AND deleted_at IS NULL"
+                        .to_string(),
+                    vec![name],
+                    "name",
+                ),
+            };
+            let found = self.catalog(&sql, &binds).await?;
+            if let Some(matched) = single(found.clone(), matched_by) {
+                return Ok(Ok(matched));
+            }
+            if found.len() > 1 {
+                return Ok(Err(format!(
+                    "'{}' matches {} catalog products; add a set code or collector number",
+                    name,
+                    found.len()
+                )));
+            }
+        }
+
+        if candidates.len() > 1 {
+            return Ok(Err(format!(
+                "{} {} matches {} catalog products",
+                set_code.unwrap_or_default(),
+                number.unwrap_or_default(),
+                candidates.len()
+            )));
+        }
+        Ok(Err(format!(
+            "No catalog match for {}",
+            [name, set_code, number]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" / ")
+        )))
+    }
+
+    async fn catalog(&mut self, sql: &str, binds: &[&str]) -> Result<Vec<(String, String)>> {
+        let mut query = sqlx::query_as(sql);
+        for bind in binds {
+            query = query.bind(*bind);
+        }
+        query
+            .fetch_all(&mut *self.tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))
+    }
+
+    async fn pile_by_id(
+        &mut self,
+        inventory_uuid: Uuid,
+        product_uuid: Uuid,
+    ) -> Result<std::result::Result<Option<Pile>, String>> {
+        let row = sqlx::query(
+            "SELECT inventory_uuid, product_uuid, quantity_on_hand, cost_basis, specific_price, bin_location
+             FROM Local_Inventory WHERE inventory_uuid = ?",
+        )
+        .bind(inventory_uuid.to_string())
+        .fetch_optional(&mut *self.tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(match row.as_ref().and_then(map_pile) {
+            Some((pile_product, _)) if pile_product != product_uuid => Err(format!(
+                "Inventory {} belongs to a different product",
+                inventory_uuid
+            )),
+            Some((_, pile)) => Ok(Some(pile)),
+            None => Err(format!("Inventory {} not found", inventory_uuid)),
+        })
+    }
+
+    /// The bulk pile this row lands on: same product, condition, variant and location
+    async fn find_pile(&mut self, product_uuid: Uuid, row: &ParsedRow) -> Result<Option<Pile>> {
+        let pile = sqlx::query(
+            "SELECT inventory_uuid, product_uuid, quantity_on_hand, cost_basis, specific_price, bin_location
+             FROM Local_Inventory
+             WHERE product_uuid = ? AND condition = ? AND location_tag = ?
+               AND COALESCE(NULLIF(variant_type, 'Normal'), '') = COALESCE(?, '')
+               AND serialized_details IS NULL AND deleted_at IS NULL
+               AND inventory_uuid NOT IN (SELECT inventory_uuid FROM Consignment_Items WHERE status = 'Active')
+             ORDER BY specific_price IS NULL DESC, inventory_uuid
+             LIMIT 1",
+        )
+        .bind(product_uuid.to_string())
+        .bind(format!("{:?}", row.condition))
+        .bind(&row.location)
+        .bind(&row.variant)
+        .fetch_optional(&mut *self.tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(pile.as_ref().and_then(map_pile).map(|(_, pile)| pile))
+    }
+
+    async fn create_pile(&mut self, product_uuid: Uuid, row: &ParsedRow) -> Result<Uuid> {
+        let inventory_uuid = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO Local_Inventory
+             (inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag,
+              specific_price, cost_basis, bin_location, received_date)
+             VALUES (?, ?, ?, ?, 0, ?, ?, ?, ?, ?)",
+        )
+        .bind(inventory_uuid.to_string())
+        .bind(product_uuid.to_string())
+        .bind(&row.variant)
+        .bind(format!("{:?}", row.condition))
+        .bind(&row.location)
+        .bind(row.price)
+        .bind(row.cost)
+        .bind(&row.bin)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *self.tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create inventory: {}", e))?;
+        Ok(inventory_uuid)
+    }
+
+    /// Apply cost, price and bin from the row; true if anything changed.
+    /// Added units blend into the cost basis; replaced piles take the file's cost.
+    async fn update_details(&mut self, pile: &Pile, row: &ParsedRow) -> Result<bool> {
+        let cost = row.cost.map(|cost| match self.options.mode {
+            ImportMode::Add => crate::services::purchasing::weighted_average_cost(
+                pile.quantity,
+                pile.cost_basis,
+                row.quantity,
+                cost,
+            ),
+            ImportMode::Replace => cost,
+        });
+        let cost = cost.or(pile.cost_basis);
+        let price = row.price.or(pile.specific_price);
+        let bin = row.bin.clone().or(pile.bin_location.clone());
+        if cost == pile.cost_basis && price == pile.specific_price && bin == pile.bin_location {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "UPDATE Local_Inventory SET cost_basis = ?, specific_price = ?, bin_location = ?
+             WHERE inventory_uuid = ?",
+        )
+        .bind(cost)
+        .bind(price)
+        .bind(&bin)
+        .bind(pile.inventory_uuid.to_string())
+        .execute(&mut *self.tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update inventory: {}", e))?;
+        Ok(true)
+    }
+}
+
+struct ParsedRow {
+    inventory_uuid: Option<Uuid>,
+    quantity: i32,
+    condition: Condition,
+    variant: Option<String>,
+    location: String,
+    bin: Option<String>,
+    cost: Option<f64>,
+    price: Option<f64>,
+}
+
+struct Pile {
+    inventory_uuid: Uuid,
+    quantity: i32,
+    cost_basis: Option<f64>,
+    specific_price: Option<f64>,
+    bin_location: Option<String>,
+}
+
+/// A Local_Inventory row as (product_uuid, pile)
+fn map_pile(row: &sqlx::sqlite::SqliteRow) -> Option<(Uuid, Pile)> {
+    use sqlx::Row;
+    let product_uuid = Uuid::parse_str(&row.try_get::<String, _>("product_uuid").ok()?).ok()?;
+    Some((
+        product_uuid,
+        Pile {
+            inventory_uuid: Uuid::parse_str(&row.try_get::<String, _>("inventory_uuid").ok()?)
+                .ok()?,
+            quantity: row.try_get("quantity_on_hand").ok()?,
+            cost_basis: row.try_get("cost_basis").ok()?,
+            specific_price: row.try_get("specific_price").ok()?,
+            bin_location: row.try_get("bin_location").ok()?,
+        },
+    ))
+}
+
+struct ProductMatch {
+    product_uuid: Uuid,
+    name: String,
+    matched_by: &'static str,
+}
+
+fn single(found: Vec<(String, String)>, matched_by: &'static str) -> Option<ProductMatch> {
+    match found.as_slice() {
+        [(product_uuid, name)] => Some(ProductMatch {
+            product_uuid: Uuid::parse_str(product_uuid).ok()?,
+            name: name.clone(),
+            matched_by,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_reader_handles_quotes_and_chunking() {
+        let input = "\u{feff}Name,Count\r\n\"Jace, the Mind Sculptor\",2\r\n\"Say \"\"hi\"\"\nthere\",1\n\nLast,3";
+        let mut reader = CsvReader::new();
+        let mut records = Vec::new();
+        // Feed one character at a time to exercise every split point
+        for c in input.chars() {
+            records.extend(reader.feed(&c.to_string()));
+        }
+        records.extend(reader.finish());
+
+        assert_eq!(
+            records,
+            vec![
+                vec!["Name", "Count"],
+                vec!["Jace, the Mind Sculptor", "2"],
+                vec!["Say \"hi\"\nthere", "1"],
+                vec!["Last", "3"],
+            ]
+        );
+        assert_eq!(
+            csv_field("Jace, the \"Mind\""),
+            "\"Jace, the \"\"Mind\"\"\""
+        );
+    }
+
+    #[test]
+    fn test_normalize_condition() {
+        assert_eq!(
+            normalize_condition("near_mint"),
+            Some((Condition::NM, false))
+        );
+        assert_eq!(
+            normalize_condition("Good (Lightly Played)"),
+            Some((Condition::LP, false))
+        );
+        assert_eq!(
+            normalize_condition("Near Mint Foil"),
+            Some((Condition::NM, true))
+        );
+        assert_eq!(
+            normalize_condition("OpenBox"),
+            Some((Condition::OpenBox, false))
+        );
+        assert_eq!(normalize_condition("pristine"), None);
+    }
+}
@@ -0,0 +1,468 @@
+//! Grading certificate lookups
+//!
+//! Each grading company publishes the grade and population behind a cert
+//! number. Providers look a cert up and return the official record; the
+//! serialized inventory service compares it against the slab we hold and
+//! flags anything that doesn't line up (an unknown cert, a different card,
+//! a different grade, the same cert on two items) as a possible fake.
+//!
+//! PSA has a public API. BGS, CGC and SGC only offer lookups on their
+//! websites, so until a feed is available those are served from a fixture
+//! file of certs that have been checked by hand, which is also what the
+//! tests use.
+
+use crate::errors::Result;
+use crate::services::grading::GradingCompany;
+use crate::services::serialized_inventory::{PopulationData, SubGrades};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use uuid::Uuid;
+
+/// A cert as the grading company has it on record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfficialCert {
+    pub grader: GradingCompany,
+    pub cert_number: String,
+    pub grade: String,
+    pub sub_grades: Option<SubGrades>,
+    /// The card as printed on the label
+    pub card_name: Option<String>,
+    pub set_name: Option<String>,
+    pub card_number: Option<String>,
+    pub year: Option<String>,
+    pub population: Option<PopulationData>,
+}
+
+#[async_trait]
+pub trait CertVerificationProvider: Send + Sync {
+    /// Shown on verification records
+    fn name(&self) -> &str;
+    fn supports(&self, grader: GradingCompany) -> bool;
+    /// `None` when the grader has no such cert
+    async fn lookup(
+        &self,
+        grader: GradingCompany,
+        cert_number: &str,
+    ) -> Result<Option<OfficialCert>>;
+}
+
+/// Certs from a fixed list, keyed by grader and normalized cert number
+pub struct FixtureCertProvider {
+    records: HashMap<(GradingCompany, String), OfficialCert>,
+}
+
+impl FixtureCertProvider {
+    pub fn new(records: Vec<OfficialCert>) -> Self {
+        Self {
+            records: records
+                .into_iter()
+                .map(|r| ((r.grader, normalize_cert_number(&r.cert_number)), r))
+                .collect(),
+        }
+    }
+
+    /// A JSON array of [`OfficialCert`]
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!("Failed to read cert fixtures {}: {}", path.display(), e)
+        })?;
+        let records: Vec<OfficialCert> = serde_json::from_str(&text)
+            .map_err(|e| anyhow::anyhow!("Invalid cert fixtures {}: {}", path.display(), e))?;
+        Ok(Self::new(records))
+    }
+}
+
+#[async_trait]
+impl CertVerificationProvider for FixtureCertProvider {
+    fn name(&self) -> &str {
+        "fixtures"
+    }
+
+    fn supports(&self, _grader: GradingCompany) -> bool {
+        true
+    }
+
+    async fn lookup(
+        &self,
+        grader: GradingCompany,
+        cert_number: &str,
+    ) -> Result<Option<OfficialCert>> {
+        Ok(self
+            .records
+            .get(&(grader, normalize_cert_number(cert_number)))
+            .cloned())
+    }
+}
+
+/// PSA's public cert API
+pub struct PsaCertProvider {
+    client: reqwest::Client,
+    api_token: String,
+    base_url: String,
+}
+
+impl PsaCertProvider {
+    pub fn new(api_token: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_token: api_token.into(),
+            base_url: "https://api.psacard.com/publicapi".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PsaCertResponse {
+    #[serde(rename = "PSACert")]
+    psa_cert: Option<PsaCert>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PsaCert {
+    cert_number: String,
+    card_grade: Option<String>,
+    subject: Option<String>,
+    brand: Option<String>,
+    card_number: Option<String>,
+    year: Option<String>,
+    total_population: Option<i32>,
+    population_higher: Option<i32>,
+}
+
+#[async_trait]
+impl CertVerificationProvider for PsaCertProvider {
+    fn name(&self) -> &str {
+        "psa"
+    }
+
+    fn supports(&self, grader: GradingCompany) -> bool {
+        grader == GradingCompany::Psa
+    }
+
+    async fn lookup(
+        &self,
+        _grader: GradingCompany,
+        cert_number: &str,
+    ) -> Result<Option<OfficialCert>> {
+        let url = format!(
+            "{}/cert/GetByCertNumber/{}",
+            self.base_url,
+            normalize_cert_number(cert_number)
+        );
+        let resp = self
+            .client
+            .get(&url)
+            .bearer_auth(&self.api_token)
+            .send()
+            .await?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!("PSA API Error {}: {}", status, text));
+        }
+
+        let body: PsaCertResponse = resp.json().await?;
+        Ok(body.psa_cert.map(|cert| {
+            let card_grade = cert.card_grade.unwrap_or_default();
+            OfficialCert {
+                grader: GradingCompany::Psa,
+                cert_number: cert.cert_number,
+                // "GEM MT 10" is recorded as "10", like the rest of our slabs
+                grade: grade_value(&card_grade)
+                    .map(|g| g.to_string())
+                    .unwrap_or(card_grade),
+                sub_grades: None,
+                card_name: cert.subject,
+                set_name: cert.brand,
+                card_number: cert.card_number,
+                year: cert.year,
+                population: match (cert.total_population, cert.population_higher) {
+                    (Some(same_grade), higher) => Some(PopulationData {
+                        same_grade,
+                        higher_grade: higher.unwrap_or(0),
+                        last_updated: Some(Utc::now().to_rfc3339()),
+                    }),
+                    _ => None,
+                },
+            }
+        }))
+    }
+}
+
+/// `CERT_FIXTURES` points at a fixture file covering any grader; PSA certs
+/// go to the PSA API when `PSA_API_TOKEN` is set. Lookups for a grader
+/// with no provider fail rather than passing.
+pub fn get_cert_providers() -> Vec<Box<dyn CertVerificationProvider>> {
+    let mut providers: Vec<Box<dyn CertVerificationProvider>> = Vec::new();
+    if let Ok(token) = std::env::var("PSA_API_TOKEN") {
+        providers.push(Box::new(PsaCertProvider::new(token)));
+    }
+    if let Ok(path) = std::env::var("CERT_FIXTURES") {
+        match FixtureCertProvider::from_file(Path::new(&path)) {
+            Ok(p) => providers.push(Box::new(p)),
+            Err(e) => tracing::warn!("Cert fixtures not loaded: {}", e),
+        }
+    }
+    providers
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    Verified,
+    /// The cert exists but doesn't match the item
+    Mismatch,
+    NotFound,
+}
+
+impl VerificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationStatus::Verified => "verified",
+            VerificationStatus::Mismatch => "mismatch",
+            VerificationStatus::NotFound => "not_found",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "verified" => Some(VerificationStatus::Verified),
+            "mismatch" => Some(VerificationStatus::Mismatch),
+            "not_found" => Some(VerificationStatus::NotFound),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagKind {
+    CertNotFound,
+    GradeMismatch,
+    CardNameMismatch,
+    CardNumberMismatch,
+    /// Another item in stock carries the same cert
+    DuplicateCert,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationFlag {
+    pub kind: FlagKind,
+    pub message: String,
+}
+
+/// One check of an item's cert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertVerification {
+    pub verification_uuid: Uuid,
+    pub inventory_uuid: Uuid,
+    pub grader: GradingCompany,
+    pub cert_number: String,
+    pub provider: String,
+    pub status: VerificationStatus,
+    pub flags: Vec<VerificationFlag>,
+    pub official: Option<OfficialCert>,
+    pub verified_by: Option<Uuid>,
+    pub verified_at: DateTime<Utc>,
+}
+
+/// Uppercase letters and digits only, so "1234-5678" and "12345678" match
+pub fn normalize_cert_number(cert_number: &str) -> String {
+    cert_number
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Numeric grade from a label such as "10", "9.5" or "GEM MT 10"
+pub fn grade_value(grade: &str) -> Option<f64> {
+    grade
+        .split_whitespace()
+        .rev()
+        .find_map(|token| token.parse::<f64>().ok())
+}
+
+fn grades_match(recorded: &str, official: &str) -> bool {
+    match (grade_value(recorded), grade_value(official)) {
+        (Some(a), Some(b)) => (a - b).abs() < f64::EPSILON,
+        _ => recorded.trim().eq_ignore_ascii_case(official.trim()),
+    }
+}
+
+/// "#004/102" and "4" are the same card number
+fn normalize_card_number(number: &str) -> String {
+    let number = number.split('/').next().unwrap_or_default();
+    let number: String = number
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let trimmed = number.trim_start_matches('0');
+    if trimmed.is_empty() && !number.is_empty() {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Words on labels that say nothing about which card it is
+const LABEL_NOISE: &[&str] = &[
+    "the",
+    "and",
+    "holo",
+    "foil",
+    "reverse",
+    "card",
+    "promo",
+    "edition",
+    "1st",
+    "first",
+    "rare",
+    "shadowless",
+    "unlimited",
+];
+
+fn name_tokens(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .map(|t| t.to_lowercase())
+        .filter(|t| t.len() >= 3 && !LABEL_NOISE.contains(&t.as_str()))
+        .collect()
+}
+
+/// Label names are abbreviated and inconsistently punctuated, so a single
+/// shared significant word counts as the same card
+fn names_match(product_name: &str, label_name: &str) -> bool {
+    let label = name_tokens(label_name);
+    if label.is_empty() {
+        return true;
+    }
+    let product = name_tokens(product_name);
+    label.iter().any(|t| product.contains(t))
+}
+
+/// Differences between the official record and the item we hold
+pub fn compare_cert(
+    official: &OfficialCert,
+    product_name: &str,
+    collector_number: Option<&str>,
+    recorded_grade: Option<&str>,
+) -> Vec<VerificationFlag> {
+    let mut flags = Vec::new();
+
+    if let Some(label_name) = official.card_name.as_deref() {
+        if !names_match(product_name, label_name) {
+            flags.push(VerificationFlag {
+                kind: FlagKind::CardNameMismatch,
+                message: format!(
+                    "{} cert {} is for '{}', not '{}'",
+                    official.grader, official.cert_number, label_name, product_name
+                ),
+            });
+        }
+    }
+
+    if let (Some(ours), Some(theirs)) = (collector_number, official.card_number.as_deref()) {
+        let (a, b) = (normalize_card_number(ours), normalize_card_number(theirs));
+        if !a.is_empty() && !b.is_empty() && a != b {
+            flags.push(VerificationFlag {
+                kind: FlagKind::CardNumberMismatch,
+                message: format!(
+                    "{} cert {} is card #{}, the product is #{}",
+                    official.grader, official.cert_number, theirs, ours
+                ),
+            });
+        }
+    }
+
+    if let Some(recorded) = recorded_grade {
+        if !grades_match(recorded, &official.grade) {
+            flags.push(VerificationFlag {
+                kind: FlagKind::GradeMismatch,
+                message: format!(
+                    "{} cert {} is graded {}, the item says {}",
+                    official.grader, official.cert_number, official.grade, recorded
+                ),
+            });
+        }
+    }
+
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn charizard() -> OfficialCert {
+        OfficialCert {
+            grader: GradingCompany::Psa,
+            cert_number: "12345678".to_string(),
+            grade: "10".to_string(),
+            sub_grades: None,
+            card_name: Some("CHARIZARD-HOLO".to_string()),
+            set_name: Some("POKEMON GAME".to_string()),
+            card_number: Some("4".to_string()),
+            year: Some("1999".to_string()),
+            population: None,
+        }
+    }
+
+    #[test]
+    fn test_grade_and_number_normalization() {
+        assert_eq!(grade_value("GEM MT 10"), Some(10.0));
+        assert_eq!(grade_value("9.5"), Some(9.5));
+        assert_eq!(grade_value("Authentic"), None);
+        assert!(grades_match("10", "GEM MT 10"));
+        assert!(!grades_match("9", "10"));
+        assert!(grades_match("authentic", "Authentic"));
+
+        assert_eq!(normalize_cert_number(" 1234-5678 "), "12345678");
+        assert_eq!(normalize_card_number("#004/102"), "4");
+        assert_eq!(normalize_card_number("000"), "0");
+    }
+
+    #[test]
+    fn test_compare_cert() {
+        let cert = charizard();
+        assert!(compare_cert(&cert, "Charizard", Some("4/102"), Some("10")).is_empty());
+
+        let flags = compare_cert(&cert, "Blastoise Holo", Some("2/102"), Some("9"));
+        let kinds: Vec<FlagKind> = flags.iter().map(|f| f.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                FlagKind::CardNameMismatch,
+                FlagKind::CardNumberMismatch,
+                FlagKind::GradeMismatch
+            ]
+        );
+
+        // Nothing on the product to compare against
+        assert!(compare_cert(&cert, "Charizard", None, None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fixture_provider_matches_normalized_cert() {
+        let provider = FixtureCertProvider::new(vec![charizard()]);
+        assert!(provider
+            .lookup(GradingCompany::Psa, "1234 5678")
+            .await
+            .unwrap()
+            .is_some());
+        assert!(provider
+            .lookup(GradingCompany::Bgs, "12345678")
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,1108 @@
+//! Consignment management
+//!
+//! Items consigned by customers are received into `Local_Inventory` like any
+//! other stock, but stay owned by the consignor. When one sells, the store
+//! keeps its commission and the consignor's share accrues to
+//! `Consignors.balance_owed` until it is paid out in cash or store credit.
+//! Voids and returns to stock write an offsetting sale row and debit the
+//! balance again.
+//! Unsold items expire after the agreed period and are returned.
+
+use crate::core::money::round_cents;
+use crate::core::Condition;
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
+use crate::database::Database;
+use crate::errors::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Location tag for consigned stock
+pub const CONSIGNMENT_LOCATION: &str = "Consignment";
+
+/// Lifecycle of a consigned item (values match the `Consignment_Items` CHECK)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConsignmentStatus {
+    Active,
+    Sold,
+    Returned,
+    Expired,
+}
+
+impl std::fmt::Display for ConsignmentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsignmentStatus::Active => write!(f, "Active"),
+            ConsignmentStatus::Sold => write!(f, "Sold"),
+            ConsignmentStatus::Returned => write!(f, "Returned"),
+            ConsignmentStatus::Expired => write!(f, "Expired"),
+        }
+    }
+}
+
+impl ConsignmentStatus {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Active" => Some(ConsignmentStatus::Active),
+            "Sold" => Some(ConsignmentStatus::Sold),
+            "Returned" => Some(ConsignmentStatus::Returned),
+            "Expired" => Some(ConsignmentStatus::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// How a consignor is paid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutMethod {
+    Cash,
+    StoreCredit,
+}
+
+impl std::fmt::Display for PayoutMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PayoutMethod::Cash => write!(f, "cash"),
+            PayoutMethod::StoreCredit => write!(f, "store_credit"),
+        }
+    }
+}
+
+impl PayoutMethod {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cash" => Some(PayoutMethod::Cash),
+            "store_credit" => Some(PayoutMethod::StoreCredit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Consignor {
+    pub consignor_uuid: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    /// Store's share of each sale (0.4 = 40%)
+    pub commission_rate: f64,
+    pub balance_owed: f64,
+    /// Customer account that receives store credit payouts
+    pub customer_uuid: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateConsignorRequest {
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub commission_rate: Option<f64>,
+    pub customer_uuid: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsignmentItem {
+    pub consignment_uuid: Uuid,
+    pub consignor_uuid: Uuid,
+    pub inventory_uuid: Uuid,
+    pub product_uuid: Option<Uuid>,
+    pub product_name: Option<String>,
+    pub asking_price: f64,
+    pub minimum_price: Option<f64>,
+    pub commission_rate: f64,
+    pub quantity: i32,
+    pub quantity_sold: i32,
+    pub status: ConsignmentStatus,
+    pub received_date: DateTime<Utc>,
+    pub sold_date: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub returned_date: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConsignmentIntakeRequest {
+    pub consignor_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub condition: Condition,
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+    pub asking_price: f64,
+    pub minimum_price: Option<f64>,
+    /// Overrides the consignor's default commission for this item
+    pub commission_rate: Option<f64>,
+    /// Days before unsold items are due back to the consignor
+    pub consignment_days: Option<i64>,
+    pub notes: Option<String>,
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+/// Commission split recorded when a consigned item sells
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsignmentSale {
+    pub sale_uuid: Uuid,
+    pub consignment_uuid: Uuid,
+    pub consignor_uuid: Uuid,
+    pub transaction_uuid: Uuid,
+    pub quantity: i32,
+    pub unit_price: f64,
+    pub gross_amount: f64,
+    pub commission_rate: f64,
+    pub commission_amount: f64,
+    pub consignor_share: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsignorPayout {
+    pub payout_uuid: Uuid,
+    pub consignor_uuid: Uuid,
+    pub amount: f64,
+    pub method: PayoutMethod,
+    pub customer_uuid: Option<Uuid>,
+    pub paid_by: Option<Uuid>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Activity and balance for one consignor over a period
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsignorStatement {
+    pub consignor: Consignor,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub opening_balance: f64,
+    pub sales: Vec<ConsignmentSale>,
+    pub payouts: Vec<ConsignorPayout>,
+    pub total_sales: f64,
+    pub total_commission: f64,
+    pub total_earned: f64,
+    pub total_paid: f64,
+    pub closing_balance: f64,
+    /// Items still on the floor or awaiting return
+    pub open_items: Vec<ConsignmentItem>,
+}
+
+/// Split a sale into (commission, consignor share)
+pub fn split_commission(gross: f64, commission_rate: f64) -> (f64, f64) {
+    let commission = round_cents(gross * commission_rate);
+    (commission, round_cents(gross - commission))
+}
+
+fn validate_rate(rate: f64) -> Result<()> {
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(anyhow::anyhow!(
+            "Commission rate must be between 0 and 1 (got {})",
+            rate
+        ));
+    }
+    Ok(())
+}
+
+pub struct ConsignmentService {
+    db: Arc<Database>,
+}
+
+impl ConsignmentService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    // ---- Consignors ----
+
+    pub async fn create_consignor(&self, request: CreateConsignorRequest) -> Result<Consignor> {
+        if request.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Consignor name is required"));
+        }
+        let commission_rate = request.commission_rate.unwrap_or(0.4);
+        validate_rate(commission_rate)?;
+
+        let consignor = Consignor {
+            consignor_uuid: Uuid::new_v4(),
+            name: request.name,
+            email: request.email,
+            phone: request.phone,
+            commission_rate,
+            balance_owed: 0.0,
+            customer_uuid: request.customer_uuid,
+            created_at: Utc::now(),
+        };
+
+        sqlx::query(
+            "INSERT INTO Consignors (consignor_uuid, name, email, phone, commission_rate, balance_owed, customer_uuid, created_at)
+             VALUES (?, ?, ?, ?, ?, 0, ?, ?)",
+        )
+        .bind(consignor.consignor_uuid.to_string())
+        .bind(&consignor.name)
+        .bind(&consignor.email)
+        .bind(&consignor.phone)
+        .bind(consignor.commission_rate)
+        .bind(consignor.customer_uuid.map(|u| u.to_string()))
+        .bind(consignor.created_at.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(consignor)
+    }
+
+    pub async fn get_consignors(&self) -> Result<Vec<Consignor>> {
+        let rows = sqlx::query("SELECT * FROM Consignors ORDER BY name")
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().filter_map(map_consignor).collect())
+    }
+
+    pub async fn get_consignor(&self, consignor_uuid: Uuid) -> Result<Option<Consignor>> {
+        let row = sqlx::query("SELECT * FROM Consignors WHERE consignor_uuid = ?")
+            .bind(consignor_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(row.as_ref().and_then(map_consignor))
+    }
+
+    // ---- Items ----
+
+    /// Receive consigned stock onto the floor
+    pub async fn intake_item(&self, request: ConsignmentIntakeRequest) -> Result<ConsignmentItem> {
+        if request.quantity <= 0 {
+            return Err(anyhow::anyhow!("Quantity must be at least 1"));
+        }
+        if request.asking_price <= 0.0 {
+            return Err(anyhow::anyhow!("Asking price must be greater than zero"));
+        }
+        if let Some(minimum) = request.minimum_price {
+            if minimum < 0.0 || minimum > request.asking_price {
+                return Err(anyhow::anyhow!(
+                    "Minimum price must be between 0 and the asking price"
+                ));
+            }
+        }
+
+        let consignor = self
+            .get_consignor(request.consignor_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Consignor {} not found", request.consignor_uuid))?;
+        let commission_rate = request.commission_rate.unwrap_or(consignor.commission_rate);
+        validate_rate(commission_rate)?;
+
+        let product_name: Option<String> =
+            sqlx::query_scalar("SELECT name FROM Global_Catalog WHERE product_uuid = ?")
+                .bind(request.product_uuid.to_string())
+                .fetch_optional(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if product_name.is_none() {
+            return Err(anyhow::anyhow!(
+                "Product {} not found",
+                request.product_uuid
+            ));
+        }
+
+        let now = Utc::now();
+        let item = ConsignmentItem {
+            consignment_uuid: Uuid::new_v4(),
+            consignor_uuid: consignor.consignor_uuid,
+            inventory_uuid: Uuid::new_v4(),
+            product_uuid: Some(request.product_uuid),
+            product_name,
+            asking_price: request.asking_price,
+            minimum_price: request.minimum_price,
+            commission_rate,
+            quantity: request.quantity,
+            quantity_sold: 0,
+            status: ConsignmentStatus::Active,
+            received_date: now,
+            sold_date: None,
+            expires_at: request.consignment_days.map(|d| now + Duration::days(d)),
+            returned_date: None,
+            notes: request.notes,
+        };
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        // specific_price keeps consigned rows out of bulk piles on buy-in
+        sqlx::query(
+            "INSERT INTO Local_Inventory
+             (inventory_uuid, product_uuid, condition, quantity_on_hand, location_tag, specific_price, received_date)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(item.inventory_uuid.to_string())
+        .bind(request.product_uuid.to_string())
+        .bind(format!("{:?}", request.condition))
+        .bind(item.quantity)
+        .bind(CONSIGNMENT_LOCATION)
+        .bind(item.asking_price)
+        .bind(now.to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create inventory: {}", e))?;
+
+        movements::record_movement_with_tx(
+            &mut tx,
+            item.inventory_uuid,
+            request.product_uuid,
+            0,
+            item.quantity,
+            &MovementSource::new(
+                MovementType::Receive,
+                Some(item.consignment_uuid),
+                None,
+                &self.db.node_id,
+            ),
+        )
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO Consignment_Items
+             (consignment_uuid, consignor_uuid, inventory_uuid, asking_price, minimum_price, commission_rate,
+              status, received_date, quantity, quantity_sold, expires_at, notes)
+             VALUES (?, ?, ?, ?, ?, ?, 'Active', ?, ?, 0, ?, ?)",
+        )
+        .bind(item.consignment_uuid.to_string())
+        .bind(item.consignor_uuid.to_string())
+        .bind(item.inventory_uuid.to_string())
+        .bind(item.asking_price)
+        .bind(item.minimum_price)
+        .bind(item.commission_rate)
+        .bind(now.to_rfc3339())
+        .bind(item.quantity)
+        .bind(item.expires_at.map(|d| d.to_rfc3339()))
+        .bind(&item.notes)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to record consignment: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!(
+            "Consignment {} received from {}: {} x ${:.2}",
+            item.consignment_uuid,
+            consignor.name,
+            item.quantity,
+            item.asking_price
+        );
+        Ok(item)
+    }
+
+    pub async fn get_items(
+        &self,
+        consignor_uuid: Uuid,
+        status: Option<ConsignmentStatus>,
+    ) -> Result<Vec<ConsignmentItem>> {
+        let rows = sqlx::query(
+            "SELECT ci.*, li.product_uuid, gc.name as product_name
+             FROM Consignment_Items ci
+             LEFT JOIN Local_Inventory li ON li.inventory_uuid = ci.inventory_uuid
+             LEFT JOIN Global_Catalog gc ON gc.product_uuid = li.product_uuid
+             WHERE ci.consignor_uuid = ? AND (? IS NULL OR ci.status = ?)
+             ORDER BY ci.received_date",
+        )
+        .bind(consignor_uuid.to_string())
+        .bind(status.map(|s| s.to_string()))
+        .bind(status.map(|s| s.to_string()))
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().filter_map(map_item).collect())
+    }
+
+    pub async fn get_item(&self, consignment_uuid: Uuid) -> Result<Option<ConsignmentItem>> {
+        let row = sqlx::query(
+            "SELECT ci.*, li.product_uuid, gc.name as product_name
+             FROM Consignment_Items ci
+             LEFT JOIN Local_Inventory li ON li.inventory_uuid = ci.inventory_uuid
+             LEFT JOIN Global_Catalog gc ON gc.product_uuid = li.product_uuid
+             WHERE ci.consignment_uuid = ?",
+        )
+        .bind(consignment_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(row.as_ref().and_then(map_item))
+    }
+
+    /// Pull active items past their consignment period off the floor.
+    /// They stay `Expired` until the consignor collects them.
+    pub async fn expire_overdue_items(&self) -> Result<Vec<Uuid>> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let expired: Vec<(String, String)> = sqlx::query_as(
+            "SELECT consignment_uuid, inventory_uuid FROM Consignment_Items
+             WHERE status = 'Active' AND expires_at IS NOT NULL AND expires_at <= ?",
+        )
+        .bind(&now)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        for (consignment_uuid, inventory_uuid) in &expired {
+            let source = MovementSource {
+                notes: Some("Consignment period expired".to_string()),
+                ..MovementSource::new(
+                    MovementType::ConsignmentReturn,
+                    Uuid::parse_str(consignment_uuid).ok(),
+                    None,
+                    &self.db.node_id,
+                )
+            };
+            sqlx::query(
+                "UPDATE Consignment_Items SET status = 'Expired' WHERE consignment_uuid = ?",
+            )
+            .bind(consignment_uuid)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            movements::set_quantity_with_tx(&mut tx, Uuid::parse_str(inventory_uuid)?, 0, &source)
+                .await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        if !expired.is_empty() {
+            tracing::info!("Expired {} consignment items", expired.len());
+        }
+        Ok(expired
+            .iter()
+            .filter_map(|(uuid, _)| Uuid::parse_str(uuid).ok())
+            .collect())
+    }
+
+    /// Hand unsold stock back to the consignor (active or expired items)
+    pub async fn return_to_consignor(&self, consignment_uuid: Uuid) -> Result<ConsignmentItem> {
+        let item = self
+            .get_item(consignment_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Consignment {} not found", consignment_uuid))?;
+
+        if !matches!(
+            item.status,
+            ConsignmentStatus::Active | ConsignmentStatus::Expired
+        ) {
+            return Err(anyhow::anyhow!(
+                "Consignment {} is {} and cannot be returned",
+                consignment_uuid,
+                item.status
+            ));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        sqlx::query(
+            "UPDATE Consignment_Items SET status = 'Returned', returned_date = ? WHERE consignment_uuid = ?",
+        )
+        .bind(&now)
+        .bind(consignment_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        movements::set_quantity_with_tx(
+            &mut tx,
+            item.inventory_uuid,
+            0,
+            &MovementSource::new(
+                MovementType::ConsignmentReturn,
+                Some(consignment_uuid),
+                None,
+                &self.db.node_id,
+            ),
+        )
+        .await?;
+        sqlx::query("UPDATE Local_Inventory SET deleted_at = ? WHERE inventory_uuid = ?")
+            .bind(&now)
+            .bind(item.inventory_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.get_item(consignment_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Consignment {} not found", consignment_uuid))
+    }
+
+    // ---- Payouts ----
+
+    /// Pay a consignor from their balance. Defaults to the full balance.
+    pub async fn pay_consignor(
+        &self,
+        consignor_uuid: Uuid,
+        amount: Option<f64>,
+        method: PayoutMethod,
+        paid_by: Option<Uuid>,
+        notes: Option<String>,
+    ) -> Result<ConsignorPayout> {
+        let consignor = self
+            .get_consignor(consignor_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Consignor {} not found", consignor_uuid))?;
+
+        let amount = round_cents(amount.unwrap_or(consignor.balance_owed));
+        if amount <= 0.0 {
+            return Err(anyhow::anyhow!("Nothing to pay out"));
+        }
+        if amount > consignor.balance_owed + 0.001 {
+            return Err(anyhow::anyhow!(
+                "Payout ${:.2} exceeds balance owed ${:.2}",
+                amount,
+                consignor.balance_owed
+            ));
+        }
+
+        let customer_uuid = match method {
+            PayoutMethod::StoreCredit => Some(consignor.customer_uuid.ok_or_else(|| {
+                anyhow::anyhow!("Consignor has no customer account for store credit")
+            })?),
+            PayoutMethod::Cash => None,
+        };
+
+        let payout = ConsignorPayout {
+            payout_uuid: Uuid::new_v4(),
+            consignor_uuid,
+            amount,
+            method,
+            customer_uuid,
+            paid_by,
+            notes,
+            created_at: Utc::now(),
+        };
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        sqlx::query(
+            "INSERT INTO Consignor_Payouts (payout_uuid, consignor_uuid, amount, method, customer_uuid, paid_by, notes, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(payout.payout_uuid.to_string())
+        .bind(consignor_uuid.to_string())
+        .bind(payout.amount)
+        .bind(payout.method.to_string())
+        .bind(payout.customer_uuid.map(|u| u.to_string()))
+        .bind(payout.paid_by.map(|u| u.to_string()))
+        .bind(&payout.notes)
+        .bind(payout.created_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to record payout: {}", e))?;
+
+        sqlx::query(
+            "UPDATE Consignors SET balance_owed = ROUND(balance_owed - ?, 2) WHERE consignor_uuid = ?",
+        )
+        .bind(payout.amount)
+        .bind(consignor_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update balance: {}", e))?;
+
+        if let Some(customer_uuid) = payout.customer_uuid {
+            let updated = sqlx::query(
+                "UPDATE Customers SET store_credit = store_credit + ? WHERE customer_uuid = ?",
+            )
+            .bind(payout.amount)
+            .bind(customer_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to add store credit: {}", e))?;
+            if updated.rows_affected() == 0 {
+                return Err(anyhow::anyhow!("Customer {} not found", customer_uuid));
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!(
+            "Paid consignor {} ${:.2} by {}",
+            consignor.name,
+            payout.amount,
+            payout.method
+        );
+        Ok(payout)
+    }
+
+    // ---- Statements ----
+
+    pub async fn get_statement(
+        &self,
+        consignor_uuid: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<ConsignorStatement> {
+        let consignor = self
+            .get_consignor(consignor_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Consignor {} not found", consignor_uuid))?;
+        let start_str = start.to_rfc3339();
+        let end_str = end.to_rfc3339();
+
+        let earned_before: f64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(consignor_share), 0.0) FROM Consignment_Sales WHERE consignor_uuid = ? AND created_at < ?",
+        )
+        .bind(consignor_uuid.to_string())
+        .bind(&start_str)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let paid_before: f64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(amount), 0.0) FROM Consignor_Payouts WHERE consignor_uuid = ? AND created_at < ?",
+        )
+        .bind(consignor_uuid.to_string())
+        .bind(&start_str)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let sale_rows = sqlx::query(
+            "SELECT * FROM Consignment_Sales WHERE consignor_uuid = ? AND created_at >= ? AND created_at < ?
+             ORDER BY created_at",
+        )
+        .bind(consignor_uuid.to_string())
+        .bind(&start_str)
+        .bind(&end_str)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let sales: Vec<ConsignmentSale> = sale_rows.iter().filter_map(map_sale).collect();
+
+        let payout_rows = sqlx::query(
+            "SELECT * FROM Consignor_Payouts WHERE consignor_uuid = ? AND created_at >= ? AND created_at < ?
+             ORDER BY created_at",
+        )
+        .bind(consignor_uuid.to_string())
+        .bind(&start_str)
+        .bind(&end_str)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let payouts: Vec<ConsignorPayout> = payout_rows.iter().filter_map(map_payout).collect();
+
+        let mut open_items = self
+            .get_items(consignor_uuid, Some(ConsignmentStatus::Active))
+            .await?;
+        open_items.extend(
+            self.get_items(consignor_uuid, Some(ConsignmentStatus::Expired))
+                .await?,
+        );
+
+        let opening_balance = round_cents(earned_before - paid_before);
+        let total_sales = round_cents(sales.iter().map(|s| s.gross_amount).sum());
+        let total_commission = round_cents(sales.iter().map(|s| s.commission_amount).sum());
+        let total_earned = round_cents(sales.iter().map(|s| s.consignor_share).sum());
+        let total_paid = round_cents(payouts.iter().map(|p| p.amount).sum());
+
+        Ok(ConsignorStatement {
+            consignor,
+            period_start: start,
+            period_end: end,
+            opening_balance,
+            sales,
+            payouts,
+            total_sales,
+            total_commission,
+            total_earned,
+            total_paid,
+            closing_balance: round_cents(opening_balance + total_earned - total_paid),
+            open_items,
+        })
+    }
+}
+
+/// Record the commission split for stock sold from `inventory_uuid`, if it
+/// is consigned. Runs inside the sale's database transaction so the accrual
+/// commits or rolls back with the sale.
+pub async fn accrue_sale_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+    inventory_uuid: &str,
+    quantity: i32,
+    unit_price: f64,
+) -> Result<Option<ConsignmentSale>> {
+    let row = sqlx::query(
+        "SELECT consignment_uuid, consignor_uuid, commission_rate, minimum_price, quantity, quantity_sold
+         FROM Consignment_Items WHERE inventory_uuid = ? AND status = 'Active'",
+    )
+    .bind(inventory_uuid)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let consignment_uuid: String = sqlx::Row::try_get(&row, "consignment_uuid")
+        .map_err(|e| anyhow::anyhow!("Missing consignment_uuid: {}", e))?;
+    let consignor_uuid: String = sqlx::Row::try_get(&row, "consignor_uuid")
+        .map_err(|e| anyhow::anyhow!("Missing consignor_uuid: {}", e))?;
+    let commission_rate: f64 = sqlx::Row::try_get(&row, "commission_rate").unwrap_or(0.4);
+    let minimum_price: Option<f64> = sqlx::Row::try_get(&row, "minimum_price").ok().flatten();
+    let consigned: i32 = sqlx::Row::try_get(&row, "quantity").unwrap_or(1);
+    let sold: i32 = sqlx::Row::try_get(&row, "quantity_sold").unwrap_or(0);
+
+    if let Some(minimum) = minimum_price {
+        if unit_price < minimum {
+            tracing::warn!(
+                "Consignment {} sold at ${:.2}, below the consignor's minimum ${:.2}",
+                consignment_uuid,
+                unit_price,
+                minimum
+            );
+        }
+    }
+
+    let gross = round_cents(unit_price * quantity as f64);
+    let (commission, share) = split_commission(gross, commission_rate);
+    let now = Utc::now();
+    let sale = ConsignmentSale {
+        sale_uuid: Uuid::new_v4(),
+        consignment_uuid: Uuid::parse_str(&consignment_uuid)?,
+        consignor_uuid: Uuid::parse_str(&consignor_uuid)?,
+        transaction_uuid,
+        quantity,
+        unit_price,
+        gross_amount: gross,
+        commission_rate,
+        commission_amount: commission,
+        consignor_share: share,
+        created_at: now,
+    };
+
+    sqlx::query(
+        "INSERT INTO Consignment_Sales
+         (sale_uuid, consignment_uuid, consignor_uuid, transaction_uuid, quantity, unit_price,
+          gross_amount, commission_rate, commission_amount, consignor_share, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(sale.sale_uuid.to_string())
+    .bind(&consignment_uuid)
+    .bind(&consignor_uuid)
+    .bind(transaction_uuid.to_string())
+    .bind(quantity)
+    .bind(unit_price)
+    .bind(gross)
+    .bind(commission_rate)
+    .bind(commission)
+    .bind(share)
+    .bind(now.to_rfc3339())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record consignment sale: {}", e))?;
+
+    let status = if sold + quantity >= consigned {
+        ConsignmentStatus::Sold
+    } else {
+        ConsignmentStatus::Active
+    };
+    sqlx::query(
+        "UPDATE Consignment_Items SET quantity_sold = quantity_sold + ?, sold_date = ?, status = ?
+         WHERE consignment_uuid = ?",
+    )
+    .bind(quantity)
+    .bind(now.to_rfc3339())
+    .bind(status.to_string())
+    .bind(&consignment_uuid)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to update consignment: {}", e))?;
+
+    sqlx::query(
+        "UPDATE Consignors SET balance_owed = ROUND(balance_owed + ?, 2) WHERE consignor_uuid = ?",
+    )
+    .bind(share)
+    .bind(&consignor_uuid)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to accrue consignor balance: {}", e))?;
+
+    Ok(Some(sale))
+}
+
+/// Undo the accrual for up to `quantity` units of a sale taken from
+/// `inventory_uuid`, e.g. when a line is voided or returned to stock. Runs
+/// inside the caller's database transaction.
+pub async fn reverse_sale_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+    inventory_uuid: &str,
+    quantity: i32,
+) -> Result<Vec<ConsignmentSale>> {
+    reverse_accruals_with_tx(tx, transaction_uuid, Some(inventory_uuid), Some(quantity)).await
+}
+
+/// Undo every accrual still standing for a voided sale. Runs inside the
+/// void's database transaction.
+pub async fn reverse_transaction_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+) -> Result<Vec<ConsignmentSale>> {
+    reverse_accruals_with_tx(tx, transaction_uuid, None, None).await
+}
+
+/// Write offsetting `Consignment_Sales` rows, put the units back on their
+/// consignments (a sold-out item becomes active again) and debit the
+/// consignors' balances
+async fn reverse_accruals_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+    inventory_uuid: Option<&str>,
+    quantity: Option<i32>,
+) -> Result<Vec<ConsignmentSale>> {
+    // Net of earlier reversals, per consignment
+    let rows = sqlx::query(
+        "SELECT cs.consignment_uuid, cs.consignor_uuid, MAX(cs.commission_rate) as commission_rate,
+                SUM(cs.quantity) as quantity, SUM(cs.gross_amount) as gross_amount,
+                SUM(cs.commission_amount) as commission_amount, SUM(cs.consignor_share) as consignor_share
+         FROM Consignment_Sales cs
+         JOIN Consignment_Items ci ON ci.consignment_uuid = cs.consignment_uuid
+         WHERE cs.transaction_uuid = ? AND (? IS NULL OR ci.inventory_uuid = ?)
+         GROUP BY cs.consignment_uuid, cs.consignor_uuid
+         HAVING SUM(cs.quantity) > 0",
+    )
+    .bind(transaction_uuid.to_string())
+    .bind(inventory_uuid)
+    .bind(inventory_uuid)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let mut remaining = quantity.unwrap_or(i32::MAX);
+    let mut reversals = Vec::new();
+    for row in rows {
+        if remaining <= 0 {
+            break;
+        }
+        let consignment_uuid: String = sqlx::Row::try_get(&row, "consignment_uuid")
+            .map_err(|e| anyhow::anyhow!("Missing consignment_uuid: {}", e))?;
+        let consignor_uuid: String = sqlx::Row::try_get(&row, "consignor_uuid")
+            .map_err(|e| anyhow::anyhow!("Missing consignor_uuid: {}", e))?;
+        let accrued: i32 = sqlx::Row::try_get(&row, "quantity").unwrap_or(0);
+        let gross: f64 = sqlx::Row::try_get(&row, "gross_amount").unwrap_or(0.0);
+        let commission: f64 = sqlx::Row::try_get(&row, "commission_amount").unwrap_or(0.0);
+        let share: f64 = sqlx::Row::try_get(&row, "consignor_share").unwrap_or(0.0);
+
+        let reversed = accrued.min(remaining);
+        remaining -= reversed;
+        // Whole reversals take the exact amounts so nothing is left behind by rounding
+        let portion = |amount: f64| {
+            if reversed == accrued {
+                round_cents(amount)
+            } else {
+                round_cents(amount * reversed as f64 / accrued as f64)
+            }
+        };
+        let now = Utc::now();
+        let reversal = ConsignmentSale {
+            sale_uuid: Uuid::new_v4(),
+            consignment_uuid: Uuid::parse_str(&consignment_uuid)?,
+            consignor_uuid: Uuid::parse_str(&consignor_uuid)?,
+            transaction_uuid,
+            quantity: -reversed,
+            unit_price: round_cents(gross / accrued as f64),
+            gross_amount: -portion(gross),
+            commission_rate: sqlx::Row::try_get(&row, "commission_rate").unwrap_or(0.0),
+            commission_amount: -portion(commission),
+            consignor_share: -portion(share),
+            created_at: now,
+        };
+
+        sqlx::query(
+            "INSERT INTO Consignment_Sales
+             (sale_uuid, consignment_uuid, consignor_uuid, transaction_uuid, quantity, unit_price,
+              gross_amount, commission_rate, commission_amount, consignor_share, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(reversal.sale_uuid.to_string())
+        .bind(&consignment_uuid)
+        .bind(&consignor_uuid)
+        .bind(transaction_uuid.to_string())
+        .bind(reversal.quantity)
+        .bind(reversal.unit_price)
+        .bind(reversal.gross_amount)
+        .bind(reversal.commission_rate)
+        .bind(reversal.commission_amount)
+        .bind(reversal.consignor_share)
+        .bind(now.to_rfc3339())
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to reverse consignment sale: {}", e))?;
+
+        sqlx::query(
+            "UPDATE Consignment_Items
+             SET quantity_sold = MAX(quantity_sold - ?, 0),
+                 status = CASE WHEN status = ? THEN ? ELSE status END,
+                 sold_date = CASE WHEN quantity_sold - ? > 0 THEN sold_date END
+             WHERE consignment_uuid = ?",
+        )
+        .bind(reversed)
+        .bind(ConsignmentStatus::Sold.to_string())
+        .bind(ConsignmentStatus::Active.to_string())
+        .bind(reversed)
+        .bind(&consignment_uuid)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update consignment: {}", e))?;
+
+        sqlx::query(
+            "UPDATE Consignors SET balance_owed = ROUND(balance_owed + ?, 2) WHERE consignor_uuid = ?",
+        )
+        .bind(reversal.consignor_share)
+        .bind(&consignor_uuid)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to debit consignor balance: {}", e))?;
+
+        reversals.push(reversal);
+    }
+
+    Ok(reversals)
+}
+
+fn parse_date(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<DateTime<Utc>> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn parse_uuid(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<Uuid> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| Uuid::parse_str(&s).ok())
+}
+
+fn map_consignor(row: &sqlx::sqlite::SqliteRow) -> Option<Consignor> {
+    Some(Consignor {
+        consignor_uuid: parse_uuid(row, "consignor_uuid")?,
+        name: sqlx::Row::try_get(row, "name").ok()?,
+        email: sqlx::Row::try_get(row, "email").ok().flatten(),
+        phone: sqlx::Row::try_get(row, "phone").ok().flatten(),
+        commission_rate: sqlx::Row::try_get(row, "commission_rate").unwrap_or(0.4),
+        balance_owed: sqlx::Row::try_get(row, "balance_owed").unwrap_or(0.0),
+        customer_uuid: parse_uuid(row, "customer_uuid"),
+        created_at: parse_date(row, "created_at").unwrap_or_else(Utc::now),
+    })
+}
+
+fn map_item(row: &sqlx::sqlite::SqliteRow) -> Option<ConsignmentItem> {
+    let status: String = sqlx::Row::try_get(row, "status").ok()?;
+    Some(ConsignmentItem {
+        consignment_uuid: parse_uuid(row, "consignment_uuid")?,
+        consignor_uuid: parse_uuid(row, "consignor_uuid")?,
+        inventory_uuid: parse_uuid(row, "inventory_uuid")?,
+        product_uuid: parse_uuid(row, "product_uuid"),
+        product_name: sqlx::Row::try_get(row, "product_name").ok().flatten(),
+        asking_price: sqlx::Row::try_get(row, "asking_price").unwrap_or(0.0),
+        minimum_price: sqlx::Row::try_get(row, "minimum_price").ok().flatten(),
+        commission_rate: sqlx::Row::try_get(row, "commission_rate").unwrap_or(0.4),
+        quantity: sqlx::Row::try_get(row, "quantity").unwrap_or(1),
+        quantity_sold: sqlx::Row::try_get(row, "quantity_sold").unwrap_or(0),
+        status: ConsignmentStatus::parse(&status)?,
+        received_date: parse_date(row, "received_date").unwrap_or_else(Utc::now),
+        sold_date: parse_date(row, "sold_date"),
+        expires_at: parse_date(row, "expires_at"),
+        returned_date: parse_date(row, "returned_date"),
+        notes: sqlx::Row::try_get(row, "notes").ok().flatten(),
+    })
+}
+
+fn map_sale(row: &sqlx::sqlite::SqliteRow) -> Option<ConsignmentSale> {
+    Some(ConsignmentSale {
+        sale_uuid: parse_uuid(row, "sale_uuid")?,
+        consignment_uuid: parse_uuid(row, "consignment_uuid")?,
+        consignor_uuid: parse_uuid(row, "consignor_uuid")?,
+        transaction_uuid: parse_uuid(row, "transaction_uuid")?,
+        quantity: sqlx::Row::try_get(row, "quantity").unwrap_or(0),
+        unit_price: sqlx::Row::try_get(row, "unit_price").unwrap_or(0.0),
+        gross_amount: sqlx::Row::try_get(row, "gross_amount").unwrap_or(0.0),
+        commission_rate: sqlx::Row::try_get(row, "commission_rate").unwrap_or(0.0),
+        commission_amount: sqlx::Row::try_get(row, "commission_amount").unwrap_or(0.0),
+        consignor_share: sqlx::Row::try_get(row, "consignor_share").unwrap_or(0.0),
+        created_at: parse_date(row, "created_at").unwrap_or_else(Utc::now),
+    })
+}
+
+fn map_payout(row: &sqlx::sqlite::SqliteRow) -> Option<ConsignorPayout> {
+    let method: String = sqlx::Row::try_get(row, "method").ok()?;
+    Some(ConsignorPayout {
+        payout_uuid: parse_uuid(row, "payout_uuid")?,
+        consignor_uuid: parse_uuid(row, "consignor_uuid")?,
+        amount: sqlx::Row::try_get(row, "amount").unwrap_or(0.0),
+        method: PayoutMethod::parse(&method)?,
+        customer_uuid: parse_uuid(row, "customer_uuid"),
+        paid_by: parse_uuid(row, "paid_by"),
+        notes: sqlx::Row::try_get(row, "notes").ok().flatten(),
+        created_at: parse_date(row, "created_at").unwrap_or_else(Utc::now),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_commission_rounds_to_cents() {
+        assert_eq!(split_commission(100.0, 0.4), (40.0, 60.0));
+        // 33.33 commission, remainder to the consignor
+        assert_eq!(split_commission(99.99, 0.3333), (33.33, 66.66));
+    }
+
+    #[test]
+    fn test_status_and_payout_method_round_trip() {
+        for status in [
+            ConsignmentStatus::Active,
+            ConsignmentStatus::Sold,
+            ConsignmentStatus::Returned,
+            ConsignmentStatus::Expired,
+        ] {
+            assert_eq!(ConsignmentStatus::parse(&status.to_string()), Some(status));
+        }
+        for method in [PayoutMethod::Cash, PayoutMethod::StoreCredit] {
+            assert_eq!(PayoutMethod::parse(&method.to_string()), Some(method));
+        }
+    }
+}
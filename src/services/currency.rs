@@ -0,0 +1,281 @@
+//! Currency configuration and exchange rates
+//!
+//! The store keeps all ledger amounts in a single base currency. Foreign
+//! tender and supplier prices are converted using exchange rates that are
+//! entered manually, so conversion keeps working while offline.
+
+use crate::core::{Currency, Money};
+use crate::database::Database;
+use crate::errors::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A manually entered exchange rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    pub currency: Currency,
+    /// Units of base currency per one unit of `currency`
+    pub rate_to_base: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Base currency plus all configured exchange rates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencySettings {
+    pub base_currency: Currency,
+    pub exchange_rates: Vec<ExchangeRate>,
+}
+
+pub struct CurrencyService {
+    db: Arc<Database>,
+}
+
+impl CurrencyService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// The currency all ledger amounts are recorded in
+    pub async fn get_base_currency(&self) -> Result<Currency> {
+        let row = sqlx::query("SELECT base_currency FROM Currency_Settings WHERE id = 1")
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(row
+            .and_then(|r| sqlx::Row::try_get::<String, _>(&r, "base_currency").ok())
+            .and_then(|code| Currency::from_code(&code))
+            .unwrap_or_default())
+    }
+
+    /// Change the base currency.
+    ///
+    /// Stored amounts are not converted, so the change is refused once any
+    /// sale, hold, purchase order or store-credit balance has been recorded.
+    /// Existing rates are rebased onto the new currency when a rate for it is
+    /// known; otherwise they are cleared and must be entered again.
+    pub async fn set_base_currency(&self, currency: Currency) -> Result<()> {
+        let current = self.get_base_currency().await?;
+        if current == currency {
+            return Ok(());
+        }
+
+        let rates = self.get_exchange_rates().await?;
+        let pivot = rates
+            .iter()
+            .find(|r| r.currency == currency)
+            .map(|r| r.rate_to_base);
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let has_ledger: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM Transactions)
+                 OR EXISTS (SELECT 1 FROM Holds)
+                 OR EXISTS (SELECT 1 FROM Purchase_Orders)
+                 OR EXISTS (SELECT 1 FROM Customers WHERE store_credit <> 0)",
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if has_ledger {
+            return Err(anyhow::anyhow!(
+                "Cannot change the base currency from {} once amounts have been recorded in it",
+                current
+            ));
+        }
+
+        sqlx::query("DELETE FROM Exchange_Rates")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        if let Some(pivot) = pivot {
+            // Old base is worth 1/pivot of the new base; others divide through
+            let mut rebased: Vec<(Currency, f64)> = vec![(current, 1.0 / pivot)];
+            rebased.extend(
+                rates
+                    .iter()
+                    .filter(|r| r.currency != currency)
+                    .map(|r| (r.currency, r.rate_to_base / pivot)),
+            );
+
+            for (code, rate) in rebased {
+                sqlx::query(
+                    "INSERT INTO Exchange_Rates (currency_code, rate_to_base, updated_at) VALUES (?, ?, ?)",
+                )
+                .bind(code.code())
+                .bind(rate)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            }
+        } else if !rates.is_empty() {
+            tracing::warn!(
+                "No {} rate on file; cleared {} exchange rates when changing base currency",
+                currency,
+                rates.len()
+            );
+        }
+
+        sqlx::query(
+            "INSERT INTO Currency_Settings (id, base_currency, updated_at) VALUES (1, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET base_currency = excluded.base_currency, updated_at = excluded.updated_at",
+        )
+        .bind(currency.code())
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!("Base currency changed from {} to {}", current, currency);
+        Ok(())
+    }
+
+    /// Enter or update the rate for a foreign currency
+    pub async fn set_exchange_rate(&self, currency: Currency, rate_to_base: f64) -> Result<()> {
+        if !rate_to_base.is_finite() || rate_to_base <= 0.0 {
+            return Err(anyhow::anyhow!("Exchange rate must be greater than zero"));
+        }
+        if currency == self.get_base_currency().await? {
+            return Err(anyhow::anyhow!(
+                "{} is the base currency and always has a rate of 1",
+                currency
+            ));
+        }
+
+        sqlx::query(
+            "INSERT INTO Exchange_Rates (currency_code, rate_to_base, updated_at) VALUES (?, ?, ?)
+             ON CONFLICT(currency_code) DO UPDATE SET rate_to_base = excluded.rate_to_base, updated_at = excluded.updated_at",
+        )
+        .bind(currency.code())
+        .bind(rate_to_base)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Remove a foreign currency so it can no longer be tendered
+    pub async fn remove_exchange_rate(&self, currency: Currency) -> Result<()> {
+        sqlx::query("DELETE FROM Exchange_Rates WHERE currency_code = ?")
+            .bind(currency.code())
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(())
+    }
+
+    pub async fn get_exchange_rates(&self) -> Result<Vec<ExchangeRate>> {
+        let rows = sqlx::query(
+            "SELECT currency_code, rate_to_base, updated_at FROM Exchange_Rates ORDER BY currency_code",
+        )
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let code: String = sqlx::Row::try_get(row, "currency_code").ok()?;
+                Some(ExchangeRate {
+                    currency: Currency::from_code(&code)?,
+                    rate_to_base: sqlx::Row::try_get(row, "rate_to_base").unwrap_or(1.0),
+                    updated_at: sqlx::Row::try_get::<String, _>(row, "updated_at")
+                        .ok()
+                        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(Utc::now),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn get_settings(&self) -> Result<CurrencySettings> {
+        Ok(CurrencySettings {
+            base_currency: self.get_base_currency().await?,
+            exchange_rates: self.get_exchange_rates().await?,
+        })
+    }
+
+    /// Units of base currency per one unit of `currency`
+    pub async fn rate_to_base(&self, currency: Currency) -> Result<Decimal> {
+        if currency == self.get_base_currency().await? {
+            return Ok(Decimal::ONE);
+        }
+
+        let row = sqlx::query("SELECT rate_to_base FROM Exchange_Rates WHERE currency_code = ?")
+            .bind(currency.code())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        row.and_then(|r| sqlx::Row::try_get::<f64, _>(&r, "rate_to_base").ok())
+            .and_then(Decimal::from_f64)
+            .ok_or_else(|| anyhow::anyhow!("No exchange rate configured for {}", currency))
+    }
+
+    /// Convert an amount into the base currency
+    pub async fn to_base(&self, amount: Money) -> Result<Money> {
+        let base = self.get_base_currency().await?;
+        let rate = self.rate_to_base(amount.currency()).await?;
+        Ok(amount.convert(base, rate))
+    }
+
+    /// Convert an amount between any two configured currencies (via the base)
+    pub async fn convert(&self, amount: Money, to: Currency) -> Result<Money> {
+        if amount.currency() == to {
+            return Ok(amount);
+        }
+        let in_base = self.to_base(amount).await?;
+        let to_rate = self.rate_to_base(to).await?;
+        Ok(in_base.convert(to, Decimal::ONE / to_rate))
+    }
+
+    /// Currency a recorded transaction was rung up in (base currency for older rows)
+    pub async fn transaction_currency(&self, transaction_uuid: uuid::Uuid) -> Result<Currency> {
+        let row = sqlx::query("SELECT currency FROM Transactions WHERE transaction_uuid = ?")
+            .bind(transaction_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        match row
+            .and_then(|r| sqlx::Row::try_get::<Option<String>, _>(&r, "currency").ok())
+            .flatten()
+            .and_then(|code| Currency::from_code(&code))
+        {
+            Some(currency) => Ok(currency),
+            None => self.get_base_currency().await,
+        }
+    }
+
+    /// Convert a legacy f64 amount in `currency` into base-currency f64.
+    /// Returns the converted amount and the rate that was applied.
+    pub async fn to_base_f64(&self, amount: f64, currency: Currency) -> Result<(f64, f64)> {
+        let rate = self.rate_to_base(currency).await?;
+        let converted = self
+            .to_base(Money::from_f64_lossy_in(amount, currency))
+            .await?;
+        Ok((
+            converted.inner().to_f64().unwrap_or(0.0),
+            rate.to_f64().unwrap_or(1.0),
+        ))
+    }
+}
@@ -0,0 +1,833 @@
+//! Customer profiles: editing, duplicate detection, merging and privacy
+//!
+//! The same person is easily entered twice at two registers. Duplicates are
+//! suggested from matching phone numbers, email addresses and similar names,
+//! and a merge moves the duplicate's history, store credit and points onto
+//! the surviving profile. The duplicate is soft-deleted with `merged_into`
+//! pointing at the survivor and a snapshot kept in `Customer_Merges`.
+//!
+//! Privacy requests export everything held about a customer, or anonymize
+//! the profile. Anonymizing strips contact details and preferences but keeps
+//! transactions, returns, payouts and tax certificates, which are financial
+//! records the store must retain.
+
+use crate::core::Customer;
+use crate::database::Database;
+use crate::errors::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Column, Row, TypeInfo, ValueRef};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Name given to anonymized profiles and their event entries
+pub const ANONYMIZED_NAME: &str = "Anonymized customer";
+
+/// Minimum score for a pair to be suggested as duplicates
+pub const DEFAULT_DUPLICATE_THRESHOLD: f64 = 0.5;
+
+/// Tables whose rows follow a customer into a merge. The loyalty ledger is
+/// left out: its rows are synced and immutable, so points move as a transfer.
+const CUSTOMER_TABLES: &[&str] = &[
+    "Transactions",
+    "Wants_Lists",
+    "Wants_Matches",
+    "Event_Participants",
+    "Holds",
+    "Tax_Exemption_Certificates",
+    "Returns",
+    "Event_Prize_Payouts",
+    "Consignors",
+    "Consignor_Payouts",
+    "Loyalty_Tier_History",
+    "Notifications",
+];
+
+/// Partial profile update. Empty email, phone, notes or contact preference
+/// clears the field.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CustomerUpdate {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub notes: Option<String>,
+    pub preferred_contact: Option<String>,
+    pub tier: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateCandidate {
+    pub customer: Customer,
+    pub duplicate: Customer,
+    /// 0..1; phone, email and name each contribute up to 0.5
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeSummary {
+    pub merge_uuid: Uuid,
+    pub survivor: Customer,
+    pub merged_uuid: Uuid,
+    pub rows_moved: u64,
+    pub store_credit_moved: f64,
+    pub points_moved: i64,
+    pub merged_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnonymizationSummary {
+    pub customer_uuid: Uuid,
+    pub wants_lists_removed: u64,
+    pub event_entries_renamed: u64,
+    pub points_forfeited: i64,
+    pub anonymized_at: DateTime<Utc>,
+}
+
+/// Digits only, country code dropped; too short to be a phone number is None
+pub fn normalize_phone(phone: &str) -> Option<String> {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 7 {
+        return None;
+    }
+    Some(digits[digits.len().saturating_sub(10)..].to_string())
+}
+
+/// Lowercased with any `+tag` removed from the local part
+pub fn normalize_email(email: &str) -> Option<String> {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = email.split_once('@')?;
+    if local.is_empty() || domain.is_empty() {
+        return None;
+    }
+    let local = local.split('+').next().unwrap_or(local);
+    Some(format!("{}@{}", local, domain))
+}
+
+/// Lowercased words in sorted order, so "Smith, John" matches "john smith"
+pub fn normalize_name(name: &str) -> String {
+    let cleaned: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    let mut words: Vec<&str> = cleaned.split_whitespace().collect();
+    words.sort_unstable();
+    words.join(" ")
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Edit-distance similarity of two normalized names, 0..1
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize_name(a), normalize_name(b));
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// How alike two profiles look, with the reasons
+pub fn duplicate_score(a: &Customer, b: &Customer) -> (f64, Vec<String>) {
+    let mut score = 0.0;
+    let mut reasons = Vec::new();
+
+    let phone = |c: &Customer| c.phone.as_deref().and_then(normalize_phone);
+    if let (Some(pa), Some(pb)) = (phone(a), phone(b)) {
+        if pa == pb {
+            score += 0.5;
+            reasons.push("Same phone number".to_string());
+        }
+    }
+    let email = |c: &Customer| c.email.as_deref().and_then(normalize_email);
+    if let (Some(ea), Some(eb)) = (email(a), email(b)) {
+        if ea == eb {
+            score += 0.5;
+            reasons.push("Same email address".to_string());
+        }
+    }
+    let similarity = name_similarity(&a.name, &b.name);
+    if similarity >= 0.85 {
+        score += 0.5 * similarity;
+        reasons.push(format!("Similar name ({:.0}%)", similarity * 100.0));
+    }
+
+    (f64::min(score, 1.0), reasons)
+}
+
+/// Keys a profile is bucketed under, so only plausible pairs are scored
+fn blocking_keys(customer: &Customer) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(phone) = customer.phone.as_deref().and_then(normalize_phone) {
+        keys.push(format!("p:{}", phone));
+    }
+    if let Some(email) = customer.email.as_deref().and_then(normalize_email) {
+        keys.push(format!("e:{}", email));
+    }
+    // Names that are 85% alike nearly always share a word's first letters
+    for word in normalize_name(&customer.name).split_whitespace() {
+        keys.push(format!("n:{}", word.chars().take(2).collect::<String>()));
+    }
+    keys
+}
+
+/// Convert a row of any table to JSON, column by column
+fn row_to_json(row: &sqlx::sqlite::SqliteRow) -> Value {
+    let mut map = serde_json::Map::new();
+    for (i, column) in row.columns().iter().enumerate() {
+        let value = match row.try_get_raw(i) {
+            Ok(raw) if !raw.is_null() => match raw.type_info().name() {
+                "INTEGER" => row.try_get::<i64, _>(i).map(Value::from).ok(),
+                "REAL" => row.try_get::<f64, _>(i).map(Value::from).ok(),
+                "TEXT" => row.try_get::<String, _>(i).map(Value::from).ok(),
+                _ => None,
+            },
+            _ => None,
+        };
+        map.insert(column.name().to_string(), value.unwrap_or(Value::Null));
+    }
+    Value::Object(map)
+}
+
+pub struct CustomerService {
+    db: Arc<Database>,
+}
+
+impl CustomerService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    async fn active_customer(&self, customer_uuid: Uuid) -> Result<Customer> {
+        let deleted: Option<Option<String>> =
+            sqlx::query_scalar("SELECT deleted_at FROM Customers WHERE customer_uuid = ?")
+                .bind(customer_uuid.to_string())
+                .fetch_optional(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        match deleted {
+            None => Err(anyhow::anyhow!("Customer {} not found", customer_uuid)),
+            Some(Some(_)) => Err(anyhow::anyhow!(
+                "Customer {} has been merged or deleted",
+                customer_uuid
+            )),
+            Some(None) => self
+                .db
+                .customers
+                .get_by_id(customer_uuid)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Customer {} not found", customer_uuid)),
+        }
+    }
+
+    /// Edit contact details, notes and tier
+    pub async fn update_customer(
+        &self,
+        customer_uuid: Uuid,
+        update: CustomerUpdate,
+    ) -> Result<Customer> {
+        self.active_customer(customer_uuid).await?;
+        if update.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+            return Err(anyhow::anyhow!("Customer name cannot be empty"));
+        }
+        if let Some(email) = update.email.as_deref().filter(|e| !e.trim().is_empty()) {
+            if normalize_email(email).is_none() {
+                return Err(anyhow::anyhow!("'{}' is not a valid email address", email));
+            }
+        }
+        // Some(None) clears the column, None leaves it alone
+        let clearable = |value: &Option<String>| {
+            value
+                .as_ref()
+                .map(|v| Some(v.trim().to_string()).filter(|v| !v.is_empty()))
+        };
+        let email = clearable(&update.email);
+        let phone = clearable(&update.phone);
+        let notes = clearable(&update.notes);
+        let preferred_contact = clearable(&update.preferred_contact);
+        let tier = clearable(&update.tier);
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        sqlx::query(
+            "UPDATE Customers SET
+                name = COALESCE(?, name),
+                email = CASE WHEN ? THEN ? ELSE email END,
+                phone = CASE WHEN ? THEN ? ELSE phone END,
+                notes = CASE WHEN ? THEN ? ELSE notes END,
+                preferred_contact = CASE WHEN ? THEN ? ELSE preferred_contact END,
+                tier = CASE WHEN ? THEN ? ELSE tier END
+             WHERE customer_uuid = ?",
+        )
+        .bind(update.name.as_deref().map(str::trim))
+        .bind(email.is_some())
+        .bind(email.flatten())
+        .bind(phone.is_some())
+        .bind(phone.flatten())
+        .bind(notes.is_some())
+        .bind(notes.flatten())
+        .bind(preferred_contact.is_some())
+        .bind(preferred_contact.flatten())
+        .bind(tier.is_some())
+        .bind(tier.flatten())
+        .bind(customer_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        self.db
+            .customers
+            .log_customer_with_tx(&mut tx, customer_uuid)
+            .await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.active_customer(customer_uuid).await
+    }
+
+    /// Likely duplicate pairs across all active customers, best first
+    pub async fn find_duplicates(&self, threshold: f64) -> Result<Vec<DuplicateCandidate>> {
+        let customers = self.db.customers.get_all().await?;
+        let mut buckets: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, customer) in customers.iter().enumerate() {
+            for key in blocking_keys(customer) {
+                buckets.entry(key).or_default().push(i);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut candidates = Vec::new();
+        for members in buckets.values() {
+            for (n, &i) in members.iter().enumerate() {
+                for &j in &members[n + 1..] {
+                    let pair = (i.min(j), i.max(j));
+                    if i == j || !seen.insert(pair) {
+                        continue;
+                    }
+                    let (a, b) = (&customers[pair.0], &customers[pair.1]);
+                    let (score, reasons) = duplicate_score(a, b);
+                    if score >= threshold {
+                        candidates.push(DuplicateCandidate {
+                            customer: a.clone(),
+                            duplicate: b.clone(),
+                            score,
+                            reasons,
+                        });
+                    }
+                }
+            }
+        }
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(candidates)
+    }
+
+    /// Profiles that look like the same person as `customer_uuid`
+    pub async fn find_duplicates_of(
+        &self,
+        customer_uuid: Uuid,
+        threshold: f64,
+    ) -> Result<Vec<DuplicateCandidate>> {
+        let customer = self.active_customer(customer_uuid).await?;
+        let mut candidates: Vec<DuplicateCandidate> = self
+            .db
+            .customers
+            .get_all()
+            .await?
+            .into_iter()
+            .filter(|other| other.customer_uuid != customer_uuid)
+            .filter_map(|other| {
+                let (score, reasons) = duplicate_score(&customer, &other);
+                (score >= threshold).then(|| DuplicateCandidate {
+                    customer: customer.clone(),
+                    duplicate: other,
+                    score,
+                    reasons,
+                })
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(candidates)
+    }
+
+    /// Fold `merged_uuid` into `survivor_uuid`: history is re-pointed, store
+    /// credit and points move over, and missing contact details are filled
+    /// from the duplicate
+    pub async fn merge_customers(
+        &self,
+        survivor_uuid: Uuid,
+        merged_uuid: Uuid,
+        user_uuid: Option<Uuid>,
+    ) -> Result<MergeSummary> {
+        if survivor_uuid == merged_uuid {
+            return Err(anyhow::anyhow!("Cannot merge a customer into itself"));
+        }
+        self.active_customer(survivor_uuid).await?;
+        self.active_customer(merged_uuid).await?;
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let snapshot = sqlx::query("SELECT * FROM Customers WHERE customer_uuid = ?")
+            .bind(merged_uuid.to_string())
+            .fetch_one(&mut *tx)
+            .await
+            .map(|row| row_to_json(&row))
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let store_credit = snapshot
+            .get("store_credit")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+
+        let mut rows_moved = 0;
+        for table in CUSTOMER_TABLES {
+            rows_moved += sqlx::query(&format!(
+                "UPDATE {} SET customer_uuid = ? WHERE customer_uuid = ?",
+                table
+            ))
+            .bind(survivor_uuid.to_string())
+            .bind(merged_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to move {} rows: {}", table, e))?
+            .rows_affected();
+        }
+        // Messaging preferences follow the duplicate only if the survivor has none
+        sqlx::query(
+            "UPDATE OR IGNORE Customer_Notification_Preferences SET customer_uuid = ?
+             WHERE customer_uuid = ?",
+        )
+        .bind(survivor_uuid.to_string())
+        .bind(merged_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        sqlx::query("DELETE FROM Customer_Notification_Preferences WHERE customer_uuid = ?")
+            .bind(merged_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        // Keep the survivor's details, filling gaps from the duplicate
+        sqlx::query(
+            "UPDATE Customers SET
+                store_credit = Customers.store_credit + d.store_credit,
+                email = COALESCE(NULLIF(Customers.email, ''), d.email),
+                phone = COALESCE(NULLIF(Customers.phone, ''), d.phone),
+                notes = COALESCE(NULLIF(Customers.notes, ''), d.notes),
+                preferred_contact = COALESCE(Customers.preferred_contact, d.preferred_contact),
+                is_banned = MAX(COALESCE(Customers.is_banned, 0), COALESCE(d.is_banned, 0)),
+                ban_reason = COALESCE(Customers.ban_reason, d.ban_reason)
+             FROM (SELECT * FROM Customers WHERE customer_uuid = ?) AS d
+             WHERE Customers.customer_uuid = ?",
+        )
+        .bind(merged_uuid.to_string())
+        .bind(survivor_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update survivor: {}", e))?;
+
+        let now = Utc::now();
+        sqlx::query(
+            "UPDATE Customers SET store_credit = 0, deleted_at = ?, merged_into = ?
+             WHERE customer_uuid = ?",
+        )
+        .bind(now.to_rfc3339())
+        .bind(survivor_uuid.to_string())
+        .bind(merged_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to retire duplicate: {}", e))?;
+
+        let points_moved = crate::services::loyalty::transfer_balance_with_tx(
+            &self.db,
+            &mut tx,
+            merged_uuid,
+            survivor_uuid,
+            user_uuid,
+        )
+        .await?;
+
+        let merge_uuid = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO Customer_Merges
+             (merge_uuid, survivor_uuid, merged_uuid, merged_snapshot, rows_moved, store_credit_moved, points_moved, user_uuid, merged_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(merge_uuid.to_string())
+        .bind(survivor_uuid.to_string())
+        .bind(merged_uuid.to_string())
+        .bind(snapshot.to_string())
+        .bind(rows_moved as i64)
+        .bind(store_credit)
+        .bind(points_moved)
+        .bind(user_uuid.map(|u| u.to_string()))
+        .bind(now.to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        self.db
+            .customers
+            .log_customer_with_tx(&mut tx, survivor_uuid)
+            .await?;
+        self.db
+            .customers
+            .log_customer_with_tx(&mut tx, merged_uuid)
+            .await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        // Combined spend may earn a different tier
+        if let Err(e) = crate::services::loyalty::evaluate_tier(&self.db, survivor_uuid).await {
+            tracing::warn!("Tier evaluation for {} failed: {}", survivor_uuid, e);
+        }
+
+        tracing::info!(
+            "Merged customer {} into {} ({} rows, ${:.2} credit, {} points)",
+            merged_uuid,
+            survivor_uuid,
+            rows_moved,
+            store_credit,
+            points_moved
+        );
+
+        Ok(MergeSummary {
+            merge_uuid,
+            survivor: self.active_customer(survivor_uuid).await?,
+            merged_uuid,
+            rows_moved,
+            store_credit_moved: store_credit,
+            points_moved,
+            merged_at: now,
+        })
+    }
+
+    async fn select_json(&self, sql: &str, customer_uuid: Uuid) -> Result<Vec<Value>> {
+        let rows = sqlx::query(sql)
+            .bind(customer_uuid.to_string())
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().map(row_to_json).collect())
+    }
+
+    async fn record_privacy_request(
+        &self,
+        customer_uuid: Uuid,
+        request_type: &str,
+        reason: Option<&str>,
+        user_uuid: Option<Uuid>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO Customer_Privacy_Requests (request_uuid, customer_uuid, request_type, reason, user_uuid, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(customer_uuid.to_string())
+        .bind(request_type)
+        .bind(reason)
+        .bind(user_uuid.map(|u| u.to_string()))
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(())
+    }
+
+    /// Everything held about a customer, for a data access request
+    pub async fn export_customer_data(
+        &self,
+        customer_uuid: Uuid,
+        user_uuid: Option<Uuid>,
+    ) -> Result<Value> {
+        let profile = self
+            .select_json(
+                "SELECT * FROM Customers WHERE customer_uuid = ?",
+                customer_uuid,
+            )
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Customer {} not found", customer_uuid))?;
+
+        let sections: [(&str, &str); 18] = [
+            ("transactions", "SELECT * FROM Transactions WHERE customer_uuid = ? ORDER BY timestamp"),
+            ("transaction_items", "SELECT ti.* FROM Transaction_Items ti JOIN Transactions t ON t.transaction_uuid = ti.transaction_uuid WHERE t.customer_uuid = ?"),
+            ("payments", "SELECT p.* FROM Payment_Methods p JOIN Transactions t ON t.transaction_uuid = p.transaction_uuid WHERE t.customer_uuid = ?"),
+            ("returns", "SELECT * FROM Returns WHERE customer_uuid = ?"),
+            ("holds", "SELECT * FROM Holds WHERE customer_uuid = ?"),
+            ("hold_items", "SELECT hi.* FROM Hold_Items hi JOIN Holds h ON h.hold_uuid = hi.hold_uuid WHERE h.customer_uuid = ?"),
+            ("wants_lists", "SELECT * FROM Wants_Lists WHERE customer_uuid = ?"),
+            ("wants_items", "SELECT wi.* FROM Wants_Items wi JOIN Wants_Lists wl ON wl.wants_list_uuid = wi.wants_list_uuid WHERE wl.customer_uuid = ?"),
+            ("wants_matches", "SELECT * FROM Wants_Matches WHERE customer_uuid = ?"),
+            ("event_entries", "SELECT * FROM Event_Participants WHERE customer_uuid = ?"),
+            ("event_prizes", "SELECT * FROM Event_Prize_Payouts WHERE customer_uuid = ?"),
+            ("tax_exemptions", "SELECT * FROM Tax_Exemption_Certificates WHERE customer_uuid = ?"),
+            ("consignor_accounts", "SELECT * FROM Consignors WHERE customer_uuid = ?"),
+            ("loyalty_ledger", "SELECT * FROM Loyalty_Ledger WHERE customer_uuid = ? ORDER BY created_at"),
+            ("loyalty_tier_history", "SELECT * FROM Loyalty_Tier_History WHERE customer_uuid = ? ORDER BY changed_at"),
+            ("notification_preferences", "SELECT * FROM Customer_Notification_Preferences WHERE customer_uuid = ?"),
+            ("notifications", "SELECT * FROM Notifications WHERE customer_uuid = ? ORDER BY created_at"),
+            ("merged_profiles", "SELECT merged_uuid, merged_snapshot, merged_at FROM Customer_Merges WHERE survivor_uuid = ?"),
+        ];
+
+        let mut export = serde_json::Map::new();
+        export.insert("customer".to_string(), profile);
+        for (name, sql) in sections {
+            export.insert(
+                name.to_string(),
+                Value::Array(self.select_json(sql, customer_uuid).await?),
+            );
+        }
+        export.insert(
+            "exported_at".to_string(),
+            Value::from(Utc::now().to_rfc3339()),
+        );
+
+        self.record_privacy_request(customer_uuid, "export", None, user_uuid)
+            .await?;
+        Ok(Value::Object(export))
+    }
+
+    /// Erase a customer's personal details on request. Financial records stay
+    /// but no longer identify them. Store credit and open holds have to be
+    /// settled first; loyalty points are forfeited.
+    pub async fn anonymize_customer(
+        &self,
+        customer_uuid: Uuid,
+        reason: Option<String>,
+        user_uuid: Option<Uuid>,
+    ) -> Result<AnonymizationSummary> {
+        let row = sqlx::query(
+            "SELECT store_credit, anonymized_at, merged_into FROM Customers WHERE customer_uuid = ?",
+        )
+        .bind(customer_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Customer {} not found", customer_uuid))?;
+        if row
+            .try_get::<Option<String>, _>("anonymized_at")
+            .ok()
+            .flatten()
+            .is_some()
+        {
+            return Err(anyhow::anyhow!(
+                "Customer {} is already anonymized",
+                customer_uuid
+            ));
+        }
+        if let Some(survivor) = row
+            .try_get::<Option<String>, _>("merged_into")
+            .ok()
+            .flatten()
+        {
+            return Err(anyhow::anyhow!(
+                "Customer {} was merged into {}; anonymize that profile instead",
+                customer_uuid,
+                survivor
+            ));
+        }
+        let store_credit: f64 = row.try_get("store_credit").unwrap_or(0.0);
+        if store_credit.abs() > 0.005 {
+            return Err(anyhow::anyhow!(
+                "Customer has ${:.2} store credit; pay it out before anonymizing",
+                store_credit
+            ));
+        }
+        let open_holds: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM Holds WHERE customer_uuid = ? AND status = 'Active'",
+        )
+        .bind(customer_uuid.to_string())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if open_holds > 0 {
+            return Err(anyhow::anyhow!(
+                "Customer has {} active holds; complete or cancel them before anonymizing",
+                open_holds
+            ));
+        }
+
+        let now = Utc::now();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let points_forfeited = crate::services::loyalty::forfeit_balance_with_tx(
+            &self.db,
+            &mut tx,
+            customer_uuid,
+            "Forfeited on anonymization",
+            user_uuid,
+        )
+        .await?;
+
+        sqlx::query(
+            "UPDATE Customers SET name = ?, email = NULL, phone = NULL, notes = NULL,
+                preferred_contact = NULL, ban_reason = NULL,
+                deleted_at = COALESCE(deleted_at, ?), anonymized_at = ?
+             WHERE customer_uuid = ?",
+        )
+        .bind(ANONYMIZED_NAME)
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .bind(customer_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        // Wants lists are preferences, not records; they go entirely
+        sqlx::query(
+            "DELETE FROM Wants_Items WHERE wants_list_uuid IN
+                (SELECT wants_list_uuid FROM Wants_Lists WHERE customer_uuid = ?)",
+        )
+        .bind(customer_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let wants_lists_removed = sqlx::query("DELETE FROM Wants_Lists WHERE customer_uuid = ?")
+            .bind(customer_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+            .rows_affected();
+
+        sqlx::query(
+            "UPDATE Wants_Matches SET status = 'Declined', updated_at = ?
+             WHERE customer_uuid = ? AND status = 'Open'",
+        )
+        .bind(now.to_rfc3339())
+        .bind(customer_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        // Consent goes with the contact details; the delivery log keeps
+        // only what was sent when, not to whom or what it said
+        sqlx::query("DELETE FROM Customer_Notification_Preferences WHERE customer_uuid = ?")
+            .bind(customer_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        sqlx::query(
+            "UPDATE Notifications SET recipient = NULL, title = '', message = '',
+                next_attempt_at = NULL
+             WHERE customer_uuid = ?",
+        )
+        .bind(customer_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        // Standings stay intact, under a neutral name
+        let event_entries_renamed =
+            sqlx::query("UPDATE Event_Participants SET name = ? WHERE customer_uuid = ?")
+                .bind(ANONYMIZED_NAME)
+                .bind(customer_uuid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+                .rows_affected();
+
+        self.db
+            .customers
+            .log_customer_with_tx(&mut tx, customer_uuid)
+            .await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.record_privacy_request(customer_uuid, "anonymize", reason.as_deref(), user_uuid)
+            .await?;
+        tracing::info!("Anonymized customer {}", customer_uuid);
+
+        Ok(AnonymizationSummary {
+            customer_uuid,
+            wants_lists_removed,
+            event_entries_renamed,
+            points_forfeited,
+            anonymized_at: now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn customer(name: &str, email: Option<&str>, phone: Option<&str>) -> Customer {
+        Customer {
+            customer_uuid: Uuid::new_v4(),
+            name: name.to_string(),
+            email: email.map(str::to_string),
+            phone: phone.map(str::to_string),
+            store_credit: 0.0,
+            tier: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_normalization() {
+        assert_eq!(
+            normalize_phone("+1 (555) 123-4567").as_deref(),
+            Some("5551234567")
+        );
+        assert_eq!(
+            normalize_phone("555.123.4567"),
+            normalize_phone("15551234567")
+        );
+        assert_eq!(normalize_phone("123"), None);
+        assert_eq!(
+            normalize_email(" Sam+Cards@Example.COM ").as_deref(),
+            Some("sam@example.com")
+        );
+        assert_eq!(normalize_email("not-an-email"), None);
+        assert_eq!(normalize_name("Smith, John"), normalize_name("john  SMITH"));
+    }
+
+    #[test]
+    fn test_duplicate_scores() {
+        let a = customer("Jon Smith", Some("jon@example.com"), Some("555-123-4567"));
+        let same_phone = customer("Jonathan Smyth", None, Some("(555) 123 4567"));
+        let (score, reasons) = duplicate_score(&a, &same_phone);
+        assert_eq!(score, 0.5);
+        assert_eq!(reasons, vec!["Same phone number".to_string()]);
+
+        let typo = customer("John Smith", Some("JON+mtg@example.com"), None);
+        let (score, reasons) = duplicate_score(&a, &typo);
+        assert!(score > 0.9 && score <= 1.0);
+        assert_eq!(reasons.len(), 2);
+
+        let stranger = customer("Alex Chen", Some("alex@example.com"), None);
+        assert_eq!(duplicate_score(&a, &stranger).0, 0.0);
+    }
+}
@@ -0,0 +1,670 @@
+//! Customer-facing display feed
+//!
+//! Each POS terminal can have a second screen facing the customer. The
+//! display subscribes to a per-terminal channel and receives the current
+//! state followed by every change: the live cart, trade-in offers, payment
+//! prompts, a thank-you screen, and store promotions while the till is idle.
+
+use crate::core::money::round_cents;
+use crate::core::{Condition, Currency};
+use crate::database::Database;
+use crate::errors::Result;
+use crate::services::{CurrencyService, JurisdictionTax};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events buffered per display before a slow subscriber starts lagging
+const CHANNEL_CAPACITY: usize = 32;
+
+/// How long the thank-you screen stays up before promotions resume
+const DEFAULT_THANK_YOU_DURATION: Duration = Duration::from_secs(15);
+
+/// How long an unwatched terminal keeps a non-idle state before it is dropped
+const UNWATCHED_TERMINAL_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// A single line of the cart as shown to the customer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayLine {
+    pub name: String,
+    pub quantity: i32,
+    pub unit_price: f64,
+    pub line_total: f64,
+}
+
+/// Live cart with running totals
+#[derive(Debug, Clone, Serialize)]
+pub struct CartView {
+    pub lines: Vec<DisplayLine>,
+    pub subtotal: f64,
+    pub tax_amount: f64,
+    pub tax_lines: Vec<JurisdictionTax>,
+    pub trade_in_credit: f64,
+    pub total: f64,
+    pub currency: Currency,
+}
+
+/// A quoted trade-in item awaiting the customer's decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeInOffer {
+    pub product_uuid: Uuid,
+    pub name: String,
+    pub condition: Condition,
+    pub quantity: i32,
+    /// Per-unit cash offer
+    pub cash_price: f64,
+    /// Per-unit store credit offer
+    pub credit_price: f64,
+}
+
+/// All trade-in offers quoted on this terminal since the last sale
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeInView {
+    pub offers: Vec<TradeInOffer>,
+    pub total_cash: f64,
+    pub total_credit: f64,
+    pub currency: Currency,
+}
+
+/// Prompt the customer to pay (insert card, tender cash, ...)
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentPromptView {
+    pub amount_due: f64,
+    pub amount_paid: f64,
+    pub balance: f64,
+    pub method: Option<String>,
+    pub message: Option<String>,
+    pub currency: Currency,
+}
+
+/// Shown once a sale completes
+#[derive(Debug, Clone, Serialize)]
+pub struct ThankYouView {
+    pub transaction_uuid: Option<Uuid>,
+    pub total: f64,
+    pub change_given: f64,
+    /// Where the receipt was sent (email address or phone), once known
+    pub receipt_sent_to: Option<String>,
+    pub currency: Currency,
+}
+
+/// Promotional content shown while a terminal is idle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayPromotion {
+    pub promotion_uuid: Uuid,
+    pub title: String,
+    pub body: Option<String>,
+    pub image_url: Option<String>,
+    pub display_seconds: i32,
+    pub sort_order: i32,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to add a promotion to the idle rotation
+#[derive(Debug, Clone, Deserialize)]
+pub struct DisplayPromotionRequest {
+    pub title: String,
+    pub body: Option<String>,
+    pub image_url: Option<String>,
+    pub display_seconds: Option<i32>,
+    pub sort_order: Option<i32>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+/// Everything a customer display can be asked to show
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum DisplayEvent {
+    Idle { promotions: Vec<DisplayPromotion> },
+    Cart(CartView),
+    TradeIn(TradeInView),
+    PaymentPrompt(PaymentPromptView),
+    ThankYou(ThankYouView),
+}
+
+impl DisplayEvent {
+    /// Event name used on the wire (matches the `state` tag)
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayEvent::Idle { .. } => "idle",
+            DisplayEvent::Cart(_) => "cart",
+            DisplayEvent::TradeIn(_) => "trade_in",
+            DisplayEvent::PaymentPrompt(_) => "payment_prompt",
+            DisplayEvent::ThankYou(_) => "thank_you",
+        }
+    }
+}
+
+struct TerminalDisplay {
+    sender: broadcast::Sender<DisplayEvent>,
+    current: DisplayEvent,
+    /// Bumped on every publish so a pending idle timer can tell it is stale
+    revision: u64,
+    trade_in_offers: Vec<TradeInOffer>,
+    last_used: Instant,
+}
+
+impl TerminalDisplay {
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            current: DisplayEvent::Idle {
+                promotions: Vec::new(),
+            },
+            revision: 0,
+            trade_in_offers: Vec::new(),
+            last_used: Instant::now(),
+        }
+    }
+
+    /// Nothing is watching and nothing worth keeping is on screen. A fresh
+    /// entry would render the same thing, so the terminal can be dropped.
+    fn is_disposable(&self, now: Instant) -> bool {
+        if self.sender.receiver_count() > 0 {
+            return false;
+        }
+        let idle =
+            matches!(self.current, DisplayEvent::Idle { .. }) && self.trade_in_offers.is_empty();
+        idle || now.duration_since(self.last_used) > UNWATCHED_TERMINAL_TTL
+    }
+}
+
+/// Per-terminal channels, shared with idle timers.
+///
+/// Terminal IDs come straight from request paths, so entries are created on
+/// demand and dropped again once they are disposable.
+#[derive(Clone, Default)]
+struct DisplayHub {
+    terminals: Arc<RwLock<HashMap<String, TerminalDisplay>>>,
+    /// Hub-wide so a terminal recreated after eviction never reuses a
+    /// revision a pending idle timer is still holding
+    last_revision: Arc<AtomicU64>,
+}
+
+impl DisplayHub {
+    fn with_terminal<T>(&self, terminal_id: &str, f: impl FnOnce(&mut TerminalDisplay) -> T) -> T {
+        let mut terminals = self.terminals.write().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        terminals.retain(|id, terminal| id == terminal_id || !terminal.is_disposable(now));
+
+        let terminal = terminals
+            .entry(terminal_id.to_string())
+            .or_insert_with(TerminalDisplay::new);
+        terminal.last_used = now;
+        let result = f(terminal);
+        if terminal.is_disposable(now) {
+            terminals.remove(terminal_id);
+        }
+        result
+    }
+
+    /// Replace the terminal's state and notify subscribers. Returns the new revision.
+    fn publish(&self, terminal_id: &str, event: DisplayEvent) -> u64 {
+        self.with_terminal(terminal_id, |terminal| {
+            terminal.current = event.clone();
+            terminal.revision = self.last_revision.fetch_add(1, Ordering::Relaxed) + 1;
+            // No connected display is not an error
+            let _ = terminal.sender.send(event);
+            terminal.revision
+        })
+    }
+
+    fn revision(&self, terminal_id: &str) -> u64 {
+        self.with_terminal(terminal_id, |terminal| terminal.revision)
+    }
+}
+
+pub struct CustomerDisplayService {
+    db: Arc<Database>,
+    currency: CurrencyService,
+    hub: DisplayHub,
+    thank_you_duration: Duration,
+}
+
+impl CustomerDisplayService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            currency: CurrencyService::new(db.clone()),
+            db,
+            hub: DisplayHub::default(),
+            thank_you_duration: DEFAULT_THANK_YOU_DURATION,
+        }
+    }
+
+    /// Override how long the thank-you screen is shown
+    pub fn with_thank_you_duration(mut self, duration: Duration) -> Self {
+        self.thank_you_duration = duration;
+        self
+    }
+
+    /// Subscribe to a terminal's display. Returns the state to render now
+    /// together with a receiver for subsequent changes.
+    pub async fn subscribe(
+        &self,
+        terminal_id: &str,
+    ) -> Result<(DisplayEvent, broadcast::Receiver<DisplayEvent>)> {
+        let current = self.current_state(terminal_id).await?;
+        let receiver = self
+            .hub
+            .with_terminal(terminal_id, |terminal| terminal.sender.subscribe());
+        Ok((current, receiver))
+    }
+
+    /// What the terminal's display is showing right now
+    pub async fn current_state(&self, terminal_id: &str) -> Result<DisplayEvent> {
+        let current = self
+            .hub
+            .with_terminal(terminal_id, |terminal| terminal.current.clone());
+
+        // Idle promotions are read fresh so schedule changes are picked up
+        match current {
+            DisplayEvent::Idle { .. } => Ok(DisplayEvent::Idle {
+                promotions: load_active_promotions(&self.db).await?,
+            }),
+            other => Ok(other),
+        }
+    }
+
+    /// Push an arbitrary state to a terminal's display
+    pub fn publish(&self, terminal_id: &str, event: DisplayEvent) {
+        self.hub.publish(terminal_id, event);
+    }
+
+    /// Show the live cart
+    pub fn show_cart(&self, terminal_id: &str, cart: CartView) {
+        self.hub.publish(terminal_id, DisplayEvent::Cart(cart));
+    }
+
+    /// Add a buylist quote to the terminal's running list of trade-in offers.
+    /// Quoting the same product and condition again replaces the earlier offer.
+    pub async fn add_trade_in_offer(
+        &self,
+        terminal_id: &str,
+        product_uuid: Uuid,
+        condition: Condition,
+        quantity: i32,
+        cash_price: f64,
+        credit_price: f64,
+    ) -> Result<TradeInView> {
+        let name = sqlx::query("SELECT name FROM Global_Catalog WHERE product_uuid = ?")
+            .bind(product_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+            .and_then(|r| sqlx::Row::try_get::<String, _>(&r, "name").ok())
+            .unwrap_or_else(|| "Unknown Item".to_string());
+        let currency = self.currency.get_base_currency().await?;
+
+        let offer = TradeInOffer {
+            product_uuid,
+            name,
+            condition,
+            quantity: quantity.max(1),
+            cash_price,
+            credit_price,
+        };
+
+        let offers = self.hub.with_terminal(terminal_id, |terminal| {
+            terminal.trade_in_offers.retain(|o| {
+                !(o.product_uuid == offer.product_uuid && o.condition == offer.condition)
+            });
+            terminal.trade_in_offers.push(offer);
+            terminal.trade_in_offers.clone()
+        });
+
+        let view = trade_in_view(offers, currency);
+        self.hub
+            .publish(terminal_id, DisplayEvent::TradeIn(view.clone()));
+        Ok(view)
+    }
+
+    /// Drop all pending trade-in offers (customer declined or sale finished)
+    pub fn clear_trade_in_offers(&self, terminal_id: &str) {
+        self.hub
+            .with_terminal(terminal_id, |terminal| terminal.trade_in_offers.clear());
+    }
+
+    /// Ask the customer for payment
+    pub async fn show_payment_prompt(
+        &self,
+        terminal_id: &str,
+        amount_due: f64,
+        amount_paid: f64,
+        method: Option<String>,
+        message: Option<String>,
+    ) -> Result<PaymentPromptView> {
+        let view = PaymentPromptView {
+            amount_due,
+            amount_paid,
+            balance: round_cents(amount_due - amount_paid),
+            method,
+            message,
+            currency: self.currency.get_base_currency().await?,
+        };
+        self.hub
+            .publish(terminal_id, DisplayEvent::PaymentPrompt(view.clone()));
+        Ok(view)
+    }
+
+    /// Show the thank-you screen, then fall back to promotions after the
+    /// configured delay unless the terminal has moved on to another sale.
+    pub fn show_thank_you(
+        &self,
+        terminal_id: &str,
+        transaction_uuid: Option<Uuid>,
+        total: f64,
+        change_given: f64,
+        currency: Currency,
+    ) {
+        self.clear_trade_in_offers(terminal_id);
+        let view = ThankYouView {
+            transaction_uuid,
+            total,
+            change_given,
+            receipt_sent_to: None,
+            currency,
+        };
+        let revision = self.hub.publish(terminal_id, DisplayEvent::ThankYou(view));
+        self.schedule_idle(terminal_id, revision);
+    }
+
+    /// Note on the thank-you screen that the receipt went out. Ignored if the
+    /// display has already moved on to something else.
+    pub fn mark_receipt_sent(&self, terminal_id: &str, sent_to: &str) -> bool {
+        let updated = self
+            .hub
+            .with_terminal(terminal_id, |terminal| match &terminal.current {
+                DisplayEvent::ThankYou(view) => {
+                    let mut view = view.clone();
+                    view.receipt_sent_to = Some(sent_to.to_string());
+                    Some(DisplayEvent::ThankYou(view))
+                }
+                _ => None,
+            });
+
+        match updated {
+            Some(event) => {
+                let revision = self.hub.publish(terminal_id, event);
+                self.schedule_idle(terminal_id, revision);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return the display to the idle promotion rotation
+    pub async fn show_idle(&self, terminal_id: &str) -> Result<()> {
+        self.clear_trade_in_offers(terminal_id);
+        let promotions = load_active_promotions(&self.db).await?;
+        self.hub
+            .publish(terminal_id, DisplayEvent::Idle { promotions });
+        Ok(())
+    }
+
+    fn schedule_idle(&self, terminal_id: &str, revision: u64) {
+        let hub = self.hub.clone();
+        let db = self.db.clone();
+        let terminal_id = terminal_id.to_string();
+        let delay = self.thank_you_duration;
+
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if hub.revision(&terminal_id) != revision {
+                return;
+            }
+            match load_active_promotions(&db).await {
+                Ok(promotions) => {
+                    // Re-check: a new sale may have started while loading
+                    if hub.revision(&terminal_id) == revision {
+                        hub.publish(&terminal_id, DisplayEvent::Idle { promotions });
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to load display promotions: {}", e),
+            }
+        });
+    }
+
+    // ---- Promotions ----
+
+    pub async fn create_promotion(
+        &self,
+        request: DisplayPromotionRequest,
+    ) -> Result<DisplayPromotion> {
+        if request.title.trim().is_empty() {
+            return Err(anyhow::anyhow!("Promotion title is required"));
+        }
+        if let (Some(starts), Some(ends)) = (request.starts_at, request.ends_at) {
+            if ends <= starts {
+                return Err(anyhow::anyhow!("Promotion must end after it starts"));
+            }
+        }
+
+        let promotion = DisplayPromotion {
+            promotion_uuid: Uuid::new_v4(),
+            title: request.title,
+            body: request.body,
+            image_url: request.image_url,
+            display_seconds: request.display_seconds.unwrap_or(10).max(1),
+            sort_order: request.sort_order.unwrap_or(0),
+            starts_at: request.starts_at,
+            ends_at: request.ends_at,
+            is_active: true,
+            created_at: Utc::now(),
+        };
+
+        sqlx::query(
+            "INSERT INTO Display_Promotions
+             (promotion_uuid, title, body, image_url, display_seconds, sort_order, starts_at, ends_at, is_active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 1, ?)",
+        )
+        .bind(promotion.promotion_uuid.to_string())
+        .bind(&promotion.title)
+        .bind(&promotion.body)
+        .bind(&promotion.image_url)
+        .bind(promotion.display_seconds)
+        .bind(promotion.sort_order)
+        .bind(promotion.starts_at.map(|d| d.to_rfc3339()))
+        .bind(promotion.ends_at.map(|d| d.to_rfc3339()))
+        .bind(promotion.created_at.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(promotion)
+    }
+
+    /// All promotions, including retired and scheduled ones
+    pub async fn get_promotions(&self) -> Result<Vec<DisplayPromotion>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Display_Promotions ORDER BY is_active DESC, sort_order, created_at",
+        )
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().filter_map(map_promotion).collect())
+    }
+
+    /// Promotions currently in the idle rotation
+    pub async fn get_active_promotions(&self) -> Result<Vec<DisplayPromotion>> {
+        load_active_promotions(&self.db).await
+    }
+
+    /// Remove a promotion from the rotation (kept for history)
+    pub async fn deactivate_promotion(&self, promotion_uuid: Uuid) -> Result<()> {
+        let result =
+            sqlx::query("UPDATE Display_Promotions SET is_active = 0 WHERE promotion_uuid = ?")
+                .bind(promotion_uuid.to_string())
+                .execute(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Promotion {} not found", promotion_uuid));
+        }
+        Ok(())
+    }
+}
+
+fn trade_in_view(offers: Vec<TradeInOffer>, currency: Currency) -> TradeInView {
+    let total_cash = offers
+        .iter()
+        .map(|o| o.cash_price * o.quantity as f64)
+        .sum();
+    let total_credit = offers
+        .iter()
+        .map(|o| o.credit_price * o.quantity as f64)
+        .sum();
+    TradeInView {
+        offers,
+        total_cash,
+        total_credit,
+        currency,
+    }
+}
+
+async fn load_active_promotions(db: &Database) -> Result<Vec<DisplayPromotion>> {
+    let now = Utc::now().to_rfc3339();
+    let rows = sqlx::query(
+        "SELECT * FROM Display_Promotions
+         WHERE is_active = 1
+           AND (starts_at IS NULL OR starts_at <= ?)
+           AND (ends_at IS NULL OR ends_at > ?)
+         ORDER BY sort_order, created_at",
+    )
+    .bind(&now)
+    .bind(&now)
+    .fetch_all(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    Ok(rows.iter().filter_map(map_promotion).collect())
+}
+
+fn map_promotion(row: &sqlx::sqlite::SqliteRow) -> Option<DisplayPromotion> {
+    let parse_date = |col: &str| -> Option<DateTime<Utc>> {
+        sqlx::Row::try_get::<Option<String>, _>(row, col)
+            .ok()
+            .flatten()
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+    };
+
+    let uuid: String = sqlx::Row::try_get(row, "promotion_uuid").ok()?;
+    Some(DisplayPromotion {
+        promotion_uuid: Uuid::parse_str(&uuid).ok()?,
+        title: sqlx::Row::try_get(row, "title").ok()?,
+        body: sqlx::Row::try_get(row, "body").ok().flatten(),
+        image_url: sqlx::Row::try_get(row, "image_url").ok().flatten(),
+        display_seconds: sqlx::Row::try_get(row, "display_seconds").unwrap_or(10),
+        sort_order: sqlx::Row::try_get(row, "sort_order").unwrap_or(0),
+        starts_at: parse_date("starts_at"),
+        ends_at: parse_date("ends_at"),
+        is_active: sqlx::Row::try_get::<i32, _>(row, "is_active").unwrap_or(0) == 1,
+        created_at: parse_date("created_at").unwrap_or_else(Utc::now),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer(cash: f64, credit: f64, quantity: i32) -> TradeInOffer {
+        TradeInOffer {
+            product_uuid: Uuid::new_v4(),
+            name: "Card".to_string(),
+            condition: Condition::NM,
+            quantity,
+            cash_price: cash,
+            credit_price: credit,
+        }
+    }
+
+    fn empty_cart() -> DisplayEvent {
+        DisplayEvent::Cart(CartView {
+            lines: Vec::new(),
+            subtotal: 0.0,
+            tax_amount: 0.0,
+            tax_lines: Vec::new(),
+            trade_in_credit: 0.0,
+            total: 0.0,
+            currency: Currency::USD,
+        })
+    }
+
+    #[test]
+    fn test_trade_in_view_totals() {
+        let view = trade_in_view(vec![offer(2.0, 3.0, 2), offer(5.0, 6.5, 1)], Currency::USD);
+        assert!((view.total_cash - 9.0).abs() < 0.001);
+        assert!((view.total_credit - 12.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_event_serializes_with_state_tag() {
+        let event = DisplayEvent::PaymentPrompt(PaymentPromptView {
+            amount_due: 10.0,
+            amount_paid: 4.0,
+            balance: 6.0,
+            method: Some("card".to_string()),
+            message: None,
+            currency: Currency::USD,
+        });
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["state"], event.name());
+        assert_eq!(json["balance"], 6.0);
+    }
+
+    #[tokio::test]
+    async fn test_hub_publish_reaches_subscribers_and_bumps_revision() {
+        let hub = DisplayHub::default();
+        let mut rx = hub.with_terminal("T1", |t| t.sender.subscribe());
+
+        let rev = hub.publish(
+            "T1",
+            DisplayEvent::Idle {
+                promotions: Vec::new(),
+            },
+        );
+        assert_eq!(rev, 1);
+        assert_eq!(hub.revision("T1"), 1);
+        assert_eq!(hub.revision("T2"), 0);
+        assert_eq!(rx.recv().await.unwrap().name(), "idle");
+    }
+
+    #[test]
+    fn test_hub_evicts_unwatched_idle_terminals() {
+        let hub = DisplayHub::default();
+        let _rx = hub.with_terminal("WATCHED", |t| t.sender.subscribe());
+        for i in 0..100 {
+            hub.revision(&format!("PROBE-{}", i));
+        }
+        let cart_rev = hub.publish("BUSY", empty_cart());
+
+        let ids: Vec<String> = hub.terminals.read().unwrap().keys().cloned().collect();
+        assert_eq!(ids.len(), 2, "unexpected terminals: {:?}", ids);
+        assert!(ids.contains(&"WATCHED".to_string()));
+        assert!(ids.contains(&"BUSY".to_string()));
+
+        // Going idle with nobody watching drops the terminal, and a recreated
+        // one never hands out a revision an old timer could match
+        let idle_rev = hub.publish(
+            "BUSY",
+            DisplayEvent::Idle {
+                promotions: Vec::new(),
+            },
+        );
+        assert!(!hub.terminals.read().unwrap().contains_key("BUSY"));
+        let next_rev = hub.publish("BUSY", empty_cart());
+        assert!(next_rev > idle_rev && idle_rev > cart_rev);
+    }
+}
@@ -0,0 +1,1263 @@
+//! Cycle counting
+//!
+//! Count plans classify products A/B/C by value and sales velocity and
+//! schedule sessions for the piles at a location that are due for a count.
+//! Sessions snapshot each pile's expected quantity, collect counts from one
+//! or more counters, and send lines that disagree or vary past the recount
+//! thresholds back for another round. Approving a reviewed session posts the
+//! variances to inventory as count adjustments; shortages go through the
+//! shrinkage module so they show up as missing stock.
+
+use crate::core::money::round_cents;
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
+use crate::database::Database;
+use crate::errors::Result;
+use crate::services::shrinkage::{self, DamageType, NewDamage, ShrinkSource};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Variance (in units) that sends a line back for a recount by default
+pub const DEFAULT_RECOUNT_UNITS: i32 = 3;
+/// Variance (at cost) that sends a line back for a recount by default
+pub const DEFAULT_RECOUNT_VALUE: f64 = 25.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AbcClass {
+    A,
+    B,
+    C,
+}
+
+impl std::fmt::Display for AbcClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbcClass::A => write!(f, "A"),
+            AbcClass::B => write!(f, "B"),
+            AbcClass::C => write!(f, "C"),
+        }
+    }
+}
+
+impl AbcClass {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "A" => Some(AbcClass::A),
+            "B" => Some(AbcClass::B),
+            "C" => Some(AbcClass::C),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CountSessionStatus {
+    Counting,
+    /// Some lines need another round before review
+    Recount,
+    Review,
+    Approved,
+    Cancelled,
+}
+
+impl std::fmt::Display for CountSessionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CountSessionStatus::Counting => write!(f, "Counting"),
+            CountSessionStatus::Recount => write!(f, "Recount"),
+            CountSessionStatus::Review => write!(f, "Review"),
+            CountSessionStatus::Approved => write!(f, "Approved"),
+            CountSessionStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl CountSessionStatus {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Counting" => Some(CountSessionStatus::Counting),
+            "Recount" => Some(CountSessionStatus::Recount),
+            "Review" => Some(CountSessionStatus::Review),
+            "Approved" => Some(CountSessionStatus::Approved),
+            "Cancelled" => Some(CountSessionStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// Sessions that still accept counts
+    pub fn is_counting(&self) -> bool {
+        matches!(
+            self,
+            CountSessionStatus::Counting | CountSessionStatus::Recount
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountPlan {
+    pub plan_uuid: Uuid,
+    pub name: String,
+    pub location_tag: String,
+    /// How often each class is due for a count
+    pub a_interval_days: i64,
+    pub b_interval_days: i64,
+    pub c_interval_days: i64,
+    /// Cumulative share of value that makes up class A, then A+B
+    pub a_share: f64,
+    pub b_share: f64,
+    pub lookback_days: i64,
+    pub recount_units: i32,
+    pub recount_value: f64,
+    pub blind: bool,
+    /// How often the plan generates a session
+    pub run_every_days: i64,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateCountPlanRequest {
+    pub name: String,
+    pub location_tag: String,
+    pub a_interval_days: Option<i64>,
+    pub b_interval_days: Option<i64>,
+    pub c_interval_days: Option<i64>,
+    pub a_share: Option<f64>,
+    pub b_share: Option<f64>,
+    pub lookback_days: Option<i64>,
+    pub recount_units: Option<i32>,
+    pub recount_value: Option<f64>,
+    pub blind: Option<bool>,
+    pub run_every_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductClassification {
+    pub product_uuid: Uuid,
+    pub abc_class: AbcClass,
+    /// Sales value over the lookback plus value on hand
+    pub score: f64,
+    pub on_hand_value: f64,
+    pub sales_value: f64,
+    pub classified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountLine {
+    pub line_uuid: Uuid,
+    pub inventory_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub product_name: Option<String>,
+    pub condition: Option<String>,
+    pub bin_location: Option<String>,
+    pub abc_class: Option<AbcClass>,
+    pub expected_quantity: i32,
+    pub unit_value: f64,
+    pub counted_quantity: Option<i32>,
+    pub variance: Option<i32>,
+    pub variance_value: Option<f64>,
+    pub needs_recount: bool,
+}
+
+/// What a counter sees: no expected quantities on blind counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountSheetLine {
+    pub line_uuid: Uuid,
+    pub inventory_uuid: Uuid,
+    pub product_name: Option<String>,
+    pub condition: Option<String>,
+    pub bin_location: Option<String>,
+    pub expected_quantity: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountSession {
+    pub session_uuid: Uuid,
+    pub plan_uuid: Option<Uuid>,
+    pub location_tag: String,
+    pub bin_location: Option<String>,
+    pub status: CountSessionStatus,
+    pub round: i32,
+    pub blind: bool,
+    pub recount_units: i32,
+    pub recount_value: f64,
+    pub created_by: Option<Uuid>,
+    pub approved_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub lines: Vec<CountLine>,
+    pub net_variance_units: i32,
+    pub net_variance_value: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateCountSessionRequest {
+    pub location_tag: String,
+    pub bin_location: Option<String>,
+    pub blind: Option<bool>,
+    pub recount_units: Option<i32>,
+    pub recount_value: Option<f64>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CountEntryRequest {
+    pub inventory_uuid: Uuid,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClassAccuracy {
+    pub lines: i64,
+    pub accurate_lines: i64,
+    pub accuracy_pct: f64,
+    pub abs_variance_value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountAccuracyReport {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub sessions: i64,
+    pub lines_counted: i64,
+    pub accurate_lines: i64,
+    /// Share of lines whose count matched the system quantity
+    pub line_accuracy_pct: f64,
+    pub units_expected: i64,
+    pub net_variance_units: i64,
+    pub abs_variance_units: i64,
+    pub expected_value: f64,
+    pub net_variance_value: f64,
+    pub abs_variance_value: f64,
+    /// 100% less absolute variance as a share of expected value
+    pub value_accuracy_pct: f64,
+    /// Share of lines that needed more than one round
+    pub recount_rate_pct: f64,
+    pub by_class: BTreeMap<String, ClassAccuracy>,
+}
+
+/// Rank by score and split at the cumulative value shares: products making up
+/// the first `a_share` of total value are A, up to `b_share` B, the rest C.
+/// Products with no score are always C.
+pub fn classify_abc(scores: &[(Uuid, f64)], a_share: f64, b_share: f64) -> Vec<(Uuid, AbcClass)> {
+    let total: f64 = scores.iter().map(|(_, s)| s.max(0.0)).sum();
+    let mut ranked: Vec<&(Uuid, f64)> = scores.iter().collect();
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut cumulative = 0.0;
+    ranked
+        .into_iter()
+        .map(|(product_uuid, score)| {
+            let class = if total <= 0.0 || *score <= 0.0 {
+                AbcClass::C
+            } else if cumulative / total < a_share {
+                AbcClass::A
+            } else if cumulative / total < b_share {
+                AbcClass::B
+            } else {
+                AbcClass::C
+            };
+            cumulative += score.max(0.0);
+            (*product_uuid, class)
+        })
+        .collect()
+}
+
+/// The quantity a round settles on, if every counter agrees
+fn consensus(quantities: &[i32]) -> Option<i32> {
+    let first = *quantities.first()?;
+    quantities.iter().all(|q| *q == first).then_some(first)
+}
+
+fn pct(part: f64, whole: f64) -> f64 {
+    if whole > 0.0 {
+        round_cents(part / whole * 100.0)
+    } else {
+        100.0
+    }
+}
+
+#[derive(Clone)]
+pub struct CycleCountService {
+    db: Arc<Database>,
+}
+
+impl CycleCountService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    // ---- Plans and classification ----
+
+    pub async fn create_plan(&self, request: CreateCountPlanRequest) -> Result<CountPlan> {
+        if request.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Plan name is required"));
+        }
+        let a_share = request.a_share.unwrap_or(0.8);
+        let b_share = request.b_share.unwrap_or(0.95);
+        if !(0.0 < a_share && a_share <= b_share && b_share <= 1.0) {
+            return Err(anyhow::anyhow!("Class shares must satisfy 0 < A <= B <= 1"));
+        }
+        let intervals = [
+            request.a_interval_days.unwrap_or(30),
+            request.b_interval_days.unwrap_or(90),
+            request.c_interval_days.unwrap_or(180),
+            request.lookback_days.unwrap_or(90),
+            request.run_every_days.unwrap_or(7),
+        ];
+        if intervals.iter().any(|d| *d <= 0) {
+            return Err(anyhow::anyhow!("Intervals must be at least one day"));
+        }
+
+        let plan_uuid = Uuid::new_v4();
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO Count_Plans
+             (plan_uuid, name, location_tag, a_interval_days, b_interval_days, c_interval_days,
+              a_share, b_share, lookback_days, recount_units, recount_value, blind,
+              run_every_days, next_run_at, is_active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?)",
+        )
+        .bind(plan_uuid.to_string())
+        .bind(request.name.trim())
+        .bind(&request.location_tag)
+        .bind(intervals[0])
+        .bind(intervals[1])
+        .bind(intervals[2])
+        .bind(a_share)
+        .bind(b_share)
+        .bind(intervals[3])
+        .bind(request.recount_units.unwrap_or(DEFAULT_RECOUNT_UNITS))
+        .bind(request.recount_value.unwrap_or(DEFAULT_RECOUNT_VALUE))
+        .bind(request.blind.unwrap_or(true))
+        .bind(intervals[4])
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create count plan: {}", e))?;
+
+        self.get_plan(plan_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Count plan {} not found", plan_uuid))
+    }
+
+    pub async fn get_plan(&self, plan_uuid: Uuid) -> Result<Option<CountPlan>> {
+        let row = sqlx::query("SELECT * FROM Count_Plans WHERE plan_uuid = ?")
+            .bind(plan_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(row.as_ref().and_then(map_plan))
+    }
+
+    pub async fn get_plans(&self) -> Result<Vec<CountPlan>> {
+        let rows = sqlx::query("SELECT * FROM Count_Plans WHERE is_active = 1 ORDER BY name")
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().filter_map(map_plan).collect())
+    }
+
+    /// Re-rank every product with stock or recent sales and store its class
+    pub async fn classify_products(
+        &self,
+        lookback_days: i64,
+        a_share: f64,
+        b_share: f64,
+    ) -> Result<Vec<ProductClassification>> {
+        let since = (Utc::now() - Duration::days(lookback_days)).to_rfc3339();
+        let rows: Vec<(String, f64, f64)> = sqlx::query_as(
+            "SELECT p.product_uuid,
+                    COALESCE((SELECT SUM(li.quantity_on_hand * COALESCE(li.cost_basis, li.specific_price, 0.0))
+                              FROM Local_Inventory li
+                              WHERE li.product_uuid = p.product_uuid AND li.deleted_at IS NULL), 0.0) AS on_hand_value,
+                    COALESCE((SELECT SUM(ti.quantity * ti.unit_price)
+                              FROM Transaction_Items ti
+                              JOIN Transactions t ON t.transaction_uuid = ti.transaction_uuid
+                              WHERE ti.product_uuid = p.product_uuid AND t.transaction_type = 'Sale'
+                                AND t.voided_at IS NULL AND t.timestamp >= ?), 0.0) AS sales_value
+             FROM (SELECT DISTINCT product_uuid FROM Local_Inventory WHERE deleted_at IS NULL) p",
+        )
+        .bind(&since)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut values: HashMap<Uuid, (f64, f64)> = HashMap::new();
+        let mut scores = Vec::with_capacity(rows.len());
+        for (product_uuid, on_hand_value, sales_value) in rows {
+            let Ok(product_uuid) = Uuid::parse_str(&product_uuid) else {
+                continue;
+            };
+            let on_hand_value = on_hand_value.max(0.0);
+            let sales_value = sales_value.max(0.0);
+            values.insert(product_uuid, (on_hand_value, sales_value));
+            scores.push((product_uuid, on_hand_value + sales_value));
+        }
+
+        let now = Utc::now();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let mut classifications = Vec::with_capacity(scores.len());
+        for (product_uuid, abc_class) in classify_abc(&scores, a_share, b_share) {
+            let (on_hand_value, sales_value) = values[&product_uuid];
+            let classification = ProductClassification {
+                product_uuid,
+                abc_class,
+                score: round_cents(on_hand_value + sales_value),
+                on_hand_value: round_cents(on_hand_value),
+                sales_value: round_cents(sales_value),
+                classified_at: now,
+            };
+            sqlx::query(
+                "INSERT OR REPLACE INTO Product_ABC_Classes
+                 (product_uuid, abc_class, score, on_hand_value, sales_value, classified_at)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(product_uuid.to_string())
+            .bind(abc_class.to_string())
+            .bind(classification.score)
+            .bind(classification.on_hand_value)
+            .bind(classification.sales_value)
+            .bind(now.to_rfc3339())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to store classification: {}", e))?;
+            classifications.push(classification);
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        Ok(classifications)
+    }
+
+    pub async fn get_classifications(&self) -> Result<Vec<ProductClassification>> {
+        let rows = sqlx::query("SELECT * FROM Product_ABC_Classes ORDER BY score DESC")
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().filter_map(map_classification).collect())
+    }
+
+    /// Classify, then open a session for the plan's piles that are due.
+    /// Returns `None` when nothing is due.
+    pub async fn run_plan(
+        &self,
+        plan_uuid: Uuid,
+        created_by: Option<Uuid>,
+    ) -> Result<Option<CountSession>> {
+        let plan = self
+            .get_plan(plan_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Count plan {} not found", plan_uuid))?;
+
+        self.classify_products(plan.lookback_days, plan.a_share, plan.b_share)
+            .await?;
+
+        let now = Utc::now();
+        let due_before = |days: i64| (now - Duration::days(days)).to_rfc3339();
+        let due: Vec<String> = sqlx::query_scalar(
+            "SELECT li.inventory_uuid
+             FROM Local_Inventory li
+             LEFT JOIN Product_ABC_Classes c ON c.product_uuid = li.product_uuid
+             WHERE li.location_tag = ? AND li.deleted_at IS NULL
+               AND (li.last_counted_date IS NULL OR li.last_counted_date <=
+                    CASE COALESCE(c.abc_class, 'C') WHEN 'A' THEN ? WHEN 'B' THEN ? ELSE ? END)
+               AND li.inventory_uuid NOT IN (
+                   SELECT l.inventory_uuid FROM Count_Session_Lines l
+                   JOIN Count_Sessions s ON s.session_uuid = l.session_uuid
+                   WHERE s.status IN ('Counting', 'Recount', 'Review'))",
+        )
+        .bind(&plan.location_tag)
+        .bind(due_before(plan.a_interval_days))
+        .bind(due_before(plan.b_interval_days))
+        .bind(due_before(plan.c_interval_days))
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let session = if due.is_empty() {
+            None
+        } else {
+            let settings = SessionSettings {
+                plan_uuid: Some(plan.plan_uuid),
+                location_tag: plan.location_tag.clone(),
+                bin_location: None,
+                blind: plan.blind,
+                recount_units: plan.recount_units,
+                recount_value: plan.recount_value,
+                notes: Some(format!("Scheduled by plan '{}'", plan.name)),
+            };
+            Some(self.open_session(settings, &due, created_by).await?)
+        };
+
+        sqlx::query("UPDATE Count_Plans SET last_run_at = ?, next_run_at = ? WHERE plan_uuid = ?")
+            .bind(now.to_rfc3339())
+            .bind((now + Duration::days(plan.run_every_days)).to_rfc3339())
+            .bind(plan_uuid.to_string())
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(session)
+    }
+
+    /// Run every active plan whose next run has come round
+    pub async fn run_due_plans(&self) -> Result<Vec<Uuid>> {
+        let due: Vec<String> = sqlx::query_scalar(
+            "SELECT plan_uuid FROM Count_Plans WHERE is_active = 1 AND next_run_at <= ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut sessions = Vec::new();
+        for plan_uuid in due.iter().filter_map(|s| Uuid::parse_str(s).ok()) {
+            if let Some(session) = self.run_plan(plan_uuid, None).await? {
+                sessions.push(session.session_uuid);
+            }
+        }
+        if !sessions.is_empty() {
+            tracing::info!("Opened {} scheduled count sessions", sessions.len());
+        }
+        Ok(sessions)
+    }
+
+    // ---- Sessions ----
+
+    /// Open an ad-hoc session for every pile at a location (or one bin)
+    pub async fn create_session(
+        &self,
+        request: CreateCountSessionRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<CountSession> {
+        let piles: Vec<String> = sqlx::query_scalar(
+            "SELECT inventory_uuid FROM Local_Inventory
+             WHERE location_tag = ? AND deleted_at IS NULL AND (? IS NULL OR bin_location = ?)",
+        )
+        .bind(&request.location_tag)
+        .bind(&request.bin_location)
+        .bind(&request.bin_location)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        if piles.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No inventory at {}{} to count",
+                request.location_tag,
+                request
+                    .bin_location
+                    .as_deref()
+                    .map(|b| format!(" / {}", b))
+                    .unwrap_or_default()
+            ));
+        }
+
+        let settings = SessionSettings {
+            plan_uuid: None,
+            location_tag: request.location_tag,
+            bin_location: request.bin_location,
+            blind: request.blind.unwrap_or(true),
+            recount_units: request.recount_units.unwrap_or(DEFAULT_RECOUNT_UNITS),
+            recount_value: request.recount_value.unwrap_or(DEFAULT_RECOUNT_VALUE),
+            notes: request.notes,
+        };
+        self.open_session(settings, &piles, created_by).await
+    }
+
+    async fn open_session(
+        &self,
+        settings: SessionSettings,
+        inventory_uuids: &[String],
+        created_by: Option<Uuid>,
+    ) -> Result<CountSession> {
+        let session_uuid = Uuid::new_v4();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        sqlx::query(
+            "INSERT INTO Count_Sessions
+             (session_uuid, plan_uuid, location_tag, bin_location, status, round, blind,
+              recount_units, recount_value, created_by, created_at, notes)
+             VALUES (?, ?, ?, ?, 'Counting', 1, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(session_uuid.to_string())
+        .bind(settings.plan_uuid.map(|u| u.to_string()))
+        .bind(&settings.location_tag)
+        .bind(&settings.bin_location)
+        .bind(settings.blind)
+        .bind(settings.recount_units)
+        .bind(settings.recount_value)
+        .bind(created_by.map(|u| u.to_string()))
+        .bind(Utc::now().to_rfc3339())
+        .bind(&settings.notes)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create count session: {}", e))?;
+
+        // Snapshot what the system expects each pile to hold right now
+        for inventory_uuid in inventory_uuids {
+            sqlx::query(
+                "INSERT INTO Count_Session_Lines
+                 (line_uuid, session_uuid, inventory_uuid, product_uuid, abc_class, expected_quantity, unit_value)
+                 SELECT ?, ?, li.inventory_uuid, li.product_uuid, c.abc_class, li.quantity_on_hand,
+                        COALESCE(li.cost_basis, li.specific_price, 0.0)
+                 FROM Local_Inventory li
+                 LEFT JOIN Product_ABC_Classes c ON c.product_uuid = li.product_uuid
+                 WHERE li.inventory_uuid = ?",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(session_uuid.to_string())
+            .bind(inventory_uuid)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to add count line: {}", e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.get_session(session_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Count session {} not found", session_uuid))
+    }
+
+    pub async fn get_session(&self, session_uuid: Uuid) -> Result<Option<CountSession>> {
+        let row = sqlx::query("SELECT * FROM Count_Sessions WHERE session_uuid = ?")
+            .bind(session_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let line_rows = sqlx::query(
+            "SELECT l.*, g.name AS product_name, li.condition, li.bin_location
+             FROM Count_Session_Lines l
+             LEFT JOIN Local_Inventory li ON li.inventory_uuid = l.inventory_uuid
+             LEFT JOIN Global_Catalog g ON g.product_uuid = l.product_uuid
+             WHERE l.session_uuid = ?
+             ORDER BY li.bin_location, g.name, l.inventory_uuid",
+        )
+        .bind(session_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let lines: Vec<CountLine> = line_rows.iter().filter_map(map_line).collect();
+        Ok(map_session(&row, lines))
+    }
+
+    pub async fn get_sessions(
+        &self,
+        status: Option<CountSessionStatus>,
+    ) -> Result<Vec<CountSession>> {
+        let uuids: Vec<String> = sqlx::query_scalar(
+            "SELECT session_uuid FROM Count_Sessions
+             WHERE (? IS NULL OR status = ?)
+             ORDER BY created_at DESC",
+        )
+        .bind(status.map(|s| s.to_string()))
+        .bind(status.map(|s| s.to_string()))
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut sessions = Vec::with_capacity(uuids.len());
+        for uuid in uuids.iter().filter_map(|s| Uuid::parse_str(s).ok()) {
+            if let Some(session) = self.get_session(uuid).await? {
+                sessions.push(session);
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// The lines still to be counted this round, without expected
+    /// quantities when the session is blind
+    pub async fn get_count_sheet(&self, session_uuid: Uuid) -> Result<Vec<CountSheetLine>> {
+        let session = self.require_session(session_uuid).await?;
+        let recounting = session.status == CountSessionStatus::Recount;
+        Ok(session
+            .lines
+            .into_iter()
+            .filter(|l| !recounting || l.needs_recount)
+            .map(|l| CountSheetLine {
+                line_uuid: l.line_uuid,
+                inventory_uuid: l.inventory_uuid,
+                product_name: l.product_name,
+                condition: l.condition,
+                bin_location: l.bin_location,
+                expected_quantity: (!session.blind).then_some(l.expected_quantity),
+            })
+            .collect())
+    }
+
+    /// Record one counter's quantities for the current round. A counter who
+    /// submits the same pile twice in a round replaces their earlier count.
+    pub async fn record_counts(
+        &self,
+        session_uuid: Uuid,
+        counted_by: Option<Uuid>,
+        entries: Vec<CountEntryRequest>,
+    ) -> Result<CountSession> {
+        let session = self.require_session(session_uuid).await?;
+        if !session.status.is_counting() {
+            return Err(anyhow::anyhow!(
+                "Session is {} and no longer accepts counts",
+                session.status
+            ));
+        }
+
+        let lines: HashMap<Uuid, &CountLine> = session
+            .lines
+            .iter()
+            .map(|l| (l.inventory_uuid, l))
+            .collect();
+        let counter = counted_by.map(|u| u.to_string()).unwrap_or_default();
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        for entry in &entries {
+            if entry.quantity < 0 {
+                return Err(anyhow::anyhow!("Counted quantities cannot be negative"));
+            }
+            let line = lines.get(&entry.inventory_uuid).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Inventory {} is not part of this count",
+                    entry.inventory_uuid
+                )
+            })?;
+            if session.status == CountSessionStatus::Recount && !line.needs_recount {
+                return Err(anyhow::anyhow!(
+                    "Inventory {} does not need a recount",
+                    entry.inventory_uuid
+                ));
+            }
+
+            sqlx::query(
+                "DELETE FROM Count_Entries WHERE line_uuid = ? AND round = ? AND counted_by = ?",
+            )
+            .bind(line.line_uuid.to_string())
+            .bind(session.round)
+            .bind(&counter)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            sqlx::query(
+                "INSERT INTO Count_Entries (entry_uuid, line_uuid, session_uuid, round, counted_by, quantity, counted_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(line.line_uuid.to_string())
+            .bind(session_uuid.to_string())
+            .bind(session.round)
+            .bind(&counter)
+            .bind(entry.quantity)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to record count: {}", e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        self.require_session(session_uuid).await
+    }
+
+    /// Close the current round. Lines where counters disagree, or where the
+    /// first count varies from the system by the recount threshold, go back
+    /// for another round; otherwise the session moves to review. Piles
+    /// nobody counted are taken as zero.
+    pub async fn finish_round(&self, session_uuid: Uuid) -> Result<CountSession> {
+        let session = self.require_session(session_uuid).await?;
+        if !session.status.is_counting() {
+            return Err(anyhow::anyhow!("Session is {}", session.status));
+        }
+
+        let entries: Vec<(String, i32)> = sqlx::query_as(
+            "SELECT line_uuid, quantity FROM Count_Entries WHERE session_uuid = ? AND round = ?",
+        )
+        .bind(session_uuid.to_string())
+        .bind(session.round)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let mut by_line: HashMap<String, Vec<i32>> = HashMap::new();
+        for (line_uuid, quantity) in entries {
+            by_line.entry(line_uuid).or_default().push(quantity);
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let mut any_recount = false;
+        for line in &session.lines {
+            if session.status == CountSessionStatus::Recount && !line.needs_recount {
+                continue;
+            }
+            let counts = by_line
+                .remove(&line.line_uuid.to_string())
+                .unwrap_or_else(|| vec![0]);
+            let counted = consensus(&counts);
+            let needs_recount = match counted {
+                None => true,
+                Some(quantity) if session.round == 1 => {
+                    let variance = quantity - line.expected_quantity;
+                    (session.recount_units > 0 && variance.abs() >= session.recount_units)
+                        || (session.recount_value > 0.0
+                            && (variance as f64 * line.unit_value).abs() >= session.recount_value)
+                }
+                Some(_) => false,
+            };
+            any_recount |= needs_recount;
+
+            sqlx::query(
+                "UPDATE Count_Session_Lines
+                 SET counted_quantity = ?, variance = ? - expected_quantity, needs_recount = ?,
+                     rounds = ?
+                 WHERE line_uuid = ?",
+            )
+            .bind(counted)
+            .bind(counted)
+            .bind(needs_recount)
+            .bind(session.round)
+            .bind(line.line_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        }
+
+        if any_recount {
+            sqlx::query(
+                "UPDATE Count_Sessions SET status = 'Recount', round = round + 1 WHERE session_uuid = ?",
+            )
+            .bind(session_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        } else {
+            sqlx::query(
+                "UPDATE Count_Sessions SET status = 'Review', reviewed_at = ? WHERE session_uuid = ?",
+            )
+            .bind(Utc::now().to_rfc3339())
+            .bind(session_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        self.require_session(session_uuid).await
+    }
+
+    /// Post every line's variance to inventory. Variances are applied as
+    /// deltas, so sales made while the count was running are kept.
+    pub async fn approve(
+        &self,
+        session_uuid: Uuid,
+        approved_by: Option<Uuid>,
+    ) -> Result<CountSession> {
+        let session = self.require_session(session_uuid).await?;
+        if session.status != CountSessionStatus::Review {
+            return Err(anyhow::anyhow!(
+                "Only sessions in review can be approved (this one is {})",
+                session.status
+            ));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let source = MovementSource::new(
+            MovementType::CountAdjustment,
+            Some(session_uuid),
+            approved_by,
+            &self.db.node_id,
+        );
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        // Claim the session first so a concurrent approval can't post the variances twice
+        let claimed = sqlx::query(
+            "UPDATE Count_Sessions SET status = 'Approved', approved_by = ?, approved_at = ?
+             WHERE session_uuid = ? AND status = 'Review'",
+        )
+        .bind(approved_by.map(|u| u.to_string()))
+        .bind(&now)
+        .bind(session_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if claimed.rows_affected() == 0 {
+            return Err(anyhow::anyhow!(
+                "Count session {} is no longer in review",
+                session_uuid
+            ));
+        }
+
+        for line in &session.lines {
+            match line.variance {
+                // Shortages are shrink: write them off as missing stock at the counted cost
+                Some(variance) if variance < 0 => {
+                    shrinkage::record_damage_with_tx(
+                        &mut tx,
+                        NewDamage {
+                            inventory_uuid: line.inventory_uuid,
+                            quantity: -variance,
+                            damage_type: DamageType::Missing,
+                            description: Some(format!(
+                                "Cycle count: expected {}, counted {}",
+                                line.expected_quantity,
+                                line.counted_quantity.unwrap_or_default()
+                            )),
+                            source: ShrinkSource::Count,
+                            source_uuid: Some(session_uuid),
+                            reported_by: approved_by,
+                            original_value: Some(round_cents(-variance as f64 * line.unit_value)),
+                            terminal_id: self.db.node_id.clone(),
+                        },
+                        true,
+                    )
+                    .await?;
+                }
+                Some(variance) if variance > 0 => {
+                    movements::adjust_quantity_with_tx(
+                        &mut tx,
+                        line.inventory_uuid,
+                        variance,
+                        &source,
+                    )
+                    .await?;
+                }
+                _ => {}
+            }
+            sqlx::query(
+                "UPDATE Local_Inventory SET last_counted_date = ? WHERE inventory_uuid = ?",
+            )
+            .bind(&now)
+            .bind(line.inventory_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!(
+            "Approved count session {}: {} units, ${:.2} variance",
+            session_uuid,
+            session.net_variance_units,
+            session.net_variance_value
+        );
+        self.require_session(session_uuid).await
+    }
+
+    pub async fn cancel(&self, session_uuid: Uuid) -> Result<CountSession> {
+        let session = self.require_session(session_uuid).await?;
+        if matches!(
+            session.status,
+            CountSessionStatus::Approved | CountSessionStatus::Cancelled
+        ) {
+            return Err(anyhow::anyhow!("Session is already {}", session.status));
+        }
+        sqlx::query("UPDATE Count_Sessions SET status = 'Cancelled' WHERE session_uuid = ?")
+            .bind(session_uuid.to_string())
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        self.require_session(session_uuid).await
+    }
+
+    async fn require_session(&self, session_uuid: Uuid) -> Result<CountSession> {
+        self.get_session(session_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Count session {} not found", session_uuid))
+    }
+
+    // ---- KPIs ----
+
+    /// Accuracy of sessions approved within the period
+    pub async fn get_accuracy_report(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<CountAccuracyReport> {
+        let rows: Vec<(String, Option<String>, i32, i32, f64, i32)> = sqlx::query_as(
+            "SELECT l.session_uuid, l.abc_class, l.expected_quantity, COALESCE(l.variance, 0),
+                    l.unit_value, l.rounds
+             FROM Count_Session_Lines l
+             JOIN Count_Sessions s ON s.session_uuid = l.session_uuid
+             WHERE s.status = 'Approved' AND s.approved_at >= ? AND s.approved_at <= ?",
+        )
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut report = CountAccuracyReport {
+            period_start: start,
+            period_end: end,
+            sessions: 0,
+            lines_counted: 0,
+            accurate_lines: 0,
+            line_accuracy_pct: 100.0,
+            units_expected: 0,
+            net_variance_units: 0,
+            abs_variance_units: 0,
+            expected_value: 0.0,
+            net_variance_value: 0.0,
+            abs_variance_value: 0.0,
+            value_accuracy_pct: 100.0,
+            recount_rate_pct: 0.0,
+            by_class: BTreeMap::new(),
+        };
+        let mut sessions = std::collections::HashSet::new();
+        let mut recounted = 0;
+
+        for (session_uuid, abc_class, expected, variance, unit_value, rounds) in rows {
+            sessions.insert(session_uuid);
+            let variance_value = variance as f64 * unit_value;
+            report.lines_counted += 1;
+            report.units_expected += expected as i64;
+            report.net_variance_units += variance as i64;
+            report.abs_variance_units += variance.abs() as i64;
+            report.expected_value += expected as f64 * unit_value;
+            report.net_variance_value += variance_value;
+            report.abs_variance_value += variance_value.abs();
+            if variance == 0 {
+                report.accurate_lines += 1;
+            }
+            if rounds > 1 {
+                recounted += 1;
+            }
+
+            let class = report
+                .by_class
+                .entry(abc_class.unwrap_or_else(|| "Unclassified".to_string()))
+                .or_default();
+            class.lines += 1;
+            if variance == 0 {
+                class.accurate_lines += 1;
+            }
+            class.abs_variance_value += variance_value.abs();
+        }
+
+        report.sessions = sessions.len() as i64;
+        report.line_accuracy_pct = pct(report.accurate_lines as f64, report.lines_counted as f64);
+        report.value_accuracy_pct = if report.expected_value > 0.0 {
+            round_cents(
+                (100.0 - report.abs_variance_value / report.expected_value * 100.0).max(0.0),
+            )
+        } else {
+            100.0
+        };
+        report.recount_rate_pct = if report.lines_counted > 0 {
+            pct(recounted as f64, report.lines_counted as f64)
+        } else {
+            0.0
+        };
+        report.expected_value = round_cents(report.expected_value);
+        report.net_variance_value = round_cents(report.net_variance_value);
+        report.abs_variance_value = round_cents(report.abs_variance_value);
+        for class in report.by_class.values_mut() {
+            class.accuracy_pct = pct(class.accurate_lines as f64, class.lines as f64);
+            class.abs_variance_value = round_cents(class.abs_variance_value);
+        }
+        Ok(report)
+    }
+}
+
+/// Header fields shared by ad-hoc and scheduled sessions
+struct SessionSettings {
+    plan_uuid: Option<Uuid>,
+    location_tag: String,
+    bin_location: Option<String>,
+    blind: bool,
+    recount_units: i32,
+    recount_value: f64,
+    notes: Option<String>,
+}
+
+fn parse_date(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<DateTime<Utc>> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn parse_uuid(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<Uuid> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| Uuid::parse_str(&s).ok())
+}
+
+fn map_plan(row: &sqlx::sqlite::SqliteRow) -> Option<CountPlan> {
+    use sqlx::Row;
+    Some(CountPlan {
+        plan_uuid: parse_uuid(row, "plan_uuid")?,
+        name: row.try_get("name").ok()?,
+        location_tag: row.try_get("location_tag").ok()?,
+        a_interval_days: row.try_get("a_interval_days").ok()?,
+        b_interval_days: row.try_get("b_interval_days").ok()?,
+        c_interval_days: row.try_get("c_interval_days").ok()?,
+        a_share: row.try_get("a_share").ok()?,
+        b_share: row.try_get("b_share").ok()?,
+        lookback_days: row.try_get("lookback_days").ok()?,
+        recount_units: row.try_get("recount_units").ok()?,
+        recount_value: row.try_get("recount_value").ok()?,
+        blind: row.try_get("blind").unwrap_or(true),
+        run_every_days: row.try_get("run_every_days").ok()?,
+        next_run_at: parse_date(row, "next_run_at")?,
+        last_run_at: parse_date(row, "last_run_at"),
+        is_active: row.try_get("is_active").unwrap_or(true),
+        created_at: parse_date(row, "created_at")?,
+    })
+}
+
+fn map_classification(row: &sqlx::sqlite::SqliteRow) -> Option<ProductClassification> {
+    use sqlx::Row;
+    let class: String = row.try_get("abc_class").ok()?;
+    Some(ProductClassification {
+        product_uuid: parse_uuid(row, "product_uuid")?,
+        abc_class: AbcClass::parse(&class)?,
+        score: row.try_get("score").unwrap_or(0.0),
+        on_hand_value: row.try_get("on_hand_value").unwrap_or(0.0),
+        sales_value: row.try_get("sales_value").unwrap_or(0.0),
+        classified_at: parse_date(row, "classified_at")?,
+    })
+}
+
+fn map_line(row: &sqlx::sqlite::SqliteRow) -> Option<CountLine> {
+    use sqlx::Row;
+    let unit_value: f64 = row.try_get("unit_value").unwrap_or(0.0);
+    let variance: Option<i32> = row.try_get("variance").ok().flatten();
+    Some(CountLine {
+        line_uuid: parse_uuid(row, "line_uuid")?,
+        inventory_uuid: parse_uuid(row, "inventory_uuid")?,
+        product_uuid: parse_uuid(row, "product_uuid")?,
+        product_name: row.try_get("product_name").ok().flatten(),
+        condition: row.try_get("condition").ok().flatten(),
+        bin_location: row.try_get("bin_location").ok().flatten(),
+        abc_class: row
+            .try_get::<Option<String>, _>("abc_class")
+            .ok()
+            .flatten()
+            .and_then(|s| AbcClass::parse(&s)),
+        expected_quantity: row.try_get("expected_quantity").ok()?,
+        unit_value,
+        counted_quantity: row.try_get("counted_quantity").ok().flatten(),
+        variance,
+        variance_value: variance.map(|v| round_cents(v as f64 * unit_value)),
+        needs_recount: row.try_get("needs_recount").unwrap_or(false),
+    })
+}
+
+fn map_session(row: &sqlx::sqlite::SqliteRow, lines: Vec<CountLine>) -> Option<CountSession> {
+    use sqlx::Row;
+    let status: String = row.try_get("status").ok()?;
+    let net_variance_units = lines.iter().filter_map(|l| l.variance).sum();
+    let net_variance_value = round_cents(lines.iter().filter_map(|l| l.variance_value).sum());
+    Some(CountSession {
+        session_uuid: parse_uuid(row, "session_uuid")?,
+        plan_uuid: parse_uuid(row, "plan_uuid"),
+        location_tag: row.try_get("location_tag").ok()?,
+        bin_location: row.try_get("bin_location").ok().flatten(),
+        status: CountSessionStatus::parse(&status)?,
+        round: row.try_get("round").unwrap_or(1),
+        blind: row.try_get("blind").unwrap_or(true),
+        recount_units: row
+            .try_get("recount_units")
+            .unwrap_or(DEFAULT_RECOUNT_UNITS),
+        recount_value: row
+            .try_get("recount_value")
+            .unwrap_or(DEFAULT_RECOUNT_VALUE),
+        created_by: parse_uuid(row, "created_by"),
+        approved_by: parse_uuid(row, "approved_by"),
+        created_at: parse_date(row, "created_at")?,
+        reviewed_at: parse_date(row, "reviewed_at"),
+        approved_at: parse_date(row, "approved_at"),
+        notes: row.try_get("notes").ok().flatten(),
+        lines,
+        net_variance_units,
+        net_variance_value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_abc_by_cumulative_share() {
+        let ids: Vec<Uuid> = (0..5).map(|_| Uuid::new_v4()).collect();
+        // 100 total: 70 | 20 | 6 | 4 | 0
+        let scores = vec![
+            (ids[3], 4.0),
+            (ids[0], 70.0),
+            (ids[4], 0.0),
+            (ids[2], 6.0),
+            (ids[1], 20.0),
+        ];
+        let classes: HashMap<Uuid, AbcClass> =
+            classify_abc(&scores, 0.8, 0.95).into_iter().collect();
+
+        assert_eq!(classes[&ids[0]], AbcClass::A);
+        // Starts at 70% of value, still inside the A band
+        assert_eq!(classes[&ids[1]], AbcClass::A);
+        assert_eq!(classes[&ids[2]], AbcClass::B);
+        assert_eq!(classes[&ids[3]], AbcClass::C);
+        assert_eq!(classes[&ids[4]], AbcClass::C);
+
+        assert!(classify_abc(&[(ids[0], 0.0)], 0.8, 0.95)
+            .iter()
+            .all(|(_, c)| *c == AbcClass::C));
+    }
+
+    #[test]
+    fn test_consensus_requires_agreement() {
+        assert_eq!(consensus(&[4, 4, 4]), Some(4));
+        assert_eq!(consensus(&[4, 5]), None);
+        assert_eq!(consensus(&[]), None);
+    }
+}
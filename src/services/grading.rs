@@ -0,0 +1,1269 @@
+//! Grading submissions
+//!
+//! Raw cards go to PSA, BGS, CGC or SGC in batches. Creating a submission
+//! pulls one unit per line out of its pile and parks it under the
+//! `AT_GRADER` location tag, so it stays on the books but is no longer
+//! offered for sale. The submission then moves through its milestones
+//! (Preparing → Shipped → Received → Grading → Returned), each recorded with
+//! who logged it and any note.
+//!
+//! When the cards come back every line is resolved. Graded cards become
+//! serialized piles carrying the grade and cert number; ungradable ones go
+//! back to the shelf raw. Either way the line's share of the grading spend
+//! (the tier fee, shipping and insurance split evenly, plus any upcharge) is
+//! added to the unit's cost basis. The submission completes once every line
+//! is resolved.
+
+use crate::core::money::round_cents;
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
+use crate::database::Database;
+use crate::errors::Result;
+use crate::services::serialized_inventory::{GradingInfo, SubGrades};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Location tag for cards out with a grading company
+pub const AT_GRADER_LOCATION: &str = "AT_GRADER";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GradingCompany {
+    #[serde(rename = "PSA")]
+    Psa,
+    #[serde(rename = "BGS")]
+    Bgs,
+    #[serde(rename = "CGC")]
+    Cgc,
+    #[serde(rename = "SGC")]
+    Sgc,
+}
+
+impl GradingCompany {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GradingCompany::Psa => "PSA",
+            GradingCompany::Bgs => "BGS",
+            GradingCompany::Cgc => "CGC",
+            GradingCompany::Sgc => "SGC",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "PSA" => Some(GradingCompany::Psa),
+            "BGS" | "BECKETT" => Some(GradingCompany::Bgs),
+            "CGC" => Some(GradingCompany::Cgc),
+            "SGC" => Some(GradingCompany::Sgc),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for GradingCompany {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubmissionStatus {
+    /// Items pulled and being packed
+    Preparing,
+    Shipped,
+    /// Grader has logged the package in
+    Received,
+    Grading,
+    /// Back in the store, awaiting results entry
+    Returned,
+    Completed,
+    Cancelled,
+}
+
+impl std::fmt::Display for SubmissionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmissionStatus::Preparing => write!(f, "Preparing"),
+            SubmissionStatus::Shipped => write!(f, "Shipped"),
+            SubmissionStatus::Received => write!(f, "Received"),
+            SubmissionStatus::Grading => write!(f, "Grading"),
+            SubmissionStatus::Returned => write!(f, "Returned"),
+            SubmissionStatus::Completed => write!(f, "Completed"),
+            SubmissionStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl SubmissionStatus {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Preparing" => Some(SubmissionStatus::Preparing),
+            "Shipped" => Some(SubmissionStatus::Shipped),
+            "Received" => Some(SubmissionStatus::Received),
+            "Grading" => Some(SubmissionStatus::Grading),
+            "Returned" => Some(SubmissionStatus::Returned),
+            "Completed" => Some(SubmissionStatus::Completed),
+            "Cancelled" => Some(SubmissionStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// Position in the milestone sequence
+    fn rank(&self) -> u8 {
+        match self {
+            SubmissionStatus::Preparing => 0,
+            SubmissionStatus::Shipped => 1,
+            SubmissionStatus::Received => 2,
+            SubmissionStatus::Grading => 3,
+            SubmissionStatus::Returned => 4,
+            SubmissionStatus::Completed | SubmissionStatus::Cancelled => 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemOutcome {
+    Pending,
+    Graded,
+    /// Returned without a grade (altered, too large, authentic only, ...)
+    Ungradable,
+}
+
+impl ItemOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemOutcome::Pending => "pending",
+            ItemOutcome::Graded => "graded",
+            ItemOutcome::Ungradable => "ungradable",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(ItemOutcome::Pending),
+            "graded" => Some(ItemOutcome::Graded),
+            "ungradable" => Some(ItemOutcome::Ungradable),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionItem {
+    pub item_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub product_name: Option<String>,
+    /// Pile the unit was pulled from
+    pub source_inventory_uuid: Uuid,
+    /// The unit's own pile while at the grader, and afterwards
+    pub inventory_uuid: Uuid,
+    pub source_location: String,
+    pub declared_value: f64,
+    /// Unit cost before grading
+    pub raw_cost: Option<f64>,
+    pub outcome: ItemOutcome,
+    pub grade: Option<String>,
+    pub cert_number: Option<String>,
+    pub sub_grades: Option<SubGrades>,
+    pub upcharge: f64,
+    pub graded_value: Option<f64>,
+    /// Fees added to the unit's cost basis
+    pub grading_cost: Option<f64>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionEvent {
+    pub status: SubmissionStatus,
+    pub notes: Option<String>,
+    pub user_uuid: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradingSubmission {
+    pub submission_uuid: Uuid,
+    pub submission_number: String,
+    pub grader: GradingCompany,
+    pub service_tier: String,
+    /// The grader's own order number
+    pub grader_reference: Option<String>,
+    pub status: SubmissionStatus,
+    pub fee_per_item: f64,
+    pub shipping_cost: f64,
+    pub insurance_cost: f64,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub shipped_at: Option<DateTime<Utc>>,
+    pub returned_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub items: Vec<SubmissionItem>,
+    pub events: Vec<SubmissionEvent>,
+    pub total_declared_value: f64,
+    /// Tier fees, shipping, insurance and upcharges
+    pub total_fees: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSubmissionRequest {
+    pub grader: String,
+    pub service_tier: String,
+    #[serde(default)]
+    pub fee_per_item: f64,
+    #[serde(default)]
+    pub shipping_cost: f64,
+    #[serde(default)]
+    pub insurance_cost: f64,
+    pub grader_reference: Option<String>,
+    pub notes: Option<String>,
+    pub items: Vec<SubmissionItemRequest>,
+}
+
+/// One card; list a pile more than once to send several copies
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmissionItemRequest {
+    pub inventory_uuid: Uuid,
+    pub declared_value: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemResultRequest {
+    pub item_uuid: Uuid,
+    pub outcome: ItemOutcome,
+    pub grade: Option<String>,
+    pub cert_number: Option<String>,
+    pub sub_grades: Option<SubGrades>,
+    /// Extra charged by the grader for this card (e.g. a value upcharge)
+    #[serde(default)]
+    pub upcharge: f64,
+    /// Market value as returned; becomes the item's price
+    pub graded_value: Option<f64>,
+    /// Where the card goes; defaults to where it came from
+    pub location_tag: Option<String>,
+}
+
+/// Return on grading spend for one grader, or all of them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraderRoi {
+    pub grader: String,
+    pub submissions: i64,
+    pub items: i64,
+    pub graded: i64,
+    pub ungradable: i64,
+    pub declared_value: f64,
+    pub grading_fees: f64,
+    /// Graded values, with ungradable cards at their declared value unless
+    /// another was given
+    pub returned_value: f64,
+    /// Returned value less declared value and fees
+    pub value_added: f64,
+    /// Value added per dollar of fees, as a percentage
+    pub roi_percent: Option<f64>,
+    /// Shipped to returned, averaged over submissions
+    pub average_turnaround_days: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeCount {
+    pub grader: String,
+    pub grade: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradingRoiReport {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub totals: GraderRoi,
+    pub by_grader: Vec<GraderRoi>,
+    pub grade_distribution: Vec<GradeCount>,
+}
+
+/// One card's share of a submission's cost
+pub fn grading_cost_per_item(
+    fee_per_item: f64,
+    shipping_cost: f64,
+    insurance_cost: f64,
+    item_count: usize,
+    upcharge: f64,
+) -> f64 {
+    let shared = if item_count > 0 {
+        (shipping_cost + insurance_cost) / item_count as f64
+    } else {
+        0.0
+    };
+    round_cents(fee_per_item + shared + upcharge)
+}
+
+/// A resolved line as the ROI report sees it
+#[derive(Debug, Clone)]
+pub struct ResolvedLine {
+    pub submission_uuid: Uuid,
+    pub outcome: ItemOutcome,
+    pub declared_value: f64,
+    pub grading_cost: f64,
+    pub graded_value: Option<f64>,
+    pub turnaround_days: Option<f64>,
+}
+
+pub fn summarize_roi(grader: &str, lines: &[ResolvedLine]) -> GraderRoi {
+    let mut roi = GraderRoi {
+        grader: grader.to_string(),
+        ..Default::default()
+    };
+    let mut turnarounds: BTreeMap<Uuid, f64> = BTreeMap::new();
+    for line in lines {
+        roi.items += 1;
+        match line.outcome {
+            ItemOutcome::Graded => roi.graded += 1,
+            ItemOutcome::Ungradable => roi.ungradable += 1,
+            ItemOutcome::Pending => {}
+        }
+        roi.declared_value += line.declared_value;
+        roi.grading_fees += line.grading_cost;
+        roi.returned_value += line.graded_value.unwrap_or(line.declared_value);
+        if let Some(days) = line.turnaround_days {
+            turnarounds.insert(line.submission_uuid, days);
+        }
+    }
+    let submissions: std::collections::BTreeSet<Uuid> =
+        lines.iter().map(|l| l.submission_uuid).collect();
+    roi.submissions = submissions.len() as i64;
+    roi.declared_value = round_cents(roi.declared_value);
+    roi.grading_fees = round_cents(roi.grading_fees);
+    roi.returned_value = round_cents(roi.returned_value);
+    roi.value_added = round_cents(roi.returned_value - roi.declared_value - roi.grading_fees);
+    roi.roi_percent = (roi.grading_fees > 0.0)
+        .then(|| (roi.value_added / roi.grading_fees * 10000.0).round() / 100.0);
+    roi.average_turnaround_days = (!turnarounds.is_empty()).then(|| {
+        (turnarounds.values().sum::<f64>() / turnarounds.len() as f64 * 10.0).round() / 10.0
+    });
+    roi
+}
+
+pub struct GradingService {
+    db: Arc<Database>,
+}
+
+impl GradingService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Pull the cards and open a submission
+    pub async fn create_submission(
+        &self,
+        request: CreateSubmissionRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<GradingSubmission> {
+        let grader = GradingCompany::parse(&request.grader).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown grader '{}'; use PSA, BGS, CGC or SGC",
+                request.grader
+            )
+        })?;
+        if request.service_tier.trim().is_empty() {
+            return Err(anyhow::anyhow!("Service tier is required"));
+        }
+        if request.items.is_empty() {
+            return Err(anyhow::anyhow!("Submission needs at least one card"));
+        }
+        if request.fee_per_item < 0.0 || request.shipping_cost < 0.0 || request.insurance_cost < 0.0
+        {
+            return Err(anyhow::anyhow!("Fees cannot be negative"));
+        }
+        if request.items.iter().any(|i| i.declared_value < 0.0) {
+            return Err(anyhow::anyhow!("Declared values cannot be negative"));
+        }
+
+        let submission_uuid = Uuid::new_v4();
+        let submission_number = format!("GS-{}", &submission_uuid.to_string()[..8].to_uppercase());
+        let now = Utc::now().to_rfc3339();
+        let out_of_stock = MovementSource {
+            notes: Some(format!(
+                "Sent to {} on {}",
+                grader.as_str(),
+                submission_number
+            )),
+            ..MovementSource::new(
+                MovementType::TransferOut,
+                Some(submission_uuid),
+                created_by,
+                &self.db.node_id,
+            )
+        };
+        let to_grader = MovementSource {
+            movement_type: MovementType::TransferIn,
+            ..out_of_stock.clone()
+        };
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        sqlx::query(
+            "INSERT INTO Grading_Submissions (submission_uuid, submission_number, grader, service_tier, grader_reference,
+                status, fee_per_item, shipping_cost, insurance_cost, notes, created_by, created_at)
+             VALUES (?, ?, ?, ?, ?, 'Preparing', ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(submission_uuid.to_string())
+        .bind(&submission_number)
+        .bind(grader.as_str())
+        .bind(request.service_tier.trim())
+        .bind(&request.grader_reference)
+        .bind(request.fee_per_item)
+        .bind(request.shipping_cost)
+        .bind(request.insurance_cost)
+        .bind(&request.notes)
+        .bind(created_by.map(|u| u.to_string()))
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create submission: {}", e))?;
+
+        for line in &request.items {
+            let pile: Option<(String, String, i32, Option<f64>)> = sqlx::query_as(
+                "SELECT product_uuid, location_tag, quantity_on_hand, cost_basis FROM Local_Inventory
+                 WHERE inventory_uuid = ? AND deleted_at IS NULL",
+            )
+            .bind(line.inventory_uuid.to_string())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            let (product_uuid, location_tag, on_hand, cost_basis) =
+                pile.ok_or_else(|| anyhow::anyhow!("Inventory {} not found", line.inventory_uuid))?;
+            if location_tag == AT_GRADER_LOCATION || location_tag == super::TRANSIT_LOCATION {
+                return Err(anyhow::anyhow!(
+                    "Inventory {} is at {} and can't be submitted",
+                    line.inventory_uuid,
+                    location_tag
+                ));
+            }
+            if on_hand < 1 {
+                return Err(anyhow::anyhow!(
+                    "Not enough of inventory {} on hand for this submission",
+                    line.inventory_uuid
+                ));
+            }
+            let consigned: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM Consignment_Items WHERE inventory_uuid = ? AND status = 'Active'",
+            )
+            .bind(line.inventory_uuid.to_string())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            if consigned > 0 {
+                return Err(anyhow::anyhow!(
+                    "Inventory {} is on consignment and can't be submitted",
+                    line.inventory_uuid
+                ));
+            }
+
+            movements::adjust_quantity_with_tx(&mut tx, line.inventory_uuid, -1, &out_of_stock)
+                .await?;
+            let held_uuid = if on_hand == 1 {
+                retag_pile(&mut tx, line.inventory_uuid, AT_GRADER_LOCATION).await?;
+                line.inventory_uuid
+            } else {
+                let held_uuid = Uuid::new_v4();
+                sqlx::query(
+                    "INSERT INTO Local_Inventory
+                     (inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag,
+                      specific_price, cost_basis, supplier_uuid, received_date)
+                     SELECT ?, product_uuid, variant_type, condition, 0, ?, specific_price, cost_basis,
+                            supplier_uuid, received_date
+                     FROM Local_Inventory WHERE inventory_uuid = ?",
+                )
+                .bind(held_uuid.to_string())
+                .bind(AT_GRADER_LOCATION)
+                .bind(line.inventory_uuid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to create pile: {}", e))?;
+                held_uuid
+            };
+            movements::adjust_quantity_with_tx(&mut tx, held_uuid, 1, &to_grader).await?;
+
+            sqlx::query(
+                "INSERT INTO Grading_Submission_Items (item_uuid, submission_uuid, product_uuid, source_inventory_uuid,
+                    inventory_uuid, source_location, declared_value, raw_cost)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(submission_uuid.to_string())
+            .bind(&product_uuid)
+            .bind(line.inventory_uuid.to_string())
+            .bind(held_uuid.to_string())
+            .bind(&location_tag)
+            .bind(line.declared_value)
+            .bind(cost_basis)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to add submission item: {}", e))?;
+
+            self.db
+                .inventory
+                .log_sync_with_tx(&mut tx, line.inventory_uuid)
+                .await?;
+            if held_uuid != line.inventory_uuid {
+                self.db
+                    .inventory
+                    .log_sync_with_tx(&mut tx, held_uuid)
+                    .await?;
+            }
+        }
+
+        record_event(
+            &mut tx,
+            submission_uuid,
+            SubmissionStatus::Preparing,
+            request.notes.clone(),
+            created_by,
+        )
+        .await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!(
+            "Opened {} submission {} with {} cards",
+            grader,
+            submission_number,
+            request.items.len()
+        );
+        self.require(submission_uuid).await
+    }
+
+    pub async fn get_submission(&self, submission_uuid: Uuid) -> Result<Option<GradingSubmission>> {
+        let row = sqlx::query("SELECT * FROM Grading_Submissions WHERE submission_uuid = ?")
+            .bind(submission_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        match row {
+            Some(row) => Ok(Some(self.load_submission(&row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn require(&self, submission_uuid: Uuid) -> Result<GradingSubmission> {
+        self.get_submission(submission_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Submission {} not found", submission_uuid))
+    }
+
+    pub async fn get_submissions(
+        &self,
+        status: Option<SubmissionStatus>,
+    ) -> Result<Vec<GradingSubmission>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Grading_Submissions WHERE (? IS NULL OR status = ?)
+             ORDER BY created_at DESC",
+        )
+        .bind(status.map(|s| s.to_string()))
+        .bind(status.map(|s| s.to_string()))
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut submissions = Vec::with_capacity(rows.len());
+        for row in &rows {
+            submissions.push(self.load_submission(row).await?);
+        }
+        Ok(submissions)
+    }
+
+    /// Record a milestone. Milestones only move forward, but may skip steps
+    /// the grader didn't report.
+    pub async fn advance(
+        &self,
+        submission_uuid: Uuid,
+        to: SubmissionStatus,
+        notes: Option<String>,
+        user_uuid: Option<Uuid>,
+    ) -> Result<GradingSubmission> {
+        let submission = self.require(submission_uuid).await?;
+        if matches!(
+            to,
+            SubmissionStatus::Preparing | SubmissionStatus::Completed | SubmissionStatus::Cancelled
+        ) {
+            return Err(anyhow::anyhow!(
+                "{} is not a milestone that can be recorded directly",
+                to
+            ));
+        }
+        if to.rank() <= submission.status.rank() {
+            return Err(anyhow::anyhow!(
+                "Submission {} is {} and cannot be marked {}",
+                submission.submission_number,
+                submission.status,
+                to
+            ));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        sqlx::query(
+            "UPDATE Grading_Submissions SET status = ?,
+                shipped_at = CASE WHEN ? THEN COALESCE(shipped_at, ?) ELSE shipped_at END,
+                returned_at = CASE WHEN ? THEN ? ELSE returned_at END
+             WHERE submission_uuid = ?",
+        )
+        .bind(to.to_string())
+        .bind(to.rank() >= SubmissionStatus::Shipped.rank())
+        .bind(&now)
+        .bind(to == SubmissionStatus::Returned)
+        .bind(&now)
+        .bind(submission_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        record_event(&mut tx, submission_uuid, to, notes, user_uuid).await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.require(submission_uuid).await
+    }
+
+    /// Call off a submission that hasn't shipped and put the cards back
+    pub async fn cancel(
+        &self,
+        submission_uuid: Uuid,
+        notes: Option<String>,
+        user_uuid: Option<Uuid>,
+    ) -> Result<GradingSubmission> {
+        let submission = self.require(submission_uuid).await?;
+        if submission.status != SubmissionStatus::Preparing {
+            return Err(anyhow::anyhow!(
+                "Submission {} is {}; only submissions that haven't shipped can be cancelled",
+                submission.submission_number,
+                submission.status
+            ));
+        }
+
+        let back_out = MovementSource {
+            notes: Some(format!("{} cancelled", submission.submission_number)),
+            ..MovementSource::new(
+                MovementType::TransferOut,
+                Some(submission_uuid),
+                user_uuid,
+                &self.db.node_id,
+            )
+        };
+        let back_in = MovementSource {
+            movement_type: MovementType::TransferIn,
+            ..back_out.clone()
+        };
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        // Piles that went out whole go back first, so units split off them
+        // have somewhere to fold back into
+        let mut items: Vec<&SubmissionItem> = submission.items.iter().collect();
+        items.sort_by_key(|i| i.inventory_uuid != i.source_inventory_uuid);
+        for item in items {
+            movements::adjust_quantity_with_tx(&mut tx, item.inventory_uuid, -1, &back_out).await?;
+            // Fold the unit back into the pile it came from when that pile
+            // is still where it was
+            let source_in_place: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM Local_Inventory
+                 WHERE inventory_uuid = ? AND location_tag = ? AND deleted_at IS NULL",
+            )
+            .bind(item.source_inventory_uuid.to_string())
+            .bind(&item.source_location)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            if item.inventory_uuid != item.source_inventory_uuid && source_in_place > 0 {
+                sqlx::query("UPDATE Local_Inventory SET deleted_at = ? WHERE inventory_uuid = ?")
+                    .bind(Utc::now().to_rfc3339())
+                    .bind(item.inventory_uuid.to_string())
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+                movements::adjust_quantity_with_tx(
+                    &mut tx,
+                    item.source_inventory_uuid,
+                    1,
+                    &back_in,
+                )
+                .await?;
+                self.db
+                    .inventory
+                    .log_sync_with_tx(&mut tx, item.source_inventory_uuid)
+                    .await?;
+            } else {
+                retag_pile(&mut tx, item.inventory_uuid, &item.source_location).await?;
+                movements::adjust_quantity_with_tx(&mut tx, item.inventory_uuid, 1, &back_in)
+                    .await?;
+            }
+            self.db
+                .inventory
+                .log_sync_with_tx(&mut tx, item.inventory_uuid)
+                .await?;
+        }
+
+        sqlx::query(
+            "UPDATE Grading_Submissions SET status = 'Cancelled', completed_at = ? WHERE submission_uuid = ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(submission_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        record_event(
+            &mut tx,
+            submission_uuid,
+            SubmissionStatus::Cancelled,
+            notes,
+            user_uuid,
+        )
+        .await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.require(submission_uuid).await
+    }
+
+    /// Enter what came back. Graded cards are converted into serialized
+    /// inventory; every resolved card carries its fees into cost basis and
+    /// leaves `AT_GRADER`.
+    pub async fn record_results(
+        &self,
+        submission_uuid: Uuid,
+        results: Vec<ItemResultRequest>,
+        user_uuid: Option<Uuid>,
+    ) -> Result<GradingSubmission> {
+        let submission = self.require(submission_uuid).await?;
+        if submission.status != SubmissionStatus::Returned {
+            return Err(anyhow::anyhow!(
+                "Submission {} is {}; mark it Returned before entering results",
+                submission.submission_number,
+                submission.status
+            ));
+        }
+        if results.is_empty() {
+            return Err(anyhow::anyhow!("No results given"));
+        }
+
+        let now = Utc::now();
+        let off_grader = MovementSource {
+            notes: Some(format!(
+                "Back from {} on {}",
+                submission.grader, submission.submission_number
+            )),
+            ..MovementSource::new(
+                MovementType::TransferOut,
+                Some(submission_uuid),
+                user_uuid,
+                &self.db.node_id,
+            )
+        };
+        let to_shelf = MovementSource {
+            movement_type: MovementType::TransferIn,
+            ..off_grader.clone()
+        };
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        for result in &results {
+            let item = submission
+                .items
+                .iter()
+                .find(|i| i.item_uuid == result.item_uuid)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Item {} is not on submission {}",
+                        result.item_uuid,
+                        submission.submission_number
+                    )
+                })?;
+            if item.outcome != ItemOutcome::Pending {
+                return Err(anyhow::anyhow!(
+                    "Item {} already has a result",
+                    result.item_uuid
+                ));
+            }
+            if result.upcharge < 0.0 || result.graded_value.is_some_and(|v| v < 0.0) {
+                return Err(anyhow::anyhow!("Upcharges and values cannot be negative"));
+            }
+            let grade = result
+                .grade
+                .as_deref()
+                .map(str::trim)
+                .filter(|g| !g.is_empty());
+            let cert_number = result
+                .cert_number
+                .as_deref()
+                .map(str::trim)
+                .filter(|c| !c.is_empty());
+            match result.outcome {
+                ItemOutcome::Pending => {
+                    return Err(anyhow::anyhow!(
+                        "Result for {} has no outcome",
+                        item.item_uuid
+                    ))
+                }
+                ItemOutcome::Graded if grade.is_none() || cert_number.is_none() => {
+                    return Err(anyhow::anyhow!(
+                        "Graded cards need a grade and cert number ({})",
+                        item.product_name.as_deref().unwrap_or("item")
+                    ))
+                }
+                _ => {}
+            }
+
+            let grading_cost = grading_cost_per_item(
+                submission.fee_per_item,
+                submission.shipping_cost,
+                submission.insurance_cost,
+                submission.items.len(),
+                result.upcharge,
+            );
+            let cost_basis = round_cents(item.raw_cost.unwrap_or(0.0) + grading_cost);
+            let location_tag = result
+                .location_tag
+                .as_deref()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .unwrap_or(&item.source_location);
+
+            movements::adjust_quantity_with_tx(&mut tx, item.inventory_uuid, -1, &off_grader)
+                .await?;
+            retag_pile(&mut tx, item.inventory_uuid, location_tag).await?;
+            movements::adjust_quantity_with_tx(&mut tx, item.inventory_uuid, 1, &to_shelf).await?;
+
+            if result.outcome == ItemOutcome::Graded {
+                let existing: Option<String> = sqlx::query_scalar(
+                    "SELECT serialized_details FROM Local_Inventory WHERE inventory_uuid = ?",
+                )
+                .bind(item.inventory_uuid.to_string())
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+                let mut details: serde_json::Value = existing
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .filter(|d: &serde_json::Value| d.is_object())
+                    .unwrap_or_else(|| serde_json::json!({}));
+                let grading = GradingInfo {
+                    grader: submission.grader.to_string(),
+                    grade: grade.unwrap_or_default().to_string(),
+                    sub_grades: result.sub_grades.clone(),
+                    cert_number: cert_number.map(str::to_string),
+                    graded_date: Some(now.format("%Y-%m-%d").to_string()),
+                    population: None,
+                };
+                details["serial_number"] = serde_json::json!(cert_number);
+                details["grading"] = serde_json::to_value(&grading)?;
+                details["acquisition_cost"] = serde_json::json!(cost_basis);
+
+                sqlx::query(
+                    "UPDATE Local_Inventory SET serialized_details = ?, cost_basis = ?,
+                        specific_price = COALESCE(?, specific_price)
+                     WHERE inventory_uuid = ?",
+                )
+                .bind(details.to_string())
+                .bind(cost_basis)
+                .bind(result.graded_value)
+                .bind(item.inventory_uuid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to update inventory: {}", e))?;
+            } else {
+                sqlx::query("UPDATE Local_Inventory SET cost_basis = ? WHERE inventory_uuid = ?")
+                    .bind(cost_basis)
+                    .bind(item.inventory_uuid.to_string())
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to update inventory: {}", e))?;
+            }
+
+            let description = match result.outcome {
+                ItemOutcome::Graded => format!(
+                    "Graded {} {} (cert {}) on {}",
+                    submission.grader,
+                    grade.unwrap_or_default(),
+                    cert_number.unwrap_or_default(),
+                    submission.submission_number
+                ),
+                _ => format!(
+                    "Returned ungraded by {} on {}",
+                    submission.grader, submission.submission_number
+                ),
+            };
+            sqlx::query(
+                "INSERT INTO Item_Provenance (entry_uuid, inventory_uuid, event_type, description, event_date, source, price)
+                 VALUES (?, ?, 'grading', ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(item.inventory_uuid.to_string())
+            .bind(&description)
+            .bind(now.to_rfc3339())
+            .bind(submission.grader.as_str())
+            .bind(grading_cost)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to log provenance: {}", e))?;
+
+            sqlx::query(
+                "UPDATE Grading_Submission_Items SET outcome = ?, grade = ?, cert_number = ?, sub_grades = ?,
+                    upcharge = ?, graded_value = ?, grading_cost = ?, resolved_at = ?
+                 WHERE item_uuid = ?",
+            )
+            .bind(result.outcome.as_str())
+            .bind(grade)
+            .bind(cert_number)
+            .bind(
+                result
+                    .sub_grades
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+            )
+            .bind(result.upcharge)
+            .bind(result.graded_value)
+            .bind(grading_cost)
+            .bind(now.to_rfc3339())
+            .bind(item.item_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to record result: {}", e))?;
+
+            self.db
+                .inventory
+                .log_sync_with_tx(&mut tx, item.inventory_uuid)
+                .await?;
+        }
+
+        let pending: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM Grading_Submission_Items WHERE submission_uuid = ? AND outcome = 'pending'",
+        )
+        .bind(submission_uuid.to_string())
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if pending == 0 {
+            sqlx::query(
+                "UPDATE Grading_Submissions SET status = 'Completed', completed_at = ? WHERE submission_uuid = ?",
+            )
+            .bind(now.to_rfc3339())
+            .bind(submission_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            record_event(
+                &mut tx,
+                submission_uuid,
+                SubmissionStatus::Completed,
+                None,
+                user_uuid,
+            )
+            .await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        self.require(submission_uuid).await
+    }
+
+    /// Grading ROI for cards resolved between `start` and `end`
+    pub async fn roi_report(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        grader: Option<GradingCompany>,
+    ) -> Result<GradingRoiReport> {
+        let rows = sqlx::query(
+            "SELECT gs.submission_uuid, gs.grader, gs.shipped_at, gs.returned_at,
+                    i.outcome, i.grade, i.declared_value, i.grading_cost, i.graded_value
+             FROM Grading_Submission_Items i
+             JOIN Grading_Submissions gs ON gs.submission_uuid = i.submission_uuid
+             WHERE i.outcome != 'pending' AND i.resolved_at >= ? AND i.resolved_at <= ?
+               AND (? IS NULL OR gs.grader = ?)",
+        )
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .bind(grader.map(|g| g.as_str()))
+        .bind(grader.map(|g| g.as_str()))
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut all = Vec::with_capacity(rows.len());
+        let mut by_grader: BTreeMap<String, Vec<ResolvedLine>> = BTreeMap::new();
+        let mut grades: BTreeMap<(String, String), i64> = BTreeMap::new();
+        for row in &rows {
+            let grader: String = row.try_get("grader").unwrap_or_default();
+            let outcome = row
+                .try_get::<String, _>("outcome")
+                .ok()
+                .and_then(|o| ItemOutcome::parse(&o))
+                .unwrap_or(ItemOutcome::Pending);
+            let shipped_at = parse_date(row, "shipped_at");
+            let returned_at = parse_date(row, "returned_at");
+            let line = ResolvedLine {
+                submission_uuid: parse_uuid(row, "submission_uuid").unwrap_or_default(),
+                outcome,
+                declared_value: row.try_get("declared_value").unwrap_or(0.0),
+                grading_cost: row
+                    .try_get::<Option<f64>, _>("grading_cost")
+                    .ok()
+                    .flatten()
+                    .unwrap_or(0.0),
+                graded_value: row.try_get::<Option<f64>, _>("graded_value").ok().flatten(),
+                turnaround_days: match (shipped_at, returned_at) {
+                    (Some(shipped), Some(returned)) => {
+                        Some((returned - shipped).num_minutes() as f64 / (24.0 * 60.0))
+                    }
+                    _ => None,
+                },
+            };
+            if outcome == ItemOutcome::Graded {
+                if let Some(grade) = row.try_get::<Option<String>, _>("grade").ok().flatten() {
+                    *grades.entry((grader.clone(), grade)).or_default() += 1;
+                }
+            }
+            by_grader.entry(grader).or_default().push(line.clone());
+            all.push(line);
+        }
+
+        Ok(GradingRoiReport {
+            start,
+            end,
+            totals: summarize_roi("All", &all),
+            by_grader: by_grader
+                .iter()
+                .map(|(grader, lines)| summarize_roi(grader, lines))
+                .collect(),
+            grade_distribution: grades
+                .into_iter()
+                .map(|((grader, grade), count)| GradeCount {
+                    grader,
+                    grade,
+                    count,
+                })
+                .collect(),
+        })
+    }
+
+    async fn load_submission(&self, row: &sqlx::sqlite::SqliteRow) -> Result<GradingSubmission> {
+        let submission_uuid = parse_uuid(row, "submission_uuid")
+            .ok_or_else(|| anyhow::anyhow!("Submission has no id"))?;
+        let grader: String = row.try_get("grader").unwrap_or_default();
+        let status: String = row.try_get("status").unwrap_or_default();
+
+        let item_rows = sqlx::query(
+            "SELECT i.*, gc.name as product_name FROM Grading_Submission_Items i
+             LEFT JOIN Global_Catalog gc ON gc.product_uuid = i.product_uuid
+             WHERE i.submission_uuid = ? ORDER BY gc.name, i.item_uuid",
+        )
+        .bind(submission_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let items: Vec<SubmissionItem> = item_rows.iter().filter_map(map_item).collect();
+
+        let event_rows = sqlx::query(
+            "SELECT * FROM Grading_Submission_Events WHERE submission_uuid = ? ORDER BY created_at, rowid",
+        )
+        .bind(submission_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let events = event_rows
+            .iter()
+            .filter_map(|row| {
+                Some(SubmissionEvent {
+                    status: SubmissionStatus::parse(&row.try_get::<String, _>("status").ok()?)?,
+                    notes: row.try_get("notes").ok().flatten(),
+                    user_uuid: parse_uuid(row, "user_uuid"),
+                    created_at: parse_date(row, "created_at")?,
+                })
+            })
+            .collect();
+
+        let fee_per_item: f64 = row.try_get("fee_per_item").unwrap_or(0.0);
+        let shipping_cost: f64 = row.try_get("shipping_cost").unwrap_or(0.0);
+        let insurance_cost: f64 = row.try_get("insurance_cost").unwrap_or(0.0);
+        let total_fees = round_cents(
+            fee_per_item * items.len() as f64
+                + shipping_cost
+                + insurance_cost
+                + items.iter().map(|i| i.upcharge).sum::<f64>(),
+        );
+
+        Ok(GradingSubmission {
+            submission_uuid,
+            submission_number: row.try_get("submission_number").unwrap_or_default(),
+            grader: GradingCompany::parse(&grader)
+                .ok_or_else(|| anyhow::anyhow!("Unknown grader '{}'", grader))?,
+            service_tier: row.try_get("service_tier").unwrap_or_default(),
+            grader_reference: row.try_get("grader_reference").ok().flatten(),
+            status: SubmissionStatus::parse(&status)
+                .ok_or_else(|| anyhow::anyhow!("Unknown submission status '{}'", status))?,
+            fee_per_item,
+            shipping_cost,
+            insurance_cost,
+            notes: row.try_get("notes").ok().flatten(),
+            created_by: parse_uuid(row, "created_by"),
+            created_at: parse_date(row, "created_at").unwrap_or_else(Utc::now),
+            shipped_at: parse_date(row, "shipped_at"),
+            returned_at: parse_date(row, "returned_at"),
+            completed_at: parse_date(row, "completed_at"),
+            total_declared_value: round_cents(items.iter().map(|i| i.declared_value).sum()),
+            total_fees,
+            items,
+            events,
+        })
+    }
+}
+
+async fn record_event(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    submission_uuid: Uuid,
+    status: SubmissionStatus,
+    notes: Option<String>,
+    user_uuid: Option<Uuid>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO Grading_Submission_Events (event_uuid, submission_uuid, status, notes, user_uuid, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(submission_uuid.to_string())
+    .bind(status.to_string())
+    .bind(notes.filter(|n| !n.trim().is_empty()))
+    .bind(user_uuid.map(|u| u.to_string()))
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record milestone: {}", e))?;
+    Ok(())
+}
+
+/// Move a whole pile to another location tag, out of any bin
+async fn retag_pile(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    inventory_uuid: Uuid,
+    location_tag: &str,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE Local_Inventory SET location_tag = ?, storage_uuid = NULL, bin_location = NULL
+         WHERE inventory_uuid = ?",
+    )
+    .bind(location_tag)
+    .bind(inventory_uuid.to_string())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to move pile: {}", e))?;
+    Ok(())
+}
+
+fn parse_date(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<DateTime<Utc>> {
+    row.try_get::<Option<String>, _>(col)
+        .ok()
+        .flatten()
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn parse_uuid(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<Uuid> {
+    row.try_get::<Option<String>, _>(col)
+        .ok()
+        .flatten()
+        .and_then(|s| Uuid::parse_str(&s).ok())
+}
+
+fn map_item(row: &sqlx::sqlite::SqliteRow) -> Option<SubmissionItem> {
+    Some(SubmissionItem {
+        item_uuid: parse_uuid(row, "item_uuid")?,
+        product_uuid: parse_uuid(row, "product_uuid")?,
+        product_name: row.try_get("product_name").ok().flatten(),
+        source_inventory_uuid: parse_uuid(row, "source_inventory_uuid")?,
+        inventory_uuid: parse_uuid(row, "inventory_uuid")?,
+        source_location: row.try_get("source_location").ok()?,
+        declared_value: row.try_get("declared_value").unwrap_or(0.0),
+        raw_cost: row.try_get("raw_cost").ok().flatten(),
+        outcome: ItemOutcome::parse(&row.try_get::<String, _>("outcome").ok()?)?,
+        grade: row.try_get("grade").ok().flatten(),
+        cert_number: row.try_get("cert_number").ok().flatten(),
+        sub_grades: row
+            .try_get::<Option<String>, _>("sub_grades")
+            .ok()
+            .flatten()
+            .and_then(|s| serde_json::from_str(&s).ok()),
+        upcharge: row.try_get("upcharge").unwrap_or(0.0),
+        graded_value: row.try_get("graded_value").ok().flatten(),
+        grading_cost: row.try_get("grading_cost").ok().flatten(),
+        resolved_at: parse_date(row, "resolved_at"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grading_cost_per_item() {
+        // $25 tier, $30 shipping + $10 insurance over 4 cards, $5 upcharge
+        assert_eq!(grading_cost_per_item(25.0, 30.0, 10.0, 4, 5.0), 40.0);
+        assert_eq!(grading_cost_per_item(19.99, 10.0, 0.0, 3, 0.0), 23.32);
+        assert_eq!(grading_cost_per_item(0.0, 10.0, 0.0, 0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_summarize_roi() {
+        let submission = Uuid::new_v4();
+        let line = |outcome, declared, cost, graded| ResolvedLine {
+            submission_uuid: submission,
+            outcome,
+            declared_value: declared,
+            grading_cost: cost,
+            graded_value: graded,
+            turnaround_days: Some(30.0),
+        };
+        let roi = summarize_roi(
+            "PSA",
+            &[
+                line(ItemOutcome::Graded, 50.0, 25.0, Some(200.0)),
+                line(ItemOutcome::Graded, 40.0, 25.0, Some(30.0)),
+                // Ungradable cards are valued at what they were declared at
+                line(ItemOutcome::Ungradable, 20.0, 25.0, None),
+            ],
+        );
+        assert_eq!(roi.submissions, 1);
+        assert_eq!((roi.items, roi.graded, roi.ungradable), (3, 2, 1));
+        assert_eq!(roi.returned_value, 250.0);
+        assert_eq!(roi.value_added, 65.0);
+        assert_eq!(roi.roi_percent, Some(86.67));
+        assert_eq!(roi.average_turnaround_days, Some(30.0));
+
+        assert_eq!(GradingCompany::parse(" beckett"), Some(GradingCompany::Bgs));
+        assert!(SubmissionStatus::Grading.rank() > SubmissionStatus::Shipped.rank());
+    }
+}
@@ -5,9 +5,16 @@
 //! - Processing payments toward holds
 //! - Hold expiration tracking
 //! - Converting holds to completed sales
+//! - Layaway plans with installment schedules, late fees and forfeiture
 
+use crate::core::money::round_cents;
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
 use crate::database::Database;
 use crate::errors::Result;
+use crate::services::layaway::{
+    CreateLayawayPlanRequest, ForfeiturePolicy, ForfeitureSettlement, HoldInstallment,
+    InstallmentStatus, LayawayLiabilityReport, LayawayPlan, RefundMethod,
+};
 use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -39,11 +46,19 @@ pub struct Hold {
     pub hold_uuid: Uuid,
     pub customer_uuid: Uuid,
     pub status: HoldStatus,
+    /// Merchandise total
     pub total_amount: f64,
     pub deposit_amount: f64,
+    /// Owed on the merchandise and any late fees
     pub balance_due: f64,
+    /// Late fees charged on installments, kept out of the merchandise total
+    pub late_fees: f64,
     pub expiration_date: chrono::DateTime<Utc>,
     pub notes: Option<String>,
+    pub plan_uuid: Option<Uuid>,
+    pub forfeited_amount: f64,
+    pub refunded_amount: f64,
+    pub refund_method: Option<String>,
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
 }
@@ -77,6 +92,9 @@ pub struct CreateHoldRequest {
     pub deposit_method: String,
     pub notes: Option<String>,
     pub hold_days: Option<i64>,
+    /// Optional layaway plan; when set, the plan's deposit, schedule and
+    /// deadline replace `hold_days` and the default minimum deposit
+    pub plan_uuid: Option<Uuid>,
 }
 
 /// Request for a single hold item
@@ -93,6 +111,7 @@ pub struct HoldSummary {
     pub hold: Hold,
     pub items: Vec<HoldItem>,
     pub payments: Vec<HoldPayment>,
+    pub installments: Vec<HoldInstallment>,
     pub total_paid: f64,
 }
 
@@ -101,6 +120,8 @@ pub struct HoldsService {
     db: Arc<Database>,
     default_hold_days: i64,
     minimum_deposit_percent: f64,
+    /// Applied to expired holds that were not created on a layaway plan
+    default_forfeiture_policy: ForfeiturePolicy,
 }
 
 impl HoldsService {
@@ -109,6 +130,7 @@ impl HoldsService {
             db,
             default_hold_days: 14,         // 2 weeks default
             minimum_deposit_percent: 0.20, // 20% minimum deposit
+            default_forfeiture_policy: ForfeiturePolicy::RefundAll,
         }
     }
 
@@ -117,6 +139,22 @@ impl HoldsService {
         let now = Utc::now();
         let hold_uuid = Uuid::new_v4();
 
+        let plan = match request.plan_uuid {
+            Some(plan_uuid) => Some(
+                self.get_layaway_plan(plan_uuid)
+                    .await?
+                    .filter(|p| p.is_active)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Layaway plan {} not found or inactive", plan_uuid)
+                    })?,
+            ),
+            None => None,
+        };
+        let minimum_deposit_percent = plan
+            .as_ref()
+            .map(|p| p.minimum_deposit_percent)
+            .unwrap_or(self.minimum_deposit_percent);
+
         // Calculate totals
         let total_amount: f64 = request
             .items
@@ -125,19 +163,29 @@ impl HoldsService {
             .sum();
 
         // Validate minimum deposit
-        let min_deposit = total_amount * self.minimum_deposit_percent;
+        let min_deposit = total_amount * minimum_deposit_percent;
         if request.deposit_amount < min_deposit {
             return Err(anyhow::anyhow!(
                 "Minimum deposit is ${:.2} ({}% of ${:.2})",
                 min_deposit,
-                self.minimum_deposit_percent * 100.0,
+                minimum_deposit_percent * 100.0,
                 total_amount
             ));
         }
 
         let balance_due = total_amount - request.deposit_amount;
-        let hold_days = request.hold_days.unwrap_or(self.default_hold_days);
-        let expiration_date = now + Duration::days(hold_days);
+        let expiration_date = match &plan {
+            Some(plan) => plan.final_deadline(now),
+            None => now + Duration::days(request.hold_days.unwrap_or(self.default_hold_days)),
+        };
+
+        // The hold, its reservations, deposit and schedule land together
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
 
         // Validate inventory availability and reserve items
         for item in &request.items {
@@ -146,7 +194,7 @@ impl HoldsService {
                  WHERE inventory_uuid = ? AND deleted_at IS NULL",
             )
             .bind(item.inventory_uuid.to_string())
-            .fetch_optional(&self.db.pool)
+            .fetch_optional(&mut *tx)
             .await
             .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
 
@@ -174,8 +222,8 @@ impl HoldsService {
         // Create hold record
         sqlx::query(
             "INSERT INTO Holds 
-             (hold_uuid, customer_uuid, status, total_amount, deposit_amount, balance_due, expiration_date, notes, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+             (hold_uuid, customer_uuid, status, total_amount, deposit_amount, balance_due, expiration_date, notes, plan_uuid, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(hold_uuid.to_string())
         .bind(request.customer_uuid.to_string())
@@ -185,17 +233,19 @@ impl HoldsService {
         .bind(balance_due)
         .bind(expiration_date.to_rfc3339())
         .bind(&request.notes)
+        .bind(plan.as_ref().map(|p| p.plan_uuid.to_string()))
         .bind(now.to_rfc3339())
         .bind(now.to_rfc3339())
-        .execute(&self.db.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create hold: {}", e))?;
 
         // Create hold items and reserve inventory
+        let reserve =
+            MovementSource::new(MovementType::Hold, Some(hold_uuid), None, &self.db.node_id);
         let mut hold_items = Vec::new();
         for item in &request.items {
             let item_uuid = Uuid::new_v4();
-
             sqlx::query(
                 "INSERT INTO Hold_Items (item_uuid, hold_uuid, inventory_uuid, quantity, unit_price)
                  VALUES (?, ?, ?, ?, ?)",
@@ -205,19 +255,17 @@ impl HoldsService {
             .bind(item.inventory_uuid.to_string())
             .bind(item.quantity)
             .bind(item.unit_price)
-            .execute(&self.db.pool)
+            .execute(&mut *tx)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to create hold item: {}", e))?;
 
             // Reserve inventory (reduce available quantity)
-            sqlx::query(
-                "UPDATE Local_Inventory 
-                 SET quantity_on_hand = quantity_on_hand - ?
-                 WHERE inventory_uuid = ?",
+            movements::adjust_quantity_with_tx(
+                &mut tx,
+                item.inventory_uuid,
+                -item.quantity,
+                &reserve,
             )
-            .bind(item.quantity)
-            .bind(item.inventory_uuid.to_string())
-            .execute(&self.db.pool)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to reserve inventory: {}", e))?;
 
@@ -241,7 +289,7 @@ impl HoldsService {
         .bind(request.deposit_amount)
         .bind(&request.deposit_method)
         .bind(now.to_rfc3339())
-        .execute(&self.db.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to record deposit: {}", e))?;
 
@@ -253,6 +301,45 @@ impl HoldsService {
             created_at: now,
         }];
 
+        // Schedule installments for the financed balance
+        let mut installments = Vec::new();
+        if let Some(plan) = &plan {
+            for (sequence, due_date, amount_due) in plan.build_schedule(now, balance_due) {
+                let installment_uuid = Uuid::new_v4();
+
+                sqlx::query(
+                    "INSERT INTO Hold_Installments
+                     (installment_uuid, hold_uuid, sequence, due_date, amount_due, amount_paid, late_fee, status)
+                     VALUES (?, ?, ?, ?, ?, 0, 0, ?)",
+                )
+                .bind(installment_uuid.to_string())
+                .bind(hold_uuid.to_string())
+                .bind(sequence)
+                .bind(due_date.to_rfc3339())
+                .bind(amount_due)
+                .bind(InstallmentStatus::Pending.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to schedule installment: {}", e))?;
+
+                installments.push(HoldInstallment {
+                    installment_uuid,
+                    hold_uuid,
+                    sequence,
+                    due_date,
+                    amount_due,
+                    amount_paid: 0.0,
+                    late_fee: 0.0,
+                    status: InstallmentStatus::Pending,
+                    paid_at: None,
+                });
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
         let hold = Hold {
             hold_uuid,
             customer_uuid: request.customer_uuid,
@@ -260,8 +347,13 @@ impl HoldsService {
             total_amount,
             deposit_amount: request.deposit_amount,
             balance_due,
+            late_fees: 0.0,
             expiration_date,
             notes: request.notes,
+            plan_uuid: plan.as_ref().map(|p| p.plan_uuid),
+            forfeited_amount: 0.0,
+            refunded_amount: 0.0,
+            refund_method: None,
             created_at: now,
             updated_at: now,
         };
@@ -278,10 +370,132 @@ impl HoldsService {
             hold,
             items: hold_items,
             payments,
+            installments,
             total_paid: request.deposit_amount,
         })
     }
 
+    /// Put stock aside for a customer for a few hours without taking a
+    /// deposit, e.g. a wants-list match waiting for pickup. Paying the
+    /// balance completes it; left alone it expires and the stock returns.
+    pub async fn reserve_items(
+        &self,
+        customer_uuid: Uuid,
+        items: Vec<HoldItemRequest>,
+        hours: i64,
+        notes: Option<String>,
+    ) -> Result<HoldSummary> {
+        if hours <= 0 {
+            return Err(anyhow::anyhow!("Reservation must last at least an hour"));
+        }
+        let now = Utc::now();
+        let hold_uuid = Uuid::new_v4();
+        let total_amount: f64 = items.iter().map(|i| i.unit_price * i.quantity as f64).sum();
+        let expiration_date = now + Duration::hours(hours);
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        sqlx::query(
+            "INSERT INTO Holds
+             (hold_uuid, customer_uuid, status, total_amount, deposit_amount, balance_due, expiration_date, notes, created_at, updated_at)
+             VALUES (?, ?, ?, ?, 0, ?, ?, ?, ?, ?)",
+        )
+        .bind(hold_uuid.to_string())
+        .bind(customer_uuid.to_string())
+        .bind(HoldStatus::Active.to_string())
+        .bind(total_amount)
+        .bind(total_amount)
+        .bind(expiration_date.to_rfc3339())
+        .bind(&notes)
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create hold: {}", e))?;
+
+        let reserve =
+            MovementSource::new(MovementType::Hold, Some(hold_uuid), None, &self.db.node_id);
+        let mut hold_items = Vec::new();
+        for item in &items {
+            let on_hand = movements::adjust_quantity_with_tx(
+                &mut tx,
+                item.inventory_uuid,
+                -item.quantity,
+                &reserve,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to reserve inventory: {}", e))?;
+            if on_hand < 0 {
+                return Err(anyhow::anyhow!(
+                    "Insufficient stock for item {}",
+                    item.inventory_uuid
+                ));
+            }
+
+            let item_uuid = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO Hold_Items (item_uuid, hold_uuid, inventory_uuid, quantity, unit_price)
+                 VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(item_uuid.to_string())
+            .bind(hold_uuid.to_string())
+            .bind(item.inventory_uuid.to_string())
+            .bind(item.quantity)
+            .bind(item.unit_price)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create hold item: {}", e))?;
+
+            hold_items.push(HoldItem {
+                item_uuid,
+                hold_uuid,
+                inventory_uuid: item.inventory_uuid,
+                quantity: item.quantity,
+                unit_price: item.unit_price,
+            });
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!(
+            "Reserved {} item(s) for customer {} until {}",
+            hold_items.len(),
+            customer_uuid,
+            expiration_date
+        );
+
+        Ok(HoldSummary {
+            hold: Hold {
+                hold_uuid,
+                customer_uuid,
+                status: HoldStatus::Active,
+                total_amount,
+                deposit_amount: 0.0,
+                balance_due: total_amount,
+                late_fees: 0.0,
+                expiration_date,
+                notes,
+                plan_uuid: None,
+                forfeited_amount: 0.0,
+                refunded_amount: 0.0,
+                refund_method: None,
+                created_at: now,
+                updated_at: now,
+            },
+            items: hold_items,
+            payments: Vec::new(),
+            installments: Vec::new(),
+            total_paid: 0.0,
+        })
+    }
+
     /// Make a payment toward a hold
     pub async fn make_payment(
         &self,
@@ -302,6 +516,23 @@ impl HoldsService {
             ));
         }
 
+        // Plans require at least the next installment unless the balance is smaller
+        if summary.hold.plan_uuid.is_some() {
+            let next_due = summary
+                .installments
+                .iter()
+                .map(|i| i.outstanding())
+                .find(|o| *o > 0.0)
+                .unwrap_or(0.0);
+            let minimum = next_due.min(summary.hold.balance_due);
+            if amount + 0.005 < minimum {
+                return Err(anyhow::anyhow!(
+                    "Minimum payment is ${:.2} (next installment)",
+                    minimum
+                ));
+            }
+        }
+
         // Calculate new balance
         let new_balance = summary.hold.balance_due - amount;
         let payment_uuid = Uuid::new_v4();
@@ -329,6 +560,9 @@ impl HoldsService {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to update hold: {}", e))?;
 
+        self.apply_payment_to_installments(&summary.installments, amount)
+            .await?;
+
         // If fully paid, complete the hold
         if new_balance <= 0.0 {
             self.complete_hold(hold_uuid).await?;
@@ -358,46 +592,73 @@ impl HoldsService {
     /// Cancel a hold and restore inventory
     pub async fn cancel_hold(&self, hold_uuid: Uuid, reason: &str) -> Result<()> {
         let now = Utc::now();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
 
+        self.restore_hold_inventory_with_tx(&mut tx, hold_uuid)
+            .await?;
+
+        // Update hold status
+        sqlx::query(
+            "UPDATE Holds SET status = ?, notes = COALESCE(notes, '') || ?, updated_at = ? WHERE hold_uuid = ?",
+        )
+        .bind(HoldStatus::Cancelled.to_string())
+        .bind(format!(" [Cancelled: {}]", reason))
+        .bind(now.to_rfc3339())
+        .bind(hold_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to cancel hold: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!("Hold {} cancelled: {}", hold_uuid, reason);
+        Ok(())
+    }
+
+    /// Return reserved hold items to available inventory
+    async fn restore_hold_inventory_with_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        hold_uuid: Uuid,
+    ) -> Result<()> {
         // Get hold items
         let items =
             sqlx::query("SELECT inventory_uuid, quantity FROM Hold_Items WHERE hold_uuid = ?")
                 .bind(hold_uuid.to_string())
-                .fetch_all(&self.db.pool)
+                .fetch_all(&mut **tx)
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to get hold items: {}", e))?;
 
+        let release = MovementSource::new(
+            MovementType::HoldRelease,
+            Some(hold_uuid),
+            None,
+            &self.db.node_id,
+        );
+
         // Restore inventory
         for item in items {
             let inventory_uuid: String =
                 sqlx::Row::try_get(&item, "inventory_uuid").unwrap_or_default();
             let quantity: i32 = sqlx::Row::try_get(&item, "quantity").unwrap_or(0);
 
-            sqlx::query(
-                "UPDATE Local_Inventory 
-                 SET quantity_on_hand = quantity_on_hand + ?
-                 WHERE inventory_uuid = ?",
+            movements::adjust_quantity_with_tx(
+                tx,
+                Uuid::parse_str(&inventory_uuid)?,
+                quantity,
+                &release,
             )
-            .bind(quantity)
-            .bind(&inventory_uuid)
-            .execute(&self.db.pool)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to restore inventory: {}", e))?;
         }
 
-        // Update hold status
-        sqlx::query(
-            "UPDATE Holds SET status = ?, notes = COALESCE(notes, '') || ?, updated_at = ? WHERE hold_uuid = ?",
-        )
-        .bind(HoldStatus::Cancelled.to_string())
-        .bind(format!(" [Cancelled: {}]", reason))
-        .bind(now.to_rfc3339())
-        .bind(hold_uuid.to_string())
-        .execute(&self.db.pool)
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to cancel hold: {}", e))?;
-
-        tracing::info!("Hold {} cancelled: {}", hold_uuid, reason);
         Ok(())
     }
 
@@ -434,12 +695,29 @@ impl HoldsService {
             total_amount: sqlx::Row::try_get(&row, "total_amount").unwrap_or(0.0),
             deposit_amount: sqlx::Row::try_get(&row, "deposit_amount").unwrap_or(0.0),
             balance_due: sqlx::Row::try_get(&row, "balance_due").unwrap_or(0.0),
+            late_fees: sqlx::Row::try_get::<Option<f64>, _>(&row, "late_fees")
+                .ok()
+                .flatten()
+                .unwrap_or(0.0),
             expiration_date: sqlx::Row::try_get::<String, _>(&row, "expiration_date")
                 .ok()
                 .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_else(Utc::now),
             notes: sqlx::Row::try_get(&row, "notes").ok(),
+            plan_uuid: sqlx::Row::try_get::<Option<String>, _>(&row, "plan_uuid")
+                .ok()
+                .flatten()
+                .and_then(|s| Uuid::parse_str(&s).ok()),
+            forfeited_amount: sqlx::Row::try_get::<Option<f64>, _>(&row, "forfeited_amount")
+                .ok()
+                .flatten()
+                .unwrap_or(0.0),
+            refunded_amount: sqlx::Row::try_get::<Option<f64>, _>(&row, "refunded_amount")
+                .ok()
+                .flatten()
+                .unwrap_or(0.0),
+            refund_method: sqlx::Row::try_get(&row, "refund_method").ok().flatten(),
             created_at: sqlx::Row::try_get::<String, _>(&row, "created_at")
                 .ok()
                 .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
@@ -502,11 +780,13 @@ impl HoldsService {
             .collect();
 
         let total_paid: f64 = payments.iter().map(|p| p.amount).sum();
+        let installments = self.get_installments(hold_uuid).await?;
 
         Ok(Some(HoldSummary {
             hold,
             items,
             payments,
+            installments,
             total_paid,
         }))
     }
@@ -534,7 +814,7 @@ impl HoldsService {
         Ok(holds)
     }
 
-    /// Check and expire overdue holds
+    /// Check and expire overdue holds, applying each hold's forfeiture policy
     pub async fn expire_overdue_holds(&self) -> Result<Vec<Uuid>> {
         let now = Utc::now();
 
@@ -550,7 +830,7 @@ impl HoldsService {
         for row in rows {
             let hold_uuid_str: String = sqlx::Row::try_get(&row, "hold_uuid").unwrap_or_default();
             if let Ok(hold_uuid) = Uuid::parse_str(&hold_uuid_str) {
-                self.cancel_hold(hold_uuid, "Expired").await?;
+                self.forfeit_hold(hold_uuid).await?;
                 expired.push(hold_uuid);
             }
         }
@@ -561,6 +841,439 @@ impl HoldsService {
 
         Ok(expired)
     }
+
+    /// Expire a hold: release its items and settle money paid per the forfeiture policy
+    pub async fn forfeit_hold(&self, hold_uuid: Uuid) -> Result<ForfeitureSettlement> {
+        let now = Utc::now();
+        let summary = self
+            .get_hold(hold_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Hold not found"))?;
+
+        if summary.hold.status != HoldStatus::Active {
+            return Err(anyhow::anyhow!(
+                "Cannot forfeit {} hold",
+                summary.hold.status
+            ));
+        }
+
+        let (policy, restocking_fee_percent) = match summary.hold.plan_uuid {
+            Some(plan_uuid) => match self.get_layaway_plan(plan_uuid).await? {
+                Some(plan) => (plan.forfeiture_policy, plan.restocking_fee_percent),
+                None => (self.default_forfeiture_policy, 0.0),
+            },
+            None => (self.default_forfeiture_policy, 0.0),
+        };
+
+        // The restocking fee is on the merchandise, not on late fees
+        let settlement = policy.settle(
+            round_cents(summary.total_paid),
+            summary.hold.total_amount,
+            restocking_fee_percent,
+        );
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        // Claim the hold first so a concurrent or retried forfeit can't settle twice
+        let claimed = sqlx::query(
+            "UPDATE Holds
+             SET status = ?, forfeited_amount = ?, refunded_amount = ?, refund_method = ?,
+                 notes = COALESCE(notes, '') || ?, updated_at = ?
+             WHERE hold_uuid = ? AND status = ?",
+        )
+        .bind(HoldStatus::Expired.to_string())
+        .bind(settlement.retained)
+        .bind(settlement.returned)
+        .bind(settlement.refund_method.map(|m| m.to_string()))
+        .bind(format!(" [Expired: {}]", policy))
+        .bind(now.to_rfc3339())
+        .bind(hold_uuid.to_string())
+        .bind(HoldStatus::Active.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to expire hold: {}", e))?;
+        if claimed.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Hold is no longer active"));
+        }
+
+        self.restore_hold_inventory_with_tx(&mut tx, hold_uuid)
+            .await?;
+
+        if settlement.refund_method == Some(RefundMethod::StoreCredit) {
+            self.db
+                .customers
+                .update_store_credit_with_tx(
+                    &mut tx,
+                    summary.hold.customer_uuid,
+                    settlement.returned,
+                )
+                .await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!(
+            "Hold {} expired under {}: ${:.2} retained, ${:.2} returned",
+            hold_uuid,
+            policy,
+            settlement.retained,
+            settlement.returned
+        );
+
+        Ok(settlement)
+    }
+
+    // ===== Layaway plans =====
+
+    /// Create a layaway plan
+    pub async fn create_layaway_plan(
+        &self,
+        request: CreateLayawayPlanRequest,
+    ) -> Result<LayawayPlan> {
+        if request.installment_count < 1 {
+            return Err(anyhow::anyhow!("A plan needs at least one installment"));
+        }
+        if request.installment_interval_days < 1 {
+            return Err(anyhow::anyhow!(
+                "Installment interval must be at least one day"
+            ));
+        }
+
+        let plan = LayawayPlan {
+            plan_uuid: Uuid::new_v4(),
+            name: request.name,
+            installment_count: request.installment_count,
+            installment_interval_days: request.installment_interval_days,
+            minimum_deposit_percent: request
+                .minimum_deposit_percent
+                .unwrap_or(self.minimum_deposit_percent),
+            grace_period_days: request.grace_period_days.unwrap_or(0),
+            late_fee: request.late_fee.unwrap_or(0.0),
+            forfeiture_policy: request
+                .forfeiture_policy
+                .unwrap_or(self.default_forfeiture_policy),
+            restocking_fee_percent: request.restocking_fee_percent.unwrap_or(0.0),
+            is_active: true,
+            created_at: Utc::now(),
+        };
+
+        sqlx::query(
+            "INSERT INTO Layaway_Plans
+             (plan_uuid, name, installment_count, installment_interval_days, minimum_deposit_percent,
+              grace_period_days, late_fee, forfeiture_policy, restocking_fee_percent, is_active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?)",
+        )
+        .bind(plan.plan_uuid.to_string())
+        .bind(&plan.name)
+        .bind(plan.installment_count)
+        .bind(plan.installment_interval_days)
+        .bind(plan.minimum_deposit_percent)
+        .bind(plan.grace_period_days)
+        .bind(plan.late_fee)
+        .bind(plan.forfeiture_policy.to_string())
+        .bind(plan.restocking_fee_percent)
+        .bind(plan.created_at.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create layaway plan: {}", e))?;
+
+        Ok(plan)
+    }
+
+    /// List active layaway plans
+    pub async fn list_layaway_plans(&self) -> Result<Vec<LayawayPlan>> {
+        let rows = sqlx::query("SELECT * FROM Layaway_Plans WHERE is_active = 1 ORDER BY name")
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().map(Self::row_to_plan).collect())
+    }
+
+    /// Get a layaway plan by ID
+    pub async fn get_layaway_plan(&self, plan_uuid: Uuid) -> Result<Option<LayawayPlan>> {
+        let row = sqlx::query("SELECT * FROM Layaway_Plans WHERE plan_uuid = ?")
+            .bind(plan_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(row.as_ref().map(Self::row_to_plan))
+    }
+
+    /// Get the installment schedule for a hold
+    pub async fn get_installments(&self, hold_uuid: Uuid) -> Result<Vec<HoldInstallment>> {
+        let rows =
+            sqlx::query("SELECT * FROM Hold_Installments WHERE hold_uuid = ? ORDER BY sequence")
+                .bind(hold_uuid.to_string())
+                .fetch_all(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().map(Self::row_to_installment).collect())
+    }
+
+    /// Allocate a payment to outstanding installments, oldest first
+    async fn apply_payment_to_installments(
+        &self,
+        installments: &[HoldInstallment],
+        amount: f64,
+    ) -> Result<()> {
+        let now = Utc::now();
+        let mut remaining = round_cents(amount);
+
+        for installment in installments {
+            if remaining <= 0.0 {
+                break;
+            }
+            let outstanding = installment.outstanding();
+            if outstanding <= 0.0 || installment.status == InstallmentStatus::Waived {
+                continue;
+            }
+
+            let applied = outstanding.min(remaining);
+            remaining = round_cents(remaining - applied);
+            let fully_paid = applied + 0.005 >= outstanding;
+
+            sqlx::query(
+                "UPDATE Hold_Installments
+                 SET amount_paid = amount_paid + ?, status = ?, paid_at = ?
+                 WHERE installment_uuid = ?",
+            )
+            .bind(applied)
+            .bind(if fully_paid {
+                InstallmentStatus::Paid.to_string()
+            } else {
+                installment.status.to_string()
+            })
+            .bind(if fully_paid {
+                Some(now.to_rfc3339())
+            } else {
+                None
+            })
+            .bind(installment.installment_uuid.to_string())
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to apply installment payment: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Mark installments past their grace period as late and charge the plan's late fee
+    pub async fn assess_late_fees(&self) -> Result<Vec<Uuid>> {
+        let now = Utc::now();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let rows = sqlx::query(
+            "SELECT hi.installment_uuid, hi.hold_uuid, hi.due_date, p.grace_period_days, p.late_fee
+             FROM Hold_Installments hi
+             JOIN Holds h ON hi.hold_uuid = h.hold_uuid
+             JOIN Layaway_Plans p ON h.plan_uuid = p.plan_uuid
+             WHERE hi.status = ? AND h.status = ? AND hi.amount_paid < hi.amount_due",
+        )
+        .bind(InstallmentStatus::Pending.to_string())
+        .bind(HoldStatus::Active.to_string())
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut assessed = Vec::new();
+        for row in rows {
+            let due_date = sqlx::Row::try_get::<String, _>(&row, "due_date")
+                .ok()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc));
+            let grace_days: i64 = sqlx::Row::try_get(&row, "grace_period_days").unwrap_or(0);
+            let late_fee: f64 = sqlx::Row::try_get(&row, "late_fee").unwrap_or(0.0);
+
+            let Some(due_date) = due_date else { continue };
+            if due_date + Duration::days(grace_days) >= now {
+                continue;
+            }
+
+            let installment_uuid: String =
+                sqlx::Row::try_get(&row, "installment_uuid").unwrap_or_default();
+            let hold_uuid: String = sqlx::Row::try_get(&row, "hold_uuid").unwrap_or_default();
+
+            let marked = sqlx::query(
+                "UPDATE Hold_Installments SET status = ?, late_fee = ?
+                 WHERE installment_uuid = ? AND status = ?",
+            )
+            .bind(InstallmentStatus::Late.to_string())
+            .bind(late_fee)
+            .bind(&installment_uuid)
+            .bind(InstallmentStatus::Pending.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to mark installment late: {}", e))?;
+            if marked.rows_affected() == 0 {
+                continue;
+            }
+
+            if late_fee > 0.0 {
+                sqlx::query(
+                    "UPDATE Holds
+                     SET late_fees = late_fees + ?, balance_due = balance_due + ?, updated_at = ?
+                     WHERE hold_uuid = ?",
+                )
+                .bind(late_fee)
+                .bind(late_fee)
+                .bind(now.to_rfc3339())
+                .bind(&hold_uuid)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to apply late fee: {}", e))?;
+            }
+
+            if let Ok(uuid) = Uuid::parse_str(&installment_uuid) {
+                assessed.push(uuid);
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        if !assessed.is_empty() {
+            tracing::info!("Assessed late fees on {} installments", assessed.len());
+        }
+
+        Ok(assessed)
+    }
+
+    /// Outstanding layaway liability across all active holds
+    pub async fn get_layaway_liability_report(&self) -> Result<LayawayLiabilityReport> {
+        let now = Utc::now();
+
+        let totals = sqlx::query(
+            "SELECT COUNT(*) as active_holds,
+                    COALESCE(SUM(total_amount + late_fees - balance_due), 0.0) as deposits_held,
+                    COALESCE(SUM(balance_due), 0.0) as balance_outstanding
+             FROM Holds WHERE status = ?",
+        )
+        .bind(HoldStatus::Active.to_string())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let reserved = sqlx::query(
+            "SELECT COALESCE(SUM(hi.quantity * hi.unit_price), 0.0) as reserved_value
+             FROM Hold_Items hi
+             JOIN Holds h ON hi.hold_uuid = h.hold_uuid
+             WHERE h.status = ?",
+        )
+        .bind(HoldStatus::Active.to_string())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let overdue = sqlx::query(
+            "SELECT COUNT(*) as overdue_count,
+                    COALESCE(SUM(hi.amount_due + hi.late_fee - hi.amount_paid), 0.0) as overdue_amount,
+                    COALESCE(SUM(CASE WHEN hi.status = ? THEN hi.late_fee ELSE 0 END), 0.0) as late_fees
+             FROM Hold_Installments hi
+             JOIN Holds h ON hi.hold_uuid = h.hold_uuid
+             WHERE h.status = ? AND hi.status IN (?, ?) AND hi.due_date < ?",
+        )
+        .bind(InstallmentStatus::Late.to_string())
+        .bind(HoldStatus::Active.to_string())
+        .bind(InstallmentStatus::Pending.to_string())
+        .bind(InstallmentStatus::Late.to_string())
+        .bind(now.to_rfc3339())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(LayawayLiabilityReport {
+            generated_at: now,
+            active_holds: sqlx::Row::try_get(&totals, "active_holds").unwrap_or(0),
+            deposits_held: round_cents(sqlx::Row::try_get(&totals, "deposits_held").unwrap_or(0.0)),
+            balance_outstanding: round_cents(
+                sqlx::Row::try_get(&totals, "balance_outstanding").unwrap_or(0.0),
+            ),
+            reserved_merchandise_value: round_cents(
+                sqlx::Row::try_get(&reserved, "reserved_value").unwrap_or(0.0),
+            ),
+            overdue_installments: sqlx::Row::try_get(&overdue, "overdue_count").unwrap_or(0),
+            overdue_amount: round_cents(
+                sqlx::Row::try_get(&overdue, "overdue_amount").unwrap_or(0.0),
+            ),
+            late_fees_outstanding: round_cents(
+                sqlx::Row::try_get(&overdue, "late_fees").unwrap_or(0.0),
+            ),
+        })
+    }
+
+    fn row_to_plan(row: &sqlx::sqlite::SqliteRow) -> LayawayPlan {
+        LayawayPlan {
+            plan_uuid: Uuid::parse_str(
+                &sqlx::Row::try_get::<String, _>(row, "plan_uuid").unwrap_or_default(),
+            )
+            .unwrap_or_default(),
+            name: sqlx::Row::try_get(row, "name").unwrap_or_default(),
+            installment_count: sqlx::Row::try_get(row, "installment_count").unwrap_or(1),
+            installment_interval_days: sqlx::Row::try_get(row, "installment_interval_days")
+                .unwrap_or(30),
+            minimum_deposit_percent: sqlx::Row::try_get(row, "minimum_deposit_percent")
+                .unwrap_or(0.2),
+            grace_period_days: sqlx::Row::try_get(row, "grace_period_days").unwrap_or(0),
+            late_fee: sqlx::Row::try_get(row, "late_fee").unwrap_or(0.0),
+            forfeiture_policy: ForfeiturePolicy::parse(
+                &sqlx::Row::try_get::<String, _>(row, "forfeiture_policy").unwrap_or_default(),
+            ),
+            restocking_fee_percent: sqlx::Row::try_get(row, "restocking_fee_percent")
+                .unwrap_or(0.0),
+            is_active: sqlx::Row::try_get(row, "is_active").unwrap_or(true),
+            created_at: sqlx::Row::try_get::<String, _>(row, "created_at")
+                .ok()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(Utc::now),
+        }
+    }
+
+    fn row_to_installment(row: &sqlx::sqlite::SqliteRow) -> HoldInstallment {
+        HoldInstallment {
+            installment_uuid: Uuid::parse_str(
+                &sqlx::Row::try_get::<String, _>(row, "installment_uuid").unwrap_or_default(),
+            )
+            .unwrap_or_default(),
+            hold_uuid: Uuid::parse_str(
+                &sqlx::Row::try_get::<String, _>(row, "hold_uuid").unwrap_or_default(),
+            )
+            .unwrap_or_default(),
+            sequence: sqlx::Row::try_get(row, "sequence").unwrap_or(0),
+            due_date: sqlx::Row::try_get::<String, _>(row, "due_date")
+                .ok()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(Utc::now),
+            amount_due: sqlx::Row::try_get(row, "amount_due").unwrap_or(0.0),
+            amount_paid: sqlx::Row::try_get(row, "amount_paid").unwrap_or(0.0),
+            late_fee: sqlx::Row::try_get(row, "late_fee").unwrap_or(0.0),
+            status: InstallmentStatus::parse(
+                &sqlx::Row::try_get::<String, _>(row, "status").unwrap_or_default(),
+            ),
+            paid_at: sqlx::Row::try_get::<Option<String>, _>(row, "paid_at")
+                .ok()
+                .flatten()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+        }
+    }
 }
 
 #[cfg(test)]
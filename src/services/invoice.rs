@@ -1,5 +1,8 @@
 use crate::config::Config;
+use crate::core::money::format_amount;
+use crate::core::Currency;
 use crate::database::Database;
+use crate::services::CurrencyService;
 use anyhow::Result;
 use serde::Serialize;
 use sqlx::Row;
@@ -19,6 +22,7 @@ pub struct InvoiceData {
     pub tax_amount: f64,
     pub total: f64,
     pub notes: Option<String>,
+    pub currency: Currency,
 }
 
 #[derive(Debug, Serialize)]
@@ -130,6 +134,10 @@ impl InvoiceService {
             })
             .collect();
 
+        let currency = CurrencyService::new(self.db.clone())
+            .transaction_currency(transaction_uuid)
+            .await?;
+
         Ok(InvoiceData {
             invoice_number: format!("INV-{}", &transaction_uuid.to_string()[..8]), // Simple ID
             issue_date: timestamp.format("%Y-%m-%d").to_string(),
@@ -148,6 +156,7 @@ impl InvoiceService {
             tax_amount,
             total,
             notes,
+            currency,
         })
     }
 
@@ -160,10 +169,13 @@ impl InvoiceService {
                 "<tr>
                     <td style='padding: 8px; border-bottom: 1px solid #ddd;'>{}</td>
                     <td style='padding: 8px; border-bottom: 1px solid #ddd; text-align: right;'>{}</td>
-                    <td style='padding: 8px; border-bottom: 1px solid #ddd; text-align: right;'>${:.2}</td>
-                    <td style='padding: 8px; border-bottom: 1px solid #ddd; text-align: right;'>${:.2}</td>
+                    <td style='padding: 8px; border-bottom: 1px solid #ddd; text-align: right;'>{}</td>
+                    <td style='padding: 8px; border-bottom: 1px solid #ddd; text-align: right;'>{}</td>
                 </tr>",
-                item.description, item.quantity, item.unit_price, item.amount
+                item.description,
+                item.quantity,
+                format_amount(item.unit_price, data.currency),
+                format_amount(item.amount, data.currency)
             ));
         }
 
@@ -227,9 +239,9 @@ impl InvoiceService {
         </table>
 
         <div class="totals">
-            <p>Subtotal: ${:.2}</p>
-            <p>Tax ({:.1}%): ${:.2}</p>
-            <p class="total-row">Total: ${:.2}</p>
+            <p>Subtotal: {}</p>
+            <p>Tax ({:.1}%): {}</p>
+            <p class="total-row">Total: {}</p>
         </div>
 
         <div class="footer">
@@ -249,10 +261,10 @@ impl InvoiceService {
             data.bill_to.email.as_deref().unwrap_or(""),
             data.bill_to.phone.as_deref().unwrap_or(""),
             items_html,
-            data.subtotal,
+            format_amount(data.subtotal, data.currency),
             data.tax_rate,
-            data.tax_amount,
-            data.total
+            format_amount(data.tax_amount, data.currency),
+            format_amount(data.total, data.currency)
         );
         Ok(html)
     }
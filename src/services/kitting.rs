@@ -0,0 +1,1002 @@
+//! Sealed product breaking and kitting
+//!
+//! A bill of materials lists what a product is made of: a booster box holds
+//! 36 packs, a starter kit is a deck plus sleeves. Breaking opens a parent
+//! pile into its components and assembling builds parents from component
+//! stock; both move stock in one database transaction and carry cost across.
+//! Kits can also be sold without assembling them first, in which case the
+//! sale consumes the components directly.
+
+use crate::core::money::round_cents;
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
+use crate::database::Database;
+use crate::errors::Result;
+use crate::services::purchasing::weighted_average_cost;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BomType {
+    /// Factory-sealed product that is opened into its contents
+    Sealed,
+    /// Store-built bundle; sales consume components when none are assembled
+    Kit,
+}
+
+impl std::fmt::Display for BomType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BomType::Sealed => write!(f, "Sealed"),
+            BomType::Kit => write!(f, "Kit"),
+        }
+    }
+}
+
+impl BomType {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Sealed" => Some(BomType::Sealed),
+            "Kit" => Some(BomType::Kit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConversionType {
+    Break,
+    Assemble,
+    /// Components consumed by selling a kit that wasn't assembled
+    KitSale,
+}
+
+impl std::fmt::Display for ConversionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionType::Break => write!(f, "Break"),
+            ConversionType::Assemble => write!(f, "Assemble"),
+            ConversionType::KitSale => write!(f, "KitSale"),
+        }
+    }
+}
+
+impl ConversionType {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Break" => Some(ConversionType::Break),
+            "Assemble" => Some(ConversionType::Assemble),
+            "KitSale" => Some(ConversionType::KitSale),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BomComponent {
+    pub component_product_uuid: Uuid,
+    pub component_name: Option<String>,
+    /// Units of the component in one parent
+    pub quantity: i32,
+    /// Relative share of the parent's cost per component unit; defaults to 1
+    pub cost_weight: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductBom {
+    pub product_uuid: Uuid,
+    pub bom_type: BomType,
+    pub components: Vec<BomComponent>,
+    pub notes: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BomComponentRequest {
+    pub component_product_uuid: Uuid,
+    pub quantity: i32,
+    pub cost_weight: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetBomRequest {
+    pub bom_type: BomType,
+    pub components: Vec<BomComponentRequest>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BreakRequest {
+    /// The parent pile to open
+    pub inventory_uuid: Uuid,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssembleRequest {
+    pub quantity: i32,
+    /// Where components are taken from and the parent is stocked
+    pub location_tag: String,
+    /// Condition of the assembled parent; defaults to New
+    pub condition: Option<crate::core::Condition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionLine {
+    pub inventory_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub quantity_change: i32,
+    pub unit_cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryConversion {
+    pub conversion_uuid: Uuid,
+    pub conversion_type: ConversionType,
+    /// The parent or kit product
+    pub product_uuid: Uuid,
+    pub quantity: i32,
+    /// Cost moved from one side of the conversion to the other
+    pub total_cost: f64,
+    pub transaction_uuid: Option<Uuid>,
+    pub performed_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub lines: Vec<ConversionLine>,
+}
+
+/// Split a parent's unit cost across its components by weight. Returns the
+/// cost of one unit of each component, in the order given; the results
+/// multiplied back by quantity add up to the parent's cost (to the cent).
+pub fn allocate_unit_costs(parent_unit_cost: f64, components: &[(i32, Option<f64>)]) -> Vec<f64> {
+    let weight = |w: Option<f64>| w.filter(|w| *w > 0.0).unwrap_or(1.0);
+    let total_weight: f64 = components
+        .iter()
+        .map(|(quantity, w)| *quantity as f64 * weight(*w))
+        .sum();
+    if total_weight <= 0.0 {
+        return vec![0.0; components.len()];
+    }
+    components
+        .iter()
+        .map(|(_, w)| round_cents(parent_unit_cost * weight(*w) / total_weight))
+        .collect()
+}
+
+/// How many kits the component stock could build; 0 if the product is not a kit.
+/// Consigned and serialized stock is never used for kits.
+const KITS_BUILDABLE_SQL: &str = "SELECT MIN(COALESCE(s.available, 0) / c.quantity)
+     FROM Product_Bom_Components c
+     JOIN Product_Boms b ON b.product_uuid = c.product_uuid AND b.bom_type = 'Kit'
+     LEFT JOIN (SELECT product_uuid, SUM(quantity_on_hand) AS available
+                FROM Local_Inventory
+                WHERE deleted_at IS NULL AND serialized_details IS NULL AND quantity_on_hand > 0
+                  AND (? IS NULL OR location_tag = ?)
+                  AND inventory_uuid NOT IN (SELECT inventory_uuid FROM Consignment_Items WHERE status = 'Active')
+                GROUP BY product_uuid) s ON s.product_uuid = c.component_product_uuid
+     WHERE c.product_uuid = ?";
+
+/// Kits that could be sold from components, for stock checks at checkout
+pub async fn kits_buildable(
+    pool: &sqlx::SqlitePool,
+    product_uuid: Uuid,
+    location_tag: Option<&str>,
+) -> Result<i32> {
+    let buildable: Option<i64> = sqlx::query_scalar(KITS_BUILDABLE_SQL)
+        .bind(location_tag)
+        .bind(location_tag)
+        .bind(product_uuid.to_string())
+        .fetch_one(pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    Ok(buildable.unwrap_or(0) as i32)
+}
+
+/// When a kit sale needs more than the `available` assembled stock, consume
+/// components for the shortfall. Returns how many kits came from components;
+/// 0 when there was enough stock or the product isn't a kit, leaving the
+/// caller's own stock check to fail as usual.
+pub async fn consume_kit_shortfall_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    product_uuid: Uuid,
+    available: i32,
+    needed: i32,
+    location_tag: Option<&str>,
+    condition: &str,
+    source: &MovementSource,
+) -> Result<i32> {
+    if available >= needed {
+        return Ok(0);
+    }
+    let Some(bom) = load_bom_with_tx(tx, product_uuid).await? else {
+        return Ok(0);
+    };
+    if bom.bom_type != BomType::Kit {
+        return Ok(0);
+    }
+
+    let kits = needed - available.max(0);
+    let mut lines = Vec::new();
+    let mut total_cost = 0.0;
+    for component in &bom.components {
+        for (line, cost) in take_from_piles_with_tx(
+            tx,
+            component,
+            component.quantity * kits,
+            location_tag,
+            Some(condition),
+            source,
+        )
+        .await?
+        {
+            total_cost += cost;
+            lines.push(line);
+        }
+    }
+
+    insert_conversion_with_tx(
+        tx,
+        &InventoryConversion {
+            conversion_uuid: Uuid::new_v4(),
+            conversion_type: ConversionType::KitSale,
+            product_uuid,
+            quantity: kits,
+            total_cost: round_cents(total_cost),
+            transaction_uuid: source.source_uuid,
+            performed_by: source.user_uuid,
+            created_at: Utc::now(),
+            lines,
+        },
+    )
+    .await?;
+    Ok(kits)
+}
+
+/// Put back the components a sale consumed for kits. Returns the number of
+/// kits restored per kit product so the caller doesn't also restock them.
+pub async fn restore_kit_sale_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+    source: &MovementSource,
+) -> Result<HashMap<Uuid, i32>> {
+    let conversions: Vec<(String, String, i32)> = sqlx::query_as(
+        "SELECT conversion_uuid, product_uuid, quantity FROM Inventory_Conversions
+         WHERE transaction_uuid = ? AND conversion_type = 'KitSale'",
+    )
+    .bind(transaction_uuid.to_string())
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let mut restored = HashMap::new();
+    for (conversion_uuid, product_uuid, quantity) in conversions {
+        let lines: Vec<(String, i32)> = sqlx::query_as(
+            "SELECT inventory_uuid, quantity_change FROM Inventory_Conversion_Lines
+             WHERE conversion_uuid = ?",
+        )
+        .bind(&conversion_uuid)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        for (inventory_uuid, quantity_change) in lines {
+            movements::adjust_quantity_with_tx(
+                tx,
+                Uuid::parse_str(&inventory_uuid)?,
+                -quantity_change,
+                source,
+            )
+            .await?;
+        }
+        *restored.entry(Uuid::parse_str(&product_uuid)?).or_insert(0) += quantity;
+    }
+    Ok(restored)
+}
+
+async fn load_bom_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    product_uuid: Uuid,
+) -> Result<Option<ProductBom>> {
+    let header = sqlx::query("SELECT * FROM Product_Boms WHERE product_uuid = ?")
+        .bind(product_uuid.to_string())
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    let Some(header) = header else {
+        return Ok(None);
+    };
+
+    let rows = sqlx::query(
+        "SELECT c.component_product_uuid, c.quantity, c.cost_weight, g.name AS component_name
+         FROM Product_Bom_Components c
+         LEFT JOIN Global_Catalog g ON g.product_uuid = c.component_product_uuid
+         WHERE c.product_uuid = ?
+         ORDER BY g.name, c.component_product_uuid",
+    )
+    .bind(product_uuid.to_string())
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let components = rows.iter().filter_map(map_component).collect();
+    Ok(map_bom(&header, components))
+}
+
+/// Take `needed` units of a component from its piles, store-owned bulk stock
+/// only, preferring the given condition then the largest piles. Returns each
+/// pile drawn from with the cost taken out of it.
+async fn take_from_piles_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    component: &BomComponent,
+    needed: i32,
+    location_tag: Option<&str>,
+    prefer_condition: Option<&str>,
+    source: &MovementSource,
+) -> Result<Vec<(ConversionLine, f64)>> {
+    let piles: Vec<(String, i32, Option<f64>)> = sqlx::query_as(
+        "SELECT inventory_uuid, quantity_on_hand, cost_basis FROM Local_Inventory
+         WHERE product_uuid = ? AND deleted_at IS NULL AND serialized_details IS NULL
+           AND quantity_on_hand > 0 AND (? IS NULL OR location_tag = ?)
+           AND inventory_uuid NOT IN (SELECT inventory_uuid FROM Consignment_Items WHERE status = 'Active')
+         ORDER BY condition = ? DESC, quantity_on_hand DESC, inventory_uuid",
+    )
+    .bind(component.component_product_uuid.to_string())
+    .bind(location_tag)
+    .bind(location_tag)
+    .bind(prefer_condition)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let available: i32 = piles.iter().map(|(_, q, _)| *q).sum();
+    if available < needed {
+        return Err(anyhow::anyhow!(
+            "Not enough {} to build: {} needed, {} available",
+            component
+                .component_name
+                .clone()
+                .unwrap_or_else(|| component.component_product_uuid.to_string()),
+            needed,
+            available
+        ));
+    }
+
+    let mut remaining = needed;
+    let mut taken = Vec::new();
+    for (inventory_uuid, on_hand, cost_basis) in piles {
+        if remaining <= 0 {
+            break;
+        }
+        let quantity = remaining.min(on_hand);
+        remaining -= quantity;
+        let inventory_uuid = Uuid::parse_str(&inventory_uuid)?;
+        movements::adjust_quantity_with_tx(tx, inventory_uuid, -quantity, source).await?;
+        let unit_cost = cost_basis.unwrap_or(0.0);
+        taken.push((
+            ConversionLine {
+                inventory_uuid,
+                product_uuid: component.component_product_uuid,
+                quantity_change: -quantity,
+                unit_cost,
+            },
+            unit_cost * quantity as f64,
+        ));
+    }
+    Ok(taken)
+}
+
+/// Add units to the product's bulk pile at a location, creating it if
+/// needed. A known unit cost is blended into the pile's cost basis.
+async fn add_to_pile_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    product_uuid: Uuid,
+    condition: &str,
+    location_tag: &str,
+    quantity: i32,
+    unit_cost: Option<f64>,
+    source: &MovementSource,
+) -> Result<Uuid> {
+    let existing: Option<(String, i32, Option<f64>)> = sqlx::query_as(
+        "SELECT inventory_uuid, quantity_on_hand, cost_basis FROM Local_Inventory
+         WHERE product_uuid = ? AND condition = ? AND location_tag = ?
+           AND serialized_details IS NULL AND specific_price IS NULL AND deleted_at IS NULL
+           AND inventory_uuid NOT IN (SELECT inventory_uuid FROM Consignment_Items WHERE status = 'Active')
+         ORDER BY inventory_uuid ASC LIMIT 1",
+    )
+    .bind(product_uuid.to_string())
+    .bind(condition)
+    .bind(location_tag)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let inventory_uuid = match existing {
+        Some((inventory_uuid, on_hand, cost_basis)) => {
+            if let Some(unit_cost) = unit_cost {
+                sqlx::query("UPDATE Local_Inventory SET cost_basis = ? WHERE inventory_uuid = ?")
+                    .bind(weighted_average_cost(
+                        on_hand, cost_basis, quantity, unit_cost,
+                    ))
+                    .bind(&inventory_uuid)
+                    .execute(&mut **tx)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to update inventory: {}", e))?;
+            }
+            Uuid::parse_str(&inventory_uuid)?
+        }
+        None => {
+            let inventory_uuid = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO Local_Inventory
+                 (inventory_uuid, product_uuid, condition, quantity_on_hand, location_tag, cost_basis, received_date)
+                 VALUES (?, ?, ?, 0, ?, ?, ?)",
+            )
+            .bind(inventory_uuid.to_string())
+            .bind(product_uuid.to_string())
+            .bind(condition)
+            .bind(location_tag)
+            .bind(unit_cost.map(round_cents))
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create inventory: {}", e))?;
+            inventory_uuid
+        }
+    };
+
+    movements::adjust_quantity_with_tx(tx, inventory_uuid, quantity, source).await?;
+    Ok(inventory_uuid)
+}
+
+async fn insert_conversion_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    conversion: &InventoryConversion,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO Inventory_Conversions
+         (conversion_uuid, conversion_type, product_uuid, quantity, total_cost, transaction_uuid, performed_by, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(conversion.conversion_uuid.to_string())
+    .bind(conversion.conversion_type.to_string())
+    .bind(conversion.product_uuid.to_string())
+    .bind(conversion.quantity)
+    .bind(conversion.total_cost)
+    .bind(conversion.transaction_uuid.map(|u| u.to_string()))
+    .bind(conversion.performed_by.map(|u| u.to_string()))
+    .bind(conversion.created_at.to_rfc3339())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record conversion: {}", e))?;
+
+    for line in &conversion.lines {
+        sqlx::query(
+            "INSERT INTO Inventory_Conversion_Lines
+             (conversion_uuid, inventory_uuid, product_uuid, quantity_change, unit_cost)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(conversion.conversion_uuid.to_string())
+        .bind(line.inventory_uuid.to_string())
+        .bind(line.product_uuid.to_string())
+        .bind(line.quantity_change)
+        .bind(line.unit_cost)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to record conversion line: {}", e))?;
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct KittingService {
+    db: Arc<Database>,
+}
+
+impl KittingService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Define (or replace) what a product is made of
+    pub async fn set_bom(&self, product_uuid: Uuid, request: SetBomRequest) -> Result<ProductBom> {
+        if request.components.is_empty() {
+            return Err(anyhow::anyhow!(
+                "A bill of materials needs at least one component"
+            ));
+        }
+        let mut seen = std::collections::HashSet::new();
+        for component in &request.components {
+            if component.component_product_uuid == product_uuid {
+                return Err(anyhow::anyhow!("A product cannot contain itself"));
+            }
+            if component.quantity <= 0 {
+                return Err(anyhow::anyhow!("Component quantities must be positive"));
+            }
+            if component.cost_weight.is_some_and(|w| w < 0.0) {
+                return Err(anyhow::anyhow!("Cost weights cannot be negative"));
+            }
+            if !seen.insert(component.component_product_uuid) {
+                return Err(anyhow::anyhow!(
+                    "Component {} is listed twice",
+                    component.component_product_uuid
+                ));
+            }
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        for product in std::iter::once(product_uuid)
+            .chain(request.components.iter().map(|c| c.component_product_uuid))
+        {
+            let exists: Option<String> = sqlx::query_scalar(
+                "SELECT product_uuid FROM Global_Catalog WHERE product_uuid = ? AND deleted_at IS NULL",
+            )
+            .bind(product.to_string())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            if exists.is_none() {
+                return Err(anyhow::anyhow!("Product {} not found", product));
+            }
+        }
+
+        // Kit sales consume components one level deep, so kits can't nest
+        if request.bom_type == BomType::Kit {
+            for component in &request.components {
+                if let Some(bom) =
+                    load_bom_with_tx(&mut tx, component.component_product_uuid).await?
+                {
+                    if bom.bom_type == BomType::Kit {
+                        return Err(anyhow::anyhow!(
+                            "Kits cannot contain other kits; assemble {} first or make it sealed",
+                            component.component_product_uuid
+                        ));
+                    }
+                }
+            }
+        }
+
+        sqlx::query("DELETE FROM Product_Bom_Components WHERE product_uuid = ?")
+            .bind(product_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        sqlx::query(
+            "INSERT OR REPLACE INTO Product_Boms (product_uuid, bom_type, notes, updated_at)
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(product_uuid.to_string())
+        .bind(request.bom_type.to_string())
+        .bind(&request.notes)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to save bill of materials: {}", e))?;
+        for component in &request.components {
+            sqlx::query(
+                "INSERT INTO Product_Bom_Components (product_uuid, component_product_uuid, quantity, cost_weight)
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(product_uuid.to_string())
+            .bind(component.component_product_uuid.to_string())
+            .bind(component.quantity)
+            .bind(component.cost_weight)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to save component: {}", e))?;
+        }
+
+        let bom = load_bom_with_tx(&mut tx, product_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Bill of materials for {} not found", product_uuid))?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        Ok(bom)
+    }
+
+    pub async fn get_bom(&self, product_uuid: Uuid) -> Result<Option<ProductBom>> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        let bom = load_bom_with_tx(&mut tx, product_uuid).await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        Ok(bom)
+    }
+
+    pub async fn delete_bom(&self, product_uuid: Uuid) -> Result<()> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        sqlx::query("DELETE FROM Product_Bom_Components WHERE product_uuid = ?")
+            .bind(product_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let deleted = sqlx::query("DELETE FROM Product_Boms WHERE product_uuid = ?")
+            .bind(product_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if deleted.rows_affected() == 0 {
+            return Err(anyhow::anyhow!(
+                "Product {} has no bill of materials",
+                product_uuid
+            ));
+        }
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        Ok(())
+    }
+
+    /// Open units of a parent pile into component stock at the same location
+    /// and condition. The parent's cost is split across the components by
+    /// their cost weights.
+    pub async fn break_product(
+        &self,
+        request: BreakRequest,
+        performed_by: Option<Uuid>,
+    ) -> Result<InventoryConversion> {
+        if request.quantity <= 0 {
+            return Err(anyhow::anyhow!("Quantity must be positive"));
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let parent: Option<(String, i32, Option<f64>, String, String)> = sqlx::query_as(
+            "SELECT product_uuid, quantity_on_hand, cost_basis, condition, location_tag
+             FROM Local_Inventory WHERE inventory_uuid = ? AND deleted_at IS NULL",
+        )
+        .bind(request.inventory_uuid.to_string())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let (product_uuid, on_hand, cost_basis, condition, location_tag) =
+            parent.ok_or_else(|| {
+                anyhow::anyhow!("Inventory item {} not found", request.inventory_uuid)
+            })?;
+        let product_uuid = Uuid::parse_str(&product_uuid)?;
+        if on_hand < request.quantity {
+            return Err(anyhow::anyhow!(
+                "Only {} on hand to break, {} requested",
+                on_hand,
+                request.quantity
+            ));
+        }
+        let bom = load_bom_with_tx(&mut tx, product_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Product {} has no bill of materials", product_uuid))?;
+
+        let conversion_uuid = Uuid::new_v4();
+        let source = MovementSource::new(
+            MovementType::Break,
+            Some(conversion_uuid),
+            performed_by,
+            &self.db.node_id,
+        );
+
+        movements::adjust_quantity_with_tx(
+            &mut tx,
+            request.inventory_uuid,
+            -request.quantity,
+            &source,
+        )
+        .await?;
+        let mut lines = vec![ConversionLine {
+            inventory_uuid: request.inventory_uuid,
+            product_uuid,
+            quantity_change: -request.quantity,
+            unit_cost: cost_basis.unwrap_or(0.0),
+        }];
+
+        let unit_costs = allocate_unit_costs(
+            cost_basis.unwrap_or(0.0),
+            &bom.components
+                .iter()
+                .map(|c| (c.quantity, c.cost_weight))
+                .collect::<Vec<_>>(),
+        );
+        for (component, unit_cost) in bom.components.iter().zip(unit_costs) {
+            let quantity = component.quantity * request.quantity;
+            // An uncosted parent leaves the component piles' costs alone
+            let unit_cost = cost_basis.map(|_| unit_cost);
+            let inventory_uuid = add_to_pile_with_tx(
+                &mut tx,
+                component.component_product_uuid,
+                &condition,
+                &location_tag,
+                quantity,
+                unit_cost,
+                &source,
+            )
+            .await?;
+            lines.push(ConversionLine {
+                inventory_uuid,
+                product_uuid: component.component_product_uuid,
+                quantity_change: quantity,
+                unit_cost: unit_cost.unwrap_or(0.0),
+            });
+        }
+
+        let conversion = InventoryConversion {
+            conversion_uuid,
+            conversion_type: ConversionType::Break,
+            product_uuid,
+            quantity: request.quantity,
+            total_cost: round_cents(cost_basis.unwrap_or(0.0) * request.quantity as f64),
+            transaction_uuid: None,
+            performed_by,
+            created_at: Utc::now(),
+            lines,
+        };
+        insert_conversion_with_tx(&mut tx, &conversion).await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!(
+            "Broke {} x {} into {} component lines",
+            request.quantity,
+            product_uuid,
+            conversion.lines.len() - 1
+        );
+        Ok(conversion)
+    }
+
+    /// Build parents from component stock at a location. The parent is
+    /// costed at what the consumed components cost.
+    pub async fn assemble(
+        &self,
+        product_uuid: Uuid,
+        request: AssembleRequest,
+        performed_by: Option<Uuid>,
+    ) -> Result<InventoryConversion> {
+        if request.quantity <= 0 {
+            return Err(anyhow::anyhow!("Quantity must be positive"));
+        }
+        let condition = format!(
+            "{:?}",
+            request.condition.unwrap_or(crate::core::Condition::New)
+        );
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        let bom = load_bom_with_tx(&mut tx, product_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Product {} has no bill of materials", product_uuid))?;
+
+        let conversion_uuid = Uuid::new_v4();
+        let source = MovementSource::new(
+            MovementType::Assemble,
+            Some(conversion_uuid),
+            performed_by,
+            &self.db.node_id,
+        );
+
+        let mut lines = Vec::new();
+        let mut total_cost = 0.0;
+        for component in &bom.components {
+            for (line, cost) in take_from_piles_with_tx(
+                &mut tx,
+                component,
+                component.quantity * request.quantity,
+                Some(&request.location_tag),
+                None,
+                &source,
+            )
+            .await?
+            {
+                total_cost += cost;
+                lines.push(line);
+            }
+        }
+
+        let unit_cost = round_cents(total_cost / request.quantity as f64);
+        let inventory_uuid = add_to_pile_with_tx(
+            &mut tx,
+            product_uuid,
+            &condition,
+            &request.location_tag,
+            request.quantity,
+            Some(unit_cost),
+            &source,
+        )
+        .await?;
+        lines.insert(
+            0,
+            ConversionLine {
+                inventory_uuid,
+                product_uuid,
+                quantity_change: request.quantity,
+                unit_cost,
+            },
+        );
+
+        let conversion = InventoryConversion {
+            conversion_uuid,
+            conversion_type: ConversionType::Assemble,
+            product_uuid,
+            quantity: request.quantity,
+            total_cost: round_cents(total_cost),
+            transaction_uuid: None,
+            performed_by,
+            created_at: Utc::now(),
+            lines,
+        };
+        insert_conversion_with_tx(&mut tx, &conversion).await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        Ok(conversion)
+    }
+
+    /// Kits that could be sold from component stock right now
+    pub async fn buildable_quantity(
+        &self,
+        product_uuid: Uuid,
+        location_tag: Option<&str>,
+    ) -> Result<i32> {
+        kits_buildable(&self.db.pool, product_uuid, location_tag).await
+    }
+
+    /// Conversion history, newest first, optionally for one parent product
+    pub async fn get_conversions(
+        &self,
+        product_uuid: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<InventoryConversion>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Inventory_Conversions
+             WHERE (? IS NULL OR product_uuid = ?)
+             ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(product_uuid.map(|u| u.to_string()))
+        .bind(product_uuid.map(|u| u.to_string()))
+        .bind(limit)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut conversions = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let Some(mut conversion) = map_conversion(row) else {
+                continue;
+            };
+            let lines: Vec<(String, String, i32, f64)> = sqlx::query_as(
+                "SELECT inventory_uuid, product_uuid, quantity_change, unit_cost
+                 FROM Inventory_Conversion_Lines WHERE conversion_uuid = ? ORDER BY id",
+            )
+            .bind(conversion.conversion_uuid.to_string())
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            conversion.lines = lines
+                .into_iter()
+                .filter_map(
+                    |(inventory_uuid, product_uuid, quantity_change, unit_cost)| {
+                        Some(ConversionLine {
+                            inventory_uuid: Uuid::parse_str(&inventory_uuid).ok()?,
+                            product_uuid: Uuid::parse_str(&product_uuid).ok()?,
+                            quantity_change,
+                            unit_cost,
+                        })
+                    },
+                )
+                .collect();
+            conversions.push(conversion);
+        }
+        Ok(conversions)
+    }
+}
+
+fn parse_date(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<DateTime<Utc>> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn parse_uuid(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<Uuid> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| Uuid::parse_str(&s).ok())
+}
+
+fn map_component(row: &sqlx::sqlite::SqliteRow) -> Option<BomComponent> {
+    Some(BomComponent {
+        component_product_uuid: parse_uuid(row, "component_product_uuid")?,
+        component_name: sqlx::Row::try_get(row, "component_name").ok().flatten(),
+        quantity: sqlx::Row::try_get(row, "quantity").ok()?,
+        cost_weight: sqlx::Row::try_get(row, "cost_weight").ok().flatten(),
+    })
+}
+
+fn map_bom(row: &sqlx::sqlite::SqliteRow, components: Vec<BomComponent>) -> Option<ProductBom> {
+    let bom_type: String = sqlx::Row::try_get(row, "bom_type").ok()?;
+    Some(ProductBom {
+        product_uuid: parse_uuid(row, "product_uuid")?,
+        bom_type: BomType::parse(&bom_type)?,
+        components,
+        notes: sqlx::Row::try_get(row, "notes").ok().flatten(),
+        updated_at: parse_date(row, "updated_at")?,
+    })
+}
+
+fn map_conversion(row: &sqlx::sqlite::SqliteRow) -> Option<InventoryConversion> {
+    let conversion_type: String = sqlx::Row::try_get(row, "conversion_type").ok()?;
+    Some(InventoryConversion {
+        conversion_uuid: parse_uuid(row, "conversion_uuid")?,
+        conversion_type: ConversionType::parse(&conversion_type)?,
+        product_uuid: parse_uuid(row, "product_uuid")?,
+        quantity: sqlx::Row::try_get(row, "quantity").ok()?,
+        total_cost: sqlx::Row::try_get(row, "total_cost").unwrap_or(0.0),
+        transaction_uuid: parse_uuid(row, "transaction_uuid"),
+        performed_by: parse_uuid(row, "performed_by"),
+        created_at: parse_date(row, "created_at")?,
+        lines: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_unit_costs() {
+        // Booster box at $108 into 36 packs
+        assert_eq!(allocate_unit_costs(108.0, &[(36, None)]), vec![3.0]);
+
+        // Starter kit: deck weighted 4x a sleeve pack
+        let costs = allocate_unit_costs(30.0, &[(1, Some(4.0)), (2, None)]);
+        assert_eq!(costs, vec![20.0, 5.0]);
+        assert_eq!(costs[0] + 2.0 * costs[1], 30.0);
+
+        // A zero weight falls back to the default
+        assert_eq!(
+            allocate_unit_costs(10.0, &[(1, Some(0.0)), (1, None)]),
+            vec![5.0, 5.0]
+        );
+    }
+
+    #[test]
+    fn test_type_round_trips() {
+        for bom_type in [BomType::Sealed, BomType::Kit] {
+            assert_eq!(BomType::parse(&bom_type.to_string()), Some(bom_type));
+        }
+        for conversion_type in [
+            ConversionType::Break,
+            ConversionType::Assemble,
+            ConversionType::KitSale,
+        ] {
+            assert_eq!(
+                ConversionType::parse(&conversion_type.to_string()),
+                Some(conversion_type)
+            );
+        }
+    }
+}
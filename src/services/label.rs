@@ -1,4 +1,6 @@
+use crate::core::money::format_amount;
 use crate::database::Database;
+use crate::services::CurrencyService;
 use anyhow::Result;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -22,6 +24,22 @@ impl LabelService {
         }
     }
 
+    /// Shelf price in the price's own currency, or a placeholder in the base currency
+    async fn price_display(&self, price: Option<crate::core::PriceInfo>) -> Result<String> {
+        match price {
+            Some(p) => Ok(format_amount(p.market_mid, p.currency)),
+            None => {
+                let base = CurrencyService::new(self.db.clone())
+                    .get_base_currency()
+                    .await?;
+                Ok(match base.symbol() {
+                    Some(symbol) => format!("{}-.--", symbol),
+                    None => format!("-.-- {}", base),
+                })
+            }
+        }
+    }
+
     pub async fn generate_inventory_label_html(&self, inventory_uuid: Uuid) -> Result<String> {
         let item = self
             .db
@@ -43,11 +61,7 @@ impl LabelService {
             .await;
 
         // Use market mid as default label price if available, logic might vary
-        let price_display = if let Some(p) = price {
-            format!("${:.2}", p.market_mid)
-        } else {
-            "$-.--".to_string()
-        };
+        let price_display = self.price_display(price).await?;
 
         // Generate barcode for the specific inventory UUID so it can be scanned for exact match
         let barcode_svg = self
@@ -111,11 +125,7 @@ impl LabelService {
 
         let price = self.pricing_service.get_cached_price(product_uuid).await;
 
-        let price_display = if let Some(p) = price {
-            format!("${:.2}", p.market_mid)
-        } else {
-            "$-.--".to_string()
-        };
+        let price_display = self.price_display(price).await?;
 
         // Prefer existing barcode (UPC), else fallback to product UUID
         let code = product.barcode.unwrap_or(product.product_uuid.to_string());
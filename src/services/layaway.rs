@@ -0,0 +1,310 @@
+//! Layaway payment plans
+//!
+//! Configurable plans layered on top of holds:
+//! - Installment schedules with due dates and grace periods
+//! - Minimum installment payments and late fees
+//! - Forfeiture/restocking rules applied to deposits when a hold expires
+
+use crate::core::money::round_cents;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What happens to money already paid when a layaway expires unpaid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ForfeiturePolicy {
+    /// Everything paid is owed back to the customer (cash/original tender)
+    RefundAll,
+    /// Everything paid is returned as store credit
+    StoreCredit,
+    /// A restocking fee is retained, the remainder is returned as store credit
+    RetainRestockingFee,
+    /// The store keeps everything paid
+    RetainAll,
+}
+
+impl std::fmt::Display for ForfeiturePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForfeiturePolicy::RefundAll => write!(f, "RefundAll"),
+            ForfeiturePolicy::StoreCredit => write!(f, "StoreCredit"),
+            ForfeiturePolicy::RetainRestockingFee => write!(f, "RetainRestockingFee"),
+            ForfeiturePolicy::RetainAll => write!(f, "RetainAll"),
+        }
+    }
+}
+
+impl ForfeiturePolicy {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "StoreCredit" => ForfeiturePolicy::StoreCredit,
+            "RetainRestockingFee" => ForfeiturePolicy::RetainRestockingFee,
+            "RetainAll" => ForfeiturePolicy::RetainAll,
+            _ => ForfeiturePolicy::RefundAll,
+        }
+    }
+
+    /// Split the amount paid into retained and returned portions
+    pub fn settle(
+        &self,
+        amount_paid: f64,
+        total_amount: f64,
+        restocking_fee_percent: f64,
+    ) -> ForfeitureSettlement {
+        let (retained, refund_method) = match self {
+            ForfeiturePolicy::RefundAll => (0.0, Some(RefundMethod::Cash)),
+            ForfeiturePolicy::StoreCredit => (0.0, Some(RefundMethod::StoreCredit)),
+            ForfeiturePolicy::RetainRestockingFee => {
+                let fee = round_cents(total_amount * restocking_fee_percent);
+                (fee.min(amount_paid), Some(RefundMethod::StoreCredit))
+            }
+            ForfeiturePolicy::RetainAll => (amount_paid, None),
+        };
+
+        let returned = round_cents(amount_paid - retained);
+        ForfeitureSettlement {
+            amount_paid,
+            retained,
+            returned,
+            refund_method: if returned > 0.0 { refund_method } else { None },
+        }
+    }
+}
+
+/// How the returned portion of a forfeited layaway is paid back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RefundMethod {
+    Cash,
+    StoreCredit,
+}
+
+impl std::fmt::Display for RefundMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefundMethod::Cash => write!(f, "Cash"),
+            RefundMethod::StoreCredit => write!(f, "StoreCredit"),
+        }
+    }
+}
+
+/// Result of applying a forfeiture policy to an expired hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForfeitureSettlement {
+    pub amount_paid: f64,
+    pub retained: f64,
+    pub returned: f64,
+    pub refund_method: Option<RefundMethod>,
+}
+
+/// A configurable layaway plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayawayPlan {
+    pub plan_uuid: Uuid,
+    pub name: String,
+    /// Number of installments after the deposit
+    pub installment_count: i32,
+    /// Days between installment due dates
+    pub installment_interval_days: i64,
+    pub minimum_deposit_percent: f64,
+    /// Days after a due date before an installment is considered late
+    pub grace_period_days: i64,
+    /// Flat fee charged per late installment
+    pub late_fee: f64,
+    pub forfeiture_policy: ForfeiturePolicy,
+    pub restocking_fee_percent: f64,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LayawayPlan {
+    /// Build the installment schedule for the amount left after the deposit.
+    ///
+    /// Amounts are split evenly to the cent; the final installment absorbs
+    /// any rounding remainder so the schedule always sums to `financed`.
+    pub fn build_schedule(
+        &self,
+        start: DateTime<Utc>,
+        financed: f64,
+    ) -> Vec<(i32, DateTime<Utc>, f64)> {
+        let count = self.installment_count.max(1);
+        let financed_cents = (financed * 100.0).round() as i64;
+        if financed_cents <= 0 {
+            return Vec::new();
+        }
+
+        let base_cents = financed_cents / count as i64;
+        let mut schedule = Vec::with_capacity(count as usize);
+        let mut allocated = 0i64;
+
+        for sequence in 1..=count {
+            let cents = if sequence == count {
+                financed_cents - allocated
+            } else {
+                base_cents
+            };
+            allocated += cents;
+            let due_date = start + Duration::days(self.installment_interval_days * sequence as i64);
+            schedule.push((sequence, due_date, cents as f64 / 100.0));
+        }
+
+        schedule
+    }
+
+    /// Final date a hold on this plan may remain open: the last due date plus grace
+    pub fn final_deadline(&self, start: DateTime<Utc>) -> DateTime<Utc> {
+        start
+            + Duration::days(
+                self.installment_interval_days * self.installment_count.max(1) as i64
+                    + self.grace_period_days,
+            )
+    }
+}
+
+/// Request to create a layaway plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateLayawayPlanRequest {
+    pub name: String,
+    pub installment_count: i32,
+    pub installment_interval_days: i64,
+    pub minimum_deposit_percent: Option<f64>,
+    pub grace_period_days: Option<i64>,
+    pub late_fee: Option<f64>,
+    pub forfeiture_policy: Option<ForfeiturePolicy>,
+    pub restocking_fee_percent: Option<f64>,
+}
+
+/// Installment status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstallmentStatus {
+    Pending,
+    Paid,
+    Late,
+    Waived,
+}
+
+impl std::fmt::Display for InstallmentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallmentStatus::Pending => write!(f, "Pending"),
+            InstallmentStatus::Paid => write!(f, "Paid"),
+            InstallmentStatus::Late => write!(f, "Late"),
+            InstallmentStatus::Waived => write!(f, "Waived"),
+        }
+    }
+}
+
+impl InstallmentStatus {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "Paid" => InstallmentStatus::Paid,
+            "Late" => InstallmentStatus::Late,
+            "Waived" => InstallmentStatus::Waived,
+            _ => InstallmentStatus::Pending,
+        }
+    }
+}
+
+/// A scheduled installment on a hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldInstallment {
+    pub installment_uuid: Uuid,
+    pub hold_uuid: Uuid,
+    pub sequence: i32,
+    pub due_date: DateTime<Utc>,
+    pub amount_due: f64,
+    pub amount_paid: f64,
+    pub late_fee: f64,
+    pub status: InstallmentStatus,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+impl HoldInstallment {
+    /// Amount still owed on this installment, including any late fee
+    pub fn outstanding(&self) -> f64 {
+        round_cents((self.amount_due + self.late_fee - self.amount_paid).max(0.0))
+    }
+}
+
+/// Outstanding layaway liability across all active holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayawayLiabilityReport {
+    pub generated_at: DateTime<Utc>,
+    pub active_holds: i64,
+    /// Customer money held against undelivered merchandise
+    pub deposits_held: f64,
+    /// Balance still to be collected on active holds
+    pub balance_outstanding: f64,
+    /// Retail value of inventory reserved by active holds
+    pub reserved_merchandise_value: f64,
+    pub overdue_installments: i64,
+    pub overdue_amount: f64,
+    pub late_fees_outstanding: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(count: i32) -> LayawayPlan {
+        LayawayPlan {
+            plan_uuid: Uuid::new_v4(),
+            name: "Test".to_string(),
+            installment_count: count,
+            installment_interval_days: 14,
+            minimum_deposit_percent: 0.2,
+            grace_period_days: 3,
+            late_fee: 5.0,
+            forfeiture_policy: ForfeiturePolicy::RetainRestockingFee,
+            restocking_fee_percent: 0.1,
+            is_active: true,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_schedule_sums_to_financed_amount() {
+        let start = Utc::now();
+        let schedule = plan(3).build_schedule(start, 100.0);
+
+        assert_eq!(schedule.len(), 3);
+        assert_eq!(schedule[0].2, 33.33);
+        assert_eq!(schedule[2].2, 33.34);
+        let total: f64 = schedule.iter().map(|(_, _, a)| a).sum();
+        assert!((total - 100.0).abs() < 0.001);
+        assert_eq!(schedule[1].1, start + Duration::days(28));
+    }
+
+    #[test]
+    fn test_final_deadline_includes_grace() {
+        let start = Utc::now();
+        assert_eq!(
+            plan(4).final_deadline(start),
+            start + Duration::days(4 * 14 + 3)
+        );
+    }
+
+    #[test]
+    fn test_restocking_fee_settlement() {
+        let settlement = ForfeiturePolicy::RetainRestockingFee.settle(50.0, 200.0, 0.1);
+        assert_eq!(settlement.retained, 20.0);
+        assert_eq!(settlement.returned, 30.0);
+        assert_eq!(settlement.refund_method, Some(RefundMethod::StoreCredit));
+
+        // Fee can never exceed what the customer actually paid
+        let settlement = ForfeiturePolicy::RetainRestockingFee.settle(10.0, 200.0, 0.1);
+        assert_eq!(settlement.retained, 10.0);
+        assert_eq!(settlement.returned, 0.0);
+        assert_eq!(settlement.refund_method, None);
+    }
+
+    #[test]
+    fn test_retain_all_and_refund_all() {
+        let kept = ForfeiturePolicy::RetainAll.settle(40.0, 100.0, 0.0);
+        assert_eq!(kept.retained, 40.0);
+        assert_eq!(kept.refund_method, None);
+
+        let refunded = ForfeiturePolicy::RefundAll.settle(40.0, 100.0, 0.0);
+        assert_eq!(refunded.returned, 40.0);
+        assert_eq!(refunded.refund_method, Some(RefundMethod::Cash));
+    }
+}
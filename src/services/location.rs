@@ -1,10 +1,29 @@
+//! Store locations and store-to-store transfers
+//!
+//! A transfer moves through Pending → Approved → InTransit → Received.
+//! Shipping takes the units out of the source piles and parks them in
+//! per-line piles under the `TRANSIT` location tag; receiving posts them from
+//! there into the destination. Short receipts leave the rest in transit until
+//! they arrive or the transfer is closed (written off or sent back), and over
+//! receipts are posted and show up as a positive variance.
+//!
+//! Serialized and consigned piles travel whole so their certificates and
+//! consignment records stay attached. The transfer document and every pile it
+//! touches are queued for sync, so a transfer shipped at one store's node can
+//! be received at another's.
+
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
 use crate::database::Database;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::Row;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Location tag for stock that has shipped but not yet been received
+pub const TRANSIT_LOCATION: &str = "TRANSIT";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Location {
     pub location_uuid: Uuid,
@@ -31,11 +50,23 @@ pub struct TransferRequest {
     pub status: TransferStatus,
     pub requested_by: Uuid,
     pub approved_by: Option<Uuid>,
+    #[serde(default)]
+    pub shipped_by: Option<Uuid>,
+    #[serde(default)]
+    pub shipped_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub received_by: Option<Uuid>,
+    #[serde(default)]
+    pub received_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub notes: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
-    pub items: Vec<TransferItem>,
+    pub items: Vec<TransferLine>,
 }
 
+/// A line to request: a specific pile, or a quantity of a product to be
+/// drawn from the source store's bulk piles
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransferItem {
     pub product_uuid: Uuid,
@@ -43,16 +74,255 @@ pub struct TransferItem {
     pub quantity: i32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferLine {
+    pub transfer_item_uuid: Uuid,
+    pub product_uuid: Uuid,
+    #[serde(default)]
+    pub product_name: Option<String>,
+    /// Source pile
+    pub inventory_uuid: Option<Uuid>,
+    pub quantity: i32,
+    pub quantity_shipped: i32,
+    pub quantity_received: i32,
+    /// Serialized or consigned: the pile itself travels
+    pub whole_pile: bool,
+    pub transit_inventory_uuid: Option<Uuid>,
+    pub shortage_resolution: Option<ShortageResolution>,
+    /// Received minus shipped; negative is short, positive is over
+    #[serde(default)]
+    pub variance: i32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum TransferStatus {
     Pending,
     Approved,
     InTransit,
+    PartiallyReceived,
     Received,
     Cancelled,
 }
 
+impl TransferStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferStatus::Pending => "pending",
+            TransferStatus::Approved => "approved",
+            TransferStatus::InTransit => "in_transit",
+            TransferStatus::PartiallyReceived => "partially_received",
+            TransferStatus::Received => "received",
+            TransferStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(TransferStatus::Pending),
+            "approved" => Some(TransferStatus::Approved),
+            "in_transit" => Some(TransferStatus::InTransit),
+            "partially_received" => Some(TransferStatus::PartiallyReceived),
+            "received" => Some(TransferStatus::Received),
+            "cancelled" => Some(TransferStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// What to do with units that never arrived
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortageResolution {
+    /// Lost in transit
+    WriteOff,
+    /// Never left, or coming back: post them to the source again
+    ReturnToSource,
+}
+
+impl ShortageResolution {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ShortageResolution::WriteOff => "write_off",
+            ShortageResolution::ReturnToSource => "return_to_source",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "write_off" => Some(ShortageResolution::WriteOff),
+            "return_to_source" => Some(ShortageResolution::ReturnToSource),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferReceipt {
+    pub transfer_item_uuid: Uuid,
+    pub quantity: i32,
+}
+
+fn parse_uuid(s: Option<String>) -> Option<Uuid> {
+    s.and_then(|s| Uuid::parse_str(&s).ok())
+}
+
+fn parse_date(s: Option<String>) -> Option<DateTime<Utc>> {
+    s.and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|d| d.with_timezone(&Utc))
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Load a transfer with its lines on any connection, including one inside a transaction
+async fn load_transfer(
+    conn: &mut sqlx::SqliteConnection,
+    transfer_uuid: Uuid,
+) -> Result<Option<TransferRequest>> {
+    let Some(row) = sqlx::query(
+        "SELECT transfer_uuid, source_location, target_location, status, requested_by, approved_by,
+                shipped_by, shipped_at, received_by, received_at, notes, created_at, updated_at
+         FROM Inventory_Transfers WHERE transfer_uuid = ?",
+    )
+    .bind(transfer_uuid.to_string())
+    .fetch_optional(&mut *conn)
+    .await
+    .context("Failed to fetch transfer")?
+    else {
+        return Ok(None);
+    };
+
+    let status: String = row.try_get("status")?;
+    let lines = sqlx::query(
+        "SELECT ti.transfer_item_uuid, ti.product_uuid, g.name, ti.inventory_uuid, ti.quantity,
+                ti.quantity_shipped, ti.quantity_received, ti.whole_pile, ti.transit_inventory_uuid,
+                ti.shortage_resolution
+         FROM Transfer_Items ti
+         LEFT JOIN Global_Catalog g ON g.product_uuid = ti.product_uuid
+         WHERE ti.transfer_uuid = ?
+         ORDER BY g.name, ti.transfer_item_uuid",
+    )
+    .bind(transfer_uuid.to_string())
+    .fetch_all(&mut *conn)
+    .await
+    .context("Failed to fetch transfer items")?;
+
+    let status = TransferStatus::parse(&status).unwrap_or(TransferStatus::Pending);
+    let receiving_done = matches!(
+        status,
+        TransferStatus::PartiallyReceived | TransferStatus::Received
+    );
+    let mut items = Vec::with_capacity(lines.len());
+    for line in lines {
+        let shipped: i32 = line.try_get("quantity_shipped")?;
+        let received: i32 = line.try_get("quantity_received")?;
+        items.push(TransferLine {
+            transfer_item_uuid: Uuid::parse_str(&line.try_get::<String, _>("transfer_item_uuid")?)?,
+            product_uuid: Uuid::parse_str(&line.try_get::<String, _>("product_uuid")?)?,
+            product_name: line.try_get("name")?,
+            inventory_uuid: parse_uuid(line.try_get("inventory_uuid")?),
+            quantity: line.try_get("quantity")?,
+            quantity_shipped: shipped,
+            quantity_received: received,
+            whole_pile: line.try_get::<i32, _>("whole_pile")? != 0,
+            transit_inventory_uuid: parse_uuid(line.try_get("transit_inventory_uuid")?),
+            shortage_resolution: line
+                .try_get::<Option<String>, _>("shortage_resolution")?
+                .and_then(|s| ShortageResolution::parse(&s)),
+            variance: if receiving_done {
+                received - shipped
+            } else {
+                0
+            },
+        });
+    }
+
+    Ok(Some(TransferRequest {
+        transfer_uuid,
+        source_location: row.try_get("source_location")?,
+        target_location: row.try_get("target_location")?,
+        status,
+        requested_by: parse_uuid(row.try_get("requested_by")?).unwrap_or_default(),
+        approved_by: parse_uuid(row.try_get("approved_by")?),
+        shipped_by: parse_uuid(row.try_get("shipped_by")?),
+        shipped_at: parse_date(row.try_get("shipped_at")?),
+        received_by: parse_uuid(row.try_get("received_by")?),
+        received_at: parse_date(row.try_get("received_at")?),
+        notes: row.try_get("notes")?,
+        created_at: parse_date(row.try_get("created_at")?).unwrap_or_else(Utc::now),
+        updated_at: parse_date(row.try_get("updated_at")?).unwrap_or_else(Utc::now),
+        items,
+    }))
+}
+
+/// Store a transfer document received from another node. Stock isn't
+/// touched here: the piles the sender changed arrive as their own records.
+pub async fn apply_synced_transfer(
+    pool: &sqlx::SqlitePool,
+    transfer: &TransferRequest,
+) -> Result<()> {
+    let mut tx = pool.begin().await.context("Failed to begin transaction")?;
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO Inventory_Transfers
+         (transfer_uuid, source_location, target_location, status, requested_by, approved_by,
+          shipped_by, shipped_at, received_by, received_at, notes, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(transfer.transfer_uuid.to_string())
+    .bind(&transfer.source_location)
+    .bind(&transfer.target_location)
+    .bind(transfer.status.as_str())
+    .bind(transfer.requested_by.to_string())
+    .bind(transfer.approved_by.map(|u| u.to_string()))
+    .bind(transfer.shipped_by.map(|u| u.to_string()))
+    .bind(transfer.shipped_at.map(|d| d.to_rfc3339()))
+    .bind(transfer.received_by.map(|u| u.to_string()))
+    .bind(transfer.received_at.map(|d| d.to_rfc3339()))
+    .bind(&transfer.notes)
+    .bind(transfer.created_at.to_rfc3339())
+    .bind(transfer.updated_at.to_rfc3339())
+    .execute(&mut *tx)
+    .await
+    .context("Failed to store synced transfer")?;
+
+    sqlx::query("DELETE FROM Transfer_Items WHERE transfer_uuid = ?")
+        .bind(transfer.transfer_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .context("Failed to replace transfer items")?;
+    for line in &transfer.items {
+        sqlx::query(
+            "INSERT INTO Transfer_Items
+             (transfer_item_uuid, transfer_uuid, product_uuid, inventory_uuid, quantity, quantity_shipped,
+              quantity_received, whole_pile, transit_inventory_uuid, shortage_resolution)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(line.transfer_item_uuid.to_string())
+        .bind(transfer.transfer_uuid.to_string())
+        .bind(line.product_uuid.to_string())
+        .bind(line.inventory_uuid.map(|u| u.to_string()))
+        .bind(line.quantity)
+        .bind(line.quantity_shipped)
+        .bind(line.quantity_received)
+        .bind(line.whole_pile)
+        .bind(line.transit_inventory_uuid.map(|u| u.to_string()))
+        .bind(line.shortage_resolution.map(|r| r.as_str()))
+        .execute(&mut *tx)
+        .await
+        .context("Failed to store synced transfer item")?;
+    }
+
+    tx.commit()
+        .await
+        .context("Failed to commit synced transfer")?;
+    Ok(())
+}
+
 pub struct LocationService {
     db: Arc<Database>,
 }
@@ -65,7 +335,8 @@ impl LocationService {
     /// TASK-174: Get all locations
     pub async fn get_locations(&self) -> Result<Vec<Location>> {
         let rows = sqlx::query(
-            "SELECT location_uuid, name, address, location_type, is_active FROM Locations",
+            "SELECT location_uuid, name, address, location_type, is_active FROM Store_Locations
+             ORDER BY is_primary DESC, name",
         )
         .fetch_all(&self.db.pool)
         .await
@@ -73,7 +344,6 @@ impl LocationService {
 
         let mut locations = Vec::new();
         for row in rows {
-            use sqlx::Row;
             let type_str: String = row.try_get("location_type").unwrap_or_default();
 
             locations.push(Location {
@@ -106,8 +376,8 @@ impl LocationService {
         };
 
         sqlx::query(
-            "INSERT INTO Locations (location_uuid, name, address, location_type, is_active)
-             VALUES (?, ?, ?, ?, ?)
+            "INSERT INTO Store_Locations (location_uuid, name, address, location_type, is_active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)
              ON CONFLICT(location_uuid) DO UPDATE SET
              name = excluded.name,
              address = excluded.address,
@@ -119,6 +389,7 @@ impl LocationService {
         .bind(location.address)
         .bind(type_str)
         .bind(location.is_active)
+        .bind(Utc::now().to_rfc3339())
         .execute(&self.db.pool)
         .await
         .context("Failed to upsert location")?;
@@ -127,6 +398,9 @@ impl LocationService {
     }
 
     /// TASK-176: Create transfer request
+    ///
+    /// Lines naming a pile must be at the source; lines naming only a product
+    /// are drawn from the source's bulk piles, largest first.
     pub async fn create_transfer_request(
         &self,
         source: String,
@@ -134,6 +408,19 @@ impl LocationService {
         requester: Uuid,
         items: Vec<TransferItem>,
     ) -> Result<Uuid> {
+        if source == target {
+            return Err(anyhow::anyhow!("Source and destination must differ"));
+        }
+        if source == TRANSIT_LOCATION || target == TRANSIT_LOCATION {
+            return Err(anyhow::anyhow!(
+                "{} is reserved for stock in transit",
+                TRANSIT_LOCATION
+            ));
+        }
+        if items.is_empty() {
+            return Err(anyhow::anyhow!("A transfer needs at least one item"));
+        }
+
         let transfer_uuid = Uuid::new_v4();
         let now = Utc::now();
 
@@ -151,8 +438,8 @@ impl LocationService {
              VALUES (?, ?, ?, 'pending', ?, ?, ?)"
         )
         .bind(transfer_uuid.to_string())
-        .bind(source)
-        .bind(target)
+        .bind(&source)
+        .bind(&target)
         .bind(requester.to_string())
         .bind(now.to_rfc3339())
         .bind(now.to_rfc3339())
@@ -160,200 +447,831 @@ impl LocationService {
         .await
         .context("Failed to insert transfer")?;
 
-        // Insert Items
+        // Resolve every item to specific source piles
+        let mut lines: Vec<(Uuid, Uuid, i32, bool)> = Vec::new();
         for item in items {
+            if item.quantity <= 0 {
+                return Err(anyhow::anyhow!("Transfer quantities must be positive"));
+            }
+            match item.inventory_uuid {
+                Some(inventory_uuid) => {
+                    let pile = sqlx::query(
+                        "SELECT li.product_uuid, li.location_tag, li.quantity_on_hand,
+                                li.serialized_details IS NOT NULL
+                                OR EXISTS(SELECT 1 FROM Consignment_Items c
+                                          WHERE c.inventory_uuid = li.inventory_uuid AND c.status = 'Active') AS whole_pile
+                         FROM Local_Inventory li WHERE li.inventory_uuid = ? AND li.deleted_at IS NULL",
+                    )
+                    .bind(inventory_uuid.to_string())
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Inventory item {} not found", inventory_uuid))?;
+                    let location_tag: String = pile.try_get("location_tag")?;
+                    let on_hand: i32 = pile.try_get("quantity_on_hand")?;
+                    let whole_pile = pile.try_get::<i32, _>("whole_pile")? != 0;
+                    if pile.try_get::<String, _>("product_uuid")? != item.product_uuid.to_string() {
+                        return Err(anyhow::anyhow!(
+                            "Inventory item {} is a different product",
+                            inventory_uuid
+                        ));
+                    }
+                    if location_tag != source {
+                        return Err(anyhow::anyhow!(
+                            "Inventory item {} is at {}, not {}",
+                            inventory_uuid,
+                            location_tag,
+                            source
+                        ));
+                    }
+                    if whole_pile && item.quantity != on_hand {
+                        return Err(anyhow::anyhow!(
+                            "Serialized and consigned items transfer as a whole pile of {}",
+                            on_hand
+                        ));
+                    }
+                    lines.push((item.product_uuid, inventory_uuid, item.quantity, whole_pile));
+                }
+                None => {
+                    let piles: Vec<(String, i32)> = sqlx::query_as(
+                        "SELECT inventory_uuid, quantity_on_hand FROM Local_Inventory
+                         WHERE product_uuid = ? AND location_tag = ? AND quantity_on_hand > 0
+                           AND serialized_details IS NULL AND deleted_at IS NULL
+                           AND inventory_uuid NOT IN (SELECT inventory_uuid FROM Consignment_Items WHERE status = 'Active')
+                         ORDER BY quantity_on_hand DESC",
+                    )
+                    .bind(item.product_uuid.to_string())
+                    .bind(&source)
+                    .fetch_all(&mut *tx)
+                    .await?;
+                    let mut remaining = item.quantity;
+                    for (inventory_uuid, on_hand) in piles {
+                        if remaining == 0 {
+                            break;
+                        }
+                        let take = on_hand.min(remaining);
+                        lines.push((
+                            item.product_uuid,
+                            Uuid::parse_str(&inventory_uuid)?,
+                            take,
+                            false,
+                        ));
+                        remaining -= take;
+                    }
+                    if remaining > 0 {
+                        return Err(anyhow::anyhow!(
+                            "Only {} of product {} available at {}",
+                            item.quantity - remaining,
+                            item.product_uuid,
+                            source
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Insert Items
+        for (product_uuid, inventory_uuid, quantity, whole_pile) in lines {
             sqlx::query(
-                "INSERT INTO Transfer_Items (transfer_item_uuid, transfer_uuid, product_uuid, inventory_uuid, quantity)
-                 VALUES (?, ?, ?, ?, ?)"
+                "INSERT INTO Transfer_Items (transfer_item_uuid, transfer_uuid, product_uuid, inventory_uuid, quantity, whole_pile)
+                 VALUES (?, ?, ?, ?, ?, ?)"
             )
             .bind(Uuid::new_v4().to_string())
             .bind(transfer_uuid.to_string())
-            .bind(item.product_uuid.to_string())
-            .bind(item.inventory_uuid.map(|u| u.to_string()))
-            .bind(item.quantity)
+            .bind(product_uuid.to_string())
+            .bind(inventory_uuid.to_string())
+            .bind(quantity)
+            .bind(whole_pile)
             .execute(&mut *tx)
             .await
             .context("Failed to insert transfer items")?;
         }
 
+        self.log_transfer_with_tx(&mut tx, transfer_uuid).await?;
         tx.commit().await.context("Failed to commit transaction")?;
 
         Ok(transfer_uuid)
     }
 
+    pub async fn get_transfer(&self, transfer_uuid: Uuid) -> Result<Option<TransferRequest>> {
+        let mut conn = self
+            .db
+            .pool
+            .acquire()
+            .await
+            .context("Failed to acquire connection")?;
+        load_transfer(&mut conn, transfer_uuid).await
+    }
+
+    /// Transfers touching a location (either end), newest first
+    pub async fn get_transfers(
+        &self,
+        status: Option<TransferStatus>,
+        location: Option<&str>,
+    ) -> Result<Vec<TransferRequest>> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT transfer_uuid FROM Inventory_Transfers
+             WHERE (? IS NULL OR status = ?)
+               AND (? IS NULL OR source_location = ? OR target_location = ?)
+             ORDER BY created_at DESC",
+        )
+        .bind(status.as_ref().map(|s| s.as_str()))
+        .bind(status.as_ref().map(|s| s.as_str()))
+        .bind(location)
+        .bind(location)
+        .bind(location)
+        .fetch_all(&self.db.pool)
+        .await
+        .context("Failed to fetch transfers")?;
+
+        let mut conn = self
+            .db
+            .pool
+            .acquire()
+            .await
+            .context("Failed to acquire connection")?;
+        let mut transfers = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(transfer) = load_transfer(&mut conn, Uuid::parse_str(&id)?).await? {
+                transfers.push(transfer);
+            }
+        }
+        Ok(transfers)
+    }
+
     /// TASK-176/177: Update transfer status (Approve, Start Transit, Complete)
+    ///
+    /// InTransit ships the transfer; Received receives everything still
+    /// outstanding. Use `receive_transfer` for partial or counted receipts.
     pub async fn update_transfer_status(
         &self,
         transfer_uuid: Uuid,
         status: TransferStatus,
         user_uuid: Uuid,
     ) -> Result<()> {
-        let now = Utc::now();
-        let status_str = match status {
-            TransferStatus::Pending => "pending",
-            TransferStatus::Approved => "approved",
-            TransferStatus::InTransit => "in_transit",
-            TransferStatus::Received => "received",
-            TransferStatus::Cancelled => "cancelled",
-        };
-
-        // If approving, set approved_by
-        if status == TransferStatus::Approved {
-            sqlx::query(
-                "UPDATE Inventory_Transfers SET status = ?, approved_by = ?, updated_at = ? WHERE transfer_uuid = ?"
-            )
-            .bind(status_str)
-            .bind(user_uuid.to_string())
-            .bind(now.to_rfc3339())
-            .bind(transfer_uuid.to_string())
-            .execute(&self.db.pool)
-            .await
-            .context("Failed to approve transfer")?;
-        } else if status == TransferStatus::Received {
-            // TASK-177: Finalize transfer (Inventory Movement)
-            self.finalize_transfer(transfer_uuid).await?;
-        } else {
-            sqlx::query(
-                "UPDATE Inventory_Transfers SET status = ?, updated_at = ? WHERE transfer_uuid = ?",
-            )
-            .bind(status_str)
-            .bind(now.to_rfc3339())
-            .bind(transfer_uuid.to_string())
-            .execute(&self.db.pool)
-            .await
-            .context("Failed to update transfer status")?;
+        match status {
+            TransferStatus::Approved => self.approve_transfer(transfer_uuid, user_uuid).await,
+            TransferStatus::InTransit => self
+                .ship_transfer(transfer_uuid, user_uuid)
+                .await
+                .map(|_| ()),
+            TransferStatus::Received => self
+                .receive_transfer(transfer_uuid, Vec::new(), user_uuid)
+                .await
+                .map(|_| ()),
+            TransferStatus::Cancelled => self.cancel_transfer(transfer_uuid, user_uuid).await,
+            TransferStatus::Pending | TransferStatus::PartiallyReceived => Err(anyhow::anyhow!(
+                "A transfer can't be set to {} directly",
+                status.as_str()
+            )),
         }
+    }
 
+    pub async fn approve_transfer(&self, transfer_uuid: Uuid, user_uuid: Uuid) -> Result<()> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+        self.require_status(&mut tx, transfer_uuid, &[TransferStatus::Pending])
+            .await?;
+        sqlx::query(
+            "UPDATE Inventory_Transfers SET status = 'approved', approved_by = ?, updated_at = ? WHERE transfer_uuid = ?"
+        )
+        .bind(user_uuid.to_string())
+        .bind(Utc::now().to_rfc3339())
+        .bind(transfer_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .context("Failed to approve transfer")?;
+        self.log_transfer_with_tx(&mut tx, transfer_uuid).await?;
+        tx.commit().await.context("Failed to commit transfer")?;
         Ok(())
     }
 
-    /// Internal: Execute the inventory movement upon receipt
-    async fn finalize_transfer(&self, transfer_uuid: Uuid) -> Result<()> {
+    pub async fn cancel_transfer(&self, transfer_uuid: Uuid, _user_uuid: Uuid) -> Result<()> {
         let mut tx = self
             .db
             .pool
             .begin()
             .await
             .context("Failed to begin transaction")?;
-
-        // Get transfer details
-        let transfer = sqlx::query(
-            "SELECT source_location, target_location FROM Inventory_Transfers WHERE transfer_uuid = ?",
+        self.require_status(
+            &mut tx,
+            transfer_uuid,
+            &[TransferStatus::Pending, TransferStatus::Approved],
+        )
+        .await?;
+        sqlx::query(
+            "UPDATE Inventory_Transfers SET status = 'cancelled', updated_at = ? WHERE transfer_uuid = ?",
         )
+        .bind(Utc::now().to_rfc3339())
         .bind(transfer_uuid.to_string())
-        .fetch_optional(&mut *tx)
+        .execute(&mut *tx)
         .await
-        .context("Database error")?
-        .context("Transfer not found")?;
+        .context("Failed to cancel transfer")?;
+        self.log_transfer_with_tx(&mut tx, transfer_uuid).await?;
+        tx.commit().await.context("Failed to commit transfer")?;
+        Ok(())
+    }
+
+    /// Take the units out of the source piles and into transit
+    pub async fn ship_transfer(
+        &self,
+        transfer_uuid: Uuid,
+        user_uuid: Uuid,
+    ) -> Result<TransferRequest> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+        let transfer = self
+            .require_status(&mut tx, transfer_uuid, &[TransferStatus::Approved])
+            .await?;
+
+        let transfer_out = MovementSource {
+            notes: Some(format!("Shipped to {}", transfer.target_location)),
+            ..MovementSource::new(
+                MovementType::TransferOut,
+                Some(transfer_uuid),
+                Some(user_uuid),
+                &self.db.node_id,
+            )
+        };
+        let into_transit = MovementSource {
+            movement_type: MovementType::TransferIn,
+            notes: Some(format!(
+                "In transit {} -> {}",
+                transfer.source_location, transfer.target_location
+            )),
+            ..transfer_out.clone()
+        };
+
+        for line in &transfer.items {
+            let source_uuid = line
+                .inventory_uuid
+                .ok_or_else(|| anyhow::anyhow!("Transfer item has no source pile"))?;
+            let (location_tag, on_hand): (String, i32) = sqlx::query_as(
+                "SELECT location_tag, quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?",
+            )
+            .bind(source_uuid.to_string())
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Source inventory {} not found", source_uuid))?;
+            if location_tag != transfer.source_location {
+                return Err(anyhow::anyhow!(
+                    "{} is no longer at {}",
+                    line.product_name.as_deref().unwrap_or("An item"),
+                    transfer.source_location
+                ));
+            }
+            if on_hand < line.quantity || (line.whole_pile && on_hand != line.quantity) {
+                return Err(anyhow::anyhow!(
+                    "Only {} of {} on hand at {}, {} requested",
+                    on_hand,
+                    line.product_name.as_deref().unwrap_or("an item"),
+                    transfer.source_location,
+                    line.quantity
+                ));
+            }
 
-        use sqlx::Row;
-        let _source: String = transfer.try_get("source_location")?;
-        let target: String = transfer.try_get("target_location")?;
+            movements::adjust_quantity_with_tx(&mut tx, source_uuid, -line.quantity, &transfer_out)
+                .await?;
+            let transit_uuid = if line.whole_pile {
+                self.retag_pile(&mut tx, source_uuid, TRANSIT_LOCATION)
+                    .await?;
+                source_uuid
+            } else {
+                self.copy_pile(&mut tx, source_uuid, TRANSIT_LOCATION)
+                    .await?
+            };
+            movements::adjust_quantity_with_tx(&mut tx, transit_uuid, line.quantity, &into_transit)
+                .await?;
 
-        // Get items
-        let items = sqlx::query(
-            "SELECT product_uuid, inventory_uuid, quantity FROM Transfer_Items WHERE transfer_uuid = ?",
+            sqlx::query(
+                "UPDATE Transfer_Items SET quantity_shipped = ?, transit_inventory_uuid = ? WHERE transfer_item_uuid = ?",
+            )
+            .bind(line.quantity)
+            .bind(transit_uuid.to_string())
+            .bind(line.transfer_item_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update transfer item")?;
+
+            self.db
+                .inventory
+                .log_sync_with_tx(&mut tx, source_uuid)
+                .await?;
+            if transit_uuid != source_uuid {
+                self.db
+                    .inventory
+                    .log_sync_with_tx(&mut tx, transit_uuid)
+                    .await?;
+            }
+        }
+
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "UPDATE Inventory_Transfers SET status = 'in_transit', shipped_by = ?, shipped_at = ?, updated_at = ?
+             WHERE transfer_uuid = ?",
         )
+        .bind(user_uuid.to_string())
+        .bind(&now)
+        .bind(&now)
         .bind(transfer_uuid.to_string())
-        .fetch_all(&mut *tx)
+        .execute(&mut *tx)
         .await
-        .context("Failed to fetch items")?;
+        .context("Failed to update transfer status")?;
 
-        for row in items {
-            let product_uuid: String = row.try_get("product_uuid")?;
-            let inv_uuid_str: Option<String> = row.try_get("inventory_uuid").ok();
-            let quantity: i32 = row.try_get("quantity")?;
+        let shipped = self.log_transfer_with_tx(&mut tx, transfer_uuid).await?;
+        tx.commit().await.context("Failed to commit transfer")?;
+        Ok(shipped)
+    }
 
-            // We require inventory_uuid to identify source pile and condition
-            let inv_id = inv_uuid_str.ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Transfer item missing inventory_uuid (required for source identification)"
-                )
-            })?;
+    /// Post received units to the destination. With no receipt lines,
+    /// everything still outstanding is received as shipped. Receiving more
+    /// than shipped is allowed and shows as a positive variance.
+    pub async fn receive_transfer(
+        &self,
+        transfer_uuid: Uuid,
+        receipts: Vec<TransferReceipt>,
+        user_uuid: Uuid,
+    ) -> Result<TransferRequest> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+        let transfer = self
+            .require_status(
+                &mut tx,
+                transfer_uuid,
+                &[TransferStatus::InTransit, TransferStatus::PartiallyReceived],
+            )
+            .await?;
+
+        let receipts: Vec<TransferReceipt> = if receipts.is_empty() {
+            transfer
+                .items
+                .iter()
+                .map(|line| TransferReceipt {
+                    transfer_item_uuid: line.transfer_item_uuid,
+                    quantity: (line.quantity_shipped - line.quantity_received).max(0),
+                })
+                .collect()
+        } else {
+            receipts
+        };
 
-            // Fetch Source Inventory Item to get condition and serialized status
-            let source_item = sqlx::query(
-                "SELECT condition, serialized_details, quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?",
+        let out_of_transit = MovementSource {
+            notes: Some(format!("Received at {}", transfer.target_location)),
+            ..MovementSource::new(
+                MovementType::TransferOut,
+                Some(transfer_uuid),
+                Some(user_uuid),
+                &self.db.node_id,
             )
-            .bind(&inv_id)
-            .fetch_optional(&mut *tx)
-            .await
-            .context("Failed to fetch source inventory item")?
-            .ok_or_else(|| anyhow::anyhow!("Source inventory item not found"))?;
-
-            let condition: String = source_item.try_get("condition")?;
-            let serialized_details: Option<serde_json::Value> =
-                source_item.try_get("serialized_details").ok();
-
-            if serialized_details.is_some() {
-                // Serialized Item Move: Update location tag directly
-                // Verify it is currently at source? Not strictly necessary if we trust the workflow, but good practice.
-                sqlx::query("UPDATE Local_Inventory SET location_tag = ? WHERE inventory_uuid = ?")
-                    .bind(&target)
-                    .bind(&inv_id)
-                    .execute(&mut *tx)
-                    .await
-                    .context("Failed to move serialized item")?;
+        };
+        let received = MovementSource {
+            movement_type: MovementType::TransferIn,
+            notes: Some(format!("Transfer from {}", transfer.source_location)),
+            ..out_of_transit.clone()
+        };
+
+        for receipt in receipts {
+            if receipt.quantity < 0 {
+                return Err(anyhow::anyhow!("Received quantities cannot be negative"));
+            }
+            if receipt.quantity == 0 {
+                continue;
+            }
+            let line = transfer
+                .items
+                .iter()
+                .find(|l| l.transfer_item_uuid == receipt.transfer_item_uuid)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Item {} is not on this transfer",
+                        receipt.transfer_item_uuid
+                    )
+                })?;
+            let transit_uuid = line
+                .transit_inventory_uuid
+                .ok_or_else(|| anyhow::anyhow!("Transfer item was never shipped"))?;
+            let outstanding = line.quantity_shipped - line.quantity_received;
+
+            let destination_uuid = if line.whole_pile {
+                if receipt.quantity != outstanding {
+                    return Err(anyhow::anyhow!(
+                        "{} travels as a whole pile; receive all {} or none",
+                        line.product_name.as_deref().unwrap_or("This item"),
+                        outstanding
+                    ));
+                }
+                movements::adjust_quantity_with_tx(
+                    &mut tx,
+                    transit_uuid,
+                    -receipt.quantity,
+                    &out_of_transit,
+                )
+                .await?;
+                self.retag_pile(&mut tx, transit_uuid, &transfer.target_location)
+                    .await?;
+                transit_uuid
             } else {
-                // Bulk Item Move
-                // Decrement from SPECIFIC source pile
-                sqlx::query(
-                    "UPDATE Local_Inventory SET quantity_on_hand = quantity_on_hand - ? WHERE inventory_uuid = ?",
+                let in_transit: i32 = sqlx::query_scalar(
+                    "SELECT quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?",
                 )
-                .bind(quantity)
-                .bind(&inv_id)
-                .execute(&mut *tx)
+                .bind(transit_uuid.to_string())
+                .fetch_one(&mut *tx)
                 .await
-                .context("Failed to decrement source inventory")?;
-
-                // Upsert into Target (match product + condition + location)
-                let target_exists = sqlx::query(
-                    "SELECT inventory_uuid FROM Local_Inventory WHERE product_uuid = ? AND location_tag = ? AND condition = ?",
+                .context("Transit inventory not found")?;
+                movements::adjust_quantity_with_tx(
+                    &mut tx,
+                    transit_uuid,
+                    -receipt.quantity.min(in_transit),
+                    &out_of_transit,
                 )
-                .bind(&product_uuid)
-                .bind(&target)
-                .bind(&condition)
-                .fetch_optional(&mut *tx)
                 .await?;
+                match self
+                    .matching_pile(&mut tx, transit_uuid, &transfer.target_location)
+                    .await?
+                {
+                    Some(existing) => existing,
+                    None => {
+                        self.copy_pile(&mut tx, transit_uuid, &transfer.target_location)
+                            .await?
+                    }
+                }
+            };
+            let source = if receipt.quantity > outstanding {
+                MovementSource {
+                    notes: Some(format!(
+                        "Transfer from {} ({} over shipped)",
+                        transfer.source_location,
+                        receipt.quantity - outstanding.max(0)
+                    )),
+                    ..received.clone()
+                }
+            } else {
+                received.clone()
+            };
+            movements::adjust_quantity_with_tx(
+                &mut tx,
+                destination_uuid,
+                receipt.quantity,
+                &source,
+            )
+            .await?;
 
-                if let Some(_) = target_exists {
-                    sqlx::query(
-                        "UPDATE Local_Inventory SET quantity_on_hand = quantity_on_hand + ? 
-                         WHERE product_uuid = ? AND location_tag = ? AND condition = ?",
-                    )
-                    .bind(quantity)
-                    .bind(&product_uuid)
-                    .bind(&target)
-                    .bind(&condition)
-                    .execute(&mut *tx)
+            sqlx::query(
+                "UPDATE Transfer_Items SET quantity_received = quantity_received + ? WHERE transfer_item_uuid = ?",
+            )
+            .bind(receipt.quantity)
+            .bind(line.transfer_item_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update transfer item")?;
+
+            self.db
+                .inventory
+                .log_sync_with_tx(&mut tx, transit_uuid)
+                .await?;
+            if destination_uuid != transit_uuid {
+                self.db
+                    .inventory
+                    .log_sync_with_tx(&mut tx, destination_uuid)
                     .await?;
+            }
+        }
+
+        let outstanding: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM Transfer_Items WHERE transfer_uuid = ? AND quantity_received < quantity_shipped",
+        )
+        .bind(transfer_uuid.to_string())
+        .fetch_one(&mut *tx)
+        .await?;
+        let status = if outstanding == 0 {
+            TransferStatus::Received
+        } else {
+            TransferStatus::PartiallyReceived
+        };
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "UPDATE Inventory_Transfers SET status = ?, received_by = ?, received_at = ?, updated_at = ?
+             WHERE transfer_uuid = ?",
+        )
+        .bind(status.as_str())
+        .bind(user_uuid.to_string())
+        .bind(&now)
+        .bind(&now)
+        .bind(transfer_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update transfer status")?;
+
+        let result = self.log_transfer_with_tx(&mut tx, transfer_uuid).await?;
+        tx.commit().await.context("Failed to commit transfer")?;
+        Ok(result)
+    }
+
+    /// Close a short transfer: whatever is still in transit is written off
+    /// or posted back to the source
+    pub async fn close_transfer(
+        &self,
+        transfer_uuid: Uuid,
+        resolution: ShortageResolution,
+        user_uuid: Uuid,
+    ) -> Result<TransferRequest> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+        let transfer = self
+            .require_status(
+                &mut tx,
+                transfer_uuid,
+                &[TransferStatus::InTransit, TransferStatus::PartiallyReceived],
+            )
+            .await?;
+
+        let out_of_transit = MovementSource {
+            notes: Some(format!("Short on transfer to {}", transfer.target_location)),
+            ..MovementSource::new(
+                match resolution {
+                    ShortageResolution::WriteOff => MovementType::CountAdjustment,
+                    ShortageResolution::ReturnToSource => MovementType::TransferOut,
+                },
+                Some(transfer_uuid),
+                Some(user_uuid),
+                &self.db.node_id,
+            )
+        };
+        let returned = MovementSource {
+            movement_type: MovementType::TransferIn,
+            notes: Some(format!(
+                "Returned from transfer to {}",
+                transfer.target_location
+            )),
+            ..out_of_transit.clone()
+        };
+
+        for line in &transfer.items {
+            let short = line.quantity_shipped - line.quantity_received;
+            let Some(transit_uuid) = line.transit_inventory_uuid.filter(|_| short > 0) else {
+                continue;
+            };
+            let in_transit: i32 = sqlx::query_scalar(
+                "SELECT quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?",
+            )
+            .bind(transit_uuid.to_string())
+            .fetch_one(&mut *tx)
+            .await
+            .context("Transit inventory not found")?;
+            let remaining = short.min(in_transit);
+
+            movements::adjust_quantity_with_tx(&mut tx, transit_uuid, -remaining, &out_of_transit)
+                .await?;
+            if resolution == ShortageResolution::ReturnToSource {
+                let back_to = if line.whole_pile {
+                    self.retag_pile(&mut tx, transit_uuid, &transfer.source_location)
+                        .await?;
+                    transit_uuid
                 } else {
-                    // Create new pile with same condition
-                    sqlx::query(
-                        "INSERT INTO Local_Inventory (inventory_uuid, product_uuid, location_tag, quantity_on_hand, condition)
-                         VALUES (?, ?, ?, ?, ?)",
-                    )
-                    .bind(Uuid::new_v4().to_string())
-                    .bind(&product_uuid)
-                    .bind(&target)
-                    .bind(quantity)
-                    .bind(&condition)
-                    .execute(&mut *tx)
-                    .await?;
+                    line.inventory_uuid.unwrap_or(transit_uuid)
+                };
+                movements::adjust_quantity_with_tx(&mut tx, back_to, remaining, &returned).await?;
+                if back_to != transit_uuid {
+                    self.db.inventory.log_sync_with_tx(&mut tx, back_to).await?;
                 }
             }
+            self.db
+                .inventory
+                .log_sync_with_tx(&mut tx, transit_uuid)
+                .await?;
+
+            sqlx::query(
+                "UPDATE Transfer_Items SET shortage_resolution = ? WHERE transfer_item_uuid = ?",
+            )
+            .bind(resolution.as_str())
+            .bind(line.transfer_item_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update transfer item")?;
         }
 
-        // Update status to received
+        let now = Utc::now().to_rfc3339();
         sqlx::query(
-            "UPDATE Inventory_Transfers SET status = 'received', updated_at = ? WHERE transfer_uuid = ?",
+            "UPDATE Inventory_Transfers
+             SET status = 'received', received_by = COALESCE(received_by, ?), received_at = COALESCE(received_at, ?),
+                 updated_at = ?
+             WHERE transfer_uuid = ?",
         )
-        .bind(Utc::now().to_rfc3339())
+        .bind(user_uuid.to_string())
+        .bind(&now)
+        .bind(&now)
         .bind(transfer_uuid.to_string())
         .execute(&mut *tx)
-        .await?;
+        .await
+        .context("Failed to close transfer")?;
 
+        let result = self.log_transfer_with_tx(&mut tx, transfer_uuid).await?;
         tx.commit().await.context("Failed to commit transfer")?;
+        Ok(result)
+    }
+
+    /// Printable packing slip to travel with the shipment
+    pub async fn packing_slip_html(&self, transfer_uuid: Uuid) -> Result<String> {
+        let transfer = self
+            .get_transfer(transfer_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transfer not found"))?;
+
+        let mut rows_html = String::new();
+        for line in &transfer.items {
+            let pile_uuid = line.transit_inventory_uuid.or(line.inventory_uuid);
+            let details: Option<(String, Option<String>)> = match pile_uuid {
+                Some(uuid) => sqlx::query_as(
+                    "SELECT condition, serialized_details FROM Local_Inventory WHERE inventory_uuid = ?",
+                )
+                .bind(uuid.to_string())
+                .fetch_optional(&self.db.pool)
+                .await
+                .context("Failed to fetch inventory")?,
+                None => None,
+            };
+            let (condition, serial) = details.unwrap_or_default();
+            let serial = serial
+                .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+                .and_then(|v| {
+                    v.get("cert_number")
+                        .or_else(|| v.get("serial_number"))
+                        .and_then(|c| c.as_str().map(str::to_string))
+                })
+                .unwrap_or_default();
+            rows_html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td></td></tr>",
+                escape_html(line.product_name.as_deref().unwrap_or("Unknown product")),
+                escape_html(&condition),
+                escape_html(&serial),
+                line.quantity,
+                line.quantity_shipped,
+            ));
+        }
+
+        Ok(format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Packing Slip {}</title>
+    <style>
+        body {{ font-family: sans-serif; margin: 20px; }}
+        table {{ width: 100%; border-collapse: collapse; }}
+        th, td {{ border: 1px solid #999; padding: 4px 8px; text-align: left; }}
+    </style>
+</head>
+<body>
+    <h2>Transfer Packing Slip</h2>
+    <p><strong>Transfer:</strong> {}<br>
+    <strong>From:</strong> {}<br>
+    <strong>To:</strong> {}<br>
+    <strong>Shipped:</strong> {}</p>
+    <table>
+        <tr><th>Item</th><th>Condition</th><th>Serial / Cert</th><th>Requested</th><th>Shipped</th><th>Received</th></tr>
+        {}
+    </table>
+    <p>Count on arrival and record short or over quantities when receiving.</p>
+</body>
+</html>"#,
+            transfer.transfer_uuid,
+            transfer.transfer_uuid,
+            escape_html(&transfer.source_location),
+            escape_html(&transfer.target_location),
+            transfer
+                .shipped_at
+                .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "not yet shipped".to_string()),
+            rows_html
+        ))
+    }
+
+    async fn require_status(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        transfer_uuid: Uuid,
+        allowed: &[TransferStatus],
+    ) -> Result<TransferRequest> {
+        let transfer = load_transfer(tx, transfer_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transfer not found"))?;
+        if !allowed.contains(&transfer.status) {
+            return Err(anyhow::anyhow!(
+                "Transfer is {}; expected {}",
+                transfer.status.as_str(),
+                allowed
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ));
+        }
+        Ok(transfer)
+    }
+
+    /// Queue the transfer document for sync and return its current state
+    async fn log_transfer_with_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        transfer_uuid: Uuid,
+    ) -> Result<TransferRequest> {
+        let transfer = load_transfer(tx, transfer_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transfer not found"))?;
+        self.db
+            .sync
+            .log_change_with_tx(
+                tx,
+                &transfer_uuid.to_string(),
+                "InventoryTransfer",
+                "Update",
+                &serde_json::to_value(&transfer)?,
+            )
+            .await?;
+        Ok(transfer)
+    }
+
+    /// Move a whole pile to another location tag, out of any bin
+    async fn retag_pile(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        inventory_uuid: Uuid,
+        location_tag: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE Local_Inventory SET location_tag = ?, storage_uuid = NULL, bin_location = NULL
+             WHERE inventory_uuid = ?",
+        )
+        .bind(location_tag)
+        .bind(inventory_uuid.to_string())
+        .execute(&mut **tx)
+        .await
+        .context("Failed to move pile")?;
         Ok(())
     }
+
+    /// New empty pile like `from` at another location
+    async fn copy_pile(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        from: Uuid,
+        location_tag: &str,
+    ) -> Result<Uuid> {
+        let inventory_uuid = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO Local_Inventory
+             (inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag,
+              specific_price, cost_basis, supplier_uuid, received_date)
+             SELECT ?, product_uuid, variant_type, condition, 0, ?, specific_price, cost_basis,
+                    supplier_uuid, received_date
+             FROM Local_Inventory WHERE inventory_uuid = ?",
+        )
+        .bind(inventory_uuid.to_string())
+        .bind(location_tag)
+        .bind(from.to_string())
+        .execute(&mut **tx)
+        .await
+        .context("Failed to create pile")?;
+        Ok(inventory_uuid)
+    }
+
+    /// A bulk pile at `location_tag` that units of `like` can merge into
+    async fn matching_pile(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        like: Uuid,
+        location_tag: &str,
+    ) -> Result<Option<Uuid>> {
+        let existing: Option<String> = sqlx::query_scalar(
+            "SELECT li.inventory_uuid FROM Local_Inventory li, Local_Inventory src
+             WHERE src.inventory_uuid = ?
+               AND li.product_uuid = src.product_uuid AND li.condition = src.condition
+               AND COALESCE(NULLIF(li.variant_type, 'Normal'), '') = COALESCE(NULLIF(src.variant_type, 'Normal'), '')
+               AND li.specific_price IS src.specific_price
+               AND li.location_tag = ? AND li.serialized_details IS NULL AND li.deleted_at IS NULL
+               AND li.inventory_uuid NOT IN (SELECT inventory_uuid FROM Consignment_Items WHERE status = 'Active')
+             ORDER BY li.quantity_on_hand DESC
+             LIMIT 1",
+        )
+        .bind(like.to_string())
+        .bind(location_tag)
+        .fetch_optional(&mut **tx)
+        .await
+        .context("Failed to find destination pile")?;
+        Ok(parse_uuid(existing))
+    }
 }
@@ -0,0 +1,1193 @@
+//! Customer loyalty points
+//!
+//! Points live in an append-only `Loyalty_Ledger`: every earn, redemption,
+//! return or void reversal, expiry and manual adjustment is its own row, and a
+//! customer's balance is the sum of their rows. Rows are never edited, so
+//! terminals exchange them as independent `LoyaltyEntry` sync records and
+//! arrive at the same balance whatever order they are received in.
+//! `Customers.loyalty_points` only caches that sum.
+//!
+//! Tiers are named spend thresholds over a rolling window. A customer's
+//! `tier`, which pricing rules match on, is promoted or demoted to follow
+//! their spend, but only while it is 'Standard' or one of the loyalty tiers;
+//! hand-assigned tiers such as wholesale accounts are left alone.
+
+use crate::core::money::round_cents;
+use crate::database::repositories::sync::SyncRepository;
+use crate::database::Database;
+use crate::errors::Result;
+use chrono::{DateTime, Duration, Months, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Tier for customers below every loyalty threshold (the column default)
+pub const BASE_TIER: &str = "Standard";
+
+/// Expiry rows are keyed off the lot they expire, so two terminals expiring
+/// the same lot write the same row
+const EXPIRY_ID_MASK: u128 = 0x5f0e_a1c3_9b27_4d68_8e41_c0d3_72a9_b6f5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoyaltyEntryType {
+    Earn,
+    Redeem,
+    /// Points taken back when a sale is returned or voided, or redeemed
+    /// points given back on a void
+    Reverse,
+    Expire,
+    Adjust,
+}
+
+impl LoyaltyEntryType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoyaltyEntryType::Earn => "earn",
+            LoyaltyEntryType::Redeem => "redeem",
+            LoyaltyEntryType::Reverse => "reverse",
+            LoyaltyEntryType::Expire => "expire",
+            LoyaltyEntryType::Adjust => "adjust",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "earn" => Some(LoyaltyEntryType::Earn),
+            "redeem" => Some(LoyaltyEntryType::Redeem),
+            "reverse" => Some(LoyaltyEntryType::Reverse),
+            "expire" => Some(LoyaltyEntryType::Expire),
+            "adjust" => Some(LoyaltyEntryType::Adjust),
+            _ => None,
+        }
+    }
+}
+
+/// One row of the points ledger; also the sync payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoyaltyEntry {
+    pub entry_uuid: Uuid,
+    pub customer_uuid: Uuid,
+    pub entry_type: LoyaltyEntryType,
+    /// Signed: earnings are positive, redemptions and expiries negative
+    pub points: i64,
+    pub transaction_uuid: Option<Uuid>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub user_uuid: Option<Uuid>,
+    pub node_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoyaltySettings {
+    /// Earn rate for categories without their own rate
+    pub points_per_dollar: f64,
+    /// Dollar value of one point when redeemed
+    pub point_value: f64,
+    /// Months until earned points expire; 0 means never
+    pub expiry_months: i32,
+    /// Days of spend counted towards a tier
+    pub tier_window_days: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRate {
+    pub category: String,
+    pub points_per_dollar: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoyaltyTier {
+    pub tier_name: String,
+    pub min_spend: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TierChange {
+    pub customer_uuid: Uuid,
+    pub old_tier: Option<String>,
+    pub new_tier: String,
+    pub rolling_spend: f64,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoyaltyAccount {
+    pub customer_uuid: Uuid,
+    pub points: i64,
+    pub redeemable_value: f64,
+    pub tier: Option<String>,
+    pub rolling_spend: f64,
+    pub next_tier: Option<LoyaltyTier>,
+}
+
+/// Earned points not yet fully spent, for FIFO expiry
+#[derive(Debug, Clone)]
+pub struct PointLot {
+    pub entry_uuid: Uuid,
+    pub points: i64,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Points already expired from this lot
+    pub expired: Option<i64>,
+}
+
+/// Share of a sale that earns points once store credit and points tender
+/// are taken out
+pub fn eligible_share(total_due: f64, excluded_tender: f64) -> f64 {
+    if total_due <= 0.0 {
+        return 0.0;
+    }
+    ((total_due - excluded_tender) / total_due).clamp(0.0, 1.0)
+}
+
+/// Points for `(line amount, points per dollar)` pairs, rounded down
+pub fn points_for_lines(lines: &[(f64, f64)], share: f64) -> i64 {
+    let raw: f64 = lines.iter().map(|(amount, rate)| amount * rate).sum();
+    // Nudge so 1.15 * 100 style float error doesn't lose a point
+    ((raw * share) + 1e-6).floor().max(0.0) as i64
+}
+
+/// Points needed to cover `value`, rounded up
+pub fn points_for_value(value: f64, point_value: f64) -> i64 {
+    if point_value <= 0.0 {
+        return 0;
+    }
+    ((value / point_value) - 1e-6).ceil().max(0.0) as i64
+}
+
+/// Highest tier the spend reaches, or the base tier
+pub fn tier_for_spend(tiers: &[LoyaltyTier], spend: f64) -> String {
+    tiers
+        .iter()
+        .filter(|t| spend + 0.005 >= t.min_spend)
+        .max_by(|a, b| a.min_spend.total_cmp(&b.min_spend))
+        .map(|t| t.tier_name.clone())
+        .unwrap_or_else(|| BASE_TIER.to_string())
+}
+
+/// Lots whose remaining points should expire now.
+///
+/// Spending draws on the soonest-expiring lots first. `consumed` is every
+/// point spent other than by expiry; lots that have already expired keep the
+/// share of spending they had when they expired.
+pub fn lots_to_expire(lots: &[PointLot], consumed: i64, now: DateTime<Utc>) -> Vec<(Uuid, i64)> {
+    let mut ordered: Vec<&PointLot> = lots.iter().collect();
+    ordered.sort_by_key(|l| (l.expires_at.is_none(), l.expires_at));
+
+    let mut consumed = consumed.max(0);
+    let mut due = Vec::new();
+    for lot in ordered {
+        if let Some(expired) = lot.expired {
+            consumed = (consumed - (lot.points - expired).max(0)).max(0);
+            continue;
+        }
+        let used = lot.points.min(consumed);
+        consumed -= used;
+        let remaining = lot.points - used;
+        if remaining > 0 && lot.expires_at.is_some_and(|at| at <= now) {
+            due.push((lot.entry_uuid, remaining));
+        }
+    }
+    due
+}
+
+fn map_entry(row: &sqlx::sqlite::SqliteRow) -> Option<LoyaltyEntry> {
+    let parse_time = |s: String| {
+        DateTime::parse_from_rfc3339(&s)
+            .ok()
+            .map(|d| d.with_timezone(&Utc))
+    };
+    Some(LoyaltyEntry {
+        entry_uuid: Uuid::parse_str(&row.try_get::<String, _>("entry_uuid").ok()?).ok()?,
+        customer_uuid: Uuid::parse_str(&row.try_get::<String, _>("customer_uuid").ok()?).ok()?,
+        entry_type: LoyaltyEntryType::parse(&row.try_get::<String, _>("entry_type").ok()?)?,
+        points: row.try_get("points").unwrap_or(0),
+        transaction_uuid: row
+            .try_get::<Option<String>, _>("transaction_uuid")
+            .ok()
+            .flatten()
+            .and_then(|s| Uuid::parse_str(&s).ok()),
+        expires_at: row
+            .try_get::<Option<String>, _>("expires_at")
+            .ok()
+            .flatten()
+            .and_then(parse_time),
+        notes: row.try_get("notes").ok().flatten(),
+        user_uuid: row
+            .try_get::<Option<String>, _>("user_uuid")
+            .ok()
+            .flatten()
+            .and_then(|s| Uuid::parse_str(&s).ok()),
+        node_id: row.try_get("node_id").unwrap_or_default(),
+        created_at: parse_time(row.try_get("created_at").ok()?)?,
+    })
+}
+
+async fn read_settings(conn: &mut sqlx::SqliteConnection) -> Result<LoyaltySettings> {
+    let row = sqlx::query(
+        "SELECT points_per_dollar, point_value, expiry_months, tier_window_days
+         FROM Loyalty_Settings WHERE id = 1",
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    Ok(match row {
+        Some(r) => LoyaltySettings {
+            points_per_dollar: r.try_get("points_per_dollar").unwrap_or(1.0),
+            point_value: r.try_get("point_value").unwrap_or(0.01),
+            expiry_months: r.try_get("expiry_months").unwrap_or(12),
+            tier_window_days: r.try_get("tier_window_days").unwrap_or(365),
+        },
+        None => LoyaltySettings {
+            points_per_dollar: 1.0,
+            point_value: 0.01,
+            expiry_months: 12,
+            tier_window_days: 365,
+        },
+    })
+}
+
+async fn balance_with_conn(conn: &mut sqlx::SqliteConnection, customer_uuid: Uuid) -> Result<i64> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(points), 0) FROM Loyalty_Ledger WHERE customer_uuid = ?",
+    )
+    .bind(customer_uuid.to_string())
+    .fetch_one(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))
+}
+
+async fn refresh_cached_balance(
+    conn: &mut sqlx::SqliteConnection,
+    customer_uuid: &str,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE Customers SET loyalty_points =
+            (SELECT COALESCE(SUM(points), 0) FROM Loyalty_Ledger WHERE customer_uuid = ?)
+         WHERE customer_uuid = ?",
+    )
+    .bind(customer_uuid)
+    .bind(customer_uuid)
+    .execute(conn)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    Ok(())
+}
+
+fn new_entry(
+    sync: &SyncRepository,
+    customer_uuid: Uuid,
+    entry_type: LoyaltyEntryType,
+    points: i64,
+) -> LoyaltyEntry {
+    LoyaltyEntry {
+        entry_uuid: Uuid::new_v4(),
+        customer_uuid,
+        entry_type,
+        points,
+        transaction_uuid: None,
+        expires_at: None,
+        notes: None,
+        user_uuid: None,
+        node_id: sync.node_id().to_string(),
+        created_at: Utc::now(),
+    }
+}
+
+/// Append a ledger row, refresh the cached balance and log it for sync
+async fn insert_entry_with_tx(
+    sync: &SyncRepository,
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    entry: &LoyaltyEntry,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO Loyalty_Ledger
+         (entry_uuid, customer_uuid, entry_type, points, transaction_uuid, expires_at, notes, user_uuid, node_id, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(entry.entry_uuid.to_string())
+    .bind(entry.customer_uuid.to_string())
+    .bind(entry.entry_type.as_str())
+    .bind(entry.points)
+    .bind(entry.transaction_uuid.map(|u| u.to_string()))
+    .bind(entry.expires_at.map(|d| d.to_rfc3339()))
+    .bind(&entry.notes)
+    .bind(entry.user_uuid.map(|u| u.to_string()))
+    .bind(&entry.node_id)
+    .bind(entry.created_at.to_rfc3339())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record loyalty points: {}", e))?;
+
+    refresh_cached_balance(tx, &entry.customer_uuid.to_string()).await?;
+
+    sync.log_change_with_tx(
+        tx,
+        &entry.entry_uuid.to_string(),
+        "LoyaltyEntry",
+        "Insert",
+        &serde_json::to_value(entry)?,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Award points for a completed sale. Lines earn at their category's rate;
+/// the part of the sale paid with store credit or points earns nothing.
+pub async fn accrue_sale_with_tx(
+    sync: &SyncRepository,
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+    customer_uuid: Uuid,
+    total_due: f64,
+    excluded_tender: f64,
+) -> Result<Option<LoyaltyEntry>> {
+    let settings = read_settings(tx).await?;
+    let rows = sqlx::query(
+        "SELECT ti.quantity * ti.unit_price AS amount,
+                COALESCE(r.points_per_dollar, ?) AS rate
+         FROM Transaction_Items ti
+         LEFT JOIN Global_Catalog p ON p.product_uuid = ti.product_uuid
+         LEFT JOIN Loyalty_Category_Rates r ON r.category = p.category
+         WHERE ti.transaction_uuid = ?",
+    )
+    .bind(settings.points_per_dollar)
+    .bind(transaction_uuid.to_string())
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let lines: Vec<(f64, f64)> = rows
+        .iter()
+        .map(|r| {
+            (
+                r.try_get("amount").unwrap_or(0.0),
+                r.try_get("rate").unwrap_or(0.0),
+            )
+        })
+        .collect();
+    let points = points_for_lines(&lines, eligible_share(total_due, excluded_tender));
+    if points <= 0 {
+        return Ok(None);
+    }
+
+    let mut entry = new_entry(sync, customer_uuid, LoyaltyEntryType::Earn, points);
+    entry.transaction_uuid = Some(transaction_uuid);
+    if settings.expiry_months > 0 {
+        entry.expires_at = entry
+            .created_at
+            .checked_add_months(Months::new(settings.expiry_months as u32));
+    }
+    insert_entry_with_tx(sync, tx, &entry).await?;
+    Ok(Some(entry))
+}
+
+/// Spend points worth `value` dollars as tender. Returns the ledger row and
+/// the balance left.
+pub async fn redeem_with_tx(
+    db: &Database,
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    customer_uuid: Uuid,
+    transaction_uuid: Uuid,
+    value: f64,
+) -> Result<(LoyaltyEntry, i64)> {
+    let settings = read_settings(tx).await?;
+    let points = points_for_value(value, settings.point_value);
+    if points <= 0 {
+        return Err(anyhow::anyhow!(
+            "Points redemption must be for a positive amount"
+        ));
+    }
+    let balance = balance_with_conn(tx, customer_uuid).await?;
+    if balance < points {
+        return Err(anyhow::anyhow!(
+            "Insufficient loyalty points. Available: {} (${:.2}), Requested: {} (${:.2})",
+            balance,
+            balance as f64 * settings.point_value,
+            points,
+            value
+        ));
+    }
+
+    let mut entry = new_entry(&db.sync, customer_uuid, LoyaltyEntryType::Redeem, -points);
+    entry.transaction_uuid = Some(transaction_uuid);
+    insert_entry_with_tx(&db.sync, tx, &entry).await?;
+    Ok((entry, balance - points))
+}
+
+async fn adjust_with_tx(
+    db: &Database,
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    customer_uuid: Uuid,
+    points: i64,
+    notes: &str,
+    user_uuid: Option<Uuid>,
+) -> Result<LoyaltyEntry> {
+    let mut entry = new_entry(&db.sync, customer_uuid, LoyaltyEntryType::Adjust, points);
+    entry.notes = Some(notes.to_string());
+    entry.user_uuid = user_uuid;
+    insert_entry_with_tx(&db.sync, tx, &entry).await?;
+    Ok(entry)
+}
+
+/// Move a customer's whole balance to another customer, as a pair of
+/// adjustments. Returns the points moved.
+pub async fn transfer_balance_with_tx(
+    db: &Database,
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    from_customer: Uuid,
+    to_customer: Uuid,
+    user_uuid: Option<Uuid>,
+) -> Result<i64> {
+    let balance = balance_with_conn(tx, from_customer).await?;
+    if balance <= 0 {
+        return Ok(0);
+    }
+    adjust_with_tx(
+        db,
+        tx,
+        from_customer,
+        -balance,
+        &format!("Transferred to {}", to_customer),
+        user_uuid,
+    )
+    .await?;
+    adjust_with_tx(
+        db,
+        tx,
+        to_customer,
+        balance,
+        &format!("Transferred from {}", from_customer),
+        user_uuid,
+    )
+    .await?;
+    Ok(balance)
+}
+
+/// Zero a customer's balance. Returns the points forfeited.
+pub async fn forfeit_balance_with_tx(
+    db: &Database,
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    customer_uuid: Uuid,
+    notes: &str,
+    user_uuid: Option<Uuid>,
+) -> Result<i64> {
+    let balance = balance_with_conn(tx, customer_uuid).await?;
+    if balance <= 0 {
+        return Ok(0);
+    }
+    adjust_with_tx(db, tx, customer_uuid, -balance, notes, user_uuid).await?;
+    Ok(balance)
+}
+
+/// Undo a voided sale's points: take back what it still has earned and give
+/// back what was redeemed on it. Runs inside the void's database transaction.
+pub async fn reverse_void_with_tx(
+    sync: &SyncRepository,
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    transaction_uuid: Uuid,
+) -> Result<Vec<LoyaltyEntry>> {
+    let rows = sqlx::query(
+        "SELECT customer_uuid,
+                COALESCE(SUM(CASE WHEN entry_type = 'earn' OR (entry_type = 'reverse' AND points < 0)
+                                  THEN points ELSE 0 END), 0) AS earned,
+                COALESCE(SUM(CASE WHEN entry_type = 'redeem' OR (entry_type = 'reverse' AND points > 0)
+                                  THEN points ELSE 0 END), 0) AS redeemed
+         FROM Loyalty_Ledger WHERE transaction_uuid = ? GROUP BY customer_uuid",
+    )
+    .bind(transaction_uuid.to_string())
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        let customer_uuid = Uuid::parse_str(&row.try_get::<String, _>("customer_uuid")?)?;
+        let earned: i64 = row.try_get("earned").unwrap_or(0);
+        let redeemed: i64 = row.try_get("redeemed").unwrap_or(0);
+        // Earnings still standing come off; redemptions (negative) go back on
+        for points in [-earned, -redeemed] {
+            if points == 0 {
+                continue;
+            }
+            let mut entry = new_entry(sync, customer_uuid, LoyaltyEntryType::Reverse, points);
+            entry.transaction_uuid = Some(transaction_uuid);
+            entry.notes = Some(format!("Void {}", transaction_uuid));
+            insert_entry_with_tx(sync, tx, &entry).await?;
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Take back the points a sale earned in proportion to what was returned
+pub async fn reverse_for_return(
+    db: &Database,
+    return_uuid: Uuid,
+    transaction_uuid: Uuid,
+    returned_value: f64,
+) -> Result<Option<LoyaltyEntry>> {
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+    let row = sqlx::query(
+        "SELECT customer_uuid,
+                COALESCE(SUM(CASE WHEN entry_type = 'earn' THEN points ELSE 0 END), 0) AS earned,
+                COALESCE(SUM(CASE WHEN entry_type = 'reverse' AND points < 0 THEN -points ELSE 0 END), 0) AS reversed
+         FROM Loyalty_Ledger WHERE transaction_uuid = ? GROUP BY customer_uuid",
+    )
+    .bind(transaction_uuid.to_string())
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let customer_uuid = Uuid::parse_str(&row.try_get::<String, _>("customer_uuid")?)?;
+    let earned: i64 = row.try_get("earned").unwrap_or(0);
+    let reversed: i64 = row.try_get("reversed").unwrap_or(0);
+
+    let subtotal: f64 = sqlx::query_scalar(
+        "SELECT COALESCE(subtotal, total, 0.0) FROM Transactions WHERE transaction_uuid = ?",
+    )
+    .bind(transaction_uuid.to_string())
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+    .unwrap_or(0.0);
+
+    let share = if subtotal > 0.0 {
+        (returned_value / subtotal).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let points = ((earned as f64 * share).round() as i64).min(earned - reversed);
+    if points <= 0 {
+        return Ok(None);
+    }
+
+    let mut entry = new_entry(&db.sync, customer_uuid, LoyaltyEntryType::Reverse, -points);
+    entry.transaction_uuid = Some(transaction_uuid);
+    entry.notes = Some(format!("Return {}", return_uuid));
+    insert_entry_with_tx(&db.sync, &mut tx, &entry).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+    Ok(Some(entry))
+}
+
+/// Store a ledger row received from another node.
+///
+/// Two terminals expiring the same lot before syncing produce the same
+/// expiry row, possibly with different amounts if one had seen spending the
+/// other hadn't; both sides keep the smaller expiry so they converge.
+pub async fn apply_synced_entry(pool: &sqlx::SqlitePool, entry: &LoyaltyEntry) -> Result<()> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+    sqlx::query(
+        "INSERT OR IGNORE INTO Loyalty_Ledger
+         (entry_uuid, customer_uuid, entry_type, points, transaction_uuid, expires_at, notes, user_uuid, node_id, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(entry.entry_uuid.to_string())
+    .bind(entry.customer_uuid.to_string())
+    .bind(entry.entry_type.as_str())
+    .bind(entry.points)
+    .bind(entry.transaction_uuid.map(|u| u.to_string()))
+    .bind(entry.expires_at.map(|d| d.to_rfc3339()))
+    .bind(&entry.notes)
+    .bind(entry.user_uuid.map(|u| u.to_string()))
+    .bind(&entry.node_id)
+    .bind(entry.created_at.to_rfc3339())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    if entry.entry_type == LoyaltyEntryType::Expire {
+        sqlx::query(
+            "UPDATE Loyalty_Ledger SET points = MAX(points, ?) WHERE entry_uuid = ? AND entry_type = 'expire'",
+        )
+        .bind(entry.points)
+        .bind(entry.entry_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    }
+
+    refresh_cached_balance(&mut tx, &entry.customer_uuid.to_string()).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+    Ok(())
+}
+
+/// Customer's spend over the tier window, net of returns
+async fn rolling_spend(db: &Database, customer_uuid: Uuid, window_days: i32) -> Result<f64> {
+    let since = (Utc::now() - Duration::days(window_days.max(1) as i64)).to_rfc3339();
+    let sales: f64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(COALESCE(subtotal, total, 0.0)), 0.0) FROM Transactions
+         WHERE customer_uuid = ? AND transaction_type = 'Sale' AND timestamp >= ?",
+    )
+    .bind(customer_uuid.to_string())
+    .bind(&since)
+    .fetch_one(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    let returned: f64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(r.refund_amount), 0.0) FROM Returns r
+         JOIN Transactions t ON t.transaction_uuid = r.transaction_uuid
+         WHERE t.customer_uuid = ? AND r.processed_at >= ?",
+    )
+    .bind(customer_uuid.to_string())
+    .bind(&since)
+    .fetch_one(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    Ok(round_cents(sales - returned))
+}
+
+async fn load_tiers(db: &Database) -> Result<Vec<LoyaltyTier>> {
+    let rows = sqlx::query("SELECT tier_name, min_spend FROM Loyalty_Tiers ORDER BY min_spend")
+        .fetch_all(&db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    Ok(rows
+        .iter()
+        .map(|r| LoyaltyTier {
+            tier_name: r.try_get("tier_name").unwrap_or_default(),
+            min_spend: r.try_get("min_spend").unwrap_or(0.0),
+        })
+        .collect())
+}
+
+/// Move the customer to the tier their rolling spend earns, if the program
+/// manages their tier. Returns the change, if any.
+pub async fn evaluate_tier(db: &Database, customer_uuid: Uuid) -> Result<Option<TierChange>> {
+    let tiers = load_tiers(db).await?;
+    if tiers.is_empty() {
+        return Ok(None);
+    }
+    let Some(customer) = db.customers.get_by_id(customer_uuid).await? else {
+        return Err(anyhow::anyhow!("Customer {} not found", customer_uuid));
+    };
+    let managed = match customer.tier.as_deref() {
+        None => true,
+        Some(tier) => tier == BASE_TIER || tiers.iter().any(|t| t.tier_name == tier),
+    };
+    if !managed {
+        return Ok(None);
+    }
+
+    let mut conn = db
+        .pool
+        .acquire()
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    let settings = read_settings(&mut conn).await?;
+    drop(conn);
+
+    let spend = rolling_spend(db, customer_uuid, settings.tier_window_days).await?;
+    let target = tier_for_spend(&tiers, spend);
+    if customer.tier.as_deref() == Some(target.as_str()) {
+        return Ok(None);
+    }
+
+    db.customers.update_tier(customer_uuid, &target).await?;
+    let change = TierChange {
+        customer_uuid,
+        old_tier: customer.tier,
+        new_tier: target,
+        rolling_spend: spend,
+        changed_at: Utc::now(),
+    };
+    sqlx::query(
+        "INSERT INTO Loyalty_Tier_History (change_uuid, customer_uuid, old_tier, new_tier, rolling_spend, changed_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(customer_uuid.to_string())
+    .bind(&change.old_tier)
+    .bind(&change.new_tier)
+    .bind(change.rolling_spend)
+    .bind(change.changed_at.to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    tracing::info!(
+        "Customer {} moved from tier {:?} to {} (rolling spend ${:.2})",
+        customer_uuid,
+        change.old_tier,
+        change.new_tier,
+        spend
+    );
+    Ok(Some(change))
+}
+
+pub struct LoyaltyService {
+    db: Arc<Database>,
+}
+
+impl LoyaltyService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    pub async fn get_settings(&self) -> Result<LoyaltySettings> {
+        let mut conn = self
+            .db
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        read_settings(&mut conn).await
+    }
+
+    pub async fn update_settings(&self, settings: LoyaltySettings) -> Result<LoyaltySettings> {
+        if settings.points_per_dollar < 0.0 {
+            return Err(anyhow::anyhow!("Earn rate cannot be negative"));
+        }
+        if settings.point_value <= 0.0 {
+            return Err(anyhow::anyhow!("Point value must be positive"));
+        }
+        if settings.expiry_months < 0 {
+            return Err(anyhow::anyhow!("expiry_months cannot be negative"));
+        }
+        if settings.tier_window_days < 1 {
+            return Err(anyhow::anyhow!("tier_window_days must be at least 1"));
+        }
+
+        sqlx::query(
+            "INSERT OR REPLACE INTO Loyalty_Settings
+             (id, points_per_dollar, point_value, expiry_months, tier_window_days, updated_at)
+             VALUES (1, ?, ?, ?, ?, ?)",
+        )
+        .bind(settings.points_per_dollar)
+        .bind(settings.point_value)
+        .bind(settings.expiry_months)
+        .bind(settings.tier_window_days)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(settings)
+    }
+
+    pub async fn get_category_rates(&self) -> Result<Vec<CategoryRate>> {
+        let rows = sqlx::query(
+            "SELECT category, points_per_dollar FROM Loyalty_Category_Rates ORDER BY category",
+        )
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows
+            .iter()
+            .map(|r| CategoryRate {
+                category: r.try_get("category").unwrap_or_default(),
+                points_per_dollar: r.try_get("points_per_dollar").unwrap_or(0.0),
+            })
+            .collect())
+    }
+
+    /// Replace the per-category earn rates
+    pub async fn set_category_rates(&self, rates: Vec<CategoryRate>) -> Result<Vec<CategoryRate>> {
+        if let Some(rate) = rates.iter().find(|r| r.points_per_dollar < 0.0) {
+            return Err(anyhow::anyhow!(
+                "Earn rate for {} cannot be negative",
+                rate.category
+            ));
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        sqlx::query("DELETE FROM Loyalty_Category_Rates")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let now = Utc::now().to_rfc3339();
+        for rate in &rates {
+            sqlx::query(
+                "INSERT OR REPLACE INTO Loyalty_Category_Rates (category, points_per_dollar, updated_at)
+                 VALUES (?, ?, ?)",
+            )
+            .bind(rate.category.trim())
+            .bind(rate.points_per_dollar)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.get_category_rates().await
+    }
+
+    pub async fn get_tiers(&self) -> Result<Vec<LoyaltyTier>> {
+        load_tiers(&self.db).await
+    }
+
+    /// Replace the tier ladder. Customers move on their next sale or the
+    /// next scheduled evaluation.
+    pub async fn set_tiers(&self, tiers: Vec<LoyaltyTier>) -> Result<Vec<LoyaltyTier>> {
+        for tier in &tiers {
+            if tier.tier_name.trim().is_empty() {
+                return Err(anyhow::anyhow!("Tier needs a name"));
+            }
+            if tier.tier_name.trim() == BASE_TIER {
+                return Err(anyhow::anyhow!(
+                    "'{}' is the tier below every threshold and can't be configured",
+                    BASE_TIER
+                ));
+            }
+            if tier.min_spend < 0.0 {
+                return Err(anyhow::anyhow!("Tier spend threshold cannot be negative"));
+            }
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        sqlx::query("DELETE FROM Loyalty_Tiers")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let now = Utc::now().to_rfc3339();
+        for tier in &tiers {
+            sqlx::query(
+                "INSERT INTO Loyalty_Tiers (tier_name, min_spend, created_at) VALUES (?, ?, ?)",
+            )
+            .bind(tier.tier_name.trim())
+            .bind(tier.min_spend)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Duplicate or invalid tier: {}", e))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.get_tiers().await
+    }
+
+    pub async fn get_balance(&self, customer_uuid: Uuid) -> Result<i64> {
+        let mut conn = self
+            .db
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        balance_with_conn(&mut conn, customer_uuid).await
+    }
+
+    /// Balance, tier standing and distance to the next tier
+    pub async fn get_account(&self, customer_uuid: Uuid) -> Result<LoyaltyAccount> {
+        let customer = self
+            .db
+            .customers
+            .get_by_id(customer_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Customer {} not found", customer_uuid))?;
+        let settings = self.get_settings().await?;
+        let points = self.get_balance(customer_uuid).await?;
+        let spend = rolling_spend(&self.db, customer_uuid, settings.tier_window_days).await?;
+        let next_tier = load_tiers(&self.db)
+            .await?
+            .into_iter()
+            .find(|t| t.min_spend > spend);
+
+        Ok(LoyaltyAccount {
+            customer_uuid,
+            points,
+            redeemable_value: ((points as f64 * settings.point_value) * 100.0).floor() / 100.0,
+            tier: customer.tier,
+            rolling_spend: spend,
+            next_tier,
+        })
+    }
+
+    /// Ledger rows for a customer, newest first
+    pub async fn get_history(&self, customer_uuid: Uuid, limit: i64) -> Result<Vec<LoyaltyEntry>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Loyalty_Ledger WHERE customer_uuid = ?
+             ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(customer_uuid.to_string())
+        .bind(limit)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().filter_map(map_entry).collect())
+    }
+
+    pub async fn get_tier_history(&self, customer_uuid: Uuid) -> Result<Vec<TierChange>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Loyalty_Tier_History WHERE customer_uuid = ? ORDER BY changed_at DESC",
+        )
+        .bind(customer_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows
+            .iter()
+            .map(|r| TierChange {
+                customer_uuid,
+                old_tier: r.try_get("old_tier").ok().flatten(),
+                new_tier: r.try_get("new_tier").unwrap_or_default(),
+                rolling_spend: r.try_get("rolling_spend").unwrap_or(0.0),
+                changed_at: r
+                    .try_get::<String, _>("changed_at")
+                    .ok()
+                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                    .map(|d| d.with_timezone(&Utc))
+                    .unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Manual correction by staff; positive adjustments don't expire
+    pub async fn adjust_points(
+        &self,
+        customer_uuid: Uuid,
+        points: i64,
+        notes: String,
+        user_uuid: Option<Uuid>,
+    ) -> Result<LoyaltyEntry> {
+        if points == 0 {
+            return Err(anyhow::anyhow!("Adjustment must change the balance"));
+        }
+        if notes.trim().is_empty() {
+            return Err(anyhow::anyhow!("Adjustments need a reason"));
+        }
+        if self.db.customers.get_by_id(customer_uuid).await?.is_none() {
+            return Err(anyhow::anyhow!("Customer {} not found", customer_uuid));
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        let balance = balance_with_conn(&mut tx, customer_uuid).await?;
+        if balance + points < 0 {
+            return Err(anyhow::anyhow!(
+                "Adjustment would leave a negative balance ({} available)",
+                balance
+            ));
+        }
+        let entry = adjust_with_tx(
+            &self.db,
+            &mut tx,
+            customer_uuid,
+            points,
+            notes.trim(),
+            user_uuid,
+        )
+        .await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        Ok(entry)
+    }
+
+    /// Expire lots past their date. Returns the points expired.
+    pub async fn expire_points(&self) -> Result<i64> {
+        let now = Utc::now();
+        let customers: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT customer_uuid FROM Loyalty_Ledger
+             WHERE entry_type = 'earn' AND expires_at IS NOT NULL AND expires_at <= ?",
+        )
+        .bind(now.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut total = 0;
+        for customer in customers {
+            let customer_uuid = Uuid::parse_str(&customer)?;
+            total += self.expire_customer_points(customer_uuid, now).await?;
+        }
+        if total > 0 {
+            tracing::info!("Expired {} loyalty points", total);
+        }
+        Ok(total)
+    }
+
+    async fn expire_customer_points(&self, customer_uuid: Uuid, now: DateTime<Utc>) -> Result<i64> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let entries: Vec<LoyaltyEntry> =
+            sqlx::query("SELECT * FROM Loyalty_Ledger WHERE customer_uuid = ?")
+                .bind(customer_uuid.to_string())
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+                .iter()
+                .filter_map(map_entry)
+                .collect();
+
+        let expiry_id = |lot: Uuid| Uuid::from_u128(lot.as_u128() ^ EXPIRY_ID_MASK);
+        let expired: std::collections::HashMap<Uuid, i64> = entries
+            .iter()
+            .filter(|e| e.entry_type == LoyaltyEntryType::Expire)
+            .map(|e| (e.entry_uuid, -e.points))
+            .collect();
+        let lots: Vec<PointLot> = entries
+            .iter()
+            .filter(|e| e.points > 0)
+            .map(|e| PointLot {
+                entry_uuid: e.entry_uuid,
+                points: e.points,
+                expires_at: e.expires_at,
+                expired: expired.get(&expiry_id(e.entry_uuid)).copied(),
+            })
+            .collect();
+        let consumed: i64 = entries
+            .iter()
+            .filter(|e| e.points < 0 && e.entry_type != LoyaltyEntryType::Expire)
+            .map(|e| -e.points)
+            .sum();
+
+        let mut total = 0;
+        for (lot, points) in lots_to_expire(&lots, consumed, now) {
+            let mut entry = new_entry(
+                &self.db.sync,
+                customer_uuid,
+                LoyaltyEntryType::Expire,
+                -points,
+            );
+            entry.entry_uuid = expiry_id(lot);
+            entry.notes = Some(format!("Expired from {}", lot));
+            insert_entry_with_tx(&self.db.sync, &mut tx, &entry).await?;
+            total += points;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        Ok(total)
+    }
+
+    pub async fn evaluate_tier(&self, customer_uuid: Uuid) -> Result<Option<TierChange>> {
+        evaluate_tier(&self.db, customer_uuid).await
+    }
+
+    /// Re-check every customer the program manages, so tiers also drop as
+    /// old spend leaves the window
+    pub async fn evaluate_all_tiers(&self) -> Result<Vec<TierChange>> {
+        if load_tiers(&self.db).await?.is_empty() {
+            return Ok(Vec::new());
+        }
+        let customers: Vec<String> = sqlx::query_scalar(
+            "SELECT customer_uuid FROM Customers
+             WHERE deleted_at IS NULL
+               AND (tier IS NULL OR tier = ? OR tier IN (SELECT tier_name FROM Loyalty_Tiers))",
+        )
+        .bind(BASE_TIER)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut changes = Vec::new();
+        for customer in customers {
+            if let Some(change) = evaluate_tier(&self.db, Uuid::parse_str(&customer)?).await? {
+                changes.push(change);
+            }
+        }
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lot(points: i64, days_from_now: Option<i64>, expired: Option<i64>) -> PointLot {
+        PointLot {
+            entry_uuid: Uuid::new_v4(),
+            points,
+            expires_at: days_from_now.map(|d| Utc::now() + Duration::days(d)),
+            expired,
+        }
+    }
+
+    #[test]
+    fn test_points_exclude_store_credit_share() {
+        // $40 of singles at 1/$ and $10 of sealed at 3/$, a quarter paid in credit
+        let lines = [(40.0, 1.0), (10.0, 3.0)];
+        assert_eq!(points_for_lines(&lines, eligible_share(50.0, 0.0)), 70);
+        assert_eq!(points_for_lines(&lines, eligible_share(50.0, 12.5)), 52);
+        assert_eq!(points_for_lines(&lines, eligible_share(50.0, 60.0)), 0);
+        assert_eq!(points_for_lines(&[(1.15, 100.0)], 1.0), 115);
+    }
+
+    #[test]
+    fn test_points_for_value_rounds_up() {
+        assert_eq!(points_for_value(5.0, 0.01), 500);
+        assert_eq!(points_for_value(5.005, 0.01), 501);
+        assert_eq!(points_for_value(0.0, 0.01), 0);
+    }
+
+    #[test]
+    fn test_tier_for_spend() {
+        let tiers = vec![
+            LoyaltyTier {
+                tier_name: "Silver".to_string(),
+                min_spend: 250.0,
+            },
+            LoyaltyTier {
+                tier_name: "Gold".to_string(),
+                min_spend: 1000.0,
+            },
+        ];
+        assert_eq!(tier_for_spend(&tiers, 100.0), BASE_TIER);
+        assert_eq!(tier_for_spend(&tiers, 250.0), "Silver");
+        assert_eq!(tier_for_spend(&tiers, 5000.0), "Gold");
+    }
+
+    #[test]
+    fn test_lots_expire_after_fifo_spending() {
+        let old = lot(100, Some(-1), None);
+        let newer = lot(100, Some(30), None);
+        // 60 spent comes out of the older lot first
+        let due = lots_to_expire(&[newer.clone(), old.clone()], 60, Utc::now());
+        assert_eq!(due, vec![(old.entry_uuid, 40)]);
+
+        // Once that lot has expired, later spending draws on the next lot
+        let settled = PointLot {
+            expired: Some(40),
+            ..old.clone()
+        };
+        assert!(lots_to_expire(&[settled, newer], 160, Utc::now()).is_empty());
+
+        // Fully spent lots leave nothing to expire
+        assert!(lots_to_expire(&[old], 100, Utc::now()).is_empty());
+    }
+}
@@ -0,0 +1,673 @@
+//! Media storage
+//!
+//! Photos of slabs, singles and trade-ins are stored once per content, under
+//! their SHA-256 in `<media_dir>/blobs/ab/abcdef...`, so the same picture
+//! attached twice (or arriving from two peers) takes one file. `Media` rows
+//! attach a blob to a product, an inventory pile or a trade-in; only the rows
+//! sync. A peer that has the row but not the bytes fetches them from the
+//! other devices the first time someone asks for them.
+//!
+//! Thumbnails are produced by a `ThumbnailGenerator` (ImageMagick by default)
+//! and kept in `<media_dir>/thumbs`. When none can be made the original is
+//! served in its place.
+
+use crate::database::Database;
+use crate::errors::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Largest upload accepted
+pub const MAX_UPLOAD_BYTES: usize = 20 * 1024 * 1024;
+
+/// Longest edge of a thumbnail, in pixels
+pub const THUMBNAIL_SIZE: u32 = 320;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaEntity {
+    Product,
+    Inventory,
+    TradeIn,
+}
+
+impl MediaEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaEntity::Product => "product",
+            MediaEntity::Inventory => "inventory",
+            MediaEntity::TradeIn => "trade_in",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "product" => Some(MediaEntity::Product),
+            "inventory" => Some(MediaEntity::Inventory),
+            "trade_in" => Some(MediaEntity::TradeIn),
+            _ => None,
+        }
+    }
+}
+
+/// A stored image attached to something
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaItem {
+    pub media_uuid: Uuid,
+    pub content_hash: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub original_filename: Option<String>,
+    pub entity_type: MediaEntity,
+    pub entity_uuid: Uuid,
+    pub caption: Option<String>,
+    pub sort_order: i32,
+    pub uploaded_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl MediaItem {
+    pub fn content_url(&self) -> String {
+        content_url(self.media_uuid)
+    }
+
+    pub fn thumbnail_url(&self) -> String {
+        format!("/api/media/{}/thumbnail", self.media_uuid)
+    }
+}
+
+pub fn content_url(media_uuid: Uuid) -> String {
+    format!("/api/media/{}/content", media_uuid)
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MediaUpdate {
+    /// Empty clears it
+    pub caption: Option<String>,
+    pub sort_order: Option<i32>,
+}
+
+/// Image type from the file's leading bytes; the uploader's claim is not
+/// trusted
+pub fn detect_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+pub fn content_hash(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn is_content_hash(s: &str) -> bool {
+    s.len() == 64
+        && s.bytes()
+            .all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase())
+}
+
+/// Where a blob lives under `blobs_dir`
+pub fn blob_path(blobs_dir: &Path, hash: &str) -> PathBuf {
+    blobs_dir.join(&hash[..2]).join(hash)
+}
+
+async fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create media directory: {}", e))?;
+    }
+    let tmp = path.with_extension(format!("tmp-{}", Uuid::new_v4().simple()));
+    tokio::fs::write(&tmp, bytes)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to write media: {}", e))?;
+    tokio::fs::rename(&tmp, path)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to store media: {}", e))?;
+    Ok(())
+}
+
+fn media_from_row(row: &sqlx::sqlite::SqliteRow) -> MediaItem {
+    let text = |col: &str| row.try_get::<Option<String>, _>(col).ok().flatten();
+    let uuid = |col: &str| text(col).and_then(|s| Uuid::parse_str(&s).ok());
+    let time = |col: &str| {
+        text(col)
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|d| d.with_timezone(&Utc))
+    };
+    MediaItem {
+        media_uuid: uuid("media_uuid").unwrap_or_default(),
+        content_hash: text("content_hash").unwrap_or_default(),
+        mime_type: text("mime_type").unwrap_or_default(),
+        size_bytes: row.try_get("size_bytes").unwrap_or(0),
+        original_filename: text("original_filename"),
+        entity_type: text("entity_type")
+            .and_then(|s| MediaEntity::parse(&s))
+            .unwrap_or(MediaEntity::Product),
+        entity_uuid: uuid("entity_uuid").unwrap_or_default(),
+        caption: text("caption"),
+        sort_order: row.try_get("sort_order").unwrap_or(0),
+        uploaded_by: uuid("uploaded_by"),
+        created_at: time("created_at").unwrap_or_default(),
+        deleted_at: time("deleted_at"),
+    }
+}
+
+async fn upsert_media<'e, E>(executor: E, item: &MediaItem) -> Result<()>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    sqlx::query(
+        "INSERT INTO Media (media_uuid, content_hash, mime_type, size_bytes, original_filename,
+            entity_type, entity_uuid, caption, sort_order, uploaded_by, created_at, deleted_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(media_uuid) DO UPDATE SET
+            caption = excluded.caption, sort_order = excluded.sort_order,
+            deleted_at = excluded.deleted_at",
+    )
+    .bind(item.media_uuid.to_string())
+    .bind(&item.content_hash)
+    .bind(&item.mime_type)
+    .bind(item.size_bytes)
+    .bind(&item.original_filename)
+    .bind(item.entity_type.as_str())
+    .bind(item.entity_uuid.to_string())
+    .bind(&item.caption)
+    .bind(item.sort_order)
+    .bind(item.uploaded_by.map(|u| u.to_string()))
+    .bind(item.created_at.to_rfc3339())
+    .bind(item.deleted_at.map(|t| t.to_rfc3339()))
+    .execute(executor)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    Ok(())
+}
+
+/// Apply a media row from a peer. The bytes follow lazily.
+pub async fn apply_synced_media(pool: &sqlx::SqlitePool, item: &MediaItem) -> Result<()> {
+    if !is_content_hash(&item.content_hash) {
+        return Err(anyhow::anyhow!(
+            "Synced media {} has an invalid content hash",
+            item.media_uuid
+        ));
+    }
+    upsert_media(pool, item).await
+}
+
+/// Content URLs of the live images attached to something, in display order
+pub async fn media_urls(
+    pool: &sqlx::SqlitePool,
+    entity_type: MediaEntity,
+    entity_uuid: Uuid,
+) -> Result<Vec<String>> {
+    let ids: Vec<String> = sqlx::query_scalar(
+        "SELECT media_uuid FROM Media
+         WHERE entity_type = ? AND entity_uuid = ? AND deleted_at IS NULL
+         ORDER BY sort_order, created_at",
+    )
+    .bind(entity_type.as_str())
+    .bind(entity_uuid.to_string())
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    Ok(ids
+        .iter()
+        .filter_map(|id| Uuid::parse_str(id).ok())
+        .map(content_url)
+        .collect())
+}
+
+#[async_trait]
+pub trait ThumbnailGenerator: Send + Sync {
+    /// Write a JPEG thumbnail of `source` to `dest`, no larger than
+    /// `max_dimension` on either side
+    async fn generate(&self, source: &Path, dest: &Path, max_dimension: u32) -> Result<()>;
+}
+
+/// For installs without an image toolchain; originals are served instead
+pub struct NoThumbnails;
+
+#[async_trait]
+impl ThumbnailGenerator for NoThumbnails {
+    async fn generate(&self, _source: &Path, _dest: &Path, _max_dimension: u32) -> Result<()> {
+        Err(anyhow::anyhow!("Thumbnail generation is disabled"))
+    }
+}
+
+/// Shells out to ImageMagick (`convert`, or `magick` on v7)
+pub struct ImageMagickThumbnails {
+    binary: String,
+}
+
+impl ImageMagickThumbnails {
+    pub fn new(binary: impl Into<String>) -> Self {
+        Self {
+            binary: binary.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl ThumbnailGenerator for ImageMagickThumbnails {
+    async fn generate(&self, source: &Path, dest: &Path, max_dimension: u32) -> Result<()> {
+        // First frame only, so animated GIFs give a single image
+        let output = tokio::process::Command::new(&self.binary)
+            .arg(format!("{}[0]", source.display()))
+            .args(["-auto-orient", "-thumbnail"])
+            .arg(format!("{0}x{0}>", max_dimension))
+            .args([
+                "-background",
+                "white",
+                "-flatten",
+                "-strip",
+                "-quality",
+                "85",
+            ])
+            .arg(format!("jpg:{}", dest.display()))
+            .output()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", self.binary, e))?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "{} failed: {}",
+                self.binary,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// `MEDIA_THUMBNAILER=none` turns thumbnails off; otherwise ImageMagick is
+/// used, from `MAGICK_BINARY` if set
+pub fn get_thumbnail_generator() -> Box<dyn ThumbnailGenerator> {
+    match std::env::var("MEDIA_THUMBNAILER").as_deref() {
+        Ok("none") => Box::new(NoThumbnails),
+        _ => Box::new(ImageMagickThumbnails::new(
+            std::env::var("MAGICK_BINARY").unwrap_or_else(|_| "convert".to_string()),
+        )),
+    }
+}
+
+pub struct MediaService {
+    db: Arc<Database>,
+    media_dir: PathBuf,
+    thumbnails: Box<dyn ThumbnailGenerator>,
+}
+
+impl MediaService {
+    pub fn new(
+        db: Arc<Database>,
+        media_dir: PathBuf,
+        thumbnails: Box<dyn ThumbnailGenerator>,
+    ) -> Self {
+        Self {
+            db,
+            media_dir,
+            thumbnails,
+        }
+    }
+
+    fn blobs_dir(&self) -> PathBuf {
+        self.media_dir.join("blobs")
+    }
+
+    fn thumbnail_path(&self, hash: &str) -> PathBuf {
+        self.media_dir
+            .join("thumbs")
+            .join(&hash[..2])
+            .join(format!("{}.jpg", hash))
+    }
+
+    async fn ensure_entity(&self, entity_type: MediaEntity, entity_uuid: Uuid) -> Result<()> {
+        let sql = match entity_type {
+            MediaEntity::Product => "SELECT COUNT(*) FROM Global_Catalog WHERE product_uuid = ?",
+            MediaEntity::Inventory => {
+                "SELECT COUNT(*) FROM Local_Inventory WHERE inventory_uuid = ?"
+            }
+            MediaEntity::TradeIn => {
+                "SELECT COUNT(*) FROM Transactions
+                 WHERE transaction_uuid = ? AND transaction_type IN ('Buy', 'Trade')"
+            }
+        };
+        let found: i64 = sqlx::query_scalar(sql)
+            .bind(entity_uuid.to_string())
+            .fetch_one(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        if found == 0 {
+            return Err(anyhow::anyhow!(
+                "No {} {} to attach media to",
+                entity_type.as_str().replace('_', "-"),
+                entity_uuid
+            ));
+        }
+        Ok(())
+    }
+
+    /// Store an uploaded image and attach it
+    pub async fn upload(
+        &self,
+        bytes: &[u8],
+        original_filename: Option<String>,
+        entity_type: MediaEntity,
+        entity_uuid: Uuid,
+        caption: Option<String>,
+        user_uuid: Option<Uuid>,
+    ) -> Result<MediaItem> {
+        if bytes.is_empty() {
+            return Err(anyhow::anyhow!("Uploaded file is empty"));
+        }
+        if bytes.len() > MAX_UPLOAD_BYTES {
+            return Err(anyhow::anyhow!(
+                "File is {} bytes; the limit is {}",
+                bytes.len(),
+                MAX_UPLOAD_BYTES
+            ));
+        }
+        let mime_type = detect_mime(bytes).ok_or_else(|| {
+            anyhow::anyhow!("Unsupported file type; upload a JPEG, PNG, GIF or WebP image")
+        })?;
+        self.ensure_entity(entity_type, entity_uuid).await?;
+
+        let hash = content_hash(bytes);
+        let path = blob_path(&self.blobs_dir(), &hash);
+        if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            write_atomic(&path, bytes).await?;
+        }
+
+        let next_order: i32 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(sort_order) + 1, 0) FROM Media
+             WHERE entity_type = ? AND entity_uuid = ? AND deleted_at IS NULL",
+        )
+        .bind(entity_type.as_str())
+        .bind(entity_uuid.to_string())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let item = MediaItem {
+            media_uuid: Uuid::new_v4(),
+            content_hash: hash,
+            mime_type: mime_type.to_string(),
+            size_bytes: bytes.len() as i64,
+            original_filename: original_filename.filter(|f| !f.trim().is_empty()),
+            entity_type,
+            entity_uuid,
+            caption: caption.filter(|c| !c.trim().is_empty()),
+            sort_order: next_order,
+            uploaded_by: user_uuid,
+            created_at: Utc::now(),
+            deleted_at: None,
+        };
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        upsert_media(&mut *tx, &item).await?;
+        self.db
+            .sync
+            .log_change_with_tx(
+                &mut tx,
+                &item.media_uuid.to_string(),
+                "Media",
+                "Insert",
+                &serde_json::to_value(&item)?,
+            )
+            .await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit: {}", e))?;
+
+        if let Err(e) = self.make_thumbnail(&item.content_hash).await {
+            tracing::warn!("No thumbnail for media {}: {}", item.media_uuid, e);
+        }
+        tracing::info!(
+            "Stored media {} ({} bytes) for {} {}",
+            item.media_uuid,
+            item.size_bytes,
+            entity_type.as_str(),
+            entity_uuid
+        );
+        Ok(item)
+    }
+
+    /// Live media row
+    pub async fn get(&self, media_uuid: Uuid) -> Result<Option<MediaItem>> {
+        let row = sqlx::query("SELECT * FROM Media WHERE media_uuid = ? AND deleted_at IS NULL")
+            .bind(media_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(row.as_ref().map(media_from_row))
+    }
+
+    pub async fn list(
+        &self,
+        entity_type: MediaEntity,
+        entity_uuid: Uuid,
+    ) -> Result<Vec<MediaItem>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Media
+             WHERE entity_type = ? AND entity_uuid = ? AND deleted_at IS NULL
+             ORDER BY sort_order, created_at",
+        )
+        .bind(entity_type.as_str())
+        .bind(entity_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().map(media_from_row).collect())
+    }
+
+    async fn save_and_log(&self, item: &MediaItem) -> Result<()> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        upsert_media(&mut *tx, item).await?;
+        self.db
+            .sync
+            .log_change_with_tx(
+                &mut tx,
+                &item.media_uuid.to_string(),
+                "Media",
+                "Update",
+                &serde_json::to_value(item)?,
+            )
+            .await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn update(&self, media_uuid: Uuid, update: MediaUpdate) -> Result<MediaItem> {
+        let mut item = self
+            .get(media_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Media {} not found", media_uuid))?;
+        if let Some(caption) = update.caption {
+            item.caption = Some(caption).filter(|c| !c.trim().is_empty());
+        }
+        if let Some(sort_order) = update.sort_order {
+            item.sort_order = sort_order;
+        }
+        self.save_and_log(&item).await?;
+        Ok(item)
+    }
+
+    /// Detach an image. The blob stays: other rows, peers and backups may
+    /// still refer to the same content.
+    pub async fn delete(&self, media_uuid: Uuid) -> Result<MediaItem> {
+        let mut item = self
+            .get(media_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Media {} not found", media_uuid))?;
+        item.deleted_at = Some(Utc::now());
+        self.save_and_log(&item).await?;
+        Ok(item)
+    }
+
+    /// Bytes of a blob held on this device
+    pub async fn read_blob(&self, hash: &str) -> Result<Option<Vec<u8>>> {
+        if !is_content_hash(hash) {
+            return Ok(None);
+        }
+        match tokio::fs::read(blob_path(&self.blobs_dir(), hash)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(anyhow::anyhow!("Failed to read media: {}", e)),
+        }
+    }
+
+    /// Bytes of a blob, fetched from a peer if this device doesn't have it
+    async fn load_blob(&self, hash: &str, peers: &[crate::network::Device]) -> Result<Vec<u8>> {
+        if let Some(bytes) = self.read_blob(hash).await? {
+            return Ok(bytes);
+        }
+        let client = reqwest::Client::new();
+        for peer in peers {
+            let url = format!(
+                "http://{}:{}/api/media/blobs/{}",
+                peer.address, peer.port, hash
+            );
+            let resp = match client
+                .get(&url)
+                .timeout(std::time::Duration::from_secs(10))
+                .send()
+                .await
+            {
+                Ok(resp) if resp.status().is_success() => resp,
+                Ok(resp) => {
+                    tracing::debug!("{} has no copy of {}: {}", peer.name, hash, resp.status());
+                    continue;
+                }
+                Err(e) => {
+                    tracing::debug!("Failed to reach {} for media: {}", peer.name, e);
+                    continue;
+                }
+            };
+            let bytes = match resp.bytes().await {
+                Ok(bytes) => bytes.to_vec(),
+                Err(_) => continue,
+            };
+            if content_hash(&bytes) != hash {
+                tracing::warn!("{} sent media that doesn't match {}", peer.name, hash);
+                continue;
+            }
+            write_atomic(&blob_path(&self.blobs_dir(), hash), &bytes).await?;
+            tracing::info!("Fetched media {} from {}", hash, peer.name);
+            return Ok(bytes);
+        }
+        Err(anyhow::anyhow!(
+            "Media content {} is not on this device or any reachable peer",
+            hash
+        ))
+    }
+
+    /// The image itself
+    pub async fn content(
+        &self,
+        media_uuid: Uuid,
+        peers: &[crate::network::Device],
+    ) -> Result<(MediaItem, Vec<u8>)> {
+        let item = self
+            .get(media_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Media {} not found", media_uuid))?;
+        let bytes = self.load_blob(&item.content_hash, peers).await?;
+        Ok((item, bytes))
+    }
+
+    async fn make_thumbnail(&self, hash: &str) -> Result<PathBuf> {
+        let dest = self.thumbnail_path(hash);
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to create thumbnail directory: {}", e))?;
+        }
+        self.thumbnails
+            .generate(&blob_path(&self.blobs_dir(), hash), &dest, THUMBNAIL_SIZE)
+            .await?;
+        Ok(dest)
+    }
+
+    /// A small JPEG of the image, or the original when none can be made.
+    /// Returns the media, content type and bytes.
+    pub async fn thumbnail(
+        &self,
+        media_uuid: Uuid,
+        peers: &[crate::network::Device],
+    ) -> Result<(MediaItem, String, Vec<u8>)> {
+        let (item, original) = self.content(media_uuid, peers).await?;
+        let path = self.thumbnail_path(&item.content_hash);
+        let thumb = match tokio::fs::read(&path).await {
+            Ok(bytes) => Some(bytes),
+            Err(_) => match self.make_thumbnail(&item.content_hash).await {
+                Ok(path) => tokio::fs::read(&path).await.ok(),
+                Err(e) => {
+                    tracing::debug!("Serving original for media {}: {}", media_uuid, e);
+                    None
+                }
+            },
+        };
+        Ok(match thumb {
+            Some(bytes) => (item, "image/jpeg".to_string(), bytes),
+            None => {
+                let mime = item.mime_type.clone();
+                (item, mime, original)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_mime() {
+        assert_eq!(
+            detect_mime(&[0xFF, 0xD8, 0xFF, 0xE0, 0]),
+            Some("image/jpeg")
+        );
+        assert_eq!(
+            detect_mime(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some("image/png")
+        );
+        assert_eq!(detect_mime(b"GIF89a...."), Some("image/gif"));
+        assert_eq!(detect_mime(b"RIFF\x10\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(detect_mime(b"%PDF-1.7"), None);
+        assert_eq!(detect_mime(b"RIFF"), None);
+    }
+
+    #[test]
+    fn test_content_addressing() {
+        let hash = content_hash(b"slab photo");
+        assert!(is_content_hash(&hash));
+        assert!(!is_content_hash("../../etc/passwd"));
+        assert!(!is_content_hash(&hash.to_uppercase()));
+        assert_eq!(
+            blob_path(Path::new("/m/blobs"), &hash),
+            Path::new("/m/blobs").join(&hash[..2]).join(&hash)
+        );
+    }
+}
@@ -2,23 +2,40 @@
 //!
 //! Contains business logic services for the VaultSync POS system.
 
+pub mod adjustment;
 pub mod backup;
 pub mod barcode;
+pub mod bulk_inventory;
 pub mod cash_drawer;
 pub mod catalog_lookup;
+pub mod cert_verification;
+pub mod consignment;
+pub mod currency;
+pub mod customer;
+pub mod customer_display;
+pub mod cycle_count;
+pub mod grading;
 pub mod holds;
 pub mod invoice;
+pub mod kitting;
 pub mod label;
+pub mod layaway;
 pub mod location;
+pub mod loyalty;
+pub mod media;
 pub mod notification;
 pub mod offline_queue;
 pub mod payment;
 pub mod printer;
 pub mod product;
+pub mod purchasing;
 pub mod receipt;
+pub mod replenishment;
 pub mod reporting;
 pub mod returns;
 pub mod serialized_inventory;
+pub mod shrinkage;
+pub mod storage;
 pub mod supervisor;
 pub mod tax;
 pub mod trade_in_protection;
@@ -26,20 +43,67 @@ pub mod transaction;
 
 pub use product::ProductService;
 
+pub use adjustment::{
+    AdjustmentSummary, AdjustmentType, TransactionAdjustment, TransactionAdjustmentService,
+};
 pub use barcode::BarcodeService;
+pub use bulk_inventory::{
+    BulkInventoryService, ColumnMapping, DataFormat, ImportMode, ImportOptions, ImportProfile,
+    ImportReport, ImportRowResult, ImportSession, MappingProfile, RowAction,
+};
 pub use cash_drawer::{
     CashCount, CashCountType, CashDrawerService, CashVarianceReport, Shift, ShiftStatus,
     ShiftVariance,
 };
 pub use catalog_lookup::CatalogLookupService;
+pub use cert_verification::{
+    CertVerification, CertVerificationProvider, FixtureCertProvider, OfficialCert,
+    VerificationFlag, VerificationStatus,
+};
+pub use consignment::{
+    ConsignmentIntakeRequest, ConsignmentItem, ConsignmentSale, ConsignmentService,
+    ConsignmentStatus, Consignor, ConsignorPayout, ConsignorStatement, CreateConsignorRequest,
+    PayoutMethod,
+};
+pub use currency::{CurrencyService, CurrencySettings, ExchangeRate};
+pub use customer::{
+    AnonymizationSummary, CustomerService, CustomerUpdate, DuplicateCandidate, MergeSummary,
+};
+pub use customer_display::{
+    CartView, CustomerDisplayService, DisplayEvent, DisplayLine, DisplayPromotion,
+    DisplayPromotionRequest, PaymentPromptView, ThankYouView, TradeInOffer, TradeInView,
+};
+pub use cycle_count::{
+    AbcClass, ClassAccuracy, CountAccuracyReport, CountEntryRequest, CountLine, CountPlan,
+    CountSession, CountSessionStatus, CountSheetLine, CreateCountPlanRequest,
+    CreateCountSessionRequest, CycleCountService, ProductClassification,
+};
+pub use grading::{
+    CreateSubmissionRequest, GraderRoi, GradingCompany, GradingRoiReport, GradingService,
+    GradingSubmission, ItemOutcome, ItemResultRequest, SubmissionItem, SubmissionItemRequest,
+    SubmissionStatus, AT_GRADER_LOCATION,
+};
 pub use holds::{
     CreateHoldRequest, Hold, HoldItem, HoldPayment, HoldStatus, HoldSummary, HoldsService,
 };
 pub use invoice::InvoiceService;
+pub use kitting::{
+    AssembleRequest, BomComponent, BomComponentRequest, BomType, BreakRequest, ConversionLine,
+    ConversionType, InventoryConversion, KittingService, ProductBom, SetBomRequest,
+};
 pub use label::LabelService;
+pub use layaway::{
+    ForfeiturePolicy, HoldInstallment, InstallmentStatus, LayawayLiabilityReport, LayawayPlan,
+};
 pub use location::{
-    Location, LocationService, LocationType, TransferItem, TransferRequest, TransferStatus,
+    Location, LocationService, LocationType, ShortageResolution, TransferItem, TransferLine,
+    TransferReceipt, TransferRequest, TransferStatus, TRANSIT_LOCATION,
 };
+pub use loyalty::{
+    CategoryRate, LoyaltyAccount, LoyaltyEntry, LoyaltyEntryType, LoyaltyService, LoyaltySettings,
+    LoyaltyTier, TierChange,
+};
+pub use media::{MediaEntity, MediaItem, MediaService, MediaUpdate};
 pub use offline_queue::{OfflineQueueService, QueueStatus, QueuedOperation};
 pub use payment::{
     CashPaymentResult, PaymentMethodType, PaymentRecord, PaymentRequest, PaymentResult,
@@ -48,14 +112,35 @@ pub use payment::{
 pub use printer::{
     EscPosBuilder, PrintJob, PrintJobType, PrinterInfo, PrinterService, PrinterType,
 };
+pub use purchasing::{
+    Backorder, CreatePurchaseOrderRequest, CreateSupplierRequest, PurchaseOrder, PurchaseOrderLine,
+    PurchaseOrderLineRequest, PurchaseOrderReceipt, PurchaseOrderStatus, PurchasingService,
+    ReceiveLineRequest, ReceivingResult, SuggestedPurchaseOrder, Supplier,
+};
 pub use receipt::ReceiptService;
+pub use replenishment::{
+    DeadStockItem, EventDemand, ForecastOptions, ProductForecast, ReplenishmentService,
+    ReplenishmentSummary, StockStatus,
+};
 pub use reporting::{InventoryValuationReport, ReportingService, SalesReport};
 pub use returns::{ReturnPolicy, ReturnReasonCode, ReturnRequest, ReturnResult, ReturnsService};
 pub use serialized_inventory::{
     CertificateInfo, GradingInfo, SerializedInventoryService, SerializedItem,
     SerializedSearchResult,
 };
-pub use tax::{ItemTax, TaxBreakdown, TaxRate, TaxService};
+pub use shrinkage::{
+    DamageRecord, DamageType, Disposition, ReportDamageRequest, ResolveDamageRequest,
+    ShrinkBreakdown, ShrinkReport, ShrinkSource, ShrinkageService,
+};
+pub use storage::{
+    BinMove, CreateStorageRequest, MoveStockRequest, PickLine, PickList, PickRequestLine,
+    PickShortage, ProductWhereabouts, StockLocation, StorageKind, StorageNode, StorageService,
+    UpdateStorageRequest,
+};
+pub use tax::{
+    ItemTax, JurisdictionLevel, JurisdictionTax, TaxBreakdown, TaxExemptionCertificate,
+    TaxJurisdiction, TaxLiabilityReport, TaxRate, TaxService,
+};
 pub use trade_in_protection::{
     AlertSeverity, CustomerTradeInHistory, SuspiciousActivity, SuspiciousActivityType,
     TradeInBlacklistEntry, TradeInCheck, TradeInProtectionService,
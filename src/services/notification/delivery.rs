@@ -0,0 +1,787 @@
+//! Customer message delivery
+//!
+//! Renders templates, applies the customer's channel preferences and
+//! marketing consent, and records every attempt in `Notifications` so staff
+//! can see what went out. Failed sends are retried with backoff; a bounced
+//! address is not used again until the customer's contact details change.
+
+use super::preferences::{NotificationPreferences, PreferencesUpdate};
+use super::sms::SmsProvider;
+use super::templates::{
+    default_template, render, validate_template, MessageCategory, MessageTemplate,
+    NotificationChannel, TemplateKey,
+};
+use super::{EmailMessage, EmailProvider};
+use crate::database::Database;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Sends before a failed message is given up on
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 4;
+
+/// Backoff after the given number of failed attempts: 15m, 30m, 1h...
+pub fn retry_delay(attempts: i32) -> Duration {
+    Duration::minutes(15 * 2i64.pow(attempts.clamp(1, 8) as u32 - 1))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Sent,
+    Failed,
+    Bounced,
+    /// Not sent because of preferences, consent or a prior bounce
+    Suppressed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Sent => "sent",
+            DeliveryStatus::Failed => "failed",
+            DeliveryStatus::Bounced => "bounced",
+            DeliveryStatus::Suppressed => "suppressed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(DeliveryStatus::Pending),
+            "sent" => Some(DeliveryStatus::Sent),
+            "failed" => Some(DeliveryStatus::Failed),
+            "bounced" => Some(DeliveryStatus::Bounced),
+            "suppressed" => Some(DeliveryStatus::Suppressed),
+            _ => None,
+        }
+    }
+}
+
+/// One message in the delivery log
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryRecord {
+    pub notification_uuid: Uuid,
+    pub customer_uuid: Option<Uuid>,
+    pub channel: Option<NotificationChannel>,
+    pub recipient: Option<String>,
+    pub template_key: Option<String>,
+    pub category: Option<String>,
+    pub subject: String,
+    pub body: String,
+    pub status: Option<DeliveryStatus>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub bounced_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DeliveryRecord {
+    pub fn is_sent(&self) -> bool {
+        self.status == Some(DeliveryStatus::Sent)
+    }
+}
+
+fn parse_time(s: Option<String>) -> Option<DateTime<Utc>> {
+    s.and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|d| d.with_timezone(&Utc))
+}
+
+fn record_from_row(row: &sqlx::sqlite::SqliteRow) -> DeliveryRecord {
+    let text = |col: &str| row.try_get::<Option<String>, _>(col).ok().flatten();
+    DeliveryRecord {
+        notification_uuid: text("notification_uuid")
+            .and_then(|s| Uuid::parse_str(&s).ok())
+            .unwrap_or_default(),
+        customer_uuid: text("customer_uuid").and_then(|s| Uuid::parse_str(&s).ok()),
+        channel: text("channel").and_then(|s| NotificationChannel::parse(&s)),
+        recipient: text("recipient"),
+        template_key: text("template_key"),
+        category: text("category"),
+        subject: text("title").unwrap_or_default(),
+        body: text("message").unwrap_or_default(),
+        status: text("status").and_then(|s| DeliveryStatus::parse(&s)),
+        attempts: row.try_get("attempts").unwrap_or(0),
+        last_error: text("last_error"),
+        next_attempt_at: parse_time(text("next_attempt_at")),
+        sent_at: parse_time(text("sent_at")),
+        bounced_at: parse_time(text("bounced_at")),
+        created_at: parse_time(text("created_at")).unwrap_or_default(),
+    }
+}
+
+fn prefs_from_row(row: &sqlx::sqlite::SqliteRow) -> NotificationPreferences {
+    let text = |col: &str| row.try_get::<Option<String>, _>(col).ok().flatten();
+    NotificationPreferences {
+        customer_uuid: text("customer_uuid")
+            .and_then(|s| Uuid::parse_str(&s).ok())
+            .unwrap_or_default(),
+        preferred_channel: text("preferred_contact").and_then(|s| NotificationChannel::parse(&s)),
+        email_enabled: row.try_get("email_enabled").unwrap_or(true),
+        sms_enabled: row.try_get("sms_enabled").unwrap_or(true),
+        email_marketing_consent_at: parse_time(text("email_marketing_consent_at")),
+        sms_marketing_consent_at: parse_time(text("sms_marketing_consent_at")),
+        unsubscribe_token: text("unsubscribe_token").unwrap_or_default(),
+        unsubscribed_at: parse_time(text("unsubscribed_at")),
+        updated_at: parse_time(text("updated_at")).unwrap_or_default(),
+    }
+}
+
+/// Add the unsubscribe link marketing messages must carry
+fn with_unsubscribe_footer(channel: NotificationChannel, body: &str, link: &str) -> String {
+    match channel {
+        NotificationChannel::Email => {
+            let footer = format!(
+                r#"<p style="font-size: 12px; color: #888;">Don't want these emails? <a href="{}">Unsubscribe</a></p>"#,
+                link
+            );
+            match body.rfind("</body>") {
+                Some(pos) => format!("{}    {}\n{}", &body[..pos], footer, &body[pos..]),
+                None => format!("{}\n{}", body, footer),
+            }
+        }
+        NotificationChannel::Sms => format!("{} Opt out: {}", body, link),
+    }
+}
+
+pub struct NotificationService {
+    db: Arc<Database>,
+    email: Arc<Box<dyn EmailProvider>>,
+    sms: Arc<Box<dyn SmsProvider>>,
+    /// Base URL customers can reach, for unsubscribe links
+    public_url: Option<String>,
+}
+
+impl NotificationService {
+    pub fn new(
+        db: Arc<Database>,
+        email: Arc<Box<dyn EmailProvider>>,
+        sms: Arc<Box<dyn SmsProvider>>,
+    ) -> Self {
+        Self {
+            db,
+            email,
+            sms,
+            public_url: None,
+        }
+    }
+
+    /// Marketing messages can only be sent once this is set
+    pub fn with_public_url(mut self, public_url: Option<String>) -> Self {
+        self.public_url = public_url.map(|url| url.trim_end_matches('/').to_string());
+        self
+    }
+
+    fn unsubscribe_link(&self, token: &str) -> Option<String> {
+        self.public_url
+            .as_ref()
+            .map(|base| format!("{}/api/notifications/unsubscribe/{}", base, token))
+    }
+
+    // ------------------------------------------------------------------
+    // Preferences
+    // ------------------------------------------------------------------
+
+    /// The customer's preferences, creating the defaults on first use
+    pub async fn get_preferences(&self, customer_uuid: Uuid) -> Result<NotificationPreferences> {
+        let defaults = NotificationPreferences::new(customer_uuid);
+        sqlx::query(
+            "INSERT OR IGNORE INTO Customer_Notification_Preferences
+                (customer_uuid, unsubscribe_token, updated_at)
+             SELECT customer_uuid, ?, ? FROM Customers WHERE customer_uuid = ?",
+        )
+        .bind(&defaults.unsubscribe_token)
+        .bind(defaults.updated_at.to_rfc3339())
+        .bind(customer_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        sqlx::query(
+            "SELECT p.*, c.preferred_contact
+             FROM Customer_Notification_Preferences p
+             JOIN Customers c ON c.customer_uuid = p.customer_uuid
+             WHERE p.customer_uuid = ?",
+        )
+        .bind(customer_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        .map(|row| prefs_from_row(&row))
+        .ok_or_else(|| anyhow::anyhow!("Customer {} not found", customer_uuid))
+    }
+
+    async fn save_preferences(&self, prefs: &NotificationPreferences) -> Result<()> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        sqlx::query(
+            "UPDATE Customer_Notification_Preferences SET
+                email_enabled = ?, sms_enabled = ?,
+                email_marketing_consent_at = ?, sms_marketing_consent_at = ?,
+                unsubscribed_at = ?, updated_at = ?
+             WHERE customer_uuid = ?",
+        )
+        .bind(prefs.email_enabled)
+        .bind(prefs.sms_enabled)
+        .bind(prefs.email_marketing_consent_at.map(|t| t.to_rfc3339()))
+        .bind(prefs.sms_marketing_consent_at.map(|t| t.to_rfc3339()))
+        .bind(prefs.unsubscribed_at.map(|t| t.to_rfc3339()))
+        .bind(prefs.updated_at.to_rfc3339())
+        .bind(prefs.customer_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        sqlx::query("UPDATE Customers SET preferred_contact = ? WHERE customer_uuid = ?")
+            .bind(prefs.preferred_channel.map(|c| c.as_str()))
+            .bind(prefs.customer_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn update_preferences(
+        &self,
+        customer_uuid: Uuid,
+        update: PreferencesUpdate,
+    ) -> Result<NotificationPreferences> {
+        if let Some(channel) = &update.preferred_channel {
+            if !channel.is_empty() && NotificationChannel::parse(channel).is_none() {
+                return Err(anyhow::anyhow!(
+                    "Unknown channel '{}'; use email or sms",
+                    channel
+                ));
+            }
+        }
+        let mut prefs = self.get_preferences(customer_uuid).await?;
+        prefs.apply(&update, Utc::now());
+        self.save_preferences(&prefs).await?;
+        Ok(prefs)
+    }
+
+    /// Withdraw marketing consent for whoever holds the token
+    pub async fn unsubscribe(&self, token: &str) -> Result<NotificationPreferences> {
+        let customer: Option<String> = sqlx::query_scalar(
+            "SELECT customer_uuid FROM Customer_Notification_Preferences WHERE unsubscribe_token = ?",
+        )
+        .bind(token)
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let customer_uuid = customer
+            .and_then(|s| Uuid::parse_str(&s).ok())
+            .ok_or_else(|| anyhow::anyhow!("Unsubscribe link is not valid"))?;
+
+        let mut prefs = self.get_preferences(customer_uuid).await?;
+        prefs.unsubscribe(Utc::now());
+        self.save_preferences(&prefs).await?;
+        tracing::info!("Customer {} unsubscribed from marketing", customer_uuid);
+        Ok(prefs)
+    }
+
+    // ------------------------------------------------------------------
+    // Templates
+    // ------------------------------------------------------------------
+
+    /// The store's template, or the built-in default
+    pub async fn get_template(
+        &self,
+        key: TemplateKey,
+        channel: NotificationChannel,
+    ) -> Result<MessageTemplate> {
+        let row = sqlx::query(
+            "SELECT subject, body FROM Notification_Templates WHERE template_key = ? AND channel = ?",
+        )
+        .bind(key.as_str())
+        .bind(channel.as_str())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(match row {
+            Some(row) => MessageTemplate {
+                template_key: key,
+                channel,
+                subject: row.try_get("subject").unwrap_or_default(),
+                body: row.try_get("body").unwrap_or_default(),
+                customized: true,
+            },
+            None => default_template(key, channel),
+        })
+    }
+
+    pub async fn list_templates(&self) -> Result<Vec<MessageTemplate>> {
+        let mut templates = Vec::new();
+        for key in TemplateKey::ALL {
+            for channel in key.channels() {
+                templates.push(self.get_template(key, *channel).await?);
+            }
+        }
+        Ok(templates)
+    }
+
+    pub async fn save_template(
+        &self,
+        key: TemplateKey,
+        channel: NotificationChannel,
+        subject: String,
+        body: String,
+        user_uuid: Option<Uuid>,
+    ) -> Result<MessageTemplate> {
+        if !key.channels().contains(&channel) {
+            return Err(anyhow::anyhow!(
+                "{} messages are not sent by {}",
+                key.as_str(),
+                channel.as_str()
+            ));
+        }
+        validate_template(key, &subject, &body).map_err(|e| anyhow::anyhow!(e))?;
+
+        sqlx::query(
+            "INSERT INTO Notification_Templates (template_key, channel, subject, body, updated_by, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(template_key, channel) DO UPDATE SET
+                subject = excluded.subject, body = excluded.body,
+                updated_by = excluded.updated_by, updated_at = excluded.updated_at",
+        )
+        .bind(key.as_str())
+        .bind(channel.as_str())
+        .bind(&subject)
+        .bind(&body)
+        .bind(user_uuid.map(|u| u.to_string()))
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(MessageTemplate {
+            template_key: key,
+            channel,
+            subject,
+            body,
+            customized: true,
+        })
+    }
+
+    /// Drop the store's version and go back to the default
+    pub async fn reset_template(
+        &self,
+        key: TemplateKey,
+        channel: NotificationChannel,
+    ) -> Result<MessageTemplate> {
+        sqlx::query("DELETE FROM Notification_Templates WHERE template_key = ? AND channel = ?")
+            .bind(key.as_str())
+            .bind(channel.as_str())
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(default_template(key, channel))
+    }
+
+    // ------------------------------------------------------------------
+    // Sending
+    // ------------------------------------------------------------------
+
+    /// Send a transactional message on the customer's preferred channel,
+    /// falling back to the other one
+    pub async fn notify(
+        &self,
+        customer_uuid: Uuid,
+        key: TemplateKey,
+        vars: &[(&str, String)],
+    ) -> Result<Vec<DeliveryRecord>> {
+        let prefs = self.get_preferences(customer_uuid).await?;
+        let channels = prefs.channel_order();
+        self.deliver(
+            &prefs,
+            key,
+            MessageCategory::Transactional,
+            vars,
+            &channels,
+            true,
+        )
+        .await
+    }
+
+    /// Send a message on each of `channels` the customer can receive it on.
+    /// With `first_only`, stop at the first one that goes out.
+    pub async fn send_to_customer(
+        &self,
+        customer_uuid: Uuid,
+        key: TemplateKey,
+        category: MessageCategory,
+        vars: &[(&str, String)],
+        channels: &[NotificationChannel],
+        first_only: bool,
+    ) -> Result<Vec<DeliveryRecord>> {
+        let prefs = self.get_preferences(customer_uuid).await?;
+        self.deliver(&prefs, key, category, vars, channels, first_only)
+            .await
+    }
+
+    async fn deliver(
+        &self,
+        prefs: &NotificationPreferences,
+        key: TemplateKey,
+        category: MessageCategory,
+        vars: &[(&str, String)],
+        channels: &[NotificationChannel],
+        first_only: bool,
+    ) -> Result<Vec<DeliveryRecord>> {
+        let customer = self
+            .db
+            .customers
+            .get_by_id(prefs.customer_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Customer {} not found", prefs.customer_uuid))?;
+        let mut vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        vars.entry("customer_name".to_string())
+            .or_insert_with(|| customer.name.clone());
+
+        let mut records = Vec::new();
+        for &channel in channels {
+            if !key.channels().contains(&channel) {
+                continue;
+            }
+            let recipient = match channel {
+                NotificationChannel::Email => customer.email.as_deref(),
+                NotificationChannel::Sms => customer.phone.as_deref(),
+            };
+            let Some(recipient) = recipient.map(str::trim).filter(|r| !r.is_empty()) else {
+                continue;
+            };
+
+            let template = self.get_template(key, channel).await?;
+            let subject = render(&template.subject, &vars);
+            let mut body = render(&template.body, &vars);
+
+            let mut blocked = prefs.blocked_reason(channel, category);
+            if blocked.is_none() && self.has_bounced(channel, recipient).await? {
+                blocked = Some("Address bounced previously");
+            }
+            if blocked.is_none() && category == MessageCategory::Marketing {
+                match self.unsubscribe_link(&prefs.unsubscribe_token) {
+                    Some(link) => body = with_unsubscribe_footer(channel, &body, &link),
+                    None => blocked = Some("No public URL configured for unsubscribe links"),
+                }
+            }
+
+            let record = self
+                .log(
+                    Some(prefs.customer_uuid),
+                    channel,
+                    recipient,
+                    key,
+                    category,
+                    &subject,
+                    &body,
+                    blocked,
+                )
+                .await?;
+            let record = match blocked {
+                Some(_) => record,
+                None => self.attempt(record).await?,
+            };
+            let sent = record.is_sent();
+            records.push(record);
+            if sent && first_only {
+                break;
+            }
+        }
+        Ok(records)
+    }
+
+    /// Email an address given at the counter (e.g. a receipt), bypassing
+    /// channel preferences but not the bounce list
+    pub async fn send_email(
+        &self,
+        recipient: &str,
+        customer_uuid: Option<Uuid>,
+        key: TemplateKey,
+        vars: &[(&str, String)],
+    ) -> Result<DeliveryRecord> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        let template = self.get_template(key, NotificationChannel::Email).await?;
+        let blocked = if self
+            .has_bounced(NotificationChannel::Email, recipient)
+            .await?
+        {
+            Some("Address bounced previously")
+        } else {
+            None
+        };
+        let record = self
+            .log(
+                customer_uuid,
+                NotificationChannel::Email,
+                recipient,
+                key,
+                MessageCategory::Transactional,
+                &render(&template.subject, &vars),
+                &render(&template.body, &vars),
+                blocked,
+            )
+            .await?;
+        match blocked {
+            Some(_) => Ok(record),
+            None => self.attempt(record).await,
+        }
+    }
+
+    async fn has_bounced(&self, channel: NotificationChannel, recipient: &str) -> Result<bool> {
+        let bounced: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM Notifications
+             WHERE channel = ? AND recipient = ? AND status = 'bounced'",
+        )
+        .bind(channel.as_str())
+        .bind(recipient)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(bounced > 0)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn log(
+        &self,
+        customer_uuid: Option<Uuid>,
+        channel: NotificationChannel,
+        recipient: &str,
+        key: TemplateKey,
+        category: MessageCategory,
+        subject: &str,
+        body: &str,
+        suppressed: Option<&str>,
+    ) -> Result<DeliveryRecord> {
+        let status = match suppressed {
+            Some(_) => DeliveryStatus::Suppressed,
+            None => DeliveryStatus::Pending,
+        };
+        let row = sqlx::query(
+            "INSERT INTO Notifications
+                (notification_uuid, customer_uuid, channel, recipient, template_key, category,
+                 title, message, status, last_error, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             RETURNING *",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(customer_uuid.map(|u| u.to_string()))
+        .bind(channel.as_str())
+        .bind(recipient)
+        .bind(key.as_str())
+        .bind(category.as_str())
+        .bind(subject)
+        .bind(body)
+        .bind(status.as_str())
+        .bind(suppressed)
+        .bind(Utc::now().to_rfc3339())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to log notification: {}", e))?;
+        Ok(record_from_row(&row))
+    }
+
+    /// Hand a logged message to its provider and record the outcome
+    async fn attempt(&self, record: DeliveryRecord) -> Result<DeliveryRecord> {
+        let recipient = record.recipient.clone().unwrap_or_default();
+        let outcome = match record.channel {
+            Some(NotificationChannel::Email) => {
+                self.email
+                    .send_email(&EmailMessage {
+                        to: recipient,
+                        subject: record.subject.clone(),
+                        body: record.body.clone(),
+                        attachment_path: None,
+                    })
+                    .await
+            }
+            Some(NotificationChannel::Sms) => self.sms.send_sms(&recipient, &record.body).await,
+            None => Err(anyhow::anyhow!("Notification has no channel")),
+        };
+
+        let now = Utc::now();
+        let attempts = record.attempts + 1;
+        let query = match &outcome {
+            Ok(()) => sqlx::query(
+                "UPDATE Notifications SET status = 'sent', attempts = ?, sent_at = ?,
+                    last_error = NULL, next_attempt_at = NULL
+                 WHERE notification_uuid = ? RETURNING *",
+            )
+            .bind(attempts)
+            .bind(now.to_rfc3339()),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to send {} notification {} (attempt {}): {}",
+                    record.channel.map(|c| c.as_str()).unwrap_or("unknown"),
+                    record.notification_uuid,
+                    attempts,
+                    e
+                );
+                let next_attempt = (attempts < MAX_DELIVERY_ATTEMPTS)
+                    .then(|| (now + retry_delay(attempts)).to_rfc3339());
+                sqlx::query(
+                    "UPDATE Notifications SET status = 'failed', attempts = ?, last_error = ?,
+                        next_attempt_at = ?
+                     WHERE notification_uuid = ? RETURNING *",
+                )
+                .bind(attempts)
+                .bind(e.to_string())
+                .bind(next_attempt)
+            }
+        };
+        let row = query
+            .bind(record.notification_uuid.to_string())
+            .fetch_one(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(record_from_row(&row))
+    }
+
+    /// Resend failed messages whose backoff has elapsed. Messages the
+    /// customer has since opted out of are suppressed instead.
+    pub async fn retry_failed(&self) -> Result<Vec<DeliveryRecord>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Notifications
+             WHERE status = 'failed' AND next_attempt_at IS NOT NULL AND next_attempt_at <= ?
+             ORDER BY next_attempt_at",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut retried = Vec::new();
+        for record in rows.iter().map(record_from_row) {
+            let blocked = match (record.customer_uuid, record.channel) {
+                (Some(customer_uuid), Some(channel)) => {
+                    let category = match record.category.as_deref() {
+                        Some("marketing") => MessageCategory::Marketing,
+                        _ => MessageCategory::Transactional,
+                    };
+                    self.get_preferences(customer_uuid)
+                        .await
+                        .ok()
+                        .and_then(|prefs| prefs.blocked_reason(channel, category))
+                }
+                _ => None,
+            };
+            retried.push(match blocked {
+                Some(reason) => self.suppress(record.notification_uuid, reason).await?,
+                None => self.attempt(record).await?,
+            });
+        }
+        Ok(retried)
+    }
+
+    async fn suppress(&self, notification_uuid: Uuid, reason: &str) -> Result<DeliveryRecord> {
+        let row = sqlx::query(
+            "UPDATE Notifications SET status = 'suppressed', last_error = ?, next_attempt_at = NULL
+             WHERE notification_uuid = ? RETURNING *",
+        )
+        .bind(reason)
+        .bind(notification_uuid.to_string())
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(record_from_row(&row))
+    }
+
+    /// Mark a sent message as bounced; its address is skipped from now on
+    pub async fn record_bounce(
+        &self,
+        notification_uuid: Uuid,
+        reason: Option<String>,
+    ) -> Result<DeliveryRecord> {
+        let row = sqlx::query(
+            "UPDATE Notifications SET status = 'bounced', bounced_at = ?,
+                last_error = COALESCE(?, last_error), next_attempt_at = NULL
+             WHERE notification_uuid = ? AND channel IS NOT NULL RETURNING *",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(reason)
+        .bind(notification_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Notification {} not found", notification_uuid))?;
+        Ok(record_from_row(&row))
+    }
+
+    pub async fn get_delivery(&self, notification_uuid: Uuid) -> Result<Option<DeliveryRecord>> {
+        let row = sqlx::query(
+            "SELECT * FROM Notifications WHERE notification_uuid = ? AND channel IS NOT NULL",
+        )
+        .bind(notification_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(row.as_ref().map(record_from_row))
+    }
+
+    /// Customer messages, newest first
+    pub async fn delivery_log(
+        &self,
+        customer_uuid: Option<Uuid>,
+        status: Option<DeliveryStatus>,
+        limit: i64,
+    ) -> Result<Vec<DeliveryRecord>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Notifications
+             WHERE channel IS NOT NULL
+               AND (?1 IS NULL OR customer_uuid = ?1)
+               AND (?2 IS NULL OR status = ?2)
+             ORDER BY created_at DESC
+             LIMIT ?3",
+        )
+        .bind(customer_uuid.map(|u| u.to_string()))
+        .bind(status.map(|s| s.as_str()))
+        .bind(limit)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().map(record_from_row).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), Duration::minutes(15));
+        assert_eq!(retry_delay(2), Duration::minutes(30));
+        assert_eq!(retry_delay(3), Duration::minutes(60));
+    }
+
+    #[test]
+    fn test_unsubscribe_footer() {
+        let html = "<html><body><p>Hi</p>\n</body></html>";
+        let out = with_unsubscribe_footer(NotificationChannel::Email, html, "https://x/u/t");
+        assert!(out.contains(r#"<a href="https://x/u/t">Unsubscribe</a>"#));
+        assert!(out.ends_with("</body></html>"));
+        assert_eq!(
+            with_unsubscribe_footer(NotificationChannel::Sms, "Sale!", "https://x/u/t"),
+            "Sale! Opt out: https://x/u/t"
+        );
+    }
+}
@@ -1,6 +1,13 @@
+pub mod delivery;
 pub mod email;
+pub mod preferences;
 pub mod scheduler;
 pub mod sms;
+pub mod templates;
+
+pub use delivery::{DeliveryRecord, DeliveryStatus, NotificationService};
+pub use preferences::{NotificationPreferences, PreferencesUpdate};
+pub use templates::{MessageCategory, MessageTemplate, NotificationChannel, TemplateKey};
 
 use anyhow::Result;
 use async_trait::async_trait;
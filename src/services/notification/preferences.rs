@@ -0,0 +1,177 @@
+//! Customer messaging preferences
+//!
+//! Each customer can switch email and SMS off entirely, and marketing
+//! messages additionally need an explicit opt-in per channel. The opt-in is
+//! stored as the time consent was given; withdrawing consent (directly or
+//! through the unsubscribe link) clears it.
+
+use super::templates::{MessageCategory, NotificationChannel};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    pub customer_uuid: Uuid,
+    /// Tried first when a message may go on either channel
+    pub preferred_channel: Option<NotificationChannel>,
+    pub email_enabled: bool,
+    pub sms_enabled: bool,
+    pub email_marketing_consent_at: Option<DateTime<Utc>>,
+    pub sms_marketing_consent_at: Option<DateTime<Utc>>,
+    pub unsubscribe_token: String,
+    pub unsubscribed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Partial update; `None` leaves a setting alone. An empty preferred
+/// channel clears it.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PreferencesUpdate {
+    pub preferred_channel: Option<String>,
+    pub email_enabled: Option<bool>,
+    pub sms_enabled: Option<bool>,
+    pub email_marketing: Option<bool>,
+    pub sms_marketing: Option<bool>,
+}
+
+impl NotificationPreferences {
+    /// Everything transactional allowed, no marketing consent
+    pub fn new(customer_uuid: Uuid) -> Self {
+        Self {
+            customer_uuid,
+            preferred_channel: None,
+            email_enabled: true,
+            sms_enabled: true,
+            email_marketing_consent_at: None,
+            sms_marketing_consent_at: None,
+            unsubscribe_token: Uuid::new_v4().simple().to_string(),
+            unsubscribed_at: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Why a message may not go out on `channel`, if it may not
+    pub fn blocked_reason(
+        &self,
+        channel: NotificationChannel,
+        category: MessageCategory,
+    ) -> Option<&'static str> {
+        let (enabled, consent) = match channel {
+            NotificationChannel::Email => (self.email_enabled, self.email_marketing_consent_at),
+            NotificationChannel::Sms => (self.sms_enabled, self.sms_marketing_consent_at),
+        };
+        if !enabled {
+            return Some("Customer has turned this channel off");
+        }
+        if category == MessageCategory::Marketing && consent.is_none() {
+            return Some("No marketing consent on this channel");
+        }
+        None
+    }
+
+    /// Channels in the order to try them
+    pub fn channel_order(&self) -> [NotificationChannel; 2] {
+        match self.preferred_channel {
+            Some(NotificationChannel::Sms) => {
+                [NotificationChannel::Sms, NotificationChannel::Email]
+            }
+            _ => [NotificationChannel::Email, NotificationChannel::Sms],
+        }
+    }
+
+    /// Apply an update, stamping consent when it is newly given
+    pub fn apply(&mut self, update: &PreferencesUpdate, now: DateTime<Utc>) {
+        if let Some(channel) = &update.preferred_channel {
+            self.preferred_channel = NotificationChannel::parse(channel);
+        }
+        if let Some(enabled) = update.email_enabled {
+            self.email_enabled = enabled;
+        }
+        if let Some(enabled) = update.sms_enabled {
+            self.sms_enabled = enabled;
+        }
+        if let Some(consent) = update.email_marketing {
+            self.email_marketing_consent_at = match consent {
+                true => self.email_marketing_consent_at.or(Some(now)),
+                false => None,
+            };
+        }
+        if let Some(consent) = update.sms_marketing {
+            self.sms_marketing_consent_at = match consent {
+                true => self.sms_marketing_consent_at.or(Some(now)),
+                false => None,
+            };
+        }
+        if self.email_marketing_consent_at.is_some() || self.sms_marketing_consent_at.is_some() {
+            self.unsubscribed_at = None;
+        }
+        self.updated_at = now;
+    }
+
+    /// Withdraw all marketing consent
+    pub fn unsubscribe(&mut self, now: DateTime<Utc>) {
+        self.email_marketing_consent_at = None;
+        self.sms_marketing_consent_at = None;
+        self.unsubscribed_at = Some(now);
+        self.updated_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consent_rules() {
+        let now = Utc::now();
+        let mut prefs = NotificationPreferences::new(Uuid::new_v4());
+        let email = NotificationChannel::Email;
+        let sms = NotificationChannel::Sms;
+
+        assert!(prefs
+            .blocked_reason(email, MessageCategory::Transactional)
+            .is_none());
+        assert!(prefs
+            .blocked_reason(email, MessageCategory::Marketing)
+            .is_some());
+
+        prefs.apply(
+            &PreferencesUpdate {
+                preferred_channel: Some("text".to_string()),
+                sms_marketing: Some(true),
+                email_enabled: Some(false),
+                ..Default::default()
+            },
+            now,
+        );
+        assert_eq!(prefs.channel_order()[0], sms);
+        assert_eq!(prefs.sms_marketing_consent_at, Some(now));
+        assert!(prefs
+            .blocked_reason(sms, MessageCategory::Marketing)
+            .is_none());
+        assert!(prefs
+            .blocked_reason(email, MessageCategory::Transactional)
+            .is_some());
+
+        // Re-confirming keeps the original consent time
+        let later = now + chrono::Duration::days(1);
+        prefs.apply(
+            &PreferencesUpdate {
+                sms_marketing: Some(true),
+                ..Default::default()
+            },
+            later,
+        );
+        assert_eq!(prefs.sms_marketing_consent_at, Some(now));
+
+        prefs.unsubscribe(later);
+        assert!(prefs
+            .blocked_reason(sms, MessageCategory::Marketing)
+            .is_some());
+        assert!(prefs
+            .blocked_reason(sms, MessageCategory::Transactional)
+            .is_none());
+        assert_eq!(prefs.unsubscribed_at, Some(later));
+    }
+}
@@ -1,13 +1,22 @@
 //! Notification Scheduler Service
 //!
 //! Handles scheduled/background notification tasks:
-//! - Wants list match notifications
+//! - Wants list match notifications and match expiry
 //! - Event reminders (email and SMS)
 //! - Hold expiration reminders
+//! - Layaway installment reminders
+//! - Retrying failed deliveries
+//!
+//! Message text comes from the store's templates and every send is recorded
+//! in the delivery log (see `delivery`).
 
+use crate::buylist::matcher::{WantsMatch, WantsMatchingService};
+use crate::core::Customer;
 use crate::database::Database;
 use crate::services::notification::sms::SmsProvider;
-use crate::services::notification::{EmailMessage, EmailProvider};
+use crate::services::notification::{
+    EmailProvider, MessageCategory, NotificationChannel, NotificationService, TemplateKey,
+};
 use anyhow::Result;
 use chrono::{Duration, Utc};
 use std::sync::Arc;
@@ -15,8 +24,7 @@ use uuid::Uuid;
 
 pub struct NotificationScheduler {
     db: Arc<Database>,
-    email_provider: Arc<Box<dyn EmailProvider>>,
-    sms_provider: Arc<Box<dyn SmsProvider>>,
+    notifications: NotificationService,
 }
 
 impl NotificationScheduler {
@@ -26,101 +34,45 @@ impl NotificationScheduler {
         sms_provider: Arc<Box<dyn SmsProvider>>,
     ) -> Self {
         Self {
+            notifications: NotificationService::new(db.clone(), email_provider, sms_provider),
             db,
-            email_provider,
-            sms_provider,
         }
     }
 
     /// TASK-194: Check for wants list matches against new inventory
-    /// Call this when new inventory is added
+    /// Call this when new inventory is added. Matching (and any requested
+    /// reservation) happens in the wants engine; each new match is then
+    /// sent on the customer's preferred channel.
     pub async fn check_wants_list_matches(&self, product_uuid: Uuid) -> Result<Vec<Uuid>> {
         tracing::info!("Checking wants list matches for product {}", product_uuid);
 
-        // Find all wants list items that match this product
-        let matching_wants = sqlx::query(
-            r#"
-            SELECT wl.customer_uuid, wli.wants_item_uuid, wli.product_uuid
-            FROM Wants_List_Items wli
-            JOIN Wants_Lists wl ON wli.wants_list_uuid = wl.wants_list_uuid
-            WHERE wli.product_uuid = ? AND wli.fulfilled = 0
-            "#,
-        )
-        .bind(product_uuid.to_string())
-        .fetch_all(&self.db.pool)
-        .await?;
+        let matches = WantsMatchingService::new(self.db.clone())
+            .match_product(product_uuid)
+            .await?;
+        let product_name = match self.db.products.get_by_id(product_uuid).await {
+            Ok(Some(p)) => p.name,
+            _ => "an item on your wants list".to_string(),
+        };
 
         let mut notified_customers = Vec::new();
-
-        for row in matching_wants {
-            let customer_uuid_str: String =
-                sqlx::Row::try_get(&row, "customer_uuid").unwrap_or_default();
-            let customer_uuid = match Uuid::parse_str(&customer_uuid_str) {
-                Ok(u) => u,
-                Err(_) => continue,
-            };
-
-            // Get customer details
-            if let Ok(Some(customer)) = self.db.customers.get_by_id(customer_uuid).await {
-                // Get product name
-                let product_name = match self.db.products.get_by_id(product_uuid).await {
-                    Ok(Some(p)) => p.name,
-                    _ => "an item on your wants list".to_string(),
-                };
-
-                // Send email if available
-                if let Some(email) = &customer.email {
-                    if !email.is_empty() {
-                        let msg = EmailMessage {
-                            to: email.clone(),
-                            subject: format!("🎉 {} is now available!", product_name),
-                            body: format!(
-                                r#"<!DOCTYPE html>
-<html>
-<body style="font-family: Arial, sans-serif;">
-    <h2>Great news, {}!</h2>
-    <p>An item from your wants list is now available:</p>
-    <div style="background: #f0f0f0; padding: 15px; border-radius: 5px; margin: 20px 0;">
-        <strong>{}</strong>
-    </div>
-    <p>Visit us soon to grab it before it's gone!</p>
-    <br>
-    <p>- The VaultSync Team</p>
-</body>
-</html>"#,
-                                customer.name, product_name
-                            ),
-                            attachment_path: None,
-                        };
-
-                        if let Err(e) = self.email_provider.send_email(&msg).await {
-                            tracing::warn!(
-                                "Failed to send wants list email to {}: {}",
-                                customer.name,
-                                e
-                            );
-                        } else {
-                            notified_customers.push(customer_uuid);
-                        }
-                    }
-                }
-
-                // Also send SMS if available
-                if let Some(phone) = &customer.phone {
-                    if !phone.is_empty() {
-                        let sms_body = format!(
-                            "VaultSync: {} is now available! Come grab it before it's gone.",
-                            product_name
-                        );
-                        if let Err(e) = self.sms_provider.send_sms(phone, &sms_body).await {
-                            tracing::warn!(
-                                "Failed to send wants list SMS to {}: {}",
-                                customer.name,
-                                e
-                            );
-                        }
+        for (customer, wants_match) in matches {
+            match self
+                .notify_wants_match(&customer, &product_name, &wants_match)
+                .await
+            {
+                Some(channel) => {
+                    WantsMatchingService::new(self.db.clone())
+                        .mark_notified(wants_match.match_uuid, channel)
+                        .await?;
+                    if !notified_customers.contains(&customer.customer_uuid) {
+                        notified_customers.push(customer.customer_uuid);
                     }
                 }
+                None => tracing::warn!(
+                    "No way to reach {} about wants match {}",
+                    customer.name,
+                    wants_match.match_uuid
+                ),
             }
         }
 
@@ -135,6 +87,87 @@ impl NotificationScheduler {
         Ok(notified_customers)
     }
 
+    /// Send one wants-match notice, preferring the customer's chosen channel
+    /// and falling back to the other. Returns the channel used.
+    async fn notify_wants_match(
+        &self,
+        customer: &Customer,
+        product_name: &str,
+        wants_match: &WantsMatch,
+    ) -> Option<&'static str> {
+        let held_until = match wants_match.hold_uuid {
+            Some(hold_uuid) => sqlx::query_scalar::<_, String>(
+                "SELECT expiration_date FROM Holds WHERE hold_uuid = ?",
+            )
+            .bind(hold_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .ok()
+            .flatten(),
+            None => None,
+        };
+        let availability = match held_until
+            .and_then(|d| chrono::DateTime::parse_from_rfc3339(&d).ok())
+            .map(|d| d.format("%b %-d at %-I:%M %p").to_string())
+        {
+            Some(until) => format!("We're holding it for you until {}.", until),
+            None => "Visit us soon to grab it before it's gone!".to_string(),
+        };
+
+        let records = match self
+            .notifications
+            .notify(
+                customer.customer_uuid,
+                TemplateKey::WantsMatch,
+                &[
+                    ("product_name", product_name.to_string()),
+                    ("quantity", wants_match.quantity.to_string()),
+                    ("availability", availability),
+                ],
+            )
+            .await
+        {
+            Ok(records) => records,
+            Err(e) => {
+                tracing::warn!("Failed to notify {} of wants match: {}", customer.name, e);
+                return None;
+            }
+        };
+        records
+            .iter()
+            .find(|r| r.is_sent())
+            .and_then(|r| r.channel)
+            .map(|c| c.as_str())
+    }
+
+    /// Send a reminder on every channel the customer receives messages on.
+    /// Returns whether anything went out.
+    async fn remind(&self, customer_uuid: Uuid, key: TemplateKey, vars: &[(&str, String)]) -> bool {
+        match self
+            .notifications
+            .send_to_customer(
+                customer_uuid,
+                key,
+                MessageCategory::Transactional,
+                vars,
+                &[NotificationChannel::Sms, NotificationChannel::Email],
+                false,
+            )
+            .await
+        {
+            Ok(records) => records.iter().any(|r| r.is_sent()),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to send {} to customer {}: {}",
+                    key.as_str(),
+                    customer_uuid,
+                    e
+                );
+                false
+            }
+        }
+    }
+
     /// TASK-195 & TASK-200: Send event reminders 24 hours before
     pub async fn send_event_reminders(&self) -> Result<i32> {
         let now = Utc::now();
@@ -146,7 +179,7 @@ impl NotificationScheduler {
         // Find events happening in ~24 hours that haven't had reminders sent
         let upcoming_events = sqlx::query(
             r#"
-            SELECT event_uuid, name, date, entry_fee
+            SELECT event_uuid, name, date
             FROM Events
             WHERE date BETWEEN ? AND ?
             AND reminder_sent = 0
@@ -155,26 +188,7 @@ impl NotificationScheduler {
         .bind(reminder_window_start.to_rfc3339())
         .bind(reminder_window_end.to_rfc3339())
         .fetch_all(&self.db.pool)
-        .await;
-
-        // If query fails (table doesn't have reminder_sent column), try without it
-        let upcoming_events = match upcoming_events {
-            Ok(rows) => rows,
-            Err(_) => {
-                // Fallback query without reminder_sent
-                sqlx::query(
-                    r#"
-                    SELECT event_uuid, name, date, entry_fee
-                    FROM Events
-                    WHERE date BETWEEN ? AND ?
-                    "#,
-                )
-                .bind(reminder_window_start.to_rfc3339())
-                .bind(reminder_window_end.to_rfc3339())
-                .fetch_all(&self.db.pool)
-                .await?
-            }
-        };
+        .await?;
 
         let mut total_sent = 0;
 
@@ -206,58 +220,25 @@ impl NotificationScheduler {
                 let participant_name: String =
                     sqlx::Row::try_get(&participant, "participant_name").unwrap_or_default();
 
-                // Try to get customer contact info
                 if let Ok(customer_uuid) = Uuid::parse_str(&customer_uuid_str) {
-                    if let Ok(Some(customer)) = self.db.customers.get_by_id(customer_uuid).await {
-                        // Send email reminder
-                        if let Some(email) = &customer.email {
-                            if !email.is_empty() {
-                                let msg = EmailMessage {
-                                    to: email.clone(),
-                                    subject: format!("⏰ Reminder: {} is tomorrow!", event_name),
-                                    body: format!(
-                                        r#"<!DOCTYPE html>
-<html>
-<body style="font-family: Arial, sans-serif;">
-    <h2>Event Reminder</h2>
-    <p>Hi {},</p>
-    <p>This is a friendly reminder that <strong>{}</strong> is happening tomorrow!</p>
-    <p><strong>Date:</strong> {}</p>
-    <p>We look forward to seeing you there!</p>
-    <br>
-    <p>- The VaultSync Team</p>
-</body>
-</html>"#,
-                                        participant_name, event_name, event_date
-                                    ),
-                                    attachment_path: None,
-                                };
-
-                                if self.email_provider.send_email(&msg).await.is_ok() {
-                                    total_sent += 1;
-                                }
-                            }
-                        }
-
-                        // Send SMS reminder
-                        if let Some(phone) = &customer.phone {
-                            if !phone.is_empty() {
-                                let sms_body = format!(
-                                    "Reminder: {} is tomorrow! See you there. - VaultSync",
-                                    event_name
-                                );
-                                let _ = self.sms_provider.send_sms(phone, &sms_body).await;
-                            }
-                        }
+                    let vars = [
+                        ("customer_name", participant_name),
+                        ("event_name", event_name.clone()),
+                        ("event_date", event_date.clone()),
+                    ];
+                    if self
+                        .remind(customer_uuid, TemplateKey::EventReminder, &vars)
+                        .await
+                    {
+                        total_sent += 1;
                     }
                 }
             }
 
-            // Mark reminder as sent (if column exists)
-            let _ = sqlx::query("UPDATE Events SET reminder_sent = 1 WHERE event_uuid = ?")
+            sqlx::query("UPDATE Events SET reminder_sent = 1 WHERE event_uuid = ?")
                 .bind(event_uuid.to_string())
                 .execute(&self.db.pool)
-                .await;
+                .await?;
         }
 
         if total_sent > 0 {
@@ -299,54 +280,22 @@ impl NotificationScheduler {
                 sqlx::Row::try_get(&hold_row, "expiration_date").unwrap_or_default();
 
             if let Ok(customer_uuid) = Uuid::parse_str(&customer_uuid_str) {
-                if let Ok(Some(customer)) = self.db.customers.get_by_id(customer_uuid).await {
-                    // Send SMS reminder (primary for hold reminders)
-                    if let Some(phone) = &customer.phone {
-                        if !phone.is_empty() {
-                            let sms_body = format!(
-                                "VaultSync: Your layaway expires on {}. Balance due: ${:.2}. Visit us to make a payment!",
-                                expiration_date.split('T').next().unwrap_or(&expiration_date),
-                                balance_due
-                            );
-
-                            if self.sms_provider.send_sms(phone, &sms_body).await.is_ok() {
-                                total_sent += 1;
-                            }
-                        }
-                    }
-
-                    // Also send email
-                    if let Some(email) = &customer.email {
-                        if !email.is_empty() {
-                            let msg = EmailMessage {
-                                to: email.clone(),
-                                subject: "⚠️ Your layaway is expiring soon".to_string(),
-                                body: format!(
-                                    r#"<!DOCTYPE html>
-<html>
-<body style="font-family: Arial, sans-serif;">
-    <h2>Layaway Expiration Notice</h2>
-    <p>Hi {},</p>
-    <p>This is a reminder that your layaway will expire on <strong>{}</strong>.</p>
-    <p><strong>Balance Due:</strong> ${:.2}</p>
-    <p>Please visit us to make a payment and avoid losing your items.</p>
-    <br>
-    <p>- The VaultSync Team</p>
-</body>
-</html>"#,
-                                    customer.name,
-                                    expiration_date
-                                        .split('T')
-                                        .next()
-                                        .unwrap_or(&expiration_date),
-                                    balance_due
-                                ),
-                                attachment_path: None,
-                            };
-
-                            let _ = self.email_provider.send_email(&msg).await;
-                        }
-                    }
+                let vars = [
+                    (
+                        "expiration_date",
+                        expiration_date
+                            .split('T')
+                            .next()
+                            .unwrap_or(&expiration_date)
+                            .to_string(),
+                    ),
+                    ("balance_due", format!("{:.2}", balance_due)),
+                ];
+                if self
+                    .remind(customer_uuid, TemplateKey::HoldReminder, &vars)
+                    .await
+                {
+                    total_sent += 1;
                 }
             }
         }
@@ -358,11 +307,86 @@ impl NotificationScheduler {
         Ok(total_sent)
     }
 
+    /// Send reminders for layaway installments due in `days_before` days
+    pub async fn send_installment_reminders(&self, days_before: i64) -> Result<i32> {
+        let now = Utc::now();
+        let window_start = now + Duration::days(days_before);
+        let window_end = window_start + Duration::hours(24);
+
+        tracing::info!(
+            "Checking for layaway installments due in {} days...",
+            days_before
+        );
+
+        let due_installments = sqlx::query(
+            r#"
+            SELECT h.customer_uuid, h.balance_due, hi.sequence, hi.due_date,
+                   hi.amount_due + hi.late_fee - hi.amount_paid AS amount_owed
+            FROM Hold_Installments hi
+            JOIN Holds h ON hi.hold_uuid = h.hold_uuid
+            WHERE h.status = 'Active'
+            AND hi.status IN ('Pending', 'Late')
+            AND hi.amount_paid < hi.amount_due + hi.late_fee
+            AND hi.due_date BETWEEN ? AND ?
+            "#,
+        )
+        .bind(window_start.to_rfc3339())
+        .bind(window_end.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let mut total_sent = 0;
+
+        for row in due_installments {
+            let customer_uuid_str: String =
+                sqlx::Row::try_get(&row, "customer_uuid").unwrap_or_default();
+            let amount_owed: f64 = sqlx::Row::try_get(&row, "amount_owed").unwrap_or(0.0);
+            let balance_due: f64 = sqlx::Row::try_get(&row, "balance_due").unwrap_or(0.0);
+            let sequence: i32 = sqlx::Row::try_get(&row, "sequence").unwrap_or(0);
+            let due_date: String = sqlx::Row::try_get(&row, "due_date").unwrap_or_default();
+            let due_day = due_date.split('T').next().unwrap_or(&due_date).to_string();
+
+            if let Ok(customer_uuid) = Uuid::parse_str(&customer_uuid_str) {
+                let vars = [
+                    ("installment_number", sequence.to_string()),
+                    ("amount_due", format!("{:.2}", amount_owed)),
+                    ("due_date", due_day),
+                    ("balance_due", format!("{:.2}", balance_due)),
+                ];
+                if self
+                    .remind(customer_uuid, TemplateKey::InstallmentReminder, &vars)
+                    .await
+                {
+                    total_sent += 1;
+                }
+            }
+        }
+
+        if total_sent > 0 {
+            tracing::info!("Sent {} layaway installment reminders", total_sent);
+        }
+
+        Ok(total_sent)
+    }
+
     /// Run all scheduled notification checks
     /// Call this from a background task/timer
     pub async fn run_scheduled_tasks(&self) -> Result<()> {
         tracing::info!("Running scheduled notification tasks...");
 
+        // Settle wants matches: paid holds fulfil, lapsed reservations release
+        if let Err(e) = WantsMatchingService::new(self.db.clone())
+            .refresh_matches()
+            .await
+        {
+            tracing::error!("Failed to refresh wants matches: {}", e);
+        }
+
+        // Failed sends whose backoff has elapsed
+        if let Err(e) = self.notifications.retry_failed().await {
+            tracing::error!("Failed to retry notifications: {}", e);
+        }
+
         // Event reminders (24h before)
         if let Err(e) = self.send_event_reminders().await {
             tracing::error!("Failed to send event reminders: {}", e);
@@ -378,6 +402,11 @@ impl NotificationScheduler {
             tracing::error!("Failed to send final hold expiration reminders: {}", e);
         }
 
+        // Layaway installment reminders (2 days before due date)
+        if let Err(e) = self.send_installment_reminders(2).await {
+            tracing::error!("Failed to send installment reminders: {}", e);
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,343 @@
+//! Message templates
+//!
+//! Every customer message is rendered from a template keyed by message kind
+//! and channel. The built-in defaults below can be overridden per store in
+//! `Notification_Templates`. Templates use `{{variable}}` placeholders; each
+//! kind documents the variables it is rendered with, and saving a template
+//! that uses anything else is rejected so typos don't reach customers.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Email,
+    Sms,
+}
+
+impl NotificationChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::Email => "email",
+            NotificationChannel::Sms => "sms",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "email" => Some(NotificationChannel::Email),
+            "sms" | "text" | "phone" => Some(NotificationChannel::Sms),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a message needs marketing consent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageCategory {
+    /// About something the customer asked for or is party to
+    Transactional,
+    /// Promotional; requires opt-in consent on the channel
+    Marketing,
+}
+
+impl MessageCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageCategory::Transactional => "transactional",
+            MessageCategory::Marketing => "marketing",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateKey {
+    WantsMatch,
+    HoldReminder,
+    InstallmentReminder,
+    EventReminder,
+    Receipt,
+    /// Free-form message typed by staff
+    CustomMessage,
+}
+
+impl TemplateKey {
+    pub const ALL: [TemplateKey; 6] = [
+        TemplateKey::WantsMatch,
+        TemplateKey::HoldReminder,
+        TemplateKey::InstallmentReminder,
+        TemplateKey::EventReminder,
+        TemplateKey::Receipt,
+        TemplateKey::CustomMessage,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TemplateKey::WantsMatch => "wants_match",
+            TemplateKey::HoldReminder => "hold_reminder",
+            TemplateKey::InstallmentReminder => "installment_reminder",
+            TemplateKey::EventReminder => "event_reminder",
+            TemplateKey::Receipt => "receipt",
+            TemplateKey::CustomMessage => "custom_message",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == s)
+    }
+
+    /// Variables the message is rendered with
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            TemplateKey::WantsMatch => {
+                &["customer_name", "product_name", "quantity", "availability"]
+            }
+            TemplateKey::HoldReminder => &["customer_name", "expiration_date", "balance_due"],
+            TemplateKey::InstallmentReminder => &[
+                "customer_name",
+                "installment_number",
+                "amount_due",
+                "due_date",
+                "balance_due",
+            ],
+            TemplateKey::EventReminder => &["customer_name", "event_name", "event_date"],
+            TemplateKey::Receipt => &["customer_name", "transaction_id", "receipt"],
+            TemplateKey::CustomMessage => &["customer_name", "message"],
+        }
+    }
+
+    pub fn channels(&self) -> &'static [NotificationChannel] {
+        match self {
+            TemplateKey::Receipt => &[NotificationChannel::Email],
+            _ => &[NotificationChannel::Email, NotificationChannel::Sms],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageTemplate {
+    pub template_key: TemplateKey,
+    pub channel: NotificationChannel,
+    /// Email subject; unused for SMS
+    pub subject: String,
+    pub body: String,
+    /// False when this is the built-in default
+    #[serde(default)]
+    pub customized: bool,
+}
+
+fn html(heading: &str, content: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif;">
+    <h2>{}</h2>
+{}
+    <br>
+    <p>- The VaultSync Team</p>
+</body>
+</html>"#,
+        heading, content
+    )
+}
+
+/// The built-in template for a kind and channel
+pub fn default_template(key: TemplateKey, channel: NotificationChannel) -> MessageTemplate {
+    let (subject, body) = match (key, channel) {
+        (TemplateKey::WantsMatch, NotificationChannel::Email) => (
+            "🎉 {{product_name}} is now available!".to_string(),
+            html(
+                "Great news, {{customer_name}}!",
+                r#"    <p>An item from your wants list is now available:</p>
+    <div style="background: #f0f0f0; padding: 15px; border-radius: 5px; margin: 20px 0;">
+        <strong>{{quantity}} x {{product_name}}</strong>
+    </div>
+    <p>{{availability}}</p>"#,
+            ),
+        ),
+        (TemplateKey::WantsMatch, NotificationChannel::Sms) => (
+            String::new(),
+            "VaultSync: {{quantity}} x {{product_name}} from your wants list is in! {{availability}}"
+                .to_string(),
+        ),
+        (TemplateKey::HoldReminder, NotificationChannel::Email) => (
+            "⚠️ Your layaway is expiring soon".to_string(),
+            html(
+                "Layaway Expiration Notice",
+                r#"    <p>Hi {{customer_name}},</p>
+    <p>This is a reminder that your layaway will expire on <strong>{{expiration_date}}</strong>.</p>
+    <p><strong>Balance Due:</strong> ${{balance_due}}</p>
+    <p>Please visit us to make a payment and avoid losing your items.</p>"#,
+            ),
+        ),
+        (TemplateKey::HoldReminder, NotificationChannel::Sms) => (
+            String::new(),
+            "VaultSync: Your layaway expires on {{expiration_date}}. Balance due: ${{balance_due}}. Visit us to make a payment!"
+                .to_string(),
+        ),
+        (TemplateKey::InstallmentReminder, NotificationChannel::Email) => (
+            "Your layaway payment is coming up".to_string(),
+            html(
+                "Layaway Payment Reminder",
+                r#"    <p>Hi {{customer_name}},</p>
+    <p>Payment <strong>#{{installment_number}}</strong> of <strong>${{amount_due}}</strong> is due on <strong>{{due_date}}</strong>.</p>
+    <p><strong>Remaining Balance:</strong> ${{balance_due}}</p>
+    <p>Late payments may incur a fee under your layaway plan.</p>"#,
+            ),
+        ),
+        (TemplateKey::InstallmentReminder, NotificationChannel::Sms) => (
+            String::new(),
+            "VaultSync: Layaway payment #{{installment_number}} of ${{amount_due}} is due {{due_date}}. Remaining balance: ${{balance_due}}."
+                .to_string(),
+        ),
+        (TemplateKey::EventReminder, NotificationChannel::Email) => (
+            "⏰ Reminder: {{event_name}} is tomorrow!".to_string(),
+            html(
+                "Event Reminder",
+                r#"    <p>Hi {{customer_name}},</p>
+    <p>This is a friendly reminder that <strong>{{event_name}}</strong> is happening tomorrow!</p>
+    <p><strong>Date:</strong> {{event_date}}</p>
+    <p>We look forward to seeing you there!</p>"#,
+            ),
+        ),
+        (TemplateKey::EventReminder, NotificationChannel::Sms) => (
+            String::new(),
+            "Reminder: {{event_name}} is tomorrow! See you there. - VaultSync".to_string(),
+        ),
+        (TemplateKey::Receipt, _) => (
+            "Receipt for Transaction {{transaction_id}}".to_string(),
+            "{{receipt}}".to_string(),
+        ),
+        (TemplateKey::CustomMessage, NotificationChannel::Email) => (
+            "Notification from VaultSync".to_string(),
+            "{{message}}".to_string(),
+        ),
+        (TemplateKey::CustomMessage, NotificationChannel::Sms) => {
+            (String::new(), "{{message}}".to_string())
+        }
+    };
+    MessageTemplate {
+        template_key: key,
+        channel,
+        subject,
+        body,
+        customized: false,
+    }
+}
+
+/// Names of the `{{variables}}` a template uses
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                names.push(after[..end].trim().to_string());
+                rest = &after[end + 2..];
+            }
+            None => break,
+        }
+    }
+    names
+}
+
+/// Reject placeholders the kind isn't rendered with
+pub fn validate_template(key: TemplateKey, subject: &str, body: &str) -> Result<(), String> {
+    if body.trim().is_empty() {
+        return Err("Template body cannot be empty".to_string());
+    }
+    let allowed = key.variables();
+    let unknown: Vec<String> = placeholders(subject)
+        .into_iter()
+        .chain(placeholders(body))
+        .filter(|name| !allowed.contains(&name.as_str()))
+        .collect();
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown template variables: {} (available: {})",
+            unknown.join(", "),
+            allowed.join(", ")
+        ))
+    }
+}
+
+/// Substitute `{{variables}}`; anything without a value renders empty
+pub fn render(text: &str, vars: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                if let Some(value) = vars.get(after[..end].trim()) {
+                    out.push_str(value);
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_and_validate() {
+        let vars: HashMap<String, String> = [
+            ("customer_name".to_string(), "Robin".to_string()),
+            ("event_name".to_string(), "FNM".to_string()),
+        ]
+        .into();
+        assert_eq!(
+            render(
+                "Hi {{customer_name}}, {{ event_name }} at {{event_date}}!",
+                &vars
+            ),
+            "Hi Robin, FNM at !"
+        );
+        assert_eq!(
+            render("Unclosed {{customer_name", &vars),
+            "Unclosed {{customer_name"
+        );
+
+        assert!(validate_template(
+            TemplateKey::EventReminder,
+            "",
+            "{{event_name}} {{event_date}}"
+        )
+        .is_ok());
+        let err = validate_template(TemplateKey::EventReminder, "{{evnt_name}}", "x").unwrap_err();
+        assert!(err.contains("evnt_name"));
+        assert!(validate_template(TemplateKey::Receipt, "", "  ").is_err());
+    }
+
+    #[test]
+    fn test_defaults_only_use_declared_variables() {
+        for key in TemplateKey::ALL {
+            for channel in key.channels() {
+                let template = default_template(key, *channel);
+                assert!(
+                    validate_template(key, &template.subject, &template.body).is_ok(),
+                    "{:?}/{:?}",
+                    key,
+                    channel
+                );
+            }
+            assert_eq!(TemplateKey::parse(key.as_str()), Some(key));
+        }
+    }
+}
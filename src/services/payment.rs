@@ -2,10 +2,17 @@
 //!
 //! Handles payment recording, split payments, store credit,
 //! and cash transactions with change calculation.
+//!
+//! Amounts are recorded in the store's base currency. Foreign tender is
+//! converted at the configured exchange rate and the original tendered
+//! amount, currency and rate are kept alongside the payment.
 
+use crate::core::{Currency, Money};
 use crate::database::Database;
 use crate::errors::Result;
+use crate::services::CurrencyService;
 use chrono::Utc;
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
@@ -18,6 +25,8 @@ pub enum PaymentMethodType {
     StoreCredit,
     Check,
     GiftCard,
+    /// Loyalty points spent at their configured dollar value
+    LoyaltyPoints,
     Other,
 }
 
@@ -29,6 +38,7 @@ impl std::fmt::Display for PaymentMethodType {
             PaymentMethodType::StoreCredit => write!(f, "StoreCredit"),
             PaymentMethodType::Check => write!(f, "Check"),
             PaymentMethodType::GiftCard => write!(f, "GiftCard"),
+            PaymentMethodType::LoyaltyPoints => write!(f, "LoyaltyPoints"),
             PaymentMethodType::Other => write!(f, "Other"),
         }
     }
@@ -44,6 +54,7 @@ impl std::str::FromStr for PaymentMethodType {
             "storecredit" | "store_credit" => Ok(PaymentMethodType::StoreCredit),
             "check" => Ok(PaymentMethodType::Check),
             "giftcard" | "gift_card" => Ok(PaymentMethodType::GiftCard),
+            "loyaltypoints" | "loyalty_points" => Ok(PaymentMethodType::LoyaltyPoints),
             "other" => Ok(PaymentMethodType::Other),
             _ => Err(anyhow::anyhow!("Invalid payment method: {}", s)),
         }
@@ -61,15 +72,23 @@ pub struct PaymentRecord {
     pub card_last_four: Option<String>,
     pub auth_code: Option<String>,
     pub created_at: chrono::DateTime<Utc>,
+    /// Original tender when paid in a foreign currency
+    #[serde(with = "crate::core::money::tagged::option")]
+    pub tendered: Option<Money>,
+    pub exchange_rate: Option<f64>,
 }
 
 /// Request to process a payment
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentRequest {
     pub method: PaymentMethodType,
+    /// Amount in `currency` (or the base currency when unset)
     pub amount: f64,
     pub reference: Option<String>,
     pub card_last_four: Option<String>,
+    /// Currency tendered in, if not the base currency
+    #[serde(default)]
+    pub currency: Option<Currency>,
 }
 
 /// Result of processing a payment
@@ -78,9 +97,13 @@ pub struct PaymentResult {
     pub success: bool,
     pub payment_uuid: Uuid,
     pub method: PaymentMethodType,
+    /// Amount credited in the base currency
     pub amount: f64,
     pub reference: Option<String>,
     pub error: Option<String>,
+    /// Original tender when paid in a foreign currency
+    #[serde(with = "crate::core::money::tagged::option")]
+    pub tendered: Option<Money>,
 }
 
 /// Result of a cash payment with change
@@ -103,11 +126,63 @@ pub struct SplitPaymentResult {
 /// Service for handling payments
 pub struct PaymentService {
     db: Arc<Database>,
+    currency: CurrencyService,
+}
+
+/// A payment request resolved into base currency
+struct ResolvedTender {
+    base_amount: f64,
+    /// (original tender, rate applied) for foreign currency payments
+    foreign: Option<(Money, f64)>,
 }
 
 impl PaymentService {
     pub fn new(db: Arc<Database>) -> Self {
-        Self { db }
+        Self {
+            currency: CurrencyService::new(db.clone()),
+            db,
+        }
+    }
+
+    /// Value of a payment request in the base currency
+    pub async fn base_amount(&self, request: &PaymentRequest) -> Result<f64> {
+        Ok(self.resolve_tender(request).await?.base_amount)
+    }
+
+    async fn resolve_tender(&self, request: &PaymentRequest) -> Result<ResolvedTender> {
+        let base = self.currency.get_base_currency().await?;
+        let tendered_in = match request.currency {
+            Some(currency) if currency != base => currency,
+            _ => {
+                return Ok(ResolvedTender {
+                    base_amount: request.amount,
+                    foreign: None,
+                })
+            }
+        };
+
+        if request.method == PaymentMethodType::StoreCredit {
+            return Err(anyhow::anyhow!(
+                "Store credit is held in {} and cannot be tendered in {}",
+                base,
+                tendered_in
+            ));
+        }
+        if request.method == PaymentMethodType::LoyaltyPoints {
+            return Err(anyhow::anyhow!(
+                "Loyalty points are valued in {} and cannot be tendered in {}",
+                base,
+                tendered_in
+            ));
+        }
+
+        let rate = self.currency.rate_to_base(tendered_in).await?;
+        let tendered = Money::from_f64_lossy_in(request.amount, tendered_in);
+        let converted = tendered.convert(base, rate);
+        Ok(ResolvedTender {
+            base_amount: converted.inner().to_f64().unwrap_or(0.0),
+            foreign: Some((tendered, rate.to_f64().unwrap_or(1.0))),
+        })
     }
 
     /// Record a payment for a transaction
@@ -118,21 +193,26 @@ impl PaymentService {
     ) -> Result<PaymentResult> {
         let payment_uuid = Uuid::new_v4();
         let now = Utc::now();
+        let tender = self.resolve_tender(&request).await?;
 
         // Insert payment record
         sqlx::query(
             "INSERT INTO Payment_Methods 
-             (payment_uuid, transaction_uuid, method_type, amount, reference, card_last_four, auth_code, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+             (payment_uuid, transaction_uuid, method_type, amount, reference, card_last_four, auth_code, created_at,
+              tendered_currency, tendered_amount, exchange_rate)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(payment_uuid.to_string())
         .bind(transaction_uuid.to_string())
         .bind(request.method.to_string())
-        .bind(request.amount)
+        .bind(tender.base_amount)
         .bind(&request.reference)
         .bind(&request.card_last_four)
         .bind(None::<String>) // auth_code not used for now
         .bind(now.to_rfc3339())
+        .bind(tender.foreign.map(|(m, _)| m.currency().to_string()))
+        .bind(tender.foreign.map(|_| request.amount))
+        .bind(tender.foreign.map(|(_, rate)| rate))
         .execute(&self.db.pool)
         .await
         .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
@@ -141,9 +221,10 @@ impl PaymentService {
             success: true,
             payment_uuid,
             method: request.method,
-            amount: request.amount,
+            amount: tender.base_amount,
             reference: request.reference,
             error: None,
+            tendered: tender.foreign.map(|(m, _)| m),
         })
     }
 
@@ -177,6 +258,7 @@ impl PaymentService {
                         cash_tendered, change_due
                     )),
                     card_last_four: None,
+                    currency: None,
                 },
             )
             .await?;
@@ -249,6 +331,7 @@ impl PaymentService {
                     amount,
                     reference: Some(format!("New balance: ${:.2}", new_balance)),
                     card_last_four: None,
+                    currency: None,
                 },
             )
             .await?;
@@ -263,6 +346,28 @@ impl PaymentService {
         Ok(payment)
     }
 
+    /// Pay with loyalty points worth `amount` dollars
+    pub async fn process_loyalty_payment(
+        &self,
+        transaction_uuid: Uuid,
+        customer_uuid: Uuid,
+        amount: f64,
+    ) -> Result<PaymentResult> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+        let payment = self
+            .process_loyalty_payment_with_tx(&mut tx, transaction_uuid, customer_uuid, amount)
+            .await?;
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+        Ok(payment)
+    }
+
     /// Process a split payment (multiple payment methods)
     pub async fn process_split_payment(
         &self,
@@ -271,8 +376,11 @@ impl PaymentService {
         payments: Vec<PaymentRequest>,
         total_due: f64,
     ) -> Result<SplitPaymentResult> {
-        // Validate total
-        let total_payments: f64 = payments.iter().map(|p| p.amount).sum();
+        // Validate total (foreign tender counted at its base-currency value)
+        let mut total_payments = 0.0;
+        for payment in &payments {
+            total_payments += self.base_amount(payment).await?;
+        }
         let tolerance = 0.01; // 1 cent tolerance for floating point
 
         if (total_payments - total_due).abs() > tolerance && total_payments < total_due {
@@ -292,6 +400,8 @@ impl PaymentService {
                     let cust_uuid = customer_uuid.ok_or_else(|| {
                         anyhow::anyhow!("Customer required for store credit payment")
                     })?;
+                    // Rejects store credit tendered in a foreign currency
+                    self.resolve_tender(&payment_request).await?;
                     self.process_store_credit_payment(
                         transaction_uuid,
                         cust_uuid,
@@ -299,6 +409,18 @@ impl PaymentService {
                     )
                     .await?
                 }
+                PaymentMethodType::LoyaltyPoints => {
+                    let cust_uuid = customer_uuid.ok_or_else(|| {
+                        anyhow::anyhow!("Customer required for loyalty points payment")
+                    })?;
+                    self.resolve_tender(&payment_request).await?;
+                    self.process_loyalty_payment(
+                        transaction_uuid,
+                        cust_uuid,
+                        payment_request.amount,
+                    )
+                    .await?
+                }
                 PaymentMethodType::Cash => {
                     // For split, cash is exact (no change on partial)
                     self.record_payment(transaction_uuid, payment_request)
@@ -328,7 +450,8 @@ impl PaymentService {
         transaction_uuid: Uuid,
     ) -> Result<Vec<PaymentRecord>> {
         let rows = sqlx::query(
-            "SELECT payment_uuid, transaction_uuid, method_type, amount, reference, card_last_four, auth_code, created_at
+            "SELECT payment_uuid, transaction_uuid, method_type, amount, reference, card_last_four, auth_code, created_at,
+                    tendered_currency, tendered_amount, exchange_rate
              FROM Payment_Methods WHERE transaction_uuid = ?
              ORDER BY created_at ASC",
         )
@@ -356,6 +479,18 @@ impl PaymentService {
                     .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
                     .map(|dt| dt.with_timezone(&Utc))
                     .unwrap_or_else(Utc::now),
+                tendered: sqlx::Row::try_get::<Option<String>, _>(&row, "tendered_currency")
+                    .ok()
+                    .flatten()
+                    .and_then(|code| Currency::from_code(&code))
+                    .map(|currency| {
+                        let amount = sqlx::Row::try_get::<Option<f64>, _>(&row, "tendered_amount")
+                            .ok()
+                            .flatten()
+                            .unwrap_or(0.0);
+                        Money::from_f64_lossy_in(amount, currency)
+                    }),
+                exchange_rate: sqlx::Row::try_get(&row, "exchange_rate").ok().flatten(),
             });
         }
 
@@ -429,20 +564,25 @@ impl PaymentService {
     ) -> Result<PaymentResult> {
         let payment_uuid = Uuid::new_v4();
         let now = Utc::now();
+        let tender = self.resolve_tender(&request).await?;
 
         sqlx::query(
             "INSERT INTO Payment_Methods 
-             (payment_uuid, transaction_uuid, method_type, amount, reference, card_last_four, auth_code, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+             (payment_uuid, transaction_uuid, method_type, amount, reference, card_last_four, auth_code, created_at,
+              tendered_currency, tendered_amount, exchange_rate)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(payment_uuid.to_string())
         .bind(transaction_uuid.to_string())
         .bind(request.method.to_string())
-        .bind(request.amount)
+        .bind(tender.base_amount)
         .bind(&request.reference)
         .bind(&request.card_last_four)
         .bind(None::<String>)
         .bind(now.to_rfc3339())
+        .bind(tender.foreign.map(|(m, _)| m.currency().to_string()))
+        .bind(tender.foreign.map(|_| request.amount))
+        .bind(tender.foreign.map(|(_, rate)| rate))
         .execute(&mut **tx)
         .await
         .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
@@ -451,9 +591,10 @@ impl PaymentService {
             success: true,
             payment_uuid,
             method: request.method,
-            amount: request.amount,
+            amount: tender.base_amount,
             reference: request.reference,
             error: None,
+            tendered: tender.foreign.map(|(m, _)| m),
         })
     }
 
@@ -509,6 +650,7 @@ impl PaymentService {
                     amount,
                     reference: Some(format!("New balance: ${:.2}", new_balance)),
                     card_last_four: None,
+                    currency: None,
                 },
             )
             .await?;
@@ -516,6 +658,37 @@ impl PaymentService {
         Ok(payment)
     }
 
+    /// Pay with loyalty points using an existing transaction
+    pub async fn process_loyalty_payment_with_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        transaction_uuid: Uuid,
+        customer_uuid: Uuid,
+        amount: f64,
+    ) -> Result<PaymentResult> {
+        let (entry, balance) = crate::services::loyalty::redeem_with_tx(
+            &self.db,
+            tx,
+            customer_uuid,
+            transaction_uuid,
+            amount,
+        )
+        .await?;
+
+        self.record_payment_with_tx(
+            tx,
+            transaction_uuid,
+            PaymentRequest {
+                method: PaymentMethodType::LoyaltyPoints,
+                amount,
+                reference: Some(format!("{} points, new balance {}", -entry.points, balance)),
+                card_last_four: None,
+                currency: None,
+            },
+        )
+        .await
+    }
+
     /// Process split payment using an existing transaction
     pub async fn process_split_payment_with_tx(
         &self,
@@ -525,8 +698,11 @@ impl PaymentService {
         payments: Vec<PaymentRequest>,
         total_due: f64,
     ) -> Result<SplitPaymentResult> {
-        // Validate total
-        let total_payments: f64 = payments.iter().map(|p| p.amount).sum();
+        // Validate total (foreign tender counted at its base-currency value)
+        let mut total_payments = 0.0;
+        for payment in &payments {
+            total_payments += self.base_amount(payment).await?;
+        }
         let tolerance = 0.01;
 
         if (total_payments - total_due).abs() > tolerance && total_payments < total_due {
@@ -546,6 +722,8 @@ impl PaymentService {
                     let cust_uuid = customer_uuid.ok_or_else(|| {
                         anyhow::anyhow!("Customer required for store credit payment")
                     })?;
+                    // Rejects store credit tendered in a foreign currency
+                    self.resolve_tender(&payment_request).await?;
                     self.process_store_credit_payment_with_tx(
                         tx,
                         transaction_uuid,
@@ -554,6 +732,19 @@ impl PaymentService {
                     )
                     .await?
                 }
+                PaymentMethodType::LoyaltyPoints => {
+                    let cust_uuid = customer_uuid.ok_or_else(|| {
+                        anyhow::anyhow!("Customer required for loyalty points payment")
+                    })?;
+                    self.resolve_tender(&payment_request).await?;
+                    self.process_loyalty_payment_with_tx(
+                        tx,
+                        transaction_uuid,
+                        cust_uuid,
+                        payment_request.amount,
+                    )
+                    .await?
+                }
                 _ => {
                     self.record_payment_with_tx(tx, transaction_uuid, payment_request)
                         .await?
@@ -1,3 +1,5 @@
+use crate::core::money::format_amount;
+use crate::core::Currency;
 use crate::errors::Result;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -177,13 +179,20 @@ impl EscPosBuilder {
     }
 
     /// Print a receipt line item
-    pub fn line_item(mut self, name: &str, price: f64, qty: i32, width: usize) -> Self {
+    pub fn line_item(
+        mut self,
+        name: &str,
+        price: f64,
+        qty: i32,
+        width: usize,
+        currency: Currency,
+    ) -> Self {
         let qty_str = if qty > 1 {
             format!("{}x", qty)
         } else {
             String::new()
         };
-        let price_str = format!("${:.2}", price);
+        let price_str = escpos_amount(price, currency);
         let name_width = width - price_str.len() - qty_str.len() - 2;
         let name_truncated = if name.len() > name_width {
             &name[..name_width]
@@ -353,6 +362,7 @@ impl PrinterService {
         tax: f64,
         total: f64,
         payment_info: &str,
+        currency: Currency,
     ) -> Vec<u8> {
         const LINE_WIDTH: usize = 42; // Standard 80mm thermal = ~42 chars
 
@@ -374,18 +384,18 @@ impl PrinterService {
         // Add items
         let mut builder = builder;
         for (name, price, qty) in items {
-            builder = builder.line_item(name, *price, *qty, LINE_WIDTH);
+            builder = builder.line_item(name, *price, *qty, LINE_WIDTH, currency);
         }
 
         builder
             .horizontal_line(LINE_WIDTH)
             .align(Alignment::Right)
-            .text(&format!("Subtotal: ${:.2}", subtotal))
+            .text(&format!("Subtotal: {}", escpos_amount(subtotal, currency)))
             .newline()
-            .text(&format!("Tax: ${:.2}", tax))
+            .text(&format!("Tax: {}", escpos_amount(tax, currency)))
             .newline()
             .bold(true)
-            .text(&format!("TOTAL: ${:.2}", total))
+            .text(&format!("TOTAL: {}", escpos_amount(total, currency)))
             .newline()
             .bold(false)
             .newline()
@@ -406,6 +416,7 @@ impl PrinterService {
         product_name: &str,
         price: f64,
         barcode_data: &str,
+        currency: Currency,
     ) -> Vec<u8> {
         // For label printers (like Zebra), actual implementation would use ZPL
         // This is a simplified ESC/POS version for thermal label printers
@@ -417,7 +428,7 @@ impl PrinterService {
             .newline()
             .bold(false)
             .double_size(true)
-            .text(&format!("${:.2}", price))
+            .text(&escpos_amount(price, currency))
             .double_size(false)
             .newline()
             .text(barcode_data) // Would use GS k for actual barcode
@@ -426,6 +437,87 @@ impl PrinterService {
             .cut()
             .build()
     }
+
+    /// Round pairings for posting at the tournament table
+    pub fn generate_pairings_escpos(
+        &self,
+        event_name: &str,
+        round: &crate::events::TournamentRound,
+    ) -> Vec<u8> {
+        const LINE_WIDTH: usize = 42;
+
+        let mut builder = EscPosBuilder::new()
+            .init()
+            .align(Alignment::Center)
+            .bold(true)
+            .text(event_name)
+            .newline()
+            .bold(false)
+            .text(&format!("Round {} Pairings", round.round_number))
+            .newline();
+        if let Some(ends_at) = round.ends_at {
+            builder = builder
+                .text(&format!("Time ends {}", ends_at.format("%H:%M UTC")))
+                .newline();
+        }
+        builder = builder.horizontal_line(LINE_WIDTH).align(Alignment::Left);
+
+        for m in &round.matches {
+            let line = match &m.player2_name {
+                Some(opponent) => {
+                    format!("{:>3}  {} vs {}", m.table_number, m.player1_name, opponent)
+                }
+                None => format!("BYE  {}", m.player1_name),
+            };
+            builder = builder.text(&line).newline();
+        }
+
+        builder.feed(3).cut().build()
+    }
+
+    /// Standings with match points and tiebreakers
+    pub fn generate_standings_escpos(
+        &self,
+        event_name: &str,
+        standings: &[crate::events::StandingsEntry],
+    ) -> Vec<u8> {
+        const LINE_WIDTH: usize = 42;
+        const NAME_WIDTH: usize = 14;
+
+        let mut builder = EscPosBuilder::new()
+            .init()
+            .align(Alignment::Center)
+            .bold(true)
+            .text(event_name)
+            .newline()
+            .bold(false)
+            .text("Standings")
+            .newline()
+            .horizontal_line(LINE_WIDTH)
+            .align(Alignment::Left)
+            .text("  # Player         Pts  OMW%  GW%  OGW%")
+            .newline();
+
+        for entry in standings {
+            let name: String = entry.name.chars().take(NAME_WIDTH).collect();
+            let dropped = if entry.dropped { " (d)" } else { "" };
+            builder = builder
+                .text(&format!(
+                    "{:>3} {:<width$} {:>3} {:>5.1} {:>4.1} {:>5.1}{}",
+                    entry.rank,
+                    name,
+                    entry.match_points,
+                    entry.omw_pct * 100.0,
+                    entry.gw_pct * 100.0,
+                    entry.ogw_pct * 100.0,
+                    dropped,
+                    width = NAME_WIDTH
+                ))
+                .newline();
+        }
+
+        builder.feed(3).cut().build()
+    }
 }
 
 impl Default for EscPosBuilder {
@@ -439,3 +531,18 @@ impl Default for PrinterService {
         Self::new()
     }
 }
+
+/// Format an amount for a thermal printer. The default ESC/POS code page has
+/// no euro/pound glyphs, so only dollar currencies keep their symbol.
+fn escpos_amount(amount: f64, currency: Currency) -> String {
+    if currency.symbol() == Some("$") {
+        format_amount(amount, currency)
+    } else {
+        format!(
+            "{:.*} {}",
+            currency.minor_units() as usize,
+            amount,
+            currency
+        )
+    }
+}
@@ -0,0 +1,1110 @@
+//! Purchasing and receiving
+//!
+//! Purchase orders against `Suppliers`, suggested orders from reorder points
+//! and recent sales velocity, and receiving into `Local_Inventory`. Received
+//! stock is costed at its landed cost (unit cost plus a value-weighted share
+//! of the order's shipping and other charges) and folded into the existing
+//! pile's `cost_basis` as a weighted average.
+
+use crate::core::money::round_cents;
+use crate::core::{Condition, VariantType};
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
+use crate::database::Database;
+use crate::errors::Result;
+use crate::services::replenishment::{ForecastOptions, ReplenishmentService};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Location tag received stock is put away to unless the receipt names one
+pub const RECEIVING_LOCATION: &str = "Receiving";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PurchaseOrderStatus {
+    Draft,
+    Ordered,
+    PartiallyReceived,
+    Received,
+    /// Closed short; anything outstanding will not arrive
+    Closed,
+    Cancelled,
+}
+
+impl std::fmt::Display for PurchaseOrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PurchaseOrderStatus::Draft => write!(f, "Draft"),
+            PurchaseOrderStatus::Ordered => write!(f, "Ordered"),
+            PurchaseOrderStatus::PartiallyReceived => write!(f, "PartiallyReceived"),
+            PurchaseOrderStatus::Received => write!(f, "Received"),
+            PurchaseOrderStatus::Closed => write!(f, "Closed"),
+            PurchaseOrderStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl PurchaseOrderStatus {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Draft" => Some(PurchaseOrderStatus::Draft),
+            "Ordered" => Some(PurchaseOrderStatus::Ordered),
+            "PartiallyReceived" => Some(PurchaseOrderStatus::PartiallyReceived),
+            "Received" => Some(PurchaseOrderStatus::Received),
+            "Closed" => Some(PurchaseOrderStatus::Closed),
+            "Cancelled" => Some(PurchaseOrderStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// Orders whose outstanding quantities still count as on order
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            PurchaseOrderStatus::Draft
+                | PurchaseOrderStatus::Ordered
+                | PurchaseOrderStatus::PartiallyReceived
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Supplier {
+    pub supplier_uuid: Uuid,
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub payment_terms: Option<String>,
+    pub notes: Option<String>,
+    pub lead_time_days: i64,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSupplierRequest {
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub payment_terms: Option<String>,
+    pub notes: Option<String>,
+    pub lead_time_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurchaseOrderLine {
+    pub line_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub product_name: Option<String>,
+    pub condition: Condition,
+    pub variant_type: Option<VariantType>,
+    pub quantity_ordered: i32,
+    pub quantity_received: i32,
+    pub quantity_backordered: i32,
+    pub unit_cost: f64,
+    /// Unit cost including this line's share of shipping and other charges
+    pub landed_unit_cost: f64,
+    pub backorder_expected_date: Option<DateTime<Utc>>,
+}
+
+impl PurchaseOrderLine {
+    pub fn quantity_outstanding(&self) -> i32 {
+        (self.quantity_ordered - self.quantity_received).max(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurchaseOrder {
+    pub po_uuid: Uuid,
+    pub po_number: String,
+    pub supplier_uuid: Uuid,
+    pub supplier_name: Option<String>,
+    pub status: PurchaseOrderStatus,
+    pub shipping_cost: f64,
+    pub other_costs: f64,
+    pub expected_date: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub ordered_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub lines: Vec<PurchaseOrderLine>,
+    pub subtotal: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PurchaseOrderLineRequest {
+    pub product_uuid: Uuid,
+    #[serde(default = "default_condition")]
+    pub condition: Condition,
+    #[serde(default)]
+    pub variant_type: Option<VariantType>,
+    pub quantity: i32,
+    pub unit_cost: f64,
+}
+
+fn default_condition() -> Condition {
+    Condition::NM
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreatePurchaseOrderRequest {
+    pub supplier_uuid: Uuid,
+    pub lines: Vec<PurchaseOrderLineRequest>,
+    #[serde(default)]
+    pub shipping_cost: f64,
+    #[serde(default)]
+    pub other_costs: f64,
+    pub expected_date: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceiveLineRequest {
+    pub line_uuid: Uuid,
+    pub quantity: i32,
+    /// Supplier reports the rest of this line as backordered
+    #[serde(default)]
+    pub backordered: bool,
+    pub backorder_expected_date: Option<DateTime<Utc>>,
+    /// Where the units are put away; defaults to the receiving area
+    pub location_tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurchaseOrderReceipt {
+    pub receipt_uuid: Uuid,
+    pub po_uuid: Uuid,
+    pub line_uuid: Uuid,
+    pub inventory_uuid: Uuid,
+    pub quantity: i32,
+    pub unit_cost: f64,
+    pub landed_unit_cost: f64,
+    /// Pile's weighted-average cost after this receipt
+    pub cost_basis: f64,
+    pub received_by: Option<Uuid>,
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceivingResult {
+    pub purchase_order: PurchaseOrder,
+    pub receipts: Vec<PurchaseOrderReceipt>,
+}
+
+/// Outstanding quantity the supplier has reported as backordered
+#[derive(Debug, Clone, Serialize)]
+pub struct Backorder {
+    pub po_uuid: Uuid,
+    pub po_number: String,
+    pub supplier_uuid: Uuid,
+    pub line: PurchaseOrderLine,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SuggestedOrderLine {
+    pub product_uuid: Uuid,
+    pub product_name: Option<String>,
+    pub condition: Condition,
+    pub on_hand: i32,
+    pub on_order: i32,
+    pub reorder_point: i32,
+    /// Average units sold per day over the lookback window
+    pub daily_velocity: f64,
+    /// Velocity adjusted for seasonality and upcoming events
+    pub forecast_daily_demand: f64,
+    pub suggested_quantity: i32,
+    pub unit_cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SuggestedPurchaseOrder {
+    pub supplier_uuid: Uuid,
+    pub supplier_name: String,
+    pub lead_time_days: i64,
+    pub lines: Vec<SuggestedOrderLine>,
+    pub estimated_total: f64,
+}
+
+/// Weighted-average unit cost after adding `quantity` units at `unit_cost`
+/// to a pile of `on_hand` units. An uncosted pile takes the new cost.
+pub fn weighted_average_cost(
+    on_hand: i32,
+    current_cost: Option<f64>,
+    quantity: i32,
+    unit_cost: f64,
+) -> f64 {
+    let on_hand = on_hand.max(0) as f64;
+    let current = current_cost.unwrap_or(unit_cost);
+    let total = on_hand + quantity as f64;
+    if total <= 0.0 {
+        return unit_cost;
+    }
+    round_cents((on_hand * current + quantity as f64 * unit_cost) / total)
+}
+
+/// Quantity to order for one product given stock position and velocity.
+///
+/// Reorders once on-hand plus on-order falls to the greater of the reorder
+/// point and expected demand over the supplier's lead time, then orders
+/// enough to cover lead time plus `cover_days` of sales (at least twice the
+/// reorder point), capped at `max_stock_level` when one is set.
+pub fn suggested_quantity(
+    on_hand: i32,
+    on_order: i32,
+    reorder_point: i32,
+    max_stock_level: Option<i32>,
+    daily_velocity: f64,
+    lead_time_days: i64,
+    cover_days: i64,
+) -> i32 {
+    let position = on_hand + on_order;
+    let lead_time_demand = (daily_velocity * lead_time_days as f64).ceil() as i32;
+    let trigger = reorder_point.max(lead_time_demand);
+    if position > trigger {
+        return 0;
+    }
+
+    let cover_demand = (daily_velocity * (lead_time_days + cover_days) as f64).ceil() as i32;
+    let mut target = cover_demand.max(reorder_point * 2).max(trigger + 1);
+    if let Some(max) = max_stock_level {
+        target = target.min(max);
+    }
+    (target - position).max(0)
+}
+
+fn parse_condition(s: &str) -> Condition {
+    serde_json::from_value(serde_json::Value::String(s.to_string())).unwrap_or(Condition::NM)
+}
+
+pub struct PurchasingService {
+    db: Arc<Database>,
+}
+
+impl PurchasingService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    // ---- Suppliers ----
+
+    pub async fn create_supplier(&self, request: CreateSupplierRequest) -> Result<Supplier> {
+        if request.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Supplier name is required"));
+        }
+        let lead_time_days = request.lead_time_days.unwrap_or(7);
+        if lead_time_days < 0 {
+            return Err(anyhow::anyhow!("Lead time cannot be negative"));
+        }
+
+        let supplier = Supplier {
+            supplier_uuid: Uuid::new_v4(),
+            name: request.name,
+            contact_name: request.contact_name,
+            email: request.email,
+            phone: request.phone,
+            address: request.address,
+            payment_terms: request.payment_terms,
+            notes: request.notes,
+            lead_time_days,
+            is_active: true,
+            created_at: Utc::now(),
+        };
+
+        sqlx::query(
+            "INSERT INTO Suppliers (supplier_uuid, name, contact_name, email, phone, address, payment_terms, notes, lead_time_days, is_active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?)",
+        )
+        .bind(supplier.supplier_uuid.to_string())
+        .bind(&supplier.name)
+        .bind(&supplier.contact_name)
+        .bind(&supplier.email)
+        .bind(&supplier.phone)
+        .bind(&supplier.address)
+        .bind(&supplier.payment_terms)
+        .bind(&supplier.notes)
+        .bind(supplier.lead_time_days)
+        .bind(supplier.created_at.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(supplier)
+    }
+
+    pub async fn get_suppliers(&self) -> Result<Vec<Supplier>> {
+        let rows = sqlx::query("SELECT * FROM Suppliers WHERE is_active = 1 ORDER BY name")
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().filter_map(map_supplier).collect())
+    }
+
+    pub async fn get_supplier(&self, supplier_uuid: Uuid) -> Result<Option<Supplier>> {
+        let row = sqlx::query("SELECT * FROM Suppliers WHERE supplier_uuid = ?")
+            .bind(supplier_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(row.as_ref().and_then(map_supplier))
+    }
+
+    // ---- Purchase orders ----
+
+    pub async fn create_purchase_order(
+        &self,
+        request: CreatePurchaseOrderRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<PurchaseOrder> {
+        if request.lines.is_empty() {
+            return Err(anyhow::anyhow!("Purchase order needs at least one line"));
+        }
+        if request.shipping_cost < 0.0 || request.other_costs < 0.0 {
+            return Err(anyhow::anyhow!(
+                "Shipping and other costs cannot be negative"
+            ));
+        }
+        for line in &request.lines {
+            if line.quantity <= 0 {
+                return Err(anyhow::anyhow!("Line quantities must be at least 1"));
+            }
+            if line.unit_cost < 0.0 {
+                return Err(anyhow::anyhow!("Unit cost cannot be negative"));
+            }
+        }
+        self.get_supplier(request.supplier_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Supplier {} not found", request.supplier_uuid))?;
+
+        let po_uuid = Uuid::new_v4();
+        let po_number = format!("PO-{}", &po_uuid.to_string()[..8].to_uppercase());
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        sqlx::query(
+            "INSERT INTO Purchase_Orders (po_uuid, po_number, supplier_uuid, status, shipping_cost, other_costs, expected_date, notes, created_by, created_at)
+             VALUES (?, ?, ?, 'Draft', ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(po_uuid.to_string())
+        .bind(&po_number)
+        .bind(request.supplier_uuid.to_string())
+        .bind(request.shipping_cost)
+        .bind(request.other_costs)
+        .bind(request.expected_date.map(|d| d.to_rfc3339()))
+        .bind(&request.notes)
+        .bind(created_by.map(|u| u.to_string()))
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create purchase order: {}", e))?;
+
+        for line in &request.lines {
+            sqlx::query(
+                "INSERT INTO Purchase_Order_Lines (line_uuid, po_uuid, product_uuid, condition, variant_type, quantity_ordered, unit_cost)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(po_uuid.to_string())
+            .bind(line.product_uuid.to_string())
+            .bind(format!("{:?}", line.condition))
+            .bind(line.variant_type.as_ref().map(|v| format!("{:?}", v)))
+            .bind(line.quantity)
+            .bind(line.unit_cost)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to add line: {}", e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.get_purchase_order(po_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Purchase order {} not found", po_uuid))
+    }
+
+    pub async fn get_purchase_order(&self, po_uuid: Uuid) -> Result<Option<PurchaseOrder>> {
+        let row = sqlx::query(
+            "SELECT po.*, s.name as supplier_name FROM Purchase_Orders po
+             LEFT JOIN Suppliers s ON s.supplier_uuid = po.supplier_uuid
+             WHERE po.po_uuid = ?",
+        )
+        .bind(po_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        match row {
+            Some(row) => Ok(Some(self.load_order(&row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_purchase_orders(
+        &self,
+        status: Option<PurchaseOrderStatus>,
+        supplier_uuid: Option<Uuid>,
+    ) -> Result<Vec<PurchaseOrder>> {
+        let rows = sqlx::query(
+            "SELECT po.*, s.name as supplier_name FROM Purchase_Orders po
+             LEFT JOIN Suppliers s ON s.supplier_uuid = po.supplier_uuid
+             WHERE (? IS NULL OR po.status = ?) AND (? IS NULL OR po.supplier_uuid = ?)
+             ORDER BY po.created_at DESC",
+        )
+        .bind(status.map(|s| s.to_string()))
+        .bind(status.map(|s| s.to_string()))
+        .bind(supplier_uuid.map(|u| u.to_string()))
+        .bind(supplier_uuid.map(|u| u.to_string()))
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut orders = Vec::with_capacity(rows.len());
+        for row in &rows {
+            orders.push(self.load_order(row).await?);
+        }
+        Ok(orders)
+    }
+
+    /// Send a draft order to the supplier
+    pub async fn submit_purchase_order(&self, po_uuid: Uuid) -> Result<PurchaseOrder> {
+        self.transition(
+            po_uuid,
+            &[PurchaseOrderStatus::Draft],
+            PurchaseOrderStatus::Ordered,
+            "ordered_at",
+        )
+        .await
+    }
+
+    /// Cancel an order before anything has been received
+    pub async fn cancel_purchase_order(&self, po_uuid: Uuid) -> Result<PurchaseOrder> {
+        self.transition(
+            po_uuid,
+            &[PurchaseOrderStatus::Draft, PurchaseOrderStatus::Ordered],
+            PurchaseOrderStatus::Cancelled,
+            "closed_at",
+        )
+        .await
+    }
+
+    /// Close a partially received order; outstanding quantities are dropped
+    pub async fn close_purchase_order(&self, po_uuid: Uuid) -> Result<PurchaseOrder> {
+        self.transition(
+            po_uuid,
+            &[PurchaseOrderStatus::PartiallyReceived],
+            PurchaseOrderStatus::Closed,
+            "closed_at",
+        )
+        .await
+    }
+
+    async fn transition(
+        &self,
+        po_uuid: Uuid,
+        from: &[PurchaseOrderStatus],
+        to: PurchaseOrderStatus,
+        timestamp_column: &str,
+    ) -> Result<PurchaseOrder> {
+        let order = self
+            .get_purchase_order(po_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Purchase order {} not found", po_uuid))?;
+        if !from.contains(&order.status) {
+            return Err(anyhow::anyhow!(
+                "Purchase order {} is {} and cannot be marked {}",
+                order.po_number,
+                order.status,
+                to
+            ));
+        }
+
+        // Column name comes from the fixed set above, never from input
+        sqlx::query(&format!(
+            "UPDATE Purchase_Orders SET status = ?, {} = ? WHERE po_uuid = ?",
+            timestamp_column
+        ))
+        .bind(to.to_string())
+        .bind(Utc::now().to_rfc3339())
+        .bind(po_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        self.get_purchase_order(po_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Purchase order {} not found", po_uuid))
+    }
+
+    // ---- Receiving ----
+
+    /// Receive stock against an ordered PO. Each received line increments the
+    /// matching bulk pile (or creates one) and re-averages its cost basis.
+    pub async fn receive(
+        &self,
+        po_uuid: Uuid,
+        lines: Vec<ReceiveLineRequest>,
+        received_by: Option<Uuid>,
+    ) -> Result<ReceivingResult> {
+        let order = self
+            .get_purchase_order(po_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Purchase order {} not found", po_uuid))?;
+        if !matches!(
+            order.status,
+            PurchaseOrderStatus::Ordered | PurchaseOrderStatus::PartiallyReceived
+        ) {
+            return Err(anyhow::anyhow!(
+                "Purchase order {} is {} and cannot be received",
+                order.po_number,
+                order.status
+            ));
+        }
+        if lines.is_empty() {
+            return Err(anyhow::anyhow!("Nothing to receive"));
+        }
+
+        let now = Utc::now();
+        let mut receipts = Vec::new();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        for request in &lines {
+            let line = order
+                .lines
+                .iter()
+                .find(|l| l.line_uuid == request.line_uuid)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Line {} is not on this order", request.line_uuid)
+                })?;
+            if request.quantity < 0 || request.quantity > line.quantity_outstanding() {
+                return Err(anyhow::anyhow!(
+                    "Cannot receive {} of line {}: {} outstanding",
+                    request.quantity,
+                    line.line_uuid,
+                    line.quantity_outstanding()
+                ));
+            }
+
+            if request.quantity > 0 {
+                let (inventory_uuid, cost_basis) = receive_into_inventory_with_tx(
+                    &mut tx,
+                    line,
+                    request.quantity,
+                    request
+                        .location_tag
+                        .as_deref()
+                        .unwrap_or(RECEIVING_LOCATION),
+                    order.supplier_uuid,
+                    now,
+                    &MovementSource::new(
+                        MovementType::Receive,
+                        Some(po_uuid),
+                        received_by,
+                        &self.db.node_id,
+                    ),
+                )
+                .await?;
+
+                let receipt = PurchaseOrderReceipt {
+                    receipt_uuid: Uuid::new_v4(),
+                    po_uuid,
+                    line_uuid: line.line_uuid,
+                    inventory_uuid,
+                    quantity: request.quantity,
+                    unit_cost: line.unit_cost,
+                    landed_unit_cost: line.landed_unit_cost,
+                    cost_basis,
+                    received_by,
+                    received_at: now,
+                };
+                sqlx::query(
+                    "INSERT INTO Purchase_Order_Receipts (receipt_uuid, po_uuid, line_uuid, inventory_uuid, quantity, unit_cost, landed_unit_cost, received_by, received_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(receipt.receipt_uuid.to_string())
+                .bind(po_uuid.to_string())
+                .bind(line.line_uuid.to_string())
+                .bind(inventory_uuid.to_string())
+                .bind(receipt.quantity)
+                .bind(receipt.unit_cost)
+                .bind(receipt.landed_unit_cost)
+                .bind(received_by.map(|u| u.to_string()))
+                .bind(now.to_rfc3339())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to record receipt: {}", e))?;
+                receipts.push(receipt);
+            }
+
+            // Whatever is still outstanding is either backordered or just late
+            let outstanding = line.quantity_outstanding() - request.quantity;
+            let backordered = if request.backordered { outstanding } else { 0 };
+            sqlx::query(
+                "UPDATE Purchase_Order_Lines
+                 SET quantity_received = quantity_received + ?, quantity_backordered = ?, backorder_expected_date = ?
+                 WHERE line_uuid = ?",
+            )
+            .bind(request.quantity)
+            .bind(backordered)
+            .bind(
+                request
+                    .backorder_expected_date
+                    .filter(|_| backordered > 0)
+                    .map(|d| d.to_rfc3339()),
+            )
+            .bind(line.line_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to update line: {}", e))?;
+        }
+
+        let outstanding: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(quantity_ordered - quantity_received), 0) FROM Purchase_Order_Lines WHERE po_uuid = ?",
+        )
+        .bind(po_uuid.to_string())
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let (status, closed_at) = if outstanding <= 0 {
+            (PurchaseOrderStatus::Received, Some(now.to_rfc3339()))
+        } else {
+            (PurchaseOrderStatus::PartiallyReceived, None)
+        };
+        sqlx::query("UPDATE Purchase_Orders SET status = ?, closed_at = ? WHERE po_uuid = ?")
+            .bind(status.to_string())
+            .bind(closed_at)
+            .bind(po_uuid.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        tracing::info!(
+            "Received {} units against {} ({})",
+            receipts.iter().map(|r| r.quantity).sum::<i32>(),
+            order.po_number,
+            status
+        );
+
+        let purchase_order = self
+            .get_purchase_order(po_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Purchase order {} not found", po_uuid))?;
+        Ok(ReceivingResult {
+            purchase_order,
+            receipts,
+        })
+    }
+
+    pub async fn get_receipts(&self, po_uuid: Uuid) -> Result<Vec<PurchaseOrderReceipt>> {
+        let rows = sqlx::query(
+            "SELECT r.*, li.cost_basis FROM Purchase_Order_Receipts r
+             LEFT JOIN Local_Inventory li ON li.inventory_uuid = r.inventory_uuid
+             WHERE r.po_uuid = ? ORDER BY r.received_at",
+        )
+        .bind(po_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().filter_map(map_receipt).collect())
+    }
+
+    /// Lines on open orders that the supplier has backordered
+    pub async fn get_backorders(&self) -> Result<Vec<Backorder>> {
+        let orders = self.get_purchase_orders(None, None).await?;
+        Ok(orders
+            .into_iter()
+            .filter(|po| po.status.is_open())
+            .flat_map(|po| {
+                let (po_uuid, po_number, supplier_uuid) =
+                    (po.po_uuid, po.po_number.clone(), po.supplier_uuid);
+                po.lines
+                    .into_iter()
+                    .filter(|l| l.quantity_backordered > 0)
+                    .map(move |line| Backorder {
+                        po_uuid,
+                        po_number: po_number.clone(),
+                        supplier_uuid,
+                        line,
+                    })
+            })
+            .collect())
+    }
+
+    // ---- Suggestions ----
+
+    /// Suggested orders from the replenishment forecast, grouped by the
+    /// supplier on the inventory rows. Velocity is averaged over the last
+    /// `lookback_days` of sales and adjusted for seasonality and upcoming
+    /// events; orders cover lead time plus `cover_days`.
+    pub async fn suggest_purchase_orders(
+        &self,
+        lookback_days: i64,
+        cover_days: i64,
+    ) -> Result<Vec<SuggestedPurchaseOrder>> {
+        let forecasts = ReplenishmentService::new(self.db.clone())
+            .get_reorder_suggestions(ForecastOptions {
+                lookback_days,
+                cover_days,
+                ..ForecastOptions::default()
+            })
+            .await?;
+
+        let mut by_supplier: BTreeMap<Uuid, SuggestedPurchaseOrder> = BTreeMap::new();
+        for forecast in forecasts {
+            let Some(supplier_uuid) = forecast.supplier_uuid else {
+                continue;
+            };
+            let suggestion = match by_supplier.entry(supplier_uuid) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let Some(supplier) = self
+                        .get_supplier(supplier_uuid)
+                        .await?
+                        .filter(|s| s.is_active)
+                    else {
+                        continue;
+                    };
+                    entry.insert(SuggestedPurchaseOrder {
+                        supplier_uuid: supplier.supplier_uuid,
+                        supplier_name: supplier.name,
+                        lead_time_days: supplier.lead_time_days,
+                        lines: Vec::new(),
+                        estimated_total: 0.0,
+                    })
+                }
+            };
+
+            suggestion.estimated_total = round_cents(
+                suggestion.estimated_total
+                    + forecast.unit_cost.unwrap_or(0.0) * forecast.suggested_quantity as f64,
+            );
+            suggestion.lines.push(SuggestedOrderLine {
+                product_uuid: forecast.product_uuid,
+                product_name: forecast.product_name,
+                condition: parse_condition(&forecast.condition),
+                on_hand: forecast.on_hand,
+                on_order: forecast.on_order,
+                reorder_point: forecast.reorder_point.unwrap_or(forecast.min_stock_level),
+                daily_velocity: forecast.daily_velocity,
+                forecast_daily_demand: forecast.forecast_daily_demand,
+                suggested_quantity: forecast.suggested_quantity,
+                unit_cost: forecast.unit_cost,
+            });
+        }
+
+        Ok(by_supplier
+            .into_values()
+            .filter(|s| !s.lines.is_empty())
+            .collect())
+    }
+
+    /// Turn current suggestions into draft purchase orders, one per supplier
+    pub async fn create_suggested_drafts(
+        &self,
+        lookback_days: i64,
+        cover_days: i64,
+        created_by: Option<Uuid>,
+    ) -> Result<Vec<PurchaseOrder>> {
+        let suggestions = self
+            .suggest_purchase_orders(lookback_days, cover_days)
+            .await?;
+
+        let mut drafts = Vec::with_capacity(suggestions.len());
+        for suggestion in suggestions {
+            let request = CreatePurchaseOrderRequest {
+                supplier_uuid: suggestion.supplier_uuid,
+                lines: suggestion
+                    .lines
+                    .iter()
+                    .map(|l| PurchaseOrderLineRequest {
+                        product_uuid: l.product_uuid,
+                        condition: l.condition.clone(),
+                        variant_type: None,
+                        quantity: l.suggested_quantity,
+                        unit_cost: l.unit_cost.unwrap_or(0.0),
+                    })
+                    .collect(),
+                shipping_cost: 0.0,
+                other_costs: 0.0,
+                expected_date: Some(Utc::now() + Duration::days(suggestion.lead_time_days)),
+                notes: Some("Generated from reorder suggestions".to_string()),
+            };
+            drafts.push(self.create_purchase_order(request, created_by).await?);
+        }
+        Ok(drafts)
+    }
+
+    async fn load_order(&self, row: &sqlx::sqlite::SqliteRow) -> Result<PurchaseOrder> {
+        let po_uuid: String = sqlx::Row::try_get(row, "po_uuid")
+            .map_err(|e| anyhow::anyhow!("Missing po_uuid: {}", e))?;
+        let status: String = sqlx::Row::try_get(row, "status").unwrap_or_default();
+        let shipping_cost: f64 = sqlx::Row::try_get(row, "shipping_cost").unwrap_or(0.0);
+        let other_costs: f64 = sqlx::Row::try_get(row, "other_costs").unwrap_or(0.0);
+
+        let line_rows = sqlx::query(
+            "SELECT l.*, gc.name as product_name FROM Purchase_Order_Lines l
+             LEFT JOIN Global_Catalog gc ON gc.product_uuid = l.product_uuid
+             WHERE l.po_uuid = ? ORDER BY gc.name, l.line_uuid",
+        )
+        .bind(&po_uuid)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let mut lines: Vec<PurchaseOrderLine> = line_rows.iter().filter_map(map_line).collect();
+
+        // Allocate shipping and other charges by each line's share of order value
+        let subtotal = round_cents(
+            lines
+                .iter()
+                .map(|l| l.unit_cost * l.quantity_ordered as f64)
+                .sum(),
+        );
+        let overhead = shipping_cost + other_costs;
+        for line in &mut lines {
+            line.landed_unit_cost = if subtotal > 0.0 {
+                (line.unit_cost * (1.0 + overhead / subtotal) * 10000.0).round() / 10000.0
+            } else {
+                line.unit_cost
+            };
+        }
+
+        Ok(PurchaseOrder {
+            po_uuid: Uuid::parse_str(&po_uuid)?,
+            po_number: sqlx::Row::try_get(row, "po_number").unwrap_or_default(),
+            supplier_uuid: parse_uuid(row, "supplier_uuid")
+                .ok_or_else(|| anyhow::anyhow!("Purchase order has no supplier"))?,
+            supplier_name: sqlx::Row::try_get(row, "supplier_name").ok().flatten(),
+            status: PurchaseOrderStatus::parse(&status)
+                .ok_or_else(|| anyhow::anyhow!("Unknown purchase order status '{}'", status))?,
+            shipping_cost,
+            other_costs,
+            expected_date: parse_date(row, "expected_date"),
+            notes: sqlx::Row::try_get(row, "notes").ok().flatten(),
+            created_by: parse_uuid(row, "created_by"),
+            created_at: parse_date(row, "created_at").unwrap_or_else(Utc::now),
+            ordered_at: parse_date(row, "ordered_at"),
+            closed_at: parse_date(row, "closed_at"),
+            lines,
+            subtotal,
+            total: round_cents(subtotal + overhead),
+        })
+    }
+}
+
+/// Add received units to the bulk pile of the same product, condition and
+/// variant at the put-away location (or a new row there) and re-average its
+/// cost. Returns the inventory row and its new cost basis.
+async fn receive_into_inventory_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    line: &PurchaseOrderLine,
+    quantity: i32,
+    location_tag: &str,
+    supplier_uuid: Uuid,
+    received_at: DateTime<Utc>,
+    movement: &MovementSource,
+) -> Result<(Uuid, f64)> {
+    let condition = format!("{:?}", line.condition);
+    let variant = line.variant_type.as_ref().map(|v| format!("{:?}", v));
+    let existing: Option<(String, i64, Option<f64>)> = sqlx::query_as(
+        "SELECT inventory_uuid, quantity_on_hand, cost_basis FROM Local_Inventory
+         WHERE product_uuid = ? AND condition = ?
+           AND COALESCE(NULLIF(variant_type, 'Normal'), '') = COALESCE(NULLIF(?, 'Normal'), '')
+           AND location_tag = ? AND serialized_details IS NULL
+           AND specific_price IS NULL AND deleted_at IS NULL
+         ORDER BY inventory_uuid ASC LIMIT 1",
+    )
+    .bind(line.product_uuid.to_string())
+    .bind(&condition)
+    .bind(&variant)
+    .bind(location_tag)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    match existing {
+        Some((inventory_uuid, on_hand, cost_basis)) => {
+            let new_cost =
+                weighted_average_cost(on_hand as i32, cost_basis, quantity, line.landed_unit_cost);
+            sqlx::query(
+                "UPDATE Local_Inventory SET cost_basis = ?, supplier_uuid = ?, received_date = ?
+                 WHERE inventory_uuid = ?",
+            )
+            .bind(new_cost)
+            .bind(supplier_uuid.to_string())
+            .bind(received_at.to_rfc3339())
+            .bind(&inventory_uuid)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to update inventory: {}", e))?;
+            let inventory_uuid = Uuid::parse_str(&inventory_uuid)?;
+            movements::adjust_quantity_with_tx(tx, inventory_uuid, quantity, movement).await?;
+            Ok((inventory_uuid, new_cost))
+        }
+        None => {
+            let inventory_uuid = Uuid::new_v4();
+            let cost = round_cents(line.landed_unit_cost);
+            sqlx::query(
+                "INSERT INTO Local_Inventory
+                 (inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, cost_basis, supplier_uuid, received_date)
+                 VALUES (?, ?, ?, ?, 0, ?, ?, ?, ?)",
+            )
+            .bind(inventory_uuid.to_string())
+            .bind(line.product_uuid.to_string())
+            .bind(&variant)
+            .bind(&condition)
+            .bind(location_tag)
+            .bind(cost)
+            .bind(supplier_uuid.to_string())
+            .bind(received_at.to_rfc3339())
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create inventory: {}", e))?;
+            movements::adjust_quantity_with_tx(tx, inventory_uuid, quantity, movement).await?;
+            Ok((inventory_uuid, cost))
+        }
+    }
+}
+
+fn parse_date(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<DateTime<Utc>> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn parse_uuid(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<Uuid> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| Uuid::parse_str(&s).ok())
+}
+
+fn map_supplier(row: &sqlx::sqlite::SqliteRow) -> Option<Supplier> {
+    Some(Supplier {
+        supplier_uuid: parse_uuid(row, "supplier_uuid")?,
+        name: sqlx::Row::try_get(row, "name").ok()?,
+        contact_name: sqlx::Row::try_get(row, "contact_name").ok().flatten(),
+        email: sqlx::Row::try_get(row, "email").ok().flatten(),
+        phone: sqlx::Row::try_get(row, "phone").ok().flatten(),
+        address: sqlx::Row::try_get(row, "address").ok().flatten(),
+        payment_terms: sqlx::Row::try_get(row, "payment_terms").ok().flatten(),
+        notes: sqlx::Row::try_get(row, "notes").ok().flatten(),
+        lead_time_days: sqlx::Row::try_get::<Option<i64>, _>(row, "lead_time_days")
+            .ok()
+            .flatten()
+            .unwrap_or(7),
+        is_active: sqlx::Row::try_get::<i64, _>(row, "is_active").unwrap_or(1) != 0,
+        created_at: parse_date(row, "created_at").unwrap_or_else(Utc::now),
+    })
+}
+
+fn map_line(row: &sqlx::sqlite::SqliteRow) -> Option<PurchaseOrderLine> {
+    let condition: String = sqlx::Row::try_get(row, "condition").unwrap_or_default();
+    let unit_cost: f64 = sqlx::Row::try_get(row, "unit_cost").unwrap_or(0.0);
+    Some(PurchaseOrderLine {
+        line_uuid: parse_uuid(row, "line_uuid")?,
+        product_uuid: parse_uuid(row, "product_uuid")?,
+        product_name: sqlx::Row::try_get(row, "product_name").ok().flatten(),
+        condition: parse_condition(&condition),
+        variant_type: sqlx::Row::try_get::<Option<String>, _>(row, "variant_type")
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_value(serde_json::Value::String(v)).ok()),
+        quantity_ordered: sqlx::Row::try_get(row, "quantity_ordered").unwrap_or(0),
+        quantity_received: sqlx::Row::try_get(row, "quantity_received").unwrap_or(0),
+        quantity_backordered: sqlx::Row::try_get(row, "quantity_backordered").unwrap_or(0),
+        unit_cost,
+        landed_unit_cost: unit_cost,
+        backorder_expected_date: parse_date(row, "backorder_expected_date"),
+    })
+}
+
+fn map_receipt(row: &sqlx::sqlite::SqliteRow) -> Option<PurchaseOrderReceipt> {
+    Some(PurchaseOrderReceipt {
+        receipt_uuid: parse_uuid(row, "receipt_uuid")?,
+        po_uuid: parse_uuid(row, "po_uuid")?,
+        line_uuid: parse_uuid(row, "line_uuid")?,
+        inventory_uuid: parse_uuid(row, "inventory_uuid")?,
+        quantity: sqlx::Row::try_get(row, "quantity").unwrap_or(0),
+        unit_cost: sqlx::Row::try_get(row, "unit_cost").unwrap_or(0.0),
+        landed_unit_cost: sqlx::Row::try_get(row, "landed_unit_cost").unwrap_or(0.0),
+        cost_basis: sqlx::Row::try_get::<Option<f64>, _>(row, "cost_basis")
+            .ok()
+            .flatten()
+            .unwrap_or(0.0),
+        received_by: parse_uuid(row, "received_by"),
+        received_at: parse_date(row, "received_at").unwrap_or_else(Utc::now),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_average_cost() {
+        // 10 @ $2.00 + 10 @ $3.00
+        assert_eq!(weighted_average_cost(10, Some(2.0), 10, 3.0), 2.5);
+        // Uncosted or empty piles take the incoming cost
+        assert_eq!(weighted_average_cost(5, None, 5, 4.0), 4.0);
+        assert_eq!(weighted_average_cost(0, Some(9.0), 3, 4.0), 4.0);
+    }
+
+    #[test]
+    fn test_suggested_quantity() {
+        // Above reorder point with no sales: nothing to order
+        assert_eq!(suggested_quantity(10, 0, 5, None, 0.0, 7, 30), 0);
+        // At reorder point with no sales: restock to twice the reorder point
+        assert_eq!(suggested_quantity(5, 0, 5, None, 0.0, 7, 30), 5);
+        // 1/day over 7 days lead time triggers above the reorder point,
+        // then orders 37 days of cover less what is on hand and on order
+        assert_eq!(suggested_quantity(6, 1, 2, None, 1.0, 7, 30), 30);
+        // Capped by max stock level
+        assert_eq!(suggested_quantity(6, 1, 2, Some(20), 1.0, 7, 30), 13);
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in [
+            PurchaseOrderStatus::Draft,
+            PurchaseOrderStatus::Ordered,
+            PurchaseOrderStatus::PartiallyReceived,
+            PurchaseOrderStatus::Received,
+            PurchaseOrderStatus::Closed,
+            PurchaseOrderStatus::Cancelled,
+        ] {
+            assert_eq!(
+                PurchaseOrderStatus::parse(&status.to_string()),
+                Some(status)
+            );
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use crate::config::Config;
+use crate::core::money::format_amount;
+use crate::core::Currency;
 use crate::database::Database;
+use crate::services::CurrencyService;
 use anyhow::Result;
 use serde::Serialize;
 use sqlx::Row;
@@ -20,6 +23,7 @@ pub struct ReceiptData {
     pub total: f64,
     pub customer_name: Option<String>,
     pub payments: Vec<ReceiptPayment>, // New field
+    pub currency: Currency,
 }
 
 #[derive(Debug, Serialize)]
@@ -35,6 +39,8 @@ pub struct ReceiptPayment {
     pub method: String,
     pub amount: f64,
     pub reference: Option<String>,
+    /// Original tender for foreign currency payments, e.g. "€20.00"
+    pub tendered: Option<String>,
 }
 
 pub struct ReceiptService {
@@ -102,7 +108,7 @@ impl ReceiptService {
 
         // Query Payments (Task 104)
         let payment_rows = sqlx::query(
-            "SELECT method_type, amount, reference FROM Payment_Methods WHERE transaction_uuid = ?",
+            "SELECT method_type, amount, reference, tendered_currency, tendered_amount FROM Payment_Methods WHERE transaction_uuid = ?",
         )
         .bind(transaction_uuid)
         .fetch_all(&self.db.pool)
@@ -114,14 +120,32 @@ impl ReceiptService {
                 let method: String = r.try_get("method_type").unwrap_or("Unknown".to_string());
                 let amount: f64 = r.try_get("amount").unwrap_or(0.0);
                 let reference: Option<String> = r.try_get("reference").ok();
+                let tendered = r
+                    .try_get::<Option<String>, _>("tendered_currency")
+                    .ok()
+                    .flatten()
+                    .and_then(|code| Currency::from_code(&code))
+                    .map(|currency| {
+                        let tendered_amount: f64 = r
+                            .try_get::<Option<f64>, _>("tendered_amount")
+                            .ok()
+                            .flatten()
+                            .unwrap_or(0.0);
+                        format_amount(tendered_amount, currency)
+                    });
                 ReceiptPayment {
                     method,
                     amount,
                     reference,
+                    tendered,
                 }
             })
             .collect();
 
+        let currency = CurrencyService::new(self.db.clone())
+            .transaction_currency(transaction_uuid)
+            .await?;
+
         Ok(ReceiptData {
             store_name: self.config.store_name.clone(),
             store_address: self.config.store_address.clone(),
@@ -135,6 +159,7 @@ impl ReceiptService {
             total,
             customer_name,
             payments,
+            currency,
         })
     }
 
@@ -145,8 +170,10 @@ impl ReceiptService {
         let mut items_html = String::new();
         for item in &data.items {
             items_html.push_str(&format!(
-                "<tr><td>{}</td><td align='right'>{}</td><td align='right'>${:.2}</td></tr>",
-                item.name, item.quantity, item.total
+                "<tr><td>{}</td><td align='right'>{}</td><td align='right'>{}</td></tr>",
+                item.name,
+                item.quantity,
+                format_amount(item.total, data.currency)
             ));
         }
 
@@ -155,14 +182,18 @@ impl ReceiptService {
         if !data.payments.is_empty() {
             payments_html.push_str("<tr><td colspan='2' style='padding-top: 5px;'><strong>Payment Method:</strong></td></tr>");
             for payment in &data.payments {
-                let method_display = if let Some(ref r) = payment.reference {
+                let mut method_display = if let Some(ref r) = payment.reference {
                     format!("{} ({})", payment.method, r)
                 } else {
                     payment.method.clone()
                 };
+                if let Some(ref tendered) = payment.tendered {
+                    method_display.push_str(&format!(" [{} tendered]", tendered));
+                }
                 payments_html.push_str(&format!(
-                    "<tr><td>{}</td><td align='right'>${:.2}</td></tr>",
-                    method_display, payment.amount
+                    "<tr><td>{}</td><td align='right'>{}</td></tr>",
+                    method_display,
+                    format_amount(payment.amount, data.currency)
                 ));
             }
         }
@@ -211,9 +242,9 @@ impl ReceiptService {
     
     <div class="total-section">
         <table>
-            <tr><td>Subtotal:</td><td align='right'>${:.2}</td></tr>
-            <tr><td>Tax:</td><td align='right'>${:.2}</td></tr>
-            <tr><td><strong>Total:</strong></td><td align='right'><strong>${:.2}</strong></td></tr>
+            <tr><td>Subtotal:</td><td align='right'>{}</td></tr>
+            <tr><td>Tax:</td><td align='right'>{}</td></tr>
+            <tr><td><strong>Total:</strong></td><td align='right'><strong>{}</strong></td></tr>
         </table>
     </div>
 
@@ -237,9 +268,9 @@ impl ReceiptService {
             data.transaction_id,
             data.date,
             items_html,
-            data.subtotal,
-            data.tax_amount,
-            data.total,
+            format_amount(data.subtotal, data.currency),
+            format_amount(data.tax_amount, data.currency),
+            format_amount(data.total, data.currency),
             payments_html
         );
         Ok(html)
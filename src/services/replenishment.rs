@@ -0,0 +1,631 @@
+//! Replenishment and demand forecasting
+//!
+//! Forecasts daily demand per product and condition from sales history:
+//! a base velocity over a recent lookback window, scaled by last year's
+//! seasonality for the coming period, plus the extra units that past events
+//! of each type (prereleases, launch weekends) pulled in whenever one is
+//! scheduled inside the order horizon. Reorder quantities use each item's
+//! `reorder_point` (or `min_stock_level`) and `max_stock_level` against
+//! the supplier's lead time; stock that hasn't sold in a long while is
+//! flagged as dead.
+
+use crate::core::money::round_cents;
+use crate::database::Database;
+use crate::services::purchasing::suggested_quantity;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Lead time assumed when an item has no supplier
+pub const DEFAULT_LEAD_TIME_DAYS: i64 = 7;
+
+/// Days around an event that count towards its demand: the day before
+/// through the day after
+const EVENT_WINDOW_DAYS: i64 = 3;
+
+/// Units sold over the trailing year before seasonality is trusted
+const MIN_SEASONAL_UNITS: i64 = 12;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ForecastOptions {
+    /// Days of sales averaged into the base velocity
+    pub lookback_days: i64,
+    /// Days of demand to cover beyond the lead time
+    pub cover_days: i64,
+    /// Days without a sale before stock counts as dead
+    pub dead_stock_days: i64,
+}
+
+impl Default for ForecastOptions {
+    fn default() -> Self {
+        Self {
+            lookback_days: 90,
+            cover_days: 30,
+            dead_stock_days: 180,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StockStatus {
+    /// At or below the reorder trigger
+    Reorder,
+    Healthy,
+    /// Above `max_stock_level`
+    Overstocked,
+    /// In stock with no sale within the dead stock window
+    Dead,
+}
+
+impl std::fmt::Display for StockStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            StockStatus::Reorder => "reorder",
+            StockStatus::Healthy => "healthy",
+            StockStatus::Overstocked => "overstocked",
+            StockStatus::Dead => "dead",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl StockStatus {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "reorder" => Some(StockStatus::Reorder),
+            "healthy" => Some(StockStatus::Healthy),
+            "overstocked" => Some(StockStatus::Overstocked),
+            "dead" => Some(StockStatus::Dead),
+            _ => None,
+        }
+    }
+}
+
+/// An upcoming event expected to lift demand for a product
+#[derive(Debug, Clone, Serialize)]
+pub struct EventDemand {
+    pub event_uuid: Uuid,
+    pub name: String,
+    pub event_type: String,
+    pub date: DateTime<Utc>,
+    /// Units above normal sales that past events of this type sold
+    pub extra_units: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductForecast {
+    pub product_uuid: Uuid,
+    pub product_name: Option<String>,
+    pub condition: String,
+    pub on_hand: i32,
+    pub on_order: i32,
+    pub min_stock_level: i32,
+    pub reorder_point: Option<i32>,
+    pub max_stock_level: Option<i32>,
+    pub supplier_uuid: Option<Uuid>,
+    pub supplier_name: Option<String>,
+    pub lead_time_days: i64,
+    pub unit_cost: Option<f64>,
+    /// Units per day over the lookback window
+    pub daily_velocity: f64,
+    /// Last year's rate for the coming period against its yearly average
+    pub seasonal_factor: f64,
+    pub upcoming_events: Vec<EventDemand>,
+    /// Expected units per day over lead time and cover, events included
+    pub forecast_daily_demand: f64,
+    /// On hand divided by forecast demand; none when nothing is selling
+    pub days_of_cover: Option<f64>,
+    pub last_sold: Option<NaiveDate>,
+    pub suggested_quantity: i32,
+    pub status: StockStatus,
+}
+
+/// A pile that hasn't sold within the dead stock window
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadStockItem {
+    pub inventory_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub product_name: Option<String>,
+    pub condition: String,
+    pub location_tag: String,
+    pub quantity_on_hand: i32,
+    pub cost_basis: Option<f64>,
+    /// Quantity at cost, the money tied up
+    pub value_at_cost: f64,
+    pub last_sold: Option<DateTime<Utc>>,
+    pub received_date: Option<DateTime<Utc>>,
+}
+
+/// Headline numbers for the dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplenishmentSummary {
+    pub reorder_count: i64,
+    /// Out of stock with demand still forecast
+    pub stockout_count: i64,
+    pub overstocked_count: i64,
+    pub dead_stock_count: i64,
+    pub dead_stock_value: f64,
+    /// At cost, for everything suggested
+    pub suggested_order_value: f64,
+}
+
+/// Last year's demand for the coming period relative to that year's
+/// average. 1.0 without enough history; clamped to 0.5–3.0 so one odd
+/// week can't swamp the forecast.
+pub fn seasonal_factor(
+    period_units: i64,
+    period_days: i64,
+    year_units: i64,
+    has_full_year: bool,
+) -> f64 {
+    if !has_full_year || year_units < MIN_SEASONAL_UNITS || period_days <= 0 {
+        return 1.0;
+    }
+    let period_rate = period_units as f64 / period_days as f64;
+    let year_rate = year_units as f64 / 365.0;
+    (period_rate / year_rate).clamp(0.5, 3.0)
+}
+
+/// Extra units one event brings beyond normal sales, from how past events
+/// of the same type sold: the window rate above the rate outside windows,
+/// over one window
+pub fn event_extra_units(
+    window_units: i64,
+    window_days: i64,
+    other_units: i64,
+    other_days: i64,
+) -> f64 {
+    if window_days <= 0 {
+        return 0.0;
+    }
+    let window_rate = window_units as f64 / window_days as f64;
+    let other_rate = if other_days > 0 {
+        other_units as f64 / other_days as f64
+    } else {
+        0.0
+    };
+    ((window_rate - other_rate) * EVENT_WINDOW_DAYS as f64).max(0.0)
+}
+
+fn parse_date(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+fn event_window(date: DateTime<Utc>) -> impl Iterator<Item = NaiveDate> {
+    let start = date.date_naive() - Duration::days(1);
+    (0..EVENT_WINDOW_DAYS).map(move |i| start + Duration::days(i))
+}
+
+struct ScheduledEvent {
+    event_uuid: Uuid,
+    name: String,
+    event_type: String,
+    date: DateTime<Utc>,
+}
+
+pub struct ReplenishmentService {
+    db: Arc<Database>,
+}
+
+impl ReplenishmentService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Forecast every stocked or recently sold product and condition
+    pub async fn get_forecasts(&self, options: ForecastOptions) -> Result<Vec<ProductForecast>> {
+        let lookback_days = options.lookback_days.max(1);
+        let since = (Utc::now() - Duration::days(lookback_days)).to_rfc3339();
+        let keys: Vec<(String, String)> = sqlx::query_as(
+            "SELECT product_uuid, condition FROM Local_Inventory WHERE deleted_at IS NULL
+             UNION
+             SELECT ti.product_uuid, ti.condition FROM Transaction_Items ti
+             JOIN Transactions t ON t.transaction_uuid = ti.transaction_uuid
+             WHERE t.transaction_type = 'Sale' AND t.voided_at IS NULL AND t.timestamp >= ?",
+        )
+        .bind(&since)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let events = self.load_events().await?;
+        let mut forecasts = Vec::with_capacity(keys.len());
+        for (product_uuid, condition) in keys {
+            forecasts.push(
+                self.forecast_one(&product_uuid, &condition, &options, &events)
+                    .await?,
+            );
+        }
+        forecasts.sort_by(|a, b| {
+            b.suggested_quantity
+                .cmp(&a.suggested_quantity)
+                .then_with(|| a.product_name.cmp(&b.product_name))
+        });
+        Ok(forecasts)
+    }
+
+    /// Forecasts for one product, one per condition
+    pub async fn forecast_product(
+        &self,
+        product_uuid: Uuid,
+        options: ForecastOptions,
+    ) -> Result<Vec<ProductForecast>> {
+        let conditions: Vec<String> = sqlx::query_scalar(
+            "SELECT condition FROM Local_Inventory WHERE product_uuid = ? AND deleted_at IS NULL
+             UNION
+             SELECT condition FROM Transaction_Items WHERE product_uuid = ?",
+        )
+        .bind(product_uuid.to_string())
+        .bind(product_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let events = self.load_events().await?;
+        let mut forecasts = Vec::with_capacity(conditions.len());
+        for condition in conditions {
+            forecasts.push(
+                self.forecast_one(&product_uuid.to_string(), &condition, &options, &events)
+                    .await?,
+            );
+        }
+        Ok(forecasts)
+    }
+
+    /// Forecasts that need ordering now
+    pub async fn get_reorder_suggestions(
+        &self,
+        options: ForecastOptions,
+    ) -> Result<Vec<ProductForecast>> {
+        Ok(self
+            .get_forecasts(options)
+            .await?
+            .into_iter()
+            .filter(|f| f.suggested_quantity > 0)
+            .collect())
+    }
+
+    /// Piles in stock that haven't sold in `days` days and weren't received
+    /// within that time either, most money tied up first
+    pub async fn get_dead_stock(&self, days: i64) -> Result<Vec<DeadStockItem>> {
+        let cutoff = (Utc::now() - Duration::days(days.max(1))).to_rfc3339();
+        let rows = sqlx::query(
+            "SELECT li.inventory_uuid, li.product_uuid, gc.name, li.condition, li.location_tag,
+                    li.quantity_on_hand, li.cost_basis, li.received_date,
+                    (SELECT MAX(t.timestamp) FROM Transaction_Items ti
+                     JOIN Transactions t ON t.transaction_uuid = ti.transaction_uuid
+                     WHERE ti.product_uuid = li.product_uuid AND ti.condition = li.condition
+                       AND t.transaction_type = 'Sale' AND t.voided_at IS NULL) AS last_sold
+             FROM Local_Inventory li
+             LEFT JOIN Global_Catalog gc ON gc.product_uuid = li.product_uuid
+             WHERE li.quantity_on_hand > 0 AND li.deleted_at IS NULL
+               AND (li.received_date IS NULL OR li.received_date < ?)",
+        )
+        .bind(&cutoff)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut items = Vec::new();
+        for row in rows {
+            let last_sold: Option<String> = row.try_get("last_sold")?;
+            if last_sold.as_deref().is_some_and(|s| s >= cutoff.as_str()) {
+                continue;
+            }
+            let quantity_on_hand: i32 = row.try_get("quantity_on_hand")?;
+            let cost_basis: Option<f64> = row.try_get("cost_basis")?;
+            items.push(DeadStockItem {
+                inventory_uuid: Uuid::parse_str(&row.try_get::<String, _>("inventory_uuid")?)?,
+                product_uuid: Uuid::parse_str(&row.try_get::<String, _>("product_uuid")?)?,
+                product_name: row.try_get("name")?,
+                condition: row.try_get("condition")?,
+                location_tag: row.try_get("location_tag")?,
+                quantity_on_hand,
+                cost_basis,
+                value_at_cost: round_cents(cost_basis.unwrap_or(0.0) * quantity_on_hand as f64),
+                last_sold: last_sold.as_deref().and_then(parse_date),
+                received_date: row
+                    .try_get::<Option<String>, _>("received_date")?
+                    .as_deref()
+                    .and_then(parse_date),
+            });
+        }
+        items.sort_by(|a, b| b.value_at_cost.total_cmp(&a.value_at_cost));
+        Ok(items)
+    }
+
+    pub async fn get_summary(&self, options: ForecastOptions) -> Result<ReplenishmentSummary> {
+        let forecasts = self.get_forecasts(options).await?;
+        let dead = self.get_dead_stock(options.dead_stock_days).await?;
+
+        let count = |status: StockStatus| forecasts.iter().filter(|f| f.status == status).count();
+        Ok(ReplenishmentSummary {
+            reorder_count: count(StockStatus::Reorder) as i64,
+            stockout_count: forecasts
+                .iter()
+                .filter(|f| f.on_hand <= 0 && f.forecast_daily_demand > 0.0)
+                .count() as i64,
+            overstocked_count: count(StockStatus::Overstocked) as i64,
+            dead_stock_count: dead.len() as i64,
+            dead_stock_value: round_cents(dead.iter().map(|d| d.value_at_cost).sum()),
+            suggested_order_value: round_cents(
+                forecasts
+                    .iter()
+                    .map(|f| f.unit_cost.unwrap_or(0.0) * f.suggested_quantity as f64)
+                    .sum(),
+            ),
+        })
+    }
+
+    /// Events from the past year and the year ahead, cancelled ones left out
+    async fn load_events(&self) -> Result<Vec<ScheduledEvent>> {
+        let rows: Vec<(String, String, String, String)> = sqlx::query_as(
+            "SELECT event_uuid, name, event_type, date FROM Events
+             WHERE COALESCE(status, 'Scheduled') != 'Cancelled' AND date >= ? AND date <= ?",
+        )
+        .bind((Utc::now() - Duration::days(365)).to_rfc3339())
+        .bind((Utc::now() + Duration::days(365)).to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(event_uuid, name, event_type, date)| {
+                Some(ScheduledEvent {
+                    event_uuid: Uuid::parse_str(&event_uuid).ok()?,
+                    name,
+                    event_type,
+                    date: parse_date(&date)?,
+                })
+            })
+            .collect())
+    }
+
+    async fn forecast_one(
+        &self,
+        product_uuid: &str,
+        condition: &str,
+        options: &ForecastOptions,
+        events: &[ScheduledEvent],
+    ) -> Result<ProductForecast> {
+        let now = Utc::now();
+        let today = now.date_naive();
+
+        let stock = sqlx::query(
+            "SELECT COALESCE(SUM(li.quantity_on_hand), 0) AS on_hand,
+                    COALESCE(MAX(li.min_stock_level), 0) AS min_stock_level,
+                    MAX(li.reorder_point) AS reorder_point,
+                    MAX(li.max_stock_level) AS max_stock_level,
+                    MAX(li.cost_basis) AS cost_basis,
+                    (SELECT li2.supplier_uuid FROM Local_Inventory li2
+                     WHERE li2.product_uuid = ? AND li2.condition = ? AND li2.supplier_uuid IS NOT NULL
+                     ORDER BY li2.received_date DESC LIMIT 1) AS supplier_uuid,
+                    (SELECT name FROM Global_Catalog WHERE product_uuid = ?) AS product_name
+             FROM Local_Inventory li
+             WHERE li.product_uuid = ? AND li.condition = ? AND li.deleted_at IS NULL",
+        )
+        .bind(product_uuid)
+        .bind(condition)
+        .bind(product_uuid)
+        .bind(product_uuid)
+        .bind(condition)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let on_hand: i64 = stock.try_get("on_hand")?;
+        let min_stock_level: i64 = stock.try_get("min_stock_level")?;
+        let reorder_point: Option<i64> = stock.try_get("reorder_point")?;
+        let max_stock_level: Option<i64> = stock.try_get("max_stock_level")?;
+        let supplier_uuid: Option<String> = stock.try_get("supplier_uuid")?;
+
+        let supplier: Option<(String, i64)> = match &supplier_uuid {
+            Some(uuid) => sqlx::query_as(
+                "SELECT name, COALESCE(lead_time_days, 7) FROM Suppliers WHERE supplier_uuid = ?",
+            )
+            .bind(uuid)
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?,
+            None => None,
+        };
+        let lead_time_days = supplier
+            .as_ref()
+            .map(|(_, days)| *days)
+            .unwrap_or(DEFAULT_LEAD_TIME_DAYS);
+
+        let on_order: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(l.quantity_ordered - l.quantity_received), 0)
+             FROM Purchase_Order_Lines l JOIN Purchase_Orders po ON po.po_uuid = l.po_uuid
+             WHERE po.status IN ('Draft', 'Ordered', 'PartiallyReceived')
+               AND l.product_uuid = ? AND l.condition = ?",
+        )
+        .bind(product_uuid)
+        .bind(condition)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        // Two years of daily sales covers seasonality and event history
+        let daily: HashMap<NaiveDate, i64> = sqlx::query_as::<_, (String, i64)>(
+            "SELECT substr(t.timestamp, 1, 10) AS day, SUM(ti.quantity)
+             FROM Transaction_Items ti
+             JOIN Transactions t ON t.transaction_uuid = ti.transaction_uuid
+             WHERE t.transaction_type = 'Sale' AND t.voided_at IS NULL
+               AND ti.product_uuid = ? AND ti.condition = ? AND t.timestamp >= ?
+             GROUP BY day",
+        )
+        .bind(product_uuid)
+        .bind(condition)
+        .bind((now - Duration::days(730)).to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        .into_iter()
+        .filter_map(|(day, units)| Some((NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok()?, units)))
+        .collect();
+        let last_sold_ever: Option<String> = sqlx::query_scalar(
+            "SELECT MAX(t.timestamp) FROM Transaction_Items ti
+             JOIN Transactions t ON t.transaction_uuid = ti.transaction_uuid
+             WHERE t.transaction_type = 'Sale' AND t.voided_at IS NULL
+               AND ti.product_uuid = ? AND ti.condition = ?",
+        )
+        .bind(product_uuid)
+        .bind(condition)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let units_between = |from: NaiveDate, to: NaiveDate| -> i64 {
+            daily
+                .iter()
+                .filter(|(day, _)| **day >= from && **day < to)
+                .map(|(_, units)| units)
+                .sum()
+        };
+
+        let lookback_days = options.lookback_days.max(1);
+        let daily_velocity = units_between(
+            today - Duration::days(lookback_days - 1),
+            today + Duration::days(1),
+        ) as f64
+            / lookback_days as f64;
+
+        let horizon_days = lead_time_days + options.cover_days.max(0);
+        let year_ago = today - Duration::days(365);
+        let seasonal = seasonal_factor(
+            units_between(year_ago, year_ago + Duration::days(horizon_days.max(1))),
+            horizon_days.max(1),
+            units_between(year_ago, today),
+            daily.keys().any(|day| *day < year_ago),
+        );
+
+        // How much each event type lifted this product over the past year
+        let mut windows_by_type: BTreeMap<&str, BTreeSet<NaiveDate>> = BTreeMap::new();
+        for event in events.iter().filter(|e| e.date < now) {
+            windows_by_type
+                .entry(event.event_type.as_str())
+                .or_default()
+                .extend(event_window(event.date).filter(|day| *day >= year_ago));
+        }
+        let year_units = units_between(year_ago, today);
+        let mut upcoming_events = Vec::new();
+        let horizon_end = now + Duration::days(horizon_days);
+        for event in events
+            .iter()
+            .filter(|e| e.date >= now && e.date <= horizon_end)
+        {
+            let Some(window) = windows_by_type.get(event.event_type.as_str()) else {
+                continue;
+            };
+            let window_units: i64 = window.iter().filter_map(|day| daily.get(day)).sum();
+            let extra = event_extra_units(
+                window_units,
+                window.len() as i64,
+                year_units - window_units,
+                365 - window.len() as i64,
+            );
+            if extra > 0.0 {
+                upcoming_events.push(EventDemand {
+                    event_uuid: event.event_uuid,
+                    name: event.name.clone(),
+                    event_type: event.event_type.clone(),
+                    date: event.date,
+                    extra_units: round_cents(extra),
+                });
+            }
+        }
+        let event_units: f64 = upcoming_events.iter().map(|e| e.extra_units).sum();
+        let forecast_daily_demand =
+            daily_velocity * seasonal + event_units / horizon_days.max(1) as f64;
+
+        let trigger = reorder_point.unwrap_or(min_stock_level) as i32;
+        let suggested = suggested_quantity(
+            on_hand as i32,
+            on_order as i32,
+            trigger,
+            max_stock_level.map(|m| m as i32),
+            forecast_daily_demand,
+            lead_time_days,
+            options.cover_days,
+        );
+        // Nothing to reorder for lines that have neither settings nor sales
+        let suggested = if trigger <= 0 && forecast_daily_demand <= 0.0 {
+            0
+        } else {
+            suggested
+        };
+
+        let last_sold = last_sold_ever
+            .as_deref()
+            .and_then(parse_date)
+            .map(|d| d.date_naive());
+        let dead_cutoff = today - Duration::days(options.dead_stock_days.max(1));
+        let status = if suggested > 0 {
+            StockStatus::Reorder
+        } else if on_hand > 0 && last_sold.is_none_or(|d| d < dead_cutoff) {
+            StockStatus::Dead
+        } else if max_stock_level.is_some_and(|max| on_hand > max) {
+            StockStatus::Overstocked
+        } else {
+            StockStatus::Healthy
+        };
+
+        Ok(ProductForecast {
+            product_uuid: Uuid::parse_str(product_uuid)?,
+            product_name: stock.try_get("product_name")?,
+            condition: condition.to_string(),
+            on_hand: on_hand as i32,
+            on_order: on_order as i32,
+            min_stock_level: min_stock_level as i32,
+            reorder_point: reorder_point.map(|p| p as i32),
+            max_stock_level: max_stock_level.map(|m| m as i32),
+            supplier_uuid: supplier_uuid.and_then(|s| Uuid::parse_str(&s).ok()),
+            supplier_name: supplier.map(|(name, _)| name),
+            lead_time_days,
+            unit_cost: stock.try_get("cost_basis")?,
+            daily_velocity: round_cents(daily_velocity),
+            seasonal_factor: round_cents(seasonal),
+            upcoming_events,
+            forecast_daily_demand: round_cents(forecast_daily_demand),
+            days_of_cover: (forecast_daily_demand > 0.0)
+                .then(|| round_cents(on_hand.max(0) as f64 / forecast_daily_demand)),
+            last_sold,
+            suggested_quantity: suggested,
+            status,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seasonal_factor_needs_history_and_is_clamped() {
+        // Not a full year yet
+        assert_eq!(seasonal_factor(30, 30, 365, false), 1.0);
+        // Too few sales to read a season from
+        assert_eq!(seasonal_factor(5, 30, 8, true), 1.0);
+        // Sold twice the yearly rate in this period last year
+        assert_eq!(seasonal_factor(60, 30, 365, true), 2.0);
+        assert_eq!(seasonal_factor(300, 30, 365, true), 3.0);
+        assert_eq!(seasonal_factor(0, 30, 365, true), 0.5);
+    }
+
+    #[test]
+    fn test_event_extra_units_is_lift_over_normal_rate() {
+        // 12 units over a 3-day window against 1/day otherwise: 9 extra
+        assert_eq!(event_extra_units(12, 3, 362, 362), 9.0);
+        // Events that sell no better than usual add nothing
+        assert_eq!(event_extra_units(3, 3, 362, 362), 0.0);
+        assert_eq!(event_extra_units(0, 0, 10, 10), 0.0);
+    }
+}
@@ -18,6 +18,13 @@ pub struct SalesReport {
     pub sales_by_category: HashMap<String, f64>,
     pub sales_by_payment_method: HashMap<String, f64>,
     pub sales_by_day: Vec<serde_json::Value>,
+    /// Post-sale adjustments made during the period, whenever the sale was
+    /// rung up. The figures above are sales as rung up in the period.
+    #[serde(default)]
+    pub adjustments: crate::services::AdjustmentSummary,
+    /// Sales rung up in the period plus adjustments made in it
+    #[serde(default)]
+    pub net_sales: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -55,6 +62,8 @@ pub struct AgingBucket {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ZReport {
     pub shift_details: crate::services::cash_drawer::Shift,
+    /// Sales rung up during the shift, plus adjustments made during it to
+    /// any sale
     pub sales_report: SalesReport,
 }
 
@@ -121,6 +130,14 @@ impl ReportingService {
             }));
         }
 
+        let adjustments =
+            crate::services::adjustment::adjustment_summary(&self.db, start, end).await?;
+
+        // Sales figures are pre-tax; so is the adjustment to them
+        let net_sales = crate::core::money::round_cents(
+            report_data.total_sales + adjustments.net_amount - adjustments.tax_amount,
+        );
+
         Ok(SalesReport {
             period,
             total_sales: report_data.total_sales,
@@ -130,6 +147,8 @@ impl ReportingService {
             sales_by_category: category_data,
             sales_by_payment_method: payment_data,
             sales_by_day: Vec::new(), // Placeholder for future implementation
+            adjustments,
+            net_sales,
         })
     }
 
@@ -201,6 +220,15 @@ impl ReportingService {
             low_stock_count,
         })
     }
+    /// Damage and loss for the period by reason, category, employee and source
+    pub async fn get_shrink_report(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<crate::services::ShrinkReport> {
+        crate::services::shrinkage::shrink_report(&self.db, start, end).await
+    }
+
     pub async fn get_inventory_aging_report(&self) -> Result<InventoryAgingReport> {
         let raw_data = self.db.inventory.get_inventory_aging().await?;
 
@@ -1,3 +1,4 @@
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
 use crate::database::Database;
 use anyhow::{bail, Context, Result};
 use chrono::{DateTime, Duration, Utc};
@@ -140,6 +141,7 @@ impl ReturnsService {
         // Validate the original transaction
         let transaction = self.validate_transaction(request.transaction_uuid).await?;
 
+        let return_uuid = Uuid::new_v4();
         let mut returned_items = Vec::new();
         let mut subtotal = 0.0;
         let mut total_restocking = 0.0;
@@ -184,8 +186,13 @@ impl ReturnsService {
 
             // Return item to inventory if applicable
             if returned_to_inventory {
-                self.restore_inventory(item_req.inventory_uuid, item_req.quantity)
-                    .await?;
+                self.restore_inventory(
+                    return_uuid,
+                    request.transaction_uuid,
+                    item_req.inventory_uuid,
+                    item_req.quantity,
+                )
+                .await?;
             }
 
             returned_items.push(ReturnedItem {
@@ -206,8 +213,6 @@ impl ReturnsService {
             .check_approval_required(request.customer_uuid, refund_amount, &request.reason_code)
             .await?;
 
-        let return_uuid = Uuid::new_v4();
-
         // Record the return
         self.record_return(
             return_uuid,
@@ -219,6 +224,18 @@ impl ReturnsService {
         )
         .await?;
 
+        self.record_unsellable_returns(return_uuid, &request, &returned_items)
+            .await?;
+
+        // Returned goods don't keep the loyalty points they earned
+        crate::services::loyalty::reverse_for_return(
+            &self.db,
+            return_uuid,
+            request.transaction_uuid,
+            subtotal,
+        )
+        .await?;
+
         Ok(ReturnResult {
             return_uuid,
             transaction_uuid: request.transaction_uuid,
@@ -236,10 +253,11 @@ impl ReturnsService {
     /// Validate that return is allowed for this transaction
     async fn validate_transaction(&self, transaction_uuid: Uuid) -> Result<TransactionInfo> {
         let row = sqlx::query(
-            "SELECT t.transaction_uuid, t.created_at, t.transaction_type, 
-                    COALESCE(pm.payment_type, 'Unknown') as payment_method
+            "SELECT t.transaction_uuid, t.timestamp as created_at, t.transaction_type,
+                    COALESCE((SELECT pm.method_type FROM Payment_Methods pm
+                              WHERE pm.transaction_uuid = t.transaction_uuid
+                              ORDER BY pm.amount DESC LIMIT 1), 'Unknown') as payment_method
              FROM Transactions t
-             LEFT JOIN Payment_Methods pm ON t.transaction_uuid = pm.transaction_uuid
              WHERE t.transaction_uuid = ?",
         )
         .bind(transaction_uuid.to_string())
@@ -278,10 +296,12 @@ impl ReturnsService {
         inventory_uuid: Uuid,
     ) -> Result<OriginalItem> {
         let row = sqlx::query(
-            "SELECT ti.quantity, ti.unit_price, p.name
+            "SELECT ti.item_uuid, ti.quantity, ti.unit_price, p.name
              FROM Transaction_Items ti
-             JOIN Products p ON ti.product_uuid = p.product_uuid
-             WHERE ti.transaction_uuid = ? AND ti.inventory_uuid = ?",
+             JOIN Local_Inventory li ON li.product_uuid = ti.product_uuid AND li.condition = ti.condition
+             JOIN Global_Catalog p ON ti.product_uuid = p.product_uuid
+             WHERE ti.transaction_uuid = ? AND li.inventory_uuid = ?
+             LIMIT 1",
         )
         .bind(transaction_uuid.to_string())
         .bind(inventory_uuid.to_string())
@@ -292,23 +312,53 @@ impl ReturnsService {
         let row = row.context("Item not found in transaction")?;
 
         use sqlx::Row;
-        Ok(OriginalItem {
+        let mut item = OriginalItem {
             quantity: row.try_get("quantity").unwrap_or(1),
             price: row.try_get("unit_price").unwrap_or(0.0),
             product_name: row.try_get("name").unwrap_or_default(),
-        })
+        };
+
+        // Price corrections and line voids made after the sale
+        let item_uuid: String = row.try_get("item_uuid").unwrap_or_default();
+        if let Some((quantity, price)) =
+            crate::services::adjustment::adjusted_line(&self.db.pool, &item_uuid).await?
+        {
+            item.quantity = quantity;
+            item.price = price;
+        }
+        Ok(item)
     }
 
-    /// Restore item to inventory
-    async fn restore_inventory(&self, inventory_uuid: Uuid, quantity: i32) -> Result<()> {
-        sqlx::query(
-            "UPDATE Inventory SET quantity_on_hand = quantity_on_hand + ? WHERE inventory_uuid = ?",
+    /// Restore item to inventory. Consigned stock goes back on consignment,
+    /// so the consignor's share of the sale is reversed with it.
+    async fn restore_inventory(
+        &self,
+        return_uuid: Uuid,
+        transaction_uuid: Uuid,
+        inventory_uuid: Uuid,
+        quantity: i32,
+    ) -> Result<()> {
+        let mut tx = self.db.pool.begin().await.context("Database error")?;
+        movements::adjust_quantity_with_tx(
+            &mut tx,
+            inventory_uuid,
+            quantity,
+            &MovementSource::new(
+                MovementType::Return,
+                Some(return_uuid),
+                None,
+                &self.db.node_id,
+            ),
         )
-        .bind(quantity)
-        .bind(inventory_uuid.to_string())
-        .execute(&self.db.pool)
-        .await
-        .context("Database error")?;
+        .await?;
+        crate::services::consignment::reverse_sale_with_tx(
+            &mut tx,
+            transaction_uuid,
+            &inventory_uuid.to_string(),
+            quantity,
+        )
+        .await?;
+        tx.commit().await.context("Database error")?;
 
         tracing::info!(
             "Restored {} units to inventory {}",
@@ -318,6 +368,54 @@ impl ReturnsService {
         Ok(())
     }
 
+    /// Items too damaged to restock are shrink: log them against their pile
+    async fn record_unsellable_returns(
+        &self,
+        return_uuid: Uuid,
+        request: &ReturnRequest,
+        items: &[ReturnedItem],
+    ) -> Result<()> {
+        use crate::services::shrinkage::{
+            record_damage_with_tx, DamageType, NewDamage, ShrinkSource,
+        };
+
+        if items.iter().all(|item| item.returned_to_inventory) {
+            return Ok(());
+        }
+
+        let mut tx = self.db.pool.begin().await.context("Database error")?;
+        for (item_req, item) in request.items.iter().zip(items) {
+            if item.returned_to_inventory {
+                continue;
+            }
+            let damage_type = if item_req.condition == ReturnCondition::Defective
+                || request.reason_code == ReturnReasonCode::Defective
+            {
+                DamageType::Defective
+            } else {
+                DamageType::Damaged
+            };
+            record_damage_with_tx(
+                &mut tx,
+                NewDamage {
+                    inventory_uuid: item.inventory_uuid,
+                    quantity: item.quantity,
+                    damage_type,
+                    description: request.reason_notes.clone(),
+                    source: ShrinkSource::Return,
+                    source_uuid: Some(return_uuid),
+                    reported_by: None,
+                    original_value: None,
+                    terminal_id: self.db.node_id.clone(),
+                },
+                false,
+            )
+            .await?;
+        }
+        tx.commit().await.context("Database error")?;
+        Ok(())
+    }
+
     /// TASK-172/173: Check if return requires manager approval
     async fn check_approval_required(
         &self,
@@ -1,4 +1,9 @@
 use crate::database::Database;
+use crate::services::cert_verification::{
+    compare_cert, normalize_cert_number, CertVerification, CertVerificationProvider, FlagKind,
+    OfficialCert, VerificationFlag, VerificationStatus,
+};
+use crate::services::grading::GradingCompany;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -8,6 +13,7 @@ use uuid::Uuid;
 /// Serialized inventory service for unique/graded items
 pub struct SerializedInventoryService {
     db: Arc<Database>,
+    cert_providers: Vec<Box<dyn CertVerificationProvider>>,
 }
 
 /// Detailed serialized item information
@@ -96,7 +102,19 @@ pub struct SerializedSearchResult {
 
 impl SerializedInventoryService {
     pub fn new(db: Arc<Database>) -> Self {
-        Self { db }
+        Self {
+            db,
+            cert_providers: Vec::new(),
+        }
+    }
+
+    /// Providers consulted, in order, when verifying certs
+    pub fn with_cert_providers(
+        mut self,
+        providers: Vec<Box<dyn CertVerificationProvider>>,
+    ) -> Self {
+        self.cert_providers = providers;
+        self
     }
 
     /// TASK-156: Get serialized details for an inventory item
@@ -106,7 +124,7 @@ impl SerializedInventoryService {
     ) -> Result<Option<SerializedItem>> {
         let row = sqlx::query(
             "SELECT i.inventory_uuid, i.product_uuid, i.specific_price, i.serialized_details, i.location_tag
-             FROM Inventory i WHERE i.inventory_uuid = ?"
+             FROM Local_Inventory i WHERE i.inventory_uuid = ?"
         )
         .bind(inventory_uuid.to_string())
         .fetch_optional(&self.db.pool)
@@ -132,10 +150,22 @@ impl SerializedInventoryService {
             let certificate = details
                 .get("certificate")
                 .and_then(|c| serde_json::from_value(c.clone()).ok());
-            let images: Vec<String> = details
+            let mut images: Vec<String> = details
                 .get("images")
                 .and_then(|i| serde_json::from_value(i.clone()).ok())
                 .unwrap_or_default();
+            // Uploaded photos come after any external links
+            for url in crate::services::media::media_urls(
+                &self.db.pool,
+                crate::services::media::MediaEntity::Inventory,
+                inventory_uuid,
+            )
+            .await?
+            {
+                if !images.contains(&url) {
+                    images.push(url);
+                }
+            }
 
             Ok(Some(SerializedItem {
                 inventory_uuid,
@@ -174,18 +204,24 @@ impl SerializedInventoryService {
         inventory_uuid: Uuid,
         item: &SerializedItem,
     ) -> Result<()> {
+        // Uploaded photos are listed from the Media table on read
+        let images: Vec<&String> = item
+            .images
+            .iter()
+            .filter(|url| !url.starts_with("/api/media/"))
+            .collect();
         let details = serde_json::json!({
             "serial_number": item.serial_number,
             "grading": item.grading,
             "certificate": item.certificate,
-            "images": item.images,
+            "images": images,
             "acquisition_cost": item.acquisition_cost,
             "acquisition_date": item.acquisition_date.map(|d| d.to_rfc3339()),
             "notes": item.notes,
         });
 
         sqlx::query(
-            "UPDATE Inventory SET serialized_details = ?, specific_price = ? WHERE inventory_uuid = ?"
+            "UPDATE Local_Inventory SET serialized_details = ?, specific_price = ? WHERE inventory_uuid = ?"
         )
         .bind(serde_json::to_string(&details).unwrap_or_default())
         .bind(item.custom_price)
@@ -252,14 +288,14 @@ impl SerializedInventoryService {
     /// TASK-159: Set individual item price
     pub async fn set_custom_price(&self, inventory_uuid: Uuid, price: f64) -> Result<()> {
         let old_price = sqlx::query_scalar::<_, Option<f64>>(
-            "SELECT specific_price FROM Inventory WHERE inventory_uuid = ?",
+            "SELECT specific_price FROM Local_Inventory WHERE inventory_uuid = ?",
         )
         .bind(inventory_uuid.to_string())
         .fetch_one(&self.db.pool)
         .await
         .context("Database error")?;
 
-        sqlx::query("UPDATE Inventory SET specific_price = ? WHERE inventory_uuid = ?")
+        sqlx::query("UPDATE Local_Inventory SET specific_price = ? WHERE inventory_uuid = ?")
             .bind(price)
             .bind(inventory_uuid.to_string())
             .execute(&self.db.pool)
@@ -287,8 +323,8 @@ impl SerializedInventoryService {
         let rows = sqlx::query(
             "SELECT i.inventory_uuid, i.location_tag, i.specific_price, i.serialized_details,
                     p.name as product_name
-             FROM Inventory i
-             JOIN Products p ON i.product_uuid = p.product_uuid
+             FROM Local_Inventory i
+             JOIN Global_Catalog p ON i.product_uuid = p.product_uuid
              WHERE i.serialized_details LIKE ?",
         )
         .bind(format!("%{}%", query))
@@ -336,6 +372,321 @@ impl SerializedInventoryService {
         Ok(results)
     }
 
+    /// Look a cert up with the first provider that covers the grader.
+    /// Returns the provider's name alongside the record.
+    pub async fn lookup_cert(
+        &self,
+        grader: GradingCompany,
+        cert_number: &str,
+    ) -> Result<(String, Option<OfficialCert>)> {
+        let provider = self
+            .cert_providers
+            .iter()
+            .find(|p| p.supports(grader))
+            .ok_or_else(|| anyhow::anyhow!("No certificate lookup is configured for {}", grader))?;
+        let record = provider.lookup(grader, cert_number).await?;
+        Ok((provider.name().to_string(), record))
+    }
+
+    /// Check an item's cert with its grader. A clean match pulls the
+    /// official grade, subgrades and population into the item and marks
+    /// the certificate verified. Any mismatch leaves the recorded grading
+    /// as the slab shows it, marks the certificate unverified and logs the
+    /// flags for review as a possible fake.
+    pub async fn verify_certificate(
+        &self,
+        inventory_uuid: Uuid,
+        verified_by: Option<Uuid>,
+    ) -> Result<CertVerification> {
+        let mut item = self
+            .get_serialized_item(inventory_uuid)
+            .await?
+            .context("Item not found")?;
+        let mut grading = item
+            .grading
+            .clone()
+            .context("Item has no grading information to verify")?;
+        let grader = GradingCompany::parse(&grading.grader)
+            .ok_or_else(|| anyhow::anyhow!("Unknown grader '{}'", grading.grader))?;
+        let cert_number = grading
+            .cert_number
+            .clone()
+            .or_else(|| {
+                item.certificate
+                    .as_ref()
+                    .and_then(|c| c.cert_number.clone())
+            })
+            .filter(|c| !c.trim().is_empty())
+            .context("Item has no cert number to verify")?;
+
+        let (provider, official) = self.lookup_cert(grader, &cert_number).await?;
+
+        let mut flags = Vec::new();
+        if let Some(official) = &official {
+            use sqlx::Row;
+            let product = sqlx::query(
+                "SELECT name, collector_number FROM Global_Catalog WHERE product_uuid = ?",
+            )
+            .bind(item.product_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .context("Database error")?;
+            let (product_name, collector_number) = match product {
+                Some(row) => (
+                    row.try_get::<String, _>("name").unwrap_or_default(),
+                    row.try_get::<Option<String>, _>("collector_number")
+                        .ok()
+                        .flatten(),
+                ),
+                None => (String::new(), None),
+            };
+            flags.extend(compare_cert(
+                official,
+                &product_name,
+                collector_number.as_deref(),
+                Some(&grading.grade),
+            ));
+        } else {
+            flags.push(VerificationFlag {
+                kind: FlagKind::CertNotFound,
+                message: format!("{} has no record of cert {}", grader, cert_number),
+            });
+        }
+        for other in self
+            .items_with_cert(grader, &cert_number, inventory_uuid)
+            .await?
+        {
+            flags.push(VerificationFlag {
+                kind: FlagKind::DuplicateCert,
+                message: format!(
+                    "{} cert {} is also on inventory {}",
+                    grader, cert_number, other
+                ),
+            });
+        }
+
+        let status = match (&official, flags.is_empty()) {
+            (None, _) => VerificationStatus::NotFound,
+            (Some(_), true) => VerificationStatus::Verified,
+            (Some(_), false) => VerificationStatus::Mismatch,
+        };
+        let verified = status == VerificationStatus::Verified;
+
+        if let (Some(official), true) = (&official, verified) {
+            grading.grade = official.grade.clone();
+            if official.sub_grades.is_some() {
+                grading.sub_grades = official.sub_grades.clone();
+            }
+            if let Some(population) = &official.population {
+                let mut population = population.clone();
+                population
+                    .last_updated
+                    .get_or_insert_with(|| Utc::now().to_rfc3339());
+                grading.population = Some(population);
+            }
+            item.grading = Some(grading);
+        }
+        let previous = item.certificate.take();
+        item.certificate = Some(CertificateInfo {
+            cert_type: previous
+                .as_ref()
+                .map(|c| c.cert_type.clone())
+                .unwrap_or_else(|| "Grading".to_string()),
+            issuer: grader.to_string(),
+            cert_number: Some(cert_number.clone()),
+            issue_date: previous.and_then(|c| c.issue_date),
+            verified,
+        });
+        self.update_serialized_item(inventory_uuid, &item).await?;
+
+        let verification = CertVerification {
+            verification_uuid: Uuid::new_v4(),
+            inventory_uuid,
+            grader,
+            cert_number: cert_number.clone(),
+            provider,
+            status,
+            flags,
+            official,
+            verified_by,
+            verified_at: Utc::now(),
+        };
+        sqlx::query(
+            "INSERT INTO Cert_Verifications
+             (verification_uuid, inventory_uuid, grader, cert_number, provider, status, flags, official, verified_by, verified_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(verification.verification_uuid.to_string())
+        .bind(inventory_uuid.to_string())
+        .bind(grader.as_str())
+        .bind(&cert_number)
+        .bind(&verification.provider)
+        .bind(status.as_str())
+        .bind(serde_json::to_string(&verification.flags).unwrap_or_default())
+        .bind(
+            verification
+                .official
+                .as_ref()
+                .and_then(|o| serde_json::to_string(o).ok()),
+        )
+        .bind(verified_by.map(|u| u.to_string()))
+        .bind(verification.verified_at.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .context("Database error")?;
+
+        let description = if verified {
+            format!("{} cert {} verified", grader, cert_number)
+        } else {
+            tracing::warn!(
+                "Cert check flagged inventory {}: {:?}",
+                inventory_uuid,
+                verification.flags
+            );
+            format!(
+                "{} cert {} flagged: {}",
+                grader,
+                cert_number,
+                verification
+                    .flags
+                    .iter()
+                    .map(|f| f.message.as_str())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            )
+        };
+        self.add_provenance_entry(
+            inventory_uuid,
+            ProvenanceEventType::Authentication,
+            description,
+            None,
+        )
+        .await?;
+
+        Ok(verification)
+    }
+
+    /// Other in-stock items carrying the same grader and cert number
+    async fn items_with_cert(
+        &self,
+        grader: GradingCompany,
+        cert_number: &str,
+        exclude: Uuid,
+    ) -> Result<Vec<Uuid>> {
+        use sqlx::Row;
+        let wanted = normalize_cert_number(cert_number);
+        let rows = sqlx::query(
+            "SELECT inventory_uuid, serialized_details FROM Local_Inventory
+             WHERE inventory_uuid != ? AND deleted_at IS NULL AND quantity_on_hand > 0
+             AND serialized_details IS NOT NULL",
+        )
+        .bind(exclude.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .context("Database error")?;
+
+        let mut matches = Vec::new();
+        for row in rows {
+            let details: Option<GradingInfo> = row
+                .try_get::<Option<String>, _>("serialized_details")
+                .ok()
+                .flatten()
+                .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+                .and_then(|d| d.get("grading").cloned())
+                .and_then(|g| serde_json::from_value(g).ok());
+            let Some(other) = details else {
+                continue;
+            };
+            let same = GradingCompany::parse(&other.grader) == Some(grader)
+                && other
+                    .cert_number
+                    .as_deref()
+                    .map(normalize_cert_number)
+                    .as_deref()
+                    == Some(wanted.as_str());
+            if same {
+                let uuid: String = row.try_get("inventory_uuid").unwrap_or_default();
+                if let Ok(uuid) = Uuid::parse_str(&uuid) {
+                    matches.push(uuid);
+                }
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Cert checks, newest first. `flagged_only` keeps each item's latest
+    /// check when it didn't verify, i.e. the slabs still awaiting review.
+    pub async fn get_cert_verifications(
+        &self,
+        inventory_uuid: Option<Uuid>,
+        flagged_only: bool,
+    ) -> Result<Vec<CertVerification>> {
+        use sqlx::Row;
+        let mut sql = String::from(
+            "SELECT verification_uuid, inventory_uuid, grader, cert_number, provider, status,
+                    flags, official, verified_by, verified_at
+             FROM Cert_Verifications v WHERE 1 = 1",
+        );
+        if inventory_uuid.is_some() {
+            sql.push_str(" AND v.inventory_uuid = ?");
+        }
+        if flagged_only {
+            sql.push_str(
+                " AND v.status != 'verified' AND v.verified_at = (
+                    SELECT MAX(v2.verified_at) FROM Cert_Verifications v2
+                    WHERE v2.inventory_uuid = v.inventory_uuid)",
+            );
+        }
+        sql.push_str(" ORDER BY v.verified_at DESC");
+
+        let mut query = sqlx::query(&sql);
+        if let Some(uuid) = inventory_uuid {
+            query = query.bind(uuid.to_string());
+        }
+        let rows = query
+            .fetch_all(&self.db.pool)
+            .await
+            .context("Database error")?;
+
+        let mut verifications = Vec::new();
+        for row in rows {
+            let uuid_col = |col: &str| {
+                row.try_get::<Option<String>, _>(col)
+                    .ok()
+                    .flatten()
+                    .and_then(|s| Uuid::parse_str(&s).ok())
+            };
+            let grader: String = row.try_get("grader").unwrap_or_default();
+            let status: String = row.try_get("status").unwrap_or_default();
+            verifications.push(CertVerification {
+                verification_uuid: uuid_col("verification_uuid").unwrap_or_default(),
+                inventory_uuid: uuid_col("inventory_uuid").unwrap_or_default(),
+                grader: GradingCompany::parse(&grader)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown grader '{}'", grader))?,
+                cert_number: row.try_get("cert_number").unwrap_or_default(),
+                provider: row.try_get("provider").unwrap_or_default(),
+                status: VerificationStatus::parse(&status).unwrap_or(VerificationStatus::Mismatch),
+                flags: row
+                    .try_get::<String, _>("flags")
+                    .ok()
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .unwrap_or_default(),
+                official: row
+                    .try_get::<Option<String>, _>("official")
+                    .ok()
+                    .flatten()
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                verified_by: uuid_col("verified_by"),
+                verified_at: DateTime::parse_from_rfc3339(
+                    &row.try_get::<String, _>("verified_at").unwrap_or_default(),
+                )
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or(Utc::now()),
+            });
+        }
+        Ok(verifications)
+    }
+
     /// Get provenance history for an item
     async fn get_provenance(&self, inventory_uuid: Uuid) -> Result<Vec<ProvenanceEntry>> {
         let rows = sqlx::query(
@@ -0,0 +1,766 @@
+//! Damage and shrinkage tracking
+//!
+//! Every unit lost to damage, defects, theft or miscounts is recorded in
+//! `Damaged_Items` against the inventory row it came from, valued at cost.
+//! A report starts `Pending`; resolving it picks a disposition and records
+//! whatever value was recovered (vendor credit, discounted sale, repair).
+
+use crate::core::money::round_cents;
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
+use crate::database::Database;
+use crate::errors::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Values match the `Damaged_Items.damage_type` CHECK
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DamageType {
+    Defective,
+    Damaged,
+    Missing,
+    Expired,
+    Other,
+}
+
+impl std::fmt::Display for DamageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DamageType::Defective => write!(f, "Defective"),
+            DamageType::Damaged => write!(f, "Damaged"),
+            DamageType::Missing => write!(f, "Missing"),
+            DamageType::Expired => write!(f, "Expired"),
+            DamageType::Other => write!(f, "Other"),
+        }
+    }
+}
+
+impl DamageType {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Defective" => Some(DamageType::Defective),
+            "Damaged" => Some(DamageType::Damaged),
+            "Missing" => Some(DamageType::Missing),
+            "Expired" => Some(DamageType::Expired),
+            "Other" => Some(DamageType::Other),
+            _ => None,
+        }
+    }
+}
+
+/// Values match the `Damaged_Items.disposition` CHECK
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Disposition {
+    Pending,
+    WriteOff,
+    ReturnToVendor,
+    /// Put back on the floor as a separately priced item
+    Discounted,
+    /// Fixed and returned to its original pile
+    Repaired,
+}
+
+impl std::fmt::Display for Disposition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Disposition::Pending => write!(f, "Pending"),
+            Disposition::WriteOff => write!(f, "WriteOff"),
+            Disposition::ReturnToVendor => write!(f, "ReturnToVendor"),
+            Disposition::Discounted => write!(f, "Discounted"),
+            Disposition::Repaired => write!(f, "Repaired"),
+        }
+    }
+}
+
+impl Disposition {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Pending" => Some(Disposition::Pending),
+            "WriteOff" => Some(Disposition::WriteOff),
+            "ReturnToVendor" => Some(Disposition::ReturnToVendor),
+            "Discounted" => Some(Disposition::Discounted),
+            "Repaired" => Some(Disposition::Repaired),
+            _ => None,
+        }
+    }
+}
+
+/// Where a shrink record came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShrinkSource {
+    /// Reported from the floor
+    Manual,
+    /// Customer return too damaged to restock
+    Return,
+    /// Shortfall found by a physical count
+    Count,
+}
+
+impl std::fmt::Display for ShrinkSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShrinkSource::Manual => write!(f, "manual"),
+            ShrinkSource::Return => write!(f, "return"),
+            ShrinkSource::Count => write!(f, "count"),
+        }
+    }
+}
+
+impl ShrinkSource {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "manual" => Some(ShrinkSource::Manual),
+            "return" => Some(ShrinkSource::Return),
+            "count" => Some(ShrinkSource::Count),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DamageRecord {
+    pub damage_uuid: Uuid,
+    pub inventory_uuid: Uuid,
+    pub product_uuid: Option<Uuid>,
+    pub product_name: Option<String>,
+    pub category: Option<String>,
+    pub quantity: i32,
+    pub damage_type: DamageType,
+    pub description: Option<String>,
+    pub disposition: Disposition,
+    /// Cost of the lost units when reported
+    pub original_value: f64,
+    pub recovered_value: f64,
+    pub net_loss: f64,
+    pub source: ShrinkSource,
+    /// Return or count conflict that produced this record
+    pub source_uuid: Option<Uuid>,
+    pub reported_by: Option<Uuid>,
+    pub resolved_by: Option<Uuid>,
+    pub resolution_notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportDamageRequest {
+    pub inventory_uuid: Uuid,
+    pub quantity: i32,
+    pub damage_type: DamageType,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResolveDamageRequest {
+    pub disposition: Disposition,
+    /// Vendor credit, expected discounted sale value, etc.
+    #[serde(default)]
+    pub recovered_value: f64,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShrinkBreakdown {
+    pub quantity: i64,
+    pub original_value: f64,
+    pub recovered_value: f64,
+    pub net_loss: f64,
+}
+
+impl ShrinkBreakdown {
+    fn add(&mut self, record: &DamageRecord) {
+        self.quantity += record.quantity as i64;
+        self.original_value = round_cents(self.original_value + record.original_value);
+        self.recovered_value = round_cents(self.recovered_value + record.recovered_value);
+        self.net_loss = round_cents(self.original_value - self.recovered_value);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShrinkReport {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub totals: ShrinkBreakdown,
+    pub pending_count: i64,
+    pub by_reason: BTreeMap<String, ShrinkBreakdown>,
+    pub by_category: BTreeMap<String, ShrinkBreakdown>,
+    /// Keyed by username of the employee who reported it
+    pub by_employee: BTreeMap<String, ShrinkBreakdown>,
+    pub by_source: BTreeMap<String, ShrinkBreakdown>,
+}
+
+const RECORD_SELECT: &str = "SELECT d.*, li.product_uuid, gc.name as product_name, gc.category
+     FROM Damaged_Items d
+     LEFT JOIN Local_Inventory li ON li.inventory_uuid = d.inventory_uuid
+     LEFT JOIN Global_Catalog gc ON gc.product_uuid = li.product_uuid";
+
+pub struct ShrinkageService {
+    db: Arc<Database>,
+}
+
+impl ShrinkageService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Report damaged or lost stock and take it out of the pile
+    pub async fn report_damage(
+        &self,
+        request: ReportDamageRequest,
+        reported_by: Option<Uuid>,
+    ) -> Result<DamageRecord> {
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let damage_uuid = record_damage_with_tx(
+            &mut tx,
+            NewDamage {
+                inventory_uuid: request.inventory_uuid,
+                quantity: request.quantity,
+                damage_type: request.damage_type,
+                description: request.description,
+                source: ShrinkSource::Manual,
+                source_uuid: None,
+                reported_by,
+                original_value: None,
+                terminal_id: self.db.node_id.clone(),
+            },
+            true,
+        )
+        .await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.get_record(damage_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Damage record {} not found", damage_uuid))
+    }
+
+    /// Write off the shortfall from a blind-count miscount conflict as
+    /// `Missing` stock, drawing it from the product's piles, and mark the
+    /// conflict resolved.
+    pub async fn record_count_shortage(
+        &self,
+        conflict_uuid: Uuid,
+        reported_by: Option<Uuid>,
+    ) -> Result<Vec<DamageRecord>> {
+        let conflict: Option<(String, String, i64, i64, String)> = sqlx::query_as(
+            "SELECT product_uuid, conflict_type, expected_quantity, actual_quantity, resolution_status
+             FROM Inventory_Conflicts WHERE conflict_uuid = ?",
+        )
+        .bind(conflict_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let (product_uuid, conflict_type, expected, actual, status) =
+            conflict.ok_or_else(|| anyhow::anyhow!("Conflict {} not found", conflict_uuid))?;
+
+        if conflict_type != "PhysicalMiscount" {
+            return Err(anyhow::anyhow!(
+                "Only count variances can be recorded as shrink"
+            ));
+        }
+        if status != "Pending" {
+            return Err(anyhow::anyhow!(
+                "Conflict {} is already {}",
+                conflict_uuid,
+                status
+            ));
+        }
+        let mut shortage = (expected - actual) as i32;
+        if shortage <= 0 {
+            return Err(anyhow::anyhow!(
+                "Count found {} more than expected; nothing to write off",
+                -shortage
+            ));
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let piles: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT inventory_uuid, quantity_on_hand FROM Local_Inventory
+             WHERE product_uuid = ? AND deleted_at IS NULL AND quantity_on_hand > 0
+             ORDER BY quantity_on_hand DESC, inventory_uuid ASC",
+        )
+        .bind(&product_uuid)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut damage_uuids = Vec::new();
+        for (inventory_uuid, on_hand) in piles {
+            if shortage <= 0 {
+                break;
+            }
+            let quantity = shortage.min(on_hand as i32);
+            shortage -= quantity;
+            damage_uuids.push(
+                record_damage_with_tx(
+                    &mut tx,
+                    NewDamage {
+                        inventory_uuid: Uuid::parse_str(&inventory_uuid)?,
+                        quantity,
+                        damage_type: DamageType::Missing,
+                        description: Some(format!(
+                            "Count variance: expected {}, counted {}",
+                            expected, actual
+                        )),
+                        source: ShrinkSource::Count,
+                        source_uuid: Some(conflict_uuid),
+                        reported_by,
+                        original_value: None,
+                        terminal_id: self.db.node_id.clone(),
+                    },
+                    true,
+                )
+                .await?,
+            );
+        }
+        if damage_uuids.is_empty() {
+            return Err(anyhow::anyhow!("No stock on hand to write off"));
+        }
+
+        sqlx::query(
+            "UPDATE Inventory_Conflicts SET resolution_status = 'Resolved', resolved_at = ? WHERE conflict_uuid = ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(conflict_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        let mut records = Vec::with_capacity(damage_uuids.len());
+        for damage_uuid in damage_uuids {
+            if let Some(record) = self.get_record(damage_uuid).await? {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Close out a pending record with a disposition and recovered value
+    pub async fn resolve(
+        &self,
+        damage_uuid: Uuid,
+        request: ResolveDamageRequest,
+        resolved_by: Option<Uuid>,
+    ) -> Result<DamageRecord> {
+        let record = self
+            .get_record(damage_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Damage record {} not found", damage_uuid))?;
+        if record.disposition != Disposition::Pending {
+            return Err(anyhow::anyhow!(
+                "Damage record {} is already resolved as {}",
+                damage_uuid,
+                record.disposition
+            ));
+        }
+        if request.disposition == Disposition::Pending {
+            return Err(anyhow::anyhow!("Choose a disposition to resolve"));
+        }
+        if request.recovered_value < 0.0 {
+            return Err(anyhow::anyhow!("Recovered value cannot be negative"));
+        }
+        if record.damage_type == DamageType::Missing
+            && matches!(
+                request.disposition,
+                Disposition::Discounted | Disposition::Repaired
+            )
+        {
+            return Err(anyhow::anyhow!("Missing stock can only be written off"));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let movement = MovementSource::new(
+            MovementType::Damage,
+            Some(damage_uuid),
+            resolved_by,
+            &self.db.node_id,
+        );
+        match request.disposition {
+            Disposition::Repaired => {
+                // Back into the pile it came from
+                movements::adjust_quantity_with_tx(
+                    &mut tx,
+                    record.inventory_uuid,
+                    record.quantity,
+                    &movement,
+                )
+                .await?;
+                sqlx::query(
+                    "UPDATE Local_Inventory SET deleted_at = NULL WHERE inventory_uuid = ?",
+                )
+                .bind(record.inventory_uuid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to restock: {}", e))?;
+            }
+            Disposition::Discounted => {
+                // A separately priced row so it never merges back into the bulk pile
+                if request.recovered_value <= 0.0 {
+                    return Err(anyhow::anyhow!(
+                        "Discounted items need a recovered (sale) value"
+                    ));
+                }
+                let unit_price = round_cents(request.recovered_value / record.quantity as f64);
+                let discounted_uuid = Uuid::new_v4();
+                sqlx::query(
+                    "INSERT INTO Local_Inventory (inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag, specific_price, cost_basis, received_date)
+                     SELECT ?, product_uuid, variant_type, condition, 0, location_tag, ?, cost_basis, ?
+                     FROM Local_Inventory WHERE inventory_uuid = ?",
+                )
+                .bind(discounted_uuid.to_string())
+                .bind(unit_price)
+                .bind(&now)
+                .bind(record.inventory_uuid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to create discounted item: {}", e))?;
+                movements::adjust_quantity_with_tx(
+                    &mut tx,
+                    discounted_uuid,
+                    record.quantity,
+                    &movement,
+                )
+                .await?;
+            }
+            _ => {}
+        }
+
+        // Repaired stock is whole again, so its full cost is recovered
+        let recovered_value = if request.disposition == Disposition::Repaired {
+            record.original_value
+        } else {
+            round_cents(request.recovered_value)
+        };
+
+        sqlx::query(
+            "UPDATE Damaged_Items SET disposition = ?, recovered_value = ?, resolution_notes = ?, resolved_by = ?, resolved_at = ?
+             WHERE damage_uuid = ?",
+        )
+        .bind(request.disposition.to_string())
+        .bind(recovered_value)
+        .bind(&request.notes)
+        .bind(resolved_by.map(|u| u.to_string()))
+        .bind(&now)
+        .bind(damage_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit transaction: {}", e))?;
+
+        self.get_record(damage_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Damage record {} not found", damage_uuid))
+    }
+
+    pub async fn get_record(&self, damage_uuid: Uuid) -> Result<Option<DamageRecord>> {
+        let row = sqlx::query(&format!("{} WHERE d.damage_uuid = ?", RECORD_SELECT))
+            .bind(damage_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(row.as_ref().and_then(map_record))
+    }
+
+    pub async fn get_records(&self, disposition: Option<Disposition>) -> Result<Vec<DamageRecord>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE (? IS NULL OR COALESCE(d.disposition, 'Pending') = ?) ORDER BY d.created_at DESC",
+            RECORD_SELECT
+        ))
+        .bind(disposition.map(|d| d.to_string()))
+        .bind(disposition.map(|d| d.to_string()))
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().filter_map(map_record).collect())
+    }
+}
+
+/// Fields for a new `Damaged_Items` row
+pub(crate) struct NewDamage {
+    pub inventory_uuid: Uuid,
+    pub quantity: i32,
+    pub damage_type: DamageType,
+    pub description: Option<String>,
+    pub source: ShrinkSource,
+    pub source_uuid: Option<Uuid>,
+    pub reported_by: Option<Uuid>,
+    /// Overrides the cost-based valuation (e.g. the price a return was sold at)
+    pub original_value: Option<f64>,
+    pub terminal_id: String,
+}
+
+/// Insert a shrink record valued at the row's cost basis. With `decrement`,
+/// the units are also taken out of the pile (stock found damaged on the
+/// floor); returns that never made it back on the shelf pass `false`.
+pub(crate) async fn record_damage_with_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    damage: NewDamage,
+    decrement: bool,
+) -> Result<Uuid> {
+    if damage.quantity <= 0 {
+        return Err(anyhow::anyhow!("Quantity must be at least 1"));
+    }
+
+    let row: Option<(i64, Option<f64>, Option<f64>)> = sqlx::query_as(
+        "SELECT quantity_on_hand, cost_basis, specific_price FROM Local_Inventory WHERE inventory_uuid = ?",
+    )
+    .bind(damage.inventory_uuid.to_string())
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    let (on_hand, cost_basis, specific_price) =
+        row.ok_or_else(|| anyhow::anyhow!("Inventory item {} not found", damage.inventory_uuid))?;
+
+    let damage_uuid = Uuid::new_v4();
+    if decrement {
+        if (on_hand as i32) < damage.quantity {
+            return Err(anyhow::anyhow!(
+                "Only {} on hand; cannot remove {}",
+                on_hand,
+                damage.quantity
+            ));
+        }
+        // Count shortages point back at the count; everything else at the shrink record
+        let movement = match damage.source {
+            ShrinkSource::Count => MovementSource::new(
+                MovementType::CountAdjustment,
+                damage.source_uuid,
+                damage.reported_by,
+                &damage.terminal_id,
+            ),
+            _ => MovementSource::new(
+                MovementType::Damage,
+                Some(damage_uuid),
+                damage.reported_by,
+                &damage.terminal_id,
+            ),
+        };
+        movements::adjust_quantity_with_tx(tx, damage.inventory_uuid, -damage.quantity, &movement)
+            .await?;
+    }
+
+    let original_value = damage
+        .original_value
+        .unwrap_or_else(|| cost_basis.or(specific_price).unwrap_or(0.0) * damage.quantity as f64);
+    sqlx::query(
+        "INSERT INTO Damaged_Items (damage_uuid, inventory_uuid, quantity, damage_type, description, disposition,
+                                    original_value, recovered_value, reported_by, created_at, source, source_uuid)
+         VALUES (?, ?, ?, ?, ?, 'Pending', ?, 0, ?, ?, ?, ?)",
+    )
+    .bind(damage_uuid.to_string())
+    .bind(damage.inventory_uuid.to_string())
+    .bind(damage.quantity)
+    .bind(damage.damage_type.to_string())
+    .bind(&damage.description)
+    .bind(round_cents(original_value))
+    .bind(damage.reported_by.map(|u| u.to_string()))
+    .bind(Utc::now().to_rfc3339())
+    .bind(damage.source.to_string())
+    .bind(damage.source_uuid.map(|u| u.to_string()))
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to record damage: {}", e))?;
+
+    tracing::info!(
+        "Recorded {} x {} shrink on {} ({})",
+        damage.quantity,
+        damage.damage_type,
+        damage.inventory_uuid,
+        damage.source
+    );
+    Ok(damage_uuid)
+}
+
+/// Shrink for the period, broken down by reason, category, employee and source
+pub(crate) async fn shrink_report(
+    db: &Database,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<ShrinkReport> {
+    let rows = sqlx::query(
+        "SELECT d.*, li.product_uuid, gc.name as product_name, gc.category, u.username
+         FROM Damaged_Items d
+         LEFT JOIN Local_Inventory li ON li.inventory_uuid = d.inventory_uuid
+         LEFT JOIN Global_Catalog gc ON gc.product_uuid = li.product_uuid
+         LEFT JOIN Users u ON u.user_uuid = d.reported_by
+         WHERE d.created_at >= ? AND d.created_at < ?",
+    )
+    .bind(start.to_rfc3339())
+    .bind(end.to_rfc3339())
+    .fetch_all(&db.pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    let mut report = ShrinkReport {
+        period_start: start,
+        period_end: end,
+        totals: ShrinkBreakdown::default(),
+        pending_count: 0,
+        by_reason: BTreeMap::new(),
+        by_category: BTreeMap::new(),
+        by_employee: BTreeMap::new(),
+        by_source: BTreeMap::new(),
+    };
+
+    for row in &rows {
+        let Some(record) = map_record(row) else {
+            continue;
+        };
+        let employee: Option<String> = sqlx::Row::try_get(row, "username").ok().flatten();
+
+        report.totals.add(&record);
+        if record.disposition == Disposition::Pending {
+            report.pending_count += 1;
+        }
+        report
+            .by_reason
+            .entry(record.damage_type.to_string())
+            .or_default()
+            .add(&record);
+        report
+            .by_category
+            .entry(
+                record
+                    .category
+                    .clone()
+                    .unwrap_or_else(|| "Uncategorized".to_string()),
+            )
+            .or_default()
+            .add(&record);
+        report
+            .by_employee
+            .entry(employee.unwrap_or_else(|| "Unknown".to_string()))
+            .or_default()
+            .add(&record);
+        report
+            .by_source
+            .entry(record.source.to_string())
+            .or_default()
+            .add(&record);
+    }
+
+    Ok(report)
+}
+
+fn parse_date(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<DateTime<Utc>> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn parse_uuid(row: &sqlx::sqlite::SqliteRow, col: &str) -> Option<Uuid> {
+    sqlx::Row::try_get::<Option<String>, _>(row, col)
+        .ok()
+        .flatten()
+        .and_then(|s| Uuid::parse_str(&s).ok())
+}
+
+fn map_record(row: &sqlx::sqlite::SqliteRow) -> Option<DamageRecord> {
+    let damage_type: String = sqlx::Row::try_get(row, "damage_type").ok()?;
+    let disposition: Option<String> = sqlx::Row::try_get(row, "disposition").ok().flatten();
+    let source: Option<String> = sqlx::Row::try_get(row, "source").ok().flatten();
+    let original_value: f64 = sqlx::Row::try_get::<Option<f64>, _>(row, "original_value")
+        .ok()
+        .flatten()
+        .unwrap_or(0.0);
+    let recovered_value: f64 = sqlx::Row::try_get::<Option<f64>, _>(row, "recovered_value")
+        .ok()
+        .flatten()
+        .unwrap_or(0.0);
+    Some(DamageRecord {
+        damage_uuid: parse_uuid(row, "damage_uuid")?,
+        inventory_uuid: parse_uuid(row, "inventory_uuid")?,
+        product_uuid: parse_uuid(row, "product_uuid"),
+        product_name: sqlx::Row::try_get(row, "product_name").ok().flatten(),
+        category: sqlx::Row::try_get(row, "category").ok().flatten(),
+        quantity: sqlx::Row::try_get(row, "quantity").unwrap_or(0),
+        damage_type: DamageType::parse(&damage_type)?,
+        description: sqlx::Row::try_get(row, "description").ok().flatten(),
+        disposition: disposition
+            .as_deref()
+            .and_then(Disposition::parse)
+            .unwrap_or(Disposition::Pending),
+        original_value,
+        recovered_value,
+        net_loss: round_cents(original_value - recovered_value),
+        source: source
+            .as_deref()
+            .and_then(ShrinkSource::parse)
+            .unwrap_or(ShrinkSource::Manual),
+        source_uuid: parse_uuid(row, "source_uuid"),
+        reported_by: parse_uuid(row, "reported_by"),
+        resolved_by: parse_uuid(row, "resolved_by"),
+        resolution_notes: sqlx::Row::try_get(row, "resolution_notes").ok().flatten(),
+        created_at: parse_date(row, "created_at").unwrap_or_else(Utc::now),
+        resolved_at: parse_date(row, "resolved_at"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enum_round_trips() {
+        for t in [
+            DamageType::Defective,
+            DamageType::Damaged,
+            DamageType::Missing,
+            DamageType::Expired,
+            DamageType::Other,
+        ] {
+            assert_eq!(DamageType::parse(&t.to_string()), Some(t));
+        }
+        for d in [
+            Disposition::Pending,
+            Disposition::WriteOff,
+            Disposition::ReturnToVendor,
+            Disposition::Discounted,
+            Disposition::Repaired,
+        ] {
+            assert_eq!(Disposition::parse(&d.to_string()), Some(d));
+        }
+        for s in [
+            ShrinkSource::Manual,
+            ShrinkSource::Return,
+            ShrinkSource::Count,
+        ] {
+            assert_eq!(ShrinkSource::parse(&s.to_string()), Some(s));
+        }
+    }
+}
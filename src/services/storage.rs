@@ -0,0 +1,1082 @@
+//! Storage locations within a store
+//!
+//! `location_tag` says which store a pile is in; storage locations say where
+//! in that store. Each store has a tree of rooms, cases or shelves, rows and
+//! bins, each with an optional unit capacity and a pick sequence among its
+//! siblings. The sequences along a node's path give its walk key, so sorting
+//! by walk key is the order a picker walks the floor.
+//!
+//! Moving stock between bins goes through the movement ledger as a pair of
+//! `bin_move` entries (out of the source pile, into the target pile) under a
+//! `Bin_Moves` record. `bin_location` on the pile is kept as the bin's full
+//! code so labels and count sheets keep showing it.
+
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
+use crate::database::Database;
+use crate::errors::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageKind {
+    Room,
+    Case,
+    Shelf,
+    Row,
+    Bin,
+}
+
+impl std::fmt::Display for StorageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageKind::Room => write!(f, "Room"),
+            StorageKind::Case => write!(f, "Case"),
+            StorageKind::Shelf => write!(f, "Shelf"),
+            StorageKind::Row => write!(f, "Row"),
+            StorageKind::Bin => write!(f, "Bin"),
+        }
+    }
+}
+
+impl StorageKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Room" => Some(StorageKind::Room),
+            "Case" => Some(StorageKind::Case),
+            "Shelf" => Some(StorageKind::Shelf),
+            "Row" => Some(StorageKind::Row),
+            "Bin" => Some(StorageKind::Bin),
+            _ => None,
+        }
+    }
+
+    /// Level in the hierarchy; children must sit at a deeper level
+    fn level(&self) -> u8 {
+        match self {
+            StorageKind::Room => 1,
+            StorageKind::Case | StorageKind::Shelf => 2,
+            StorageKind::Row => 3,
+            StorageKind::Bin => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageNode {
+    pub storage_uuid: Uuid,
+    pub location_tag: String,
+    pub parent_uuid: Option<Uuid>,
+    pub kind: StorageKind,
+    pub code: String,
+    pub name: Option<String>,
+    /// Codes along the path, e.g. `BACK-C2-R3-B14`
+    pub full_code: String,
+    pub capacity: Option<i32>,
+    pub pick_sequence: i32,
+    pub is_active: bool,
+    /// Units on hand here and below
+    pub units_stored: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateStorageRequest {
+    /// Store the node belongs to; ignored (taken from the parent) when a parent is given
+    pub location_tag: Option<String>,
+    pub parent_uuid: Option<Uuid>,
+    pub kind: StorageKind,
+    pub code: String,
+    pub name: Option<String>,
+    pub capacity: Option<i32>,
+    #[serde(default)]
+    pub pick_sequence: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateStorageRequest {
+    pub name: Option<String>,
+    pub capacity: Option<i32>,
+    #[serde(default)]
+    pub clear_capacity: bool,
+    pub pick_sequence: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveStockRequest {
+    pub inventory_uuid: Uuid,
+    pub to_storage_uuid: Uuid,
+    /// Units to move; the whole pile when omitted
+    pub quantity: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinMove {
+    pub move_uuid: Uuid,
+    pub source_inventory_uuid: Uuid,
+    /// Same as the source when the whole pile was re-slotted
+    pub target_inventory_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub from_storage_uuid: Option<Uuid>,
+    pub to_storage_uuid: Uuid,
+    pub quantity: i32,
+    pub moved_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One thing to pick: a specific pile, or any stock of a product
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickRequestLine {
+    pub product_uuid: Option<Uuid>,
+    pub inventory_uuid: Option<Uuid>,
+    pub condition: Option<String>,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickLine {
+    /// 1-based stop in walking order
+    pub sequence: usize,
+    pub storage_uuid: Option<Uuid>,
+    /// None for stock that hasn't been put in a bin yet; those come last
+    pub full_code: Option<String>,
+    pub inventory_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub product_name: String,
+    pub condition: String,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickShortage {
+    pub product_uuid: Option<Uuid>,
+    pub inventory_uuid: Option<Uuid>,
+    pub quantity_short: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickList {
+    pub location_tag: String,
+    /// What the list is for, e.g. `hold:<uuid>`
+    pub source: String,
+    pub lines: Vec<PickLine>,
+    pub shortages: Vec<PickShortage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockLocation {
+    pub location_tag: String,
+    pub storage_uuid: Option<Uuid>,
+    pub full_code: Option<String>,
+    pub inventory_uuid: Uuid,
+    pub condition: String,
+    pub variant_type: Option<String>,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductWhereabouts {
+    pub product_uuid: Uuid,
+    pub name: String,
+    pub total_quantity: i64,
+    pub locations: Vec<StockLocation>,
+}
+
+/// Walk key for a node: the parent's key plus this node's place among its siblings
+fn walk_key(parent_key: &str, pick_sequence: i32, code: &str) -> String {
+    format!("{}{:06}:{}/", parent_key, pick_sequence.max(0), code)
+}
+
+/// Take up to `needed` units from piles in order, skipping what earlier
+/// lines already took. Returns the picks and the quantity left unfilled.
+fn allocate_picks(
+    needed: i32,
+    piles: &[(Uuid, i32)],
+    taken: &mut HashMap<Uuid, i32>,
+) -> (Vec<(Uuid, i32)>, i32) {
+    let mut remaining = needed;
+    let mut picks = Vec::new();
+    for (inventory_uuid, on_hand) in piles {
+        if remaining <= 0 {
+            break;
+        }
+        let used = taken.entry(*inventory_uuid).or_insert(0);
+        let take = (on_hand - *used).min(remaining);
+        if take > 0 {
+            *used += take;
+            remaining -= take;
+            picks.push((*inventory_uuid, take));
+        }
+    }
+    (picks, remaining.max(0))
+}
+
+fn parse_uuid(s: Option<String>) -> Option<Uuid> {
+    s.and_then(|s| Uuid::parse_str(&s).ok())
+}
+
+fn map_node(row: &sqlx::sqlite::SqliteRow) -> Option<(StorageNode, String)> {
+    let created_at: String = row.try_get("created_at").ok()?;
+    Some((
+        StorageNode {
+            storage_uuid: Uuid::parse_str(&row.try_get::<String, _>("storage_uuid").ok()?).ok()?,
+            location_tag: row.try_get("location_tag").ok()?,
+            parent_uuid: parse_uuid(row.try_get("parent_uuid").ok()?),
+            kind: StorageKind::parse(&row.try_get::<String, _>("kind").ok()?)?,
+            code: row.try_get("code").ok()?,
+            name: row.try_get("name").ok()?,
+            full_code: row.try_get("full_code").ok()?,
+            capacity: row.try_get("capacity").ok()?,
+            pick_sequence: row.try_get("pick_sequence").ok()?,
+            is_active: row.try_get::<i32, _>("is_active").ok()? != 0,
+            units_stored: 0,
+            created_at: DateTime::parse_from_rfc3339(&created_at)
+                .ok()?
+                .with_timezone(&Utc),
+        },
+        row.try_get("walk_key").ok()?,
+    ))
+}
+
+fn map_stock_location(row: &sqlx::sqlite::SqliteRow) -> Option<StockLocation> {
+    Some(StockLocation {
+        location_tag: row.try_get("location_tag").ok()?,
+        storage_uuid: parse_uuid(row.try_get("storage_uuid").ok()?),
+        full_code: row.try_get("full_code").ok()?,
+        inventory_uuid: Uuid::parse_str(&row.try_get::<String, _>("inventory_uuid").ok()?).ok()?,
+        condition: row.try_get("condition").ok()?,
+        variant_type: row.try_get("variant_type").ok()?,
+        quantity: row.try_get("quantity_on_hand").ok()?,
+    })
+}
+
+const NODE_COLUMNS: &str = "storage_uuid, location_tag, parent_uuid, kind, code, name, full_code,
+     capacity, pick_sequence, walk_key, is_active, created_at";
+
+#[derive(Clone)]
+pub struct StorageService {
+    db: Arc<Database>,
+}
+
+impl StorageService {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Add a room, case, shelf, row or bin
+    pub async fn create_node(&self, req: CreateStorageRequest) -> Result<StorageNode> {
+        let code = req.code.trim().to_string();
+        if code.is_empty() || code.contains(['-', '/', ':']) {
+            return Err(anyhow::anyhow!(
+                "Storage code is required and cannot contain '-', '/' or ':'"
+            ));
+        }
+        if req.capacity.is_some_and(|c| c < 0) {
+            return Err(anyhow::anyhow!("Capacity cannot be negative"));
+        }
+
+        let (location_tag, full_code, parent_key) = match req.parent_uuid {
+            Some(parent_uuid) => {
+                let (parent, parent_key) = self
+                    .load_node(parent_uuid)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Parent storage location not found"))?;
+                if req.kind.level() <= parent.kind.level() {
+                    return Err(anyhow::anyhow!(
+                        "A {} cannot go inside a {}",
+                        req.kind,
+                        parent.kind
+                    ));
+                }
+                (
+                    parent.location_tag,
+                    format!("{}-{}", parent.full_code, code),
+                    parent_key,
+                )
+            }
+            None => {
+                let location_tag = req
+                    .location_tag
+                    .filter(|t| !t.trim().is_empty())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("location_tag is required for a top-level node")
+                    })?;
+                (location_tag, code.clone(), String::new())
+            }
+        };
+
+        let storage_uuid = Uuid::new_v4();
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO Storage_Locations
+             (storage_uuid, location_tag, parent_uuid, kind, code, name, full_code, capacity,
+              pick_sequence, walk_key, is_active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?)",
+        )
+        .bind(storage_uuid.to_string())
+        .bind(&location_tag)
+        .bind(req.parent_uuid.map(|u| u.to_string()))
+        .bind(req.kind.to_string())
+        .bind(&code)
+        .bind(&req.name)
+        .bind(&full_code)
+        .bind(req.capacity)
+        .bind(req.pick_sequence)
+        .bind(walk_key(&parent_key, req.pick_sequence, &code))
+        .bind(now.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| {
+            if e.to_string().contains("UNIQUE") {
+                anyhow::anyhow!("{} already exists in {}", full_code, location_tag)
+            } else {
+                anyhow::anyhow!("Failed to create storage location: {}", e)
+            }
+        })?;
+
+        self.get_node(storage_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Storage location vanished after insert"))
+    }
+
+    /// Rename, resize, resequence or retire a node. Resequencing moves the
+    /// whole subtree in walking order.
+    pub async fn update_node(
+        &self,
+        storage_uuid: Uuid,
+        req: UpdateStorageRequest,
+    ) -> Result<StorageNode> {
+        let (node, old_key) = self
+            .load_node(storage_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Storage location not found"))?;
+        if req.capacity.is_some_and(|c| c < 0) {
+            return Err(anyhow::anyhow!("Capacity cannot be negative"));
+        }
+        if req.is_active == Some(false) {
+            let units = self.units_below(&node.location_tag, &old_key).await?;
+            if units > 0 {
+                return Err(anyhow::anyhow!(
+                    "{} still holds {} units; move them out first",
+                    node.full_code,
+                    units
+                ));
+            }
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let capacity = if req.clear_capacity {
+            None
+        } else {
+            req.capacity.or(node.capacity)
+        };
+        sqlx::query(
+            "UPDATE Storage_Locations SET name = ?, capacity = ?, is_active = ? WHERE storage_uuid = ?",
+        )
+        .bind(req.name.or(node.name))
+        .bind(capacity)
+        .bind(req.is_active.unwrap_or(node.is_active))
+        .bind(storage_uuid.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to update storage location: {}", e))?;
+
+        if let Some(pick_sequence) = req.pick_sequence.filter(|s| *s != node.pick_sequence) {
+            let parent_key =
+                &old_key[..old_key.len() - walk_key("", node.pick_sequence, &node.code).len()];
+            let new_key = walk_key(parent_key, pick_sequence, &node.code);
+            sqlx::query(
+                "UPDATE Storage_Locations
+                 SET walk_key = ? || substr(walk_key, ?),
+                     pick_sequence = CASE WHEN storage_uuid = ? THEN ? ELSE pick_sequence END
+                 WHERE location_tag = ? AND substr(walk_key, 1, ?) = ?",
+            )
+            .bind(&new_key)
+            .bind(old_key.chars().count() as i64 + 1)
+            .bind(storage_uuid.to_string())
+            .bind(pick_sequence)
+            .bind(&node.location_tag)
+            .bind(old_key.chars().count() as i64)
+            .bind(&old_key)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to resequence storage: {}", e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit: {}", e))?;
+
+        self.get_node(storage_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Storage location not found"))
+    }
+
+    pub async fn get_node(&self, storage_uuid: Uuid) -> Result<Option<StorageNode>> {
+        let Some((mut node, key)) = self.load_node(storage_uuid).await? else {
+            return Ok(None);
+        };
+        node.units_stored = self.units_below(&node.location_tag, &key).await?;
+        Ok(Some(node))
+    }
+
+    /// A store's storage tree in walking order, with units stored at each level
+    pub async fn get_tree(&self, location_tag: &str) -> Result<Vec<StorageNode>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM Storage_Locations WHERE location_tag = ? ORDER BY walk_key",
+            NODE_COLUMNS
+        ))
+        .bind(location_tag)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let mut nodes: Vec<(StorageNode, String)> = rows.iter().filter_map(map_node).collect();
+
+        let direct: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT storage_uuid, COALESCE(SUM(quantity_on_hand), 0) FROM Local_Inventory
+             WHERE storage_uuid IS NOT NULL AND location_tag = ? AND deleted_at IS NULL
+             GROUP BY storage_uuid",
+        )
+        .bind(location_tag)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let direct: HashMap<String, i64> = direct.into_iter().collect();
+
+        // Keys are prefixes of their descendants' keys
+        let stored: Vec<(String, i64)> = nodes
+            .iter()
+            .map(|(n, key)| {
+                (
+                    key.clone(),
+                    direct
+                        .get(&n.storage_uuid.to_string())
+                        .copied()
+                        .unwrap_or(0),
+                )
+            })
+            .collect();
+        for (node, key) in nodes.iter_mut() {
+            node.units_stored = stored
+                .iter()
+                .filter(|(k, _)| k.starts_with(key.as_str()))
+                .map(|(_, units)| units)
+                .sum();
+        }
+        Ok(nodes.into_iter().map(|(n, _)| n).collect())
+    }
+
+    /// Everything stored in a node and below it, in walking order
+    pub async fn get_contents(&self, storage_uuid: Uuid) -> Result<Vec<StockLocation>> {
+        let (node, key) = self
+            .load_node(storage_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Storage location not found"))?;
+        let rows = sqlx::query(
+            "SELECT li.location_tag, li.storage_uuid, s.full_code, li.inventory_uuid, li.condition,
+                    li.variant_type, li.quantity_on_hand
+             FROM Local_Inventory li
+             JOIN Storage_Locations s ON s.storage_uuid = li.storage_uuid
+             WHERE s.location_tag = ? AND substr(s.walk_key, 1, ?) = ?
+               AND li.deleted_at IS NULL AND li.quantity_on_hand > 0
+             ORDER BY s.walk_key, li.inventory_uuid",
+        )
+        .bind(&node.location_tag)
+        .bind(key.chars().count() as i64)
+        .bind(&key)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(rows.iter().filter_map(map_stock_location).collect())
+    }
+
+    /// Every copy of a product, in every store and bin
+    pub async fn locate_product(&self, product_uuid: Uuid) -> Result<ProductWhereabouts> {
+        let name: Option<String> =
+            sqlx::query_scalar("SELECT name FROM Global_Catalog WHERE product_uuid = ?")
+                .bind(product_uuid.to_string())
+                .fetch_optional(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let name = name.ok_or_else(|| anyhow::anyhow!("Product not found"))?;
+
+        let rows = sqlx::query(
+            "SELECT li.location_tag, li.storage_uuid, s.full_code, li.inventory_uuid, li.condition,
+                    li.variant_type, li.quantity_on_hand
+             FROM Local_Inventory li
+             LEFT JOIN Storage_Locations s ON s.storage_uuid = li.storage_uuid
+             WHERE li.product_uuid = ? AND li.deleted_at IS NULL AND li.quantity_on_hand > 0
+             ORDER BY li.location_tag, s.walk_key IS NULL, s.walk_key, li.inventory_uuid",
+        )
+        .bind(product_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let locations: Vec<StockLocation> = rows.iter().filter_map(map_stock_location).collect();
+
+        Ok(ProductWhereabouts {
+            product_uuid,
+            name,
+            total_quantity: locations.iter().map(|l| l.quantity as i64).sum(),
+            locations,
+        })
+    }
+
+    /// Move units of a pile into another bin in the same store. Partial moves
+    /// split the pile; moves onto a matching pile merge into it. Serialized and
+    /// consigned piles only move whole.
+    pub async fn move_stock(
+        &self,
+        req: MoveStockRequest,
+        moved_by: Option<Uuid>,
+    ) -> Result<BinMove> {
+        let (target_node, target_key) = self
+            .load_node(req.to_storage_uuid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Target storage location not found"))?;
+        if !target_node.is_active {
+            return Err(anyhow::anyhow!("{} is inactive", target_node.full_code));
+        }
+
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        let pile = sqlx::query(
+            "SELECT li.product_uuid, li.variant_type, li.condition, li.location_tag, li.quantity_on_hand,
+                    li.storage_uuid, li.specific_price, li.cost_basis, li.serialized_details,
+                    s.full_code, s.walk_key,
+                    EXISTS(SELECT 1 FROM Consignment_Items c
+                           WHERE c.inventory_uuid = li.inventory_uuid AND c.status = 'Active') AS consigned
+             FROM Local_Inventory li
+             LEFT JOIN Storage_Locations s ON s.storage_uuid = li.storage_uuid
+             WHERE li.inventory_uuid = ? AND li.deleted_at IS NULL",
+        )
+        .bind(req.inventory_uuid.to_string())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Inventory item not found"))?;
+
+        let product_uuid: String = pile.try_get("product_uuid")?;
+        let location_tag: String = pile.try_get("location_tag")?;
+        let on_hand: i32 = pile.try_get("quantity_on_hand")?;
+        let from_storage = parse_uuid(pile.try_get("storage_uuid")?);
+        let from_code: Option<String> = pile.try_get("full_code")?;
+        let from_key: Option<String> = pile.try_get("walk_key")?;
+        let serialized: Option<String> = pile.try_get("serialized_details")?;
+        let move_whole_only = serialized.is_some() || pile.try_get::<i32, _>("consigned")? != 0;
+
+        if location_tag != target_node.location_tag {
+            return Err(anyhow::anyhow!(
+                "{} is in {}, not {}; use a transfer to move stock between stores",
+                target_node.full_code,
+                target_node.location_tag,
+                location_tag
+            ));
+        }
+        if from_storage == Some(req.to_storage_uuid) {
+            return Err(anyhow::anyhow!(
+                "Stock is already in {}",
+                target_node.full_code
+            ));
+        }
+        let quantity = req.quantity.unwrap_or(on_hand);
+        if quantity <= 0 || quantity > on_hand {
+            return Err(anyhow::anyhow!(
+                "Can move 1 to {} units from this pile, not {}",
+                on_hand,
+                quantity
+            ));
+        }
+        if move_whole_only && quantity != on_hand {
+            return Err(anyhow::anyhow!(
+                "Serialized and consigned stock can only be moved as a whole pile"
+            ));
+        }
+        self.check_capacity(
+            &mut tx,
+            &target_node.location_tag,
+            &target_key,
+            from_key.as_deref(),
+            quantity,
+        )
+        .await?;
+
+        let move_uuid = Uuid::new_v4();
+        let source = MovementSource {
+            notes: Some(format!(
+                "{} -> {}",
+                from_code.as_deref().unwrap_or("unslotted"),
+                target_node.full_code
+            )),
+            ..MovementSource::new(
+                MovementType::BinMove,
+                Some(move_uuid),
+                moved_by,
+                &self.db.node_id,
+            )
+        };
+
+        let existing: Option<(String, i32, Option<f64>)> = if move_whole_only {
+            None
+        } else {
+            sqlx::query_as(
+                "SELECT li.inventory_uuid, li.quantity_on_hand, li.cost_basis FROM Local_Inventory li
+                 WHERE li.product_uuid = ? AND li.condition = ? AND li.location_tag = ? AND li.storage_uuid = ?
+                   AND COALESCE(NULLIF(li.variant_type, 'Normal'), '') = COALESCE(NULLIF(?, 'Normal'), '')
+                   AND li.specific_price IS ?
+                   AND li.serialized_details IS NULL AND li.deleted_at IS NULL
+                   AND li.inventory_uuid NOT IN (SELECT inventory_uuid FROM Consignment_Items WHERE status = 'Active')
+                 LIMIT 1",
+            )
+            .bind(&product_uuid)
+            .bind(pile.try_get::<String, _>("condition")?)
+            .bind(&location_tag)
+            .bind(req.to_storage_uuid.to_string())
+            .bind(pile.try_get::<Option<String>, _>("variant_type")?)
+            .bind(pile.try_get::<Option<f64>, _>("specific_price")?)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        };
+
+        movements::adjust_quantity_with_tx(&mut tx, req.inventory_uuid, -quantity, &source).await?;
+
+        let target_inventory_uuid = match existing {
+            Some((target_uuid, target_on_hand, target_cost)) => {
+                let source_cost: Option<f64> = pile.try_get("cost_basis")?;
+                if let Some(cost) = source_cost {
+                    let blended = crate::services::purchasing::weighted_average_cost(
+                        target_on_hand,
+                        target_cost,
+                        quantity,
+                        cost,
+                    );
+                    sqlx::query(
+                        "UPDATE Local_Inventory SET cost_basis = ? WHERE inventory_uuid = ?",
+                    )
+                    .bind(blended)
+                    .bind(&target_uuid)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+                }
+                Uuid::parse_str(&target_uuid)?
+            }
+            None if quantity == on_hand => {
+                // Whole pile, nothing to merge with: re-slot it
+                self.slot(&mut tx, req.inventory_uuid, &target_node).await?;
+                req.inventory_uuid
+            }
+            None => {
+                let split_uuid = Uuid::new_v4();
+                sqlx::query(
+                    "INSERT INTO Local_Inventory
+                     (inventory_uuid, product_uuid, variant_type, condition, quantity_on_hand, location_tag,
+                      specific_price, cost_basis, supplier_uuid, received_date, min_stock_level,
+                      max_stock_level, reorder_point, storage_uuid, bin_location)
+                     SELECT ?, product_uuid, variant_type, condition, 0, location_tag,
+                            specific_price, cost_basis, supplier_uuid, received_date, min_stock_level,
+                            max_stock_level, reorder_point, ?, ?
+                     FROM Local_Inventory WHERE inventory_uuid = ?",
+                )
+                .bind(split_uuid.to_string())
+                .bind(req.to_storage_uuid.to_string())
+                .bind(&target_node.full_code)
+                .bind(req.inventory_uuid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to split pile: {}", e))?;
+                split_uuid
+            }
+        };
+        movements::adjust_quantity_with_tx(&mut tx, target_inventory_uuid, quantity, &source)
+            .await?;
+
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO Bin_Moves
+             (move_uuid, source_inventory_uuid, target_inventory_uuid, product_uuid, from_storage_uuid,
+              to_storage_uuid, quantity, moved_by, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(move_uuid.to_string())
+        .bind(req.inventory_uuid.to_string())
+        .bind(target_inventory_uuid.to_string())
+        .bind(&product_uuid)
+        .bind(from_storage.map(|u| u.to_string()))
+        .bind(req.to_storage_uuid.to_string())
+        .bind(quantity)
+        .bind(moved_by.map(|u| u.to_string()))
+        .bind(now.to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to record bin move: {}", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit: {}", e))?;
+
+        Ok(BinMove {
+            move_uuid,
+            source_inventory_uuid: req.inventory_uuid,
+            target_inventory_uuid,
+            product_uuid: Uuid::parse_str(&product_uuid)?,
+            from_storage_uuid: from_storage,
+            to_storage_uuid: req.to_storage_uuid,
+            quantity,
+            moved_by,
+            created_at: now,
+        })
+    }
+
+    /// Bin moves for a product, newest first
+    pub async fn get_moves(&self, product_uuid: Uuid, limit: i64) -> Result<Vec<BinMove>> {
+        let rows = sqlx::query(
+            "SELECT move_uuid, source_inventory_uuid, target_inventory_uuid, product_uuid,
+                    from_storage_uuid, to_storage_uuid, quantity, moved_by, created_at
+             FROM Bin_Moves WHERE product_uuid = ? ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(product_uuid.to_string())
+        .bind(limit)
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let created_at: String = row.try_get("created_at").ok()?;
+                Some(BinMove {
+                    move_uuid: parse_uuid(row.try_get("move_uuid").ok()?)?,
+                    source_inventory_uuid: parse_uuid(row.try_get("source_inventory_uuid").ok()?)?,
+                    target_inventory_uuid: parse_uuid(row.try_get("target_inventory_uuid").ok()?)?,
+                    product_uuid: parse_uuid(row.try_get("product_uuid").ok()?)?,
+                    from_storage_uuid: parse_uuid(row.try_get("from_storage_uuid").ok()?),
+                    to_storage_uuid: parse_uuid(row.try_get("to_storage_uuid").ok()?)?,
+                    quantity: row.try_get("quantity").ok()?,
+                    moved_by: parse_uuid(row.try_get("moved_by").ok()?),
+                    created_at: DateTime::parse_from_rfc3339(&created_at)
+                        .ok()?
+                        .with_timezone(&Utc),
+                })
+            })
+            .collect())
+    }
+
+    /// Pick list for the items on a hold. Held units have already left
+    /// on-hand stock, so each item is picked from its own pile's bin.
+    pub async fn pick_list_for_hold(&self, hold_uuid: Uuid) -> Result<PickList> {
+        let rows: Vec<(String, i32, String)> = sqlx::query_as(
+            "SELECT hi.inventory_uuid, hi.quantity, li.location_tag FROM Hold_Items hi
+             JOIN Local_Inventory li ON li.inventory_uuid = hi.inventory_uuid
+             WHERE hi.hold_uuid = ?",
+        )
+        .bind(hold_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        let location_tag = rows
+            .first()
+            .map(|(_, _, tag)| tag.clone())
+            .ok_or_else(|| anyhow::anyhow!("Hold not found or has no items"))?;
+
+        let lines = rows
+            .into_iter()
+            .map(|(inventory_uuid, quantity, _)| PickRequestLine {
+                product_uuid: None,
+                inventory_uuid: Uuid::parse_str(&inventory_uuid).ok(),
+                condition: None,
+                quantity,
+            })
+            .collect();
+        self.pick_list(&location_tag, format!("hold:{}", hold_uuid), lines)
+            .await
+    }
+
+    /// Pick list for an outgoing transfer at its source store
+    pub async fn pick_list_for_transfer(&self, transfer_uuid: Uuid) -> Result<PickList> {
+        let location_tag: String = sqlx::query_scalar(
+            "SELECT source_location FROM Inventory_Transfers WHERE transfer_uuid = ?",
+        )
+        .bind(transfer_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?
+        .ok_or_else(|| anyhow::anyhow!("Transfer not found"))?;
+
+        let rows: Vec<(String, Option<String>, i32)> = sqlx::query_as(
+            "SELECT product_uuid, inventory_uuid, quantity FROM Transfer_Items WHERE transfer_uuid = ?",
+        )
+        .bind(transfer_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let lines = rows
+            .into_iter()
+            .map(|(product_uuid, inventory_uuid, quantity)| PickRequestLine {
+                product_uuid: Uuid::parse_str(&product_uuid).ok(),
+                inventory_uuid: inventory_uuid.and_then(|u| Uuid::parse_str(&u).ok()),
+                condition: None,
+                quantity,
+            })
+            .collect();
+        self.pick_list(&location_tag, format!("transfer:{}", transfer_uuid), lines)
+            .await
+    }
+
+    /// Pick list for arbitrary lines, e.g. an online order: lines naming a
+    /// pile are picked from it; lines naming a product are filled from that
+    /// store's stock in walking order. Unslotted stock comes last.
+    pub async fn pick_list(
+        &self,
+        location_tag: &str,
+        source: String,
+        lines: Vec<PickRequestLine>,
+    ) -> Result<PickList> {
+        let mut picks: Vec<(Uuid, i32)> = Vec::new();
+        let mut shortages = Vec::new();
+        let mut taken: HashMap<Uuid, i32> = HashMap::new();
+
+        for line in lines {
+            if line.quantity <= 0 {
+                continue;
+            }
+            if let Some(inventory_uuid) = line.inventory_uuid {
+                picks.push((inventory_uuid, line.quantity));
+                continue;
+            }
+            let Some(product_uuid) = line.product_uuid else {
+                return Err(anyhow::anyhow!(
+                    "Each pick line needs a product_uuid or inventory_uuid"
+                ));
+            };
+            let piles: Vec<(String, i32)> = sqlx::query_as(
+                "SELECT li.inventory_uuid, li.quantity_on_hand FROM Local_Inventory li
+                 LEFT JOIN Storage_Locations s ON s.storage_uuid = li.storage_uuid
+                 WHERE li.product_uuid = ? AND li.location_tag = ? AND li.quantity_on_hand > 0
+                   AND li.deleted_at IS NULL AND (? IS NULL OR li.condition = ?)
+                 ORDER BY s.walk_key IS NULL, s.walk_key, li.quantity_on_hand DESC",
+            )
+            .bind(product_uuid.to_string())
+            .bind(location_tag)
+            .bind(&line.condition)
+            .bind(&line.condition)
+            .fetch_all(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            let piles: Vec<(Uuid, i32)> = piles
+                .into_iter()
+                .filter_map(|(id, qty)| Uuid::parse_str(&id).ok().map(|id| (id, qty)))
+                .collect();
+
+            let (allocated, short) = allocate_picks(line.quantity, &piles, &mut taken);
+            picks.extend(allocated);
+            if short > 0 {
+                shortages.push(PickShortage {
+                    product_uuid: Some(product_uuid),
+                    inventory_uuid: None,
+                    quantity_short: short,
+                });
+            }
+        }
+
+        // Resolve each pick to its bin and sort into walking order
+        let mut stops: Vec<(Option<String>, PickLine)> = Vec::with_capacity(picks.len());
+        for (inventory_uuid, quantity) in picks {
+            let row = sqlx::query(
+                "SELECT li.product_uuid, g.name, li.condition, li.storage_uuid, s.full_code, s.walk_key
+                 FROM Local_Inventory li
+                 JOIN Global_Catalog g ON g.product_uuid = li.product_uuid
+                 LEFT JOIN Storage_Locations s ON s.storage_uuid = li.storage_uuid
+                 WHERE li.inventory_uuid = ?",
+            )
+            .bind(inventory_uuid.to_string())
+            .fetch_optional(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            let Some(row) = row else {
+                shortages.push(PickShortage {
+                    product_uuid: None,
+                    inventory_uuid: Some(inventory_uuid),
+                    quantity_short: quantity,
+                });
+                continue;
+            };
+            stops.push((
+                row.try_get("walk_key")?,
+                PickLine {
+                    sequence: 0,
+                    storage_uuid: parse_uuid(row.try_get("storage_uuid")?),
+                    full_code: row.try_get("full_code")?,
+                    inventory_uuid,
+                    product_uuid: Uuid::parse_str(&row.try_get::<String, _>("product_uuid")?)?,
+                    product_name: row.try_get("name")?,
+                    condition: row.try_get("condition")?,
+                    quantity,
+                },
+            ));
+        }
+        stops.sort_by(|(a_key, a), (b_key, b)| {
+            (a_key.is_none(), a_key, &a.product_name).cmp(&(
+                b_key.is_none(),
+                b_key,
+                &b.product_name,
+            ))
+        });
+
+        Ok(PickList {
+            location_tag: location_tag.to_string(),
+            source,
+            lines: stops
+                .into_iter()
+                .enumerate()
+                .map(|(i, (_, line))| PickLine {
+                    sequence: i + 1,
+                    ..line
+                })
+                .collect(),
+            shortages,
+        })
+    }
+
+    async fn load_node(&self, storage_uuid: Uuid) -> Result<Option<(StorageNode, String)>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM Storage_Locations WHERE storage_uuid = ?",
+            NODE_COLUMNS
+        ))
+        .bind(storage_uuid.to_string())
+        .fetch_optional(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+        Ok(row.as_ref().and_then(map_node))
+    }
+
+    async fn units_below(&self, location_tag: &str, key: &str) -> Result<i64> {
+        sqlx::query_scalar(
+            "SELECT COALESCE(SUM(li.quantity_on_hand), 0) FROM Local_Inventory li
+             JOIN Storage_Locations s ON s.storage_uuid = li.storage_uuid
+             WHERE s.location_tag = ? AND substr(s.walk_key, 1, ?) = ? AND li.deleted_at IS NULL",
+        )
+        .bind(location_tag)
+        .bind(key.chars().count() as i64)
+        .bind(key)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))
+    }
+
+    /// Reject a move that would overfill the target or any level above it.
+    /// Levels that already contain the source aren't gaining anything.
+    async fn check_capacity(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        location_tag: &str,
+        target_key: &str,
+        source_key: Option<&str>,
+        adding: i32,
+    ) -> Result<()> {
+        let limits: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT full_code, walk_key, capacity FROM Storage_Locations
+             WHERE location_tag = ? AND capacity IS NOT NULL
+               AND substr(?, 1, length(walk_key)) = walk_key",
+        )
+        .bind(location_tag)
+        .bind(target_key)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        for (full_code, key, capacity) in limits {
+            if source_key.is_some_and(|source| source.starts_with(key.as_str())) {
+                continue;
+            }
+            let used: i64 = sqlx::query_scalar(
+                "SELECT COALESCE(SUM(li.quantity_on_hand), 0) FROM Local_Inventory li
+                 JOIN Storage_Locations s ON s.storage_uuid = li.storage_uuid
+                 WHERE s.location_tag = ? AND substr(s.walk_key, 1, ?) = ? AND li.deleted_at IS NULL",
+            )
+            .bind(location_tag)
+            .bind(key.chars().count() as i64)
+            .bind(&key)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+            if used + adding as i64 > capacity {
+                return Err(anyhow::anyhow!(
+                    "{} holds {} of {} units; no room for {} more",
+                    full_code,
+                    used,
+                    capacity,
+                    adding
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    async fn slot(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        inventory_uuid: Uuid,
+        node: &StorageNode,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE Local_Inventory SET storage_uuid = ?, bin_location = ? WHERE inventory_uuid = ?",
+        )
+        .bind(node.storage_uuid.to_string())
+        .bind(&node.full_code)
+        .bind(inventory_uuid.to_string())
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to slot inventory: {}", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_keys_sort_in_walking_order() {
+        let front = walk_key("", 1, "FRONT");
+        let back = walk_key("", 2, "BACK");
+        let case_10 = walk_key(&front, 10, "C10");
+        let case_2 = walk_key(&front, 2, "C2");
+        let mut keys = vec![back.clone(), case_10.clone(), front.clone(), case_2.clone()];
+        keys.sort();
+        assert_eq!(keys, vec![front, case_2, case_10, back]);
+    }
+
+    #[test]
+    fn test_allocate_picks_shares_piles_across_lines() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let piles = vec![(a, 3), (b, 5)];
+        let mut taken = HashMap::new();
+
+        let (picks, short) = allocate_picks(4, &piles, &mut taken);
+        assert_eq!(picks, vec![(a, 3), (b, 1)]);
+        assert_eq!(short, 0);
+
+        let (picks, short) = allocate_picks(6, &piles, &mut taken);
+        assert_eq!(picks, vec![(b, 4)]);
+        assert_eq!(short, 2);
+    }
+}
@@ -2,11 +2,18 @@
 //!
 //! Handles tax rate lookup and calculation for transactions,
 //! including customer tax-exempt status and category-based rates.
+//!
+//! Stores can also configure stacked jurisdictions (state + county + city)
+//! per `Store_Locations` entry, tax-inclusive pricing per location, and
+//! customer exemption certificates with expiry. Tax collected is recorded
+//! per jurisdiction for the liability report used at filing time.
 
+use crate::core::money::round_cents;
 use crate::database::Database;
 use crate::errors::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -61,6 +68,10 @@ pub struct TaxBreakdown {
     pub total_taxable: f64,
     pub total_tax: f64,
     pub effective_rate: f64,
+    /// Tax collected per jurisdiction (empty when only flat rates apply)
+    pub jurisdictions: Vec<JurisdictionTax>,
+    /// Item amounts were gross prices with tax already included
+    pub prices_include_tax: bool,
 }
 
 impl TaxBreakdown {
@@ -70,8 +81,180 @@ impl TaxBreakdown {
             total_taxable: 0.0,
             total_tax: 0.0,
             effective_rate: 0.0,
+            jurisdictions: Vec::new(),
+            prices_include_tax: false,
+        }
+    }
+}
+
+/// Level of a taxing jurisdiction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JurisdictionLevel {
+    State,
+    County,
+    City,
+    Special,
+}
+
+impl std::fmt::Display for JurisdictionLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JurisdictionLevel::State => write!(f, "State"),
+            JurisdictionLevel::County => write!(f, "County"),
+            JurisdictionLevel::City => write!(f, "City"),
+            JurisdictionLevel::Special => write!(f, "Special"),
+        }
+    }
+}
+
+impl JurisdictionLevel {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "County" => JurisdictionLevel::County,
+            "City" => JurisdictionLevel::City,
+            "Special" => JurisdictionLevel::Special,
+            _ => JurisdictionLevel::State,
+        }
+    }
+}
+
+/// A taxing jurisdiction whose rate stacks with others at a location
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxJurisdiction {
+    pub jurisdiction_uuid: Uuid,
+    pub name: String,
+    /// Filing code used by the taxing authority
+    pub code: Option<String>,
+    pub level: JurisdictionLevel,
+    pub rate: f64,
+    /// Compound rates are charged on the base plus taxes of earlier jurisdictions
+    pub is_compound: bool,
+    /// Restricts the rate to one product category (None = all categories)
+    pub applies_to_category: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaxJurisdiction {
+    fn applies_to(&self, category: Option<&str>) -> bool {
+        match &self.applies_to_category {
+            None => true,
+            Some(cat) => category == Some(cat.as_str()),
+        }
+    }
+}
+
+/// Tax charged (or exempted) for one jurisdiction on a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JurisdictionTax {
+    /// None for the flat default/category rate
+    pub jurisdiction_uuid: Option<Uuid>,
+    pub name: String,
+    pub rate: f64,
+    pub taxable_amount: f64,
+    pub tax_amount: f64,
+    pub exempt: bool,
+    pub certificate_uuid: Option<Uuid>,
+}
+
+/// A customer's tax exemption certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxExemptionCertificate {
+    pub certificate_uuid: Uuid,
+    pub customer_uuid: Uuid,
+    pub certificate_number: String,
+    /// e.g. "Resale", "NonProfit", "Government"
+    pub exemption_type: String,
+    /// Jurisdiction the certificate covers (None = every jurisdiction)
+    pub jurisdiction_uuid: Option<Uuid>,
+    pub effective_date: DateTime<Utc>,
+    pub expiry_date: Option<DateTime<Utc>>,
+    pub document_ref: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl TaxExemptionCertificate {
+    pub fn is_valid_at(&self, at: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none()
+            && self.effective_date <= at
+            && self.expiry_date.map(|exp| at < exp).unwrap_or(true)
+    }
+
+    pub fn covers(&self, jurisdiction_uuid: Option<Uuid>) -> bool {
+        match self.jurisdiction_uuid {
+            None => true,
+            Some(j) => jurisdiction_uuid == Some(j),
+        }
+    }
+}
+
+/// Tax liability for one jurisdiction over a filing period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JurisdictionLiability {
+    pub jurisdiction_uuid: Option<Uuid>,
+    pub name: String,
+    pub code: Option<String>,
+    pub taxable_sales: f64,
+    pub exempt_sales: f64,
+    pub tax_collected: f64,
+    pub transaction_count: i64,
+}
+
+/// Tax liability by jurisdiction for a filing period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxLiabilityReport {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub jurisdictions: Vec<JurisdictionLiability>,
+    pub total_tax_collected: f64,
+}
+
+/// Combined rate of stacked jurisdictions, applying compound rates on top of earlier taxes
+pub fn effective_stacked_rate(rates: &[(f64, bool)]) -> f64 {
+    rates.iter().fold(0.0, |acc, (rate, is_compound)| {
+        if *is_compound {
+            acc + (1.0 + acc) * rate
+        } else {
+            acc + rate
+        }
+    })
+}
+
+/// Split an amount into its net base and per-jurisdiction tax.
+///
+/// With `inclusive` the amount is a gross price and tax is backed out of it;
+/// the last jurisdiction absorbs rounding so base plus taxes equals the gross.
+pub fn stack_taxes(amount: f64, rates: &[(f64, bool)], inclusive: bool) -> (f64, Vec<f64>) {
+    let base = if inclusive {
+        round_cents(amount / (1.0 + effective_stacked_rate(rates)))
+    } else {
+        amount
+    };
+
+    let mut accumulated = 0.0;
+    let mut taxes: Vec<f64> = rates
+        .iter()
+        .map(|(rate, is_compound)| {
+            let taxable = if *is_compound {
+                base + accumulated
+            } else {
+                base
+            };
+            let tax = round_cents(taxable * rate);
+            accumulated += tax;
+            tax
+        })
+        .collect();
+
+    if inclusive {
+        if let Some(last) = taxes.last_mut() {
+            let drift = round_cents(amount - base - accumulated);
+            *last = round_cents(*last + drift);
         }
     }
+
+    (base, taxes)
 }
 
 /// Service for calculating taxes on transactions
@@ -190,6 +373,8 @@ impl TaxService {
                 total_taxable,
                 total_tax: 0.0,
                 effective_rate: 0.0,
+                jurisdictions: Vec::new(),
+                prices_include_tax: false,
             });
         }
 
@@ -218,9 +403,548 @@ impl TaxService {
             total_taxable,
             total_tax,
             effective_rate,
+            jurisdictions: Vec::new(),
+            prices_include_tax: false,
         })
     }
 
+    /// Calculate tax for a sale at a store location.
+    ///
+    /// Uses the location's stacked jurisdictions when configured, otherwise
+    /// the category/default rate. Valid exemption certificates exempt the
+    /// jurisdictions they cover; the legacy `tax_exempt` flag exempts all.
+    pub async fn calculate_location_tax(
+        &self,
+        location_uuid: Option<Uuid>,
+        items: &[(Uuid, f64, Option<String>)], // (item_uuid, amount, category)
+        customer_uuid: Option<Uuid>,
+        customer_tax_exempt: bool,
+    ) -> Result<TaxBreakdown> {
+        let now = Utc::now();
+        let (jurisdictions, prices_include_tax) = match location_uuid {
+            Some(location_uuid) => (
+                self.get_location_jurisdictions(location_uuid).await?,
+                self.location_prices_include_tax(location_uuid).await?,
+            ),
+            None => (Vec::new(), false),
+        };
+        let certificates: Vec<TaxExemptionCertificate> = match customer_uuid {
+            Some(customer_uuid) => self
+                .get_exemption_certificates(customer_uuid)
+                .await?
+                .into_iter()
+                .filter(|c| c.is_valid_at(now))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let mut item_taxes = Vec::new();
+        let mut lines: Vec<JurisdictionTax> = Vec::new();
+        let mut total_taxable = 0.0;
+        let mut total_tax = 0.0;
+
+        for (item_uuid, amount, category) in items {
+            // (jurisdiction_uuid, name, rate, is_compound)
+            let applicable: Vec<(Option<Uuid>, String, f64, bool)> = if jurisdictions.is_empty() {
+                let rate = match category {
+                    Some(cat) => self.get_rate_for_category(cat).await?,
+                    None => self.get_default_rate().await?,
+                };
+                vec![(None, "Default".to_string(), rate, false)]
+            } else {
+                jurisdictions
+                    .iter()
+                    .filter(|j| j.applies_to(category.as_deref()))
+                    .map(|j| {
+                        (
+                            Some(j.jurisdiction_uuid),
+                            j.name.clone(),
+                            j.rate,
+                            j.is_compound,
+                        )
+                    })
+                    .collect()
+            };
+
+            let exemptions: Vec<Option<Uuid>> = applicable
+                .iter()
+                .map(|(jurisdiction_uuid, _, _, _)| {
+                    certificates
+                        .iter()
+                        .find(|c| c.covers(*jurisdiction_uuid))
+                        .map(|c| c.certificate_uuid)
+                })
+                .collect();
+            let is_exempt = |idx: usize| customer_tax_exempt || exemptions[idx].is_some();
+
+            let charged_rates: Vec<(f64, bool)> = applicable
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| !is_exempt(*idx))
+                .map(|(_, (_, _, rate, compound))| (*rate, *compound))
+                .collect();
+            let (base, taxes) = if prices_include_tax && charged_rates.len() < applicable.len() {
+                // The shelf price carries every jurisdiction's tax; an exempt
+                // buyer pays the net of all of it plus only what they owe
+                let all_rates: Vec<(f64, bool)> = applicable
+                    .iter()
+                    .map(|(_, _, rate, compound)| (*rate, *compound))
+                    .collect();
+                let (net, _) = stack_taxes(*amount, &all_rates, true);
+                stack_taxes(net, &charged_rates, false)
+            } else {
+                stack_taxes(*amount, &charged_rates, prices_include_tax)
+            };
+
+            let mut charged = taxes.into_iter();
+            let mut item_tax_total = 0.0;
+            for (idx, (jurisdiction_uuid, name, rate, _)) in applicable.iter().enumerate() {
+                let exempt = is_exempt(idx);
+                let tax_amount = if exempt {
+                    0.0
+                } else {
+                    charged.next().unwrap_or(0.0)
+                };
+                item_tax_total += tax_amount;
+
+                match lines
+                    .iter_mut()
+                    .find(|l| l.jurisdiction_uuid == *jurisdiction_uuid && l.exempt == exempt)
+                {
+                    Some(line) => {
+                        line.taxable_amount += base;
+                        line.tax_amount += tax_amount;
+                    }
+                    None => lines.push(JurisdictionTax {
+                        jurisdiction_uuid: *jurisdiction_uuid,
+                        name: name.clone(),
+                        rate: *rate,
+                        taxable_amount: base,
+                        tax_amount,
+                        exempt,
+                        certificate_uuid: exemptions[idx],
+                    }),
+                }
+            }
+
+            let item_tax_total = round_cents(item_tax_total);
+            total_taxable += base;
+            total_tax += item_tax_total;
+            item_taxes.push(ItemTax {
+                item_uuid: *item_uuid,
+                taxable_amount: base,
+                tax_rate: if base > 0.0 {
+                    item_tax_total / base
+                } else {
+                    0.0
+                },
+                tax_amount: item_tax_total,
+            });
+        }
+
+        for line in &mut lines {
+            line.taxable_amount = round_cents(line.taxable_amount);
+            line.tax_amount = round_cents(line.tax_amount);
+        }
+
+        let total_taxable = round_cents(total_taxable);
+        let total_tax = round_cents(total_tax);
+        Ok(TaxBreakdown {
+            items: item_taxes,
+            total_taxable,
+            total_tax,
+            effective_rate: if total_taxable > 0.0 {
+                total_tax / total_taxable
+            } else {
+                0.0
+            },
+            jurisdictions: lines,
+            prices_include_tax,
+        })
+    }
+
+    /// Record per-jurisdiction tax lines for a completed transaction
+    pub async fn record_tax_lines_with_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        transaction_uuid: Uuid,
+        lines: &[JurisdictionTax],
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        for line in lines {
+            sqlx::query(
+                "INSERT INTO Transaction_Tax_Lines
+                 (line_uuid, transaction_uuid, jurisdiction_uuid, jurisdiction_name, rate,
+                  taxable_amount, tax_amount, exempt, certificate_uuid, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(transaction_uuid.to_string())
+            .bind(line.jurisdiction_uuid.map(|u| u.to_string()))
+            .bind(&line.name)
+            .bind(line.rate)
+            .bind(line.taxable_amount)
+            .bind(line.tax_amount)
+            .bind(line.exempt as i32)
+            .bind(line.certificate_uuid.map(|u| u.to_string()))
+            .bind(&now)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to record tax line: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    // ===== Jurisdictions =====
+
+    /// Create a taxing jurisdiction
+    pub async fn create_jurisdiction(&self, jurisdiction: &TaxJurisdiction) -> Result<()> {
+        if !(0.0..=1.0).contains(&jurisdiction.rate) {
+            return Err(anyhow::anyhow!("Tax rate must be between 0 and 1"));
+        }
+
+        sqlx::query(
+            "INSERT INTO Tax_Jurisdictions
+             (jurisdiction_uuid, name, code, level, rate, is_compound, applies_to_category, is_active, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(jurisdiction.jurisdiction_uuid.to_string())
+        .bind(&jurisdiction.name)
+        .bind(&jurisdiction.code)
+        .bind(jurisdiction.level.to_string())
+        .bind(jurisdiction.rate)
+        .bind(jurisdiction.is_compound as i32)
+        .bind(&jurisdiction.applies_to_category)
+        .bind(jurisdiction.is_active as i32)
+        .bind(jurisdiction.created_at.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get all active jurisdictions
+    pub async fn get_jurisdictions(&self) -> Result<Vec<TaxJurisdiction>> {
+        let rows =
+            sqlx::query("SELECT * FROM Tax_Jurisdictions WHERE is_active = 1 ORDER BY level, name")
+                .fetch_all(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().map(Self::row_to_jurisdiction).collect())
+    }
+
+    /// Attach a jurisdiction to a store location; `sequence` orders compounding
+    pub async fn assign_jurisdiction_to_location(
+        &self,
+        location_uuid: Uuid,
+        jurisdiction_uuid: Uuid,
+        sequence: i32,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO Location_Tax_Jurisdictions (location_uuid, jurisdiction_uuid, sequence)
+             VALUES (?, ?, ?)
+             ON CONFLICT(location_uuid, jurisdiction_uuid) DO UPDATE SET sequence = excluded.sequence",
+        )
+        .bind(location_uuid.to_string())
+        .bind(jurisdiction_uuid.to_string())
+        .bind(sequence)
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Active jurisdictions for a store location, in compounding order
+    pub async fn get_location_jurisdictions(
+        &self,
+        location_uuid: Uuid,
+    ) -> Result<Vec<TaxJurisdiction>> {
+        let rows = sqlx::query(
+            "SELECT j.* FROM Tax_Jurisdictions j
+             JOIN Location_Tax_Jurisdictions lj ON lj.jurisdiction_uuid = j.jurisdiction_uuid
+             WHERE lj.location_uuid = ? AND j.is_active = 1
+             ORDER BY lj.sequence ASC",
+        )
+        .bind(location_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().map(Self::row_to_jurisdiction).collect())
+    }
+
+    /// Whether shelf prices at a location already include tax
+    pub async fn location_prices_include_tax(&self, location_uuid: Uuid) -> Result<bool> {
+        let row =
+            sqlx::query("SELECT prices_include_tax FROM Store_Locations WHERE location_uuid = ?")
+                .bind(location_uuid.to_string())
+                .fetch_optional(&self.db.pool)
+                .await
+                .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(row
+            .and_then(|r| sqlx::Row::try_get::<Option<i32>, _>(&r, "prices_include_tax").ok())
+            .flatten()
+            .unwrap_or(0)
+            == 1)
+    }
+
+    /// Switch tax-inclusive pricing on or off for a location
+    pub async fn set_location_prices_include_tax(
+        &self,
+        location_uuid: Uuid,
+        prices_include_tax: bool,
+    ) -> Result<()> {
+        let result = sqlx::query(
+            "UPDATE Store_Locations SET prices_include_tax = ? WHERE location_uuid = ?",
+        )
+        .bind(prices_include_tax as i32)
+        .bind(location_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!(
+                "Store location {} not found",
+                location_uuid
+            ));
+        }
+        Ok(())
+    }
+
+    // ===== Exemption certificates =====
+
+    /// Record an exemption certificate for a customer
+    pub async fn add_exemption_certificate(
+        &self,
+        certificate: &TaxExemptionCertificate,
+    ) -> Result<()> {
+        if let Some(expiry) = certificate.expiry_date {
+            if expiry <= certificate.effective_date {
+                return Err(anyhow::anyhow!(
+                    "Certificate expiry must be after its effective date"
+                ));
+            }
+        }
+
+        sqlx::query(
+            "INSERT INTO Tax_Exemption_Certificates
+             (certificate_uuid, customer_uuid, certificate_number, exemption_type, jurisdiction_uuid,
+              effective_date, expiry_date, document_ref, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(certificate.certificate_uuid.to_string())
+        .bind(certificate.customer_uuid.to_string())
+        .bind(&certificate.certificate_number)
+        .bind(&certificate.exemption_type)
+        .bind(certificate.jurisdiction_uuid.map(|u| u.to_string()))
+        .bind(certificate.effective_date.to_rfc3339())
+        .bind(certificate.expiry_date.map(|d| d.to_rfc3339()))
+        .bind(&certificate.document_ref)
+        .bind(certificate.created_at.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        // Keep the customer's display ID in step with the latest certificate
+        sqlx::query("UPDATE Customers SET tax_exempt_id = ? WHERE customer_uuid = ?")
+            .bind(&certificate.certificate_number)
+            .bind(certificate.customer_uuid.to_string())
+            .execute(&self.db.pool)
+            .await
+            .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(())
+    }
+
+    /// All certificates on file for a customer, including expired ones
+    pub async fn get_exemption_certificates(
+        &self,
+        customer_uuid: Uuid,
+    ) -> Result<Vec<TaxExemptionCertificate>> {
+        let rows = sqlx::query(
+            "SELECT * FROM Tax_Exemption_Certificates
+             WHERE customer_uuid = ? ORDER BY effective_date DESC",
+        )
+        .bind(customer_uuid.to_string())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(rows.iter().map(Self::row_to_certificate).collect())
+    }
+
+    /// Revoke a certificate so it no longer exempts sales
+    pub async fn revoke_exemption_certificate(&self, certificate_uuid: Uuid) -> Result<()> {
+        sqlx::query(
+            "UPDATE Tax_Exemption_Certificates SET revoked_at = ? WHERE certificate_uuid = ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(certificate_uuid.to_string())
+        .execute(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        Ok(())
+    }
+
+    // ===== Reporting =====
+
+    /// Tax liability by jurisdiction for a filing period (voided sales excluded).
+    /// Post-sale corrections count in the period they were made.
+    pub async fn get_tax_liability_report(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<TaxLiabilityReport> {
+        let rows = sqlx::query(
+            "SELECT tl.jurisdiction_uuid, tl.jurisdiction_name, j.code,
+                    COALESCE(SUM(CASE WHEN tl.exempt = 0 THEN tl.taxable_amount ELSE 0 END), 0.0) as taxable_sales,
+                    COALESCE(SUM(CASE WHEN tl.exempt = 1 THEN tl.taxable_amount ELSE 0 END), 0.0) as exempt_sales,
+                    COALESCE(SUM(tl.tax_amount), 0.0) as tax_collected,
+                    COUNT(DISTINCT tl.transaction_uuid) as transaction_count
+             FROM Transaction_Tax_Lines tl
+             JOIN Transactions t ON t.transaction_uuid = tl.transaction_uuid
+             LEFT JOIN Tax_Jurisdictions j ON j.jurisdiction_uuid = tl.jurisdiction_uuid
+             WHERE t.timestamp >= ? AND t.timestamp <= ? AND t.voided_at IS NULL
+             GROUP BY tl.jurisdiction_uuid, tl.jurisdiction_name, j.code
+             UNION ALL
+             -- Post-sale corrections in the period scale the sale's lines by the change in subtotal
+             SELECT tl.jurisdiction_uuid, tl.jurisdiction_name, j.code,
+                    COALESCE(SUM(CASE WHEN tl.exempt = 0 THEN tl.taxable_amount * a.ratio ELSE 0 END), 0.0),
+                    COALESCE(SUM(CASE WHEN tl.exempt = 1 THEN tl.taxable_amount * a.ratio ELSE 0 END), 0.0),
+                    COALESCE(SUM(tl.tax_amount * a.ratio), 0.0),
+                    0
+             FROM (SELECT adj.transaction_uuid,
+                          (CAST(json_extract(adj.after_values, '$.subtotal') AS REAL)
+                           - CAST(json_extract(adj.before_values, '$.subtotal') AS REAL)) / t.subtotal as ratio
+                   FROM Transaction_Adjustments adj
+                   JOIN Transactions t ON t.transaction_uuid = adj.transaction_uuid
+                   WHERE adj.adjustment_type IN ('price_correction', 'line_void')
+                     AND adj.created_at >= ? AND adj.created_at <= ?
+                     AND t.voided_at IS NULL AND t.subtotal <> 0) a
+             JOIN Transaction_Tax_Lines tl ON tl.transaction_uuid = a.transaction_uuid
+             LEFT JOIN Tax_Jurisdictions j ON j.jurisdiction_uuid = tl.jurisdiction_uuid
+             GROUP BY tl.jurisdiction_uuid, tl.jurisdiction_name, j.code",
+        )
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+        let mut by_jurisdiction: HashMap<Option<Uuid>, JurisdictionLiability> = HashMap::new();
+        for row in rows {
+            let jurisdiction_uuid =
+                sqlx::Row::try_get::<Option<String>, _>(&row, "jurisdiction_uuid")
+                    .ok()
+                    .flatten()
+                    .and_then(|s| Uuid::parse_str(&s).ok());
+            let entry =
+                by_jurisdiction
+                    .entry(jurisdiction_uuid)
+                    .or_insert_with(|| JurisdictionLiability {
+                        jurisdiction_uuid,
+                        name: sqlx::Row::try_get(&row, "jurisdiction_name").unwrap_or_default(),
+                        code: sqlx::Row::try_get(&row, "code").ok().flatten(),
+                        taxable_sales: 0.0,
+                        exempt_sales: 0.0,
+                        tax_collected: 0.0,
+                        transaction_count: 0,
+                    });
+            entry.taxable_sales +=
+                sqlx::Row::try_get::<f64, _>(&row, "taxable_sales").unwrap_or(0.0);
+            entry.exempt_sales += sqlx::Row::try_get::<f64, _>(&row, "exempt_sales").unwrap_or(0.0);
+            entry.tax_collected +=
+                sqlx::Row::try_get::<f64, _>(&row, "tax_collected").unwrap_or(0.0);
+            entry.transaction_count +=
+                sqlx::Row::try_get::<i64, _>(&row, "transaction_count").unwrap_or(0);
+        }
+
+        let mut jurisdictions: Vec<JurisdictionLiability> = by_jurisdiction
+            .into_values()
+            .map(|mut j| {
+                j.taxable_sales = round_cents(j.taxable_sales);
+                j.exempt_sales = round_cents(j.exempt_sales);
+                j.tax_collected = round_cents(j.tax_collected);
+                j
+            })
+            .collect();
+        jurisdictions.sort_by(|a, b| a.name.cmp(&b.name));
+        let total_tax_collected = round_cents(jurisdictions.iter().map(|j| j.tax_collected).sum());
+
+        Ok(TaxLiabilityReport {
+            period_start: start,
+            period_end: end,
+            jurisdictions,
+            total_tax_collected,
+        })
+    }
+
+    fn row_to_jurisdiction(row: &sqlx::sqlite::SqliteRow) -> TaxJurisdiction {
+        TaxJurisdiction {
+            jurisdiction_uuid: Uuid::parse_str(
+                &sqlx::Row::try_get::<String, _>(row, "jurisdiction_uuid").unwrap_or_default(),
+            )
+            .unwrap_or_default(),
+            name: sqlx::Row::try_get(row, "name").unwrap_or_default(),
+            code: sqlx::Row::try_get(row, "code").ok().flatten(),
+            level: JurisdictionLevel::parse(
+                &sqlx::Row::try_get::<String, _>(row, "level").unwrap_or_default(),
+            ),
+            rate: sqlx::Row::try_get(row, "rate").unwrap_or(0.0),
+            is_compound: sqlx::Row::try_get::<i32, _>(row, "is_compound").unwrap_or(0) == 1,
+            applies_to_category: sqlx::Row::try_get(row, "applies_to_category")
+                .ok()
+                .flatten(),
+            is_active: sqlx::Row::try_get::<i32, _>(row, "is_active").unwrap_or(1) == 1,
+            created_at: sqlx::Row::try_get::<String, _>(row, "created_at")
+                .ok()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(Utc::now),
+        }
+    }
+
+    fn row_to_certificate(row: &sqlx::sqlite::SqliteRow) -> TaxExemptionCertificate {
+        let parse_date = |col: &str| {
+            sqlx::Row::try_get::<Option<String>, _>(row, col)
+                .ok()
+                .flatten()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+        };
+
+        TaxExemptionCertificate {
+            certificate_uuid: Uuid::parse_str(
+                &sqlx::Row::try_get::<String, _>(row, "certificate_uuid").unwrap_or_default(),
+            )
+            .unwrap_or_default(),
+            customer_uuid: Uuid::parse_str(
+                &sqlx::Row::try_get::<String, _>(row, "customer_uuid").unwrap_or_default(),
+            )
+            .unwrap_or_default(),
+            certificate_number: sqlx::Row::try_get(row, "certificate_number").unwrap_or_default(),
+            exemption_type: sqlx::Row::try_get(row, "exemption_type").unwrap_or_default(),
+            jurisdiction_uuid: sqlx::Row::try_get::<Option<String>, _>(row, "jurisdiction_uuid")
+                .ok()
+                .flatten()
+                .and_then(|s| Uuid::parse_str(&s).ok()),
+            effective_date: parse_date("effective_date").unwrap_or_else(Utc::now),
+            expiry_date: parse_date("expiry_date"),
+            document_ref: sqlx::Row::try_get(row, "document_ref").ok().flatten(),
+            created_at: parse_date("created_at").unwrap_or_else(Utc::now),
+            revoked_at: parse_date("revoked_at"),
+        }
+    }
+
     /// Create a new tax rate
     pub async fn create_tax_rate(&self, rate: &TaxRate) -> Result<()> {
         sqlx::query(
@@ -340,4 +1064,51 @@ mod tests {
         assert_eq!(breakdown.total_taxable, 0.0);
         assert!(breakdown.items.is_empty());
     }
+
+    #[test]
+    fn test_stacked_rates_compound_on_prior_tax() {
+        // 6% state + 1% county, then a 2% compound city rate on base + prior tax
+        let rates = [(0.06, false), (0.01, false), (0.02, true)];
+        let (base, taxes) = stack_taxes(100.0, &rates, false);
+
+        assert_eq!(base, 100.0);
+        assert_eq!(taxes, vec![6.0, 1.0, 2.14]);
+        assert!((effective_stacked_rate(&rates) - 0.0914).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_inclusive_prices_back_out_tax_exactly() {
+        let rates = [(0.0825, false)];
+        let (base, taxes) = stack_taxes(10.00, &rates, true);
+
+        assert_eq!(base, 9.24);
+        assert_eq!(taxes, vec![0.76]);
+        assert!((base + taxes.iter().sum::<f64>() - 10.00).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_certificate_validity_window() {
+        let now = Utc::now();
+        let jurisdiction = Uuid::new_v4();
+        let mut cert = TaxExemptionCertificate {
+            certificate_uuid: Uuid::new_v4(),
+            customer_uuid: Uuid::new_v4(),
+            certificate_number: "RS-1001".to_string(),
+            exemption_type: "Resale".to_string(),
+            jurisdiction_uuid: Some(jurisdiction),
+            effective_date: now - chrono::Duration::days(30),
+            expiry_date: Some(now + chrono::Duration::days(30)),
+            document_ref: None,
+            created_at: now,
+            revoked_at: None,
+        };
+
+        assert!(cert.is_valid_at(now));
+        assert!(cert.covers(Some(jurisdiction)));
+        assert!(!cert.covers(Some(Uuid::new_v4())));
+        assert!(!cert.is_valid_at(now + chrono::Duration::days(31)));
+
+        cert.revoked_at = Some(now);
+        assert!(!cert.is_valid_at(now));
+    }
 }
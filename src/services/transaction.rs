@@ -6,10 +6,16 @@
 //! - Trade-in limits
 //! - Split payment handling
 //! - Transaction totals calculation
+//! - Mirroring the cart to the terminal's customer display
 
+use crate::core::Currency;
+use crate::database::repositories::movements::{self, MovementSource, MovementType};
 use crate::database::Database;
 use crate::errors::Result;
-use crate::services::{PaymentMethodType, PaymentRequest, PaymentService, TaxService};
+use crate::services::{
+    CartView, CurrencyService, CustomerDisplayService, DisplayLine, JurisdictionTax,
+    PaymentMethodType, PaymentRequest, PaymentService, TaxService,
+};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -24,6 +30,9 @@ pub struct TransactionRequest {
     pub trade_in_items: Option<Vec<TradeInItemRequest>>,
     pub notes: Option<String>,
     pub location_uuid: Option<Uuid>,
+    /// Terminal whose customer display should mirror this cart
+    #[serde(default)]
+    pub terminal_id: Option<String>,
 }
 
 /// Request for a single item in a transaction
@@ -49,6 +58,8 @@ pub struct TradeInItemRequest {
 #[derive(Debug, Clone, Serialize)]
 pub struct TransactionResult {
     pub transaction_uuid: Uuid,
+    /// Base currency all amounts below are expressed in
+    pub currency: Currency,
     pub subtotal: f64,
     pub tax_amount: f64,
     pub trade_in_credit: f64,
@@ -69,6 +80,8 @@ pub struct ValidationResult {
     pub tax_amount: f64,
     pub trade_in_credit: f64,
     pub grand_total: f64,
+    /// Per-jurisdiction tax lines recorded with the sale
+    pub tax_lines: Vec<JurisdictionTax>,
 }
 
 /// Enhanced transaction processing service
@@ -76,6 +89,8 @@ pub struct TransactionValidationService {
     db: Arc<Database>,
     tax_service: Arc<TaxService>,
     payment_service: Arc<PaymentService>,
+    currency_service: CurrencyService,
+    display: Option<Arc<CustomerDisplayService>>,
 }
 
 impl TransactionValidationService {
@@ -85,12 +100,20 @@ impl TransactionValidationService {
         payment_service: Arc<PaymentService>,
     ) -> Self {
         Self {
+            currency_service: CurrencyService::new(db.clone()),
             db,
             tax_service,
             payment_service,
+            display: None,
         }
     }
 
+    /// Mirror validated carts and completed sales to customer displays
+    pub fn with_customer_display(mut self, display: Arc<CustomerDisplayService>) -> Self {
+        self.display = Some(display);
+        self
+    }
+
     /// Validate a transaction request without processing it
     pub async fn validate_transaction(
         &self,
@@ -100,6 +123,8 @@ impl TransactionValidationService {
         let mut warnings = Vec::new();
         let mut subtotal = 0.0;
         let mut trade_in_credit = 0.0;
+        let mut taxable_items = Vec::new();
+        let mut display_lines = Vec::new();
 
         // Check customer if specified
         let customer_tax_exempt = if let Some(customer_uuid) = request.customer_uuid {
@@ -142,8 +167,15 @@ impl TransactionValidationService {
         // Validate each item
         for item in &request.items {
             match self.validate_item(item).await {
-                Ok(item_total) => {
-                    subtotal += item_total;
+                Ok(validated) => {
+                    subtotal += validated.total;
+                    display_lines.push(DisplayLine {
+                        name: validated.name,
+                        quantity: item.quantity,
+                        unit_price: item.override_price.unwrap_or(item.unit_price),
+                        line_total: validated.total,
+                    });
+                    taxable_items.push((item.inventory_uuid, validated.total, validated.category));
                 }
                 Err(e) => {
                     errors.push(e.to_string());
@@ -161,18 +193,33 @@ impl TransactionValidationService {
             }
         }
 
-        // Calculate tax
-        let tax_amount = if customer_tax_exempt {
-            0.0
-        } else {
-            self.tax_service.get_default_rate().await? * subtotal
-        };
+        // Calculate tax across the location's jurisdictions. With tax-inclusive
+        // pricing the item prices are gross, so the subtotal becomes the net.
+        let breakdown = self
+            .tax_service
+            .calculate_location_tax(
+                request.location_uuid,
+                &taxable_items,
+                request.customer_uuid,
+                customer_tax_exempt,
+            )
+            .await?;
+        if breakdown.prices_include_tax {
+            subtotal = breakdown.total_taxable;
+        }
+        let tax_amount = breakdown.total_tax;
 
         // Calculate grand total
         let grand_total = subtotal + tax_amount - trade_in_credit;
 
-        // Validate payment amounts
-        let payment_total: f64 = request.payments.iter().map(|p| p.amount).sum();
+        // Validate payment amounts (foreign tender at its base-currency value)
+        let mut payment_total = 0.0;
+        for payment in &request.payments {
+            match self.payment_service.base_amount(payment).await {
+                Ok(amount) => payment_total += amount,
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
 
         if request.payments.is_empty() && grand_total > 0.0 {
             errors.push("No payment methods specified".to_string());
@@ -188,9 +235,13 @@ impl TransactionValidationService {
             if payment.method == PaymentMethodType::StoreCredit && request.customer_uuid.is_none() {
                 errors.push("Store credit payment requires a customer".to_string());
             }
+            if payment.method == PaymentMethodType::LoyaltyPoints && request.customer_uuid.is_none()
+            {
+                errors.push("Loyalty points payment requires a customer".to_string());
+            }
         }
 
-        Ok(ValidationResult {
+        let result = ValidationResult {
             is_valid: errors.is_empty(),
             errors,
             warnings,
@@ -198,21 +249,42 @@ impl TransactionValidationService {
             tax_amount,
             trade_in_credit,
             grand_total,
-        })
+            tax_lines: breakdown.jurisdictions,
+        };
+
+        if let (Some(display), Some(terminal_id)) = (&self.display, &request.terminal_id) {
+            display.show_cart(
+                terminal_id,
+                CartView {
+                    lines: display_lines,
+                    subtotal: result.subtotal,
+                    tax_amount: result.tax_amount,
+                    tax_lines: result.tax_lines.clone(),
+                    trade_in_credit: result.trade_in_credit,
+                    total: result.grand_total,
+                    currency: self.currency_service.get_base_currency().await?,
+                },
+            );
+        }
+
+        Ok(result)
     }
 
     /// Validate a single transaction item
-    async fn validate_item(&self, item: &TransactionItemRequest) -> Result<f64> {
+    async fn validate_item(&self, item: &TransactionItemRequest) -> Result<ValidatedItem> {
         // Check inventory availability
         let row = sqlx::query(
-            "SELECT quantity_on_hand, deleted_at FROM Local_Inventory WHERE inventory_uuid = ?",
+            "SELECT li.product_uuid, li.location_tag, li.quantity_on_hand, li.deleted_at, gc.category, gc.name
+             FROM Local_Inventory li
+             LEFT JOIN Global_Catalog gc ON gc.product_uuid = li.product_uuid
+             WHERE li.inventory_uuid = ?",
         )
         .bind(item.inventory_uuid.to_string())
         .fetch_optional(&self.db.pool)
         .await
         .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
 
-        match row {
+        let (category, name) = match row {
             Some(r) => {
                 let deleted_at: Option<String> =
                     sqlx::Row::try_get(&r, "deleted_at").ok().flatten();
@@ -223,7 +295,23 @@ impl TransactionValidationService {
                     ));
                 }
 
-                let on_hand: i32 = sqlx::Row::try_get(&r, "quantity_on_hand").unwrap_or(0);
+                let mut on_hand: i32 = sqlx::Row::try_get(&r, "quantity_on_hand").unwrap_or(0);
+                if on_hand < item.quantity {
+                    // Kits can be made up from component stock at the same location
+                    if let Some(product_uuid) = sqlx::Row::try_get::<String, _>(&r, "product_uuid")
+                        .ok()
+                        .and_then(|s| Uuid::parse_str(&s).ok())
+                    {
+                        let location_tag: Option<String> =
+                            sqlx::Row::try_get(&r, "location_tag").ok();
+                        on_hand += crate::services::kitting::kits_buildable(
+                            &self.db.pool,
+                            product_uuid,
+                            location_tag.as_deref(),
+                        )
+                        .await?;
+                    }
+                }
                 if on_hand < item.quantity {
                     return Err(anyhow::anyhow!(
                         "Insufficient stock for item {}: {} available, {} requested",
@@ -232,6 +320,15 @@ impl TransactionValidationService {
                         item.quantity
                     ));
                 }
+
+                (
+                    sqlx::Row::try_get::<Option<String>, _>(&r, "category")
+                        .ok()
+                        .flatten(),
+                    sqlx::Row::try_get::<Option<String>, _>(&r, "name")
+                        .ok()
+                        .flatten(),
+                )
             }
             None => {
                 return Err(anyhow::anyhow!(
@@ -239,11 +336,15 @@ impl TransactionValidationService {
                     item.inventory_uuid
                 ));
             }
-        }
+        };
 
         // Calculate item total
         let price = item.override_price.unwrap_or(item.unit_price);
-        Ok(price * item.quantity as f64)
+        Ok(ValidatedItem {
+            total: price * item.quantity as f64,
+            category,
+            name: name.unwrap_or_else(|| "Unknown Item".to_string()),
+        })
     }
 
     /// Get customer info for validation
@@ -275,10 +376,12 @@ impl TransactionValidationService {
     ) -> Result<TransactionResult> {
         // First validate (read-only checks)
         let validation = self.validate_transaction(request).await?;
+        let currency = self.currency_service.get_base_currency().await?;
 
         if !validation.is_valid {
             return Ok(TransactionResult {
                 transaction_uuid: Uuid::nil(),
+                currency,
                 subtotal: validation.subtotal,
                 tax_amount: validation.tax_amount,
                 trade_in_credit: validation.trade_in_credit,
@@ -304,8 +407,8 @@ impl TransactionValidationService {
         // Create transaction record
         sqlx::query(
             "INSERT INTO Transactions 
-             (transaction_uuid, customer_uuid, user_uuid, timestamp, transaction_type, subtotal, tax_amount, total, notes, location_uuid)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+             (transaction_uuid, customer_uuid, user_uuid, timestamp, transaction_type, subtotal, tax_amount, total, notes, location_uuid, currency)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(transaction_uuid.to_string())
         .bind(request.customer_uuid.map(|u| u.to_string()))
@@ -317,25 +420,38 @@ impl TransactionValidationService {
         .bind(validation.grand_total)
         .bind(&request.notes)
         .bind(request.location_uuid.map(|u| u.to_string()))
+        .bind(currency.code())
         .execute(&mut *tx)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to create transaction: {}", e))?;
 
+        let movement_source = MovementSource::new(
+            MovementType::Sale,
+            Some(transaction_uuid),
+            user_uuid,
+            request.terminal_id.as_deref().unwrap_or(&self.db.node_id),
+        );
+
         // Create transaction items and update inventory
         for item in &request.items {
             let item_uuid = Uuid::new_v4();
             let price = item.override_price.unwrap_or(item.unit_price);
 
             // Get product_uuid from inventory (Read within TX for consistency, though low risk of race if UUIDs are stable)
-            let inv_row =
-                sqlx::query("SELECT product_uuid FROM Local_Inventory WHERE inventory_uuid = ?")
-                    .bind(item.inventory_uuid.to_string())
-                    .fetch_one(&mut *tx)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Failed to get inventory item: {}", e))?;
+            let inv_row = sqlx::query(
+                "SELECT product_uuid, quantity_on_hand, location_tag, condition FROM Local_Inventory WHERE inventory_uuid = ?",
+            )
+            .bind(item.inventory_uuid.to_string())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get inventory item: {}", e))?;
 
             let product_uuid: String = sqlx::Row::try_get(&inv_row, "product_uuid")
                 .map_err(|e| anyhow::anyhow!("Missing product_uuid in inventory: {}", e))?;
+            let on_hand: i32 = sqlx::Row::try_get(&inv_row, "quantity_on_hand").unwrap_or(0);
+            let location_tag: String =
+                sqlx::Row::try_get(&inv_row, "location_tag").unwrap_or_default();
+            let condition: String = sqlx::Row::try_get(&inv_row, "condition").unwrap_or_default();
 
             // Insert transaction item
             sqlx::query(
@@ -353,18 +469,41 @@ impl TransactionValidationService {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to create transaction item: {}", e))?;
 
-            // Deduct inventory
-            sqlx::query(
-                "UPDATE Local_Inventory 
-                 SET quantity_on_hand = quantity_on_hand - ?, last_sold_date = ?
-                 WHERE inventory_uuid = ?",
+            // Deduct inventory, making up a kit shortfall from its components
+            let from_components = crate::services::kitting::consume_kit_shortfall_with_tx(
+                &mut tx,
+                Uuid::parse_str(&product_uuid)?,
+                on_hand,
+                item.quantity,
+                Some(&location_tag),
+                &condition,
+                &movement_source,
             )
-            .bind(item.quantity)
-            .bind(now.to_rfc3339())
-            .bind(item.inventory_uuid.to_string())
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to update inventory: {}", e))?;
+            .await?;
+            let from_pile = item.quantity - from_components;
+            movements::adjust_quantity_with_tx(
+                &mut tx,
+                item.inventory_uuid,
+                -from_pile,
+                &movement_source,
+            )
+            .await?;
+            sqlx::query("UPDATE Local_Inventory SET last_sold_date = ? WHERE inventory_uuid = ?")
+                .bind(now.to_rfc3339())
+                .bind(item.inventory_uuid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to update inventory: {}", e))?;
+
+            // Accrue the consignor's share if this stock is consigned
+            crate::services::consignment::accrue_sale_with_tx(
+                &mut tx,
+                transaction_uuid,
+                &item.inventory_uuid.to_string(),
+                from_pile,
+                price,
+            )
+            .await?;
         }
 
         // Process payments
@@ -379,6 +518,30 @@ impl TransactionValidationService {
             )
             .await?;
 
+        // Award loyalty points on whatever wasn't paid with credit or points
+        if let Some(customer_uuid) = request.customer_uuid {
+            let excluded_tender: f64 = payment_result
+                .payments
+                .iter()
+                .filter(|p| {
+                    matches!(
+                        p.method,
+                        PaymentMethodType::StoreCredit | PaymentMethodType::LoyaltyPoints
+                    )
+                })
+                .map(|p| p.amount)
+                .sum();
+            crate::services::loyalty::accrue_sale_with_tx(
+                &self.db.sync,
+                &mut tx,
+                transaction_uuid,
+                customer_uuid,
+                validation.grand_total,
+                excluded_tender,
+            )
+            .await?;
+        }
+
         // Add trade-in credit to customer if applicable
         if validation.trade_in_credit > 0.0 {
             if let Some(customer_uuid) = request.customer_uuid {
@@ -409,6 +572,11 @@ impl TransactionValidationService {
                 .map_err(|e| anyhow::anyhow!("Failed to record change: {}", e))?;
         }
 
+        // Record tax collected per jurisdiction for liability reporting
+        self.tax_service
+            .record_tax_lines_with_tx(&mut tx, transaction_uuid, &validation.tax_lines)
+            .await?;
+
         // COMMIT TRANSACTION
         tx.commit()
             .await
@@ -422,8 +590,32 @@ impl TransactionValidationService {
             validation.grand_total
         );
 
+        // Tier moves and wants fulfilment are bookkeeping; the sale stands if they fail
+        if let Some(customer_uuid) = request.customer_uuid {
+            if let Err(e) = crate::services::loyalty::evaluate_tier(&self.db, customer_uuid).await {
+                tracing::warn!("Tier evaluation for {} failed: {}", customer_uuid, e);
+            }
+            if let Err(e) =
+                crate::buylist::matcher::record_purchase(&self.db, customer_uuid, transaction_uuid)
+                    .await
+            {
+                tracing::warn!("Wants fulfilment for {} failed: {}", transaction_uuid, e);
+            }
+        }
+
+        if let (Some(display), Some(terminal_id)) = (&self.display, &request.terminal_id) {
+            display.show_thank_you(
+                terminal_id,
+                Some(transaction_uuid),
+                validation.grand_total,
+                change_given,
+                currency,
+            );
+        }
+
         Ok(TransactionResult {
             transaction_uuid,
+            currency,
             subtotal: validation.subtotal,
             tax_amount: validation.tax_amount,
             trade_in_credit: validation.trade_in_credit,
@@ -444,39 +636,101 @@ impl TransactionValidationService {
     ) -> Result<()> {
         let now = Utc::now();
 
-        // Get transaction items to restore inventory
+        let mut tx = self
+            .db
+            .pool
+            .begin()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start transaction: {}", e))?;
+
+        // Get transaction items
         let items = sqlx::query(
-            "SELECT ti.product_uuid, ti.quantity, li.inventory_uuid
-             FROM Transaction_Items ti
-             JOIN Local_Inventory li ON ti.product_uuid = li.product_uuid
-             WHERE ti.transaction_uuid = ?",
+            "SELECT item_uuid, quantity, product_uuid, condition FROM Transaction_Items WHERE transaction_uuid = ?",
         )
         .bind(transaction_uuid.to_string())
-        .fetch_all(&self.db.pool)
+        .fetch_all(&mut *tx)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to get transaction items: {}", e))?;
 
+        let movement_source = MovementSource {
+            notes: Some(reason.to_string()),
+            ..MovementSource::new(
+                MovementType::Void,
+                Some(transaction_uuid),
+                Uuid::parse_str(voided_by).ok(),
+                &self.db.node_id,
+            )
+        };
+
+        // Kits sold from components get their components back, not kit stock
+        let mut kits_restored = crate::services::kitting::restore_kit_sale_with_tx(
+            &mut tx,
+            transaction_uuid,
+            &movement_source,
+        )
+        .await?;
+
         // Restore inventory
         for item in items {
-            let quantity: i32 = sqlx::Row::try_get(&item, "quantity")
+            let mut quantity: i32 = sqlx::Row::try_get(&item, "quantity")
                 .map_err(|e| anyhow::anyhow!("Missing quantity in transaction item: {}", e))?;
-            let inventory_uuid: String =
-                sqlx::Row::try_get(&item, "inventory_uuid").map_err(|e| {
-                    anyhow::anyhow!("Missing inventory_uuid in transaction item: {}", e)
-                })?;
-
-            sqlx::query(
-                "UPDATE Local_Inventory 
-                 SET quantity_on_hand = quantity_on_hand + ?
-                 WHERE inventory_uuid = ?",
+            // Lines voided after the sale were already restocked
+            let item_uuid: String = sqlx::Row::try_get(&item, "item_uuid").unwrap_or_default();
+            if let Some((remaining, _)) =
+                crate::services::adjustment::adjusted_line(&mut *tx, &item_uuid).await?
+            {
+                quantity = remaining;
+            }
+            if quantity <= 0 {
+                continue;
+            }
+            let product_uuid: String = sqlx::Row::try_get(&item, "product_uuid")
+                .map_err(|e| anyhow::anyhow!("Missing product_uuid in transaction item: {}", e))?;
+            if let Some(kits) = Uuid::parse_str(&product_uuid)
+                .ok()
+                .and_then(|product_uuid| kits_restored.get_mut(&product_uuid))
+            {
+                let from_components = (*kits).min(quantity);
+                *kits -= from_components;
+                quantity -= from_components;
+                if quantity == 0 {
+                    continue;
+                }
+            }
+            let condition: String = sqlx::Row::try_get(&item, "condition").unwrap_or_default();
+
+            // One pile per line to restore into, preferring the sold condition
+            let inventory_uuid: Option<String> = sqlx::query_scalar(
+                "SELECT inventory_uuid FROM Local_Inventory
+                 WHERE product_uuid = ?
+                 ORDER BY condition = ? DESC, deleted_at IS NULL DESC, inventory_uuid
+                 LIMIT 1",
             )
-            .bind(quantity)
-            .bind(&inventory_uuid)
-            .execute(&self.db.pool)
+            .bind(&product_uuid)
+            .bind(&condition)
+            .fetch_optional(&mut *tx)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to restore inventory: {}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to find inventory to restock: {}", e))?;
+
+            let Some(inventory_uuid) = inventory_uuid.and_then(|s| Uuid::parse_str(&s).ok()) else {
+                tracing::warn!(
+                    "No inventory row to restock voided transaction {}",
+                    transaction_uuid
+                );
+                continue;
+            };
+            movements::adjust_quantity_with_tx(&mut tx, inventory_uuid, quantity, &movement_source)
+                .await?;
         }
 
+        // Consignors are no longer owed for stock that came back
+        crate::services::consignment::reverse_transaction_with_tx(&mut tx, transaction_uuid)
+            .await?;
+
+        // Points earned on the sale come off; points spent on it go back
+        crate::services::loyalty::reverse_void_with_tx(&self.db.sync, &mut tx, transaction_uuid)
+            .await?;
+
         // Mark transaction as voided
         sqlx::query(
             "UPDATE Transactions SET void_reason = ?, voided_at = ?, notes = COALESCE(notes, '') || ? WHERE transaction_uuid = ?",
@@ -485,16 +739,27 @@ impl TransactionValidationService {
         .bind(now.to_rfc3339())
         .bind(format!(" [Voided by: {}]", voided_by))
         .bind(transaction_uuid.to_string())
-        .execute(&self.db.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to void transaction: {}", e))?;
 
+        tx.commit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to commit void: {}", e))?;
+
         tracing::info!("Transaction {} voided: {}", transaction_uuid, reason);
 
         Ok(())
     }
 }
 
+/// A stock-checked line item
+struct ValidatedItem {
+    total: f64,
+    category: Option<String>,
+    name: String,
+}
+
 /// Internal customer info for validation
 #[allow(dead_code)]
 struct CustomerInfo {
@@ -520,6 +785,7 @@ mod tests {
             tax_amount: 8.0,
             trade_in_credit: 0.0,
             grand_total: 108.0,
+            tax_lines: vec![],
         };
 
         assert!(result.is_valid);
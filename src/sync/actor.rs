@@ -17,6 +17,7 @@
 //! - Natural backpressure via channel capacity
 
 use crate::core::{InventoryItem, Ordering, Product, RecordType, SyncOperation, VectorTimestamp};
+use crate::database::repositories::movements::{MovementSource, MovementType};
 use crate::database::Database;
 use crate::errors::Result;
 use crate::network::NetworkService;
@@ -450,7 +451,16 @@ impl SyncActor {
                 }
                 _ => {
                     if let Ok(item) = serde_json::from_value::<InventoryItem>(change.data.clone()) {
-                        self.db.inventory.insert(&item).await?;
+                        let source = MovementSource::new(
+                            MovementType::SyncMerge,
+                            None,
+                            None,
+                            &self.db.node_id,
+                        );
+                        self.db
+                            .inventory
+                            .insert_with_movement(&item, &source)
+                            .await?;
                     }
                 }
             },
@@ -481,6 +491,29 @@ impl SyncActor {
                     self.db.events.register_participant(&participant).await?;
                 }
             }
+            RecordType::InventoryTransfer => {
+                if let Ok(transfer) = serde_json::from_value::<
+                    crate::services::location::TransferRequest,
+                >(change.data.clone())
+                {
+                    crate::services::location::apply_synced_transfer(&self.db.pool, &transfer)
+                        .await?;
+                }
+            }
+            RecordType::LoyaltyEntry => {
+                if let Ok(entry) = serde_json::from_value::<crate::services::loyalty::LoyaltyEntry>(
+                    change.data.clone(),
+                ) {
+                    crate::services::loyalty::apply_synced_entry(&self.db.pool, &entry).await?;
+                }
+            }
+            RecordType::Media => {
+                if let Ok(item) =
+                    serde_json::from_value::<crate::services::media::MediaItem>(change.data.clone())
+                {
+                    crate::services::media::apply_synced_media(&self.db.pool, &item).await?;
+                }
+            }
             _ => {
                 tracing::warn!("Unsupported record type for sync: {:?}", change.record_type);
             }
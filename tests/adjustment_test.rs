@@ -0,0 +1,413 @@
+// Integration tests for post-sale adjustments
+
+use uuid::Uuid;
+use vaultsync::services::{AdjustmentType, PaymentMethodType, TransactionAdjustmentService};
+
+mod common;
+
+struct Sale {
+    transaction_uuid: Uuid,
+    item_uuid: Uuid,
+    inventory_uuid: Uuid,
+    payment_uuid: Uuid,
+}
+
+/// 2 x $50 at 10% tax, paid $110 by card
+async fn seed_sale(db: &vaultsync::database::Database, customer_uuid: Option<Uuid>) -> Sale {
+    let product_uuid = common::seed_product(db, "Deck Box", "Supplies").await;
+    let sale = Sale {
+        transaction_uuid: Uuid::new_v4(),
+        item_uuid: Uuid::new_v4(),
+        inventory_uuid: common::TestPile::new(product_uuid, 3).insert(db).await,
+        payment_uuid: Uuid::new_v4(),
+    };
+    let now = chrono::Utc::now().to_rfc3339();
+
+    sqlx::query(
+        "INSERT INTO Transactions (transaction_uuid, customer_uuid, timestamp, transaction_type, subtotal, tax_amount, total)
+         VALUES (?, ?, ?, 'Sale', 100.0, 10.0, 110.0)",
+    )
+    .bind(sale.transaction_uuid.to_string())
+    .bind(customer_uuid.map(|u| u.to_string()))
+    .bind(&now)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO Transaction_Items (item_uuid, transaction_uuid, product_uuid, quantity, unit_price, condition)
+         VALUES (?, ?, ?, 2, 50.0, 'NM')",
+    )
+    .bind(sale.item_uuid.to_string())
+    .bind(sale.transaction_uuid.to_string())
+    .bind(product_uuid.to_string())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO Payment_Methods (payment_uuid, transaction_uuid, method_type, amount, created_at)
+         VALUES (?, ?, 'Card', 110.0, ?)",
+    )
+    .bind(sale.payment_uuid.to_string())
+    .bind(sale.transaction_uuid.to_string())
+    .bind(&now)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    sale
+}
+
+async fn seed_user(db: &vaultsync::database::Database, role: &str) -> Uuid {
+    let user_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Users (user_uuid, username, password_hash, role, created_at) VALUES (?, ?, 'x', ?, ?)",
+    )
+    .bind(user_uuid.to_string())
+    .bind(format!("{}-{}", role, user_uuid))
+    .bind(role)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    user_uuid
+}
+
+#[tokio::test]
+async fn test_price_correction_and_line_void_are_chained_and_settled() {
+    let db = common::setup_test_db().await;
+    let service = TransactionAdjustmentService::new(db.clone());
+    let sale = seed_sale(&db, None).await;
+    let clerk = seed_user(&db, "Employee").await;
+    let manager = seed_user(&db, "Manager").await;
+
+    // A clerk cannot approve their own price correction
+    assert!(service
+        .correct_price(
+            sale.transaction_uuid,
+            sale.item_uuid,
+            40.0,
+            "Mistyped",
+            Some(clerk),
+            clerk
+        )
+        .await
+        .is_err());
+
+    let correction = service
+        .correct_price(
+            sale.transaction_uuid,
+            sale.item_uuid,
+            40.0,
+            "Mistyped",
+            Some(clerk),
+            manager,
+        )
+        .await
+        .unwrap();
+    assert_eq!(correction.total_before, 110.0);
+    assert_eq!(correction.total_after, 88.0);
+    assert_eq!(correction.amount_delta, -22.0);
+    assert!(correction.previous_adjustment_uuid.is_none());
+
+    let void = service
+        .void_line(
+            sale.transaction_uuid,
+            sale.item_uuid,
+            1,
+            "Customer changed mind",
+            Some(clerk),
+        )
+        .await
+        .unwrap();
+    assert_eq!(void.total_after, 44.0);
+    assert_eq!(
+        void.previous_adjustment_uuid,
+        Some(correction.adjustment_uuid)
+    );
+    assert!(service
+        .void_line(
+            sale.transaction_uuid,
+            sale.item_uuid,
+            2,
+            "Too many",
+            Some(clerk)
+        )
+        .await
+        .is_err());
+
+    // Stock came back and refunds went to the original card
+    assert_eq!(common::on_hand(&db, sale.inventory_uuid).await, 4);
+    let card_total: f64 = sqlx::query_scalar(
+        "SELECT SUM(amount) FROM Payment_Methods WHERE transaction_uuid = ? AND method_type = 'Card'",
+    )
+    .bind(sale.transaction_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert!((card_total - 44.0).abs() < 0.001);
+
+    let history = service
+        .get_adjustments(sale.transaction_uuid)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].adjustment_type, AdjustmentType::LineVoid);
+    assert_eq!(history[0].approved_by, Some(manager));
+
+    // Adjustment records cannot be rewritten
+    assert!(
+        sqlx::query("UPDATE Transaction_Adjustments SET reason = 'edited'")
+            .execute(&db.pool)
+            .await
+            .is_err()
+    );
+    assert!(sqlx::query("DELETE FROM Transaction_Adjustments")
+        .execute(&db.pool)
+        .await
+        .is_err());
+
+    // The sale keeps what was rung up
+    let (total, quantity, unit_price): (f64, i32, f64) = sqlx::query_as(
+        "SELECT t.total, ti.quantity, ti.unit_price FROM Transactions t
+         JOIN Transaction_Items ti ON ti.transaction_uuid = t.transaction_uuid
+         WHERE t.transaction_uuid = ?",
+    )
+    .bind(sale.transaction_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!((total, quantity, unit_price), (110.0, 2, 50.0));
+
+    // Reports show the sale as rung up and the adjustments beside it
+    let reporting = vaultsync::services::ReportingService::new(db.clone());
+    let report = reporting
+        .get_sales_report(
+            "today".to_string(),
+            chrono::Utc::now() - chrono::Duration::hours(1),
+            chrono::Utc::now() + chrono::Duration::hours(1),
+        )
+        .await
+        .unwrap();
+    assert!((report.total_sales - 100.0).abs() < 0.001);
+    assert_eq!(report.adjustments.adjustment_count, 2);
+    assert!((report.adjustments.net_amount + 66.0).abs() < 0.001);
+    assert!((report.adjustments.tax_amount + 6.0).abs() < 0.001);
+    assert!((report.net_sales - 40.0).abs() < 0.001);
+    assert!((report.sales_by_payment_method["Card"] - 110.0).abs() < 0.001);
+    assert!((report.adjustments.settled_by_method["Card"] + 66.0).abs() < 0.001);
+
+    let audited: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE record_uuid = ?")
+        .bind(sale.transaction_uuid.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(audited, 2);
+}
+
+#[tokio::test]
+async fn test_adjustment_reported_in_the_period_it_was_made() {
+    let db = common::setup_test_db().await;
+    let service = TransactionAdjustmentService::new(db.clone());
+    let sale = seed_sale(&db, None).await;
+    let clerk = seed_user(&db, "Employee").await;
+
+    // Rung up two days ago, corrected today
+    let rung_up = chrono::Utc::now() - chrono::Duration::days(2);
+    sqlx::query("UPDATE Transactions SET timestamp = ? WHERE transaction_uuid = ?")
+        .bind(rung_up.to_rfc3339())
+        .bind(sale.transaction_uuid.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let shift_opened = chrono::Utc::now() - chrono::Duration::hours(1);
+
+    service
+        .void_line(
+            sale.transaction_uuid,
+            sale.item_uuid,
+            1,
+            "Damaged in bag",
+            Some(clerk),
+        )
+        .await
+        .unwrap();
+
+    let reporting = vaultsync::services::ReportingService::new(db.clone());
+    // The window a Z-report covers for a shift opened an hour ago
+    let z = reporting
+        .get_sales_report("shift".to_string(), shift_opened, chrono::Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(z.total_transactions, 0);
+    assert_eq!(z.adjustments.adjustment_count, 1);
+    assert!((z.adjustments.net_amount + 55.0).abs() < 0.001);
+    assert!((z.net_sales + 50.0).abs() < 0.001);
+    assert!((z.adjustments.settled_by_method["Card"] + 55.0).abs() < 0.001);
+
+    // The day of the sale is unchanged
+    let day = reporting
+        .get_sales_report(
+            "day".to_string(),
+            rung_up - chrono::Duration::hours(1),
+            rung_up + chrono::Duration::hours(1),
+        )
+        .await
+        .unwrap();
+    assert!((day.total_sales - 100.0).abs() < 0.001);
+    assert_eq!(day.adjustments.adjustment_count, 0);
+    assert!((day.sales_by_payment_method["Card"] - 110.0).abs() < 0.001);
+}
+
+#[tokio::test]
+async fn test_void_restocks_sold_pile_and_increase_is_owed_not_paid() {
+    let db = common::setup_test_db().await;
+    let service = TransactionAdjustmentService::new(db.clone());
+    let sale = seed_sale(&db, None).await;
+    let manager = seed_user(&db, "Manager").await;
+
+    // The line actually came out of a second pile of the same product
+    let product_uuid: String =
+        sqlx::query_scalar("SELECT product_uuid FROM Local_Inventory WHERE inventory_uuid = ?")
+            .bind(sale.inventory_uuid.to_string())
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    let product_uuid = Uuid::parse_str(&product_uuid).unwrap();
+    let back_room = common::TestPile {
+        location_tag: "BACK",
+        ..common::TestPile::new(product_uuid, 0)
+    }
+    .insert(&db)
+    .await;
+    sqlx::query(
+        "INSERT INTO Inventory_Movements
+         (movement_uuid, inventory_uuid, product_uuid, movement_type, quantity_change, quantity_before, quantity_after, source_uuid, terminal_id, created_at)
+         VALUES (?, ?, ?, 'sale', -2, 2, 0, ?, 'T1', ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(back_room.to_string())
+    .bind(product_uuid.to_string())
+    .bind(sale.transaction_uuid.to_string())
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    service
+        .void_line(sale.transaction_uuid, sale.item_uuid, 1, "Duplicate", None)
+        .await
+        .unwrap();
+    assert_eq!(common::on_hand(&db, back_room).await, 1);
+    assert_eq!(common::on_hand(&db, sale.inventory_uuid).await, 3);
+
+    // Corrected upwards: the customer owes the difference, nothing is collected
+    let correction = service
+        .correct_price(
+            sale.transaction_uuid,
+            sale.item_uuid,
+            60.0,
+            "Rang up the wrong printing",
+            Some(manager),
+            manager,
+        )
+        .await
+        .unwrap();
+    assert_eq!(correction.amount_delta, 11.0);
+    assert!(correction.payment_uuid.is_none());
+    assert_eq!(correction.after["balance_due"], 11.0);
+
+    let card_total = || async {
+        sqlx::query_scalar::<_, f64>(
+            "SELECT SUM(amount) FROM Payment_Methods WHERE transaction_uuid = ?",
+        )
+        .bind(sale.transaction_uuid.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .unwrap()
+    };
+    assert!((card_total().await - 55.0).abs() < 0.001);
+
+    // Voiding the rest clears the balance first and refunds only what was paid
+    let void = service
+        .void_line(sale.transaction_uuid, sale.item_uuid, 1, "Returned", None)
+        .await
+        .unwrap();
+    assert_eq!(void.amount_delta, -66.0);
+    assert_eq!(void.after["balance_due"], -11.0);
+    assert!(card_total().await.abs() < 0.001);
+    assert_eq!(common::on_hand(&db, back_room).await, 2);
+
+    let summary = service
+        .get_summary(
+            chrono::Utc::now() - chrono::Duration::hours(1),
+            chrono::Utc::now() + chrono::Duration::hours(1),
+        )
+        .await
+        .unwrap();
+    assert!(summary.balance_due.abs() < 0.001);
+}
+
+#[tokio::test]
+async fn test_attach_customer_then_retender_to_store_credit() {
+    let db = common::setup_test_db().await;
+    let service = TransactionAdjustmentService::new(db.clone());
+    let sale = seed_sale(&db, None).await;
+
+    let customer_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Customers (customer_uuid, name, store_credit, created_at) VALUES (?, 'Regular', 150.0, ?)",
+    )
+    .bind(customer_uuid.to_string())
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    // Store credit needs a customer on the sale first
+    assert!(service
+        .retender(
+            sale.transaction_uuid,
+            sale.payment_uuid,
+            PaymentMethodType::StoreCredit,
+            "Wrong tender",
+            None
+        )
+        .await
+        .is_err());
+
+    service
+        .attach_customer(
+            sale.transaction_uuid,
+            customer_uuid,
+            "Forgot loyalty lookup",
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(service
+        .attach_customer(sale.transaction_uuid, customer_uuid, "Again", None)
+        .await
+        .is_err());
+
+    let retender = service
+        .retender(
+            sale.transaction_uuid,
+            sale.payment_uuid,
+            PaymentMethodType::StoreCredit,
+            "Wrong tender",
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(retender.amount_delta, 0.0);
+    assert_eq!(retender.before["method"], "Card");
+    assert_eq!(retender.after["method"], "StoreCredit");
+
+    let credit: f64 =
+        sqlx::query_scalar("SELECT store_credit FROM Customers WHERE customer_uuid = ?")
+            .bind(customer_uuid.to_string())
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert!((credit - 40.0).abs() < 0.001);
+}
@@ -63,6 +63,15 @@ async fn setup_test_app() -> axum::Router {
     // Phase 2 Services
     let tax_service = Arc::new(services::TaxService::new(db.clone()));
     let payment_service = Arc::new(services::PaymentService::new(db.clone()));
+    let customer_display_service = Arc::new(services::CustomerDisplayService::new(db.clone()));
+    let checkout_service = Arc::new(
+        services::TransactionValidationService::new(
+            db.clone(),
+            tax_service.clone(),
+            payment_service.clone(),
+        )
+        .with_customer_display(customer_display_service.clone()),
+    );
     let holds_service = Arc::new(services::HoldsService::new(db.clone()));
     let barcode_service = Arc::new(services::BarcodeService::new(db.clone()));
     let receipt_service = Arc::new(services::ReceiptService::new(db.clone(), config.clone()));
@@ -102,6 +111,11 @@ async fn setup_test_app() -> axum::Router {
             sms_service.clone(),
         ),
     );
+    let notification_service = Arc::new(services::notification::NotificationService::new(
+        db.clone(),
+        email_service.clone(),
+        sms_service.clone(),
+    ));
 
     let app_state = api::AppState {
         db: db.clone(),
@@ -114,12 +128,27 @@ async fn setup_test_app() -> axum::Router {
             holds: holds_service,
             payments: payment_service,
             taxes: tax_service,
+            currency: Arc::new(services::CurrencyService::new(db.clone())),
+            checkout: checkout_service,
+            adjustments: Arc::new(services::TransactionAdjustmentService::new(db.clone())),
             returns: returns_service,
             trade_in: trade_in_protection_service,
+            consignment: Arc::new(services::ConsignmentService::new(db.clone())),
+            purchasing: Arc::new(services::PurchasingService::new(db.clone())),
+            shrinkage: Arc::new(services::ShrinkageService::new(db.clone())),
+            cycle_counts: Arc::new(services::CycleCountService::new(db.clone())),
+            kitting: Arc::new(services::KittingService::new(db.clone())),
+            bulk_inventory: Arc::new(services::BulkInventoryService::new(db.clone())),
+            replenishment: Arc::new(services::ReplenishmentService::new(db.clone())),
+            loyalty: Arc::new(services::LoyaltyService::new(db.clone())),
+            customers: Arc::new(services::CustomerService::new(db.clone())),
+            grading: Arc::new(services::GradingService::new(db.clone())),
         },
         system: api::state_groups::SystemServices {
             audit: Arc::new(audit::AuditService::new(db.clone())),
             events: Arc::new(vaultsync::events::EventService::new(db.clone())),
+            tournaments: Arc::new(vaultsync::events::TournamentService::new(db.clone())),
+            prizes: Arc::new(vaultsync::events::PrizeService::new(db.clone())),
             barcode: barcode_service,
             receipts: receipt_service,
             invoices: invoice_service,
@@ -129,13 +158,23 @@ async fn setup_test_app() -> axum::Router {
             catalog: catalog_lookup_service,
             serialized: serialized_inventory_service,
             locations: location_service,
+            storage: Arc::new(services::StorageService::new(db.clone())),
             reporting: reporting_service,
+            customer_display: customer_display_service,
             email: email_service,
             sms: sms_service,
             notification_scheduler,
+            notifications: notification_service,
+            media: Arc::new(services::MediaService::new(
+                db.clone(),
+                config.media_dir.clone(),
+                Box::new(services::media::NoThumbnails),
+            )),
         },
         sync_actor: sync_actor_handle,
         config: Arc::new(config.clone()),
+        metrics: Arc::new(vaultsync::monitoring::MetricsRegistry::new()),
+        alerting: Arc::new(vaultsync::monitoring::AlertingService::new(db.clone())),
     };
 
     api::create_router(app_state, &config)
@@ -0,0 +1,180 @@
+// Integration tests for bulk inventory import and export
+
+use uuid::Uuid;
+use vaultsync::services::{BulkInventoryService, DataFormat, ImportMode, ImportOptions, RowAction};
+
+mod common;
+
+async fn seed_card(
+    db: &vaultsync::database::Database,
+    name: &str,
+    set_code: &str,
+    number: &str,
+) -> Uuid {
+    let product_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Global_Catalog (product_uuid, name, category, set_code, collector_number)
+         VALUES (?, ?, 'TCG', ?, ?)",
+    )
+    .bind(product_uuid.to_string())
+    .bind(name)
+    .bind(set_code)
+    .bind(number)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    product_uuid
+}
+
+fn options(format: DataFormat, profile: &str, mode: ImportMode, dry_run: bool) -> ImportOptions {
+    ImportOptions {
+        format,
+        profile: profile.to_string(),
+        mode,
+        default_location: "MAIN".to_string(),
+        dry_run,
+    }
+}
+
+#[tokio::test]
+async fn test_dry_run_reports_diff_and_errors_then_commit_applies() {
+    let db = common::setup_test_db().await;
+    let service = BulkInventoryService::new(db.clone());
+    let bolt = seed_card(&db, "Lightning Bolt", "M10", "146").await;
+    let counterspell = seed_card(&db, "Counterspell", "MH2", "267").await;
+    common::TestPile::new(bolt, 2).insert(&db).await;
+
+    let manabox = "Name,Set code,Collector number,Foil,Quantity,Condition,Purchase price\n\
+                   Lightning Bolt,m10,0146,normal,3,near_mint,$1.50\n\
+                   Counterspell,MH2,267,foil,1,lightly_played,\n\
+                   Black Lotus,LEA,232,normal,1,near_mint,\n\
+                   Counterspell,MH2,267,normal,two,pristine,\n";
+
+    // Dry run: the diff shows what would happen, but nothing is saved
+    let report = service
+        .import(
+            manabox.as_bytes(),
+            options(DataFormat::Csv, "manabox", ImportMode::Add, true),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(!report.committed);
+    assert_eq!(report.rows, 4);
+    assert_eq!((report.created, report.updated, report.errors), (1, 1, 2));
+    assert_eq!(report.results[0].action, RowAction::Update);
+    assert_eq!(
+        report.results[0].matched_by.as_deref(),
+        Some("set_code+collector_number")
+    );
+    assert_eq!(
+        (
+            report.results[0].quantity_before,
+            report.results[0].quantity_after
+        ),
+        (2, 5)
+    );
+    assert_eq!(report.results[1].action, RowAction::Create);
+    assert!(report.results[2].errors[0].contains("No catalog match"));
+    assert_eq!(report.results[3].errors.len(), 2);
+    assert_eq!(common::product_on_hand(&db, bolt).await, 2);
+    assert_eq!(common::product_on_hand(&db, counterspell).await, 0);
+
+    // A real import with bad rows is rejected as a whole
+    let report = service
+        .import(
+            manabox.as_bytes(),
+            options(DataFormat::Csv, "manabox", ImportMode::Add, false),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(!report.committed);
+    assert_eq!(common::product_on_hand(&db, bolt).await, 2);
+
+    // Clean rows commit, streamed in awkward chunks
+    let clean: String = manabox
+        .lines()
+        .take(3)
+        .map(|l| format!("{}\n", l))
+        .collect();
+    let mut session = service
+        .begin_import(
+            options(DataFormat::Csv, "manabox", ImportMode::Add, false),
+            None,
+        )
+        .await
+        .unwrap();
+    for chunk in clean.as_bytes().chunks(7) {
+        session.push(chunk).await.unwrap();
+    }
+    let report = session.finish().await.unwrap();
+    assert!(report.committed);
+    assert_eq!(common::product_on_hand(&db, bolt).await, 5);
+    assert_eq!(common::product_on_hand(&db, counterspell).await, 1);
+
+    let (variant, condition): (Option<String>, String) = sqlx::query_as(
+        "SELECT variant_type, condition FROM Local_Inventory WHERE product_uuid = ?",
+    )
+    .bind(counterspell.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        (variant.as_deref(), condition.as_str()),
+        (Some("Foil"), "LP")
+    );
+
+    // Every change is on the ledger under the import
+    let movements: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM Inventory_Movements WHERE movement_type = 'import' AND source_uuid = ?",
+    )
+    .bind(report.import_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(movements, 2);
+}
+
+#[tokio::test]
+async fn test_export_round_trips_through_import() {
+    let db = common::setup_test_db().await;
+    let service = BulkInventoryService::new(db.clone());
+    let bolt = seed_card(&db, "Lightning Bolt", "M10", "146").await;
+    let jace = seed_card(&db, "Jace, the Mind Sculptor", "WWK", "31").await;
+    for (product_uuid, quantity, condition) in [(bolt, 4, "NM"), (jace, 1, "MP")] {
+        common::TestPile {
+            condition,
+            specific_price: Some(12.5),
+            ..common::TestPile::new(product_uuid, quantity)
+        }
+        .insert(&db)
+        .await;
+    }
+
+    for (format, profile) in [
+        (DataFormat::Csv, "vaultsync"),
+        (DataFormat::Csv, "tcgplayer"),
+        (DataFormat::Json, "deckbox"),
+    ] {
+        let exported = service.export(format, profile).await.unwrap();
+        let report = service
+            .import(
+                exported.as_bytes(),
+                options(format, profile, ImportMode::Replace, true),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            report.errors, 0,
+            "{} {}: {:?}",
+            format, profile, report.results
+        );
+        assert_eq!(report.rows, 2);
+        assert_eq!(report.unchanged, 2, "{} {}", format, profile);
+    }
+
+    let csv = service.export(DataFormat::Csv, "manabox").await.unwrap();
+    assert!(csv.contains("\"Jace, the Mind Sculptor\",WWK,31,moderately_played,normal,1"));
+}
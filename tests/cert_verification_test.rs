@@ -0,0 +1,150 @@
+// Integration tests for grading cert verification
+
+use uuid::Uuid;
+use vaultsync::services::cert_verification::{
+    FixtureCertProvider, FlagKind, OfficialCert, VerificationStatus,
+};
+use vaultsync::services::serialized_inventory::{GradingInfo, PopulationData};
+use vaultsync::services::{GradingCompany, SerializedInventoryService};
+
+mod common;
+
+async fn slab(
+    db: &std::sync::Arc<vaultsync::database::Database>,
+    name: &str,
+    grade: &str,
+    cert_number: &str,
+) -> Uuid {
+    let product_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Global_Catalog (product_uuid, name, category, collector_number)
+         VALUES (?, ?, 'TCG', '4/102')",
+    )
+    .bind(product_uuid.to_string())
+    .bind(name)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    let inventory_uuid = common::TestPile {
+        location_tag: "Case A",
+        ..common::TestPile::new(product_uuid, 1)
+    }
+    .insert(db)
+    .await;
+    let service = SerializedInventoryService::new(db.clone());
+    service
+        .add_grading(
+            inventory_uuid,
+            GradingInfo {
+                grader: "PSA".to_string(),
+                grade: grade.to_string(),
+                sub_grades: None,
+                cert_number: Some(cert_number.to_string()),
+                graded_date: None,
+                population: None,
+            },
+        )
+        .await
+        .unwrap();
+    inventory_uuid
+}
+
+fn service(db: std::sync::Arc<vaultsync::database::Database>) -> SerializedInventoryService {
+    SerializedInventoryService::new(db).with_cert_providers(vec![Box::new(
+        FixtureCertProvider::new(vec![OfficialCert {
+            grader: GradingCompany::Psa,
+            cert_number: "12345678".to_string(),
+            grade: "10".to_string(),
+            sub_grades: None,
+            card_name: Some("CHARIZARD-HOLO".to_string()),
+            set_name: Some("POKEMON GAME".to_string()),
+            card_number: Some("4".to_string()),
+            year: Some("1999".to_string()),
+            population: Some(PopulationData {
+                same_grade: 121,
+                higher_grade: 0,
+                last_updated: None,
+            }),
+        }]),
+    )])
+}
+
+#[tokio::test]
+async fn test_verified_cert_pulls_official_data() {
+    let db = common::setup_test_db().await;
+    let service = service(db.clone());
+    let item = slab(&db, "Charizard", "GEM MT 10", "1234-5678").await;
+
+    let verification = service.verify_certificate(item, None).await.unwrap();
+    assert_eq!(verification.status, VerificationStatus::Verified);
+    assert!(verification.flags.is_empty());
+    assert_eq!(verification.provider, "fixtures");
+
+    let details = service.get_serialized_item(item).await.unwrap().unwrap();
+    let grading = details.grading.unwrap();
+    assert_eq!(grading.grade, "10");
+    let population = grading.population.unwrap();
+    assert_eq!(population.same_grade, 121);
+    assert!(population.last_updated.is_some());
+    let certificate = details.certificate.unwrap();
+    assert!(certificate.verified);
+    assert_eq!(certificate.issuer, "PSA");
+
+    assert!(service
+        .get_cert_verifications(None, true)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        service
+            .get_cert_verifications(Some(item), false)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_mismatches_are_flagged() {
+    let db = common::setup_test_db().await;
+    let service = service(db.clone());
+    let genuine = slab(&db, "Charizard", "10", "12345678").await;
+    let swapped = slab(&db, "Blastoise", "9", "12345678").await;
+    let unknown = slab(&db, "Charizard", "10", "99999999").await;
+
+    let verification = service.verify_certificate(swapped, None).await.unwrap();
+    assert_eq!(verification.status, VerificationStatus::Mismatch);
+    let kinds: Vec<FlagKind> = verification.flags.iter().map(|f| f.kind).collect();
+    assert!(kinds.contains(&FlagKind::CardNameMismatch));
+    assert!(kinds.contains(&FlagKind::GradeMismatch));
+    assert!(kinds.contains(&FlagKind::DuplicateCert));
+
+    // The slab's own grade is kept when the cert doesn't match
+    let details = service.get_serialized_item(swapped).await.unwrap().unwrap();
+    assert_eq!(details.grading.unwrap().grade, "9");
+    assert!(!details.certificate.unwrap().verified);
+
+    let verification = service.verify_certificate(unknown, None).await.unwrap();
+    assert_eq!(verification.status, VerificationStatus::NotFound);
+    assert_eq!(verification.flags[0].kind, FlagKind::CertNotFound);
+
+    // Only slabs still awaiting review are listed
+    let flagged: Vec<Uuid> = service
+        .get_cert_verifications(None, true)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|v| v.inventory_uuid)
+        .collect();
+    assert_eq!(flagged.len(), 2);
+    assert!(flagged.contains(&swapped) && flagged.contains(&unknown));
+    assert!(!flagged.contains(&genuine));
+
+    // Lookups without a provider fail rather than pass
+    let err = SerializedInventoryService::new(db.clone())
+        .verify_certificate(genuine, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("No certificate lookup"));
+}
@@ -141,6 +141,140 @@ pub async fn seed_test_inventory(
     items
 }
 
+/// Create a customer with no contact details, tier or store credit
+pub fn blank_customer(name: &str) -> Customer {
+    Customer {
+        customer_uuid: Uuid::new_v4(),
+        name: name.to_string(),
+        email: None,
+        phone: None,
+        store_credit: 0.0,
+        tier: None,
+        created_at: Utc::now(),
+    }
+}
+
+/// Insert a customer into database and return its UUID
+pub async fn seed_customer(db: &Database, customer: Customer) -> Uuid {
+    db.customers
+        .insert(&customer)
+        .await
+        .expect("Failed to insert customer");
+    customer.customer_uuid
+}
+
+/// Insert a bare catalog entry and return its product UUID
+pub async fn seed_product(db: &Database, name: &str, category: &str) -> Uuid {
+    let product_uuid = Uuid::new_v4();
+    sqlx::query("INSERT INTO Global_Catalog (product_uuid, name, category) VALUES (?, ?, ?)")
+        .bind(product_uuid.to_string())
+        .bind(name)
+        .bind(category)
+        .execute(&db.pool)
+        .await
+        .expect("Failed to insert product");
+    product_uuid
+}
+
+/// A stock pile written straight to Local_Inventory, bypassing the movement ledger
+pub struct TestPile<'a> {
+    pub product_uuid: Uuid,
+    pub quantity: i32,
+    pub condition: &'a str,
+    pub variant_type: Option<&'a str>,
+    pub location_tag: &'a str,
+    pub cost_basis: Option<f64>,
+    pub specific_price: Option<f64>,
+    pub bin_location: Option<&'a str>,
+    pub serialized_details: Option<&'a str>,
+    pub supplier_uuid: Option<Uuid>,
+    pub reorder_point: Option<i32>,
+    pub received_date: Option<chrono::DateTime<Utc>>,
+}
+
+impl<'a> TestPile<'a> {
+    /// An NM pile at MAIN with nothing else set
+    pub fn new(product_uuid: Uuid, quantity: i32) -> Self {
+        Self {
+            product_uuid,
+            quantity,
+            condition: "NM",
+            variant_type: None,
+            location_tag: "MAIN",
+            cost_basis: None,
+            specific_price: None,
+            bin_location: None,
+            serialized_details: None,
+            supplier_uuid: None,
+            reorder_point: None,
+            received_date: None,
+        }
+    }
+
+    /// Insert the pile and return its inventory UUID
+    pub async fn insert(&self, db: &Database) -> Uuid {
+        let inventory_uuid = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO Local_Inventory
+             (inventory_uuid, product_uuid, quantity_on_hand, condition, variant_type, location_tag,
+              cost_basis, specific_price, bin_location, serialized_details, supplier_uuid, reorder_point,
+              received_date)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(inventory_uuid.to_string())
+        .bind(self.product_uuid.to_string())
+        .bind(self.quantity)
+        .bind(self.condition)
+        .bind(self.variant_type)
+        .bind(self.location_tag)
+        .bind(self.cost_basis)
+        .bind(self.specific_price)
+        .bind(self.bin_location)
+        .bind(self.serialized_details)
+        .bind(self.supplier_uuid.map(|u| u.to_string()))
+        .bind(self.reorder_point)
+        .bind(self.received_date.map(|d| d.to_rfc3339()))
+        .execute(&db.pool)
+        .await
+        .expect("Failed to insert inventory");
+        inventory_uuid
+    }
+}
+
+/// Quantity on hand of a single pile
+pub async fn on_hand(db: &Database, inventory_uuid: Uuid) -> i64 {
+    sqlx::query_scalar("SELECT quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?")
+        .bind(inventory_uuid.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .expect("Failed to read quantity")
+}
+
+/// Quantity on hand of a product across its live piles
+pub async fn product_on_hand(db: &Database, product_uuid: Uuid) -> i64 {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(quantity_on_hand), 0) FROM Local_Inventory
+         WHERE product_uuid = ? AND deleted_at IS NULL",
+    )
+    .bind(product_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .expect("Failed to read quantity")
+}
+
+/// Quantity on hand of a product at one location
+pub async fn on_hand_at(db: &Database, product_uuid: Uuid, location_tag: &str) -> i64 {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(quantity_on_hand), 0) FROM Local_Inventory
+         WHERE product_uuid = ? AND location_tag = ? AND deleted_at IS NULL",
+    )
+    .bind(product_uuid.to_string())
+    .bind(location_tag)
+    .fetch_one(&db.pool)
+    .await
+    .expect("Failed to read quantity")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
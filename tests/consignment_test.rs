@@ -0,0 +1,318 @@
+// Integration tests for consignment intake, sales and payouts
+
+use uuid::Uuid;
+use vaultsync::core::{Condition, TransactionItem};
+use vaultsync::services::{
+    ConsignmentIntakeRequest, ConsignmentService, ConsignmentStatus, CreateConsignorRequest,
+    PayoutMethod,
+};
+
+mod common;
+
+fn intake(consignor_uuid: Uuid, product_uuid: Uuid, quantity: i32) -> ConsignmentIntakeRequest {
+    ConsignmentIntakeRequest {
+        consignor_uuid,
+        product_uuid,
+        condition: Condition::NM,
+        quantity,
+        asking_price: 100.0,
+        minimum_price: Some(80.0),
+        commission_rate: None,
+        consignment_days: Some(60),
+        notes: None,
+    }
+}
+
+#[tokio::test]
+async fn test_sale_accrues_consignor_share_and_pays_out_store_credit() {
+    let db = common::setup_test_db().await;
+    let service = ConsignmentService::new(db.clone());
+    let product_uuid = common::seed_product(&db, "Vintage Binder", "Supplies").await;
+
+    let customer_uuid = common::seed_customer(&db, common::blank_customer("Consignor")).await;
+
+    let consignor = service
+        .create_consignor(CreateConsignorRequest {
+            name: "Consignor".to_string(),
+            email: None,
+            phone: None,
+            commission_rate: Some(0.25),
+            customer_uuid: Some(customer_uuid),
+        })
+        .await
+        .unwrap();
+
+    // Store-owned copy on hand sells first
+    common::TestPile::new(product_uuid, 1).insert(&db).await;
+    let item = service
+        .intake_item(intake(consignor.consignor_uuid, product_uuid, 2))
+        .await
+        .unwrap();
+    assert_eq!(item.commission_rate, 0.25);
+
+    let sale_item = |quantity| TransactionItem {
+        item_uuid: Uuid::new_v4(),
+        product_uuid,
+        quantity,
+        unit_price: 100.0,
+        condition: Condition::NM,
+    };
+    db.transactions
+        .execute_sale(None, None, vec![sale_item(1)])
+        .await
+        .unwrap();
+    let consignor_after = service
+        .get_consignor(consignor.consignor_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(consignor_after.balance_owed, 0.0);
+
+    // Next two come from the consignment
+    db.transactions
+        .execute_sale(None, None, vec![sale_item(2)])
+        .await
+        .unwrap();
+    let consignor_after = service
+        .get_consignor(consignor.consignor_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(consignor_after.balance_owed, 150.0);
+    let item = service
+        .get_item(item.consignment_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(item.status, ConsignmentStatus::Sold);
+    assert_eq!(item.quantity_sold, 2);
+
+    // Can't pay more than is owed
+    assert!(service
+        .pay_consignor(
+            consignor.consignor_uuid,
+            Some(200.0),
+            PayoutMethod::Cash,
+            None,
+            None
+        )
+        .await
+        .is_err());
+
+    service
+        .pay_consignor(
+            consignor.consignor_uuid,
+            Some(50.0),
+            PayoutMethod::StoreCredit,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let store_credit: f64 =
+        sqlx::query_scalar("SELECT store_credit FROM Customers WHERE customer_uuid = ?")
+            .bind(customer_uuid.to_string())
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(store_credit, 50.0);
+
+    let statement = service
+        .get_statement(
+            consignor.consignor_uuid,
+            chrono::Utc::now() - chrono::Duration::days(1),
+            chrono::Utc::now() + chrono::Duration::days(1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(statement.opening_balance, 0.0);
+    assert_eq!(statement.total_sales, 200.0);
+    assert_eq!(statement.total_commission, 50.0);
+    assert_eq!(statement.total_paid, 50.0);
+    assert_eq!(statement.closing_balance, 100.0);
+    assert!(statement.open_items.is_empty());
+}
+
+#[tokio::test]
+async fn test_expired_items_leave_the_floor_and_return_to_consignor() {
+    let db = common::setup_test_db().await;
+    let service = ConsignmentService::new(db.clone());
+    let product_uuid = common::seed_product(&db, "Vintage Binder", "Supplies").await;
+
+    let consignor = service
+        .create_consignor(CreateConsignorRequest {
+            name: "Collector".to_string(),
+            email: None,
+            phone: None,
+            commission_rate: None,
+            customer_uuid: None,
+        })
+        .await
+        .unwrap();
+    let item = service
+        .intake_item(intake(consignor.consignor_uuid, product_uuid, 1))
+        .await
+        .unwrap();
+
+    // Backdate the agreement so it is overdue
+    sqlx::query("UPDATE Consignment_Items SET expires_at = ? WHERE consignment_uuid = ?")
+        .bind((chrono::Utc::now() - chrono::Duration::days(1)).to_rfc3339())
+        .bind(item.consignment_uuid.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+    let expired = service.expire_overdue_items().await.unwrap();
+    assert_eq!(expired, vec![item.consignment_uuid]);
+    assert_eq!(common::on_hand(&db, item.inventory_uuid).await, 0);
+
+    let returned = service
+        .return_to_consignor(item.consignment_uuid)
+        .await
+        .unwrap();
+    assert_eq!(returned.status, ConsignmentStatus::Returned);
+    assert!(returned.returned_date.is_some());
+    assert!(service
+        .return_to_consignor(item.consignment_uuid)
+        .await
+        .is_err());
+
+    // No customer account, so store credit payout is refused
+    assert!(service
+        .pay_consignor(
+            consignor.consignor_uuid,
+            None,
+            PayoutMethod::StoreCredit,
+            None,
+            None
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_voids_and_returns_reverse_the_consignor_accrual() {
+    let db = common::setup_test_db().await;
+    let service = ConsignmentService::new(db.clone());
+    let product_uuid = common::seed_product(&db, "Signed Playmat", "Supplies").await;
+    let consignor = service
+        .create_consignor(CreateConsignorRequest {
+            name: "Consignor".to_string(),
+            email: None,
+            phone: None,
+            commission_rate: Some(0.25),
+            customer_uuid: None,
+        })
+        .await
+        .unwrap();
+    let item = service
+        .intake_item(intake(consignor.consignor_uuid, product_uuid, 3))
+        .await
+        .unwrap();
+
+    let sell = |quantity| {
+        let db = db.clone();
+        async move {
+            db.transactions
+                .execute_sale(
+                    None,
+                    None,
+                    vec![TransactionItem {
+                        item_uuid: Uuid::new_v4(),
+                        product_uuid,
+                        quantity,
+                        unit_price: 100.0,
+                        condition: Condition::NM,
+                    }],
+                )
+                .await
+                .unwrap()
+        }
+    };
+    let owed = || async {
+        service
+            .get_consignor(consignor.consignor_uuid)
+            .await
+            .unwrap()
+            .unwrap()
+            .balance_owed
+    };
+    let consigned = || async {
+        service
+            .get_item(item.consignment_uuid)
+            .await
+            .unwrap()
+            .unwrap()
+    };
+
+    let first = sell(2).await;
+    let second = sell(1).await;
+    assert_eq!(owed().await, 225.0);
+    assert_eq!(consigned().await.status, ConsignmentStatus::Sold);
+
+    // Voiding the sale that sold it out puts the item back on the floor
+    let checkout = vaultsync::services::TransactionValidationService::new(
+        db.clone(),
+        std::sync::Arc::new(vaultsync::services::TaxService::new(db.clone())),
+        std::sync::Arc::new(vaultsync::services::PaymentService::new(db.clone())),
+    );
+    checkout
+        .void_transaction(second.transaction_uuid, "Rang twice", "manager")
+        .await
+        .unwrap();
+    assert_eq!(owed().await, 150.0);
+    let after_void = consigned().await;
+    assert_eq!(after_void.status, ConsignmentStatus::Active);
+    assert_eq!(after_void.quantity_sold, 2);
+
+    // A line void on the first sale takes one unit back
+    let line: String =
+        sqlx::query_scalar("SELECT item_uuid FROM Transaction_Items WHERE transaction_uuid = ?")
+            .bind(first.transaction_uuid.to_string())
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    vaultsync::services::TransactionAdjustmentService::new(db.clone())
+        .void_line(
+            first.transaction_uuid,
+            Uuid::parse_str(&line).unwrap(),
+            1,
+            "Scratched",
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(owed().await, 75.0);
+    assert_eq!(consigned().await.quantity_sold, 1);
+
+    // Returning the last one to stock clears the accrual
+    vaultsync::services::ReturnsService::new(db.clone())
+        .process_return(vaultsync::services::ReturnRequest {
+            transaction_uuid: first.transaction_uuid,
+            items: vec![vaultsync::services::returns::ReturnItemRequest {
+                inventory_uuid: item.inventory_uuid,
+                quantity: 1,
+                condition: vaultsync::services::returns::ReturnCondition::Original,
+            }],
+            reason_code: vaultsync::services::ReturnReasonCode::ChangedMind,
+            reason_notes: None,
+            customer_uuid: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(owed().await, 0.0);
+    assert_eq!(consigned().await.quantity_sold, 0);
+    assert_eq!(common::on_hand(&db, item.inventory_uuid).await, 3);
+
+    // The statement nets the reversals against the sales
+    let statement = service
+        .get_statement(
+            consignor.consignor_uuid,
+            chrono::Utc::now() - chrono::Duration::days(1),
+            chrono::Utc::now() + chrono::Duration::days(1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(statement.total_sales, 0.0);
+    assert_eq!(statement.closing_balance, 0.0);
+}
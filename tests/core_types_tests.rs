@@ -310,6 +310,7 @@ mod price_info_tests {
             market_mid: 15.00,
             market_low: 10.00,
             last_sync_timestamp: Utc::now(),
+            currency: vaultsync::core::Currency::USD,
         }
     }
 
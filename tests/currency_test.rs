@@ -0,0 +1,128 @@
+// Integration tests for currencies and foreign tender
+
+use uuid::Uuid;
+use vaultsync::core::{Currency, Money};
+use vaultsync::services::payment::{PaymentMethodType, PaymentRequest, PaymentService};
+use vaultsync::services::CurrencyService;
+
+mod common;
+
+#[tokio::test]
+async fn test_foreign_tender_is_converted_and_recorded() {
+    let db = common::setup_test_db().await;
+    let currency = CurrencyService::new(db.clone());
+    let payments = PaymentService::new(db.clone());
+
+    assert_eq!(currency.get_base_currency().await.unwrap(), Currency::USD);
+    currency
+        .set_exchange_rate(Currency::EUR, 1.10)
+        .await
+        .unwrap();
+
+    let transaction_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Transactions (transaction_uuid, timestamp, transaction_type) VALUES (?, ?, 'Sale')",
+    )
+    .bind(transaction_uuid.to_string())
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let result = payments
+        .record_payment(
+            transaction_uuid,
+            PaymentRequest {
+                method: PaymentMethodType::Cash,
+                amount: 20.0,
+                reference: None,
+                card_last_four: None,
+                currency: Some(Currency::EUR),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.amount, 22.0);
+    assert_eq!(
+        result.tendered,
+        Some(Money::from_minor(2000, Currency::EUR))
+    );
+
+    let records = payments
+        .get_payments_for_transaction(transaction_uuid)
+        .await
+        .unwrap();
+    assert_eq!(records[0].amount, 22.0);
+    assert_eq!(records[0].exchange_rate, Some(1.10));
+
+    // Store credit is a base-currency balance
+    let credit = payments
+        .base_amount(&PaymentRequest {
+            method: PaymentMethodType::StoreCredit,
+            amount: 5.0,
+            reference: None,
+            card_last_four: None,
+            currency: Some(Currency::EUR),
+        })
+        .await;
+    assert!(credit.is_err());
+
+    // Unknown currencies cannot be tendered
+    let gbp = payments
+        .base_amount(&PaymentRequest {
+            method: PaymentMethodType::Card,
+            amount: 5.0,
+            reference: None,
+            card_last_four: None,
+            currency: Some(Currency::GBP),
+        })
+        .await;
+    assert!(gbp.is_err());
+}
+
+#[tokio::test]
+async fn test_changing_base_currency_rebases_rates() {
+    let db = common::setup_test_db().await;
+    let currency = CurrencyService::new(db.clone());
+
+    currency
+        .set_exchange_rate(Currency::EUR, 1.25)
+        .await
+        .unwrap();
+    currency
+        .set_exchange_rate(Currency::GBP, 1.50)
+        .await
+        .unwrap();
+    currency.set_base_currency(Currency::EUR).await.unwrap();
+
+    let settings = currency.get_settings().await.unwrap();
+    assert_eq!(settings.base_currency, Currency::EUR);
+
+    let usd = settings
+        .exchange_rates
+        .iter()
+        .find(|r| r.currency == Currency::USD)
+        .unwrap();
+    assert!((usd.rate_to_base - 0.8).abs() < 1e-9);
+    let gbp = settings
+        .exchange_rates
+        .iter()
+        .find(|r| r.currency == Currency::GBP)
+        .unwrap();
+    assert!((gbp.rate_to_base - 1.2).abs() < 1e-9);
+
+    let converted = currency.to_base(Money::from_cents(1000)).await.unwrap();
+    assert_eq!(converted, Money::from_minor(800, Currency::EUR));
+
+    // Once a sale is recorded in EUR the base is fixed
+    sqlx::query(
+        "INSERT INTO Transactions (transaction_uuid, timestamp, transaction_type) VALUES (?, ?, 'Sale')",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    assert!(currency.set_base_currency(Currency::USD).await.is_err());
+    assert_eq!(currency.get_base_currency().await.unwrap(), Currency::EUR);
+}
@@ -0,0 +1,149 @@
+// Integration tests for the customer-facing display feed
+
+use std::sync::Arc;
+use std::time::Duration;
+use vaultsync::services::payment::{PaymentMethodType, PaymentRequest, PaymentService};
+use vaultsync::services::{
+    CustomerDisplayService, DisplayEvent, DisplayPromotionRequest, TaxService,
+    TransactionItemRequest, TransactionRequest, TransactionValidationService,
+};
+
+mod common;
+
+fn promotion(title: &str, starts_in_days: i64, ends_in_days: i64) -> DisplayPromotionRequest {
+    let now = chrono::Utc::now();
+    DisplayPromotionRequest {
+        title: title.to_string(),
+        body: None,
+        image_url: None,
+        display_seconds: Some(8),
+        sort_order: None,
+        starts_at: Some(now + chrono::Duration::days(starts_in_days)),
+        ends_at: Some(now + chrono::Duration::days(ends_in_days)),
+    }
+}
+
+#[tokio::test]
+async fn test_display_follows_checkout_from_idle_to_thank_you() {
+    let db = common::setup_test_db().await;
+    let display = Arc::new(
+        CustomerDisplayService::new(db.clone()).with_thank_you_duration(Duration::from_millis(50)),
+    );
+    let checkout = TransactionValidationService::new(
+        db.clone(),
+        Arc::new(TaxService::new(db.clone())),
+        Arc::new(PaymentService::new(db.clone())),
+    )
+    .with_customer_display(display.clone());
+
+    display
+        .create_promotion(promotion("Friday Night Magic", -1, 7))
+        .await
+        .unwrap();
+    display
+        .create_promotion(promotion("Last week's sale", -8, -1))
+        .await
+        .unwrap();
+    display
+        .create_promotion(promotion("Prerelease", 3, 10))
+        .await
+        .unwrap();
+
+    let product_uuid = common::seed_product(&db, "Booster Box", "TCG").await;
+    let inventory_uuid = common::TestPile::new(product_uuid, 5).insert(&db).await;
+
+    // A fresh display shows the promotions that are currently running
+    let (current, mut rx) = display.subscribe("T1").await.unwrap();
+    match current {
+        DisplayEvent::Idle { promotions } => {
+            assert_eq!(promotions.len(), 1);
+            assert_eq!(promotions[0].title, "Friday Night Magic");
+        }
+        other => panic!("expected idle, got {}", other.name()),
+    }
+
+    // Quoting the same card twice replaces the earlier offer
+    for quantity in [1, 3] {
+        display
+            .add_trade_in_offer(
+                "T1",
+                product_uuid,
+                vaultsync::core::Condition::NM,
+                quantity,
+                2.0,
+                2.5,
+            )
+            .await
+            .unwrap();
+    }
+    let _ = rx.recv().await.unwrap();
+    match rx.recv().await.unwrap() {
+        DisplayEvent::TradeIn(view) => {
+            assert_eq!(view.offers.len(), 1);
+            assert_eq!(view.offers[0].name, "Booster Box");
+            assert!((view.total_credit - 7.5).abs() < 0.001);
+        }
+        other => panic!("expected trade-in, got {}", other.name()),
+    }
+
+    let request = TransactionRequest {
+        customer_uuid: None,
+        items: vec![TransactionItemRequest {
+            inventory_uuid,
+            quantity: 2,
+            unit_price: 100.0,
+            override_price: None,
+            override_reason: None,
+        }],
+        payments: vec![PaymentRequest {
+            method: PaymentMethodType::Cash,
+            amount: 300.0,
+            reference: None,
+            card_last_four: None,
+            currency: None,
+        }],
+        trade_in_items: None,
+        notes: None,
+        location_uuid: None,
+        terminal_id: Some("T1".to_string()),
+    };
+
+    let validation = checkout.validate_transaction(&request).await.unwrap();
+    assert!(validation.is_valid, "{:?}", validation.errors);
+    match rx.recv().await.unwrap() {
+        DisplayEvent::Cart(cart) => {
+            assert_eq!(cart.lines.len(), 1);
+            assert_eq!(cart.lines[0].name, "Booster Box");
+            assert_eq!(cart.lines[0].quantity, 2);
+            assert!((cart.total - (cart.subtotal + cart.tax_amount)).abs() < 0.001);
+        }
+        other => panic!("expected cart, got {}", other.name()),
+    }
+
+    let result = checkout.process_transaction(&request, None).await.unwrap();
+    assert!(result.success);
+    let _ = rx.recv().await.unwrap(); // cart re-validated during processing
+    match rx.recv().await.unwrap() {
+        DisplayEvent::ThankYou(view) => {
+            assert_eq!(view.transaction_uuid, Some(result.transaction_uuid));
+            assert!((view.change_given - result.change_given).abs() < 0.001);
+        }
+        other => panic!("expected thank you, got {}", other.name()),
+    }
+
+    assert!(display.mark_receipt_sent("T1", "buyer@example.com"));
+    match rx.recv().await.unwrap() {
+        DisplayEvent::ThankYou(view) => {
+            assert_eq!(view.receipt_sent_to.as_deref(), Some("buyer@example.com"))
+        }
+        other => panic!("expected thank you, got {}", other.name()),
+    }
+
+    // After the thank-you delay the display drops back to promotions
+    let idle = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("display never returned to idle")
+        .unwrap();
+    assert_eq!(idle.name(), "idle");
+    assert!(!display.mark_receipt_sent("T1", "late@example.com"));
+}
@@ -0,0 +1,187 @@
+// Integration tests for customer profiles, merges and privacy requests
+
+use uuid::Uuid;
+use vaultsync::core::Customer;
+use vaultsync::services::customer::ANONYMIZED_NAME;
+use vaultsync::services::{CustomerService, CustomerUpdate, LoyaltyService};
+
+mod common;
+
+async fn seed_history(db: &vaultsync::database::Database, customer_uuid: Uuid) {
+    let now = chrono::Utc::now().to_rfc3339();
+    let product_uuid = Uuid::new_v4().to_string();
+    let wants_list_uuid = Uuid::new_v4().to_string();
+    let event_uuid = Uuid::new_v4().to_string();
+    let statements = [
+        "INSERT INTO Transactions (transaction_uuid, customer_uuid, timestamp, transaction_type) VALUES (?1, ?2, ?3, 'Sale')",
+        "INSERT INTO Global_Catalog (product_uuid, name, category) VALUES (?4, 'Sheoldred', 'TCG')",
+        "INSERT INTO Wants_Lists (wants_list_uuid, customer_uuid, created_at) VALUES (?5, ?2, ?3)",
+        "INSERT INTO Wants_Items (item_uuid, wants_list_uuid, product_uuid, min_condition, created_at) VALUES (?1, ?5, ?4, 'NM', ?3)",
+        "INSERT INTO Holds (hold_uuid, customer_uuid, status, total_amount, deposit_amount, balance_due, expiration_date, created_at, updated_at) VALUES (?1, ?2, 'Completed', 20.0, 20.0, 0.0, ?3, ?3, ?3)",
+        "INSERT INTO Events (event_uuid, name, event_type, date, created_at) VALUES (?6, 'FNM', 'Tournament', ?3, ?3)",
+        "INSERT INTO Event_Participants (participant_uuid, event_uuid, customer_uuid, name, created_at) VALUES (?1, ?6, ?2, 'Jon', ?3)",
+    ];
+    for sql in statements {
+        sqlx::query(sql)
+            .bind(Uuid::new_v4().to_string())
+            .bind(customer_uuid.to_string())
+            .bind(&now)
+            .bind(&product_uuid)
+            .bind(&wants_list_uuid)
+            .bind(&event_uuid)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+}
+
+async fn count(db: &vaultsync::database::Database, table: &str, customer_uuid: Uuid) -> i64 {
+    sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM {} WHERE customer_uuid = ?",
+        table
+    ))
+    .bind(customer_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_update_find_and_merge_duplicates() {
+    let db = common::setup_test_db().await;
+    let customers = CustomerService::new(db.clone());
+    let loyalty = LoyaltyService::new(db.clone());
+
+    let jon = Customer {
+        phone: Some("555-123-4567".to_string()),
+        store_credit: 10.0,
+        ..common::blank_customer("Jon Smith")
+    };
+    let jon = common::seed_customer(&db, jon).await;
+    let dupe = Customer {
+        email: Some("jsmith@example.com".to_string()),
+        phone: Some("(555) 123 4567".to_string()),
+        store_credit: 5.0,
+        ..common::blank_customer("John Smith")
+    };
+    let dupe = common::seed_customer(&db, dupe).await;
+    let other = Customer {
+        email: Some("alex@example.com".to_string()),
+        ..common::blank_customer("Alex Chen")
+    };
+    let other = common::seed_customer(&db, other).await;
+
+    let updated = customers
+        .update_customer(
+            other,
+            CustomerUpdate {
+                phone: Some("555-999-0000".to_string()),
+                email: Some(String::new()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.phone.as_deref(), Some("555-999-0000"));
+    assert_eq!(updated.email, None);
+    assert_eq!(updated.name, "Alex Chen");
+
+    let candidates = customers.find_duplicates(0.5).await.unwrap();
+    assert_eq!(candidates.len(), 1);
+    let pair = [
+        candidates[0].customer.customer_uuid,
+        candidates[0].duplicate.customer_uuid,
+    ];
+    assert!(pair.contains(&jon) && pair.contains(&dupe));
+    assert_eq!(candidates[0].reasons.len(), 2);
+
+    seed_history(&db, dupe).await;
+    loyalty
+        .adjust_points(dupe, 250, "Welcome bonus".to_string(), None)
+        .await
+        .unwrap();
+
+    let summary = customers.merge_customers(jon, dupe, None).await.unwrap();
+    assert_eq!(summary.rows_moved, 4);
+    assert_eq!(summary.points_moved, 250);
+    assert!((summary.survivor.store_credit - 15.0).abs() < 0.001);
+    assert_eq!(
+        summary.survivor.email.as_deref(),
+        Some("jsmith@example.com")
+    );
+    assert_eq!(summary.survivor.phone.as_deref(), Some("555-123-4567"));
+
+    for table in ["Transactions", "Wants_Lists", "Holds", "Event_Participants"] {
+        assert_eq!(count(&db, table, jon).await, 1, "{}", table);
+        assert_eq!(count(&db, table, dupe).await, 0, "{}", table);
+    }
+    assert_eq!(loyalty.get_balance(jon).await.unwrap(), 250);
+    assert_eq!(loyalty.get_balance(dupe).await.unwrap(), 0);
+
+    // The duplicate is retired and can't be merged again
+    assert!(db
+        .customers
+        .get_all()
+        .await
+        .unwrap()
+        .iter()
+        .all(|c| c.customer_uuid != dupe));
+    assert!(customers.merge_customers(jon, dupe, None).await.is_err());
+    assert!(customers.find_duplicates(0.5).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_export_and_anonymize() {
+    let db = common::setup_test_db().await;
+    let customers = CustomerService::new(db.clone());
+    let loyalty = LoyaltyService::new(db.clone());
+
+    let jon = Customer {
+        email: Some("jon@example.com".to_string()),
+        store_credit: 12.5,
+        ..common::blank_customer("Jon Smith")
+    };
+    let jon = common::seed_customer(&db, jon).await;
+    seed_history(&db, jon).await;
+    loyalty
+        .adjust_points(jon, 40, "Promo".to_string(), None)
+        .await
+        .unwrap();
+
+    let export = customers.export_customer_data(jon, None).await.unwrap();
+    assert_eq!(export["customer"]["email"], "jon@example.com");
+    assert_eq!(export["transactions"].as_array().unwrap().len(), 1);
+    assert_eq!(export["wants_items"].as_array().unwrap().len(), 1);
+    assert_eq!(export["event_entries"].as_array().unwrap().len(), 1);
+    assert_eq!(export["loyalty_ledger"].as_array().unwrap().len(), 1);
+
+    // Outstanding store credit has to be settled first
+    assert!(customers.anonymize_customer(jon, None, None).await.is_err());
+    db.customers.update_store_credit(jon, -12.5).await.unwrap();
+
+    let summary = customers
+        .anonymize_customer(jon, Some("Customer request".to_string()), None)
+        .await
+        .unwrap();
+    assert_eq!(summary.wants_lists_removed, 1);
+    assert_eq!(summary.event_entries_renamed, 1);
+    assert_eq!(summary.points_forfeited, 40);
+
+    let profile = db.customers.get_by_id(jon).await.unwrap().unwrap();
+    assert_eq!(profile.name, ANONYMIZED_NAME);
+    assert_eq!(profile.email, None);
+    assert_eq!(count(&db, "Transactions", jon).await, 1);
+    assert_eq!(count(&db, "Holds", jon).await, 1);
+    assert_eq!(count(&db, "Wants_Lists", jon).await, 0);
+    assert_eq!(loyalty.get_balance(jon).await.unwrap(), 0);
+
+    let requests: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM Customer_Privacy_Requests WHERE customer_uuid = ?",
+    )
+    .bind(jon.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(requests, 2);
+    assert!(customers.anonymize_customer(jon, None, None).await.is_err());
+}
@@ -0,0 +1,280 @@
+// Integration tests for cycle counts
+
+use uuid::Uuid;
+use vaultsync::database::repositories::movements::MovementType;
+use vaultsync::services::{
+    AbcClass, CountEntryRequest, CountSessionStatus, CreateCountPlanRequest,
+    CreateCountSessionRequest, CycleCountService, DamageType, ShrinkSource, ShrinkageService,
+};
+
+mod common;
+
+/// A binned NM pile of its own product
+async fn binned_pile(
+    db: &vaultsync::database::Database,
+    name: &str,
+    quantity: i32,
+    cost: f64,
+    bin: &str,
+) -> Uuid {
+    let product_uuid = common::seed_product(db, name, "TCG").await;
+    common::TestPile {
+        cost_basis: Some(cost),
+        bin_location: Some(bin),
+        ..common::TestPile::new(product_uuid, quantity)
+    }
+    .insert(db)
+    .await
+}
+
+fn count(inventory_uuid: Uuid, quantity: i32) -> CountEntryRequest {
+    CountEntryRequest {
+        inventory_uuid,
+        quantity,
+    }
+}
+
+#[tokio::test]
+async fn test_multi_counter_recount_and_approval() {
+    let db = common::setup_test_db().await;
+    let service = CycleCountService::new(db.clone());
+    let binder = binned_pile(&db, "Binder Card", 10, 12.0, "A1").await;
+    let bulk = binned_pile(&db, "Bulk Common", 5, 2.0, "B1").await;
+
+    let session = service
+        .create_session(
+            CreateCountSessionRequest {
+                location_tag: "MAIN".to_string(),
+                bin_location: None,
+                blind: None,
+                recount_units: None,
+                recount_value: None,
+                notes: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(session.lines.len(), 2);
+
+    // Blind by default: counters don't see what the system expects
+    let sheet = service.get_count_sheet(session.session_uuid).await.unwrap();
+    assert!(sheet.iter().all(|l| l.expected_quantity.is_none()));
+
+    let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+    service
+        .record_counts(
+            session.session_uuid,
+            Some(first),
+            vec![count(binder, 6), count(bulk, 4)],
+        )
+        .await
+        .unwrap();
+    service
+        .record_counts(
+            session.session_uuid,
+            Some(second),
+            vec![count(binder, 6), count(bulk, 5)],
+        )
+        .await
+        .unwrap();
+
+    // Binder agrees but is past the unit threshold; bulk counters disagree
+    let session = service.finish_round(session.session_uuid).await.unwrap();
+    assert_eq!(session.status, CountSessionStatus::Recount);
+    assert_eq!(session.round, 2);
+    assert!(session.lines.iter().all(|l| l.needs_recount));
+
+    service
+        .record_counts(
+            session.session_uuid,
+            Some(first),
+            vec![count(binder, 9), count(bulk, 4)],
+        )
+        .await
+        .unwrap();
+    let session = service.finish_round(session.session_uuid).await.unwrap();
+    assert_eq!(session.status, CountSessionStatus::Review);
+    assert_eq!(session.net_variance_units, -2);
+    assert_eq!(session.net_variance_value, -14.0);
+
+    // Inventory only moves on approval
+    assert_eq!(common::on_hand(&db, binder).await, 10);
+    let approver = Uuid::new_v4();
+    let session = service
+        .approve(session.session_uuid, Some(approver))
+        .await
+        .unwrap();
+    assert_eq!(session.status, CountSessionStatus::Approved);
+    assert_eq!(common::on_hand(&db, binder).await, 9);
+    assert_eq!(common::on_hand(&db, bulk).await, 4);
+
+    let movements = db
+        .movements
+        .get_by_source(session.session_uuid)
+        .await
+        .unwrap();
+    assert_eq!(movements.len(), 2);
+    assert!(
+        movements
+            .iter()
+            .all(|m| m.movement_type == MovementType::CountAdjustment
+                && m.user_uuid == Some(approver))
+    );
+
+    // Both shortages are written off as missing stock against the session
+    let shrink = ShrinkageService::new(db.clone())
+        .get_records(None)
+        .await
+        .unwrap();
+    assert_eq!(shrink.len(), 2);
+    assert!(shrink.iter().all(|r| r.source == ShrinkSource::Count
+        && r.source_uuid == Some(session.session_uuid)
+        && r.damage_type == DamageType::Missing
+        && r.reported_by == Some(approver)));
+    let binder_shrink = shrink.iter().find(|r| r.inventory_uuid == binder).unwrap();
+    assert_eq!(binder_shrink.quantity, 1);
+    assert_eq!(binder_shrink.original_value, 12.0);
+
+    assert!(service
+        .record_counts(session.session_uuid, Some(first), vec![count(binder, 9)])
+        .await
+        .is_err());
+
+    let kpis = service
+        .get_accuracy_report(
+            chrono::Utc::now() - chrono::Duration::days(1),
+            chrono::Utc::now(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(kpis.sessions, 1);
+    assert_eq!(kpis.lines_counted, 2);
+    assert_eq!(kpis.accurate_lines, 0);
+    assert_eq!(kpis.abs_variance_units, 2);
+    assert_eq!(kpis.abs_variance_value, 14.0);
+    assert_eq!(kpis.recount_rate_pct, 100.0);
+    // 14 of 130 at cost went missing
+    assert_eq!(kpis.value_accuracy_pct, 89.23);
+}
+
+#[tokio::test]
+async fn test_concurrent_approvals_post_variances_once() {
+    let db = common::setup_test_db().await;
+    let service = CycleCountService::new(db.clone());
+    let pile = binned_pile(&db, "Booster Pack", 10, 4.0, "A1").await;
+
+    let session = service
+        .create_session(
+            CreateCountSessionRequest {
+                location_tag: "MAIN".to_string(),
+                bin_location: None,
+                blind: None,
+                recount_units: None,
+                recount_value: None,
+                notes: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    service
+        .record_counts(session.session_uuid, None, vec![count(pile, 8)])
+        .await
+        .unwrap();
+    let session = service.finish_round(session.session_uuid).await.unwrap();
+    assert_eq!(session.status, CountSessionStatus::Review);
+
+    let (first, second) = tokio::join!(
+        service.approve(session.session_uuid, None),
+        service.approve(session.session_uuid, None)
+    );
+    assert!(first.is_ok() != second.is_ok());
+    assert_eq!(common::on_hand(&db, pile).await, 8);
+    let shrink = ShrinkageService::new(db.clone())
+        .get_records(None)
+        .await
+        .unwrap();
+    assert_eq!(shrink.len(), 1);
+    assert_eq!(shrink[0].quantity, 2);
+}
+
+#[tokio::test]
+async fn test_plan_schedules_only_what_is_due() {
+    let db = common::setup_test_db().await;
+    let service = CycleCountService::new(db.clone());
+    let graded = binned_pile(&db, "Graded Slab", 1, 120.0, "CASE").await;
+    let sleeves = binned_pile(&db, "Sleeves", 5, 2.0, "WALL").await;
+
+    let plan = service
+        .create_plan(CreateCountPlanRequest {
+            name: "Weekly".to_string(),
+            location_tag: "MAIN".to_string(),
+            a_interval_days: None,
+            b_interval_days: None,
+            c_interval_days: None,
+            a_share: None,
+            b_share: None,
+            lookback_days: None,
+            recount_units: None,
+            recount_value: None,
+            blind: Some(false),
+            run_every_days: None,
+        })
+        .await
+        .unwrap();
+
+    let session = service
+        .run_plan(plan.plan_uuid, None)
+        .await
+        .unwrap()
+        .expect("never-counted stock is due");
+    assert_eq!(session.plan_uuid, Some(plan.plan_uuid));
+    let class_of = |inventory_uuid| {
+        session
+            .lines
+            .iter()
+            .find(|l| l.inventory_uuid == inventory_uuid)
+            .unwrap()
+            .abc_class
+    };
+    assert_eq!(class_of(graded), Some(AbcClass::A));
+    assert_eq!(class_of(sleeves), Some(AbcClass::B));
+
+    // Piles in an open session aren't scheduled twice
+    assert!(service
+        .run_plan(plan.plan_uuid, None)
+        .await
+        .unwrap()
+        .is_none());
+
+    service
+        .record_counts(
+            session.session_uuid,
+            None,
+            vec![count(graded, 1), count(sleeves, 5)],
+        )
+        .await
+        .unwrap();
+    let session = service.finish_round(session.session_uuid).await.unwrap();
+    service.approve(session.session_uuid, None).await.unwrap();
+
+    // Freshly counted, so nothing is due until the class interval passes
+    assert!(service
+        .run_plan(plan.plan_uuid, None)
+        .await
+        .unwrap()
+        .is_none());
+    let next_run = service.get_plan(plan.plan_uuid).await.unwrap().unwrap();
+    assert!(next_run.next_run_at > chrono::Utc::now() + chrono::Duration::days(6));
+
+    let kpis = service
+        .get_accuracy_report(
+            chrono::Utc::now() - chrono::Duration::days(1),
+            chrono::Utc::now(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(kpis.line_accuracy_pct, 100.0);
+    assert_eq!(kpis.by_class["A"].lines, 1);
+}
@@ -0,0 +1,288 @@
+// Integration tests for grading submissions
+
+use uuid::Uuid;
+use vaultsync::services::grading::{
+    CreateSubmissionRequest, GradingCompany, GradingService, ItemOutcome, ItemResultRequest,
+    SubmissionItemRequest, SubmissionStatus, AT_GRADER_LOCATION,
+};
+use vaultsync::services::SerializedInventoryService;
+
+mod common;
+
+/// A pile of Charizards in the display case
+async fn case_pile(db: &vaultsync::database::Database, quantity: i32, cost: f64) -> Uuid {
+    let product_uuid = common::seed_product(db, "Charizard", "TCG").await;
+    common::TestPile {
+        location_tag: "Case A",
+        cost_basis: Some(cost),
+        ..common::TestPile::new(product_uuid, quantity)
+    }
+    .insert(db)
+    .await
+}
+
+async fn location_and_quantity(
+    db: &vaultsync::database::Database,
+    inventory_uuid: Uuid,
+) -> (String, i32) {
+    sqlx::query_as(
+        "SELECT location_tag, quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?",
+    )
+    .bind(inventory_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap()
+}
+
+fn request(items: Vec<SubmissionItemRequest>) -> CreateSubmissionRequest {
+    CreateSubmissionRequest {
+        grader: "psa".to_string(),
+        service_tier: "Value".to_string(),
+        fee_per_item: 25.0,
+        shipping_cost: 30.0,
+        insurance_cost: 0.0,
+        grader_reference: Some("PSA-1234".to_string()),
+        notes: None,
+        items,
+    }
+}
+
+#[tokio::test]
+async fn test_submission_lifecycle() {
+    let db = common::setup_test_db().await;
+    let service = GradingService::new(db.clone());
+    let bulk = case_pile(&db, 3, 10.0).await;
+    let single = case_pile(&db, 1, 40.0).await;
+
+    let submission = service
+        .create_submission(
+            request(vec![
+                SubmissionItemRequest {
+                    inventory_uuid: bulk,
+                    declared_value: 20.0,
+                },
+                SubmissionItemRequest {
+                    inventory_uuid: single,
+                    declared_value: 60.0,
+                },
+            ]),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(submission.grader, GradingCompany::Psa);
+    assert_eq!(submission.status, SubmissionStatus::Preparing);
+    assert_eq!(submission.total_fees, 80.0);
+
+    // One unit split off the bulk pile; the single moved whole
+    assert_eq!(
+        location_and_quantity(&db, bulk).await,
+        ("Case A".to_string(), 2)
+    );
+    assert_eq!(
+        location_and_quantity(&db, single).await,
+        (AT_GRADER_LOCATION.to_string(), 1)
+    );
+    let from_bulk = submission
+        .items
+        .iter()
+        .find(|i| i.source_inventory_uuid == bulk)
+        .unwrap()
+        .clone();
+    assert_ne!(from_bulk.inventory_uuid, bulk);
+    let from_single = submission
+        .items
+        .iter()
+        .find(|i| i.source_inventory_uuid == single)
+        .unwrap()
+        .clone();
+
+    // Milestones only move forward; results wait for the return
+    service
+        .advance(
+            submission.submission_uuid,
+            SubmissionStatus::Shipped,
+            Some("UPS 1Z".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(service
+        .advance(
+            submission.submission_uuid,
+            SubmissionStatus::Shipped,
+            None,
+            None
+        )
+        .await
+        .is_err());
+    assert!(service
+        .cancel(submission.submission_uuid, None, None)
+        .await
+        .is_err());
+    service
+        .advance(
+            submission.submission_uuid,
+            SubmissionStatus::Grading,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let graded = ItemResultRequest {
+        item_uuid: from_single.item_uuid,
+        outcome: ItemOutcome::Graded,
+        grade: Some("10".to_string()),
+        cert_number: Some("81234567".to_string()),
+        sub_grades: None,
+        upcharge: 5.0,
+        graded_value: Some(500.0),
+        location_tag: Some("Slab Case".to_string()),
+    };
+    assert!(service
+        .record_results(submission.submission_uuid, vec![graded.clone()], None)
+        .await
+        .is_err());
+    let returned = service
+        .advance(
+            submission.submission_uuid,
+            SubmissionStatus::Returned,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(returned.shipped_at.is_some() && returned.returned_at.is_some());
+    assert_eq!(returned.events.len(), 4);
+
+    // Graded card becomes serialized stock with fees in its cost
+    let partial = service
+        .record_results(submission.submission_uuid, vec![graded], None)
+        .await
+        .unwrap();
+    assert_eq!(partial.status, SubmissionStatus::Returned);
+    let (cost, price): (f64, f64) = sqlx::query_as(
+        "SELECT cost_basis, specific_price FROM Local_Inventory WHERE inventory_uuid = ?",
+    )
+    .bind(single.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    // $40 raw + $25 fee + $15 shipping share + $5 upcharge
+    assert_eq!(cost, 85.0);
+    assert_eq!(price, 500.0);
+    assert_eq!(
+        location_and_quantity(&db, single).await,
+        ("Slab Case".to_string(), 1)
+    );
+    let serialized = SerializedInventoryService::new(db.clone())
+        .get_serialized_item(single)
+        .await
+        .unwrap()
+        .unwrap();
+    let grading = serialized.grading.unwrap();
+    assert_eq!(
+        (grading.grader.as_str(), grading.grade.as_str()),
+        ("PSA", "10")
+    );
+    assert_eq!(serialized.serial_number.as_deref(), Some("81234567"));
+    assert_eq!(serialized.provenance.len(), 1);
+
+    // Ungradable card goes back raw; the submission completes
+    let done = service
+        .record_results(
+            submission.submission_uuid,
+            vec![ItemResultRequest {
+                item_uuid: from_bulk.item_uuid,
+                outcome: ItemOutcome::Ungradable,
+                grade: None,
+                cert_number: None,
+                sub_grades: None,
+                upcharge: 0.0,
+                graded_value: None,
+                location_tag: None,
+            }],
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(done.status, SubmissionStatus::Completed);
+    assert_eq!(
+        location_and_quantity(&db, from_bulk.inventory_uuid).await,
+        ("Case A".to_string(), 1)
+    );
+
+    let report = service
+        .roi_report(
+            chrono::Utc::now() - chrono::Duration::days(1),
+            chrono::Utc::now() + chrono::Duration::days(1),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!((report.totals.graded, report.totals.ungradable), (1, 1));
+    assert_eq!(report.totals.grading_fees, 85.0);
+    // 500 + 20 returned, less 80 declared and 85 fees
+    assert_eq!(report.totals.value_added, 355.0);
+    assert_eq!(report.by_grader.len(), 1);
+    assert_eq!(report.grade_distribution[0].grade, "10");
+}
+
+#[tokio::test]
+async fn test_cancel_restocks_cards() {
+    let db = common::setup_test_db().await;
+    let service = GradingService::new(db.clone());
+    let bulk = case_pile(&db, 2, 10.0).await;
+
+    assert!(service
+        .create_submission(
+            CreateSubmissionRequest {
+                grader: "ABC".to_string(),
+                ..request(vec![SubmissionItemRequest {
+                    inventory_uuid: bulk,
+                    declared_value: 20.0,
+                }])
+            },
+            None,
+        )
+        .await
+        .is_err());
+
+    let items = vec![
+        SubmissionItemRequest {
+            inventory_uuid: bulk,
+            declared_value: 20.0,
+        };
+        2
+    ];
+    let submission = service
+        .create_submission(request(items), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        location_and_quantity(&db, bulk).await,
+        (AT_GRADER_LOCATION.to_string(), 1)
+    );
+
+    let cancelled = service
+        .cancel(
+            submission.submission_uuid,
+            Some("Changed our minds".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(cancelled.status, SubmissionStatus::Cancelled);
+    assert_eq!(
+        location_and_quantity(&db, bulk).await,
+        ("Case A".to_string(), 2)
+    );
+    let live: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM Local_Inventory WHERE location_tag = ? AND deleted_at IS NULL",
+    )
+    .bind(AT_GRADER_LOCATION)
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(live, 0);
+}
@@ -391,6 +391,7 @@ async fn test_buylist_functionality() {
         market_mid: 300.0,
         market_low: 250.0,
         last_sync_timestamp: chrono::Utc::now(),
+        currency: vaultsync::core::Currency::USD,
     };
     db.pricing.insert_matrix(&price_info).await.unwrap();
 
@@ -436,6 +437,7 @@ fn test_price_info_creation() {
         market_mid: 10.50,
         market_low: 8.25,
         last_sync_timestamp: chrono::Utc::now(),
+        currency: vaultsync::core::Currency::USD,
     };
 
     assert_eq!(price_info.market_mid, 10.50);
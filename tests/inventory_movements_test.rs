@@ -0,0 +1,178 @@
+// Integration tests for the inventory movement ledger
+
+use vaultsync::core::Category;
+use vaultsync::database::repositories::movements::MovementType;
+
+mod common;
+
+#[tokio::test]
+async fn test_every_quantity_change_is_recorded() {
+    let db = common::setup_test_db().await;
+
+    let product = common::create_test_product("Test Card", Category::TCG);
+    db.products.insert(&product).await.unwrap();
+    let item = common::create_test_inventory_item(product.product_uuid, 10);
+    db.inventory.insert(&item).await.unwrap();
+
+    db.inventory
+        .update_quantity(item.inventory_uuid, -3)
+        .await
+        .unwrap();
+
+    let clerk = uuid::Uuid::new_v4();
+    let sale = db
+        .transactions
+        .execute_sale(
+            None,
+            Some(clerk),
+            vec![common::create_test_transaction_item(
+                product.product_uuid,
+                2,
+                5.0,
+            )],
+        )
+        .await
+        .unwrap();
+    let buy = db
+        .transactions
+        .execute_buy(
+            None,
+            None,
+            vec![common::create_test_transaction_item(
+                product.product_uuid,
+                4,
+                1.0,
+            )],
+        )
+        .await
+        .unwrap();
+
+    let card = db
+        .movements
+        .get_stock_card(
+            item.inventory_uuid,
+            chrono::Utc::now() - chrono::Duration::hours(1),
+            chrono::Utc::now(),
+        )
+        .await
+        .unwrap();
+
+    let kinds: Vec<MovementType> = card.movements.iter().map(|m| m.movement_type).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            MovementType::ManualAdjustment,
+            MovementType::ManualAdjustment,
+            MovementType::Sale,
+            MovementType::Buy,
+        ]
+    );
+    assert_eq!(card.opening_quantity, 0);
+    assert_eq!(card.closing_quantity, 9);
+
+    // Each movement picks up where the previous one left off
+    for pair in card.movements.windows(2) {
+        assert_eq!(pair[0].quantity_after, pair[1].quantity_before);
+    }
+    for movement in &card.movements {
+        assert_eq!(
+            movement.quantity_after - movement.quantity_before,
+            movement.quantity_change
+        );
+        assert_eq!(movement.terminal_id, db.node_id);
+    }
+
+    let sale_movements = db
+        .movements
+        .get_by_source(sale.transaction_uuid)
+        .await
+        .unwrap();
+    assert_eq!(sale_movements.len(), 1);
+    assert_eq!(sale_movements[0].quantity_change, -2);
+    assert_eq!(sale_movements[0].user_uuid, Some(clerk));
+    assert_eq!(
+        db.movements
+            .get_by_source(buy.transaction_uuid)
+            .await
+            .unwrap()[0]
+            .quantity_change,
+        4
+    );
+}
+
+#[tokio::test]
+async fn test_quantity_as_of() {
+    let db = common::setup_test_db().await;
+
+    let product = common::create_test_product("Test Card", Category::TCG);
+    db.products.insert(&product).await.unwrap();
+    let first = common::create_test_inventory_item(product.product_uuid, 10);
+    db.inventory.insert(&first).await.unwrap();
+    let second = common::create_test_inventory_item(product.product_uuid, 5);
+    db.inventory.insert(&second).await.unwrap();
+
+    let before_sale = chrono::Utc::now();
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    db.inventory
+        .update_quantity(first.inventory_uuid, -4)
+        .await
+        .unwrap();
+
+    let long_ago = before_sale - chrono::Duration::days(1);
+    assert_eq!(
+        db.movements
+            .quantity_as_of(first.inventory_uuid, long_ago)
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        db.movements
+            .quantity_as_of(first.inventory_uuid, before_sale)
+            .await
+            .unwrap(),
+        10
+    );
+    assert_eq!(
+        db.movements
+            .quantity_as_of(first.inventory_uuid, chrono::Utc::now())
+            .await
+            .unwrap(),
+        6
+    );
+    assert_eq!(
+        db.movements
+            .product_quantity_as_of(product.product_uuid, before_sale)
+            .await
+            .unwrap(),
+        15
+    );
+    assert_eq!(
+        db.movements
+            .product_quantity_as_of(product.product_uuid, chrono::Utc::now())
+            .await
+            .unwrap(),
+        11
+    );
+}
+
+#[tokio::test]
+async fn test_movements_are_immutable() {
+    let db = common::setup_test_db().await;
+
+    let product = common::create_test_product("Test Card", Category::TCG);
+    db.products.insert(&product).await.unwrap();
+    let item = common::create_test_inventory_item(product.product_uuid, 3);
+    db.inventory.insert(&item).await.unwrap();
+
+    assert!(
+        sqlx::query("UPDATE Inventory_Movements SET quantity_after = 100")
+            .execute(&db.pool)
+            .await
+            .is_err()
+    );
+    assert!(sqlx::query("DELETE FROM Inventory_Movements")
+        .execute(&db.pool)
+        .await
+        .is_err());
+}
@@ -0,0 +1,247 @@
+// Integration tests for kits and bills of materials
+
+use std::sync::Arc;
+use uuid::Uuid;
+use vaultsync::database::repositories::movements::MovementType;
+use vaultsync::services::{
+    AssembleRequest, BomComponentRequest, BomType, BreakRequest, ConversionType, KittingService,
+    PaymentMethodType, PaymentRequest, PaymentService, SetBomRequest, TaxService,
+    TransactionItemRequest, TransactionRequest, TransactionValidationService,
+};
+
+mod common;
+
+/// (quantity, cost) summed over a product's live piles
+async fn stock(db: &vaultsync::database::Database, product_uuid: Uuid) -> (i64, Option<f64>) {
+    sqlx::query_as(
+        "SELECT COALESCE(SUM(quantity_on_hand), 0), MAX(cost_basis) FROM Local_Inventory
+         WHERE product_uuid = ? AND deleted_at IS NULL",
+    )
+    .bind(product_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap()
+}
+
+fn component(component_product_uuid: Uuid, quantity: i32) -> BomComponentRequest {
+    BomComponentRequest {
+        component_product_uuid,
+        quantity,
+        cost_weight: None,
+    }
+}
+
+#[tokio::test]
+async fn test_break_booster_box_into_packs() {
+    let db = common::setup_test_db().await;
+    let service = KittingService::new(db.clone());
+    let box_uuid = common::seed_product(&db, "Booster Box", "TCG").await;
+    let pack_uuid = common::seed_product(&db, "Booster Pack", "TCG").await;
+    let box_pile = common::TestPile {
+        condition: "New",
+        cost_basis: Some(108.0),
+        ..common::TestPile::new(box_uuid, 2)
+    }
+    .insert(&db)
+    .await;
+
+    assert!(service
+        .set_bom(
+            box_uuid,
+            SetBomRequest {
+                bom_type: BomType::Sealed,
+                components: vec![component(box_uuid, 1)],
+                notes: None,
+            },
+        )
+        .await
+        .is_err());
+    service
+        .set_bom(
+            box_uuid,
+            SetBomRequest {
+                bom_type: BomType::Sealed,
+                components: vec![component(pack_uuid, 36)],
+                notes: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    assert!(service
+        .break_product(
+            BreakRequest {
+                inventory_uuid: box_pile,
+                quantity: 3,
+            },
+            None,
+        )
+        .await
+        .is_err());
+
+    let conversion = service
+        .break_product(
+            BreakRequest {
+                inventory_uuid: box_pile,
+                quantity: 1,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(conversion.conversion_type, ConversionType::Break);
+    assert_eq!(conversion.total_cost, 108.0);
+    assert_eq!(stock(&db, box_uuid).await.0, 1);
+    assert_eq!(stock(&db, pack_uuid).await, (36, Some(3.0)));
+
+    // The second box lands on the same pack pile
+    service
+        .break_product(
+            BreakRequest {
+                inventory_uuid: box_pile,
+                quantity: 1,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(stock(&db, box_uuid).await.0, 0);
+    assert_eq!(stock(&db, pack_uuid).await, (72, Some(3.0)));
+
+    let movements = db
+        .movements
+        .get_by_source(conversion.conversion_uuid)
+        .await
+        .unwrap();
+    assert_eq!(movements.len(), 2);
+    assert!(movements
+        .iter()
+        .all(|m| m.movement_type == MovementType::Break));
+}
+
+#[tokio::test]
+async fn test_assemble_and_sell_kits_from_components() {
+    let db = common::setup_test_db().await;
+    let service = KittingService::new(db.clone());
+    let kit_uuid = common::seed_product(&db, "Starter Kit", "TCG").await;
+    let deck_uuid = common::seed_product(&db, "Starter Deck", "TCG").await;
+    let sleeves_uuid = common::seed_product(&db, "Sleeves", "TCG").await;
+    common::TestPile {
+        condition: "New",
+        cost_basis: Some(20.0),
+        ..common::TestPile::new(deck_uuid, 4)
+    }
+    .insert(&db)
+    .await;
+    common::TestPile {
+        condition: "New",
+        cost_basis: Some(5.0),
+        ..common::TestPile::new(sleeves_uuid, 8)
+    }
+    .insert(&db)
+    .await;
+
+    service
+        .set_bom(
+            kit_uuid,
+            SetBomRequest {
+                bom_type: BomType::Kit,
+                components: vec![component(deck_uuid, 1), component(sleeves_uuid, 2)],
+                notes: Some("Deck plus two packs of sleeves".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(service.buildable_quantity(kit_uuid, None).await.unwrap(), 4);
+
+    let assembled = service
+        .assemble(
+            kit_uuid,
+            AssembleRequest {
+                quantity: 1,
+                location_tag: "MAIN".to_string(),
+                condition: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(assembled.total_cost, 30.0);
+    let kit_pile = assembled.lines[0].inventory_uuid;
+    assert_eq!(stock(&db, kit_uuid).await, (1, Some(30.0)));
+    assert_eq!(stock(&db, deck_uuid).await.0, 3);
+    assert_eq!(stock(&db, sleeves_uuid).await.0, 6);
+
+    // One line for three kits: one assembled, two built from components
+    let checkout = TransactionValidationService::new(
+        db.clone(),
+        Arc::new(TaxService::new(db.clone())),
+        Arc::new(PaymentService::new(db.clone())),
+    );
+    let request = |quantity| TransactionRequest {
+        customer_uuid: None,
+        items: vec![TransactionItemRequest {
+            inventory_uuid: kit_pile,
+            quantity,
+            unit_price: 40.0,
+            override_price: None,
+            override_reason: None,
+        }],
+        payments: vec![PaymentRequest {
+            method: PaymentMethodType::Cash,
+            amount: 500.0,
+            reference: None,
+            card_last_four: None,
+            currency: None,
+        }],
+        trade_in_items: None,
+        notes: None,
+        location_uuid: None,
+        terminal_id: None,
+    };
+
+    let validation = checkout.validate_transaction(&request(5)).await.unwrap();
+    assert!(!validation.is_valid);
+
+    let result = checkout
+        .process_transaction(&request(3), None)
+        .await
+        .unwrap();
+    assert!(result.success, "{:?}", result.errors);
+    assert_eq!(stock(&db, kit_uuid).await.0, 0);
+    assert_eq!(stock(&db, deck_uuid).await.0, 1);
+    assert_eq!(stock(&db, sleeves_uuid).await.0, 2);
+
+    let kit_sales = service.get_conversions(Some(kit_uuid), 10).await.unwrap();
+    assert_eq!(kit_sales[0].conversion_type, ConversionType::KitSale);
+    assert_eq!(kit_sales[0].quantity, 2);
+    assert_eq!(kit_sales[0].transaction_uuid, Some(result.transaction_uuid));
+
+    // Voiding gives back the components and the one assembled kit
+    checkout
+        .void_transaction(result.transaction_uuid, "Customer changed mind", "manager")
+        .await
+        .unwrap();
+    assert_eq!(stock(&db, kit_uuid).await.0, 1);
+    assert_eq!(stock(&db, deck_uuid).await.0, 3);
+    assert_eq!(stock(&db, sleeves_uuid).await.0, 6);
+
+    // Product-level sales draw on components the same way
+    db.transactions
+        .execute_sale(
+            None,
+            None,
+            vec![vaultsync::core::TransactionItem {
+                item_uuid: Uuid::new_v4(),
+                product_uuid: kit_uuid,
+                quantity: 2,
+                unit_price: 40.0,
+                condition: vaultsync::core::Condition::New,
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(stock(&db, kit_uuid).await.0, 0);
+    assert_eq!(stock(&db, deck_uuid).await.0, 2);
+    assert_eq!(stock(&db, sleeves_uuid).await.0, 4);
+}
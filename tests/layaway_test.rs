@@ -0,0 +1,171 @@
+// Integration tests for layaway plans on holds
+
+use uuid::Uuid;
+use vaultsync::services::holds::{CreateHoldRequest, HoldItemRequest};
+use vaultsync::services::layaway::{CreateLayawayPlanRequest, ForfeiturePolicy};
+use vaultsync::services::{HoldStatus, HoldsService, InstallmentStatus};
+
+mod common;
+
+async fn seed_customer_and_stock(
+    db: &vaultsync::database::Database,
+    quantity: i32,
+) -> (Uuid, Uuid) {
+    let customer_uuid = common::seed_customer(db, common::blank_customer("Layaway Customer")).await;
+    let product_uuid = common::seed_product(db, "Booster Box", "TCG").await;
+    let inventory_uuid = common::TestPile::new(product_uuid, quantity)
+        .insert(db)
+        .await;
+    (customer_uuid, inventory_uuid)
+}
+
+#[tokio::test]
+async fn test_plan_schedules_installments_and_forfeits_with_restocking_fee() {
+    let db = common::setup_test_db().await;
+    let holds = HoldsService::new(db.clone());
+    let (customer_uuid, inventory_uuid) = seed_customer_and_stock(&db, 2).await;
+
+    let plan = holds
+        .create_layaway_plan(CreateLayawayPlanRequest {
+            name: "3 x fortnightly".to_string(),
+            installment_count: 3,
+            installment_interval_days: 14,
+            minimum_deposit_percent: Some(0.1),
+            grace_period_days: Some(3),
+            late_fee: Some(5.0),
+            forfeiture_policy: Some(ForfeiturePolicy::RetainRestockingFee),
+            restocking_fee_percent: Some(0.1),
+        })
+        .await
+        .expect("Failed to create plan");
+
+    let summary = holds
+        .create_hold(CreateHoldRequest {
+            customer_uuid,
+            items: vec![HoldItemRequest {
+                inventory_uuid,
+                quantity: 1,
+                unit_price: 130.0,
+            }],
+            deposit_amount: 40.0,
+            deposit_method: "Cash".to_string(),
+            notes: None,
+            hold_days: None,
+            plan_uuid: Some(plan.plan_uuid),
+        })
+        .await
+        .expect("Failed to create hold");
+
+    assert_eq!(summary.installments.len(), 3);
+    assert_eq!(summary.installments[0].amount_due, 30.0);
+
+    // Below the next installment is rejected
+    assert!(holds
+        .make_payment(summary.hold.hold_uuid, 10.0, "Cash")
+        .await
+        .is_err());
+
+    let after = holds
+        .make_payment(summary.hold.hold_uuid, 30.0, "Cash")
+        .await
+        .expect("Failed to pay installment");
+    assert_eq!(after.installments[0].status, InstallmentStatus::Paid);
+    assert_eq!(after.installments[1].status, InstallmentStatus::Pending);
+
+    let settlement = holds
+        .forfeit_hold(summary.hold.hold_uuid)
+        .await
+        .expect("Failed to forfeit hold");
+    assert_eq!(settlement.retained, 13.0);
+    assert_eq!(settlement.returned, 57.0);
+
+    let customer = db
+        .customers
+        .get_by_id(customer_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!((customer.store_credit - 57.0).abs() < 0.001);
+
+    let expired = holds
+        .get_hold(summary.hold.hold_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(expired.hold.status, HoldStatus::Expired);
+
+    assert_eq!(common::on_hand(&db, inventory_uuid).await, 2);
+
+    let report = holds.get_layaway_liability_report().await.unwrap();
+    assert_eq!(report.active_holds, 0);
+}
+
+#[tokio::test]
+async fn test_late_fees_are_not_charged_restocking() {
+    let db = common::setup_test_db().await;
+    let holds = HoldsService::new(db.clone());
+    let (customer_uuid, inventory_uuid) = seed_customer_and_stock(&db, 1).await;
+
+    let plan = holds
+        .create_layaway_plan(CreateLayawayPlanRequest {
+            name: "2 x weekly".to_string(),
+            installment_count: 2,
+            installment_interval_days: 7,
+            minimum_deposit_percent: Some(0.1),
+            grace_period_days: Some(0),
+            late_fee: Some(10.0),
+            forfeiture_policy: Some(ForfeiturePolicy::RetainRestockingFee),
+            restocking_fee_percent: Some(0.5),
+        })
+        .await
+        .unwrap();
+    let summary = holds
+        .create_hold(CreateHoldRequest {
+            customer_uuid,
+            items: vec![HoldItemRequest {
+                inventory_uuid,
+                quantity: 1,
+                unit_price: 100.0,
+            }],
+            deposit_amount: 20.0,
+            deposit_method: "Cash".to_string(),
+            notes: None,
+            hold_days: None,
+            plan_uuid: Some(plan.plan_uuid),
+        })
+        .await
+        .unwrap();
+    let hold_uuid = summary.hold.hold_uuid;
+
+    // Both installments fall overdue
+    sqlx::query("UPDATE Hold_Installments SET due_date = ? WHERE hold_uuid = ?")
+        .bind((chrono::Utc::now() - chrono::Duration::days(2)).to_rfc3339())
+        .bind(hold_uuid.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(holds.assess_late_fees().await.unwrap().len(), 2);
+
+    let late = holds.get_hold(hold_uuid).await.unwrap().unwrap();
+    assert_eq!(late.hold.total_amount, 100.0);
+    assert_eq!(late.hold.late_fees, 20.0);
+    assert_eq!(late.hold.balance_due, 100.0);
+
+    holds.make_payment(hold_uuid, 50.0, "Cash").await.unwrap();
+
+    // 50% of the $100 merchandise, not of $120 with late fees
+    let settlement = holds.forfeit_hold(hold_uuid).await.unwrap();
+    assert_eq!(settlement.amount_paid, 70.0);
+    assert_eq!(settlement.retained, 50.0);
+    assert_eq!(settlement.returned, 20.0);
+
+    // A second forfeit settles nothing
+    assert!(holds.forfeit_hold(hold_uuid).await.is_err());
+    let customer = db
+        .customers
+        .get_by_id(customer_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!((customer.store_credit - 20.0).abs() < 0.001);
+}
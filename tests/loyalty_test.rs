@@ -0,0 +1,353 @@
+// Integration tests for loyalty points and tiers
+
+use std::sync::Arc;
+use uuid::Uuid;
+use vaultsync::core::Customer;
+use vaultsync::services::loyalty::{apply_synced_entry, BASE_TIER};
+use vaultsync::services::transaction::{
+    TransactionItemRequest, TransactionRequest, TransactionValidationService,
+};
+use vaultsync::services::{
+    CategoryRate, LoyaltyEntryType, LoyaltyService, LoyaltySettings, LoyaltyTier,
+    PaymentMethodType, PaymentRequest, PaymentService, TaxService,
+};
+
+mod common;
+
+/// Ten NM copies of a fresh product in the given category
+async fn product_pile(db: &vaultsync::database::Database, name: &str, category: &str) -> Uuid {
+    let product_uuid = common::seed_product(db, name, category).await;
+    common::TestPile::new(product_uuid, 10).insert(db).await
+}
+
+fn line(inventory_uuid: Uuid, unit_price: f64) -> TransactionItemRequest {
+    TransactionItemRequest {
+        inventory_uuid,
+        quantity: 1,
+        unit_price,
+        override_price: None,
+        override_reason: None,
+    }
+}
+
+fn pay(method: PaymentMethodType, amount: f64) -> PaymentRequest {
+    PaymentRequest {
+        method,
+        amount,
+        reference: None,
+        card_last_four: None,
+        currency: None,
+    }
+}
+
+fn sale(
+    customer_uuid: Uuid,
+    items: Vec<TransactionItemRequest>,
+    payments: Vec<PaymentRequest>,
+) -> TransactionRequest {
+    TransactionRequest {
+        customer_uuid: Some(customer_uuid),
+        items,
+        payments,
+        trade_in_items: None,
+        notes: None,
+        location_uuid: None,
+        terminal_id: None,
+    }
+}
+
+#[tokio::test]
+async fn test_earn_redeem_expire_and_tiers() {
+    let db = common::setup_test_db().await;
+    let loyalty = LoyaltyService::new(db.clone());
+    let checkout = TransactionValidationService::new(
+        db.clone(),
+        Arc::new(TaxService::new(db.clone())),
+        Arc::new(PaymentService::new(db.clone())),
+    );
+    loyalty
+        .update_settings(LoyaltySettings {
+            points_per_dollar: 1.0,
+            point_value: 0.05,
+            expiry_months: 12,
+            tier_window_days: 365,
+        })
+        .await
+        .unwrap();
+    loyalty
+        .set_category_rates(vec![CategoryRate {
+            category: "TCG".to_string(),
+            points_per_dollar: 2.0,
+        }])
+        .await
+        .unwrap();
+    loyalty
+        .set_tiers(vec![
+            LoyaltyTier {
+                tier_name: "Silver".to_string(),
+                min_spend: 100.0,
+            },
+            LoyaltyTier {
+                tier_name: "Gold".to_string(),
+                min_spend: 1000.0,
+            },
+        ])
+        .await
+        .unwrap();
+    assert!(loyalty
+        .set_tiers(vec![LoyaltyTier {
+            tier_name: BASE_TIER.to_string(),
+            min_spend: 0.0,
+        }])
+        .await
+        .is_err());
+
+    let robin = common::seed_customer(&db, common::blank_customer("Robin")).await;
+    let wholesale = Customer {
+        tier: Some("Wholesale".to_string()),
+        ..common::blank_customer("Robin")
+    };
+    let wholesale = common::seed_customer(&db, wholesale).await;
+    let booster_box = product_pile(&db, "Booster Box", "TCG").await;
+    let sleeves = product_pile(&db, "Sleeves", "Accessory").await;
+
+    // $100 of TCG at 2/$ and $20 of accessories at the 1/$ default
+    let first = checkout
+        .process_transaction(
+            &sale(
+                robin,
+                vec![line(booster_box, 100.0), line(sleeves, 20.0)],
+                vec![pay(PaymentMethodType::Cash, 500.0)],
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(first.success, "{:?}", first.errors);
+    let account = loyalty.get_account(robin).await.unwrap();
+    assert_eq!(account.points, 220);
+    assert_eq!(account.redeemable_value, 11.0);
+    assert_eq!(account.tier.as_deref(), Some("Silver"));
+    assert_eq!(account.next_tier.unwrap().tier_name, "Gold");
+    let cached: i64 =
+        sqlx::query_scalar("SELECT loyalty_points FROM Customers WHERE customer_uuid = ?")
+            .bind(robin.to_string())
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(cached, 220);
+
+    // Points can't be spent without a customer, or beyond the balance
+    let mut anonymous = sale(
+        robin,
+        vec![line(sleeves, 20.0)],
+        vec![
+            pay(PaymentMethodType::LoyaltyPoints, 5.0),
+            pay(PaymentMethodType::Cash, 500.0),
+        ],
+    );
+    anonymous.customer_uuid = None;
+    assert!(
+        !checkout
+            .validate_transaction(&anonymous)
+            .await
+            .unwrap()
+            .is_valid
+    );
+    assert!(checkout
+        .process_transaction(
+            &sale(
+                robin,
+                vec![line(sleeves, 20.0)],
+                vec![
+                    pay(PaymentMethodType::LoyaltyPoints, 50.0),
+                    pay(PaymentMethodType::Cash, 500.0),
+                ],
+            ),
+            None,
+        )
+        .await
+        .is_err());
+    assert_eq!(loyalty.get_balance(robin).await.unwrap(), 220);
+
+    // $5 in points is 100 points, and that share of the sale earns nothing
+    let second = checkout
+        .process_transaction(
+            &sale(
+                robin,
+                vec![line(sleeves, 20.0)],
+                vec![
+                    pay(PaymentMethodType::LoyaltyPoints, 5.0),
+                    pay(PaymentMethodType::Cash, 500.0),
+                ],
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(second.success, "{:?}", second.errors);
+    let earned = (20.0 * (second.total - 5.0) / second.total + 1e-6).floor() as i64;
+    assert_eq!(
+        loyalty.get_balance(robin).await.unwrap(),
+        220 - 100 + earned
+    );
+    let history = loyalty.get_history(robin, 10).await.unwrap();
+    assert!(history
+        .iter()
+        .any(|e| e.entry_type == LoyaltyEntryType::Redeem
+            && e.points == -100
+            && e.transaction_uuid == Some(second.transaction_uuid)));
+
+    // An old lot of 150 absorbs the 100 redeemed first; the other 50 expire once
+    sqlx::query(
+        "INSERT INTO Loyalty_Ledger (entry_uuid, customer_uuid, entry_type, points, expires_at, node_id, created_at)
+         VALUES (?, ?, 'earn', 150, ?, 'test', ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(robin.to_string())
+    .bind((chrono::Utc::now() - chrono::Duration::days(1)).to_rfc3339())
+    .bind((chrono::Utc::now() - chrono::Duration::days(400)).to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(loyalty.expire_points().await.unwrap(), 50);
+    assert_eq!(loyalty.expire_points().await.unwrap(), 0);
+    let balance = loyalty.get_balance(robin).await.unwrap();
+    assert_eq!(balance, 220 - 100 + earned + 150 - 50);
+
+    // Replaying a synced row doesn't count it twice
+    let expiry = loyalty
+        .get_history(robin, 10)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|e| e.entry_type == LoyaltyEntryType::Expire)
+        .unwrap();
+    apply_synced_entry(&db.pool, &expiry).await.unwrap();
+    assert_eq!(loyalty.get_balance(robin).await.unwrap(), balance);
+
+    // Manual adjustments need a reason and can't overdraw
+    assert!(loyalty
+        .adjust_points(robin, 10, " ".to_string(), None)
+        .await
+        .is_err());
+    assert!(loyalty
+        .adjust_points(robin, -(balance + 1), "Goodwill".to_string(), None)
+        .await
+        .is_err());
+    loyalty
+        .adjust_points(robin, 25, "Birthday bonus".to_string(), None)
+        .await
+        .unwrap();
+    assert_eq!(loyalty.get_balance(robin).await.unwrap(), balance + 25);
+
+    // Hand-assigned tiers are left alone; spend leaving the window demotes
+    assert!(loyalty.evaluate_tier(wholesale).await.unwrap().is_none());
+    sqlx::query("UPDATE Transactions SET timestamp = ? WHERE customer_uuid = ?")
+        .bind((chrono::Utc::now() - chrono::Duration::days(400)).to_rfc3339())
+        .bind(robin.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let changes = loyalty.evaluate_all_tiers().await.unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].old_tier.as_deref(), Some("Silver"));
+    assert_eq!(changes[0].new_tier, BASE_TIER);
+    assert_eq!(
+        db.customers
+            .get_by_id(robin)
+            .await
+            .unwrap()
+            .unwrap()
+            .tier
+            .as_deref(),
+        Some(BASE_TIER)
+    );
+    assert_eq!(loyalty.get_tier_history(robin).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_direct_sales_earn_and_voids_reverse_points() {
+    let db = common::setup_test_db().await;
+    let loyalty = LoyaltyService::new(db.clone());
+    let checkout = TransactionValidationService::new(
+        db.clone(),
+        Arc::new(TaxService::new(db.clone())),
+        Arc::new(PaymentService::new(db.clone())),
+    );
+    loyalty
+        .update_settings(LoyaltySettings {
+            points_per_dollar: 1.0,
+            point_value: 0.05,
+            expiry_months: 0,
+            tier_window_days: 365,
+        })
+        .await
+        .unwrap();
+    let robin = common::seed_customer(&db, common::blank_customer("Robin")).await;
+    let product_uuid = common::seed_product(&db, "Playmat", "Accessory").await;
+    let playmats = common::TestPile::new(product_uuid, 10).insert(&db).await;
+
+    // Sales recorded through the transactions API earn too
+    let direct = db
+        .transactions
+        .execute_sale(
+            Some(robin),
+            None,
+            vec![vaultsync::core::TransactionItem {
+                item_uuid: Uuid::new_v4(),
+                product_uuid,
+                quantity: 1,
+                unit_price: 30.0,
+                condition: vaultsync::core::Condition::NM,
+            }],
+        )
+        .await
+        .unwrap();
+    assert_eq!(loyalty.get_balance(robin).await.unwrap(), 30);
+
+    // 20 points ($1) spent at checkout, the rest paid in cash
+    let checkout_sale = checkout
+        .process_transaction(
+            &sale(
+                robin,
+                vec![line(playmats, 20.0)],
+                vec![
+                    pay(PaymentMethodType::LoyaltyPoints, 1.0),
+                    pay(PaymentMethodType::Cash, 500.0),
+                ],
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(checkout_sale.success, "{:?}", checkout_sale.errors);
+    let earned = (20.0 * (checkout_sale.total - 1.0) / checkout_sale.total + 1e-6).floor() as i64;
+    assert_eq!(loyalty.get_balance(robin).await.unwrap(), 30 - 20 + earned);
+
+    // Voiding takes back what it earned and returns what was spent
+    checkout
+        .void_transaction(checkout_sale.transaction_uuid, "Wrong customer", "manager")
+        .await
+        .unwrap();
+    assert_eq!(loyalty.get_balance(robin).await.unwrap(), 30);
+    let reversals: Vec<i64> = loyalty
+        .get_history(robin, 10)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|e| {
+            e.entry_type == LoyaltyEntryType::Reverse
+                && e.transaction_uuid == Some(checkout_sale.transaction_uuid)
+        })
+        .map(|e| e.points)
+        .collect();
+    assert_eq!(reversals.len(), 2);
+    assert!(reversals.contains(&20) && reversals.contains(&-earned));
+
+    checkout
+        .void_transaction(direct.transaction_uuid, "Rang twice", "manager")
+        .await
+        .unwrap();
+    assert_eq!(loyalty.get_balance(robin).await.unwrap(), 0);
+}
@@ -0,0 +1,206 @@
+// Integration tests for media storage
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::Path;
+use uuid::Uuid;
+use vaultsync::services::media::{
+    apply_synced_media, media_urls, MediaEntity, MediaService, MediaUpdate, ThumbnailGenerator,
+};
+
+mod common;
+
+/// Stands in for ImageMagick: the "thumbnail" is the source with a
+/// marker prefix
+struct CopyThumbnails;
+
+#[async_trait]
+impl ThumbnailGenerator for CopyThumbnails {
+    async fn generate(&self, source: &Path, dest: &Path, _max_dimension: u32) -> Result<()> {
+        let mut bytes = b"THUMB".to_vec();
+        bytes.extend(tokio::fs::read(source).await?);
+        tokio::fs::write(dest, bytes).await?;
+        Ok(())
+    }
+}
+
+fn png(seed: u8) -> Vec<u8> {
+    let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+    bytes.extend([seed; 32]);
+    bytes
+}
+
+#[tokio::test]
+async fn test_upload_dedupe_and_thumbnails() {
+    let db = common::setup_test_db().await;
+    let dir = tempfile::tempdir().unwrap();
+    let service = MediaService::new(
+        db.clone(),
+        dir.path().to_path_buf(),
+        Box::new(CopyThumbnails),
+    );
+
+    let product_uuid = common::seed_product(&db, "Slab", "TCG").await;
+    let inventory_uuid = common::TestPile {
+        location_tag: "CASE-1",
+        ..common::TestPile::new(product_uuid, 1)
+    }
+    .insert(&db)
+    .await;
+
+    // Not an image, and nothing to attach to
+    assert!(service
+        .upload(
+            b"%PDF-1.7",
+            None,
+            MediaEntity::Product,
+            product_uuid,
+            None,
+            None
+        )
+        .await
+        .is_err());
+    assert!(service
+        .upload(
+            &png(1),
+            None,
+            MediaEntity::TradeIn,
+            Uuid::new_v4(),
+            None,
+            None
+        )
+        .await
+        .is_err());
+
+    let front = service
+        .upload(
+            &png(1),
+            Some("front.png".to_string()),
+            MediaEntity::Inventory,
+            inventory_uuid,
+            Some("Front".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(front.mime_type, "image/png");
+    assert_eq!(front.sort_order, 0);
+    let back = service
+        .upload(
+            &png(2),
+            None,
+            MediaEntity::Inventory,
+            inventory_uuid,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(back.sort_order, 1);
+
+    // The same photo on the product shares the stored blob
+    let shared = service
+        .upload(
+            &png(1),
+            None,
+            MediaEntity::Product,
+            product_uuid,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(shared.content_hash, front.content_hash);
+    let blobs = walk(&dir.path().join("blobs"));
+    assert_eq!(blobs, 2);
+
+    let (_, content) = service.content(front.media_uuid, &[]).await.unwrap();
+    assert_eq!(content, png(1));
+    let (_, content_type, thumb) = service.thumbnail(front.media_uuid, &[]).await.unwrap();
+    assert_eq!(content_type, "image/jpeg");
+    assert!(thumb.starts_with(b"THUMB"));
+
+    // Reorder, then detach
+    service
+        .update(
+            back.media_uuid,
+            MediaUpdate {
+                sort_order: Some(-1),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let listed = service
+        .list(MediaEntity::Inventory, inventory_uuid)
+        .await
+        .unwrap();
+    assert_eq!(listed[0].media_uuid, back.media_uuid);
+
+    service.delete(back.media_uuid).await.unwrap();
+    let urls = media_urls(&db.pool, MediaEntity::Inventory, inventory_uuid)
+        .await
+        .unwrap();
+    assert_eq!(urls, vec![front.content_url()]);
+    assert!(service.get(back.media_uuid).await.unwrap().is_none());
+    // Bytes stay for the other references and backups
+    assert!(service
+        .read_blob(&back.content_hash)
+        .await
+        .unwrap()
+        .is_some());
+
+    // Peers learn of the detach through sync
+    let logged: String = sqlx::query_scalar(
+        "SELECT data FROM Sync_Log WHERE record_type = 'Media' AND record_id = ?",
+    )
+    .bind(back.media_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    let logged: serde_json::Value = serde_json::from_str(&logged).unwrap();
+    assert!(!logged["deleted_at"].is_null());
+}
+
+#[tokio::test]
+async fn test_synced_media_without_local_content() {
+    let db = common::setup_test_db().await;
+    let dir = tempfile::tempdir().unwrap();
+    let service = MediaService::new(
+        db.clone(),
+        dir.path().to_path_buf(),
+        Box::new(vaultsync::services::media::NoThumbnails),
+    );
+
+    let mut item = vaultsync::services::MediaItem {
+        media_uuid: Uuid::new_v4(),
+        content_hash: "../../etc/passwd".to_string(),
+        mime_type: "image/png".to_string(),
+        size_bytes: 40,
+        original_filename: None,
+        entity_type: MediaEntity::TradeIn,
+        entity_uuid: Uuid::new_v4(),
+        caption: None,
+        sort_order: 0,
+        uploaded_by: None,
+        created_at: chrono::Utc::now(),
+        deleted_at: None,
+    };
+    assert!(apply_synced_media(&db.pool, &item).await.is_err());
+
+    item.content_hash = vaultsync::services::media::content_hash(&png(7));
+    apply_synced_media(&db.pool, &item).await.unwrap();
+    assert!(service.get(item.media_uuid).await.unwrap().is_some());
+
+    // The row arrived but no peer can supply the bytes
+    let err = service.content(item.media_uuid, &[]).await.unwrap_err();
+    assert!(err.to_string().contains("not on this device"));
+}
+
+fn walk(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .map(|p| if p.is_dir() { walk(&p) } else { 1 })
+        .sum()
+}
@@ -0,0 +1,247 @@
+// Integration tests for notification preferences, templates and delivery
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+use vaultsync::core::Customer;
+use vaultsync::services::notification::sms::{MockSmsProvider, SmsProvider};
+use vaultsync::services::notification::{
+    DeliveryStatus, EmailMessage, EmailProvider, MessageCategory, NotificationChannel,
+    NotificationService, PreferencesUpdate, TemplateKey,
+};
+
+mod common;
+
+/// Fails the first `failures` sends
+struct FlakyEmailProvider {
+    failures: usize,
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl EmailProvider for FlakyEmailProvider {
+    async fn send_email(&self, _message: &EmailMessage) -> Result<()> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            anyhow::bail!("SMTP unavailable");
+        }
+        Ok(())
+    }
+}
+
+async fn setup(
+    email_failures: usize,
+) -> (
+    Arc<vaultsync::database::Database>,
+    NotificationService,
+    Uuid,
+) {
+    let db = common::setup_test_db().await;
+    let email: Arc<Box<dyn EmailProvider>> = Arc::new(Box::new(FlakyEmailProvider {
+        failures: email_failures,
+        calls: AtomicUsize::new(0),
+    }));
+    let sms: Arc<Box<dyn SmsProvider>> = Arc::new(Box::new(MockSmsProvider));
+    let service = NotificationService::new(db.clone(), email, sms)
+        .with_public_url(Some("https://shop.example/".to_string()));
+
+    let customer = Customer {
+        email: Some("robin@example.com".to_string()),
+        phone: Some("555-0101".to_string()),
+        ..common::blank_customer("Robin")
+    };
+    let customer_uuid = common::seed_customer(&db, customer).await;
+    (db, service, customer_uuid)
+}
+
+async fn promo(
+    service: &NotificationService,
+    customer_uuid: Uuid,
+) -> Vec<vaultsync::services::notification::DeliveryRecord> {
+    service
+        .send_to_customer(
+            customer_uuid,
+            TemplateKey::CustomMessage,
+            MessageCategory::Marketing,
+            &[("message", "Booster sale!".to_string())],
+            &[NotificationChannel::Sms],
+            false,
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_templates_preferences_and_consent() {
+    let (_db, service, customer_uuid) = setup(0).await;
+
+    // Store edits the SMS event reminder; unknown variables are rejected
+    assert!(service
+        .save_template(
+            TemplateKey::EventReminder,
+            NotificationChannel::Sms,
+            String::new(),
+            "{{evnt}} tomorrow".to_string(),
+            None,
+        )
+        .await
+        .is_err());
+    service
+        .save_template(
+            TemplateKey::EventReminder,
+            NotificationChannel::Sms,
+            String::new(),
+            "Hi {{customer_name}}, {{event_name}} starts {{event_date}}".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Prefers text; email switched off
+    service
+        .update_preferences(
+            customer_uuid,
+            PreferencesUpdate {
+                preferred_channel: Some("sms".to_string()),
+                email_enabled: Some(false),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let sent = service
+        .notify(
+            customer_uuid,
+            TemplateKey::EventReminder,
+            &[
+                ("event_name", "Commander Night".to_string()),
+                ("event_date", "Friday".to_string()),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].channel, Some(NotificationChannel::Sms));
+    assert_eq!(sent[0].body, "Hi Robin, Commander Night starts Friday");
+    assert!(sent[0].is_sent());
+
+    // Email is off, so only a suppressed record on that channel
+    let both = service
+        .send_to_customer(
+            customer_uuid,
+            TemplateKey::CustomMessage,
+            MessageCategory::Transactional,
+            &[("message", "Your order is in".to_string())],
+            &[NotificationChannel::Email, NotificationChannel::Sms],
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(both[0].status, Some(DeliveryStatus::Suppressed));
+    assert!(both[1].is_sent());
+
+    // Marketing needs consent, and carries the unsubscribe link
+    assert_eq!(
+        promo(&service, customer_uuid).await[0].status,
+        Some(DeliveryStatus::Suppressed)
+    );
+
+    let prefs = service
+        .update_preferences(
+            customer_uuid,
+            PreferencesUpdate {
+                sms_marketing: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(prefs.sms_marketing_consent_at.is_some());
+    let sent = promo(&service, customer_uuid).await;
+    assert!(sent[0].is_sent());
+    assert!(sent[0].body.ends_with(&format!(
+        "Opt out: https://shop.example/api/notifications/unsubscribe/{}",
+        prefs.unsubscribe_token
+    )));
+
+    let prefs = service.unsubscribe(&prefs.unsubscribe_token).await.unwrap();
+    assert!(prefs.unsubscribed_at.is_some());
+    assert!(prefs.sms_marketing_consent_at.is_none());
+    assert_eq!(
+        promo(&service, customer_uuid).await[0].status,
+        Some(DeliveryStatus::Suppressed)
+    );
+    assert!(service.unsubscribe("not-a-token").await.is_err());
+
+    let log = service
+        .delivery_log(Some(customer_uuid), None, 100)
+        .await
+        .unwrap();
+    assert_eq!(log.len(), 6);
+}
+
+#[tokio::test]
+async fn test_retry_and_bounce() {
+    let (db, service, customer_uuid) = setup(1).await;
+
+    let first = service
+        .send_email(
+            "robin@example.com",
+            Some(customer_uuid),
+            TemplateKey::Receipt,
+            &[
+                ("transaction_id", "T-1".to_string()),
+                ("receipt", "<p>Thanks</p>".to_string()),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(first.status, Some(DeliveryStatus::Failed));
+    assert_eq!(first.attempts, 1);
+    assert_eq!(first.subject, "Receipt for Transaction T-1");
+    assert!(first.next_attempt_at.is_some());
+
+    // Not due yet
+    assert!(service.retry_failed().await.unwrap().is_empty());
+
+    sqlx::query("UPDATE Notifications SET next_attempt_at = ? WHERE notification_uuid = ?")
+        .bind((chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339())
+        .bind(first.notification_uuid.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let retried = service.retry_failed().await.unwrap();
+    assert_eq!(retried.len(), 1);
+    assert!(retried[0].is_sent());
+    assert_eq!(retried[0].attempts, 2);
+
+    // A bounce stops further mail to that address
+    service
+        .record_bounce(first.notification_uuid, Some("Mailbox full".to_string()))
+        .await
+        .unwrap();
+    let next = service
+        .notify(
+            customer_uuid,
+            TemplateKey::HoldReminder,
+            &[
+                ("expiration_date", "2026-11-01".to_string()),
+                ("balance_due", "20.00".to_string()),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(next[0].channel, Some(NotificationChannel::Email));
+    assert_eq!(next[0].status, Some(DeliveryStatus::Suppressed));
+    assert_eq!(next[1].channel, Some(NotificationChannel::Sms));
+    assert!(next[1].is_sent());
+
+    let bounced = service
+        .delivery_log(None, Some(DeliveryStatus::Bounced), 10)
+        .await
+        .unwrap();
+    assert_eq!(bounced.len(), 1);
+    assert_eq!(bounced[0].last_error.as_deref(), Some("Mailbox full"));
+}
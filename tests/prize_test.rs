@@ -0,0 +1,164 @@
+// Integration tests for event prizes and event P&L
+
+use vaultsync::core::Customer;
+use vaultsync::events::{EventService, PrizeBasis, PrizeService, PrizeTierRequest, PrizeType};
+
+mod common;
+
+#[tokio::test]
+async fn test_prize_payouts_and_event_pnl() {
+    let db = common::setup_test_db().await;
+    let events = EventService::new(db.clone());
+    let prizes = PrizeService::new(db.clone());
+
+    let mut customers = Vec::new();
+    for name in ["Ash", "Brook"] {
+        let customer = Customer {
+            store_credit: 50.0,
+            ..common::blank_customer(name)
+        };
+        customers.push(common::seed_customer(&db, customer).await);
+    }
+    let pack = common::seed_product(&db, "Booster Pack", "Sealed").await;
+    common::TestPile {
+        condition: "New",
+        cost_basis: Some(3.0),
+        ..common::TestPile::new(pack, 5)
+    }
+    .insert(&db)
+    .await;
+
+    let event = events
+        .create_event(
+            "Sealed Night".to_string(),
+            "Sealed".to_string(),
+            chrono::Utc::now(),
+            10.0,
+            None,
+        )
+        .await
+        .unwrap();
+    let ash = events
+        .register_player(
+            event.event_uuid,
+            "Ash".to_string(),
+            Some(customers[0]),
+            true,
+        )
+        .await
+        .unwrap();
+    let brook = events
+        .register_player(
+            event.event_uuid,
+            "Brook".to_string(),
+            Some(customers[1]),
+            true,
+        )
+        .await
+        .unwrap();
+    let walk_in = events
+        .register_player(event.event_uuid, "Casey".to_string(), None, false)
+        .await
+        .unwrap();
+    assert_eq!(ash.payment_method.as_deref(), Some("credit"));
+    assert!(!walk_in.paid);
+
+    let paid = events
+        .record_cash_payment(walk_in.participant_uuid, None)
+        .await
+        .unwrap();
+    assert_eq!(paid.payment_method.as_deref(), Some("cash"));
+    assert_eq!(paid.fee_paid, 10.0);
+    assert!(events
+        .record_cash_payment(walk_in.participant_uuid, None)
+        .await
+        .is_err());
+
+    // The walk-in wins, so their credit prize can't be paid
+    events
+        .record_placement(walk_in.participant_uuid, 1)
+        .await
+        .unwrap();
+    events
+        .record_placement(ash.participant_uuid, 2)
+        .await
+        .unwrap();
+    events
+        .record_placement(brook.participant_uuid, 3)
+        .await
+        .unwrap();
+
+    prizes
+        .set_prize_structure(
+            event.event_uuid,
+            vec![
+                PrizeTierRequest {
+                    basis: PrizeBasis::Placement,
+                    place_from: Some(1),
+                    place_to: Some(2),
+                    min_match_points: None,
+                    prize_type: PrizeType::Credit,
+                    credit_amount: Some(15.0),
+                    product_uuid: None,
+                    condition: None,
+                    quantity: None,
+                },
+                PrizeTierRequest {
+                    basis: PrizeBasis::Placement,
+                    place_from: Some(1),
+                    place_to: Some(3),
+                    min_match_points: None,
+                    prize_type: PrizeType::Product,
+                    credit_amount: None,
+                    product_uuid: Some(pack),
+                    condition: None,
+                    quantity: Some(1),
+                },
+            ],
+        )
+        .await
+        .unwrap();
+
+    let summary = prizes.pay_out_prizes(event.event_uuid, None).await.unwrap();
+    assert_eq!(summary.payouts.len(), 4);
+    assert_eq!(summary.skipped.len(), 1);
+    assert_eq!(
+        summary.skipped[0].participant_uuid,
+        walk_in.participant_uuid
+    );
+    assert_eq!(summary.credit_paid, 15.0);
+    assert_eq!(summary.product_cost, 9.0);
+
+    // 50 - 10 entry + 15 prize
+    let ash_customer = db.customers.get_by_id(customers[0]).await.unwrap().unwrap();
+    assert_eq!(ash_customer.store_credit, 55.0);
+    assert_eq!(common::product_on_hand(&db, pack).await, 2);
+
+    // Running it again pays nothing twice, and the structure is now locked
+    let again = prizes.pay_out_prizes(event.event_uuid, None).await.unwrap();
+    assert!(again.payouts.is_empty());
+    assert_eq!(prizes.get_payouts(event.event_uuid).await.unwrap().len(), 4);
+    assert!(prizes
+        .set_prize_structure(event.event_uuid, vec![])
+        .await
+        .is_err());
+
+    let pnl = prizes.get_pnl(event.event_uuid).await.unwrap();
+    assert_eq!(pnl.attendance, 3);
+    assert_eq!(pnl.paid_entries, 3);
+    assert_eq!(pnl.entry_fees_credit, 20.0);
+    assert_eq!(pnl.entry_fees_cash, 10.0);
+    assert_eq!(pnl.prize_credit, 15.0);
+    assert_eq!(pnl.prize_product_cost, 9.0);
+    assert_eq!(pnl.net, 6.0);
+
+    let history = prizes.get_attendance(Some("Sealed")).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].attendance, 3);
+    assert_eq!(history[0].net, 6.0);
+    assert!(prizes
+        .get_attendance(Some("Draft"))
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,293 @@
+// Integration tests for suppliers and purchase orders
+
+use uuid::Uuid;
+use vaultsync::core::{Condition, VariantType};
+use vaultsync::services::{
+    CreatePurchaseOrderRequest, CreateSupplierRequest, PurchaseOrderLineRequest,
+    PurchaseOrderStatus, PurchasingService, ReceiveLineRequest,
+};
+
+mod common;
+
+async fn seed_supplier(service: &PurchasingService) -> Uuid {
+    service
+        .create_supplier(CreateSupplierRequest {
+            name: "Distributor".to_string(),
+            contact_name: None,
+            email: None,
+            phone: None,
+            address: None,
+            payment_terms: Some("Net 30".to_string()),
+            notes: None,
+            lead_time_days: Some(7),
+        })
+        .await
+        .unwrap()
+        .supplier_uuid
+}
+
+#[tokio::test]
+async fn test_partial_receipt_backorder_and_weighted_cost() {
+    let db = common::setup_test_db().await;
+    let service = PurchasingService::new(db.clone());
+    let supplier_uuid = seed_supplier(&service).await;
+    let product_uuid = common::seed_product(&db, "Booster Box", "Sealed").await;
+
+    // 4 on hand at $80
+    let inventory_uuid = common::TestPile {
+        condition: "New",
+        cost_basis: Some(80.0),
+        ..common::TestPile::new(product_uuid, 4)
+    }
+    .insert(&db)
+    .await;
+
+    // 10 @ $90 plus $100 shipping: landed at $100 each
+    let order = service
+        .create_purchase_order(
+            CreatePurchaseOrderRequest {
+                supplier_uuid,
+                lines: vec![PurchaseOrderLineRequest {
+                    product_uuid,
+                    condition: Condition::New,
+                    variant_type: None,
+                    quantity: 10,
+                    unit_cost: 90.0,
+                }],
+                shipping_cost: 100.0,
+                other_costs: 0.0,
+                expected_date: None,
+                notes: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(order.status, PurchaseOrderStatus::Draft);
+    assert_eq!(order.total, 1000.0);
+    let line_uuid = order.lines[0].line_uuid;
+
+    // Can't receive a draft
+    let receive = |quantity, backordered| {
+        vec![ReceiveLineRequest {
+            line_uuid,
+            quantity,
+            backordered,
+            backorder_expected_date: None,
+            location_tag: Some("MAIN".to_string()),
+        }]
+    };
+    assert!(service
+        .receive(order.po_uuid, receive(4, false), None)
+        .await
+        .is_err());
+
+    service.submit_purchase_order(order.po_uuid).await.unwrap();
+    let result = service
+        .receive(order.po_uuid, receive(4, true), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        result.purchase_order.status,
+        PurchaseOrderStatus::PartiallyReceived
+    );
+    assert_eq!(result.receipts.len(), 1);
+    assert_eq!(result.receipts[0].inventory_uuid, inventory_uuid);
+    assert_eq!(result.receipts[0].landed_unit_cost, 100.0);
+    // (4 x 80 + 4 x 100) / 8
+    assert_eq!(result.receipts[0].cost_basis, 90.0);
+
+    let (on_hand, supplier): (i64, Option<String>) = sqlx::query_as(
+        "SELECT quantity_on_hand, supplier_uuid FROM Local_Inventory WHERE inventory_uuid = ?",
+    )
+    .bind(inventory_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(on_hand, 8);
+    assert_eq!(supplier, Some(supplier_uuid.to_string()));
+
+    let backorders = service.get_backorders().await.unwrap();
+    assert_eq!(backorders.len(), 1);
+    assert_eq!(backorders[0].line.quantity_backordered, 6);
+
+    // Over-receiving is refused
+    assert!(service
+        .receive(order.po_uuid, receive(7, false), None)
+        .await
+        .is_err());
+
+    let result = service
+        .receive(order.po_uuid, receive(6, false), None)
+        .await
+        .unwrap();
+    assert_eq!(result.purchase_order.status, PurchaseOrderStatus::Received);
+    assert!(service.get_backorders().await.unwrap().is_empty());
+    assert_eq!(service.get_receipts(order.po_uuid).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_receiving_keeps_variants_and_locations_apart() {
+    let db = common::setup_test_db().await;
+    let service = PurchasingService::new(db.clone());
+    let supplier_uuid = seed_supplier(&service).await;
+    let product_uuid = common::seed_product(&db, "Holo Rare", "TCG").await;
+
+    // A foil pile in receiving and a regular pile on the floor
+    let foil = common::TestPile {
+        variant_type: Some("Foil"),
+        location_tag: "Receiving",
+        cost_basis: Some(20.0),
+        ..common::TestPile::new(product_uuid, 2)
+    }
+    .insert(&db)
+    .await;
+    let floor = common::TestPile {
+        cost_basis: Some(5.0),
+        ..common::TestPile::new(product_uuid, 3)
+    }
+    .insert(&db)
+    .await;
+
+    let line = |variant_type| PurchaseOrderLineRequest {
+        product_uuid,
+        condition: Condition::NM,
+        variant_type,
+        quantity: 4,
+        unit_cost: 6.0,
+    };
+    let order = service
+        .create_purchase_order(
+            CreatePurchaseOrderRequest {
+                supplier_uuid,
+                lines: vec![line(None), line(Some(VariantType::Foil))],
+                shipping_cost: 0.0,
+                other_costs: 0.0,
+                expected_date: None,
+                notes: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    service.submit_purchase_order(order.po_uuid).await.unwrap();
+    let regular_line = order
+        .lines
+        .iter()
+        .find(|l| l.variant_type.is_none())
+        .unwrap()
+        .line_uuid;
+    let foil_line = order
+        .lines
+        .iter()
+        .find(|l| l.variant_type == Some(VariantType::Foil))
+        .unwrap()
+        .line_uuid;
+
+    let result = service
+        .receive(
+            order.po_uuid,
+            vec![
+                ReceiveLineRequest {
+                    line_uuid: regular_line,
+                    quantity: 4,
+                    backordered: false,
+                    backorder_expected_date: None,
+                    location_tag: None,
+                },
+                ReceiveLineRequest {
+                    line_uuid: foil_line,
+                    quantity: 4,
+                    backordered: false,
+                    backorder_expected_date: None,
+                    location_tag: None,
+                },
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+    let receipt_for = |line_uuid| {
+        result
+            .receipts
+            .iter()
+            .find(|r| r.line_uuid == line_uuid)
+            .unwrap()
+    };
+
+    // Regular stock doesn't land on the foil pile or the floor pile
+    let regular = receipt_for(regular_line);
+    assert_ne!(regular.inventory_uuid, foil);
+    assert_ne!(regular.inventory_uuid, floor);
+    assert_eq!(regular.cost_basis, 6.0);
+    assert_eq!(common::on_hand_at(&db, product_uuid, "Receiving").await, 10);
+    assert_eq!(common::on_hand(&db, floor).await, 3);
+
+    // Foil stock joins the foil pile and re-averages its cost: (2 x 20 + 4 x 6) / 6
+    let foil_receipt = receipt_for(foil_line);
+    assert_eq!(foil_receipt.inventory_uuid, foil);
+    assert_eq!(foil_receipt.cost_basis, 10.67);
+    assert_eq!(common::on_hand(&db, foil).await, 6);
+}
+
+#[tokio::test]
+async fn test_suggestions_use_reorder_point_velocity_and_on_order() {
+    let db = common::setup_test_db().await;
+    let service = PurchasingService::new(db.clone());
+    let supplier_uuid = seed_supplier(&service).await;
+    let low = common::seed_product(&db, "Sleeves", "Sealed").await;
+    let healthy = common::seed_product(&db, "Dice", "Sealed").await;
+
+    for (product_uuid, on_hand) in [(low, 2), (healthy, 50)] {
+        common::TestPile {
+            condition: "New",
+            cost_basis: Some(5.0),
+            supplier_uuid: Some(supplier_uuid),
+            reorder_point: Some(5),
+            ..common::TestPile::new(product_uuid, on_hand)
+        }
+        .insert(&db)
+        .await;
+    }
+
+    // 30 sleeves sold in the last 30 days: 1/day
+    let transaction_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Transactions (transaction_uuid, timestamp, transaction_type) VALUES (?, ?, 'Sale')",
+    )
+    .bind(transaction_uuid.to_string())
+    .bind((chrono::Utc::now() - chrono::Duration::days(3)).to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO Transaction_Items (item_uuid, transaction_uuid, product_uuid, quantity, unit_price, condition)
+         VALUES (?, ?, ?, 30, 10.0, 'New')",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(transaction_uuid.to_string())
+    .bind(low.to_string())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let suggestions = service.suggest_purchase_orders(30, 30).await.unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].supplier_uuid, supplier_uuid);
+    assert_eq!(suggestions[0].lines.len(), 1);
+    let line = &suggestions[0].lines[0];
+    assert_eq!(line.product_uuid, low);
+    // 37 days of cover at 1/day less 2 on hand
+    assert_eq!(line.suggested_quantity, 35);
+    assert_eq!(suggestions[0].estimated_total, 175.0);
+
+    // Drafting the suggestion puts it on order, so it isn't suggested again
+    let drafts = service.create_suggested_drafts(30, 30, None).await.unwrap();
+    assert_eq!(drafts.len(), 1);
+    assert_eq!(drafts[0].lines[0].quantity_ordered, 35);
+    assert!(service
+        .suggest_purchase_orders(30, 30)
+        .await
+        .unwrap()
+        .is_empty());
+}
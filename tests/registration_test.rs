@@ -0,0 +1,174 @@
+// Integration tests for event registration and waitlists
+
+use chrono::Datelike;
+use vaultsync::core::{Customer, ParticipantStatus};
+use vaultsync::events::{EntryPaymentMethod, EventService, EventTemplateRequest, PrizeService};
+
+mod common;
+
+#[tokio::test]
+async fn test_waitlist_check_in_no_shows_and_refunds() {
+    let db = common::setup_test_db().await;
+    let events = EventService::new(db.clone());
+    let event = events
+        .create_event(
+            "Commander Night".to_string(),
+            "Casual".to_string(),
+            chrono::Utc::now() + chrono::Duration::days(2),
+            10.0,
+            Some(3),
+        )
+        .await
+        .unwrap();
+    let mut players = Vec::new();
+    for name in ["Ash", "Casey", "Drew"] {
+        let customer = Customer {
+            store_credit: 30.0,
+            ..common::blank_customer(name)
+        };
+        players.push(common::seed_customer(&db, customer).await);
+    }
+    let (ash, casey, drew) = (players[0], players[1], players[2]);
+
+    let a = events
+        .pre_register(event.event_uuid, ash, Some(4.0))
+        .await
+        .unwrap();
+    assert_eq!(a.status, ParticipantStatus::Registered);
+    assert_eq!(a.fee_paid, 4.0);
+    assert!(!a.paid);
+    assert!(events
+        .pre_register(event.event_uuid, ash, Some(20.0))
+        .await
+        .is_err());
+
+    let b = events
+        .register_player(event.event_uuid, "Brook".to_string(), None, false)
+        .await
+        .unwrap();
+    let c = events
+        .register_player(event.event_uuid, "Casey".to_string(), Some(casey), true)
+        .await
+        .unwrap();
+    assert!(c.paid);
+
+    // Full: Drew is waitlisted and not charged
+    let d = events
+        .register_player(event.event_uuid, "Drew".to_string(), Some(drew), true)
+        .await
+        .unwrap();
+    assert_eq!(d.status, ParticipantStatus::Waitlisted);
+    assert_eq!(
+        db.customers
+            .get_by_id(drew)
+            .await
+            .unwrap()
+            .unwrap()
+            .store_credit,
+        30.0
+    );
+    assert!(events.check_in(d.participant_uuid, None).await.is_err());
+
+    // Ash cancels; the deposit goes back as credit and Drew gets the seat
+    let cancelled = events
+        .cancel_registration(
+            a.participant_uuid,
+            Some(EntryPaymentMethod::StoreCredit),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(cancelled.refunded, 4.0);
+    assert_eq!(cancelled.participant.status, ParticipantStatus::Cancelled);
+    assert_eq!(
+        cancelled.promoted.map(|p| p.participant_uuid),
+        Some(d.participant_uuid)
+    );
+    assert_eq!(
+        db.customers
+            .get_by_id(ash)
+            .await
+            .unwrap()
+            .unwrap()
+            .store_credit,
+        30.0
+    );
+
+    // Brook owes the fee at the door
+    assert!(events.check_in(b.participant_uuid, None).await.is_err());
+    let b = events
+        .check_in(b.participant_uuid, Some(EntryPaymentMethod::Cash))
+        .await
+        .unwrap();
+    assert_eq!(b.status, ParticipantStatus::CheckedIn);
+    assert_eq!(b.cash_paid, 10.0);
+    events.check_in(c.participant_uuid, None).await.unwrap();
+
+    // Drew never shows
+    let no_shows = events.mark_no_shows(event.event_uuid).await.unwrap();
+    assert_eq!(no_shows.len(), 1);
+    assert_eq!(no_shows[0].participant_uuid, d.participant_uuid);
+
+    let pnl = PrizeService::new(db.clone())
+        .get_pnl(event.event_uuid)
+        .await
+        .unwrap();
+    assert_eq!(pnl.attendance, 2);
+    assert_eq!(pnl.entry_fees_credit, 14.0);
+    assert_eq!(pnl.entry_fees_cash, 10.0);
+    assert_eq!(pnl.refunds, 4.0);
+    assert_eq!(pnl.entry_fees_total, 20.0);
+}
+
+#[tokio::test]
+async fn test_recurring_template_spawns_instances_once() {
+    let db = common::setup_test_db().await;
+    let events = EventService::new(db.clone());
+    let weekday = (chrono::Utc::now() + chrono::Duration::days(2)).weekday();
+
+    let template = events
+        .create_template(EventTemplateRequest {
+            name: "Friday Night Magic".to_string(),
+            event_type: "Tournament".to_string(),
+            entry_fee: 5.0,
+            max_participants: Some(32),
+            weekday,
+            start_time: "18:30".to_string(),
+            interval_weeks: None,
+            starts_on: None,
+        })
+        .await
+        .unwrap();
+
+    let spawned = events
+        .spawn_instances(template.template_uuid, 28)
+        .await
+        .unwrap();
+    assert_eq!(spawned.len(), 4);
+    assert!(spawned.iter().all(|e| e.date.weekday() == weekday));
+    assert!(spawned.iter().all(|e| e.max_participants == Some(32)));
+    assert_eq!(events.spawn_due_instances(28).await.unwrap(), 0);
+
+    events
+        .set_template_active(template.template_uuid, false)
+        .await
+        .unwrap();
+    assert!(events
+        .spawn_instances(template.template_uuid, 60)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(events
+        .create_template(EventTemplateRequest {
+            name: "Bad".to_string(),
+            event_type: "Casual".to_string(),
+            entry_fee: 0.0,
+            max_participants: None,
+            weekday,
+            start_time: "7pm".to_string(),
+            interval_weeks: None,
+            starts_on: None,
+        })
+        .await
+        .is_err());
+}
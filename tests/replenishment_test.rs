@@ -0,0 +1,158 @@
+// Integration tests for replenishment forecasting
+
+use uuid::Uuid;
+use vaultsync::services::{ForecastOptions, ReplenishmentService, StockStatus};
+
+mod common;
+
+/// A sealed product with one $4 pile received some days ago
+async fn seed_stock(
+    db: &vaultsync::database::Database,
+    name: &str,
+    on_hand: i32,
+    reorder_point: Option<i32>,
+    received_days_ago: i64,
+) -> Uuid {
+    let product_uuid = common::seed_product(db, name, "Sealed").await;
+    common::TestPile {
+        condition: "New",
+        cost_basis: Some(4.0),
+        reorder_point,
+        received_date: Some(chrono::Utc::now() - chrono::Duration::days(received_days_ago)),
+        ..common::TestPile::new(product_uuid, on_hand)
+    }
+    .insert(db)
+    .await;
+    product_uuid
+}
+
+async fn sell(
+    db: &vaultsync::database::Database,
+    product_uuid: Uuid,
+    quantity: i32,
+    at: chrono::DateTime<chrono::Utc>,
+) {
+    let transaction_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Transactions (transaction_uuid, timestamp, transaction_type) VALUES (?, ?, 'Sale')",
+    )
+    .bind(transaction_uuid.to_string())
+    .bind(at.to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO Transaction_Items (item_uuid, transaction_uuid, product_uuid, quantity, unit_price, condition)
+         VALUES (?, ?, ?, ?, 10.0, 'New')",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(transaction_uuid.to_string())
+    .bind(product_uuid.to_string())
+    .bind(quantity)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+}
+
+async fn schedule_event(
+    db: &vaultsync::database::Database,
+    event_type: &str,
+    at: chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query(
+        "INSERT INTO Events (event_uuid, name, event_type, date, created_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(format!("{} {}", event_type, at.format("%Y-%m-%d")))
+    .bind(event_type)
+    .bind(at.to_rfc3339())
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_upcoming_prerelease_raises_forecast_and_reorder() {
+    let db = common::setup_test_db().await;
+    let service = ReplenishmentService::new(db.clone());
+    let now = chrono::Utc::now();
+    let booster = seed_stock(&db, "Booster Box", 6, Some(5), 10).await;
+
+    // 90 units over the last 90 days, 30 of them on last prerelease weekend
+    for day in 1..=60 {
+        sell(&db, booster, 1, now - chrono::Duration::days(day)).await;
+    }
+    let last_prerelease = now - chrono::Duration::days(70);
+    sell(&db, booster, 30, last_prerelease).await;
+    schedule_event(&db, "Prerelease", last_prerelease).await;
+
+    let options = ForecastOptions::default();
+    let before = service.forecast_product(booster, options).await.unwrap();
+    assert_eq!(before.len(), 1);
+    assert_eq!(before[0].daily_velocity, 1.0);
+    assert!(before[0].upcoming_events.is_empty());
+    // Below a week of lead time demand: order 37 days of cover less 6 on hand
+    assert_eq!(before[0].status, StockStatus::Reorder);
+    assert_eq!(before[0].suggested_quantity, 31);
+
+    // Another prerelease inside the lead time plus cover window
+    schedule_event(&db, "Prerelease", now + chrono::Duration::days(10)).await;
+    schedule_event(&db, "Casual Night", now + chrono::Duration::days(3)).await;
+    let after = service.forecast_product(booster, options).await.unwrap();
+    assert_eq!(after[0].upcoming_events.len(), 1);
+    let lift = after[0].upcoming_events[0].extra_units;
+    assert!(lift > 25.0 && lift < 30.0, "lift was {}", lift);
+    assert!(after[0].forecast_daily_demand > before[0].forecast_daily_demand);
+    assert!(after[0].suggested_quantity >= before[0].suggested_quantity + 25);
+
+    // The purchasing and dashboard views read the same forecast
+    let suggestions = service.get_reorder_suggestions(options).await.unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(
+        suggestions[0].suggested_quantity,
+        after[0].suggested_quantity
+    );
+    let summary = service.get_summary(options).await.unwrap();
+    assert_eq!(summary.reorder_count, 1);
+    assert_eq!(summary.dead_stock_count, 0);
+}
+
+#[tokio::test]
+async fn test_dead_stock_and_per_item_low_stock_levels() {
+    let db = common::setup_test_db().await;
+    let service = ReplenishmentService::new(db.clone());
+    let now = chrono::Utc::now();
+
+    let stale = seed_stock(&db, "Old Starter Deck", 12, None, 400).await;
+    sell(&db, stale, 1, now - chrono::Duration::days(300)).await;
+    let fresh_unsold = seed_stock(&db, "New Arrival", 8, None, 5).await;
+    let mover = seed_stock(&db, "Sleeves", 40, None, 400).await;
+    sell(&db, mover, 2, now - chrono::Duration::days(2)).await;
+
+    let dead = service.get_dead_stock(180).await.unwrap();
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].product_uuid, stale);
+    assert_eq!(dead[0].value_at_cost, 48.0);
+
+    let forecasts = service
+        .get_forecasts(ForecastOptions::default())
+        .await
+        .unwrap();
+    let status = |product: Uuid| {
+        forecasts
+            .iter()
+            .find(|f| f.product_uuid == product)
+            .unwrap()
+            .status
+    };
+    assert_eq!(status(stale), StockStatus::Dead);
+    assert_eq!(status(mover), StockStatus::Healthy);
+    assert!(status(fresh_unsold) != StockStatus::Reorder);
+
+    // Low stock honours each item's reorder point before the fallback threshold
+    let _ = seed_stock(&db, "Playmat", 6, Some(10), 5).await;
+    let low = db.inventory.get_low_stock(5).await.unwrap();
+    assert_eq!(low.len(), 1);
+    assert_eq!(db.inventory.get_low_stock_count(5).await.unwrap(), 1);
+}
@@ -123,6 +123,7 @@ mod backup_service_tests {
             retention_days: 7,
             max_backups: 5,
             create_checksum: true,
+            media_dir: None,
         };
 
         let backup_service = BackupService::new(config);
@@ -145,6 +146,7 @@ mod backup_service_tests {
             retention_days: 7,
             max_backups: 5,
             create_checksum: false,
+            media_dir: None,
         };
 
         let backup_service = BackupService::new(config);
@@ -185,6 +187,7 @@ mod backup_service_tests {
             retention_days: 7,
             max_backups: 5,
             create_checksum: false,
+            media_dir: None,
         };
 
         let backup_service = BackupService::new(config);
@@ -216,6 +219,7 @@ mod backup_service_tests {
             retention_days: 7,
             max_backups: 5,
             create_checksum: false,
+            media_dir: None,
         };
 
         let backup_service = BackupService::new(config);
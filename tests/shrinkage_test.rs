@@ -0,0 +1,215 @@
+// Integration tests for damage and shrinkage tracking
+
+use uuid::Uuid;
+use vaultsync::services::{
+    DamageType, Disposition, ReportDamageRequest, ResolveDamageRequest, ReturnsService,
+    ShrinkSource, ShrinkageService,
+};
+
+mod common;
+
+/// 10 Playmats in stock at $12 cost
+async fn seed_inventory(db: &vaultsync::database::Database) -> (Uuid, Uuid) {
+    let product_uuid = common::seed_product(db, "Playmat", "Accessories").await;
+    let inventory_uuid = common::TestPile {
+        condition: "New",
+        cost_basis: Some(12.0),
+        ..common::TestPile::new(product_uuid, 10)
+    }
+    .insert(db)
+    .await;
+    (product_uuid, inventory_uuid)
+}
+
+#[tokio::test]
+async fn test_damage_dispositions_and_shrink_report() {
+    let db = common::setup_test_db().await;
+    let service = ShrinkageService::new(db.clone());
+    let reporting = vaultsync::services::ReportingService::new(db.clone());
+    let (product_uuid, inventory_uuid) = seed_inventory(&db).await;
+
+    let report = |quantity, damage_type| ReportDamageRequest {
+        inventory_uuid,
+        quantity,
+        damage_type,
+        description: None,
+    };
+
+    // Can't lose more than is on hand
+    assert!(service
+        .report_damage(report(11, DamageType::Damaged), None)
+        .await
+        .is_err());
+
+    let written_off = service
+        .report_damage(report(2, DamageType::Damaged), None)
+        .await
+        .unwrap();
+    assert_eq!(written_off.original_value, 24.0);
+    assert_eq!(written_off.disposition, Disposition::Pending);
+    let discounted = service
+        .report_damage(report(1, DamageType::Damaged), None)
+        .await
+        .unwrap();
+    let repaired = service
+        .report_damage(report(1, DamageType::Defective), None)
+        .await
+        .unwrap();
+    assert_eq!(common::product_on_hand(&db, product_uuid).await, 6);
+
+    service
+        .resolve(
+            written_off.damage_uuid,
+            ResolveDamageRequest {
+                disposition: Disposition::WriteOff,
+                recovered_value: 0.0,
+                notes: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    // Goes back on the floor as its own discounted row
+    let resolved = service
+        .resolve(
+            discounted.damage_uuid,
+            ResolveDamageRequest {
+                disposition: Disposition::Discounted,
+                recovered_value: 5.0,
+                notes: Some("Creased corner".to_string()),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(resolved.net_loss, 7.0);
+    service
+        .resolve(
+            repaired.damage_uuid,
+            ResolveDamageRequest {
+                disposition: Disposition::Repaired,
+                recovered_value: 0.0,
+                notes: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(common::product_on_hand(&db, product_uuid).await, 8);
+
+    // Already resolved
+    assert!(service
+        .resolve(
+            repaired.damage_uuid,
+            ResolveDamageRequest {
+                disposition: Disposition::WriteOff,
+                recovered_value: 0.0,
+                notes: None,
+            },
+            None,
+        )
+        .await
+        .is_err());
+
+    let shrink = reporting
+        .get_shrink_report(
+            chrono::Utc::now() - chrono::Duration::days(1),
+            chrono::Utc::now() + chrono::Duration::days(1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(shrink.totals.quantity, 4);
+    assert_eq!(shrink.totals.original_value, 48.0);
+    assert_eq!(shrink.totals.recovered_value, 17.0);
+    assert_eq!(shrink.totals.net_loss, 31.0);
+    assert_eq!(shrink.pending_count, 0);
+    assert_eq!(shrink.by_reason["Damaged"].quantity, 3);
+    assert_eq!(shrink.by_category["Accessories"].net_loss, 31.0);
+}
+
+#[tokio::test]
+async fn test_count_shortage_is_written_off_as_missing() {
+    let db = common::setup_test_db().await;
+    let service = ShrinkageService::new(db.clone());
+    let audit = vaultsync::audit::AuditService::new(db.clone());
+    let (product_uuid, _) = seed_inventory(&db).await;
+
+    let conflicts = audit
+        .submit_blind_count("MAIN".to_string(), vec![(product_uuid, 7)])
+        .await
+        .unwrap();
+    assert_eq!(conflicts.len(), 1);
+
+    let records = service
+        .record_count_shortage(conflicts[0].conflict_uuid, None)
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].quantity, 3);
+    assert_eq!(records[0].damage_type, DamageType::Missing);
+    assert_eq!(records[0].source, ShrinkSource::Count);
+    assert_eq!(common::product_on_hand(&db, product_uuid).await, 7);
+    assert!(audit.get_pending_conflicts().await.unwrap().is_empty());
+
+    // The conflict is closed, so it can't be written off twice
+    assert!(service
+        .record_count_shortage(conflicts[0].conflict_uuid, None)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_unsellable_return_is_recorded_as_shrink() {
+    let db = common::setup_test_db().await;
+    let service = ShrinkageService::new(db.clone());
+    let returns = ReturnsService::new(db.clone());
+    let (product_uuid, inventory_uuid) = seed_inventory(&db).await;
+
+    let transaction_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Transactions (transaction_uuid, timestamp, transaction_type) VALUES (?, ?, 'Sale')",
+    )
+    .bind(transaction_uuid.to_string())
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO Transaction_Items (item_uuid, transaction_uuid, product_uuid, quantity, unit_price, condition)
+         VALUES (?, ?, ?, 2, 25.0, 'New')",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(transaction_uuid.to_string())
+    .bind(product_uuid.to_string())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let result = returns
+        .process_return(vaultsync::services::ReturnRequest {
+            transaction_uuid,
+            items: vec![vaultsync::services::returns::ReturnItemRequest {
+                inventory_uuid,
+                quantity: 1,
+                condition: vaultsync::services::returns::ReturnCondition::Defective,
+            }],
+            reason_code: vaultsync::services::ReturnReasonCode::Defective,
+            reason_notes: Some("Print peeling".to_string()),
+            customer_uuid: None,
+        })
+        .await
+        .unwrap();
+    assert!(!result.items[0].returned_to_inventory);
+
+    // Never went back on the shelf, so stock is unchanged
+    assert_eq!(common::product_on_hand(&db, product_uuid).await, 10);
+    let pending = service
+        .get_records(Some(Disposition::Pending))
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].source, ShrinkSource::Return);
+    assert_eq!(pending[0].source_uuid, Some(result.return_uuid));
+    assert_eq!(pending[0].damage_type, DamageType::Defective);
+    assert_eq!(pending[0].original_value, 12.0);
+}
@@ -0,0 +1,349 @@
+// Integration tests for storage locations and bin moves
+
+use uuid::Uuid;
+use vaultsync::services::{
+    CreateStorageRequest, MoveStockRequest, PickRequestLine, StorageKind, StorageService,
+    UpdateStorageRequest,
+};
+
+mod common;
+
+fn node(
+    location_tag: Option<&str>,
+    parent_uuid: Option<Uuid>,
+    kind: StorageKind,
+    code: &str,
+    pick_sequence: i32,
+    capacity: Option<i32>,
+) -> CreateStorageRequest {
+    CreateStorageRequest {
+        location_tag: location_tag.map(str::to_string),
+        parent_uuid,
+        kind,
+        code: code.to_string(),
+        name: None,
+        capacity,
+        pick_sequence,
+    }
+}
+
+/// A fresh product with one NM pile at MAIN costing $2
+async fn stocked_product(
+    db: &vaultsync::database::Database,
+    name: &str,
+    quantity: i32,
+) -> (Uuid, Uuid) {
+    let product_uuid = common::seed_product(db, name, "TCG").await;
+    let inventory_uuid = common::TestPile {
+        cost_basis: Some(2.0),
+        ..common::TestPile::new(product_uuid, quantity)
+    }
+    .insert(db)
+    .await;
+    (product_uuid, inventory_uuid)
+}
+
+#[tokio::test]
+async fn test_bin_moves_split_merge_and_respect_capacity() {
+    let db = common::setup_test_db().await;
+    let service = StorageService::new(db.clone());
+
+    let room = service
+        .create_node(node(Some("MAIN"), None, StorageKind::Room, "BACK", 1, None))
+        .await
+        .unwrap();
+    let case = service
+        .create_node(node(
+            None,
+            Some(room.storage_uuid),
+            StorageKind::Case,
+            "C1",
+            1,
+            Some(10),
+        ))
+        .await
+        .unwrap();
+    let bin_a = service
+        .create_node(node(
+            None,
+            Some(case.storage_uuid),
+            StorageKind::Bin,
+            "A",
+            1,
+            None,
+        ))
+        .await
+        .unwrap();
+    let bin_b = service
+        .create_node(node(
+            None,
+            Some(case.storage_uuid),
+            StorageKind::Bin,
+            "B",
+            2,
+            Some(4),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(bin_b.full_code, "BACK-C1-B");
+    assert_eq!(bin_b.location_tag, "MAIN");
+    // A room can't go inside a bin
+    assert!(service
+        .create_node(node(
+            None,
+            Some(bin_a.storage_uuid),
+            StorageKind::Room,
+            "X",
+            0,
+            None
+        ))
+        .await
+        .is_err());
+
+    let (product_uuid, pile) = stocked_product(&db, "Sol Ring", 8).await;
+
+    // Slot the whole pile into A
+    let first = service
+        .move_stock(
+            MoveStockRequest {
+                inventory_uuid: pile,
+                to_storage_uuid: bin_a.storage_uuid,
+                quantity: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(first.target_inventory_uuid, pile);
+
+    // Split 3 into B; the case and bin limits still have room
+    let split = service
+        .move_stock(
+            MoveStockRequest {
+                inventory_uuid: pile,
+                to_storage_uuid: bin_b.storage_uuid,
+                quantity: Some(3),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_ne!(split.target_inventory_uuid, pile);
+
+    // B only holds 4
+    let err = service
+        .move_stock(
+            MoveStockRequest {
+                inventory_uuid: pile,
+                to_storage_uuid: bin_b.storage_uuid,
+                quantity: Some(2),
+            },
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("BACK-C1-B"), "{}", err);
+
+    // One more merges into the existing pile in B
+    let merge = service
+        .move_stock(
+            MoveStockRequest {
+                inventory_uuid: pile,
+                to_storage_uuid: bin_b.storage_uuid,
+                quantity: Some(1),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(merge.target_inventory_uuid, split.target_inventory_uuid);
+
+    let (a_qty, b_qty, b_bin): (i32, i32, Option<String>) = sqlx::query_as(
+        "SELECT a.quantity_on_hand, b.quantity_on_hand, b.bin_location
+         FROM Local_Inventory a, Local_Inventory b
+         WHERE a.inventory_uuid = ? AND b.inventory_uuid = ?",
+    )
+    .bind(pile.to_string())
+    .bind(split.target_inventory_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!((a_qty, b_qty, b_bin.as_deref()), (4, 4, Some("BACK-C1-B")));
+
+    // Editing the pile through the inventory API keeps it in its bin
+    let item = db
+        .inventory
+        .get_by_id(split.target_inventory_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    db.inventory.insert(&item).await.unwrap();
+    let kept: Option<String> =
+        sqlx::query_scalar("SELECT storage_uuid FROM Local_Inventory WHERE inventory_uuid = ?")
+            .bind(split.target_inventory_uuid.to_string())
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(kept, Some(bin_b.storage_uuid.to_string()));
+
+    // Every move is an out/in pair on the ledger that nets to zero
+    let (entries, net): (i64, i64) = sqlx::query_as(
+        "SELECT COUNT(*), COALESCE(SUM(quantity_change), 0) FROM Inventory_Movements
+         WHERE product_uuid = ? AND movement_type = 'bin_move'",
+    )
+    .bind(product_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!((entries, net), (6, 0));
+
+    let tree = service.get_tree("MAIN").await.unwrap();
+    let units: Vec<(String, i64)> = tree
+        .iter()
+        .map(|n| (n.full_code.clone(), n.units_stored))
+        .collect();
+    assert_eq!(
+        units,
+        vec![
+            ("BACK".to_string(), 8),
+            ("BACK-C1".to_string(), 8),
+            ("BACK-C1-A".to_string(), 4),
+            ("BACK-C1-B".to_string(), 4),
+        ]
+    );
+
+    // A bin with stock can't be retired
+    assert!(service
+        .update_node(
+            bin_b.storage_uuid,
+            UpdateStorageRequest {
+                is_active: Some(false),
+                ..Default::default()
+            },
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_pick_list_follows_walking_order_and_locate_spans_stores() {
+    let db = common::setup_test_db().await;
+    let service = StorageService::new(db.clone());
+
+    let front = service
+        .create_node(node(
+            Some("MAIN"),
+            None,
+            StorageKind::Room,
+            "FRONT",
+            1,
+            None,
+        ))
+        .await
+        .unwrap();
+    let back = service
+        .create_node(node(Some("MAIN"), None, StorageKind::Room, "BACK", 2, None))
+        .await
+        .unwrap();
+    let front_bin = service
+        .create_node(node(
+            None,
+            Some(front.storage_uuid),
+            StorageKind::Bin,
+            "F1",
+            1,
+            None,
+        ))
+        .await
+        .unwrap();
+    let back_bin = service
+        .create_node(node(
+            None,
+            Some(back.storage_uuid),
+            StorageKind::Bin,
+            "K1",
+            1,
+            None,
+        ))
+        .await
+        .unwrap();
+
+    let (zebra, zebra_pile) = stocked_product(&db, "Zebra Deck Box", 2).await;
+    let (apple, apple_pile) = stocked_product(&db, "Apple Sleeves", 5).await;
+    let (_, loose_pile) = stocked_product(&db, "Loose Dice", 3).await;
+    for (pile, bin) in [
+        (zebra_pile, front_bin.storage_uuid),
+        (apple_pile, back_bin.storage_uuid),
+    ] {
+        service
+            .move_stock(
+                MoveStockRequest {
+                    inventory_uuid: pile,
+                    to_storage_uuid: bin,
+                    quantity: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    let list = service
+        .pick_list(
+            "MAIN",
+            "order-1001".to_string(),
+            vec![
+                PickRequestLine {
+                    product_uuid: None,
+                    inventory_uuid: Some(loose_pile),
+                    condition: None,
+                    quantity: 1,
+                },
+                PickRequestLine {
+                    product_uuid: Some(apple),
+                    inventory_uuid: None,
+                    condition: None,
+                    quantity: 7,
+                },
+                PickRequestLine {
+                    product_uuid: Some(zebra),
+                    inventory_uuid: None,
+                    condition: None,
+                    quantity: 1,
+                },
+            ],
+        )
+        .await
+        .unwrap();
+    let order: Vec<(Option<&str>, &str, i32)> = list
+        .lines
+        .iter()
+        .map(|l| (l.full_code.as_deref(), l.product_name.as_str(), l.quantity))
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            (Some("FRONT-F1"), "Zebra Deck Box", 1),
+            (Some("BACK-K1"), "Apple Sleeves", 5),
+            (None, "Loose Dice", 1),
+        ]
+    );
+    assert_eq!(list.shortages.len(), 1);
+    assert_eq!(list.shortages[0].quantity_short, 2);
+
+    // A second store's copies show up too
+    common::TestPile {
+        location_tag: "DOWNTOWN",
+        ..common::TestPile::new(apple, 4)
+    }
+    .insert(&db)
+    .await;
+    let whereabouts = service.locate_product(apple).await.unwrap();
+    assert_eq!(whereabouts.total_quantity, 9);
+    let places: Vec<(&str, Option<&str>)> = whereabouts
+        .locations
+        .iter()
+        .map(|l| (l.location_tag.as_str(), l.full_code.as_deref()))
+        .collect();
+    assert_eq!(places, vec![("DOWNTOWN", None), ("MAIN", Some("BACK-K1"))]);
+}
@@ -0,0 +1,149 @@
+// Integration tests for stacked tax jurisdictions and exemption certificates
+
+use uuid::Uuid;
+use vaultsync::services::{
+    JurisdictionLevel, TaxExemptionCertificate, TaxJurisdiction, TaxService,
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_stacked_jurisdictions_certificates_and_liability() {
+    let db = common::setup_test_db().await;
+    let taxes = TaxService::new(db.clone());
+    let now = chrono::Utc::now();
+    let location_uuid = Uuid::new_v4();
+
+    sqlx::query(
+        "INSERT INTO Store_Locations (location_uuid, name, created_at) VALUES (?, 'Downtown', ?)",
+    )
+    .bind(location_uuid.to_string())
+    .bind(now.to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    let customer_uuid = common::seed_customer(&db, common::blank_customer("Reseller")).await;
+
+    let jurisdiction = |name: &str, level, rate| TaxJurisdiction {
+        jurisdiction_uuid: Uuid::new_v4(),
+        name: name.to_string(),
+        code: None,
+        level,
+        rate,
+        is_compound: false,
+        applies_to_category: None,
+        is_active: true,
+        created_at: now,
+    };
+    let state = jurisdiction("State", JurisdictionLevel::State, 0.06);
+    let city = jurisdiction("City", JurisdictionLevel::City, 0.02);
+    for (seq, j) in [&state, &city].iter().enumerate() {
+        taxes.create_jurisdiction(j).await.unwrap();
+        taxes
+            .assign_jurisdiction_to_location(location_uuid, j.jurisdiction_uuid, seq as i32)
+            .await
+            .unwrap();
+    }
+
+    let items = vec![(Uuid::new_v4(), 100.0, Some("TCG".to_string()))];
+    let breakdown = taxes
+        .calculate_location_tax(Some(location_uuid), &items, Some(customer_uuid), false)
+        .await
+        .unwrap();
+    assert_eq!(breakdown.total_tax, 8.0);
+    assert_eq!(breakdown.jurisdictions.len(), 2);
+
+    // A resale certificate scoped to the city only exempts the city portion
+    taxes
+        .add_exemption_certificate(&TaxExemptionCertificate {
+            certificate_uuid: Uuid::new_v4(),
+            customer_uuid,
+            certificate_number: "RS-42".to_string(),
+            exemption_type: "Resale".to_string(),
+            jurisdiction_uuid: Some(city.jurisdiction_uuid),
+            effective_date: now - chrono::Duration::days(1),
+            expiry_date: Some(now + chrono::Duration::days(365)),
+            document_ref: None,
+            created_at: now,
+            revoked_at: None,
+        })
+        .await
+        .unwrap();
+    let breakdown = taxes
+        .calculate_location_tax(Some(location_uuid), &items, Some(customer_uuid), false)
+        .await
+        .unwrap();
+    assert_eq!(breakdown.total_tax, 6.0);
+    assert!(breakdown
+        .jurisdictions
+        .iter()
+        .any(|l| l.exempt && l.jurisdiction_uuid == Some(city.jurisdiction_uuid)));
+
+    // Tax-inclusive pricing backs tax out of the shelf price
+    taxes
+        .set_location_prices_include_tax(location_uuid, true)
+        .await
+        .unwrap();
+    let breakdown = taxes
+        .calculate_location_tax(
+            Some(location_uuid),
+            &[(Uuid::new_v4(), 108.0, None)],
+            None,
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(breakdown.total_taxable, 100.0);
+    assert_eq!(breakdown.total_tax, 8.0);
+
+    // Exempt buyers pay the net price, not the gross shelf price
+    let exempt = taxes
+        .calculate_location_tax(
+            Some(location_uuid),
+            &[(Uuid::new_v4(), 108.0, None)],
+            None,
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(exempt.total_taxable, 100.0);
+    assert_eq!(exempt.total_tax, 0.0);
+    let partly_exempt = taxes
+        .calculate_location_tax(
+            Some(location_uuid),
+            &[(Uuid::new_v4(), 108.0, None)],
+            Some(customer_uuid),
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(partly_exempt.total_taxable, 100.0);
+    assert_eq!(partly_exempt.total_tax, 6.0);
+
+    // Recorded lines roll up into the liability report
+    let transaction_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Transactions (transaction_uuid, timestamp, transaction_type) VALUES (?, ?, 'Sale')",
+    )
+    .bind(transaction_uuid.to_string())
+    .bind(now.to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    let mut tx = db.pool.begin().await.unwrap();
+    taxes
+        .record_tax_lines_with_tx(&mut tx, transaction_uuid, &breakdown.jurisdictions)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    let report = taxes
+        .get_tax_liability_report(
+            now - chrono::Duration::hours(1),
+            now + chrono::Duration::hours(1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(report.jurisdictions.len(), 2);
+    assert_eq!(report.total_tax_collected, 8.0);
+}
@@ -0,0 +1,209 @@
+// Integration tests for Swiss tournaments
+
+use std::collections::HashSet;
+use uuid::Uuid;
+use vaultsync::events::{
+    EventService, MatchResult, RoundStage, RoundStatus, TournamentConfig, TournamentRound,
+    TournamentService,
+};
+
+mod common;
+
+async fn report_all(tournaments: &TournamentService, round: &TournamentRound) {
+    for m in round.matches.iter().filter(|m| !m.is_bye()) {
+        tournaments
+            .report_result(
+                m.match_uuid,
+                MatchResult {
+                    player1_wins: 2,
+                    player2_wins: 1,
+                    draws: 0,
+                },
+            )
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_swiss_with_bye_drop_and_top_cut() {
+    let db = common::setup_test_db().await;
+    let events = EventService::new(db.clone());
+    let tournaments = TournamentService::new(db.clone());
+
+    let event = events
+        .create_event(
+            "Friday Draft".to_string(),
+            "Draft".to_string(),
+            chrono::Utc::now(),
+            0.0,
+            None,
+        )
+        .await
+        .unwrap();
+    let mut players = Vec::new();
+    for name in ["Ash", "Brook", "Casey", "Drew", "Emery"] {
+        let p = events
+            .register_player(event.event_uuid, name.to_string(), None, false)
+            .await
+            .unwrap();
+        players.push(p.participant_uuid);
+    }
+
+    let round1 = tournaments
+        .start_tournament(
+            event.event_uuid,
+            TournamentConfig {
+                swiss_rounds: None,
+                top_cut: Some(2),
+                round_minutes: Some(40),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(round1.stage, RoundStage::Swiss);
+    assert_eq!(round1.matches.len(), 3);
+    let byes: Vec<_> = round1.matches.iter().filter(|m| m.is_bye()).collect();
+    assert_eq!(byes.len(), 1);
+    assert!(byes[0].reported);
+    let bye_player = byes[0].player1_uuid;
+
+    // Can't pair ahead of unreported results
+    assert!(tournaments.pair_next_round(event.event_uuid).await.is_err());
+
+    let started = tournaments
+        .start_round(event.event_uuid, 1, None)
+        .await
+        .unwrap();
+    assert_eq!(started.status, RoundStatus::InProgress);
+    assert!(started.remaining_seconds.unwrap() > 39 * 60);
+
+    report_all(&tournaments, &round1).await;
+    let round1 = tournaments
+        .get_round(event.event_uuid, 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(round1.status, RoundStatus::Completed);
+
+    let standings = tournaments.get_standings(event.event_uuid).await.unwrap();
+    assert_eq!(standings.iter().filter(|s| s.match_points == 3).count(), 3);
+    let bye_entry = standings
+        .iter()
+        .find(|s| s.participant_uuid == bye_player)
+        .unwrap();
+    assert_eq!(bye_entry.gw_pct, 1.0);
+
+    // One of the round one losers drops; four remain, so no bye
+    let loser = round1
+        .matches
+        .iter()
+        .find(|m| !m.is_bye())
+        .unwrap()
+        .player2_uuid
+        .unwrap();
+    tournaments
+        .drop_player(event.event_uuid, loser)
+        .await
+        .unwrap();
+    assert!(tournaments
+        .drop_player(event.event_uuid, loser)
+        .await
+        .is_err());
+
+    let round2 = tournaments.pair_next_round(event.event_uuid).await.unwrap();
+    assert_eq!(round2.round_number, 2);
+    assert_eq!(round2.matches.len(), 2);
+    assert!(round2.matches.iter().all(|m| !m.is_bye()));
+    let round1_pairs: HashSet<(Uuid, Uuid)> = round1
+        .matches
+        .iter()
+        .filter_map(|m| m.player2_uuid.map(|p2| (m.player1_uuid, p2)))
+        .flat_map(|(a, b)| [(a, b), (b, a)])
+        .collect();
+    for m in &round2.matches {
+        assert_ne!(m.player1_uuid, loser);
+        assert_ne!(m.player2_uuid, Some(loser));
+        assert!(!round1_pairs.contains(&(m.player1_uuid, m.player2_uuid.unwrap())));
+    }
+
+    // Round one results are locked once round two is paired
+    let locked = round1.matches.iter().find(|m| !m.is_bye()).unwrap();
+    assert!(tournaments
+        .report_result(
+            locked.match_uuid,
+            MatchResult {
+                player1_wins: 0,
+                player2_wins: 2,
+                draws: 0
+            }
+        )
+        .await
+        .is_err());
+
+    report_all(&tournaments, &round2).await;
+    let round3 = tournaments.pair_next_round(event.event_uuid).await.unwrap();
+    assert_eq!(round3.stage, RoundStage::Swiss);
+    report_all(&tournaments, &round3).await;
+
+    // Top two go to a final, which needs a winner
+    let standings = tournaments.get_standings(event.event_uuid).await.unwrap();
+    let final_round = tournaments.pair_next_round(event.event_uuid).await.unwrap();
+    assert_eq!(final_round.stage, RoundStage::TopCut);
+    assert_eq!(final_round.matches.len(), 1);
+    let final_match = &final_round.matches[0];
+    assert_eq!(final_match.player1_uuid, standings[0].participant_uuid);
+    assert_eq!(
+        final_match.player2_uuid,
+        Some(standings[1].participant_uuid)
+    );
+    assert!(tournaments
+        .report_result(
+            final_match.match_uuid,
+            MatchResult {
+                player1_wins: 1,
+                player2_wins: 1,
+                draws: 1
+            }
+        )
+        .await
+        .is_err());
+    tournaments
+        .report_result(
+            final_match.match_uuid,
+            MatchResult {
+                player1_wins: 0,
+                player2_wins: 2,
+                draws: 0,
+            },
+        )
+        .await
+        .unwrap();
+    assert!(tournaments.pair_next_round(event.event_uuid).await.is_err());
+
+    // The second seed won the final; everyone else follows Swiss order
+    let participants = db.events.get_participants(event.event_uuid).await.unwrap();
+    let placement = |id: Uuid| {
+        participants
+            .iter()
+            .find(|p| p.participant_uuid == id)
+            .and_then(|p| p.placement)
+    };
+    assert_eq!(placement(standings[1].participant_uuid), Some(1));
+    assert_eq!(placement(standings[0].participant_uuid), Some(2));
+    assert_eq!(placement(standings[2].participant_uuid), Some(3));
+    assert_eq!(
+        participants
+            .iter()
+            .filter(|p| p.placement.is_some())
+            .count(),
+        5
+    );
+
+    let status: String = sqlx::query_scalar("SELECT status FROM Events WHERE event_uuid = ?")
+        .bind(event.event_uuid.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "Completed");
+}
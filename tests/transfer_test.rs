@@ -0,0 +1,202 @@
+// Integration tests for inter-store transfers
+
+use uuid::Uuid;
+use vaultsync::services::location::apply_synced_transfer;
+use vaultsync::services::{
+    LocationService, ShortageResolution, TransferItem, TransferReceipt, TransferStatus,
+    TRANSIT_LOCATION,
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_short_receipt_stays_in_transit_until_written_off() {
+    let db = common::setup_test_db().await;
+    let service = LocationService::new(db.clone());
+    let user = Uuid::new_v4();
+    let product_uuid = common::seed_product(&db, "Transfer Card", "TCG").await;
+    for quantity in [6, 4] {
+        common::TestPile {
+            cost_basis: Some(2.0),
+            ..common::TestPile::new(product_uuid, quantity)
+        }
+        .insert(&db)
+        .await;
+    }
+
+    // Product-only line is drawn from both piles
+    let transfer_uuid = service
+        .create_transfer_request(
+            "MAIN".to_string(),
+            "DOWNTOWN".to_string(),
+            user,
+            vec![TransferItem {
+                product_uuid,
+                inventory_uuid: None,
+                quantity: 8,
+            }],
+        )
+        .await
+        .unwrap();
+    assert!(service.ship_transfer(transfer_uuid, user).await.is_err());
+    service
+        .update_transfer_status(transfer_uuid, TransferStatus::Approved, user)
+        .await
+        .unwrap();
+
+    let shipped = service.ship_transfer(transfer_uuid, user).await.unwrap();
+    assert_eq!(shipped.items.len(), 2);
+    assert_eq!(common::on_hand_at(&db, product_uuid, "MAIN").await, 2);
+    assert_eq!(
+        common::on_hand_at(&db, product_uuid, TRANSIT_LOCATION).await,
+        8
+    );
+
+    // Six of the eight arrive, two short from the larger pile
+    let receipts = shipped
+        .items
+        .iter()
+        .map(|line| TransferReceipt {
+            transfer_item_uuid: line.transfer_item_uuid,
+            quantity: line.quantity_shipped - if line.quantity_shipped == 6 { 2 } else { 0 },
+        })
+        .collect();
+    let partial = service
+        .receive_transfer(transfer_uuid, receipts, user)
+        .await
+        .unwrap();
+    assert_eq!(partial.status, TransferStatus::PartiallyReceived);
+    assert_eq!(partial.items.iter().map(|l| l.variance).sum::<i32>(), -2);
+    assert_eq!(common::on_hand_at(&db, product_uuid, "DOWNTOWN").await, 6);
+    assert_eq!(
+        common::on_hand_at(&db, product_uuid, TRANSIT_LOCATION).await,
+        2
+    );
+    // Both lines landed in one destination pile
+    let downtown_piles: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM Local_Inventory WHERE product_uuid = ? AND location_tag = 'DOWNTOWN'",
+    )
+    .bind(product_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(downtown_piles, 1);
+
+    let closed = service
+        .close_transfer(transfer_uuid, ShortageResolution::WriteOff, user)
+        .await
+        .unwrap();
+    assert_eq!(closed.status, TransferStatus::Received);
+    assert_eq!(
+        common::on_hand_at(&db, product_uuid, TRANSIT_LOCATION).await,
+        0
+    );
+
+    // The ledger accounts for every unit: 10 started, 2 written off
+    let movements = db.movements.get_by_source(transfer_uuid).await.unwrap();
+    assert_eq!(movements.iter().map(|m| m.quantity_change).sum::<i32>(), -2);
+    assert_eq!(common::on_hand_at(&db, product_uuid, "MAIN").await, 2);
+
+    // The closed document and the emptied transit piles are queued for the other node
+    let queued: String = sqlx::query_scalar(
+        "SELECT data FROM Sync_Log WHERE record_type = 'InventoryTransfer' AND record_id = ?",
+    )
+    .bind(transfer_uuid.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert!(queued.contains("\"received\""));
+    for line in &closed.items {
+        let transit = line.transit_inventory_uuid.unwrap().to_string();
+        let logged: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM Sync_Log WHERE record_type = 'InventoryItem' AND record_id = ?",
+        )
+        .bind(&transit)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(logged, 1);
+    }
+}
+
+#[tokio::test]
+async fn test_serialized_pile_travels_whole_and_syncs() {
+    let db = common::setup_test_db().await;
+    let service = LocationService::new(db.clone());
+    let user = Uuid::new_v4();
+    let product_uuid = common::seed_product(&db, "Transfer Card", "TCG").await;
+    let slab = common::TestPile {
+        cost_basis: Some(2.0),
+        serialized_details: Some(r#"{"cert_number":"PSA-12345"}"#),
+        ..common::TestPile::new(product_uuid, 1)
+    }
+    .insert(&db)
+    .await;
+
+    // Part of a serialized pile can't be split off
+    let bad = service
+        .create_transfer_request(
+            "MAIN".to_string(),
+            "DOWNTOWN".to_string(),
+            user,
+            vec![TransferItem {
+                product_uuid,
+                inventory_uuid: Some(slab),
+                quantity: 2,
+            }],
+        )
+        .await;
+    assert!(bad.is_err());
+
+    let transfer_uuid = service
+        .create_transfer_request(
+            "MAIN".to_string(),
+            "DOWNTOWN".to_string(),
+            user,
+            vec![TransferItem {
+                product_uuid,
+                inventory_uuid: Some(slab),
+                quantity: 1,
+            }],
+        )
+        .await
+        .unwrap();
+    service.approve_transfer(transfer_uuid, user).await.unwrap();
+    let shipped = service.ship_transfer(transfer_uuid, user).await.unwrap();
+    assert_eq!(shipped.items[0].transit_inventory_uuid, Some(slab));
+
+    let slip = service.packing_slip_html(transfer_uuid).await.unwrap();
+    assert!(slip.contains("PSA-12345"));
+    assert!(slip.contains("DOWNTOWN"));
+
+    service
+        .update_transfer_status(transfer_uuid, TransferStatus::Received, user)
+        .await
+        .unwrap();
+    let (tag, qty): (String, i32) = sqlx::query_as(
+        "SELECT location_tag, quantity_on_hand FROM Local_Inventory WHERE inventory_uuid = ?",
+    )
+    .bind(slab.to_string())
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!((tag.as_str(), qty), ("DOWNTOWN", 1));
+
+    // The other store's node stores the document as-is
+    let transfer = service.get_transfer(transfer_uuid).await.unwrap().unwrap();
+    let remote = common::setup_test_db().await;
+    apply_synced_transfer(&remote.pool, &transfer)
+        .await
+        .unwrap();
+    apply_synced_transfer(&remote.pool, &transfer)
+        .await
+        .unwrap();
+    let mirrored = LocationService::new(remote.clone())
+        .get_transfer(transfer_uuid)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(mirrored.status, TransferStatus::Received);
+    assert_eq!(mirrored.items.len(), 1);
+    assert!(mirrored.items[0].whole_pile);
+}
@@ -0,0 +1,231 @@
+// Integration tests for wants-list matching
+
+use std::sync::Arc;
+use uuid::Uuid;
+use vaultsync::buylist::matcher::{record_purchase, WantsMatchStatus, WantsMatchingService};
+use vaultsync::core::{Condition, Customer, VariantType, WantsItem, WantsList};
+use vaultsync::services::notification::scheduler::NotificationScheduler;
+use vaultsync::services::notification::{email::MockEmailProvider, sms::MockSmsProvider};
+
+mod common;
+
+/// A customer reachable by email and/or phone, with an optional preferred channel
+async fn seed_contact(
+    db: &vaultsync::database::Database,
+    email: Option<&str>,
+    phone: Option<&str>,
+    preferred_contact: Option<&str>,
+) -> Uuid {
+    let customer = Customer {
+        email: email.map(str::to_string),
+        phone: phone.map(str::to_string),
+        ..common::blank_customer("Sam")
+    };
+    let customer_uuid = common::seed_customer(db, customer).await;
+    sqlx::query("UPDATE Customers SET preferred_contact = ? WHERE customer_uuid = ?")
+        .bind(preferred_contact)
+        .bind(customer_uuid.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    customer_uuid
+}
+
+async fn want(
+    db: &vaultsync::database::Database,
+    customer_uuid: Uuid,
+    product_uuid: Uuid,
+    min_condition: Condition,
+    variant_type: Option<VariantType>,
+    max_price: Option<f64>,
+    hold_hours: Option<i64>,
+) -> Uuid {
+    let item = WantsItem {
+        item_uuid: Uuid::new_v4(),
+        product_uuid,
+        min_condition,
+        max_price,
+        variant_type,
+        quantity: 1,
+        quantity_fulfilled: 0,
+        hold_hours,
+        created_at: chrono::Utc::now(),
+    };
+    let item_uuid = item.item_uuid;
+    db.customers
+        .save_wants_list(&WantsList {
+            wants_list_uuid: Uuid::new_v4(),
+            customer_uuid,
+            items: vec![item],
+            created_at: chrono::Utc::now(),
+        })
+        .await
+        .unwrap();
+    item_uuid
+}
+
+async fn fulfilled(db: &vaultsync::database::Database, item_uuid: Uuid) -> i64 {
+    sqlx::query_scalar("SELECT quantity_fulfilled FROM Wants_Items WHERE item_uuid = ?")
+        .bind(item_uuid.to_string())
+        .fetch_one(&db.pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_match_hold_notify_and_fulfil() {
+    let db = common::setup_test_db().await;
+    let matcher = WantsMatchingService::new(db.clone());
+    let scheduler = NotificationScheduler::new(
+        db.clone(),
+        Arc::new(Box::new(MockEmailProvider)),
+        Arc::new(Box::new(MockSmsProvider)),
+    );
+
+    let product_uuid = common::seed_product(&db, "Ragavan", "TCG").await;
+    let nm = common::TestPile {
+        variant_type: Some("Normal"),
+        specific_price: Some(15.0),
+        ..common::TestPile::new(product_uuid, 2)
+    }
+    .insert(&db)
+    .await;
+    common::TestPile {
+        condition: "LP",
+        variant_type: Some("Foil"),
+        specific_price: Some(12.0),
+        ..common::TestPile::new(product_uuid, 1)
+    }
+    .insert(&db)
+    .await;
+
+    let holder = seed_contact(&db, Some("holder@example.com"), None, None).await;
+    let texter = seed_contact(&db, Some("t@example.com"), Some("555-0100"), Some("sms")).await;
+    let cheap = seed_contact(&db, Some("c@example.com"), None, None).await;
+    let held_want = want(
+        &db,
+        holder,
+        product_uuid,
+        Condition::NM,
+        None,
+        Some(20.0),
+        Some(24),
+    )
+    .await;
+    let foil_want = want(
+        &db,
+        texter,
+        product_uuid,
+        Condition::LP,
+        Some(VariantType::Foil),
+        None,
+        None,
+    )
+    .await;
+    want(
+        &db,
+        cheap,
+        product_uuid,
+        Condition::NM,
+        None,
+        Some(5.0),
+        None,
+    )
+    .await;
+
+    let notified = scheduler
+        .check_wants_list_matches(product_uuid)
+        .await
+        .unwrap();
+    assert_eq!(notified.len(), 2);
+    assert!(!notified.contains(&cheap));
+
+    let held = matcher.list_matches(Some(holder), None).await.unwrap();
+    assert_eq!(held.len(), 1);
+    assert_eq!(held[0].status, WantsMatchStatus::Held);
+    assert_eq!(held[0].inventory_uuid, nm);
+    assert_eq!(held[0].notified_via.as_deref(), Some("email"));
+    assert_eq!(common::on_hand(&db, nm).await, 1);
+
+    let foil = matcher.list_matches(Some(texter), None).await.unwrap();
+    assert_eq!(foil[0].status, WantsMatchStatus::Open);
+    assert_eq!(foil[0].notified_via.as_deref(), Some("sms"));
+
+    // Already-matched wants aren't offered the same stock again
+    assert!(scheduler
+        .check_wants_list_matches(product_uuid)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // A held match can only be fulfilled through its hold
+    assert!(matcher
+        .fulfill_match(held[0].match_uuid, None)
+        .await
+        .is_err());
+    let declined = matcher.decline_match(held[0].match_uuid).await.unwrap();
+    assert_eq!(declined.status, WantsMatchStatus::Declined);
+    assert_eq!(common::on_hand(&db, nm).await, 2);
+
+    matcher
+        .fulfill_match(foil[0].match_uuid, None)
+        .await
+        .unwrap();
+    assert_eq!(fulfilled(&db, foil_want).await, 1);
+    assert!(matcher
+        .find_matches(
+            product_uuid,
+            &Condition::NM,
+            Some(&VariantType::Foil),
+            Some(1.0)
+        )
+        .await
+        .unwrap()
+        .iter()
+        .all(|(c, _)| c.customer_uuid != texter));
+
+    // The declined want is matched again and reserved; an overdue
+    // reservation lapses and returns the stock
+    let rematch = matcher.match_inventory(nm).await.unwrap();
+    assert_eq!(rematch.len(), 1);
+    let hold_uuid = rematch[0].1.hold_uuid.unwrap();
+    sqlx::query("UPDATE Holds SET expiration_date = ? WHERE hold_uuid = ?")
+        .bind((chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339())
+        .bind(hold_uuid.to_string())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let refresh = matcher.refresh_matches().await.unwrap();
+    assert_eq!(refresh.expired, vec![rematch[0].1.match_uuid]);
+    assert_eq!(common::on_hand(&db, nm).await, 2);
+
+    // Buying a copy at the register counts toward the want
+    let transaction_uuid = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO Transactions (transaction_uuid, customer_uuid, timestamp, transaction_type) VALUES (?, ?, ?, 'Sale')",
+    )
+    .bind(transaction_uuid.to_string())
+    .bind(holder.to_string())
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO Transaction_Items (item_uuid, transaction_uuid, product_uuid, quantity, unit_price, condition)
+         VALUES (?, ?, ?, 1, 15.0, 'NM')",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(transaction_uuid.to_string())
+    .bind(product_uuid.to_string())
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        record_purchase(&db, holder, transaction_uuid)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(fulfilled(&db, held_want).await, 1);
+    assert!(matcher.match_inventory(nm).await.unwrap().is_empty());
+}